@@ -96,7 +96,10 @@ impl Backoff {
     }
 
     /// Returns the next backoff duration to wait for
-    fn next(&mut self) -> Duration {
+    ///
+    /// The durations grow exponentially (with jitter) from the initial up to the maximum backoff of the
+    /// [`BackoffConfig`].
+    pub fn next_backoff(&mut self) -> Duration {
         let range = self.init_backoff..(self.next_backoff_secs * self.base);
 
         let rand_backoff = match self.rng.as_mut() {
@@ -130,7 +133,7 @@ impl Backoff {
                 ControlFlow::Continue(e) => e,
             };
 
-            let backoff = self.next();
+            let backoff = self.next_backoff();
             info!(
                 e=%e,
                 task_name,
@@ -191,7 +194,7 @@ mod tests {
         let mut backoff = Backoff::new_with_rng(&config, Some(rng));
 
         for _ in 0..20 {
-            assert_eq!(backoff.next().as_secs_f64(), init_backoff_secs);
+            assert_eq!(backoff.next_backoff().as_secs_f64(), init_backoff_secs);
         }
 
         // Create a static rng that takes the maximum of the range
@@ -200,7 +203,7 @@ mod tests {
 
         for i in 0..20 {
             let value = (base.powi(i) * init_backoff_secs).min(max_backoff_secs);
-            assert_fuzzy_eq(backoff.next().as_secs_f64(), value);
+            assert_fuzzy_eq(backoff.next_backoff().as_secs_f64(), value);
         }
 
         // Create a static rng that takes the mid point of the range
@@ -209,7 +212,7 @@ mod tests {
 
         let mut value = init_backoff_secs;
         for _ in 0..20 {
-            assert_fuzzy_eq(backoff.next().as_secs_f64(), value);
+            assert_fuzzy_eq(backoff.next_backoff().as_secs_f64(), value);
             value =
                 (init_backoff_secs + (value * base - init_backoff_secs) / 2.).min(max_backoff_secs);
         }
@@ -1,5 +1,6 @@
 use iox_catalog::{
     create_or_get_default_records, interface::Catalog, mem::MemCatalog, postgres::PostgresCatalog,
+    sqlite::SqliteCatalog,
 };
 use snafu::{OptionExt, ResultExt, Snafu};
 use std::{ops::DerefMut, sync::Arc};
@@ -9,6 +10,11 @@ pub enum Error {
     #[snafu(display("A Postgres connection string in --catalog-dsn is required."))]
     ConnectionStringRequired,
 
+    #[snafu(display(
+        "A SQLite DSN in --catalog-dsn is required (e.g. sqlite:///path/to/catalog.sqlite)."
+    ))]
+    SqliteDsnRequired,
+
     #[snafu(display("A catalog error occurred: {}", source))]
     Catalog {
         source: iox_catalog::interface::Error,
@@ -18,7 +24,8 @@ pub enum Error {
 /// CLI config for catalog DSN.
 #[derive(Debug, Clone, clap::Parser)]
 pub struct CatalogDsnConfig {
-    /// The type of catalog to use. "memory" is only useful for testing purposes.
+    /// The type of catalog to use. "memory" is only useful for testing purposes, "sqlite" stores
+    /// the catalog in a single local file and is meant for single-node deployments.
     #[clap(
         arg_enum,
         long = "--catalog",
@@ -27,7 +34,8 @@ pub struct CatalogDsnConfig {
     )]
     pub(crate) catalog_type_: CatalogType,
 
-    /// Postgres connection string. Required if catalog is set to postgres.
+    /// Catalog connection string. Required if catalog is set to postgres, or sqlite (e.g.
+    /// `sqlite:///path/to/catalog.sqlite`).
     #[clap(long = "--catalog-dsn", env = "INFLUXDB_IOX_CATALOG_DSN")]
     pub dsn: Option<String>,
 
@@ -44,6 +52,7 @@ pub struct CatalogDsnConfig {
 pub enum CatalogType {
     Postgres,
     Memory,
+    Sqlite,
 }
 
 impl CatalogDsnConfig {
@@ -75,6 +84,15 @@ impl CatalogDsnConfig {
 
                 Arc::new(mem) as Arc<dyn Catalog>
             }
+            CatalogType::Sqlite => Arc::new(
+                SqliteCatalog::connect(
+                    self.dsn.as_ref().context(SqliteDsnRequiredSnafu)?,
+                    self.max_catalog_connections,
+                    metrics,
+                )
+                .await
+                .context(CatalogSnafu)?,
+            ) as Arc<dyn Catalog>,
         };
 
         Ok(catalog)
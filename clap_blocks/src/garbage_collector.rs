@@ -0,0 +1,25 @@
+/// CLI config for the parquet file garbage collector
+#[derive(Debug, Clone, Copy, clap::Parser)]
+pub struct GarbageCollectorConfig {
+    /// Number of seconds a parquet file must have been flagged for deletion before the garbage
+    /// collector removes it from object storage and the catalog. Unreferenced objects must be at
+    /// least this old to be removed as well.
+    #[clap(
+        long = "--gc-grace-period-seconds",
+        env = "INFLUXDB_IOX_GC_GRACE_PERIOD_SECONDS",
+        default_value = "3600"
+    )]
+    pub grace_period_seconds: u64,
+
+    /// Number of seconds between two garbage collection rounds.
+    #[clap(
+        long = "--gc-interval-seconds",
+        env = "INFLUXDB_IOX_GC_INTERVAL_SECONDS",
+        default_value = "600"
+    )]
+    pub interval_seconds: u64,
+
+    /// Only log the files the garbage collector would remove, without removing anything.
+    #[clap(long = "--gc-dry-run", env = "INFLUXDB_IOX_GC_DRY_RUN")]
+    pub dry_run: bool,
+}
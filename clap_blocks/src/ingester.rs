@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 /// CLI config for catalog ingest lifecycle
 #[derive(Debug, Clone, clap::Parser)]
 pub struct IngesterConfig {
@@ -62,4 +64,30 @@ pub struct IngesterConfig {
         default_value = "300"
     )]
     pub persist_partition_cold_threshold_seconds: u64,
+
+    /// Directory of the local write-ahead log. If set, all buffered operations
+    /// are logged to this directory and replayed on startup, before reading
+    /// from the write buffer resumes. This protects unpersisted data against
+    /// the write buffer retention being exceeded while the ingester is down.
+    #[clap(long = "--wal-dir", env = "INFLUXDB_IOX_WAL_DIR")]
+    pub wal_dir: Option<PathBuf>,
+
+    /// Once a write-ahead log segment grows beyond this size, a new segment is
+    /// started. Segments are deleted once all their data is persisted.
+    /// The default value is 64MB (in bytes).
+    #[clap(
+        long = "--wal-segment-size-bytes",
+        env = "INFLUXDB_IOX_WAL_SEGMENT_SIZE_BYTES",
+        default_value = "67108864"
+    )]
+    pub wal_segment_size_bytes: u64,
+
+    /// Object store prefix under which write buffer operations are recorded
+    /// if the ingester has to skip them because they cannot be decoded or
+    /// buffered. Each record contains the raw message, its sequence number
+    /// and the error, and can be re-injected with `influxdb_iox dead-letter`.
+    ///
+    /// Skipped operations are only logged if not set.
+    #[clap(long = "--dead-letter-prefix", env = "INFLUXDB_IOX_DEAD_LETTER_PREFIX")]
+    pub dead_letter_prefix: Option<String>,
 }
@@ -3,8 +3,10 @@
 //! They can easily be re-used using `#[clap(flatten)]`.
 pub mod catalog_dsn;
 pub mod compactor;
+pub mod garbage_collector;
 pub mod ingester;
 pub mod object_store;
+pub mod rate_limit;
 pub mod run_config;
 pub mod server_id;
 pub mod socket_addr;
@@ -2,7 +2,9 @@
 use std::{convert::TryFrom, fs, num::NonZeroUsize, path::PathBuf, time::Duration};
 
 use futures::TryStreamExt;
-use object_store::{path::ObjectStorePath, DynObjectStore, ObjectStoreImpl, ThrottleConfig};
+use object_store::{
+    cache::LocalFSCache, path::ObjectStorePath, DynObjectStore, ObjectStoreImpl, ThrottleConfig,
+};
 use observability_deps::tracing::{info, warn};
 use snafu::{ResultExt, Snafu};
 use uuid::Uuid;
@@ -36,6 +38,9 @@ pub enum ParseError {
 
     #[snafu(display("Error configuring Microsoft Azure: {}", source))]
     InvalidAzureConfig { source: object_store::Error },
+
+    #[snafu(display("Error configuring the local object store cache: {}", source))]
+    InvalidObjectStoreCache { source: object_store::cache::Error },
 }
 
 /// The AWS region to use for Amazon S3 based object storage if none is
@@ -182,6 +187,27 @@ Possible values (case insensitive):
         default_value = "16"
     )]
     pub object_store_connection_limit: NonZeroUsize,
+
+    /// When using a cloud object store (S3, Google or Azure), cache parquet files read by the
+    /// querier in this local directory. Objects cached by a previous run are reused.
+    ///
+    /// If not set, no local cache is used.
+    #[clap(
+        long = "--object-store-cache-dir",
+        env = "INFLUXDB_IOX_OBJECT_STORE_CACHE_DIR"
+    )]
+    pub object_store_cache_dir: Option<PathBuf>,
+
+    /// Maximum size in bytes of all objects in the local object store cache. The least recently
+    /// used objects are evicted once this limit is exceeded.
+    ///
+    /// Only used if `--object-store-cache-dir` is set.
+    #[clap(
+        long = "--object-store-cache-size-bytes",
+        env = "INFLUXDB_IOX_OBJECT_STORE_CACHE_SIZE_BYTES",
+        default_value = "10737418240"
+    )]
+    pub object_store_cache_size_bytes: u64,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, clap::ArgEnum)]
@@ -343,6 +369,40 @@ impl TryFrom<&ObjectStoreConfig> for ObjectStoreImpl {
     }
 }
 
+/// Create the object store described by `config`, including the local filesystem cache for
+/// cloud object stores if `--object-store-cache-dir` is set.
+pub fn make_object_store(
+    config: &ObjectStoreConfig,
+    metric_registry: &metric::Registry,
+) -> Result<ObjectStoreImpl, ParseError> {
+    let object_store = ObjectStoreImpl::try_from(config)?;
+
+    match (config.object_store, config.object_store_cache_dir.as_ref()) {
+        (
+            Some(ObjectStoreType::S3 | ObjectStoreType::Google | ObjectStoreType::Azure),
+            Some(cache_dir),
+        ) => {
+            info!(
+                ?cache_dir,
+                limit = config.object_store_cache_size_bytes,
+                "using local object store cache"
+            );
+            let cache = LocalFSCache::new(
+                cache_dir,
+                config.object_store_cache_size_bytes,
+                metric_registry,
+            )
+            .context(InvalidObjectStoreCacheSnafu)?;
+            Ok(object_store.with_local_fs_cache(cache))
+        }
+        (_, Some(_)) => {
+            warn!("object store cache is only used for cloud object stores, ignoring");
+            Ok(object_store)
+        }
+        (_, None) => Ok(object_store),
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum CheckError {
     #[snafu(display("Cannot read from object store: {}", source))]
@@ -403,6 +463,42 @@ mod tests {
         assert!(matches!(integration, ObjectStoreIntegration::InMemory(_)));
     }
 
+    #[test]
+    fn object_store_cache_ignored_for_memory() {
+        let cache_dir = TempDir::new().unwrap();
+        let config = ObjectStoreConfig::try_parse_from(&[
+            "server",
+            "--object-store",
+            "memory",
+            "--object-store-cache-dir",
+            cache_dir.path().to_str().unwrap(),
+        ])
+        .unwrap();
+
+        let object_store = make_object_store(&config, &metric::Registry::default()).unwrap();
+        assert!(object_store.cache().is_none());
+    }
+
+    #[test]
+    fn object_store_cache_ignored_for_file() {
+        let root = TempDir::new().unwrap();
+        let cache_dir = TempDir::new().unwrap();
+        let config = ObjectStoreConfig::try_parse_from(&[
+            "server",
+            "--object-store",
+            "file",
+            "--data-dir",
+            root.path().to_str().unwrap(),
+            "--object-store-cache-dir",
+            cache_dir.path().to_str().unwrap(),
+        ])
+        .unwrap();
+
+        // files are read in place, a copy would only waste disk space
+        let object_store = make_object_store(&config, &metric::Registry::default()).unwrap();
+        assert!(object_store.cache().is_none());
+    }
+
     #[test]
     #[cfg(feature = "aws")]
     fn valid_s3_config() {
@@ -0,0 +1,170 @@
+use snafu::{ResultExt, Snafu};
+use std::{
+    num::{NonZeroU64, NonZeroUsize, ParseIntError},
+    str::FromStr,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(
+        "Invalid namespace rate limit {:?}, expected NAMESPACE:BYTES_PER_SECOND:LINES_PER_SECOND",
+        value
+    ))]
+    InvalidFormat { value: String },
+
+    #[snafu(display(
+        "Invalid rate limit {:?} for namespace {}: {}",
+        value,
+        namespace,
+        source
+    ))]
+    InvalidLimit {
+        namespace: String,
+        value: String,
+        source: ParseIntError,
+    },
+}
+
+/// CLI config for request admission control and write rate limiting.
+#[derive(Debug, Clone, Default, clap::Parser)]
+pub struct RateLimitConfig {
+    /// The maximum number of write and delete requests processed
+    /// concurrently. Requests arriving while the limit is reached are
+    /// rejected with HTTP 429 / gRPC RESOURCE_EXHAUSTED.
+    ///
+    /// Unlimited if not set.
+    #[clap(
+        long = "--max-in-flight-requests",
+        env = "INFLUXDB_IOX_MAX_IN_FLIGHT_REQUESTS"
+    )]
+    pub max_in_flight_requests: Option<NonZeroUsize>,
+
+    /// The maximum sustained write rate of a single namespace, in bytes per
+    /// second.
+    ///
+    /// Unlimited if not set.
+    #[clap(
+        long = "--namespace-max-bytes-per-second",
+        env = "INFLUXDB_IOX_NAMESPACE_MAX_BYTES_PER_SECOND"
+    )]
+    pub namespace_max_bytes_per_second: Option<NonZeroU64>,
+
+    /// The maximum sustained write rate of a single namespace, in lines per
+    /// second.
+    ///
+    /// Unlimited if not set.
+    #[clap(
+        long = "--namespace-max-lines-per-second",
+        env = "INFLUXDB_IOX_NAMESPACE_MAX_LINES_PER_SECOND"
+    )]
+    pub namespace_max_lines_per_second: Option<NonZeroU64>,
+
+    /// Per-namespace write rate limits, replacing the default limits for the
+    /// namespace.
+    ///
+    /// Each override is of the form `namespace:bytes_per_second:lines_per_second`,
+    /// where an empty (or zero) limit is unlimited - for example
+    /// `--namespace-rate-limit-override 'backfill:1048576:'`.
+    ///
+    /// Environment variables are passed as `override1,override2,...`.
+    #[clap(
+        long = "--namespace-rate-limit-override",
+        env = "INFLUXDB_IOX_NAMESPACE_RATE_LIMIT_OVERRIDES",
+        multiple_values = true,
+        use_value_delimiter = true
+    )]
+    pub namespace_overrides: Vec<NamespaceRateLimitOverride>,
+}
+
+/// The write rate limits of a single namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceRateLimitOverride {
+    pub namespace: String,
+    pub bytes_per_second: Option<NonZeroU64>,
+    pub lines_per_second: Option<NonZeroU64>,
+}
+
+impl FromStr for NamespaceRateLimitOverride {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (namespace, bytes, lines) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(namespace), Some(bytes), Some(lines), None) if !namespace.is_empty() => {
+                    (namespace, bytes, lines)
+                }
+                _ => return InvalidFormatSnafu { value: s }.fail(),
+            };
+
+        let parse = |value: &str| -> Result<Option<NonZeroU64>, Error> {
+            if value.is_empty() {
+                return Ok(None);
+            }
+            let v = value
+                .parse::<u64>()
+                .context(InvalidLimitSnafu { namespace, value })?;
+            Ok(NonZeroU64::new(v))
+        };
+
+        Ok(Self {
+            namespace: namespace.to_string(),
+            bytes_per_second: parse(bytes)?,
+            lines_per_second: parse(lines)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::StructOpt;
+
+    #[test]
+    fn test_parse_override() {
+        let got: NamespaceRateLimitOverride = "bananas:100:0".parse().unwrap();
+        assert_eq!(
+            got,
+            NamespaceRateLimitOverride {
+                namespace: "bananas".to_string(),
+                bytes_per_second: NonZeroU64::new(100),
+                lines_per_second: None,
+            }
+        );
+
+        let got: NamespaceRateLimitOverride = "bananas::42".parse().unwrap();
+        assert_eq!(got.bytes_per_second, None);
+        assert_eq!(got.lines_per_second, NonZeroU64::new(42));
+
+        assert!(matches!(
+            "bananas:1".parse::<NamespaceRateLimitOverride>(),
+            Err(Error::InvalidFormat { .. })
+        ));
+        assert!(matches!(
+            ":1:1".parse::<NamespaceRateLimitOverride>(),
+            Err(Error::InvalidFormat { .. })
+        ));
+        assert!(matches!(
+            "bananas:lots:1".parse::<NamespaceRateLimitOverride>(),
+            Err(Error::InvalidLimit { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cli() {
+        let config = RateLimitConfig::try_parse_from([
+            "my_binary",
+            "--max-in-flight-requests",
+            "10",
+            "--namespace-rate-limit-override",
+            "a:1:2",
+            "b::3",
+        ])
+        .unwrap();
+
+        assert_eq!(config.max_in_flight_requests, NonZeroUsize::new(10));
+        assert_eq!(config.namespace_max_bytes_per_second, None);
+        assert_eq!(config.namespace_overrides.len(), 2);
+        assert_eq!(config.namespace_overrides[1].namespace, "b");
+    }
+}
@@ -29,7 +29,8 @@ pub struct WriteBufferConfig {
 
     /// Write buffer connection config.
     ///
-    /// The concrete options depend on the write buffer type.
+    /// The concrete options depend on the write buffer type. For the file write buffer, this includes the retention
+    /// options `retention_ms` and `retention_bytes`, which also apply to existing sequencers.
     ///
     /// Command line arguments are passed as `--write-buffer-connection-config key1=value1 key2=value2` or
     /// `--write-buffer-connection-config key1=value1,key2=value2`.
@@ -50,6 +51,23 @@ pub struct WriteBufferConfig {
         env = "INFLUXDB_IOX_WRITE_BUFFER_AUTO_CREATE_TOPICS"
     )]
     pub(crate) auto_create_topics: Option<NonZeroU32>,
+
+    /// Options applied to the automatically created topics, such as their retention.
+    ///
+    /// The concrete options depend on the write buffer type, and require `--write-buffer-auto-create-topics`.
+    ///
+    /// Command line arguments are passed as `--write-buffer-creation-config key1=value1 key2=value2` or
+    /// `--write-buffer-creation-config key1=value1,key2=value2`.
+    ///
+    /// Environment variables are passed as `key1=value1,key2=value2,...`.
+    #[clap(
+        long = "--write-buffer-creation-config",
+        env = "INFLUXDB_IOX_WRITE_BUFFER_CREATION_CONFIG",
+        multiple_values = true,
+        use_value_delimiter = true,
+        requires = "auto-create-topics"
+    )]
+    pub(crate) creation_config: Vec<String>,
 }
 
 impl WriteBufferConfig {
@@ -80,35 +98,23 @@ impl WriteBufferConfig {
     }
 
     fn connection_config(&self) -> BTreeMap<String, String> {
-        let mut cfg = BTreeMap::new();
-
-        for s in &self.connection_config {
-            if s.is_empty() {
-                continue;
-            }
-
-            if let Some((k, v)) = s.split_once('=') {
-                cfg.insert(k.to_owned(), v.to_owned());
-            } else {
-                cfg.insert(s.clone(), String::from(""));
-            }
-        }
+        parse_key_values(&self.connection_config)
+    }
 
-        cfg
+    fn creation_config(&self) -> Option<WriteBufferCreationConfig> {
+        self.auto_create_topics
+            .map(|n_sequencers| WriteBufferCreationConfig {
+                n_sequencers,
+                options: parse_key_values(&self.creation_config),
+            })
     }
 
     fn conn(&self) -> WriteBufferConnection {
-        let creation_config =
-            self.auto_create_topics
-                .map(|n_sequencers| WriteBufferCreationConfig {
-                    n_sequencers,
-                    ..Default::default()
-                });
         WriteBufferConnection {
             type_: self.type_.clone(),
             connection: self.connection_string.clone(),
             connection_config: self.connection_config(),
-            creation_config,
+            creation_config: self.creation_config(),
         }
     }
 
@@ -132,6 +138,25 @@ impl WriteBufferConfig {
     }
 }
 
+/// Parse `key=value` pairs, where a missing `=` results in an empty value and later keys win.
+fn parse_key_values(pairs: &[String]) -> BTreeMap<String, String> {
+    let mut cfg = BTreeMap::new();
+
+    for s in pairs {
+        if s.is_empty() {
+            continue;
+        }
+
+        if let Some((k, v)) = s.split_once('=') {
+            cfg.insert(k.to_owned(), v.to_owned());
+        } else {
+            cfg.insert(s.clone(), String::from(""));
+        }
+    }
+
+    cfg
+}
+
 #[cfg(test)]
 mod tests {
     use clap::StructOpt;
@@ -164,4 +189,42 @@ mod tests {
         ]);
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_creation_config() {
+        let cfg = WriteBufferConfig::try_parse_from([
+            "my_binary",
+            "--write-buffer",
+            "file",
+            "--write-buffer-addr",
+            "/tmp/wb",
+            "--write-buffer-auto-create-topics",
+            "2",
+            "--write-buffer-creation-config",
+            "retention_ms=1000,retention_bytes=42",
+        ])
+        .unwrap();
+        let actual = cfg.conn().creation_config.unwrap();
+        assert_eq!(actual.n_sequencers.get(), 2);
+        assert_eq!(
+            actual.options,
+            BTreeMap::from([
+                (String::from("retention_bytes"), String::from("42")),
+                (String::from("retention_ms"), String::from("1000")),
+            ])
+        );
+
+        // creation options without auto-creation are rejected rather than silently ignored
+        let err = WriteBufferConfig::try_parse_from([
+            "my_binary",
+            "--write-buffer",
+            "file",
+            "--write-buffer-addr",
+            "/tmp/wb",
+            "--write-buffer-creation-config",
+            "retention_ms=1000",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::ErrorKind::MissingRequiredArgument);
+    }
 }
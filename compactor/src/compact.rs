@@ -2,27 +2,29 @@
 
 use crate::{
     query::QueryableParquetChunk,
+    retention,
     utils::{CatalogUpdate, CompactedData, GroupWithTombstones, ParquetFileWithTombstone},
 };
 use arrow::record_batch::RecordBatch;
 use backoff::{Backoff, BackoffConfig};
 use bytes::Bytes;
 use data_types2::{
-    ParquetFile, ParquetFileId, PartitionId, SequencerId, TableId, TablePartition, Timestamp,
-    TombstoneId,
+    NamespaceId, ParquetFile, ParquetFileId, PartitionId, SequencerId, TableId, TablePartition,
+    Timestamp, TombstoneId,
 };
 use datafusion::error::DataFusionError;
 use iox_catalog::interface::{Catalog, Transaction};
 use iox_object_store::ParquetFilePath;
+use metric::{Attributes, DurationHistogram, Metric, U64Counter};
 use object_store::DynObjectStore;
-use observability_deps::tracing::warn;
+use observability_deps::tracing::{debug, warn};
 use parquet_file::metadata::{IoxMetadata, IoxParquetMetaData};
 use query::{
     compute_sort_key_for_chunks, exec::ExecutorType, frontend::reorg::ReorgPlanner,
     util::compute_timenanosecond_min_max,
 };
 use query::{exec::Executor, QueryChunk};
-use snafu::{ensure, ResultExt, Snafu};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
 use std::{
     cmp::{max, min},
     collections::{BTreeMap, HashSet},
@@ -84,6 +86,16 @@ pub enum Error {
     ))]
     TableNotExist { table_id: TableId },
 
+    #[snafu(display(
+        "Error while looking up the retention period of namespace ID {}: {}",
+        namespace_id,
+        source
+    ))]
+    NamespaceLookup {
+        source: iox_catalog::interface::Error,
+        namespace_id: NamespaceId,
+    },
+
     #[snafu(display("Error building compact logical plan  {}", source))]
     CompactLogicalPlan {
         source: query::frontend::reorg::Error,
@@ -151,6 +163,135 @@ pub enum Error {
 /// A specialized `Error` for Compactor Data errors
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// Summary of a single [`Compactor::find_and_compact`] round.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionSummary {
+    /// Number of input files that were compacted into new files.
+    pub compacted_files: u64,
+
+    /// Number of level-0 files that were upgraded to level 1 without compaction.
+    pub upgraded_files: u64,
+
+    /// Total size of the compacted input files in bytes.
+    pub bytes_read: u64,
+
+    /// Total size of the newly created files in bytes.
+    pub bytes_written: u64,
+
+    /// Number of level-0 files that were flagged for deletion because all of their data is older than the retention
+    /// period of their namespace.
+    pub expired_files: u64,
+}
+
+impl CompactionSummary {
+    /// Returns `true` if the round compacted, upgraded or expired at least one file.
+    pub fn did_work(&self) -> bool {
+        self.compacted_files > 0 || self.upgraded_files > 0 || self.expired_files > 0
+    }
+}
+
+/// Per-sequencer metrics of the compactor.
+#[derive(Debug)]
+struct CompactorMetrics {
+    /// Number of files compacted.
+    compacted_files: Metric<U64Counter>,
+
+    /// Number of files upgraded to level 1 without compaction.
+    upgraded_files: Metric<U64Counter>,
+
+    /// Bytes read from compacted files.
+    bytes_read: Metric<U64Counter>,
+
+    /// Bytes written to new files.
+    bytes_written: Metric<U64Counter>,
+
+    /// Number of files flagged for deletion because they are past the retention period.
+    expired_files: Metric<U64Counter>,
+
+    /// Duration of compaction rounds that did any work.
+    duration: Metric<DurationHistogram>,
+
+    /// Number of failed compaction rounds.
+    failures: Metric<U64Counter>,
+}
+
+impl CompactorMetrics {
+    fn new(registry: &metric::Registry) -> Self {
+        Self {
+            compacted_files: registry.register_metric(
+                "compactor_compacted_files_total",
+                "Number of parquet files compacted into new files",
+            ),
+            upgraded_files: registry.register_metric(
+                "compactor_upgraded_files_total",
+                "Number of level-0 parquet files upgraded to level 1 without compaction",
+            ),
+            bytes_read: registry.register_metric(
+                "compactor_read_bytes_total",
+                "Total size of the parquet files read for compaction",
+            ),
+            bytes_written: registry.register_metric(
+                "compactor_written_bytes_total",
+                "Total size of the parquet files written by compaction",
+            ),
+            expired_files: registry.register_metric(
+                "compactor_expired_files_total",
+                "Number of level-0 parquet files flagged for deletion because they are past retention",
+            ),
+            duration: registry.register_metric(
+                "compactor_compaction_duration",
+                "Duration of compaction rounds that compacted or upgraded at least one file",
+            ),
+            failures: registry.register_metric(
+                "compactor_failures_total",
+                "Number of failed compaction rounds",
+            ),
+        }
+    }
+
+    fn record(
+        &self,
+        sequencer_id: SequencerId,
+        summary: &CompactionSummary,
+        duration: Option<std::time::Duration>,
+    ) {
+        let attributes = Self::attributes(sequencer_id);
+
+        self.compacted_files
+            .recorder(attributes.clone())
+            .inc(summary.compacted_files);
+        self.upgraded_files
+            .recorder(attributes.clone())
+            .inc(summary.upgraded_files);
+        self.bytes_read
+            .recorder(attributes.clone())
+            .inc(summary.bytes_read);
+        self.bytes_written
+            .recorder(attributes.clone())
+            .inc(summary.bytes_written);
+        self.expired_files
+            .recorder(attributes.clone())
+            .inc(summary.expired_files);
+
+        if let Some(duration) = duration {
+            self.duration.recorder(attributes).record(duration);
+        }
+    }
+
+    fn record_failure(&self, sequencer_id: SequencerId) {
+        self.failures
+            .recorder(Self::attributes(sequencer_id))
+            .inc(1);
+    }
+
+    fn attributes(sequencer_id: SequencerId) -> Attributes {
+        Attributes::from([("sequencer_id", format!("{}", sequencer_id.get()).into())])
+    }
+}
+
+/// Default for the maximum number of partitions compacted by a single [`Compactor::find_and_compact`] round.
+pub const DEFAULT_MAX_PARTITIONS_PER_ROUND: usize = 10;
+
 /// Data points need to run a compactor
 #[derive(Debug)]
 pub struct Compactor {
@@ -168,6 +309,12 @@ pub struct Compactor {
 
     /// Backoff config
     backoff_config: BackoffConfig,
+
+    /// Maximum number of partitions compacted by a single [`find_and_compact`](Self::find_and_compact) round
+    max_partitions_per_round: usize,
+
+    /// Metrics
+    metrics: CompactorMetrics,
 }
 
 impl Compactor {
@@ -179,7 +326,10 @@ impl Compactor {
         exec: Arc<Executor>,
         time_provider: Arc<dyn TimeProvider>,
         backoff_config: BackoffConfig,
+        registry: Arc<metric::Registry>,
     ) -> Self {
+        let metrics = CompactorMetrics::new(&registry);
+
         Self {
             sequencers,
             catalog,
@@ -187,9 +337,26 @@ impl Compactor {
             exec,
             time_provider,
             backoff_config,
+            max_partitions_per_round: DEFAULT_MAX_PARTITIONS_PER_ROUND,
+            metrics,
+        }
+    }
+
+    /// Limit the number of partitions that a single [`find_and_compact`](Self::find_and_compact) round compacts.
+    ///
+    /// Defaults to [`DEFAULT_MAX_PARTITIONS_PER_ROUND`].
+    pub fn with_max_partitions_per_round(self, max_partitions_per_round: usize) -> Self {
+        Self {
+            max_partitions_per_round,
+            ..self
         }
     }
 
+    /// Sequencers assigned to this compactor
+    pub fn sequencers(&self) -> &[SequencerId] {
+        &self.sequencers
+    }
+
     async fn level_0_parquet_files(&self, sequencer_id: SequencerId) -> Result<Vec<ParquetFile>> {
         let mut repos = self.catalog.repositories().await;
 
@@ -238,23 +405,67 @@ impl Compactor {
         Ok(())
     }
 
-    // TODO: this function should be invoked in a backround loop
-    /// Find and compact parquet files for a given sequencer
-    pub async fn find_and_compact(&self, sequencer_id: SequencerId) -> Result<()> {
+    /// Find and compact parquet files for a given sequencer.
+    ///
+    /// At most `max_partitions_per_round` partitions are compacted, the ones with the most level-0 files first. The
+    /// remaining partitions are left for later rounds, which bounds the duration of a round. Metrics of this round are
+    /// recorded for the given sequencer.
+    pub async fn find_and_compact(&self, sequencer_id: SequencerId) -> Result<CompactionSummary> {
         if !self.sequencers.contains(&sequencer_id) {
             return Err(Error::SequencerNotFound { sequencer_id });
         }
 
+        let start = self.time_provider.now();
+        let mut summary = CompactionSummary::default();
+
+        let res = self
+            .find_and_compact_inner(sequencer_id, &mut summary)
+            .await;
+
+        // record partial progress even if the round failed
+        let duration = summary
+            .did_work()
+            .then(|| self.time_provider.now().checked_duration_since(start))
+            .flatten();
+        self.metrics.record(sequencer_id, &summary, duration);
+
+        match res {
+            Ok(()) => {
+                debug!(
+                    sequencer_id = sequencer_id.get(),
+                    ?summary,
+                    "compaction round done"
+                );
+                Ok(summary)
+            }
+            Err(e) => {
+                self.metrics.record_failure(sequencer_id);
+                Err(e)
+            }
+        }
+    }
+
+    async fn find_and_compact_inner(
+        &self,
+        sequencer_id: SequencerId,
+        summary: &mut CompactionSummary,
+    ) -> Result<()> {
         // Read level-0 parquet files
         let level_0_files = self.level_0_parquet_files(sequencer_id).await?;
 
+        // Files past the retention period are not worth compacting
+        let level_0_files = self.flag_expired_files(level_0_files, summary).await?;
+
         // If there are no level-0 parquet files, return because there's nothing to do
         if level_0_files.is_empty() {
             return Ok(());
         }
 
-        // Group files into table partition
-        let mut partitions = Self::group_parquet_files_into_partition(level_0_files);
+        // Group files into table partition and only compact the partitions with the most level-0 files this round
+        let mut partitions = Self::partitions_by_level_0_count(
+            Self::group_parquet_files_into_partition(level_0_files),
+        );
+        partitions.truncate(self.max_partitions_per_round);
 
         // Get level-1 files overlapped in time with level-0
         for (key, val) in &mut partitions.iter_mut() {
@@ -306,10 +517,17 @@ impl Compactor {
             // deleted. These should already be unique, no need to dedupe.
             let original_parquet_file_ids: Vec<_> =
                 group.parquet_files.iter().map(|f| f.data.id).collect();
+            let original_parquet_file_ids_len = original_parquet_file_ids.len() as u64;
+            let bytes_read: u64 = group
+                .parquet_files
+                .iter()
+                .map(|f| f.data.file_size_bytes as u64)
+                .sum();
 
             // compact
             let split_compacted_files = self.compact(group.parquet_files).await?;
             let mut catalog_update_info = Vec::with_capacity(split_compacted_files.len());
+            let mut bytes_written = 0;
 
             for split_file in split_compacted_files {
                 let CompactedData {
@@ -326,6 +544,7 @@ impl Compactor {
                     .expect("retry forever");
 
                 if let Some((file_size, md)) = file_size_and_md {
+                    bytes_written += file_size as u64;
                     catalog_update_info.push(CatalogUpdate::new(
                         meta,
                         file_size,
@@ -348,6 +567,10 @@ impl Compactor {
             .await?;
 
             txn.commit().await.context(TransactionCommitSnafu)?;
+
+            summary.compacted_files += original_parquet_file_ids_len;
+            summary.bytes_read += bytes_read;
+            summary.bytes_written += bytes_written;
         }
 
         // Remove fully processed tombstones
@@ -355,10 +578,28 @@ impl Compactor {
 
         // Upgrade old level-0 to level 1
         self.update_to_level_1(&upgrade_level_list).await?;
+        summary.upgraded_files += upgrade_level_list.len() as u64;
 
         Ok(())
     }
 
+    /// Orders partitions so that the ones with the most level-0 files come first.
+    ///
+    /// This is called before level-1 files are added, so the length of each file list is the level-0 count. Ties are
+    /// broken by the partition key to keep the order deterministic.
+    fn partitions_by_level_0_count(
+        partitions: BTreeMap<TablePartition, Vec<ParquetFile>>,
+    ) -> Vec<(TablePartition, Vec<ParquetFile>)> {
+        let mut partitions: Vec<_> = partitions.into_iter().collect();
+        partitions.sort_by(|(key_a, files_a), (key_b, files_b)| {
+            files_b
+                .len()
+                .cmp(&files_a.len())
+                .then_with(|| key_a.cmp(key_b))
+        });
+        partitions
+    }
+
     // Group given parquet files into partition of the same (sequencer_id, table_id, partition_id)
     fn group_parquet_files_into_partition(
         parquet_files: Vec<ParquetFile>,
@@ -645,6 +886,72 @@ impl Compactor {
         Ok(())
     }
 
+    /// Flag the given files for deletion if all of their data is older than the retention period of their namespace.
+    ///
+    /// Returns the files that were not flagged.
+    async fn flag_expired_files(
+        &self,
+        parquet_files: Vec<ParquetFile>,
+        summary: &mut CompactionSummary,
+    ) -> Result<Vec<ParquetFile>> {
+        let now = self.time_provider.now();
+        let mut repos = self.catalog.repositories().await;
+
+        // Retention cutoff in nanoseconds per table, `None` if the namespace retains data forever
+        let mut cutoffs: BTreeMap<TableId, Option<i64>> = BTreeMap::new();
+        let mut remaining = Vec::with_capacity(parquet_files.len());
+
+        for file in parquet_files {
+            let cutoff = match cutoffs.get(&file.table_id) {
+                Some(cutoff) => *cutoff,
+                None => {
+                    let table = repos
+                        .tables()
+                        .get_by_id(file.table_id)
+                        .await
+                        .context(TableNotFoundSnafu {
+                            table_id: file.table_id,
+                        })?
+                        .context(TableNotExistSnafu {
+                            table_id: file.table_id,
+                        })?;
+                    let namespace = repos
+                        .namespaces()
+                        .get_by_id(table.namespace_id)
+                        .await
+                        .context(NamespaceLookupSnafu {
+                            namespace_id: table.namespace_id,
+                        })?;
+                    let cutoff = namespace
+                        .and_then(|namespace| retention::retention_cutoff(&namespace, now));
+
+                    cutoffs.insert(file.table_id, cutoff);
+                    cutoff
+                }
+            };
+
+            match cutoff {
+                Some(cutoff) if retention::is_expired(&file, cutoff) => {
+                    debug!(
+                        parquet_file_id = file.id.get(),
+                        max_time = file.max_time.get(),
+                        cutoff,
+                        "flagging parquet file past retention period for deletion"
+                    );
+                    repos
+                        .parquet_files()
+                        .flag_for_delete(file.id)
+                        .await
+                        .context(FlagForDeleteSnafu)?;
+                    summary.expired_files += 1;
+                }
+                _ => remaining.push(file),
+            }
+        }
+
+        Ok(remaining)
+    }
+
     /// Given a list of parquet files that come from the same Table Partition, group files together
     /// if their (min_time, max_time) ranges overlap. Does not preserve or guarantee any ordering.
     fn overlapped_groups(mut parquet_files: Vec<ParquetFile>) -> Vec<Vec<ParquetFile>> {
@@ -803,14 +1110,15 @@ mod tests {
             .parquet_file
             .clone();
 
-        let compactor = Compactor {
-            sequencers: vec![sequencer.sequencer.id],
-            object_store: Arc::clone(&catalog.object_store),
-            catalog: Arc::clone(&catalog.catalog),
-            exec: Arc::new(Executor::new(1)),
-            time_provider: Arc::new(SystemProvider::new()),
-            backoff_config: BackoffConfig::default(),
-        };
+        let compactor = Compactor::new(
+            vec![sequencer.sequencer.id],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
 
         // ------------------------------------------------
         // no files provided
@@ -863,6 +1171,148 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_find_and_compact_flags_expired_files() {
+        let catalog = TestCatalog::new();
+        let hour = 60 * 60 * 1_000_000_000;
+        catalog
+            .mock_time_provider()
+            .set(Time::from_timestamp_nanos(10 * hour));
+
+        let ns = catalog.create_namespace_with_retention("ns", "1h").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let table = ns.create_table("table").await;
+        let partition = table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await;
+        partition
+            .create_parquet_file_with_min_max(
+                "table field_int=1 3600000000000",
+                1,
+                1,
+                hour,
+                2 * hour,
+            )
+            .await;
+        let fresh = partition
+            .create_parquet_file_with_min_max(
+                "table field_int=2 36000000000000",
+                2,
+                2,
+                10 * hour,
+                10 * hour,
+            )
+            .await;
+
+        let compactor = Compactor::new(
+            vec![sequencer.sequencer.id],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            catalog.time_provider(),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
+
+        let summary = compactor
+            .find_and_compact(sequencer.sequencer.id)
+            .await
+            .unwrap();
+        assert_eq!(summary.expired_files, 1);
+        assert_eq!(summary.compacted_files, 0);
+        assert!(summary.did_work());
+
+        let remaining: Vec<_> = catalog
+            .catalog
+            .repositories()
+            .await
+            .parquet_files()
+            .list_by_table_not_to_delete(table.table.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+        assert_eq!(remaining, vec![fresh.parquet_file.id]);
+
+        let expired_total = catalog
+            .metric_registry
+            .get_instrument::<Metric<U64Counter>>("compactor_expired_files_total")
+            .unwrap()
+            .get_observer(&CompactorMetrics::attributes(sequencer.sequencer.id))
+            .unwrap()
+            .fetch();
+        assert_eq!(expired_total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_and_compact_limits_partitions_per_round() {
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace("ns").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let table = ns.create_table("table").await;
+
+        // partition "a" has more level-0 files, so it is compacted first
+        let partition_a = table.with_sequencer(&sequencer).create_partition("a").await;
+        for (i, lp) in [
+            "table,tag1=WA field_int=1 8000",
+            "table,tag1=WA field_int=2 9000",
+            "table,tag1=WA field_int=3 10000",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let seq = i as i64 + 1;
+            partition_a
+                .create_parquet_file_with_min_max(lp, seq, seq, 8000, 10000)
+                .await;
+        }
+        let partition_b = table.with_sequencer(&sequencer).create_partition("b").await;
+        for (i, lp) in [
+            "table,tag1=VT field_int=1 8000",
+            "table,tag1=VT field_int=2 9000",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let seq = i as i64 + 10;
+            partition_b
+                .create_parquet_file_with_min_max(lp, seq, seq, 8000, 9000)
+                .await;
+        }
+
+        let compactor = Compactor::new(
+            vec![sequencer.sequencer.id],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        )
+        .with_max_partitions_per_round(1);
+
+        let summary = compactor
+            .find_and_compact(sequencer.sequencer.id)
+            .await
+            .unwrap();
+        assert_eq!(summary.compacted_files, 3);
+
+        let summary = compactor
+            .find_and_compact(sequencer.sequencer.id)
+            .await
+            .unwrap();
+        assert_eq!(summary.compacted_files, 2);
+
+        let summary = compactor
+            .find_and_compact(sequencer.sequencer.id)
+            .await
+            .unwrap();
+        assert!(!summary.did_work());
+    }
+
     #[tokio::test]
     async fn test_compact_two_files() {
         let catalog = TestCatalog::new();
@@ -899,14 +1349,15 @@ mod tests {
             .parquet_file
             .clone();
 
-        let compactor = Compactor {
-            sequencers: vec![sequencer.sequencer.id],
-            object_store: Arc::clone(&catalog.object_store),
-            catalog: Arc::clone(&catalog.catalog),
-            exec: Arc::new(Executor::new(1)),
-            time_provider: Arc::new(SystemProvider::new()),
-            backoff_config: BackoffConfig::default(),
-        };
+        let compactor = Compactor::new(
+            vec![sequencer.sequencer.id],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
 
         // File 1 with tombstone
         let tombstone = table
@@ -1004,14 +1455,15 @@ mod tests {
             .parquet_file
             .clone();
 
-        let compactor = Compactor {
-            sequencers: vec![sequencer.sequencer.id],
-            object_store: Arc::clone(&catalog.object_store),
-            catalog: Arc::clone(&catalog.catalog),
-            exec: Arc::new(Executor::new(1)),
-            time_provider: Arc::new(SystemProvider::new()),
-            backoff_config: BackoffConfig::default(),
-        };
+        let compactor = Compactor::new(
+            vec![sequencer.sequencer.id],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
 
         // File 1 with tombstone
         let tombstone = table
@@ -1236,14 +1688,15 @@ mod tests {
     async fn add_tombstones_to_parquet_files_in_groups() {
         let catalog = TestCatalog::new();
 
-        let compactor = Compactor {
-            sequencers: vec![],
-            object_store: Arc::clone(&catalog.object_store),
-            catalog: Arc::clone(&catalog.catalog),
-            exec: Arc::new(Executor::new(1)),
-            time_provider: Arc::new(SystemProvider::new()),
-            backoff_config: BackoffConfig::default(),
-        };
+        let compactor = Compactor::new(
+            vec![],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
 
         let mut txn = catalog.catalog.start_transaction().await.unwrap();
 
@@ -1413,14 +1866,15 @@ mod tests {
     async fn persist_adds_to_object_store() {
         let catalog = TestCatalog::new();
 
-        let compactor = Compactor {
-            sequencers: vec![],
-            object_store: Arc::clone(&catalog.object_store),
-            catalog: Arc::clone(&catalog.catalog),
-            exec: Arc::new(Executor::new(1)),
-            time_provider: Arc::new(SystemProvider::new()),
-            backoff_config: BackoffConfig::default(),
-        };
+        let compactor = Compactor::new(
+            vec![],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
 
         let table_id = TableId::new(3);
         let sequencer_id = SequencerId::new(2);
@@ -1487,14 +1941,15 @@ mod tests {
     async fn test_add_parquet_file_with_tombstones() {
         let catalog = TestCatalog::new();
 
-        let compactor = Compactor {
-            sequencers: vec![],
-            object_store: Arc::clone(&catalog.object_store),
-            catalog: Arc::clone(&catalog.catalog),
-            exec: Arc::new(Executor::new(1)),
-            time_provider: Arc::new(SystemProvider::new()),
-            backoff_config: BackoffConfig::default(),
-        };
+        let compactor = Compactor::new(
+            vec![],
+            Arc::clone(&catalog.catalog),
+            Arc::clone(&catalog.object_store),
+            Arc::new(Executor::new(1)),
+            Arc::new(SystemProvider::new()),
+            BackoffConfig::default(),
+            Arc::clone(&catalog.metric_registry),
+        );
 
         let mut txn = catalog.catalog.start_transaction().await.unwrap();
         let kafka = txn.kafka_topics().create_or_get("foo").await.unwrap();
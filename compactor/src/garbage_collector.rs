@@ -0,0 +1,1123 @@
+//! Garbage collection of parquet files.
+//!
+//! The compactor only [flags](iox_catalog::interface::ParquetFileRepo::flag_for_delete) the files
+//! it replaces, because queriers may still be reading them. Once a flagged file is older than the
+//! grace period, the [`GarbageCollector`] removes it from object storage and then removes its
+//! catalog record. It also removes objects that look like parquet files but that no catalog
+//! record refers to (e.g. left behind by a crashed ingester or compactor).
+//!
+//! Files whose data is entirely older than the retention period of their namespace are flagged
+//! for deletion by the garbage collector as well, and are then removed like any other flagged
+//! file once the grace period is over.
+//!
+//! Namespaces and tables can be soft-deleted in the catalog, which flags their parquet files at
+//! the time of the deletion. Files that are persisted afterwards (e.g. by an ingester that was
+//! still buffering data for the deleted namespace / table) are flagged by the garbage collector.
+
+use crate::{
+    handler::{shared_handle, CompactorHandler, SharedJoinHandle},
+    retention,
+};
+use async_trait::async_trait;
+use data_types2::{NamespaceId, ParquetFile, TableId, Timestamp};
+use iox_catalog::interface::Catalog;
+use iox_object_store::{IoxObjectStore, ParquetFilePath};
+use metric::U64Counter;
+use object_store::{
+    path::{parsed::DirsAndFileName, Path},
+    DynObjectStore, ObjectMeta,
+};
+use observability_deps::tracing::{debug, error, info, warn};
+use snafu::{ResultExt, Snafu};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use time::{Time, TimeProvider};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Number of directories between the object store root and a NG parquet file, see
+/// [`ParquetFilePath::absolute_dirs_and_file_name`].
+const PARQUET_FILE_DEPTH: usize = 4;
+
+#[derive(Debug, Snafu)]
+#[allow(missing_copy_implementations, missing_docs)]
+pub enum Error {
+    #[snafu(display("Error while listing parquet files flagged for deletion {}", source))]
+    ListToDelete {
+        source: iox_catalog::interface::Error,
+    },
+
+    #[snafu(display("Error while looking up table {} {}", table_id, source))]
+    LookupTable {
+        source: iox_catalog::interface::Error,
+        table_id: TableId,
+    },
+
+    #[snafu(display("Error while deleting parquet files from the catalog {}", source))]
+    DeleteFromCatalog {
+        source: iox_catalog::interface::Error,
+    },
+
+    #[snafu(display("Error while checking for parquet file {} {}", object_store_id, source))]
+    CheckExists {
+        source: iox_catalog::interface::Error,
+        object_store_id: Uuid,
+    },
+
+    #[snafu(display("Error listing object store {}", source))]
+    ListObjectStore { source: object_store::Error },
+
+    #[snafu(display("Error while listing namespaces {}", source))]
+    ListNamespaces {
+        source: iox_catalog::interface::Error,
+    },
+
+    #[snafu(display(
+        "Error while listing parquet files of namespace {} {}",
+        namespace_id,
+        source
+    ))]
+    ListNamespaceFiles {
+        source: iox_catalog::interface::Error,
+        namespace_id: NamespaceId,
+    },
+
+    #[snafu(display("Error while listing soft-deleted namespaces and tables {}", source))]
+    ListSoftDeleted {
+        source: iox_catalog::interface::Error,
+    },
+
+    #[snafu(display("Error while listing parquet files of table {} {}", table_id, source))]
+    ListTableFiles {
+        source: iox_catalog::interface::Error,
+        table_id: TableId,
+    },
+
+    #[snafu(display("Error while flagging a parquet file for deletion {}", source))]
+    FlagForDelete {
+        source: iox_catalog::interface::Error,
+    },
+}
+
+/// A specialized `Error` for garbage collector errors
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Configuration of the [`GarbageCollector`].
+#[derive(Debug, Clone, Copy)]
+pub struct GarbageCollectorConfig {
+    /// How long a file must have been flagged for deletion before it is removed. Orphaned
+    /// objects must be at least this old as well, so that files that are just being persisted
+    /// (and are not yet in the catalog) are not removed.
+    pub grace_period: Duration,
+
+    /// Pause between two garbage collection rounds.
+    pub interval: Duration,
+
+    /// Only log the files that would be deleted, without deleting anything.
+    pub dry_run: bool,
+}
+
+/// Summary of a single [`GarbageCollector::run_once`] round.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GarbageCollectionSummary {
+    /// Number of files past the retention period of their namespace that were flagged for
+    /// deletion (or would be flagged in dry-run mode).
+    pub expired_files: u64,
+
+    /// Number of files of soft-deleted namespaces / tables that were flagged for deletion (or
+    /// would be flagged in dry-run mode).
+    pub soft_deleted_files: u64,
+
+    /// Number of flagged files that were removed (or would be removed in dry-run mode).
+    pub deleted_files: u64,
+
+    /// Total size of the removed flagged files in bytes.
+    pub deleted_bytes: u64,
+
+    /// Number of orphaned objects that were found.
+    pub orphaned_objects: u64,
+
+    /// Number of orphaned objects that were removed.
+    pub deleted_orphaned_objects: u64,
+}
+
+/// Metrics of the garbage collector.
+#[derive(Debug)]
+struct GarbageCollectorMetrics {
+    /// Number of files flagged for deletion because they are past the retention period.
+    expired_files: U64Counter,
+
+    /// Number of files flagged for deletion because their namespace / table was soft-deleted.
+    soft_deleted_files: U64Counter,
+
+    /// Number of flagged files removed.
+    deleted_files: U64Counter,
+
+    /// Bytes of flagged files removed.
+    deleted_bytes: U64Counter,
+
+    /// Number of orphaned objects found.
+    orphaned_objects: U64Counter,
+
+    /// Number of orphaned objects removed.
+    deleted_orphaned_objects: U64Counter,
+
+    /// Number of failed garbage collection rounds.
+    failures: U64Counter,
+}
+
+impl GarbageCollectorMetrics {
+    fn new(registry: &metric::Registry) -> Self {
+        Self {
+            expired_files: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_expired_files_total",
+                    "Number of parquet files flagged for deletion because they are past retention",
+                )
+                .recorder(&[]),
+            soft_deleted_files: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_soft_deleted_files_total",
+                    "Number of parquet files flagged for deletion because their namespace or table was soft-deleted",
+                )
+                .recorder(&[]),
+            deleted_files: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_deleted_files_total",
+                    "Number of parquet files flagged for deletion that were removed",
+                )
+                .recorder(&[]),
+            deleted_bytes: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_deleted_bytes_total",
+                    "Total size of the parquet files flagged for deletion that were removed",
+                )
+                .recorder(&[]),
+            orphaned_objects: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_orphaned_objects_total",
+                    "Number of parquet files in object storage without a catalog record",
+                )
+                .recorder(&[]),
+            deleted_orphaned_objects: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_deleted_orphaned_objects_total",
+                    "Number of parquet files without a catalog record that were removed",
+                )
+                .recorder(&[]),
+            failures: registry
+                .register_metric::<U64Counter>(
+                    "garbage_collector_failures_total",
+                    "Number of failed garbage collection rounds",
+                )
+                .recorder(&[]),
+        }
+    }
+}
+
+/// Removes parquet files that are no longer needed from object storage and the catalog.
+#[derive(Debug)]
+pub struct GarbageCollector {
+    catalog: Arc<dyn Catalog>,
+    object_store: Arc<DynObjectStore>,
+    time_provider: Arc<dyn TimeProvider>,
+    config: GarbageCollectorConfig,
+    metrics: GarbageCollectorMetrics,
+}
+
+impl GarbageCollector {
+    /// Initialize the garbage collector
+    pub fn new(
+        catalog: Arc<dyn Catalog>,
+        object_store: Arc<DynObjectStore>,
+        time_provider: Arc<dyn TimeProvider>,
+        config: GarbageCollectorConfig,
+        registry: Arc<metric::Registry>,
+    ) -> Self {
+        Self {
+            catalog,
+            object_store,
+            time_provider,
+            config,
+            metrics: GarbageCollectorMetrics::new(&registry),
+        }
+    }
+
+    /// Configuration of this garbage collector
+    pub fn config(&self) -> &GarbageCollectorConfig {
+        &self.config
+    }
+
+    /// Run a single garbage collection round: flag files that are past the retention period of
+    /// their namespace or that belong to a soft-deleted namespace / table, remove flagged files
+    /// whose grace period is over, then remove orphaned objects.
+    pub async fn run_once(&self) -> Result<GarbageCollectionSummary> {
+        let cutoff = self
+            .time_provider
+            .now()
+            .checked_sub(self.config.grace_period)
+            .unwrap_or_else(|| Time::from_timestamp_nanos(0));
+
+        let mut summary = GarbageCollectionSummary::default();
+        let res = async {
+            self.flag_expired_files(&mut summary).await?;
+            self.flag_soft_deleted_files(&mut summary).await?;
+            self.delete_flagged_files(cutoff, &mut summary).await?;
+            self.delete_orphaned_objects(cutoff, &mut summary).await
+        }
+        .await;
+
+        self.metrics.orphaned_objects.inc(summary.orphaned_objects);
+        if !self.config.dry_run {
+            self.metrics.expired_files.inc(summary.expired_files);
+            self.metrics
+                .soft_deleted_files
+                .inc(summary.soft_deleted_files);
+            self.metrics.deleted_files.inc(summary.deleted_files);
+            self.metrics.deleted_bytes.inc(summary.deleted_bytes);
+            self.metrics
+                .deleted_orphaned_objects
+                .inc(summary.deleted_orphaned_objects);
+        }
+        if res.is_err() {
+            self.metrics.failures.inc(1);
+        }
+
+        res.map(|_| summary)
+    }
+
+    async fn flag_expired_files(&self, summary: &mut GarbageCollectionSummary) -> Result<()> {
+        let now = self.time_provider.now();
+
+        let mut repos = self.catalog.repositories().await;
+        let namespaces = repos
+            .namespaces()
+            .list()
+            .await
+            .context(ListNamespacesSnafu)?;
+
+        for namespace in namespaces {
+            let cutoff = match retention::retention_cutoff(&namespace, now) {
+                Some(cutoff) => cutoff,
+                None => continue,
+            };
+
+            let files = repos
+                .parquet_files()
+                .list_by_namespace_not_to_delete(namespace.id)
+                .await
+                .context(ListNamespaceFilesSnafu {
+                    namespace_id: namespace.id,
+                })?;
+
+            for file in files
+                .into_iter()
+                .filter(|f| retention::is_expired(f, cutoff))
+            {
+                summary.expired_files += 1;
+
+                if self.config.dry_run {
+                    info!(
+                        parquet_file_id = file.id.get(),
+                        namespace = %namespace.name,
+                        "dry-run: would flag parquet file past retention period for deletion"
+                    );
+                    continue;
+                }
+
+                repos
+                    .parquet_files()
+                    .flag_for_delete(file.id)
+                    .await
+                    .context(FlagForDeleteSnafu)?;
+                debug!(
+                    parquet_file_id = file.id.get(),
+                    namespace = %namespace.name,
+                    "flagged parquet file past retention period for deletion"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn flag_soft_deleted_files(&self, summary: &mut GarbageCollectionSummary) -> Result<()> {
+        let mut repos = self.catalog.repositories().await;
+
+        let mut files = vec![];
+        let namespaces = repos
+            .namespaces()
+            .list_soft_deleted(None)
+            .await
+            .context(ListSoftDeletedSnafu)?;
+        for namespace in namespaces {
+            files.extend(
+                repos
+                    .parquet_files()
+                    .list_by_namespace_not_to_delete(namespace.id)
+                    .await
+                    .context(ListNamespaceFilesSnafu {
+                        namespace_id: namespace.id,
+                    })?,
+            );
+        }
+        let tables = repos
+            .tables()
+            .list_soft_deleted(None)
+            .await
+            .context(ListSoftDeletedSnafu)?;
+        for table in tables {
+            files.extend(
+                repos
+                    .parquet_files()
+                    .list_by_table_not_to_delete(table.id)
+                    .await
+                    .context(ListTableFilesSnafu { table_id: table.id })?,
+            );
+        }
+
+        // A file of a soft-deleted table within a soft-deleted namespace is listed twice.
+        files.sort_unstable_by_key(|f| f.id);
+        files.dedup_by_key(|f| f.id);
+
+        for file in files {
+            summary.soft_deleted_files += 1;
+
+            if self.config.dry_run {
+                info!(
+                    parquet_file_id = file.id.get(),
+                    table_id = file.table_id.get(),
+                    "dry-run: would flag parquet file of soft-deleted namespace / table for deletion"
+                );
+                continue;
+            }
+
+            repos
+                .parquet_files()
+                .flag_for_delete(file.id)
+                .await
+                .context(FlagForDeleteSnafu)?;
+            debug!(
+                parquet_file_id = file.id.get(),
+                table_id = file.table_id.get(),
+                "flagged parquet file of soft-deleted namespace / table for deletion"
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn delete_flagged_files(
+        &self,
+        cutoff: Time,
+        summary: &mut GarbageCollectionSummary,
+    ) -> Result<()> {
+        let mut repos = self.catalog.repositories().await;
+        let files = repos
+            .parquet_files()
+            .list_to_delete_older_than(Timestamp::new(cutoff.timestamp_nanos()))
+            .await
+            .context(ListToDeleteSnafu)?;
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let mut namespace_ids: HashMap<TableId, NamespaceId> = HashMap::new();
+        for table_id in files.iter().map(|f| f.table_id) {
+            if namespace_ids.contains_key(&table_id) {
+                continue;
+            }
+            let table = repos
+                .tables()
+                .get_by_id(table_id)
+                .await
+                .context(LookupTableSnafu { table_id })?;
+            match table {
+                Some(table) => {
+                    namespace_ids.insert(table_id, table.namespace_id);
+                }
+                None => warn!(
+                    table_id = table_id.get(),
+                    "table of parquet files flagged for deletion not found"
+                ),
+            }
+        }
+
+        let iox_object_store = IoxObjectStore::existing(
+            Arc::clone(&self.object_store),
+            IoxObjectStore::root_path_for(&*self.object_store, Uuid::new_v4()),
+        );
+
+        let mut deleted = Vec::with_capacity(files.len());
+        let mut deleted_bytes = 0;
+        for file in &files {
+            let namespace_id = match namespace_ids.get(&file.table_id) {
+                Some(namespace_id) => *namespace_id,
+                None => continue,
+            };
+            let path = parquet_file_path(namespace_id, file);
+
+            if self.config.dry_run {
+                info!(
+                    parquet_file_id = file.id.get(),
+                    object_store_id = %file.object_store_id,
+                    "dry-run: would delete parquet file flagged for deletion"
+                );
+                deleted.push(file.id);
+                deleted_bytes += file.file_size_bytes as u64;
+                continue;
+            }
+
+            // Remove the object first. Should that fail, the catalog record is kept and the
+            // deletion is retried in the next round.
+            match iox_object_store.delete_parquet_file(&path).await {
+                Ok(()) => {
+                    debug!(
+                        parquet_file_id = file.id.get(),
+                        object_store_id = %file.object_store_id,
+                        "deleted parquet file flagged for deletion"
+                    );
+                    deleted.push(file.id);
+                    deleted_bytes += file.file_size_bytes as u64;
+                }
+                Err(e) => {
+                    warn!(
+                        %e,
+                        parquet_file_id = file.id.get(),
+                        object_store_id = %file.object_store_id,
+                        "cannot delete parquet file from object store"
+                    );
+                }
+            }
+        }
+
+        if !self.config.dry_run && !deleted.is_empty() {
+            repos
+                .parquet_files()
+                .delete_by_ids(&deleted)
+                .await
+                .context(DeleteFromCatalogSnafu)?;
+        }
+
+        summary.deleted_files += deleted.len() as u64;
+        summary.deleted_bytes += deleted_bytes;
+
+        Ok(())
+    }
+
+    async fn delete_orphaned_objects(
+        &self,
+        cutoff: Time,
+        summary: &mut GarbageCollectionSummary,
+    ) -> Result<()> {
+        let objects = self.list_parquet_objects().await?;
+
+        let mut repos = self.catalog.repositories().await;
+        for (meta, path) in objects {
+            // objects that are too young may belong to a file that is being persisted
+            if Time::from_date_time(meta.last_modified) >= cutoff {
+                continue;
+            }
+
+            let object_store_id = path
+                .object_store_id()
+                .expect("NG parquet file path has an object store ID");
+            let exists = repos
+                .parquet_files()
+                .exist_by_object_store_id(object_store_id)
+                .await
+                .context(CheckExistsSnafu { object_store_id })?;
+            if exists {
+                continue;
+            }
+            summary.orphaned_objects += 1;
+
+            if self.config.dry_run {
+                info!(
+                    location = %meta.location,
+                    "dry-run: would delete orphaned parquet file"
+                );
+                continue;
+            }
+
+            match self.object_store.delete(&meta.location).await {
+                Ok(()) => {
+                    info!(location = %meta.location, "deleted orphaned parquet file");
+                    summary.deleted_orphaned_objects += 1;
+                }
+                Err(e) => {
+                    warn!(%e, location = %meta.location, "cannot delete orphaned parquet file");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// List all objects that have the layout of an NG parquet file.
+    ///
+    /// Traverses only the directories that make up [`ParquetFilePath`]s, so objects of other
+    /// layouts that share the same object store are never visited deeper than necessary.
+    async fn list_parquet_objects(&self) -> Result<Vec<(ObjectMeta<Path>, ParquetFilePath)>> {
+        let mut found = vec![];
+        let mut prefixes = vec![(self.object_store.new_path(), 0)];
+
+        while let Some((prefix, depth)) = prefixes.pop() {
+            let list = self
+                .object_store
+                .list_with_delimiter(&prefix)
+                .await
+                .context(ListObjectStoreSnafu)?;
+
+            if depth < PARQUET_FILE_DEPTH {
+                prefixes.extend(list.common_prefixes.into_iter().map(|p| (p, depth + 1)));
+                continue;
+            }
+
+            for meta in list.objects {
+                let dirs_and_file_name: DirsAndFileName = meta.location.clone().into();
+                if let Ok(path) =
+                    ParquetFilePath::from_absolute_dirs_and_file_name(&dirs_and_file_name)
+                {
+                    found.push((meta, path));
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+fn parquet_file_path(namespace_id: NamespaceId, file: &ParquetFile) -> ParquetFilePath {
+    ParquetFilePath::new_new_gen(
+        namespace_id,
+        file.table_id,
+        file.sequencer_id,
+        file.partition_id,
+        file.object_store_id,
+    )
+}
+
+/// Runs garbage collection rounds, separated by the configured interval, until `shutdown` is
+/// cancelled.
+pub async fn run_garbage_collector(gc: Arc<GarbageCollector>, shutdown: CancellationToken) {
+    while !shutdown.is_cancelled() {
+        tokio::select! {
+            res = gc.run_once() => match res {
+                Ok(summary) => {
+                    if summary != GarbageCollectionSummary::default() {
+                        info!(
+                            expired_files = summary.expired_files,
+                            soft_deleted_files = summary.soft_deleted_files,
+                            deleted_files = summary.deleted_files,
+                            deleted_bytes = summary.deleted_bytes,
+                            orphaned_objects = summary.orphaned_objects,
+                            deleted_orphaned_objects = summary.deleted_orphaned_objects,
+                            dry_run = gc.config.dry_run,
+                            "garbage collection round finished"
+                        );
+                    }
+                }
+                Err(e) => error!(%e, "garbage collection round failed"),
+            },
+            _ = shutdown.cancelled() => break,
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(gc.config.interval) => {},
+            _ = shutdown.cancelled() => break,
+        }
+    }
+
+    info!("garbage collector shutdown");
+}
+
+/// Runs the [`GarbageCollector`] as the only background worker, for deployments that run the
+/// garbage collector separately from the compactor.
+#[derive(Debug)]
+pub struct GarbageCollectorHandlerImpl {
+    /// Future that resolves when the background worker exits
+    join_handle: SharedJoinHandle,
+
+    /// A token that is used to trigger shutdown of the background worker
+    shutdown: CancellationToken,
+}
+
+impl GarbageCollectorHandlerImpl {
+    /// Initialize the garbage collector and start its background worker
+    pub fn new(
+        catalog: Arc<dyn Catalog>,
+        object_store: Arc<DynObjectStore>,
+        time_provider: Arc<dyn TimeProvider>,
+        config: GarbageCollectorConfig,
+        registry: Arc<metric::Registry>,
+    ) -> Self {
+        let shutdown = CancellationToken::new();
+
+        let gc = Arc::new(GarbageCollector::new(
+            catalog,
+            object_store,
+            time_provider,
+            config,
+            registry,
+        ));
+        let join_handle = shared_handle(tokio::spawn(run_garbage_collector(gc, shutdown.clone())));
+
+        Self {
+            join_handle,
+            shutdown,
+        }
+    }
+}
+
+#[async_trait]
+impl CompactorHandler for GarbageCollectorHandlerImpl {
+    async fn join(&self) {
+        self.join_handle
+            .clone()
+            .await
+            .expect("garbage collector worker panicked");
+
+        if !self.shutdown.is_cancelled() {
+            panic!("Background worker 'garbage collector' exited early!");
+        }
+    }
+
+    fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+}
+
+impl Drop for GarbageCollectorHandlerImpl {
+    fn drop(&mut self) {
+        if !self.shutdown.is_cancelled() {
+            warn!("GarbageCollectorHandlerImpl dropped without calling shutdown()");
+            self.shutdown.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use iox_tests::util::{TestCatalog, TestParquetFile};
+    use time::{MockProvider, SystemProvider};
+
+    const GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+    fn config(dry_run: bool) -> GarbageCollectorConfig {
+        GarbageCollectorConfig {
+            grace_period: GRACE_PERIOD,
+            interval: Duration::from_millis(10),
+            dry_run,
+        }
+    }
+
+    async fn object_exists(catalog: &TestCatalog, path: &ParquetFilePath) -> bool {
+        let object_store = catalog.object_store();
+        let iox_object_store = IoxObjectStore::existing(
+            Arc::clone(&object_store),
+            IoxObjectStore::root_path_for(&*object_store, Uuid::new_v4()),
+        );
+        iox_object_store.get_parquet_file(path).await.is_ok()
+    }
+
+    async fn file_exists(catalog: &TestCatalog, file: &TestParquetFile) -> bool {
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .exist(file.parquet_file.id)
+            .await
+            .unwrap()
+    }
+
+    /// Catalog with one flagged and one live file.
+    async fn setup() -> (
+        Arc<TestCatalog>,
+        NamespaceId,
+        Arc<TestParquetFile>,
+        Arc<TestParquetFile>,
+    ) {
+        let catalog = TestCatalog::new();
+        let lp = "table,tag1=WA field_int=1000 8000";
+
+        let ns = catalog.create_namespace("ns").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let table = ns.create_table("table").await;
+        let partition = table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await;
+        let flagged = partition
+            .create_parquet_file_with_min_max(lp, 1, 5, 8000, 8000)
+            .await;
+        flagged.flag_for_delete().await;
+        let live = partition
+            .create_parquet_file_with_min_max(lp, 6, 10, 8000, 8000)
+            .await;
+
+        (catalog, ns.namespace.id, flagged, live)
+    }
+
+    fn time_provider_after_grace_period() -> Arc<MockProvider> {
+        let now = SystemProvider::new().now();
+        Arc::new(MockProvider::new(
+            now + GRACE_PERIOD + Duration::from_secs(1),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_keeps_files_within_grace_period() {
+        let (catalog, namespace_id, flagged, live) = setup().await;
+
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            Arc::new(SystemProvider::new()),
+            config(false),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary, GarbageCollectionSummary::default());
+
+        assert!(file_exists(&catalog, &flagged).await);
+        assert!(
+            object_exists(
+                &catalog,
+                &parquet_file_path(namespace_id, &flagged.parquet_file)
+            )
+            .await
+        );
+        assert!(file_exists(&catalog, &live).await);
+    }
+
+    #[tokio::test]
+    async fn test_deletes_flagged_files() {
+        let (catalog, namespace_id, flagged, live) = setup().await;
+
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            time_provider_after_grace_period(),
+            config(false),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.deleted_files, 1);
+        assert_eq!(
+            summary.deleted_bytes,
+            flagged.parquet_file.file_size_bytes as u64
+        );
+        assert_eq!(summary.orphaned_objects, 0);
+
+        assert!(!file_exists(&catalog, &flagged).await);
+        assert!(
+            !object_exists(
+                &catalog,
+                &parquet_file_path(namespace_id, &flagged.parquet_file)
+            )
+            .await
+        );
+        assert!(file_exists(&catalog, &live).await);
+        assert!(
+            object_exists(
+                &catalog,
+                &parquet_file_path(namespace_id, &live.parquet_file)
+            )
+            .await
+        );
+
+        // nothing left to do
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary, GarbageCollectionSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_deletes_orphaned_objects() {
+        let (catalog, namespace_id, _flagged, live) = setup().await;
+
+        let orphan = ParquetFilePath::new_new_gen(
+            namespace_id,
+            live.parquet_file.table_id,
+            live.parquet_file.sequencer_id,
+            live.parquet_file.partition_id,
+            Uuid::new_v4(),
+        );
+        let object_store = catalog.object_store();
+        let iox_object_store = IoxObjectStore::existing(
+            Arc::clone(&object_store),
+            IoxObjectStore::root_path_for(&*object_store, Uuid::new_v4()),
+        );
+        iox_object_store
+            .put_parquet_file(&orphan, Bytes::from("foo"))
+            .await
+            .unwrap();
+
+        // objects of other layouts are ignored
+        let mut other = object_store.new_path();
+        other.push_all_dirs(&["1", "2", "3", "4"]);
+        other.set_file_name("rules.pb");
+        object_store.put(&other, Bytes::from("bar")).await.unwrap();
+
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            time_provider_after_grace_period(),
+            config(false),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.orphaned_objects, 1);
+        assert_eq!(summary.deleted_orphaned_objects, 1);
+
+        assert!(!object_exists(&catalog, &orphan).await);
+        assert!(object_store.get(&other).await.is_ok());
+        assert!(
+            object_exists(
+                &catalog,
+                &parquet_file_path(namespace_id, &live.parquet_file)
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let (catalog, namespace_id, flagged, _live) = setup().await;
+
+        let orphan = ParquetFilePath::new_new_gen(
+            namespace_id,
+            flagged.parquet_file.table_id,
+            flagged.parquet_file.sequencer_id,
+            flagged.parquet_file.partition_id,
+            Uuid::new_v4(),
+        );
+        let object_store = catalog.object_store();
+        let iox_object_store = IoxObjectStore::existing(
+            Arc::clone(&object_store),
+            IoxObjectStore::root_path_for(&*object_store, Uuid::new_v4()),
+        );
+        iox_object_store
+            .put_parquet_file(&orphan, Bytes::from("foo"))
+            .await
+            .unwrap();
+
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            time_provider_after_grace_period(),
+            config(true),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.deleted_files, 1);
+        assert_eq!(summary.orphaned_objects, 1);
+        assert_eq!(summary.deleted_orphaned_objects, 0);
+
+        // nothing was actually deleted
+        assert!(file_exists(&catalog, &flagged).await);
+        assert!(
+            object_exists(
+                &catalog,
+                &parquet_file_path(namespace_id, &flagged.parquet_file)
+            )
+            .await
+        );
+        assert!(object_exists(&catalog, &orphan).await);
+    }
+
+    #[tokio::test]
+    async fn test_flags_expired_files() {
+        let catalog = TestCatalog::new();
+        let lp = "table,tag1=WA field_int=1000 8000";
+        let hour = 60 * 60 * 1_000_000_000;
+
+        let ns = catalog.create_namespace_with_retention("ns", "1h").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let table = ns.create_table("table").await;
+        let partition = table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await;
+        let expired = partition
+            .create_parquet_file_with_min_max(lp, 1, 5, hour, 2 * hour)
+            .await;
+        let live = partition
+            .create_parquet_file_with_min_max(lp, 6, 10, 8 * hour, 10 * hour)
+            .await;
+
+        // files of namespaces with infinite retention are never expired
+        let other_ns = catalog.create_namespace("other_ns").await;
+        let other_table = other_ns.create_table("table").await;
+        let other = other_table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await
+            .create_parquet_file_with_min_max(lp, 1, 5, hour, 2 * hour)
+            .await;
+
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(10 * hour)));
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            Arc::clone(&time_provider) as _,
+            config(true),
+            catalog.metric_registry(),
+        );
+
+        // dry-run does not flag anything
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.expired_files, 1);
+        let flagged = catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .list_to_delete_older_than(Timestamp::new(i64::MAX))
+            .await
+            .unwrap();
+        assert!(flagged.is_empty());
+
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            Arc::clone(&time_provider) as _,
+            config(false),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.expired_files, 1);
+        // still within the grace period
+        assert_eq!(summary.deleted_files, 0);
+
+        let flagged: Vec<_> = catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .list_to_delete_older_than(Timestamp::new(i64::MAX))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+        assert_eq!(flagged, vec![expired.parquet_file.id]);
+        assert!(file_exists(&catalog, &live).await);
+        assert!(file_exists(&catalog, &other).await);
+    }
+
+    #[tokio::test]
+    async fn test_flags_soft_deleted_files() {
+        let catalog = TestCatalog::new();
+        let lp = "table,tag1=WA field_int=1000 8000";
+
+        let deleted_ns = catalog.create_namespace("deleted_ns").await;
+        let sequencer = deleted_ns.create_sequencer(1).await;
+        let partition = deleted_ns
+            .create_table("table")
+            .await
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await;
+
+        let ns = catalog.create_namespace("ns").await;
+        let deleted_table = ns.create_table("deleted_table").await;
+        let live_table = ns.create_table("live_table").await;
+
+        let mut repos = catalog.catalog().repositories().await;
+        repos.namespaces().soft_delete("deleted_ns").await.unwrap();
+        repos
+            .tables()
+            .soft_delete(deleted_table.table.id)
+            .await
+            .unwrap();
+        drop(repos);
+
+        // persisted after the deletion, so not flagged by it
+        let ns_file = partition
+            .create_parquet_file_with_min_max(lp, 1, 5, 8000, 8000)
+            .await;
+        let table_file = deleted_table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await
+            .create_parquet_file_with_min_max(lp, 1, 5, 8000, 8000)
+            .await;
+        let live = live_table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await
+            .create_parquet_file_with_min_max(lp, 1, 5, 8000, 8000)
+            .await;
+
+        // dry-run does not flag anything
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            Arc::new(SystemProvider::new()),
+            config(true),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.soft_deleted_files, 2);
+
+        let gc = GarbageCollector::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            Arc::new(SystemProvider::new()),
+            config(false),
+            catalog.metric_registry(),
+        );
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.soft_deleted_files, 2);
+
+        let mut flagged: Vec<_> = catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .list_to_delete_older_than(Timestamp::new(i64::MAX))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+        flagged.sort_unstable();
+        assert_eq!(
+            flagged,
+            vec![ns_file.parquet_file.id, table_file.parquet_file.id]
+        );
+        assert!(file_exists(&catalog, &live).await);
+
+        // already flagged files are not counted again
+        let summary = gc.run_once().await.unwrap();
+        assert_eq!(summary.soft_deleted_files, 0);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shutdown() {
+        let catalog = TestCatalog::new();
+
+        let handler = GarbageCollectorHandlerImpl::new(
+            catalog.catalog(),
+            catalog.object_store(),
+            Arc::new(SystemProvider::new()),
+            config(false),
+            catalog.metric_registry(),
+        );
+
+        // does not exit w/o shutdown
+        tokio::select! {
+            _ = handler.join() => panic!("garbage collector finished w/o shutdown"),
+            _ = tokio::time::sleep(Duration::from_millis(50)) => {},
+        };
+
+        handler.shutdown();
+
+        tokio::time::timeout(Duration::from_millis(1000), handler.join())
+            .await
+            .unwrap();
+    }
+}
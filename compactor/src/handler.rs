@@ -5,25 +5,29 @@ use backoff::BackoffConfig;
 use data_types2::SequencerId;
 use futures::{
     future::{BoxFuture, Shared},
-    FutureExt, TryFutureExt,
+    stream::FuturesUnordered,
+    FutureExt, StreamExt, TryFutureExt,
 };
 use iox_catalog::interface::Catalog;
 use object_store::DynObjectStore;
-use observability_deps::tracing::warn;
+use observability_deps::tracing::{error, info, warn};
 use query::exec::Executor;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use thiserror::Error;
 use time::TimeProvider;
 use tokio::task::{JoinError, JoinHandle};
 use tokio_util::sync::CancellationToken;
 
-use crate::compact::Compactor;
+use crate::{
+    compact::Compactor,
+    garbage_collector::{run_garbage_collector, GarbageCollector, GarbageCollectorConfig},
+};
 
 #[derive(Debug, Error)]
 #[allow(missing_copy_implementations, missing_docs)]
 pub enum Error {}
 
-/// The [`CompactorHandler`] does nothing at this point
+/// The [`CompactorHandler`] runs the background compaction workers
 #[async_trait]
 pub trait CompactorHandler: Send + Sync {
     /// Wait until the handler finished  to shutdown.
@@ -36,35 +40,61 @@ pub trait CompactorHandler: Send + Sync {
 }
 
 /// A [`JoinHandle`] that can be cloned
-type SharedJoinHandle = Shared<BoxFuture<'static, Result<(), Arc<JoinError>>>>;
+pub(crate) type SharedJoinHandle = Shared<BoxFuture<'static, Result<(), Arc<JoinError>>>>;
 
 /// Convert a [`JoinHandle`] into a [`SharedJoinHandle`].
-fn shared_handle(handle: JoinHandle<()>) -> SharedJoinHandle {
+pub(crate) fn shared_handle(handle: JoinHandle<()>) -> SharedJoinHandle {
     handle.map_err(Arc::new).boxed().shared()
 }
 
-/// Implementation of the `CompactorHandler` trait (that currently does nothing)
+/// Pause of a sequencer worker after a compaction round that found nothing to do or failed.
+///
+/// The pause doubles with every idle round up to [`MAX_IDLE_PAUSE`] and is reset as soon as a round does any work.
+const MIN_IDLE_PAUSE: Duration = Duration::from_secs(1);
+
+/// Upper bound for the pause between idle compaction rounds, see [`MIN_IDLE_PAUSE`].
+const MAX_IDLE_PAUSE: Duration = Duration::from_secs(60);
+
+/// Implementation of the `CompactorHandler` trait
 #[derive(Debug)]
 pub struct CompactorHandlerImpl {
     /// Data to compact
     compactor_data: Arc<Compactor>,
 
+    /// Futures that resolve when the background workers exit, one per sequencer plus one for the
+    /// garbage collector (if enabled)
+    join_handles: Vec<(String, SharedJoinHandle)>,
+
     /// A token that is used to trigger shutdown of the background worker
     shutdown: CancellationToken,
 }
 
 impl CompactorHandlerImpl {
     /// Initialize the Compactor
+    ///
+    /// If `gc_config` is set, a [`GarbageCollector`] runs alongside the compaction workers.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         sequencers: Vec<SequencerId>,
         catalog: Arc<dyn Catalog>,
         object_store: Arc<DynObjectStore>,
         exec: Arc<Executor>,
         time_provider: Arc<dyn TimeProvider>,
-        _registry: &metric::Registry,
+        registry: Arc<metric::Registry>,
+        gc_config: Option<GarbageCollectorConfig>,
     ) -> Self {
         let shutdown = CancellationToken::new();
 
+        let garbage_collector = gc_config.map(|config| {
+            Arc::new(GarbageCollector::new(
+                Arc::clone(&catalog),
+                Arc::clone(&object_store),
+                Arc::clone(&time_provider),
+                config,
+                Arc::clone(&registry),
+            ))
+        });
+
         let compactor_data = Arc::new(Compactor::new(
             sequencers,
             catalog,
@@ -72,23 +102,95 @@ impl CompactorHandlerImpl {
             exec,
             time_provider,
             BackoffConfig::default(),
+            registry,
         ));
 
+        let mut join_handles: Vec<_> = compactor_data
+            .sequencers()
+            .iter()
+            .map(|sequencer_id| {
+                let handle = tokio::spawn(run_compactor(
+                    Arc::clone(&compactor_data),
+                    *sequencer_id,
+                    shutdown.clone(),
+                ));
+                (
+                    format!("compactor sequencer {}", sequencer_id.get()),
+                    shared_handle(handle),
+                )
+            })
+            .collect();
+
+        if let Some(garbage_collector) = garbage_collector {
+            let handle = tokio::spawn(run_garbage_collector(garbage_collector, shutdown.clone()));
+            join_handles.push((String::from("garbage collector"), shared_handle(handle)));
+        }
+
         Self {
             compactor_data,
+            join_handles,
             shutdown,
         }
     }
 }
 
+/// Compacts the given sequencer in a loop until `shutdown` is cancelled.
+///
+/// Every round compacts a bounded number of partitions (see [`Compactor::find_and_compact`]). A round is always run to
+/// completion so that shutdown never leaves half-written files or catalog updates behind, `shutdown` is only checked
+/// between rounds. Rounds that did not find any work (or failed) are followed by an exponentially increasing pause.
+async fn run_compactor(
+    compactor: Arc<Compactor>,
+    sequencer_id: SequencerId,
+    shutdown: CancellationToken,
+) {
+    let mut idle_pause = MIN_IDLE_PAUSE;
+
+    while !shutdown.is_cancelled() {
+        let did_work = match compactor.find_and_compact(sequencer_id).await {
+            Ok(summary) => summary.did_work(),
+            Err(e) => {
+                error!(%e, sequencer_id = sequencer_id.get(), "compaction round failed");
+                false
+            }
+        };
+
+        if did_work {
+            idle_pause = MIN_IDLE_PAUSE;
+            continue;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(idle_pause) => {},
+            _ = shutdown.cancelled() => break,
+        }
+        idle_pause = (idle_pause * 2).min(MAX_IDLE_PAUSE);
+    }
+
+    info!(sequencer_id = sequencer_id.get(), "compactor shutdown");
+}
+
 #[async_trait]
 impl CompactorHandler for CompactorHandlerImpl {
     async fn join(&self) {
-        // TODO: this should block on the compactor background loop exiting, not
-        // this token being cancelled.
-        //
-        // This fires immediately when shutdown() is called.
-        self.shutdown.cancelled().await
+        // Need to poll handlers unordered to detect early exists of any worker in the list.
+        let mut unordered: FuturesUnordered<_> = self
+            .join_handles
+            .iter()
+            .cloned()
+            .map(|(name, handle)| async move { handle.await.map(|_| name) })
+            .collect();
+
+        while let Some(e) = unordered.next().await {
+            let name = e.unwrap();
+
+            if !self.shutdown.is_cancelled() {
+                panic!("Background worker '{name}' exited early!");
+            }
+        }
+
+        // without any sequencers there are no workers, so wait for the shutdown signal instead
+        self.shutdown.cancelled().await;
     }
 
     fn shutdown(&self) {
@@ -104,3 +206,119 @@ impl Drop for CompactorHandlerImpl {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iox_tests::util::TestCatalog;
+    use metric::{Attributes, Metric, U64Counter};
+    use time::SystemProvider;
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace("ns").await;
+        let sequencer = ns.create_sequencer(1).await;
+
+        let compactor = CompactorHandlerImpl::new(
+            vec![sequencer.sequencer.id],
+            catalog.catalog(),
+            catalog.object_store(),
+            catalog.exec(),
+            Arc::new(SystemProvider::new()),
+            catalog.metric_registry(),
+            None,
+        );
+
+        // does not exit w/o shutdown
+        tokio::select! {
+            _ = compactor.join() => panic!("compactor finished w/o shutdown"),
+            _ = tokio::time::sleep(Duration::from_millis(10)) => {},
+        };
+
+        compactor.shutdown();
+
+        tokio::time::timeout(Duration::from_millis(1000), compactor.join())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_background_loop_compacts() {
+        let catalog = TestCatalog::new();
+
+        let lp1 = vec![
+            "table,tag1=WA field_int=1000 8000",
+            "table,tag1=VT field_int=10 10000",
+        ]
+        .join("\n");
+        let lp2 = vec![
+            "table,tag1=WA field_int=1500 8000",
+            "table,tag1=UT field_int=270 25000",
+        ]
+        .join("\n");
+
+        let ns = catalog.create_namespace("ns").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let table = ns.create_table("table").await;
+        let partition = table
+            .with_sequencer(&sequencer)
+            .create_partition("part")
+            .await;
+        partition
+            .create_parquet_file_with_min_max(&lp1, 1, 5, 8000, 10000)
+            .await;
+        partition
+            .create_parquet_file_with_min_max(&lp2, 10, 15, 8000, 25000)
+            .await;
+
+        let compactor = CompactorHandlerImpl::new(
+            vec![sequencer.sequencer.id],
+            catalog.catalog(),
+            catalog.object_store(),
+            catalog.exec(),
+            Arc::new(SystemProvider::new()),
+            catalog.metric_registry(),
+            None,
+        );
+
+        let attributes = Attributes::from([(
+            "sequencer_id",
+            format!("{}", sequencer.sequencer.id.get()).into(),
+        )]);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let compacted = catalog
+                    .metric_registry()
+                    .get_instrument::<Metric<U64Counter>>("compactor_compacted_files_total")
+                    .and_then(|m| m.get_observer(&attributes).map(|o| o.fetch()))
+                    .unwrap_or_default();
+                if compacted == 2 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("files were not compacted in time");
+
+        // both original files are now flagged for deletion
+        let remaining = catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .list_by_table_not_to_delete(table.table.id)
+            .await
+            .unwrap();
+        assert!(!remaining.is_empty());
+        assert!(remaining
+            .iter()
+            .all(|f| f.min_sequence_number.get() == 1 && f.max_sequence_number.get() == 15));
+
+        compactor.shutdown();
+        tokio::time::timeout(Duration::from_millis(1000), compactor.join())
+            .await
+            .unwrap();
+    }
+}
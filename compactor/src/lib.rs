@@ -13,7 +13,9 @@
 #![allow(dead_code)]
 
 pub mod compact;
+pub mod garbage_collector;
 pub mod handler;
 pub mod query;
+pub mod retention;
 pub mod server;
 pub mod utils;
@@ -0,0 +1,67 @@
+//! Retention period enforcement shared by the compactor and the garbage collector.
+
+use data_types2::{Namespace, ParquetFile};
+use observability_deps::tracing::warn;
+use time::Time;
+
+/// Oldest timestamp (in nanoseconds since the epoch) that is still within the retention period of `namespace` at
+/// `now`.
+///
+/// Returns `None` if no data of the namespace has expired, e.g. because it retains data forever. An invalid retention
+/// duration is logged and treated as infinite, so that data is never deleted by accident.
+pub fn retention_cutoff(namespace: &Namespace, now: Time) -> Option<i64> {
+    let retention_period = match namespace.retention_period() {
+        Ok(retention_period) => retention_period?,
+        Err(e) => {
+            warn!(
+                %e,
+                namespace = %namespace.name,
+                "ignoring invalid namespace retention duration"
+            );
+            return None;
+        }
+    };
+
+    now.checked_sub(retention_period)
+        .map(|cutoff| cutoff.timestamp_nanos())
+}
+
+/// Returns `true` if all data of `file` is older than the retention `cutoff`.
+///
+/// Data stamped exactly at the cutoff is still within the retention period, matching the router that accepts it.
+pub fn is_expired(file: &ParquetFile, cutoff: i64) -> bool {
+    file.max_time.get() < cutoff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_types2::{KafkaTopicId, NamespaceId, QueryPoolId};
+
+    fn namespace(retention_duration: Option<&str>) -> Namespace {
+        Namespace {
+            id: NamespaceId::new(1),
+            name: String::from("ns"),
+            retention_duration: retention_duration.map(ToString::to_string),
+            kafka_topic_id: KafkaTopicId::new(1),
+            query_pool_id: QueryPoolId::new(1),
+            max_tables: 10,
+            max_columns_per_table: 10,
+            deleted_at: None,
+            partition_template: None,
+        }
+    }
+
+    #[test]
+    fn test_retention_cutoff() {
+        let now = Time::from_timestamp_nanos(10_000_000_000);
+
+        assert_eq!(retention_cutoff(&namespace(None), now), None);
+        assert_eq!(retention_cutoff(&namespace(Some("inf")), now), None);
+        assert_eq!(retention_cutoff(&namespace(Some("invalid")), now), None);
+        assert_eq!(
+            retention_cutoff(&namespace(Some("1s")), now),
+            Some(9_000_000_000)
+        );
+    }
+}
@@ -11,6 +11,9 @@ pub enum OrgBucketMappingError {
 
     #[snafu(display("missing org/bucket value"))]
     NotSpecified,
+
+    #[snafu(display("missing db value"))]
+    DbNotSpecified,
 }
 
 /// Map an InfluxDB 2.X org & bucket into an IOx DatabaseName.
@@ -37,6 +40,34 @@ pub fn org_and_bucket_to_database<'a, O: AsRef<str>, B: AsRef<str>>(
     DatabaseName::new(db_name).context(InvalidDatabaseNameSnafu)
 }
 
+/// Map an InfluxDB 1.X database & retention policy into an IOx DatabaseName.
+///
+/// Writes to the default retention policy (either unspecified, empty or
+/// `autogen`) map to the percent-encoded `db` alone, so that a 1.x `db` is
+/// addressable without knowing its retention policy. Any other retention
+/// policy is mapped exactly as [`org_and_bucket_to_database`] maps an org &
+/// bucket pair, using `db` as the org and `rp` as the bucket.
+pub fn db_and_rp_to_database<'a, D: AsRef<str>>(
+    db: D,
+    rp: Option<&str>,
+) -> Result<DatabaseName<'a>, OrgBucketMappingError> {
+    const DEFAULT_RETENTION_POLICY: &str = "autogen";
+
+    if db.as_ref().is_empty() {
+        return Err(OrgBucketMappingError::DbNotSpecified);
+    }
+
+    match rp {
+        Some(rp) if !rp.is_empty() && rp != DEFAULT_RETENTION_POLICY => {
+            org_and_bucket_to_database(db, rp)
+        }
+        _ => {
+            let db: Cow<'_, str> = utf8_percent_encode(db.as_ref(), NON_ALPHANUMERIC).into();
+            DatabaseName::new(db.into_owned()).context(InvalidDatabaseNameSnafu)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +118,35 @@ mod tests {
             .expect_err("should fail with empty org/bucket valuese");
         assert!(matches!(err, OrgBucketMappingError::NotSpecified));
     }
+
+    #[test]
+    fn test_db_rp_map_db_ok() {
+        let got = db_and_rp_to_database("telegraf", None).unwrap();
+        assert_eq!(got.as_str(), "telegraf");
+
+        let got = db_and_rp_to_database("telegraf", Some("")).unwrap();
+        assert_eq!(got.as_str(), "telegraf");
+
+        let got = db_and_rp_to_database("telegraf", Some("autogen")).unwrap();
+        assert_eq!(got.as_str(), "telegraf");
+
+        let got = db_and_rp_to_database("telegraf", Some("weekly")).unwrap();
+        assert_eq!(got.as_str(), "telegraf_weekly");
+    }
+
+    #[test]
+    fn test_db_rp_map_db_is_encoded() {
+        let got = db_and_rp_to_database("my_db", None).unwrap();
+        assert_eq!(got.as_str(), "my%5Fdb");
+
+        let got = db_and_rp_to_database("my_db", Some("my_rp")).unwrap();
+        assert_eq!(got.as_str(), "my%5Fdb_my%5Frp");
+    }
+
+    #[test]
+    fn test_empty_db() {
+        let err = db_and_rp_to_database("", Some("autogen"))
+            .expect_err("should fail with empty db value");
+        assert!(matches!(err, OrgBucketMappingError::DbNotSpecified));
+    }
 }
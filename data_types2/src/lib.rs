@@ -13,20 +13,24 @@
 use influxdb_line_protocol::FieldValue;
 use predicate::{delete_predicate::parse_delete_predicate, Predicate};
 use schema::{builder::SchemaBuilder, InfluxColumnType, InfluxFieldType, Schema};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     convert::TryFrom,
     fmt::{Debug, Formatter},
     ops::{Add, Sub},
     sync::Arc,
+    time::Duration,
 };
 use uuid::Uuid;
 
+pub use mutable_batch::CompiledPartitionTemplate;
+
 pub use data_types::{
     chunk_metadata::{ChunkAddr, ChunkId, ChunkOrder, ChunkSummary},
-    database_rules::{PartitionTemplate, TemplatePart},
+    database_rules::{PartitionTemplate, RegexCapture, StrftimeColumn, TemplatePart},
     delete_predicate::{DeleteExpr, DeletePredicate, Op, Scalar},
-    names::{org_and_bucket_to_database, OrgBucketMappingError},
+    names::{db_and_rp_to_database, org_and_bucket_to_database, OrgBucketMappingError},
     non_empty::NonEmptyString,
     partition_metadata::{InfluxDbType, PartitionAddr, TableSummary},
     sequence::Sequence,
@@ -330,6 +334,12 @@ pub struct QueryPool {
     pub name: String,
 }
 
+/// The default maximum number of tables in a namespace.
+pub const DEFAULT_MAX_TABLES: i32 = 10_000;
+
+/// The default maximum number of columns per table in a namespace.
+pub const DEFAULT_MAX_COLUMNS_PER_TABLE: i32 = 1_000;
+
 /// Data object for a namespace
 #[derive(Debug, Clone, Eq, PartialEq, sqlx::FromRow)]
 pub struct Namespace {
@@ -348,6 +358,208 @@ pub struct Namespace {
     pub max_tables: i32,
     /// The maximum number of columns per table in this namespace
     pub max_columns_per_table: i32,
+    /// When the namespace was soft-deleted, if it was.
+    pub deleted_at: Option<Timestamp>,
+    /// The encoded partition template used to partition writes to this namespace. If not present,
+    /// the router's default template is used.
+    #[sqlx(default)]
+    pub partition_template: Option<String>,
+}
+
+impl Namespace {
+    /// Parse the [`partition_template`](Self::partition_template) of this namespace.
+    ///
+    /// Returns `None` if the namespace uses the default template.
+    pub fn partition_template(
+        &self,
+    ) -> Result<Option<CompiledPartitionTemplate>, PartitionTemplateParseError> {
+        self.partition_template
+            .as_deref()
+            .map(parse_partition_template)
+            .transpose()
+    }
+
+    /// Parse the [`retention_duration`](Self::retention_duration) of this namespace.
+    ///
+    /// Returns `None` if the namespace retains data forever.
+    pub fn retention_period(&self) -> Result<Option<Duration>, RetentionDurationParseError> {
+        match &self.retention_duration {
+            Some(s) => parse_retention_duration(s),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Error returned by [`parse_retention_duration`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionDurationParseError {
+    input: String,
+}
+
+impl std::fmt::Display for RetentionDurationParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid retention duration '{}', expected 'inf' or a duration such as '30d' or '1h30m'",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for RetentionDurationParseError {}
+
+/// Parse a retention duration string as stored in the catalog.
+///
+/// An empty string, `"inf"` and `"0"` denote an infinite retention period and
+/// are returned as `None`. Otherwise the input is a sequence of
+/// `<integer><unit>` pairs, where unit is one of `ns`, `us`/`µs`, `ms`, `s`,
+/// `m`, `h`, `d` or `w` (e.g. `"7d"` or `"1h30m"`).
+pub fn parse_retention_duration(s: &str) -> Result<Option<Duration>, RetentionDurationParseError> {
+    let err = || RetentionDurationParseError {
+        input: s.to_owned(),
+    };
+
+    let trimmed = s.trim();
+    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("inf") || trimmed == "0" {
+        return Ok(None);
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let digits_end = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(err)?;
+        if digits_end == 0 {
+            return Err(err());
+        }
+        let value: u64 = rest[..digits_end].parse().map_err(|_| err())?;
+        rest = &rest[digits_end..];
+
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let nanos_per_unit: u64 = match &rest[..unit_end] {
+            "ns" => 1,
+            "us" | "µs" | "u" | "µ" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60 * 1_000_000_000,
+            "h" => 60 * 60 * 1_000_000_000,
+            "d" => 24 * 60 * 60 * 1_000_000_000,
+            "w" => 7 * 24 * 60 * 60 * 1_000_000_000,
+            _ => return Err(err()),
+        };
+        rest = &rest[unit_end..];
+
+        let nanos = value.checked_mul(nanos_per_unit).ok_or_else(err)?;
+        total = total
+            .checked_add(Duration::from_nanos(nanos))
+            .ok_or_else(err)?;
+    }
+
+    if total.is_zero() {
+        Ok(None)
+    } else {
+        Ok(Some(total))
+    }
+}
+
+/// Error returned by [`parse_partition_template`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionTemplateParseError {
+    input: String,
+    reason: String,
+}
+
+impl std::fmt::Display for PartitionTemplateParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid partition template '{}': {}",
+            self.input, self.reason
+        )
+    }
+}
+
+impl std::error::Error for PartitionTemplateParseError {}
+
+/// The catalog representation of a [`TemplatePart`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TemplatePartRepr {
+    Table,
+    Column(String),
+    TimeFormat(String),
+    RegexCapture { column: String, regex: String },
+    StrftimeColumn { column: String, format: String },
+}
+
+impl From<&TemplatePart> for TemplatePartRepr {
+    fn from(part: &TemplatePart) -> Self {
+        match part {
+            TemplatePart::Table => Self::Table,
+            TemplatePart::Column(name) => Self::Column(name.clone()),
+            TemplatePart::TimeFormat(format) => Self::TimeFormat(format.clone()),
+            TemplatePart::RegexCapture(c) => Self::RegexCapture {
+                column: c.column.clone(),
+                regex: c.regex.clone(),
+            },
+            TemplatePart::StrftimeColumn(c) => Self::StrftimeColumn {
+                column: c.column.clone(),
+                format: c.format.clone(),
+            },
+        }
+    }
+}
+
+impl From<TemplatePartRepr> for TemplatePart {
+    fn from(part: TemplatePartRepr) -> Self {
+        match part {
+            TemplatePartRepr::Table => Self::Table,
+            TemplatePartRepr::Column(name) => Self::Column(name),
+            TemplatePartRepr::TimeFormat(format) => Self::TimeFormat(format),
+            TemplatePartRepr::RegexCapture { column, regex } => {
+                Self::RegexCapture(RegexCapture { column, regex })
+            }
+            TemplatePartRepr::StrftimeColumn { column, format } => {
+                Self::StrftimeColumn(StrftimeColumn { column, format })
+            }
+        }
+    }
+}
+
+/// Encode a partition template for storage in the catalog.
+///
+/// The template is stored as a JSON array of its parts, for example
+/// `["table",{"column":"region"},{"time_format":"%Y-%m-%d"}]`.
+pub fn encode_partition_template(template: &PartitionTemplate) -> String {
+    let parts: Vec<_> = template.parts.iter().map(TemplatePartRepr::from).collect();
+    serde_json::to_string(&parts).expect("template parts are serialisable")
+}
+
+/// Parse and compile a partition template as stored in the catalog by
+/// [`encode_partition_template`].
+///
+/// A template must have at least one part, the regex of every
+/// [`TemplatePart::RegexCapture`] must be valid, and so must be the strftime
+/// format of every [`TemplatePart::TimeFormat`] and
+/// [`TemplatePart::StrftimeColumn`].
+pub fn parse_partition_template(
+    s: &str,
+) -> Result<CompiledPartitionTemplate, PartitionTemplateParseError> {
+    let err = |reason: String| PartitionTemplateParseError {
+        input: s.to_owned(),
+        reason,
+    };
+
+    let parts: Vec<TemplatePartRepr> = serde_json::from_str(s).map_err(|e| err(e.to_string()))?;
+    if parts.is_empty() {
+        return Err(err("template has no parts".to_string()));
+    }
+
+    CompiledPartitionTemplate::try_new(PartitionTemplate {
+        parts: parts.into_iter().map(TemplatePart::from).collect(),
+    })
+    .map_err(|e| err(e.to_string()))
 }
 
 /// Schema collection for a namespace. This is an in-memory object useful for a schema
@@ -362,18 +574,39 @@ pub struct NamespaceSchema {
     pub query_pool_id: QueryPoolId,
     /// the tables in the namespace by name
     pub tables: BTreeMap<String, TableSchema>,
+    /// the retention period of the namespace, `None` if data is retained forever
+    pub retention_period: Option<Duration>,
+    /// the partition template of the namespace, `None` if the default template is used
+    pub partition_template: Option<CompiledPartitionTemplate>,
+    /// the maximum number of tables that can exist in this namespace
+    pub max_tables: i32,
+    /// the maximum number of columns per table in this namespace
+    pub max_columns_per_table: i32,
 }
 
 impl NamespaceSchema {
-    /// Create a new `NamespaceSchema`
+    /// Create a new `NamespaceSchema` with the default table and column limits
     pub fn new(id: NamespaceId, kafka_topic_id: KafkaTopicId, query_pool_id: QueryPoolId) -> Self {
         Self {
             id,
             tables: BTreeMap::new(),
             kafka_topic_id,
             query_pool_id,
+            retention_period: None,
+            partition_template: None,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
         }
     }
+
+    /// Return the partition template for writes to `table_name`, if one is configured for the
+    /// table or the namespace.
+    pub fn partition_template_for(&self, table_name: &str) -> Option<&CompiledPartitionTemplate> {
+        self.tables
+            .get(table_name)
+            .and_then(|t| t.partition_template.as_ref())
+            .or(self.partition_template.as_ref())
+    }
 }
 
 /// Data object for a table
@@ -385,6 +618,26 @@ pub struct Table {
     pub namespace_id: NamespaceId,
     /// The name of the table, which is unique within the associated namespace
     pub name: String,
+    /// When the table was soft-deleted, if it was.
+    pub deleted_at: Option<Timestamp>,
+    /// The encoded partition template used to partition writes to this table. If not present,
+    /// the template of the namespace is used.
+    #[sqlx(default)]
+    pub partition_template: Option<String>,
+}
+
+impl Table {
+    /// Parse the [`partition_template`](Self::partition_template) of this table.
+    ///
+    /// Returns `None` if the table uses the template of its namespace.
+    pub fn partition_template(
+        &self,
+    ) -> Result<Option<CompiledPartitionTemplate>, PartitionTemplateParseError> {
+        self.partition_template
+            .as_deref()
+            .map(parse_partition_template)
+            .transpose()
+    }
 }
 
 /// Column definitions for a table
@@ -394,6 +647,8 @@ pub struct TableSchema {
     pub id: TableId,
     /// the table's columns by their name
     pub columns: BTreeMap<String, ColumnSchema>,
+    /// the partition template of the table, `None` if the template of the namespace is used
+    pub partition_template: Option<CompiledPartitionTemplate>,
 }
 
 impl TableSchema {
@@ -402,6 +657,7 @@ impl TableSchema {
         Self {
             id,
             columns: BTreeMap::new(),
+            partition_template: None,
         }
     }
 
@@ -804,3 +1060,153 @@ impl IngesterQueryRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retention_duration_infinite() {
+        for input in ["", "inf", "INF", "0", "0s"] {
+            assert_eq!(parse_retention_duration(input).unwrap(), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_retention_duration() {
+        assert_eq!(
+            parse_retention_duration("30d").unwrap(),
+            Some(Duration::from_secs(30 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            parse_retention_duration("1h30m").unwrap(),
+            Some(Duration::from_secs(90 * 60))
+        );
+        assert_eq!(
+            parse_retention_duration("2w").unwrap(),
+            Some(Duration::from_secs(14 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            parse_retention_duration("1s500ms").unwrap(),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            parse_retention_duration("10µs").unwrap(),
+            Some(Duration::from_micros(10))
+        );
+        assert_eq!(
+            parse_retention_duration("10ns").unwrap(),
+            Some(Duration::from_nanos(10))
+        );
+    }
+
+    #[test]
+    fn test_parse_retention_duration_invalid() {
+        for input in [
+            "d",
+            "10",
+            "10x",
+            "-1d",
+            "1.5h",
+            "h1",
+            "99999999999999999999w",
+        ] {
+            let err = parse_retention_duration(input).unwrap_err();
+            assert!(err.to_string().contains(input), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_partition_template_roundtrip() {
+        let template = PartitionTemplate {
+            parts: vec![
+                TemplatePart::Table,
+                TemplatePart::Column("region".to_string()),
+                TemplatePart::TimeFormat("%Y-%m-%d %H:00".to_string()),
+                TemplatePart::RegexCapture(RegexCapture {
+                    column: "url".to_string(),
+                    regex: "^https?://([^/]+)".to_string(),
+                }),
+                TemplatePart::StrftimeColumn(StrftimeColumn {
+                    column: "other_time".to_string(),
+                    format: "%Y".to_string(),
+                }),
+            ],
+        };
+
+        let encoded = encode_partition_template(&template);
+        assert_eq!(
+            parse_partition_template(&encoded).unwrap().template(),
+            &template
+        );
+    }
+
+    #[test]
+    fn test_parse_partition_template() {
+        let template = parse_partition_template(r#"["table",{"time_format":"%Y"}]"#).unwrap();
+        assert_eq!(
+            template.template().parts,
+            vec![
+                TemplatePart::Table,
+                TemplatePart::TimeFormat("%Y".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_partition_template_invalid() {
+        for input in [
+            "",
+            "[]",
+            "{}",
+            r#"["bananas"]"#,
+            r#"[{"column":42}]"#,
+            r#"[{"regex_capture":{"column":"host","regex":"("}}]"#,
+            r#"[{"time_format":"%Y-%Q"}]"#,
+            r#"[{"strftime_column":{"column":"ts","format":"%Q"}}]"#,
+        ] {
+            let err = parse_partition_template(input).unwrap_err();
+            assert!(err.to_string().contains(input), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_namespace_schema_partition_template_for() {
+        let ns_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
+            parts: vec![TemplatePart::TimeFormat("%Y".to_string())],
+        })
+        .unwrap();
+        let table_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
+            parts: vec![TemplatePart::TimeFormat("%Y-%m-%d %H".to_string())],
+        })
+        .unwrap();
+
+        let mut schema = NamespaceSchema::new(
+            NamespaceId::new(1),
+            KafkaTopicId::new(2),
+            QueryPoolId::new(3),
+        );
+        assert_eq!(schema.partition_template_for("bananas"), None);
+
+        schema.partition_template = Some(ns_template.clone());
+        let mut table = TableSchema::new(TableId::new(1));
+        table.partition_template = Some(table_template.clone());
+        schema.tables.insert("bananas".to_string(), table);
+        schema
+            .tables
+            .insert("platanos".to_string(), TableSchema::new(TableId::new(2)));
+
+        assert_eq!(
+            schema.partition_template_for("bananas"),
+            Some(&table_template)
+        );
+        assert_eq!(
+            schema.partition_template_for("platanos"),
+            Some(&ns_template)
+        );
+        assert_eq!(
+            schema.partition_template_for("new_table"),
+            Some(&ns_template)
+        );
+    }
+}
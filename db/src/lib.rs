@@ -32,7 +32,7 @@ use dml::{DmlDelete, DmlMeta, DmlOperation, DmlWrite};
 use internal_types::mailbox::Mailbox;
 use iox_object_store::IoxObjectStore;
 use job_registry::JobRegistry;
-use mutable_batch::payload::PartitionWrite;
+use mutable_batch::payload::{CompiledPartitionTemplate, PartitionWrite};
 use mutable_buffer::{ChunkMetrics as MutableBufferChunkMetrics, MBChunk};
 use observability_deps::tracing::{debug, error, info, warn};
 use parking_lot::{Mutex, RwLock};
@@ -149,6 +149,9 @@ pub enum DmlError {
         errors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
     ))]
     SchemaErrors { errors: Vec<schema::merge::Error> },
+
+    #[snafu(display("Invalid partition template: {}", source))]
+    InvalidPartitionTemplate { source: mutable_batch::Error },
 }
 
 /// `Db` is an instance-local, queryable, possibly persisted, and possibly mutable data store
@@ -1090,6 +1093,9 @@ impl Db {
         let mub_row_threshold = rules.lifecycle_rules.mub_row_threshold;
         std::mem::drop(rules);
 
+        let partition_template = CompiledPartitionTemplate::try_new(partition_template)
+            .context(InvalidPartitionTemplateSnafu)?;
+
         // We may have gotten here through `store_entry`, in which case this is checking the
         // configuration again unnecessarily, but we may have come here by consuming records from
         // the write buffer, so this check is necessary in that case.
@@ -191,6 +191,9 @@ pub struct DmlWrite {
     tables: HashMap<String, MutableBatch>,
     /// Write metadata
     meta: DmlMeta,
+    /// The partition key all rows in this write were assigned to, if the
+    /// write was partitioned before being enqueued
+    partition_key: Option<String>,
     min_timestamp: i64,
     max_timestamp: i64,
 }
@@ -232,11 +235,25 @@ impl DmlWrite {
             namespace: namespace.into(),
             tables,
             meta,
+            partition_key: None,
             min_timestamp: stats.min.unwrap(),
             max_timestamp: stats.max.unwrap(),
         }
     }
 
+    /// Set the partition key all rows in this write belong to
+    pub fn with_partition_key(self, partition_key: impl Into<String>) -> Self {
+        Self {
+            partition_key: Some(partition_key.into()),
+            ..self
+        }
+    }
+
+    /// The partition key of this write, if it was partitioned
+    pub fn partition_key(&self) -> Option<&str> {
+        self.partition_key.as_deref()
+    }
+
     /// Namespace associated with this write
     pub fn namespace(&self) -> &str {
         &self.namespace
@@ -300,10 +317,9 @@ impl DmlWrite {
         batches
             .into_iter()
             .map(|(shard_id, tables)| {
-                (
-                    shard_id,
-                    Self::new(&self.namespace, tables, self.meta.clone()),
-                )
+                let mut write = Self::new(&self.namespace, tables, self.meta.clone());
+                write.partition_key = self.partition_key.clone();
+                (shard_id, write)
             })
             .collect()
     }
@@ -320,6 +336,11 @@ impl DmlWrite {
                 .sum::<usize>()
             + self.meta.size()
             - std::mem::size_of::<DmlMeta>()
+            + self
+                .partition_key
+                .as_ref()
+                .map(|k| k.capacity())
+                .unwrap_or(0)
     }
 }
 
@@ -461,6 +482,7 @@ pub mod test_util {
     pub fn assert_writes_eq(a: &DmlWrite, b: &DmlWrite) {
         assert_eq!(a.namespace, b.namespace);
         assert_eq!(a.meta(), b.meta());
+        assert_eq!(a.partition_key(), b.partition_key());
 
         assert_eq!(a.table_count(), b.table_count());
 
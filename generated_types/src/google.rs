@@ -118,7 +118,7 @@ pub mod longrunning {
 use self::protobuf::Any;
 use observability_deps::tracing::error;
 use prost::{bytes::BytesMut, Message};
-use std::convert::TryInto;
+use std::{convert::TryInto, time::Duration};
 
 // A newtype struct to provide conversion into tonic::Status
 #[derive(Debug)]
@@ -647,6 +647,59 @@ where
     }
 }
 
+/// IOx returns [`ResourceExhausted`] when a request is rejected because a
+/// resource limit (such as a rate limit) was reached, optionally telling the
+/// client how long to wait before retrying.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceExhausted {
+    pub description: String,
+    pub retry_after: Option<Duration>,
+}
+
+fn encode_retry_info(retry_after: Duration) -> Result<Any, EncodeError> {
+    let mut buffer = BytesMut::new();
+
+    rpc::RetryInfo {
+        retry_delay: Some(protobuf::Duration {
+            seconds: retry_after.as_secs() as i64,
+            nanos: retry_after.subsec_nanos() as i32,
+        }),
+    }
+    .encode(&mut buffer)?;
+
+    Ok(Any {
+        type_url: "type.googleapis.com/google.rpc.RetryInfo".to_string(),
+        value: buffer.freeze(),
+    })
+}
+
+impl From<ResourceExhausted> for tonic::Status {
+    fn from(exhausted: ResourceExhausted) -> Self {
+        let retry_after = match exhausted.retry_after {
+            Some(v) => v,
+            None => return tonic::Status::resource_exhausted(exhausted.description),
+        };
+
+        match encode_retry_info(retry_after) {
+            Ok(details) => encode_status(
+                tonic::Code::ResourceExhausted,
+                exhausted.description,
+                details,
+            ),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+/// Returns an iterator over the retry delays in the provided [`tonic::Status`]
+pub fn decode_retry_info(status: &tonic::Status) -> impl Iterator<Item = Duration> {
+    get_details(status)
+        .filter(|details| details.type_url == "type.googleapis.com/google.rpc.RetryInfo")
+        .flat_map(|details| rpc::RetryInfo::decode(details.value).ok())
+        .flat_map(|info| info.retry_delay)
+        .map(|d| Duration::new(d.seconds.max(0) as u64, d.nanos.max(0) as u32))
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct QuotaFailure {
     pub subject: String,
@@ -813,6 +866,15 @@ mod tests {
         let status = tonic::Status::from(precondition.clone());
         let collected: Vec<_> = decode_precondition_violation(&status).collect();
         assert_eq!(collected, vec![precondition]);
+
+        let exhausted = ResourceExhausted {
+            description: "slow down".to_string(),
+            retry_after: Some(Duration::from_millis(1500)),
+        };
+        let status = tonic::Status::from(exhausted);
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        let collected: Vec<_> = decode_retry_info(&status).collect();
+        assert_eq!(collected, vec![Duration::from_millis(1500)]);
     }
 
     #[test]
@@ -81,6 +81,16 @@ pub mod influxdata {
             }
         }
 
+        pub mod namespace {
+            pub mod v1 {
+                include!(concat!(env!("OUT_DIR"), "/influxdata.iox.namespace.v1.rs"));
+                include!(concat!(
+                    env!("OUT_DIR"),
+                    "/influxdata.iox.namespace.v1.serde.rs"
+                ));
+            }
+        }
+
         pub mod predicate {
             pub mod v1 {
                 include!(concat!(env!("OUT_DIR"), "/influxdata.iox.predicate.v1.rs"));
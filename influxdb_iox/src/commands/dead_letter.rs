@@ -0,0 +1,167 @@
+//! This module implements the `dead-letter` CLI command
+
+use std::sync::Arc;
+
+use clap_blocks::{
+    object_store::{make_object_store, ObjectStoreConfig},
+    write_buffer::WriteBufferConfig,
+};
+use ingester::dead_letter::{DeadLetter, DeadLetterSink};
+use object_store::DynObjectStore;
+use thiserror::Error;
+use write_buffer::core::WriteBufferError;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Cannot parse object store config: {0}")]
+    ObjectStoreParsing(#[from] clap_blocks::object_store::ParseError),
+
+    #[error("Dead letter error: {0}")]
+    DeadLetter(#[from] ingester::dead_letter::Error),
+
+    #[error("Write buffer error: {0}")]
+    WriteBuffer(#[from] WriteBufferError),
+
+    #[error("{0} dead letter(s) could not be decoded and were not re-injected")]
+    Undecodable(usize),
+}
+
+/// Inspect and re-inject write buffer operations skipped by the ingester
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// Where the dead letters are stored
+#[derive(Debug, clap::Parser)]
+struct Location {
+    #[clap(flatten)]
+    object_store_config: ObjectStoreConfig,
+
+    /// Object store prefix the ingester records skipped operations under.
+    #[clap(long = "--dead-letter-prefix", env = "INFLUXDB_IOX_DEAD_LETTER_PREFIX")]
+    dead_letter_prefix: String,
+}
+
+impl Location {
+    fn sink(&self) -> Result<DeadLetterSink, Error> {
+        let metrics = metric::Registry::new();
+        let object_store: Arc<DynObjectStore> =
+            Arc::new(make_object_store(&self.object_store_config, &metrics)?);
+        Ok(DeadLetterSink::new(
+            object_store,
+            self.dead_letter_prefix.clone(),
+        ))
+    }
+}
+
+/// List the recorded dead letters
+#[derive(Debug, clap::Parser)]
+struct List {
+    #[clap(flatten)]
+    location: Location,
+}
+
+/// Write recorded dead letters back to the write buffer they were read from
+#[derive(Debug, clap::Parser)]
+struct Reinject {
+    #[clap(flatten)]
+    location: Location,
+
+    #[clap(flatten)]
+    write_buffer_config: WriteBufferConfig,
+
+    /// Only re-inject the dead letters of this kafka partition.
+    #[clap(long = "--kafka-partition")]
+    kafka_partition: Option<i32>,
+
+    /// Only re-inject the dead letter with this sequence number.
+    #[clap(long = "--sequence-number")]
+    sequence_number: Option<u64>,
+
+    /// Keep dead letters after re-injecting them, instead of deleting them.
+    #[clap(long = "--keep")]
+    keep: bool,
+}
+
+/// All possible subcommands for dead-letter
+#[derive(Debug, clap::Parser)]
+enum Command {
+    List(List),
+    Reinject(Reinject),
+}
+
+pub async fn command(config: Config) -> Result<(), Error> {
+    match config.command {
+        Command::List(list) => {
+            let sink = list.location.sink()?;
+            for letter in sink.list().await? {
+                println!("{}", describe(&letter));
+            }
+            Ok(())
+        }
+        Command::Reinject(reinject) => {
+            let sink = reinject.location.sink()?;
+            let writer = reinject
+                .write_buffer_config
+                .writing(Arc::new(metric::Registry::new()), None)
+                .await?;
+
+            let letters = sink.list().await?.into_iter().filter(|letter| {
+                letter.kafka_topic == reinject.write_buffer_config.topic()
+                    && reinject
+                        .kafka_partition
+                        .map_or(true, |p| p == letter.kafka_partition)
+                    && reinject
+                        .sequence_number
+                        .map_or(true, |n| n == letter.sequence_number)
+            });
+
+            let mut undecodable = 0;
+            for letter in letters {
+                let op = match letter.operation() {
+                    Ok(op) => op,
+                    Err(e) => {
+                        eprintln!("{}: still cannot be decoded: {}", describe(&letter), e);
+                        undecodable += 1;
+                        continue;
+                    }
+                };
+
+                let meta = writer
+                    .store_operation(letter.kafka_partition as u32, &op)
+                    .await?;
+                println!(
+                    "{}: re-injected as sequence number {}",
+                    describe(&letter),
+                    meta.sequence()
+                        .map(|s| s.number.to_string())
+                        .unwrap_or_default(),
+                );
+
+                if !reinject.keep {
+                    sink.remove(&letter).await?;
+                }
+            }
+
+            match undecodable {
+                0 => Ok(()),
+                n => Err(Error::Undecodable(n)),
+            }
+        }
+    }
+}
+
+/// A one-line summary of a dead letter.
+fn describe(letter: &DeadLetter) -> String {
+    format!(
+        "{}/{}/{} ({}, {} bytes): {}",
+        letter.kafka_topic,
+        letter.kafka_partition,
+        letter.sequence_number,
+        letter.reason.as_str(),
+        letter.payload.len(),
+        letter.error,
+    )
+}
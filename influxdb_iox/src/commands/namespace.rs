@@ -0,0 +1,149 @@
+//! This module implements the `namespace` CLI command
+
+use generated_types::influxdata::iox::management::v1::PartitionTemplate;
+use influxdb_iox_client::{
+    connection::Connection,
+    namespace::{self, generated_types::*},
+};
+use thiserror::Error;
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("JSON Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Client error: {0}")]
+    ClientError(#[from] influxdb_iox_client::error::Error),
+}
+
+/// Various commands for namespace management
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// Create a new namespace
+#[derive(Debug, clap::Parser)]
+struct Create {
+    /// The name of the namespace
+    name: String,
+
+    /// Retention period of the namespace, e.g. "30d" or "inf"
+    #[clap(long)]
+    retention: Option<String>,
+
+    /// The name of the kafka topic writes to the namespace land in
+    #[clap(long, default_value = "iox-shared")]
+    kafka_topic: String,
+
+    /// The name of the query pool that answers queries for the namespace
+    #[clap(long, default_value = "iox-shared")]
+    query_pool: String,
+
+    /// The maximum number of tables in the namespace
+    #[clap(long)]
+    max_tables: Option<i32>,
+
+    /// The maximum number of columns per table in the namespace
+    #[clap(long)]
+    max_columns_per_table: Option<i32>,
+}
+
+/// Describe a namespace
+#[derive(Debug, clap::Parser)]
+struct Get {
+    /// The name of the namespace
+    name: String,
+}
+
+/// Update the configuration of a namespace
+#[derive(Debug, clap::Parser)]
+struct Update {
+    /// The name of the namespace
+    name: String,
+
+    /// Retention period of the namespace, e.g. "30d" or "inf"
+    #[clap(long)]
+    retention: Option<String>,
+
+    /// The name of the query pool to assign the namespace to
+    #[clap(long)]
+    query_pool: Option<String>,
+
+    /// The maximum number of tables in the namespace
+    #[clap(long)]
+    max_tables: Option<i32>,
+
+    /// The maximum number of columns per table in the namespace
+    #[clap(long)]
+    max_columns_per_table: Option<i32>,
+
+    /// The partition template of the namespace as JSON, e.g.
+    /// '{"parts": [{"table": {}}, {"time": "%Y-%m-%d"}]}'. A template without
+    /// parts ('{"parts": []}') resets the namespace to the default template.
+    #[clap(long, parse(try_from_str = serde_json::from_str))]
+    partition_template: Option<PartitionTemplate>,
+}
+
+/// All possible subcommands for namespace
+#[derive(Debug, clap::Parser)]
+enum Command {
+    /// Create a new namespace
+    Create(Create),
+
+    /// List all namespaces
+    List,
+
+    /// Describe a namespace
+    Get(Get),
+
+    /// Update the configuration of a namespace
+    Update(Update),
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<(), Error> {
+    let mut client = namespace::Client::new(connection);
+
+    match config.command {
+        Command::Create(command) => {
+            let namespace = client
+                .create_namespace(CreateNamespaceRequest {
+                    name: command.name,
+                    retention_duration: command.retention,
+                    kafka_topic: command.kafka_topic,
+                    query_pool: command.query_pool,
+                    max_tables: command.max_tables,
+                    max_columns_per_table: command.max_columns_per_table,
+                })
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&namespace)?);
+        }
+        Command::List => {
+            let namespaces = client.get_namespaces().await?;
+            println!("{}", serde_json::to_string_pretty(&namespaces)?);
+        }
+        Command::Get(command) => {
+            let namespace = client.get_namespace(&command.name).await?;
+            println!("{}", serde_json::to_string_pretty(&namespace)?);
+        }
+        Command::Update(command) => {
+            let namespace = client
+                .update_namespace(UpdateNamespaceRequest {
+                    name: command.name,
+                    retention_duration: command.retention,
+                    kafka_topic: None,
+                    query_pool: command.query_pool,
+                    max_tables: command.max_tables,
+                    max_columns_per_table: command.max_columns_per_table,
+                    partition_template: command.partition_template,
+                })
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&namespace)?);
+        } // Deliberately not adding _ => so the compiler will direct people here to impl new
+          // commands
+    }
+
+    Ok(())
+}
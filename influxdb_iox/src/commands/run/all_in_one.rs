@@ -5,6 +5,7 @@ use clap_blocks::compactor::CompactorConfig;
 use clap_blocks::{
     catalog_dsn::CatalogDsnConfig,
     ingester::IngesterConfig,
+    object_store::make_object_store,
     run_config::{RunConfig, DEFAULT_API_BIND_ADDR, DEFAULT_GRPC_BIND_ADDR},
     socket_addr::SocketAddr,
     write_buffer::WriteBufferConfig,
@@ -20,7 +21,7 @@ use influxdb_ioxd::{
     },
     Service,
 };
-use object_store::{DynObjectStore, ObjectStoreImpl};
+use object_store::DynObjectStore;
 use observability_deps::tracing::*;
 use query::exec::Executor;
 use thiserror::Error;
@@ -315,10 +316,10 @@ pub async fn command(config: Config) -> Result<()> {
         .create_or_get(query_pool_name)
         .await?;
 
-    let object_store: Arc<DynObjectStore> = Arc::new(
-        ObjectStoreImpl::try_from(router_run_config.object_store_config())
-            .map_err(Error::ObjectStoreParsing)?,
-    );
+    let object_store = make_object_store(router_run_config.object_store_config(), &metrics)
+        .map_err(Error::ObjectStoreParsing)?;
+    let object_store_cache = object_store.cache().clone();
+    let object_store: Arc<DynObjectStore> = Arc::new(object_store);
 
     let time_provider: Arc<dyn TimeProvider> = Arc::new(SystemProvider::new());
 
@@ -338,6 +339,12 @@ pub async fn command(config: Config) -> Result<()> {
         Arc::clone(&catalog),
         &write_buffer_config,
         query_pool_name,
+        iox_catalog::INFINITE_RETENTION_POLICY,
+        None,
+        true,
+        Default::default(),
+        None,
+        &Default::default(),
     )
     .await?;
 
@@ -362,17 +369,21 @@ pub async fn command(config: Config) -> Result<()> {
         Arc::clone(&exec),
         Arc::clone(&time_provider),
         compactor_config,
+        None,
     )
     .await?;
 
-    info!("starting querier");
+    let ingester_addresses = vec![format!("http://{}", ingester_run_config.grpc_bind_address)];
+    info!(?ingester_addresses, "starting querier");
     let querier = create_querier_server_type(
         &common_state,
         metrics,
         catalog,
         object_store,
+        object_store_cache,
         time_provider,
         exec,
+        ingester_addresses,
     )
     .await;
 
@@ -1,6 +1,6 @@
 //! Implementation of command line option for running the compactor
 
-use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore, ObjectStoreImpl};
+use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore};
 use observability_deps::tracing::*;
 use query::exec::Executor;
 use std::sync::Arc;
@@ -8,7 +8,9 @@ use thiserror::Error;
 use time::SystemProvider;
 
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, compactor::CompactorConfig, run_config::RunConfig,
+    catalog_dsn::CatalogDsnConfig, compactor::CompactorConfig,
+    garbage_collector::GarbageCollectorConfig, object_store::make_object_store,
+    run_config::RunConfig,
 };
 use influxdb_ioxd::{
     self,
@@ -65,6 +67,15 @@ pub struct Config {
     #[clap(flatten)]
     pub(crate) compactor_config: CompactorConfig,
 
+    /// Also run the parquet file garbage collector in this compactor.
+    ///
+    /// The garbage collector can alternatively be run with `influxdb_iox run garbage-collector`.
+    #[clap(long = "--gc-enable", env = "INFLUXDB_IOX_GC_ENABLE")]
+    pub(crate) gc_enable: bool,
+
+    #[clap(flatten)]
+    pub(crate) gc_config: GarbageCollectorConfig,
+
     /// Number of threads to use for the compactor query execution, compaction and persistence.
     #[clap(
         long = "--query-exec-thread-count",
@@ -83,7 +94,7 @@ pub async fn command(config: Config) -> Result<(), Error> {
         .get_catalog("compactor", Arc::clone(&metric_registry))
         .await?;
 
-    let object_store = ObjectStoreImpl::try_from(config.run_config.object_store_config())
+    let object_store = make_object_store(config.run_config.object_store_config(), &metric_registry)
         .map_err(Error::ObjectStoreParsing)?;
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> =
@@ -100,6 +111,7 @@ pub async fn command(config: Config) -> Result<(), Error> {
         exec,
         time_provider,
         config.compactor_config,
+        config.gc_enable.then(|| config.gc_config),
     )
     .await?;
 
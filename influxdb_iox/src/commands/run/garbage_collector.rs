@@ -0,0 +1,101 @@
+//! Implementation of command line option for running the parquet file garbage collector
+
+use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore};
+use observability_deps::tracing::*;
+use std::sync::Arc;
+use thiserror::Error;
+use time::SystemProvider;
+
+use clap_blocks::{
+    catalog_dsn::CatalogDsnConfig, garbage_collector::GarbageCollectorConfig,
+    object_store::make_object_store, run_config::RunConfig,
+};
+use influxdb_ioxd::{
+    self,
+    server_type::{
+        common_state::{CommonServerState, CommonServerStateError},
+        garbage_collector::create_garbage_collector_server_type,
+    },
+    Service,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Run: {0}")]
+    Run(#[from] influxdb_ioxd::Error),
+
+    #[error("Invalid config: {0}")]
+    InvalidConfig(#[from] CommonServerStateError),
+
+    #[error("Catalog error: {0}")]
+    Catalog(#[from] iox_catalog::interface::Error),
+
+    #[error("Catalog DSN error: {0}")]
+    CatalogDsn(#[from] clap_blocks::catalog_dsn::Error),
+
+    #[error("Cannot parse object store config: {0}")]
+    ObjectStoreParsing(#[from] clap_blocks::object_store::ParseError),
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(
+    name = "run",
+    about = "Runs in garbage collector mode",
+    long_about = "Run the IOx parquet file garbage collector.\n\nThe garbage collector removes \
+    parquet files that were flagged for deletion (e.g. by the compactor) as well as parquet files \
+    in object storage that are not referenced by the catalog.\n\nThe configuration options below \
+    can be set either with the command line flags or with the specified environment \
+    variable. If there is a file named '.env' in the current working directory, \
+    it is sourced before loading the configuration.
+
+Configuration is loaded from the following sources (highest precedence first):
+        - command line arguments
+        - user set environment variables
+        - .env file contents
+        - pre-configured default values"
+)]
+pub struct Config {
+    #[clap(flatten)]
+    pub(crate) run_config: RunConfig,
+
+    #[clap(flatten)]
+    pub(crate) catalog_dsn: CatalogDsnConfig,
+
+    #[clap(flatten)]
+    pub(crate) gc_config: GarbageCollectorConfig,
+}
+
+pub async fn command(config: Config) -> Result<(), Error> {
+    let common_state = CommonServerState::from_config(config.run_config.clone())?;
+
+    let metric_registry: Arc<metric::Registry> = Default::default();
+    let catalog = config
+        .catalog_dsn
+        .get_catalog("garbage_collector", Arc::clone(&metric_registry))
+        .await?;
+
+    let object_store = make_object_store(config.run_config.object_store_config(), &metric_registry)
+        .map_err(Error::ObjectStoreParsing)?;
+    // Decorate the object store with a metric recorder.
+    let object_store: Arc<DynObjectStore> =
+        Arc::new(ObjectStoreMetrics::new(object_store, &*metric_registry));
+
+    let time_provider = Arc::new(SystemProvider::new());
+
+    let server_type = create_garbage_collector_server_type(
+        &common_state,
+        metric_registry,
+        catalog,
+        object_store,
+        time_provider,
+        config.gc_config,
+    );
+
+    info!(
+        dry_run = config.gc_config.dry_run,
+        "starting garbage collector"
+    );
+
+    let services = vec![Service::create(server_type, common_state.run_config())];
+    Ok(influxdb_ioxd::main(common_state, services).await?)
+}
@@ -1,8 +1,8 @@
 //! Implementation of command line option for running ingester
 
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, ingester::IngesterConfig, run_config::RunConfig,
-    write_buffer::WriteBufferConfig,
+    catalog_dsn::CatalogDsnConfig, ingester::IngesterConfig, object_store::make_object_store,
+    run_config::RunConfig, write_buffer::WriteBufferConfig,
 };
 use influxdb_ioxd::{
     self,
@@ -12,10 +12,10 @@ use influxdb_ioxd::{
     },
     Service,
 };
-use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore, ObjectStoreImpl};
+use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore};
 use observability_deps::tracing::*;
 use query::exec::Executor;
-use std::{convert::TryFrom, sync::Arc};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -83,7 +83,7 @@ pub async fn command(config: Config) -> Result<()> {
         .get_catalog("ingester", Arc::clone(&metric_registry))
         .await?;
 
-    let object_store = ObjectStoreImpl::try_from(config.run_config.object_store_config())
+    let object_store = make_object_store(config.run_config.object_store_config(), &metric_registry)
         .map_err(Error::ObjectStoreParsing)?;
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> =
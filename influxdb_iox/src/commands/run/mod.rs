@@ -5,6 +5,7 @@ use clap_blocks::run_config::RunConfig;
 mod all_in_one;
 mod compactor;
 mod database;
+mod garbage_collector;
 mod ingester;
 mod querier;
 mod router;
@@ -20,6 +21,9 @@ pub enum Error {
     #[snafu(display("Error in database subcommand: {}", source))]
     DatabaseError { source: database::Error },
 
+    #[snafu(display("Error in garbage collector subcommand: {}", source))]
+    GarbageCollectorError { source: garbage_collector::Error },
+
     #[snafu(display("Error in querier subcommand: {}", source))]
     QuerierError { source: querier::Error },
 
@@ -58,6 +62,7 @@ impl Config {
             None => &self.database_config.run_config,
             Some(Command::Compactor(config)) => &config.run_config,
             Some(Command::Database(config)) => &config.run_config,
+            Some(Command::GarbageCollector(config)) => &config.run_config,
             Some(Command::Querier(config)) => &config.run_config,
             Some(Command::Router(config)) => &config.run_config,
             Some(Command::Router2(config)) => &config.run_config,
@@ -76,6 +81,9 @@ enum Command {
     /// Run the server in database mode (Deprecated)
     Database(database::Config),
 
+    /// Run the server in parquet file garbage collector mode
+    GarbageCollector(garbage_collector::Config),
+
     /// Run the server in querier mode
     Querier(querier::Config),
 
@@ -109,6 +117,9 @@ pub async fn command(config: Config) -> Result<()> {
             compactor::command(config).await.context(CompactorSnafu)
         }
         Some(Command::Database(config)) => database::command(config).await.context(DatabaseSnafu),
+        Some(Command::GarbageCollector(config)) => garbage_collector::command(config)
+            .await
+            .context(GarbageCollectorSnafu),
         Some(Command::Querier(config)) => querier::command(config).await.context(QuerierSnafu),
         Some(Command::Router(config)) => router::command(config).await.context(RouterSnafu),
         Some(Command::Router2(config)) => router2::command(config).await.context(Router2Snafu),
@@ -1,13 +1,15 @@
 //! Implementation of command line option for running the querier
 
-use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore, ObjectStoreImpl};
+use object_store::{instrumentation::ObjectStoreMetrics, DynObjectStore};
 use observability_deps::tracing::*;
 use query::exec::Executor;
 use std::sync::Arc;
 use thiserror::Error;
 use time::SystemProvider;
 
-use clap_blocks::{catalog_dsn::CatalogDsnConfig, run_config::RunConfig};
+use clap_blocks::{
+    catalog_dsn::CatalogDsnConfig, object_store::make_object_store, run_config::RunConfig,
+};
 use influxdb_ioxd::{
     self,
     server_type::{
@@ -62,6 +64,20 @@ pub struct Config {
     /// If not specified, defaults to the number of cores on the system
     #[clap(long = "--num-query-threads", env = "INFLUXDB_IOX_NUM_QUERY_THREADS")]
     pub num_query_threads: Option<usize>,
+
+    /// gRPC address of an ingester to query for unpersisted data, e.g. `http://127.0.0.1:8083`.
+    ///
+    /// May be specified multiple times (or as a comma-separated list in the environment variable). Every ingester is
+    /// asked for the data of all sequencers of a namespace.
+    ///
+    /// If not specified, the querier only serves persisted data.
+    #[clap(
+        long = "--ingester-address",
+        env = "INFLUXDB_IOX_INGESTER_ADDRESSES",
+        multiple_occurrences = true,
+        use_value_delimiter = true
+    )]
+    pub ingester_addresses: Vec<String>,
 }
 
 pub async fn command(config: Config) -> Result<(), Error> {
@@ -73,8 +89,9 @@ pub async fn command(config: Config) -> Result<(), Error> {
         .get_catalog("querier", Arc::clone(&metric_registry))
         .await?;
 
-    let object_store = ObjectStoreImpl::try_from(config.run_config.object_store_config())
+    let object_store = make_object_store(config.run_config.object_store_config(), &metric_registry)
         .map_err(Error::ObjectStoreParsing)?;
+    let object_store_cache = object_store.cache().clone();
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> =
         Arc::new(ObjectStoreMetrics::new(object_store, &*metric_registry));
@@ -84,14 +101,19 @@ pub async fn command(config: Config) -> Result<(), Error> {
     let num_threads = config.num_query_threads.unwrap_or_else(num_cpus::get);
     info!(%num_threads, "using specified number of threads per thread pool");
 
+    let ingester_addresses = config.ingester_addresses;
+    info!(?ingester_addresses, "using ingester addresses");
+
     let exec = Arc::new(Executor::new(num_threads));
     let server_type = create_querier_server_type(
         &common_state,
         metric_registry,
         catalog,
         object_store,
+        object_store_cache,
         time_provider,
         exec,
+        ingester_addresses,
     )
     .await;
 
@@ -1,9 +1,14 @@
 //! Implementation of command line option for running router2
 
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, run_config::RunConfig, write_buffer::WriteBufferConfig,
+    catalog_dsn::CatalogDsnConfig, rate_limit::RateLimitConfig, run_config::RunConfig,
+    write_buffer::WriteBufferConfig,
 };
 use influxdb_ioxd::{
     self,
@@ -14,6 +19,7 @@ use influxdb_ioxd::{
     Service,
 };
 use observability_deps::tracing::*;
+use router2::{dml_handlers::RetryConfig, sharder::ShardConfig};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -29,6 +35,18 @@ pub enum Error {
 
     #[error("Catalog DSN error: {0}")]
     CatalogDsn(#[from] clap_blocks::catalog_dsn::Error),
+
+    #[error("Cannot read shard config file {path:?}: {source}")]
+    ReadShardConfig {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot parse shard config file {path:?}: {source}")]
+    ParseShardConfig {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -58,6 +76,9 @@ pub struct Config {
     #[clap(flatten)]
     pub(crate) write_buffer_config: WriteBufferConfig,
 
+    #[clap(flatten)]
+    pub(crate) rate_limit_config: RateLimitConfig,
+
     /// Query pool name to dispatch writes to.
     #[clap(
         long = "--query-pool",
@@ -65,10 +86,96 @@ pub struct Config {
         default_value = "iox-shared"
     )]
     pub(crate) query_pool_name: String,
+
+    /// Retention period of namespaces that are implicitly created by a write.
+    ///
+    /// Either "inf" (retain data forever) or a duration such as "30d" or
+    /// "1h30m". Writes containing data older than the retention period are
+    /// rejected.
+    #[clap(
+        long = "--new-namespace-retention",
+        env = "INFLUXDB_IOX_NEW_NAMESPACE_RETENTION",
+        default_value = "inf"
+    )]
+    pub(crate) new_namespace_retention: String,
+
+    /// Accept the valid lines of a line protocol write request that contains
+    /// invalid lines, rejecting only the invalid lines.
+    ///
+    /// The response to a partial write lists the line numbers and errors of
+    /// the rejected lines.
+    #[clap(long = "--partial-writes", env = "INFLUXDB_IOX_PARTIAL_WRITES")]
+    pub(crate) partial_writes: bool,
+
+    /// The maximum number of rejected lines listed in the response to a
+    /// partial write.
+    #[clap(
+        long = "--partial-write-max-errors",
+        env = "INFLUXDB_IOX_PARTIAL_WRITE_MAX_ERRORS",
+        default_value = "100"
+    )]
+    pub(crate) partial_write_max_errors: usize,
+
+    /// Reject writes to namespaces that do not exist, rather than implicitly
+    /// creating them.
+    ///
+    /// Namespaces must then be created explicitly with the namespace gRPC
+    /// service (see the `namespace` CLI command).
+    #[clap(
+        long = "--disable-namespace-autocreation",
+        env = "INFLUXDB_IOX_DISABLE_NAMESPACE_AUTOCREATION"
+    )]
+    pub(crate) disable_namespace_autocreation: bool,
+
+    /// The maximum number of times a write buffer enqueue failing with a
+    /// transient error is retried.
+    ///
+    /// Retries are made independently for each shard of a write.
+    #[clap(
+        long = "--write-buffer-max-retries",
+        env = "INFLUXDB_IOX_WRITE_BUFFER_MAX_RETRIES",
+        default_value = "3"
+    )]
+    pub(crate) write_buffer_max_retries: usize,
+
+    /// The time after the start of a write request beyond which failed write
+    /// buffer enqueues are no longer retried.
+    #[clap(
+        long = "--write-buffer-retry-deadline",
+        env = "INFLUXDB_IOX_WRITE_BUFFER_RETRY_DEADLINE",
+        default_value = "5s",
+        parse(try_from_str = humantime::parse_duration)
+    )]
+    pub(crate) write_buffer_retry_deadline: Duration,
+
+    /// Path to a JSON file configuring the placement of namespaces and tables
+    /// on sequencers.
+    ///
+    /// When set, writes are sharded using a weighted consistent hash ring
+    /// that supports pinning namespaces / tables to a sequencer, and spreading
+    /// hot tables over multiple sequencers by a tag value. Example:
+    ///
+    ///     {
+    ///       "weights": {"0": 2},
+    ///       "namespace_overrides": {"ns": 1},
+    ///       "table_overrides": [{
+    ///         "namespace": "ns", "table": "cpu",
+    ///         "shard": {"spread": {"tag": "host", "count": 4}}
+    ///       }]
+    ///     }
+    ///
+    /// All routers must use the same shard config.
+    #[clap(long = "--shard-config", env = "INFLUXDB_IOX_SHARD_CONFIG")]
+    pub(crate) shard_config: Option<PathBuf>,
 }
 
 pub async fn command(config: Config) -> Result<()> {
     let common_state = CommonServerState::from_config(config.run_config.clone())?;
+    let shard_config = config
+        .shard_config
+        .as_ref()
+        .map(|path| read_shard_config(path))
+        .transpose()?;
     let metrics = Arc::new(metric::Registry::default());
 
     let catalog = config
@@ -82,6 +189,18 @@ pub async fn command(config: Config) -> Result<()> {
         catalog,
         &config.write_buffer_config,
         &config.query_pool_name,
+        &config.new_namespace_retention,
+        config
+            .partial_writes
+            .then(|| config.partial_write_max_errors),
+        !config.disable_namespace_autocreation,
+        RetryConfig {
+            max_retries: config.write_buffer_max_retries,
+            deadline: config.write_buffer_retry_deadline,
+            ..Default::default()
+        },
+        shard_config,
+        &config.rate_limit_config,
     )
     .await?;
 
@@ -89,3 +208,15 @@ pub async fn command(config: Config) -> Result<()> {
     let services = vec![Service::create(server_type, common_state.run_config())];
     Ok(influxdb_ioxd::main(common_state, services).await?)
 }
+
+/// Read and parse the JSON [`ShardConfig`] at `path`.
+fn read_shard_config(path: &Path) -> Result<ShardConfig> {
+    let data = std::fs::read_to_string(path).map_err(|source| Error::ReadShardConfig {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_str(&data).map_err(|source| Error::ParseShardConfig {
+        path: path.to_path_buf(),
+        source,
+    })
+}
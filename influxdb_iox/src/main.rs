@@ -26,7 +26,9 @@ use tokio::runtime::Runtime;
 mod commands {
     pub mod catalog;
     pub mod database;
+    pub mod dead_letter;
     pub mod debug;
+    pub mod namespace;
     pub mod operations;
     pub mod router;
     pub mod run;
@@ -169,6 +171,9 @@ enum Command {
     /// Router-related commands
     Router(commands::router::Config),
 
+    /// IOx namespace management commands
+    Namespace(commands::namespace::Config),
+
     /// IOx schema configuration commands
     Schema(commands::schema::Config),
 
@@ -184,6 +189,9 @@ enum Command {
     /// Various commands for catalog manipulation
     Catalog(commands::catalog::Config),
 
+    /// Inspect and re-inject write buffer operations skipped by the ingester
+    DeadLetter(commands::dead_letter::Config),
+
     /// Interrogate internal database data
     Debug(commands::debug::Config),
 
@@ -286,6 +294,14 @@ fn main() -> Result<(), std::io::Error> {
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
+            Command::Namespace(config) => {
+                let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
+                let connection = connection().await;
+                if let Err(e) = commands::namespace::command(connection, config).await {
+                    eprintln!("{}", e);
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
             Command::Schema(config) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
                 let connection = connection().await;
@@ -317,6 +333,13 @@ fn main() -> Result<(), std::io::Error> {
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
+            Command::DeadLetter(config) => {
+                let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
+                if let Err(e) = commands::dead_letter::command(config).await {
+                    eprintln!("{}", e);
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
             Command::Debug(config) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
                 if let Err(e) = commands::debug::command(config).await {
@@ -729,6 +729,7 @@ pub async fn fixture_replay_broken(db_name: &str, write_buffer_path: &Path) -> S
     let producer = FileBufferProducer::new(
         write_buffer_path,
         db_name,
+        &Default::default(),
         Default::default(),
         time_provider,
     )
@@ -45,6 +45,7 @@ async fn reads_come_from_write_buffer() {
     let producer = FileBufferProducer::new(
         write_buffer_dir.path(),
         &db_name,
+        &Default::default(),
         Default::default(),
         time_provider,
     )
@@ -69,6 +69,7 @@ pub async fn test_write_pb_router() {
 fn write_request(db_name: &str) -> pb::WriteRequest {
     let database_batch = pb::DatabaseBatch {
         database_name: db_name.to_string(),
+        partition_key: Default::default(),
         table_batches: vec![pb::TableBatch {
             table_name: "mytable".to_string(),
             columns: vec![
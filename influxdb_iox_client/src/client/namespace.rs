@@ -0,0 +1,64 @@
+use self::generated_types::{namespace_service_client::NamespaceServiceClient, *};
+use ::generated_types::google::OptionalField;
+
+use crate::connection::Connection;
+use crate::error::Error;
+
+/// Re-export generated_types
+pub mod generated_types {
+    pub use generated_types::influxdata::iox::namespace::v1::*;
+}
+
+/// A basic client for managing the namespaces of the catalog.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: NamespaceServiceClient<Connection>,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(channel: Connection) -> Self {
+        Self {
+            inner: NamespaceServiceClient::new(channel),
+        }
+    }
+
+    /// Create a namespace.
+    pub async fn create_namespace(
+        &mut self,
+        request: CreateNamespaceRequest,
+    ) -> Result<Namespace, Error> {
+        let response = self.inner.create_namespace(request).await?;
+
+        Ok(response.into_inner().namespace.unwrap_field("namespace")?)
+    }
+
+    /// List all namespaces.
+    pub async fn get_namespaces(&mut self) -> Result<Vec<Namespace>, Error> {
+        let response = self.inner.get_namespaces(GetNamespacesRequest {}).await?;
+
+        Ok(response.into_inner().namespaces)
+    }
+
+    /// Get a namespace by name.
+    pub async fn get_namespace(&mut self, name: &str) -> Result<Namespace, Error> {
+        let response = self
+            .inner
+            .get_namespace(GetNamespaceRequest {
+                name: name.to_string(),
+            })
+            .await?;
+
+        Ok(response.into_inner().namespace.unwrap_field("namespace")?)
+    }
+
+    /// Update the configuration of a namespace.
+    pub async fn update_namespace(
+        &mut self,
+        request: UpdateNamespaceRequest,
+    ) -> Result<Namespace, Error> {
+        let response = self.inner.update_namespace(request).await?;
+
+        Ok(response.into_inner().namespace.unwrap_field("namespace")?)
+    }
+}
@@ -1,5 +1,6 @@
-use hyper::{Body, Response, StatusCode};
+use hyper::{header::RETRY_AFTER, Body, Response, StatusCode};
 use observability_deps::tracing::warn;
+use std::time::Duration;
 
 /// Constants used in API error codes.
 ///
@@ -101,6 +102,9 @@ pub struct HttpApiError {
 
     /// Human-readable message.
     msg: String,
+
+    /// How long the client should wait before retrying the request.
+    retry_after: Option<Duration>,
 }
 
 impl HttpApiError {
@@ -109,9 +113,17 @@ impl HttpApiError {
         Self {
             code: code.into(),
             msg: msg.into(),
+            retry_after: None,
         }
     }
 
+    /// Ask the client to wait for `retry_after` before retrying the request,
+    /// using the `Retry-After` response header.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
     /// Generate response body for this error.
     fn body(&self) -> Body {
         let json = serde_json::json!({
@@ -125,10 +137,15 @@ impl HttpApiError {
 
     /// Generate response for this error.
     pub fn response(&self) -> Response<Body> {
-        Response::builder()
-            .status(self.code.status_code())
-            .body(self.body())
-            .unwrap()
+        let mut builder = Response::builder().status(self.code.status_code());
+        if let Some(retry_after) = self.retry_after {
+            // The header value is in whole seconds - round up so clients never
+            // retry early.
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            builder = builder.header(RETRY_AFTER, secs);
+        }
+
+        builder.body(self.body()).unwrap()
     }
 
     /// Check if the error is an internal server error.
@@ -5,6 +5,7 @@ use std::{
 
 use async_trait::async_trait;
 use compactor::{
+    garbage_collector::GarbageCollectorConfig,
     handler::{CompactorHandler, CompactorHandlerImpl},
     server::CompactorServer,
 };
@@ -23,6 +24,7 @@ use crate::{
     server_type::{common_state::CommonServerState, RpcError, ServerType},
 };
 use clap_blocks::compactor::CompactorConfig;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -120,7 +122,21 @@ impl HttpApiErrorSource for IoxHttpError {
     }
 }
 
+/// Convert the CLI config of the garbage collector.
+pub(crate) fn garbage_collector_config(
+    config: clap_blocks::garbage_collector::GarbageCollectorConfig,
+) -> GarbageCollectorConfig {
+    GarbageCollectorConfig {
+        grace_period: Duration::from_secs(config.grace_period_seconds),
+        interval: Duration::from_secs(config.interval_seconds),
+        dry_run: config.dry_run,
+    }
+}
+
 /// Instantiate a compactor server
+///
+/// The garbage collector runs as part of the compactor if `gc_config` is set.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_compactor_server_type(
     common_state: &CommonServerState,
     metric_registry: Arc<metric::Registry>,
@@ -129,6 +145,7 @@ pub async fn create_compactor_server_type(
     exec: Arc<Executor>,
     time_provider: Arc<dyn TimeProvider>,
     compactor_config: CompactorConfig,
+    gc_config: Option<clap_blocks::garbage_collector::GarbageCollectorConfig>,
 ) -> Result<Arc<dyn ServerType>> {
     if compactor_config.write_buffer_partition_range_start
         > compactor_config.write_buffer_partition_range_end
@@ -161,7 +178,8 @@ pub async fn create_compactor_server_type(
         object_store,
         exec,
         time_provider,
-        &metric_registry,
+        Arc::clone(&metric_registry),
+        gc_config.map(garbage_collector_config),
     ));
 
     let compactor = CompactorServer::new(metric_registry, compactor_handler);
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use compactor::{garbage_collector::GarbageCollectorHandlerImpl, server::CompactorServer};
+use iox_catalog::interface::Catalog;
+use object_store::DynObjectStore;
+use time::TimeProvider;
+
+use crate::server_type::{
+    common_state::CommonServerState,
+    compactor::{garbage_collector_config, CompactorServerType},
+    ServerType,
+};
+use clap_blocks::garbage_collector::GarbageCollectorConfig;
+
+/// Instantiate a server that only runs the parquet file garbage collector.
+///
+/// The garbage collector is a compactor without compaction workers, so this reuses the compactor
+/// server type.
+pub fn create_garbage_collector_server_type(
+    common_state: &CommonServerState,
+    metric_registry: Arc<metric::Registry>,
+    catalog: Arc<dyn Catalog>,
+    object_store: Arc<DynObjectStore>,
+    time_provider: Arc<dyn TimeProvider>,
+    gc_config: GarbageCollectorConfig,
+) -> Arc<dyn ServerType> {
+    let handler = Arc::new(GarbageCollectorHandlerImpl::new(
+        catalog,
+        object_store,
+        time_provider,
+        garbage_collector_config(gc_config),
+        Arc::clone(&metric_registry),
+    ));
+
+    let server = CompactorServer::new(metric_registry, handler);
+    Arc::new(CompactorServerType::new(server, common_state))
+}
@@ -10,9 +10,11 @@ use clap_blocks::{ingester::IngesterConfig, write_buffer::WriteBufferConfig};
 use data_types2::KafkaPartition;
 use hyper::{Body, Request, Response};
 use ingester::{
+    dead_letter::DeadLetterSink,
     handler::IngestHandlerImpl,
     lifecycle::LifecycleConfig,
     server::{grpc::GrpcDelegate, http::HttpDelegate, IngesterServer},
+    wal::Wal,
 };
 use iox_catalog::interface::Catalog;
 use metric::Registry;
@@ -21,7 +23,7 @@ use query::exec::Executor;
 use trace::TraceCollector;
 
 use crate::{
-    http::error::{HttpApiError, HttpApiErrorCode, HttpApiErrorSource},
+    http::error::{HttpApiError, HttpApiErrorSource},
     rpc::{add_service, serve_builder, setup_builder, RpcBuilderInput},
     server_type::{common_state::CommonServerState, RpcError, ServerType},
 };
@@ -45,6 +47,9 @@ pub enum Error {
 
     #[error("error initializing write buffer {0}")]
     WriteBuffer(#[from] write_buffer::core::WriteBufferError),
+
+    #[error("error initializing write-ahead log: {0}")]
+    Wal(#[from] ingester::wal::Error),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -76,12 +81,17 @@ impl<I: IngestHandler + Sync + Send + Debug + 'static> ServerType for IngesterSe
         self.trace_collector.as_ref().map(Arc::clone)
     }
 
-    /// Just return "not found".
+    /// Dispatches `req` to the ingester [`HttpDelegate`] delegate.
     async fn route_http_request(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
     ) -> Result<Response<Body>, Box<dyn HttpApiErrorSource>> {
-        Err(Box::new(IoxHttpError::NotFound))
+        self.server
+            .http()
+            .route(req)
+            .await
+            .map_err(IoxHttpErrorAdaptor)
+            .map_err(|e| Box::new(e) as _)
     }
 
     /// Provide a placeholder gRPC service.
@@ -102,31 +112,23 @@ impl<I: IngestHandler + Sync + Send + Debug + 'static> ServerType for IngesterSe
     }
 }
 
-/// Simple error struct, we're not really providing an HTTP interface for the ingester.
+/// This adaptor converts the `ingester` http error type into a type that
+/// satisfies the requirements of influxdb_ioxd's runner framework, keeping the
+/// two decoupled.
 #[derive(Debug)]
-pub enum IoxHttpError {
-    NotFound,
-}
+pub struct IoxHttpErrorAdaptor(ingester::server::http::Error);
 
-impl IoxHttpError {
-    fn status_code(&self) -> HttpApiErrorCode {
-        match self {
-            IoxHttpError::NotFound => HttpApiErrorCode::NotFound,
-        }
-    }
-}
-
-impl Display for IoxHttpError {
+impl Display for IoxHttpErrorAdaptor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        Display::fmt(&self.0, f)
     }
 }
 
-impl std::error::Error for IoxHttpError {}
+impl std::error::Error for IoxHttpErrorAdaptor {}
 
-impl HttpApiErrorSource for IoxHttpError {
+impl HttpApiErrorSource for IoxHttpErrorAdaptor {
     fn to_http_api_error(&self) -> HttpApiError {
-        HttpApiError::new(self.status_code(), self.to_string())
+        HttpApiError::new(self.0.as_status_code(), self.to_string())
     }
 }
 
@@ -178,6 +180,23 @@ pub async fn create_ingester_server_type(
         Duration::from_secs(ingester_config.persist_partition_age_threshold_seconds),
         Duration::from_secs(ingester_config.persist_partition_cold_threshold_seconds),
     );
+    let wal = ingester_config
+        .wal_dir
+        .as_ref()
+        .map(|dir| {
+            Wal::new(
+                dir,
+                ingester_config.wal_segment_size_bytes,
+                &metric_registry,
+            )
+        })
+        .transpose()?
+        .map(Arc::new);
+    let dead_letter_sink = ingester_config
+        .dead_letter_prefix
+        .as_ref()
+        .map(|prefix| DeadLetterSink::new(Arc::clone(&object_store), prefix.clone()));
+
     let ingest_handler = Arc::new(
         IngestHandlerImpl::new(
             lifecycle_config,
@@ -189,6 +208,8 @@ pub async fn create_ingester_server_type(
             exec,
             Arc::clone(&metric_registry),
             Arc::new(SystemProvider::new()),
+            wal,
+            dead_letter_sink,
         )
         .await?,
     );
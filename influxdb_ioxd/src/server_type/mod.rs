@@ -11,6 +11,7 @@ use crate::{http::error::HttpApiErrorSource, rpc::RpcBuilderInput};
 pub mod common_state;
 pub mod compactor;
 pub mod database;
+pub mod garbage_collector;
 pub mod ingester;
 pub mod querier;
 pub mod router;
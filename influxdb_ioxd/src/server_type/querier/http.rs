@@ -0,0 +1,586 @@
+//! A minimal InfluxDB 1.x compatible `/query` endpoint for the querier.
+//!
+//! Requests are addressed with the 1.x `db` & `rp` parameters, which are
+//! mapped to a namespace with [`db_and_rp_to_database`]. The `q` parameter
+//! must be a single SQL statement - InfluxQL is NOT supported, and a query
+//! that cannot be parsed as SQL is rejected with a `400 Bad Request`. The
+//! result is rendered in the 1.x JSON response format, as a single statement
+//! containing a single series. The series is named after the queried table
+//! when the query selects from a single table, and is unnamed otherwise.
+//!
+//! Timestamps are rendered as RFC3339 strings, unless the `epoch` parameter
+//! requests integer timestamps of the given precision (`h`, `m`, `s`, `ms`,
+//! `u`/`us` or `n`/`ns`).
+
+use arrow::{
+    array::{
+        Array, ArrayRef, BooleanArray, Float64Array, Int64Array, TimestampNanosecondArray,
+        UInt64Array,
+    },
+    datatypes::{DataType, TimeUnit},
+    record_batch::RecordBatch,
+    util::display::array_value_to_string,
+};
+use bytes::BytesMut;
+use chrono::{SecondsFormat, TimeZone, Utc};
+use data_types2::{db_and_rp_to_database, OrgBucketMappingError};
+use futures::StreamExt;
+use http::header::CONTENT_TYPE;
+use hyper::{Body, Method, Request, Response};
+use observability_deps::tracing::debug;
+use query::{exec::ExecutionContextProvider, QueryDatabase};
+use serde::Serialize;
+use serde_json::Value;
+use service_common::{planner::Planner, QueryDatabaseProvider};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use sqlparser::{
+    ast::{SetExpr, Statement, TableFactor, TableWithJoins},
+    dialect::GenericDialect,
+    parser::{Parser, ParserError},
+};
+
+use crate::http::error::{HttpApiError, HttpApiErrorExt, HttpApiErrorSource};
+
+/// The maximum size of a `/query` request body.
+const MAX_QUERY_BODY_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Snafu)]
+pub enum ApplicationError {
+    #[snafu(display("No handler for {:?} {}", method, path))]
+    RouteNotFound { method: Method, path: String },
+
+    #[snafu(display("Invalid query parameters '{}': {}", params, source))]
+    InvalidQueryParams {
+        params: String,
+        source: serde_urlencoded::de::Error,
+    },
+
+    #[snafu(display("missing required parameter '{}'", name))]
+    MissingParameter { name: &'static str },
+
+    #[snafu(display("invalid epoch '{}'", epoch))]
+    InvalidEpoch { epoch: String },
+
+    #[snafu(display("query is not valid SQL (InfluxQL is not supported): {}", source))]
+    InvalidSql { source: ParserError },
+
+    #[snafu(display("expected a single SQL statement, got {}", n))]
+    MultipleStatements { n: usize },
+
+    #[snafu(display("Invalid db/rp: {}", source))]
+    DbRpMapping { source: OrgBucketMappingError },
+
+    #[snafu(display("database not found: {}", db_name))]
+    DatabaseNotFound { db_name: String },
+
+    #[snafu(display("Error reading request body: {}", source))]
+    ReadingBody { source: hyper::Error },
+
+    #[snafu(display("max request size ({} bytes) exceeded", MAX_QUERY_BODY_BYTES))]
+    RequestSizeExceeded,
+
+    #[snafu(display("Internal error creating HTTP response:  {}", source))]
+    CreatingResponse { source: http::Error },
+}
+
+impl HttpApiErrorSource for ApplicationError {
+    fn to_http_api_error(&self) -> HttpApiError {
+        match self {
+            e @ Self::RouteNotFound { .. } => e.not_found(),
+            e @ Self::InvalidQueryParams { .. } => e.invalid(),
+            e @ Self::MissingParameter { .. } => e.invalid(),
+            e @ Self::InvalidEpoch { .. } => e.invalid(),
+            e @ Self::InvalidSql { .. } => e.invalid(),
+            e @ Self::MultipleStatements { .. } => e.invalid(),
+            e @ Self::DbRpMapping { .. } => e.invalid(),
+            e @ Self::DatabaseNotFound { .. } => e.not_found(),
+            e @ Self::ReadingBody { .. } => e.invalid(),
+            e @ Self::RequestSizeExceeded => e.invalid(),
+            e @ Self::CreatingResponse { .. } => e.internal_error(),
+        }
+    }
+}
+
+pub async fn route_request<D>(
+    database: &D,
+    req: Request<Body>,
+) -> Result<Response<Body>, ApplicationError>
+where
+    D: QueryDatabaseProvider,
+{
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/query") | (&Method::POST, "/query") => query(database, req).await,
+        (method, path) => Err(ApplicationError::RouteNotFound {
+            method: method.clone(),
+            path: path.to_string(),
+        }),
+    }
+}
+
+/// The timestamp precision requested by the `epoch` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Epoch {
+    Hours,
+    Minutes,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl Epoch {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "h" => Self::Hours,
+            "m" => Self::Minutes,
+            "s" => Self::Seconds,
+            "ms" => Self::Milliseconds,
+            "u" | "us" => Self::Microseconds,
+            "n" | "ns" => Self::Nanoseconds,
+            _ => return None,
+        })
+    }
+
+    /// Returns the divisor to convert a nanosecond timestamp to this precision
+    fn divisor(&self) -> i64 {
+        match self {
+            Self::Hours => 3_600_000_000_000,
+            Self::Minutes => 60_000_000_000,
+            Self::Seconds => 1_000_000_000,
+            Self::Milliseconds => 1_000_000,
+            Self::Microseconds => 1_000,
+            Self::Nanoseconds => 1,
+        }
+    }
+}
+
+/// The parameters of a 1.x `/query` request.
+///
+/// The `u` & `p` credential parameters are accepted, but ignored.
+#[derive(Debug, Default, PartialEq)]
+struct QueryParams {
+    db: Option<String>,
+    rp: Option<String>,
+    q: Option<String>,
+    epoch: Option<String>,
+}
+
+impl QueryParams {
+    /// Merge the url-encoded parameters in `params` into `self`, overwriting
+    /// any parameters already set.
+    fn merge(&mut self, params: &str) -> Result<(), ApplicationError> {
+        let pairs: Vec<(String, String)> =
+            serde_urlencoded::from_str(params).context(InvalidQueryParamsSnafu { params })?;
+
+        for (k, v) in pairs {
+            match k.as_str() {
+                "db" => self.db = Some(v),
+                "rp" => self.rp = Some(v),
+                "q" => self.q = Some(v),
+                "epoch" => self.epoch = Some(v),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The 1.x JSON response body.
+#[derive(Debug, Serialize)]
+struct QueryResponse {
+    results: Vec<StatementResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct StatementResult {
+    statement_id: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    series: Vec<Series>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Series {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    columns: Vec<String>,
+    values: Vec<Vec<Value>>,
+}
+
+async fn query<D>(database: &D, req: Request<Body>) -> Result<Response<Body>, ApplicationError>
+where
+    D: QueryDatabaseProvider,
+{
+    let span_ctx = req.extensions().get().cloned();
+
+    // Parameters may be provided in the URI and, for POST requests, as a
+    // url-encoded form body.
+    let mut params = QueryParams::default();
+    if let Some(uri_query) = req.uri().query() {
+        params.merge(uri_query)?;
+    }
+    if req.method() == Method::POST {
+        let body = read_body(req).await?;
+        params.merge(&String::from_utf8_lossy(&body))?;
+    }
+
+    let db = params.db.context(MissingParameterSnafu { name: "db" })?;
+    let q = params.q.context(MissingParameterSnafu { name: "q" })?;
+    let epoch = params
+        .epoch
+        .map(|epoch| Epoch::parse(&epoch).context(InvalidEpochSnafu { epoch }))
+        .transpose()?;
+
+    // Reject queries that are not SQL up front, rather than reporting them
+    // as a failed statement.
+    let statements = Parser::parse_sql(&GenericDialect {}, &q).context(InvalidSqlSnafu)?;
+    ensure!(
+        statements.len() == 1,
+        MultipleStatementsSnafu {
+            n: statements.len()
+        }
+    );
+    let name = series_name(&statements[0]);
+
+    let db_name = db_and_rp_to_database(&db, params.rp.as_deref()).context(DbRpMappingSnafu)?;
+    debug!(%q, ?epoch, %db_name, "running v1 query");
+
+    let db = database.db(&db_name).context(DatabaseNotFoundSnafu {
+        db_name: db_name.as_str(),
+    })?;
+
+    let ctx = db.new_query_context(span_ctx);
+    let mut query_completed_token = db.record_query(&ctx, "sql", Box::new(q.clone()));
+
+    // Query errors are reported in the result body, rather than as an HTTP
+    // error, matching the 1.x API.
+    let result = match Planner::new(&ctx).sql(&q).await {
+        Ok(physical_plan) => ctx.collect(physical_plan).await,
+        Err(e) => Err(e),
+    };
+    let result = match result {
+        Ok(batches) => {
+            query_completed_token.set_success();
+            StatementResult {
+                statement_id: 0,
+                series: batches_to_series(&batches, name, epoch)
+                    .into_iter()
+                    .collect(),
+                error: None,
+            }
+        }
+        Err(e) => StatementResult {
+            statement_id: 0,
+            series: vec![],
+            error: Some(e.to_string()),
+        },
+    };
+
+    let body = QueryResponse {
+        results: vec![result],
+    };
+
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_string(&body).expect("query response is serialisable"),
+        ))
+        .context(CreatingResponseSnafu)
+}
+
+/// Read the request body, up to [`MAX_QUERY_BODY_BYTES`] in size.
+async fn read_body(req: Request<Body>) -> Result<BytesMut, ApplicationError> {
+    let mut payload = req.into_body();
+
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.context(ReadingBodySnafu)?;
+        if (body.len() + chunk.len()) > MAX_QUERY_BODY_BYTES {
+            return Err(ApplicationError::RequestSizeExceeded);
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
+/// Returns the name of the series of results of `statement` - the name of
+/// the queried table if it selects from a single table.
+fn series_name(statement: &Statement) -> Option<String> {
+    let query = match statement {
+        Statement::Query(query) => query,
+        _ => return None,
+    };
+    let select = match &query.body {
+        SetExpr::Select(select) => select,
+        _ => return None,
+    };
+    match select.from.as_slice() {
+        [TableWithJoins {
+            relation: TableFactor::Table { name, .. },
+            joins,
+        }] if joins.is_empty() => name.0.last().map(|ident| ident.value.clone()),
+        _ => None,
+    }
+}
+
+/// Render `batches` as a single 1.x series called `name`, returning `None`
+/// if there are no rows.
+fn batches_to_series(
+    batches: &[RecordBatch],
+    name: Option<String>,
+    epoch: Option<Epoch>,
+) -> Option<Series> {
+    let schema = batches.first()?.schema();
+    let columns = schema.fields().iter().map(|f| f.name().clone()).collect();
+
+    let values: Vec<_> = batches
+        .iter()
+        .flat_map(|batch| {
+            (0..batch.num_rows()).map(move |row| {
+                batch
+                    .columns()
+                    .iter()
+                    .map(|column| value_to_json(column, row, epoch))
+                    .collect()
+            })
+        })
+        .collect();
+
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(Series {
+        name,
+        columns,
+        values,
+    })
+}
+
+/// Convert the value at `column[row]` to JSON.
+fn value_to_json(column: &ArrayRef, row: usize, epoch: Option<Epoch>) -> Value {
+    if !column.is_valid(row) {
+        return Value::Null;
+    }
+
+    let any = column.as_any();
+    match column.data_type() {
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            let ts = any
+                .downcast_ref::<TimestampNanosecondArray>()
+                .expect("timestamp column")
+                .value(row);
+            match epoch {
+                Some(epoch) => Value::from(ts / epoch.divisor()),
+                None => Value::from(
+                    Utc.timestamp_nanos(ts)
+                        .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                ),
+            }
+        }
+        DataType::Int64 => Value::from(
+            any.downcast_ref::<Int64Array>()
+                .expect("i64 column")
+                .value(row),
+        ),
+        DataType::UInt64 => Value::from(
+            any.downcast_ref::<UInt64Array>()
+                .expect("u64 column")
+                .value(row),
+        ),
+        DataType::Float64 => Value::from(
+            any.downcast_ref::<Float64Array>()
+                .expect("f64 column")
+                .value(row),
+        ),
+        DataType::Boolean => Value::from(
+            any.downcast_ref::<BooleanArray>()
+                .expect("bool column")
+                .value(row),
+        ),
+        _ => array_value_to_string(column, row)
+            .map(Value::from)
+            .unwrap_or(Value::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{DictionaryArray, StringArray};
+    use arrow::datatypes::Int32Type;
+    use serde_json::json;
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let host: DictionaryArray<Int32Type> = vec![Some("a"), None].into_iter().collect();
+        RecordBatch::try_from_iter(vec![
+            ("host", Arc::new(host) as ArrayRef),
+            (
+                "usage",
+                Arc::new(Float64Array::from(vec![Some(1.5), None])) as ArrayRef,
+            ),
+            ("count", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "msg",
+                Arc::new(StringArray::from(vec!["hello", "world"])) as ArrayRef,
+            ),
+            (
+                "time",
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    1647622800000000000,
+                    1647622800500000000,
+                ])) as ArrayRef,
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_params_merge() {
+        let mut params = QueryParams::default();
+        params.merge("db=bananas&u=user&p=pass").unwrap();
+        params
+            .merge("q=SELECT+*+FROM+cpu&rp=autogen&epoch=ms")
+            .unwrap();
+
+        assert_eq!(
+            params,
+            QueryParams {
+                db: Some("bananas".to_string()),
+                rp: Some("autogen".to_string()),
+                q: Some("SELECT * FROM cpu".to_string()),
+                epoch: Some("ms".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_epoch_parse() {
+        assert_eq!(Epoch::parse("h"), Some(Epoch::Hours));
+        assert_eq!(Epoch::parse("u"), Some(Epoch::Microseconds));
+        assert_eq!(Epoch::parse("ns"), Some(Epoch::Nanoseconds));
+        assert_eq!(Epoch::parse("d"), None);
+    }
+
+    #[test]
+    fn test_batches_to_series_rfc3339() {
+        let series = batches_to_series(&[batch()], Some("cpu".to_string()), None).unwrap();
+        assert_eq!(
+            serde_json::to_value(series).unwrap(),
+            json!({
+                "name": "cpu",
+                "columns": ["host", "usage", "count", "msg", "time"],
+                "values": [
+                    ["a", 1.5, 1, "hello", "2022-03-18T17:00:00Z"],
+                    [null, null, 2, "world", "2022-03-18T17:00:00.500Z"],
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_batches_to_series_epoch() {
+        let series = batches_to_series(&[batch()], None, Some(Epoch::Milliseconds)).unwrap();
+        assert!(!serde_json::to_value(&series)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("name"));
+        assert_eq!(
+            series
+                .values
+                .iter()
+                .map(|row| row[4].clone())
+                .collect::<Vec<_>>(),
+            vec![json!(1647622800000_i64), json!(1647622800500_i64)]
+        );
+    }
+
+    #[test]
+    fn test_batches_to_series_empty() {
+        assert!(batches_to_series(&[], None, None).is_none());
+        assert!(batches_to_series(&[batch().slice(0, 0)], None, None).is_none());
+    }
+
+    #[test]
+    fn test_series_name() {
+        let name = |sql: &str| {
+            let statements = Parser::parse_sql(&GenericDialect {}, sql).unwrap();
+            series_name(&statements[0])
+        };
+
+        assert_eq!(name("SELECT * FROM cpu"), Some("cpu".to_string()));
+        assert_eq!(
+            name(r#"SELECT count(*) FROM "my cpu" WHERE host = 'a'"#),
+            Some("my cpu".to_string())
+        );
+        assert_eq!(name("SELECT * FROM iox.cpu AS c"), Some("cpu".to_string()));
+        assert_eq!(
+            name("SELECT * FROM cpu JOIN mem ON cpu.host = mem.host"),
+            None
+        );
+        assert_eq!(name("SELECT * FROM cpu, mem"), None);
+        assert_eq!(name("SELECT 1"), None);
+    }
+
+    /// A [`QueryDatabaseProvider`] without any databases.
+    #[derive(Debug)]
+    struct NoDatabases;
+
+    impl QueryDatabaseProvider for NoDatabases {
+        type Db = query::test::TestDatabase;
+
+        fn db(&self, _name: &str) -> Option<Arc<Self::Db>> {
+            None
+        }
+    }
+
+    async fn get_query(q: &str) -> Result<Response<Body>, ApplicationError> {
+        let uri = format!(
+            "https://bananas.example/query?{}",
+            serde_urlencoded::to_string([("db", "bananas"), ("q", q)]).unwrap()
+        );
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        route_request(&NoDatabases, req).await
+    }
+
+    #[tokio::test]
+    async fn test_query_not_sql() {
+        // InfluxQL duration literals are not valid SQL
+        let err = get_query("SELECT mean(usage) FROM cpu WHERE time > now() - 1h")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ApplicationError::InvalidSql { .. }),
+            "{}",
+            err
+        );
+        assert_eq!(
+            err.to_http_api_error().response().status(),
+            hyper::StatusCode::BAD_REQUEST
+        );
+
+        let err = get_query("SELECT * FROM cpu; SELECT * FROM mem")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ApplicationError::MultipleStatements { n: 2 }),
+            "{}",
+            err
+        );
+
+        // A valid SQL query makes it to the database lookup
+        let err = get_query("SELECT * FROM cpu").await.unwrap_err();
+        assert!(
+            matches!(err, ApplicationError::DatabaseNotFound { .. }),
+            "{}",
+            err
+        );
+    }
+}
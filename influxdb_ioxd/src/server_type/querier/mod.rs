@@ -1,16 +1,14 @@
-use std::{
-    fmt::{Debug, Display},
-    sync::Arc,
-};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use hyper::{Body, Request, Response};
 use iox_catalog::interface::Catalog;
 use metric::Registry;
-use object_store::DynObjectStore;
+use object_store::{DynObjectStore, ObjectStoreFileCache};
 use querier::{
     database::QuerierDatabase,
     handler::{QuerierHandler, QuerierHandlerImpl},
+    ingester::create_ingester_connection,
     server::QuerierServer,
 };
 use query::exec::Executor;
@@ -18,13 +16,16 @@ use time::TimeProvider;
 use trace::TraceCollector;
 
 use crate::{
-    http::error::{HttpApiError, HttpApiErrorCode, HttpApiErrorSource},
+    http::error::HttpApiErrorSource,
     rpc::{add_service, serve_builder, setup_builder, RpcBuilderInput},
     server_type::{common_state::CommonServerState, RpcError, ServerType},
 };
 
+mod http;
 mod rpc;
 
+pub use self::http::ApplicationError;
+
 #[derive(Debug)]
 pub struct QuerierServerType<C: QuerierHandler> {
     database: Arc<QuerierDatabase>,
@@ -58,12 +59,14 @@ impl<C: QuerierHandler + std::fmt::Debug + 'static> ServerType for QuerierServer
         self.trace_collector.as_ref().map(Arc::clone)
     }
 
-    /// Just return "not found".
+    /// Route HTTP requests to the InfluxDB 1.x compatible `/query` endpoint.
     async fn route_http_request(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
     ) -> Result<Response<Body>, Box<dyn HttpApiErrorSource>> {
-        Err(Box::new(IoxHttpError::NotFound))
+        self::http::route_request(self.database.as_ref(), req)
+            .await
+            .map_err(|e| Box::new(e) as _)
     }
 
     /// Provide a placeholder gRPC service.
@@ -91,49 +94,27 @@ impl<C: QuerierHandler + std::fmt::Debug + 'static> ServerType for QuerierServer
     }
 }
 
-/// Simple error struct, we're not really providing an HTTP interface for the compactor.
-#[derive(Debug)]
-pub enum IoxHttpError {
-    NotFound,
-}
-
-impl IoxHttpError {
-    fn status_code(&self) -> HttpApiErrorCode {
-        match self {
-            IoxHttpError::NotFound => HttpApiErrorCode::NotFound,
-        }
-    }
-}
-
-impl Display for IoxHttpError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-impl std::error::Error for IoxHttpError {}
-
-impl HttpApiErrorSource for IoxHttpError {
-    fn to_http_api_error(&self) -> HttpApiError {
-        HttpApiError::new(self.status_code(), self.to_string())
-    }
-}
-
 /// Instantiate a querier server
+#[allow(clippy::too_many_arguments)]
 pub async fn create_querier_server_type(
     common_state: &CommonServerState,
     metric_registry: Arc<metric::Registry>,
     catalog: Arc<dyn Catalog>,
     object_store: Arc<DynObjectStore>,
+    object_store_cache: Option<Arc<ObjectStoreFileCache>>,
     time_provider: Arc<dyn TimeProvider>,
     exec: Arc<Executor>,
+    ingester_addresses: Vec<String>,
 ) -> Arc<dyn ServerType> {
+    let ingester_connection = create_ingester_connection(ingester_addresses);
     let database = Arc::new(QuerierDatabase::new(
         catalog,
         Arc::clone(&metric_registry),
         object_store,
+        object_store_cache,
         time_provider,
         exec,
+        ingester_connection,
     ));
     let querier_handler = Arc::new(QuerierHandlerImpl::new(Arc::clone(&database)));
 
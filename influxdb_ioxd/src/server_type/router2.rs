@@ -1,12 +1,16 @@
 use std::{
     collections::BTreeSet,
     fmt::{Debug, Display},
+    num::NonZeroUsize,
     sync::Arc,
+    time::Duration,
 };
 
 use async_trait::async_trait;
-use clap_blocks::write_buffer::WriteBufferConfig;
-use data_types2::{PartitionTemplate, TemplatePart};
+use clap_blocks::{rate_limit::RateLimitConfig, write_buffer::WriteBufferConfig};
+use data_types2::{
+    CompiledPartitionTemplate, DatabaseName, DeletePredicate, PartitionTemplate, TemplatePart,
+};
 use hashbrown::HashMap;
 use hyper::{Body, Request, Response};
 use iox_catalog::interface::Catalog;
@@ -15,14 +19,19 @@ use mutable_batch::MutableBatch;
 use observability_deps::tracing::info;
 use router2::{
     dml_handlers::{
-        DmlHandler, DmlHandlerChainExt, FanOutAdaptor, InstrumentationDecorator,
-        NamespaceAutocreation, Partitioner, SchemaValidator, ShardedWriteBuffer,
+        self, DmlHandler, DmlHandlerChainExt, FanOutAdaptor, InstrumentationDecorator,
+        NamespaceAutocreation, NamespaceRateLimit, Partitioner, RateLimiter, RetryConfig,
+        SchemaValidator, ShardedWriteBuffer,
+    },
+    namespace_cache::{
+        metrics::InstrumentedCache, DeletionPruner, LruNamespaceCache, MemoryNamespaceCache,
+        NamespaceCache, SchemaRefresher, ShardedCache,
     },
-    namespace_cache::{metrics::InstrumentedCache, MemoryNamespaceCache, ShardedCache},
     sequencer::Sequencer,
     server::{grpc::GrpcDelegate, http::HttpDelegate, RouterServer},
-    sharder::JumpHash,
+    sharder::{BatchSharder, HashRing, JumpHash, ShardConfig, Sharder},
 };
+use time::SystemProvider;
 use tokio_util::sync::CancellationToken;
 use trace::TraceCollector;
 
@@ -44,10 +53,26 @@ pub enum Error {
 
     #[error("Catalog DSN error: {0}")]
     CatalogDsn(#[from] clap_blocks::catalog_dsn::Error),
+
+    #[error("Invalid retention for new namespaces: {0}")]
+    NewNamespaceRetention(#[from] data_types2::RetentionDurationParseError),
+
+    #[error("Invalid shard config: {0}")]
+    ShardConfig(#[from] router2::sharder::ShardConfigError),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// How often the namespace cache is checked for namespaces / tables that were
+/// soft-deleted in the catalog.
+const DELETION_PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often all cached namespace schemas are reloaded from the catalog.
+const SCHEMA_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The maximum number of namespace schemas held in the namespace cache.
+const NAMESPACE_CACHE_MAX_ENTRIES: usize = 10_000;
+
 #[derive(Debug)]
 pub struct RouterServerType<D> {
     server: RouterServer<D>,
@@ -102,6 +127,7 @@ where
         let builder = setup_builder!(builder_input, self);
         add_service!(builder, self.server.grpc().write_service());
         add_service!(builder, self.server.grpc().schema_service());
+        add_service!(builder, self.server.grpc().namespace_service());
         serve_builder!(builder);
 
         Ok(())
@@ -132,52 +158,133 @@ impl std::error::Error for IoxHttpErrorAdaptor {}
 
 impl HttpApiErrorSource for IoxHttpErrorAdaptor {
     fn to_http_api_error(&self) -> HttpApiError {
-        HttpApiError::new(self.0.as_status_code(), self.to_string())
+        let err = HttpApiError::new(self.0.as_status_code(), self.to_string());
+        match self.0.retry_after() {
+            Some(retry_after) => err.with_retry_after(retry_after),
+            None => err,
+        }
     }
 }
 
 /// Instantiate a router2 server
+///
+/// If `namespace_autocreation` is false, writes are only accepted for
+/// namespaces that were explicitly created (i.e. using the namespace gRPC
+/// service).
+///
+/// Failed write buffer enqueues are retried according to `enqueue_retry`.
+///
+/// If a `shard_config` is provided, writes are sharded using a [`HashRing`]
+/// configured by it, otherwise using [`JumpHash`].
+///
+/// Requests are subject to the admission control and namespace write rate
+/// limits of `rate_limit_config`.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_router2_server_type(
     common_state: &CommonServerState,
     metrics: Arc<metric::Registry>,
     catalog: Arc<dyn Catalog>,
     write_buffer_config: &WriteBufferConfig,
     query_pool_name: &str,
+    new_namespace_retention: &str,
+    partial_write_max_errors: Option<usize>,
+    namespace_autocreation: bool,
+    enqueue_retry: RetryConfig,
+    shard_config: Option<ShardConfig>,
+    rate_limit_config: &RateLimitConfig,
 ) -> Result<Arc<dyn ServerType>> {
+    // Reject an invalid retention at startup, rather than failing every write
+    // to a new namespace.
+    data_types2::parse_retention_duration(new_namespace_retention)?;
+
     // Initialise the sharded write buffer and instrument it with DML handler
     // metrics.
     let write_buffer = init_write_buffer(
         write_buffer_config,
+        shard_config,
         Arc::clone(&metrics),
         common_state.trace_collector(),
     )
-    .await?;
+    .await?
+    .with_retries(enqueue_retry, &*metrics);
     let write_buffer =
         InstrumentationDecorator::new("sharded_write_buffer", Arc::clone(&metrics), write_buffer);
 
     // Initialise an instrumented namespace cache to be shared with the schema
     // validator, and namespace auto-creator that reports cache hit/miss/update
-    // metrics.
-    let ns_cache = Arc::new(InstrumentedCache::new(
-        Arc::new(ShardedCache::new(
-            std::iter::repeat_with(|| Arc::new(MemoryNamespaceCache::default())).take(10),
+    // metrics, bounded to hold at most NAMESPACE_CACHE_MAX_ENTRIES namespaces.
+    let ns_cache = Arc::new(LruNamespaceCache::new(
+        Arc::new(InstrumentedCache::new(
+            Arc::new(ShardedCache::new(
+                std::iter::repeat_with(|| Arc::new(MemoryNamespaceCache::default())).take(10),
+            )),
+            &*metrics,
         )),
-        &*metrics,
+        NonZeroUsize::new(NAMESPACE_CACHE_MAX_ENTRIES).expect("capacity must be non-zero"),
     ));
 
+    // Evict namespaces that were soft-deleted (or had a table soft-deleted)
+    // in the catalog, so the deletion is observed by subsequent writes.
+    let deletion_pruner = DeletionPruner::new(Arc::clone(&catalog), Arc::clone(&ns_cache));
+
+    // Periodically reload the cached schemas, observing schema changes made
+    // by other routers.
+    let schema_refresher = SchemaRefresher::new(Arc::clone(&catalog), Arc::clone(&ns_cache));
+
     // Initialise and instrument the schema validator
-    let schema_validator = SchemaValidator::new(Arc::clone(&catalog), Arc::clone(&ns_cache));
+    let schema_validator = SchemaValidator::new(
+        Arc::clone(&catalog),
+        Arc::clone(&ns_cache),
+        Arc::new(SystemProvider::new()),
+        &*metrics,
+    );
     let schema_validator =
         InstrumentationDecorator::new("schema_validator", Arc::clone(&metrics), schema_validator);
 
     // Add a write partitioner into the handler stack that splits by the date
-    // portion of the write's timestamp.
-    let partitioner = Partitioner::new(PartitionTemplate {
+    // portion of the write's timestamp, unless the namespace or table has a
+    // partition template of its own.
+    let default_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
         parts: vec![TemplatePart::TimeFormat("%Y-%m-%d".to_owned())],
-    });
+    })
+    .expect("default partition template is valid");
+    let partitioner = Partitioner::new(default_template, Arc::clone(&ns_cache));
     let partitioner =
         InstrumentationDecorator::new("partitioner", Arc::clone(&metrics), partitioner);
 
+    // Build the chain of DML handlers that forms the request processing
+    // pipeline, starting with the write partitioner that yields a set of
+    // partitioned batches.
+    let schema_catalog = Arc::clone(&catalog);
+    let handler_stack = schema_validator
+        .and_then(partitioner)
+        // Once writes have been partitioned, they are processed in parallel.
+        //
+        // This block initialises a fan-out adaptor that parallelises partitioned
+        // writes into the handler chain it decorates (schema validation, and then
+        // into the sharded write buffer), and instruments the parallelised
+        // operation.
+        .and_then(InstrumentationDecorator::new(
+            "parallel_write",
+            Arc::clone(&metrics),
+            FanOutAdaptor::new(write_buffer),
+        ));
+
+    if !namespace_autocreation {
+        info!("namespace autocreation disabled");
+        return Ok(init_server_type(
+            handler_stack,
+            deletion_pruner,
+            schema_refresher,
+            ns_cache,
+            schema_catalog,
+            metrics,
+            common_state,
+            partial_write_max_errors,
+            rate_limit_config,
+        ));
+    }
+
     ////////////////////////////////////////////////////////////////////////////
     //
     // THIS CODE IS FOR TESTING ONLY.
@@ -191,9 +298,8 @@ pub async fn create_router2_server_type(
     // requests.
     //
     // This code / auto-creation is for architecture testing purposes only - a
-    // prod deployment would expect namespaces to be explicitly created and this
-    // layer would be removed.
-    let schema_catalog = Arc::clone(&catalog);
+    // prod deployment would expect namespaces to be explicitly created (see
+    // the `NamespaceService`) and run with autocreation disabled.
     let mut txn = catalog.start_transaction().await?;
     let topic_id = txn
         .kafka_topics()
@@ -222,31 +328,57 @@ pub async fn create_router2_server_type(
 
     let ns_creator = NamespaceAutocreation::new(
         catalog,
-        ns_cache,
+        Arc::clone(&ns_cache),
         topic_id,
         query_id,
-        iox_catalog::INFINITE_RETENTION_POLICY.to_owned(),
+        new_namespace_retention.to_owned(),
     );
     //
     ////////////////////////////////////////////////////////////////////////////
 
-    // Build the chain of DML handlers that forms the request processing
-    // pipeline, starting with the namespace creator (for testing purposes) and
-    // write partitioner that yields a set of partitioned batches.
-    let handler_stack = ns_creator
-        .and_then(schema_validator)
-        .and_then(partitioner)
-        // Once writes have been partitioned, they are processed in parallel.
-        //
-        // This block initialises a fan-out adaptor that parallelises partitioned
-        // writes into the handler chain it decorates (schema validation, and then
-        // into the sharded write buffer), and instruments the parallelised
-        // operation.
-        .and_then(InstrumentationDecorator::new(
-            "parallel_write",
-            Arc::clone(&metrics),
-            FanOutAdaptor::new(write_buffer),
-        ));
+    // Prepend the namespace creator (for testing purposes) to the handler
+    // chain.
+    Ok(init_server_type(
+        ns_creator.and_then(handler_stack),
+        deletion_pruner,
+        schema_refresher,
+        ns_cache,
+        schema_catalog,
+        metrics,
+        common_state,
+        partial_write_max_errors,
+        rate_limit_config,
+    ))
+}
+
+/// Initialise the router2 API delegates and server, sharing `handler_stack`
+/// between them, and start the namespace cache `deletion_pruner` and
+/// `schema_refresher`. Partial writes are checked against `ns_cache`, and
+/// namespaces updated through the gRPC API are removed from it.
+#[allow(clippy::too_many_arguments)]
+fn init_server_type<D, C>(
+    handler_stack: D,
+    deletion_pruner: DeletionPruner<C>,
+    schema_refresher: SchemaRefresher<C>,
+    ns_cache: C,
+    catalog: Arc<dyn Catalog>,
+    metrics: Arc<metric::Registry>,
+    common_state: &CommonServerState,
+    partial_write_max_errors: Option<usize>,
+    rate_limit_config: &RateLimitConfig,
+) -> Arc<dyn ServerType>
+where
+    D: DmlHandler<WriteInput = HashMap<String, MutableBatch>> + 'static,
+    C: NamespaceCache + Clone + 'static,
+{
+    // Reject requests exceeding the in-flight request limit or the namespace
+    // write rate limits before any work is done.
+    let handler_stack = RateLimiter::new(
+        handler_stack,
+        init_rate_limits(rate_limit_config),
+        Arc::new(SystemProvider::new()),
+        &*metrics,
+    );
 
     // Record the overall request handling latency
     let handler_stack =
@@ -254,26 +386,116 @@ pub async fn create_router2_server_type(
 
     // Initialise the API delegates, sharing the handler stack between them.
     let handler_stack = Arc::new(handler_stack);
-    let http = HttpDelegate::new(
+    let mut http = HttpDelegate::new(
         common_state.run_config().max_http_request_size,
         Arc::clone(&handler_stack),
         &metrics,
     );
-    let grpc = GrpcDelegate::new(handler_stack, schema_catalog, Arc::clone(&metrics));
+    if let Some(max_errors) = partial_write_max_errors {
+        http = http.with_partial_writes(max_errors, ns_cache.clone());
+    }
+    let grpc = GrpcDelegate::new(handler_stack, catalog, ns_cache, Arc::clone(&metrics));
 
     let router_server = RouterServer::new(http, grpc, metrics, common_state.trace_collector());
     let server_type = Arc::new(RouterServerType::new(router_server, common_state));
-    Ok(server_type)
+
+    let shutdown = server_type.shutdown.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = deletion_pruner.run(DELETION_PRUNE_INTERVAL) => {},
+            _ = schema_refresher.run(SCHEMA_REFRESH_INTERVAL) => {},
+            _ = shutdown.cancelled() => {},
+        }
+    });
+
+    server_type
+}
+
+/// Convert the CLI rate limit config into the [`RateLimiter`] config.
+fn init_rate_limits(config: &RateLimitConfig) -> dml_handlers::RateLimitConfig {
+    dml_handlers::RateLimitConfig {
+        max_in_flight: config.max_in_flight_requests,
+        namespace_default: NamespaceRateLimit {
+            bytes_per_second: config.namespace_max_bytes_per_second,
+            lines_per_second: config.namespace_max_lines_per_second,
+        },
+        namespace_overrides: config
+            .namespace_overrides
+            .iter()
+            .map(|o| {
+                let limit = NamespaceRateLimit {
+                    bytes_per_second: o.bytes_per_second,
+                    lines_per_second: o.lines_per_second,
+                };
+                (o.namespace.clone(), limit)
+            })
+            .collect(),
+    }
+}
+
+/// The [`Sharder`] mapping operations to sequencers: a [`HashRing`] if a
+/// [`ShardConfig`] was provided, or [`JumpHash`] otherwise.
+#[derive(Debug)]
+enum WriteSharder {
+    JumpHash(JumpHash<Arc<Sequencer>>),
+    HashRing(HashRing<Arc<Sequencer>>),
+}
+
+impl Sharder<MutableBatch> for WriteSharder {
+    type Item = Arc<Sequencer>;
+
+    fn shard(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        payload: &MutableBatch,
+    ) -> &Self::Item {
+        match self {
+            Self::JumpHash(s) => s.shard(table, namespace, payload),
+            Self::HashRing(s) => s.shard(table, namespace, payload),
+        }
+    }
+}
+
+impl BatchSharder for WriteSharder {
+    fn shard_batch(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        payload: MutableBatch,
+    ) -> Vec<(&Self::Item, MutableBatch)> {
+        match self {
+            Self::JumpHash(s) => s.shard_batch(table, namespace, payload),
+            Self::HashRing(s) => s.shard_batch(table, namespace, payload),
+        }
+    }
+}
+
+impl Sharder<DeletePredicate> for WriteSharder {
+    type Item = [Arc<Sequencer>];
+
+    fn shard(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        payload: &DeletePredicate,
+    ) -> &Self::Item {
+        match self {
+            Self::JumpHash(s) => std::slice::from_ref(s.shard(table, namespace, payload)),
+            Self::HashRing(s) => s.shard(table, namespace, payload),
+        }
+    }
 }
 
 /// Initialise the [`ShardedWriteBuffer`] with one shard per Kafka partition,
-/// using [`JumpHash`] to shard operations by their destination namespace &
-/// table name.
+/// using a [`HashRing`] configured by `shard_config` (if any) or [`JumpHash`]
+/// to shard operations by their destination namespace & table name.
 async fn init_write_buffer(
     write_buffer_config: &WriteBufferConfig,
+    shard_config: Option<ShardConfig>,
     metrics: Arc<metric::Registry>,
     trace_collector: Option<Arc<dyn TraceCollector>>,
-) -> Result<ShardedWriteBuffer<JumpHash<Arc<Sequencer>>>> {
+) -> Result<ShardedWriteBuffer<WriteSharder>> {
     let write_buffer = Arc::new(
         write_buffer_config
             .writing(Arc::clone(&metrics), trace_collector)
@@ -294,11 +516,20 @@ async fn init_write_buffer(
         "connected to write buffer topic",
     );
 
-    Ok(ShardedWriteBuffer::new(
-        shards
-            .into_iter()
-            .map(|id| Sequencer::new(id as _, Arc::clone(&write_buffer), &metrics))
-            .map(Arc::new)
-            .collect::<JumpHash<_>>(),
-    ))
+    let sequencers = shards.into_iter().map(|id| {
+        (
+            id as usize,
+            Arc::new(Sequencer::new(id as _, Arc::clone(&write_buffer), &metrics)),
+        )
+    });
+
+    let sharder = match shard_config {
+        Some(config) => {
+            info!(?config, "using hash ring sharder");
+            WriteSharder::HashRing(HashRing::new(sequencers, &config)?)
+        }
+        None => WriteSharder::JumpHash(sequencers.map(|(_, s)| s).collect()),
+    };
+
+    Ok(ShardedWriteBuffer::new(sharder))
 }
@@ -235,6 +235,27 @@ impl Client {
         Ok(Self::collect_strings(responses))
     }
 
+    /// Make a request to query::read_series_cardinality and do the
+    /// required async dance to flatten the resulting stream to the
+    /// series count
+    pub async fn read_series_cardinality(
+        &mut self,
+        request: ReadSeriesCardinalityRequest,
+    ) -> Result<i64, tonic::Status> {
+        let responses: Vec<Int64ValuesResponse> = self
+            .inner
+            .read_series_cardinality(request)
+            .await?
+            .into_inner()
+            .try_collect()
+            .await?;
+
+        Ok(responses
+            .into_iter()
+            .flat_map(|r| r.values.into_iter())
+            .sum())
+    }
+
     /// Make a request to query::read_filter and do the
     /// required async dance to flatten the resulting stream
     pub async fn read_filter(
@@ -2,7 +2,7 @@
 
 use crate::{
     compact::compact_persisting_batch, lifecycle::LifecycleHandle, persist::persist,
-    querier_handler::query,
+    querier_handler::query, wal::Wal,
 };
 use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
@@ -19,7 +19,7 @@ use metric::U64Counter;
 use mutable_batch::{column::ColumnData, MutableBatch};
 use object_store::DynObjectStore;
 use observability_deps::tracing::warn;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use predicate::Predicate;
 use query::exec::Executor;
 use schema::{selection::Selection, Schema, TIME_COLUMN_NAME};
@@ -90,6 +90,9 @@ pub enum Error {
 
     #[snafu(display("Error adding to buffer in mutable batch: {}", source))]
     BufferWrite { source: mutable_batch::Error },
+
+    #[snafu(display("Error writing to the write-ahead log: {}", source))]
+    Wal { source: crate::wal::Error },
 }
 
 /// A specialized `Error` for Ingester Data errors
@@ -110,6 +113,8 @@ pub struct IngesterData {
     pub(crate) exec: Arc<Executor>,
     /// Backoff config
     pub(crate) backoff_config: BackoffConfig,
+    /// Optional local write-ahead log of the buffered operations
+    pub(crate) wal: Option<Arc<Wal>>,
 }
 
 impl IngesterData {
@@ -119,11 +124,33 @@ impl IngesterData {
     /// created in the catalog before putting into the buffer. Writes will
     /// get logged in the lifecycle manager. If it indicates ingest should
     /// be paused, this function will return true.
+    ///
+    /// If a write-ahead log is configured, the operation is appended to it
+    /// before being buffered.
     pub async fn buffer_operation(
         &self,
         sequencer_id: SequencerId,
         dml_operation: DmlOperation,
         lifecycle_handle: &LifecycleHandle,
+    ) -> Result<bool> {
+        if let Some(wal) = &self.wal {
+            wal.append(sequencer_id, &dml_operation)
+                .await
+                .context(WalSnafu)?;
+        }
+
+        self.replay_operation(sequencer_id, dml_operation, lifecycle_handle)
+            .await
+    }
+
+    /// Store an operation replayed from the write-ahead log in the in memory
+    /// buffer. Same as [`buffer_operation`](Self::buffer_operation), except
+    /// that the operation is not appended to the write-ahead log again.
+    pub async fn replay_operation(
+        &self,
+        sequencer_id: SequencerId,
+        dml_operation: DmlOperation,
+        lifecycle_handle: &LifecycleHandle,
     ) -> Result<bool> {
         let sequencer_data = self
             .sequencers
@@ -281,7 +308,15 @@ impl Persister for IngesterData {
                     .await
             })
             .await
-            .expect("retry forever")
+            .expect("retry forever");
+
+        // Everything before `sequence_number` is now in the catalog, so the
+        // WAL segments only containing those operations are no longer needed.
+        if let Some(wal) = &self.wal {
+            if let Err(e) = wal.truncate(sequencer_id, sequence_number).await {
+                warn!(error=%e, %sequencer_id, "failed to truncate write-ahead log");
+            }
+        }
     }
 }
 
@@ -293,6 +328,9 @@ pub struct SequencerData {
 
     metrics: Arc<metric::Registry>,
     namespace_count: U64Counter,
+
+    // The sequence number of the last operation buffered for this sequencer
+    last_sequence_number: Mutex<Option<SequenceNumber>>,
 }
 
 impl SequencerData {
@@ -309,6 +347,7 @@ impl SequencerData {
             namespaces: Default::default(),
             metrics,
             namespace_count,
+            last_sequence_number: Default::default(),
         }
     }
 
@@ -319,6 +358,7 @@ impl SequencerData {
             namespaces: RwLock::new(namespaces),
             metrics: Default::default(),
             namespace_count: Default::default(),
+            last_sequence_number: Default::default(),
         }
     }
 
@@ -334,6 +374,12 @@ impl SequencerData {
         lifecycle_handle: &LifecycleHandle,
         executor: &Executor,
     ) -> Result<bool> {
+        if let Some(sequence) = dml_operation.meta().sequence() {
+            let sequence_number = SequenceNumber::new(sequence.number as i64);
+            let mut last = self.last_sequence_number.lock();
+            *last = (*last).max(Some(sequence_number));
+        }
+
         let namespace_data = match self.namespace(dml_operation.namespace()) {
             Some(d) => d,
             None => {
@@ -359,6 +405,48 @@ impl SequencerData {
         n.get(namespace).cloned()
     }
 
+    /// Return the sequence number of the last operation buffered for this
+    /// sequencer, if any.
+    pub fn last_sequence_number(&self) -> Option<SequenceNumber> {
+        *self.last_sequence_number.lock()
+    }
+
+    /// Return the names of all partitions buffered for this sequencer.
+    pub(crate) async fn partition_names(&self) -> BTreeMap<PartitionId, PartitionNames> {
+        let namespaces: Vec<_> = self
+            .namespaces
+            .read()
+            .iter()
+            .map(|(name, data)| (name.clone(), Arc::clone(data)))
+            .collect();
+
+        let mut names = BTreeMap::new();
+        for (namespace, namespace_data) in namespaces {
+            let tables: Vec<_> = namespace_data
+                .tables
+                .read()
+                .iter()
+                .map(|(name, data)| (name.clone(), Arc::clone(data)))
+                .collect();
+
+            for (table, table_data) in tables {
+                let table_data = table_data.read().await;
+                for (partition_key, partition_data) in &table_data.partition_data {
+                    names.insert(
+                        partition_data.id,
+                        PartitionNames {
+                            namespace: namespace.clone(),
+                            table: table.clone(),
+                            partition_key: partition_key.clone(),
+                        },
+                    );
+                }
+            }
+        }
+
+        names
+    }
+
     /// Retrieves the namespace from the catalog and initializes an empty buffer, or
     /// retrieves the buffer if some other caller gets it first
     async fn insert_namespace(
@@ -389,6 +477,17 @@ impl SequencerData {
     }
 }
 
+/// The names identifying a partition buffered by the ingester.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionNames {
+    /// Name of the namespace the partition belongs to
+    pub namespace: String,
+    /// Name of the table the partition belongs to
+    pub table: String,
+    /// The partition key
+    pub partition_key: String,
+}
+
 /// Data of a Namespace that belongs to a given Shard
 #[derive(Debug)]
 pub struct NamespaceData {
@@ -449,6 +548,7 @@ impl NamespaceData {
         match dml_operation {
             DmlOperation::Write(write) => {
                 let mut pause_writes = false;
+                let partition_key = write.partition_key().map(ToOwned::to_owned);
 
                 for (t, b) in write.into_tables() {
                     let table_data = match self.table_data(&t) {
@@ -461,6 +561,7 @@ impl NamespaceData {
                         .buffer_table_write(
                             sequence_number,
                             b,
+                            partition_key.as_deref(),
                             sequencer_id,
                             catalog,
                             lifecycle_handle,
@@ -674,6 +775,7 @@ impl TableData {
         &mut self,
         sequence_number: SequenceNumber,
         batch: MutableBatch,
+        partition_key: Option<&str>,
         sequencer_id: SequencerId,
         catalog: &dyn Catalog,
         lifecycle_handle: &LifecycleHandle,
@@ -684,20 +786,27 @@ impl TableData {
             }
         }
 
-        let (_, col) = batch
-            .columns()
-            .find(|(name, _)| *name == TIME_COLUMN_NAME)
-            .unwrap();
-        let timestamp = match col.data() {
-            ColumnData::I64(_, s) => s.min.unwrap(),
-            _ => return Err(Error::TimeColumnNotPresent),
-        };
+        // Use the partition key assigned by the router, falling back to the
+        // date of the earliest timestamp for writes that carry no key.
+        let partition_key = match partition_key {
+            Some(key) => key.to_owned(),
+            None => {
+                let (_, col) = batch
+                    .columns()
+                    .find(|(name, _)| *name == TIME_COLUMN_NAME)
+                    .unwrap();
+                let timestamp = match col.data() {
+                    ColumnData::I64(_, s) => s.min.unwrap(),
+                    _ => return Err(Error::TimeColumnNotPresent),
+                };
 
-        let partition_key = format!(
-            "{}",
-            Utc.timestamp_nanos(timestamp)
-                .format_with_items(StrftimeItems::new("%Y-%m-%d"))
-        );
+                format!(
+                    "{}",
+                    Utc.timestamp_nanos(timestamp)
+                        .format_with_items(StrftimeItems::new("%Y-%m-%d"))
+                )
+            }
+        };
 
         let partition_data = match self.partition_data.get_mut(&partition_key) {
             Some(p) => p,
@@ -1408,6 +1517,7 @@ mod tests {
             sequencers,
             exec: Arc::new(Executor::new(1)),
             backoff_config: BackoffConfig::default(),
+            wal: None,
         });
 
         let schema = NamespaceSchema::new(namespace.id, kafka_topic.id, query_pool.id);
@@ -1454,6 +1564,94 @@ mod tests {
         assert!(should_pause);
     }
 
+    #[tokio::test]
+    async fn buffer_write_uses_router_partition_key() {
+        let metrics = Arc::new(metric::Registry::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let mut repos = catalog.repositories().await;
+        let kafka_topic = repos.kafka_topics().create_or_get("whatevs").await.unwrap();
+        let query_pool = repos.query_pools().create_or_get("whatevs").await.unwrap();
+        let kafka_partition = KafkaPartition::new(0);
+        let namespace = repos
+            .namespaces()
+            .create("foo", "inf", kafka_topic.id, query_pool.id)
+            .await
+            .unwrap();
+        let sequencer1 = repos
+            .sequencers()
+            .create_or_get(&kafka_topic, kafka_partition)
+            .await
+            .unwrap();
+
+        let mut sequencers = BTreeMap::new();
+        sequencers.insert(sequencer1.id, SequencerData::new(Arc::clone(&metrics)));
+
+        let object_store: Arc<DynObjectStore> = Arc::new(ObjectStoreImpl::new_in_memory());
+
+        let data = Arc::new(IngesterData {
+            object_store: Arc::clone(&object_store),
+            catalog: Arc::clone(&catalog),
+            sequencers,
+            exec: Arc::new(Executor::new(1)),
+            backoff_config: BackoffConfig::default(),
+            wal: None,
+        });
+
+        let schema = NamespaceSchema::new(namespace.id, kafka_topic.id, query_pool.id);
+
+        let ignored_ts = Time::from_timestamp_millis(42);
+
+        // One write carries the router-assigned key, the other falls back to
+        // the date of its timestamp.
+        let w1 = DmlWrite::new(
+            "foo",
+            lines_to_batches("mem,host=a foo=1 10", 0).unwrap(),
+            DmlMeta::sequenced(Sequence::new(1, 1), ignored_ts, None, 50),
+        )
+        .with_partition_key("host_a");
+        let w2 = DmlWrite::new(
+            "foo",
+            lines_to_batches("mem,host=b foo=1 10", 0).unwrap(),
+            DmlMeta::sequenced(Sequence::new(1, 2), ignored_ts, None, 50),
+        );
+
+        let _ = validate_or_insert_schema(w1.tables(), &schema, repos.deref_mut())
+            .await
+            .unwrap()
+            .unwrap();
+
+        std::mem::drop(repos);
+        let manager = LifecycleManager::new(
+            LifecycleConfig::new(
+                1024 * 1024,
+                0,
+                0,
+                Duration::from_secs(1),
+                Duration::from_secs(1),
+            ),
+            metrics,
+            Arc::new(SystemProvider::new()),
+        );
+        for w in [w1, w2] {
+            data.buffer_operation(sequencer1.id, DmlOperation::Write(w), &manager.handle())
+                .await
+                .unwrap();
+        }
+
+        let mut keys = catalog
+            .repositories()
+            .await
+            .partitions()
+            .list_by_sequencer(sequencer1.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.partition_key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["1970-01-01".to_string(), "host_a".to_string()]);
+    }
+
     #[tokio::test]
     async fn persist() {
         let metrics = Arc::new(metric::Registry::new());
@@ -1489,6 +1687,7 @@ mod tests {
             sequencers,
             exec: Arc::new(Executor::new(1)),
             backoff_config: BackoffConfig::default(),
+            wal: None,
         });
 
         let schema = NamespaceSchema::new(namespace.id, kafka_topic.id, query_pool.id);
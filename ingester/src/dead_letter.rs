@@ -0,0 +1,398 @@
+//! Capture of write buffer operations the ingester had to skip.
+//!
+//! The ingester skips messages it cannot decode and operations it cannot buffer, so that a
+//! single bad message does not block a sequencer. If a [`DeadLetterSink`] is configured, every
+//! skipped message is stored as a JSON encoded [`DeadLetter`] at
+//!
+//! ```text
+//! <prefix>/<kafka topic>/<kafka partition>/<sequence number>.json
+//! ```
+//!
+//! in the object store, along with its raw headers and payload. Once the cause has been fixed,
+//! dead letters can be decoded again and re-injected into the write buffer.
+
+use bytes::Bytes;
+use data_types2::{KafkaPartition, Sequence};
+use dml::{DmlMeta, DmlOperation};
+use futures::TryStreamExt;
+use object_store::{
+    path::{ObjectStorePath, Path},
+    DynObjectStore,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{fmt::Display, sync::Arc};
+use time::Time;
+use write_buffer::{core::WriteBufferError, ContentType, IoxHeaders};
+
+#[derive(Debug, Snafu)]
+#[allow(missing_copy_implementations, missing_docs)]
+pub enum Error {
+    #[snafu(display("Error encoding skipped operation: {}", source))]
+    EncodeOperation { source: WriteBufferError },
+
+    #[snafu(display("Error serializing dead letter: {}", source))]
+    Serialize { source: serde_json::Error },
+
+    #[snafu(display("Error deserializing dead letter {}: {}", path, source))]
+    Deserialize {
+        source: serde_json::Error,
+        path: String,
+    },
+
+    #[snafu(display("Error writing dead letter {}: {}", path, source))]
+    Write {
+        source: object_store::Error,
+        path: String,
+    },
+
+    #[snafu(display("Error reading dead letter {}: {}", path, source))]
+    Read {
+        source: object_store::Error,
+        path: String,
+    },
+
+    #[snafu(display("Error listing dead letters: {}", source))]
+    List { source: object_store::Error },
+
+    #[snafu(display("Error deleting dead letter {}: {}", path, source))]
+    Delete {
+        source: object_store::Error,
+        path: String,
+    },
+}
+
+/// A specialized `Error` for dead letter errors
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Why the ingester skipped a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The message read from the write buffer could not be decoded.
+    Decode,
+    /// The decoded operation could not be buffered.
+    Buffer,
+}
+
+impl SkipReason {
+    /// The name of the reason, as used in metric attributes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Decode => "decode",
+            Self::Buffer => "buffer",
+        }
+    }
+}
+
+/// A message header of a [`DeadLetter`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetterHeader {
+    /// Header name
+    pub name: String,
+    /// Raw header value
+    #[serde(with = "base64_bytes")]
+    pub value: Vec<u8>,
+}
+
+/// A write buffer message skipped by the ingester.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The kafka topic the message was read from
+    pub kafka_topic: String,
+    /// The kafka partition the message was read from
+    pub kafka_partition: i32,
+    /// The sequence number of the message
+    pub sequence_number: u64,
+    /// Why the message was skipped
+    pub reason: SkipReason,
+    /// The error the message was skipped with
+    pub error: String,
+    /// Raw message headers
+    pub headers: Vec<DeadLetterHeader>,
+    /// Raw message payload
+    #[serde(with = "base64_bytes")]
+    pub payload: Vec<u8>,
+}
+
+impl DeadLetter {
+    /// Create a dead letter for a write buffer message that could not be decoded.
+    ///
+    /// Returns `None` if `error` does not carry the raw message, i.e. if it was not raised while
+    /// decoding a message.
+    pub fn undecodable(
+        kafka_topic: &str,
+        kafka_partition: KafkaPartition,
+        error: &WriteBufferError,
+    ) -> Option<Self> {
+        let message = error.undecodable()?;
+
+        Some(Self {
+            kafka_topic: kafka_topic.to_string(),
+            kafka_partition: kafka_partition.get(),
+            sequence_number: message.sequence.number,
+            reason: SkipReason::Decode,
+            error: error.to_string(),
+            headers: message
+                .headers
+                .iter()
+                .map(|(name, value)| DeadLetterHeader {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            payload: message.payload.clone(),
+        })
+    }
+
+    /// Create a dead letter for an operation that could not be buffered, re-encoding it the same
+    /// way it is encoded in the write buffer.
+    pub fn unbuffered(
+        kafka_topic: &str,
+        kafka_partition: KafkaPartition,
+        op: &DmlOperation,
+        error: &dyn Display,
+    ) -> Result<Self> {
+        let mut payload = vec![];
+        write_buffer::encode_operation(op.namespace(), op, &mut payload)
+            .context(EncodeOperationSnafu)?;
+
+        let headers = IoxHeaders::new(
+            ContentType::Protobuf,
+            op.meta().span_context().cloned(),
+            op.namespace().to_string(),
+        );
+
+        Ok(Self {
+            kafka_topic: kafka_topic.to_string(),
+            kafka_partition: kafka_partition.get(),
+            sequence_number: op.meta().sequence().map(|s| s.number).unwrap_or_default(),
+            reason: SkipReason::Buffer,
+            error: error.to_string(),
+            headers: headers
+                .headers()
+                .map(|(name, value)| DeadLetterHeader {
+                    name: name.to_string(),
+                    value: value.as_bytes().to_vec(),
+                })
+                .collect(),
+            payload,
+        })
+    }
+
+    /// Decode the operation of this dead letter.
+    ///
+    /// The returned operation is unsequenced, ready to be written to the write buffer again.
+    pub fn operation(&self) -> Result<DmlOperation, WriteBufferError> {
+        let headers = IoxHeaders::from_headers(
+            self.headers.iter().map(|h| (h.name.as_str(), &h.value)),
+            None,
+        )?;
+
+        let mut op = write_buffer::decode(
+            &self.payload,
+            headers,
+            Sequence::new(self.kafka_partition as u32, self.sequence_number),
+            Time::from_timestamp_nanos(0),
+            self.payload.len(),
+        )?;
+        op.set_meta(DmlMeta::unsequenced(op.meta().span_context().cloned()));
+
+        Ok(op)
+    }
+}
+
+/// Stores [`DeadLetter`]s in the object store.
+#[derive(Debug, Clone)]
+pub struct DeadLetterSink {
+    object_store: Arc<DynObjectStore>,
+    prefix: String,
+}
+
+impl DeadLetterSink {
+    /// Create a sink storing dead letters below `prefix`, a `/` separated object store path.
+    pub fn new(object_store: Arc<DynObjectStore>, prefix: impl Into<String>) -> Self {
+        Self {
+            object_store,
+            prefix: prefix.into(),
+        }
+    }
+
+    fn prefix_path(&self) -> Path {
+        let mut path = self.object_store.new_path();
+        for dir in self.prefix.split('/').filter(|dir| !dir.is_empty()) {
+            path.push_dir(dir);
+        }
+        path
+    }
+
+    fn path(&self, letter: &DeadLetter) -> Path {
+        let mut path = self.prefix_path();
+        path.push_dir(&letter.kafka_topic);
+        path.push_dir(letter.kafka_partition.to_string());
+        path.set_file_name(format!("{}.json", letter.sequence_number));
+        path
+    }
+
+    /// Store `letter`, replacing a dead letter previously recorded for the same message.
+    pub async fn record(&self, letter: &DeadLetter) -> Result<()> {
+        let path = self.path(letter);
+        let data = serde_json::to_vec_pretty(letter).context(SerializeSnafu)?;
+
+        self.object_store
+            .put(&path, Bytes::from(data))
+            .await
+            .context(WriteSnafu {
+                path: path.to_raw(),
+            })
+    }
+
+    /// List all stored dead letters, ordered by topic, partition and sequence number.
+    pub async fn list(&self) -> Result<Vec<DeadLetter>> {
+        let prefix = self.prefix_path();
+        let paths: Vec<Path> = self
+            .object_store
+            .list(Some(&prefix))
+            .await
+            .context(ListSnafu)?
+            .try_concat()
+            .await
+            .context(ListSnafu)?;
+
+        let mut letters: Vec<DeadLetter> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let data = match self.object_store.get(&path).await {
+                Ok(result) => result.bytes().await,
+                Err(e) => Err(e),
+            }
+            .context(ReadSnafu {
+                path: path.to_raw(),
+            })?;
+
+            letters.push(serde_json::from_slice(&data).context(DeserializeSnafu {
+                path: path.to_raw(),
+            })?);
+        }
+
+        letters.sort_by(|a, b| {
+            (&a.kafka_topic, a.kafka_partition, a.sequence_number).cmp(&(
+                &b.kafka_topic,
+                b.kafka_partition,
+                b.sequence_number,
+            ))
+        });
+
+        Ok(letters)
+    }
+
+    /// Delete a stored dead letter, e.g. after it was re-injected.
+    pub async fn remove(&self, letter: &DeadLetter) -> Result<()> {
+        let path = self.path(letter);
+        self.object_store.delete(&path).await.context(DeleteSnafu {
+            path: path.to_raw(),
+        })
+    }
+}
+
+/// (De)serialize raw bytes as base64 strings.
+mod base64_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        base64::decode(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dml::{test_util::assert_op_eq, DmlWrite};
+    use mutable_batch_lp::lines_to_batches;
+    use object_store::ObjectStoreImpl;
+    use write_buffer::core::UndecodableMessage;
+
+    fn sink() -> DeadLetterSink {
+        DeadLetterSink::new(
+            Arc::new(ObjectStoreImpl::new_in_memory()),
+            "dead_letters/ingester",
+        )
+    }
+
+    fn write(sequence_number: u64) -> DmlOperation {
+        DmlOperation::Write(DmlWrite::new(
+            "bananas",
+            lines_to_batches("mem foo=1 10", 0).unwrap(),
+            DmlMeta::sequenced(
+                Sequence::new(1, sequence_number),
+                Time::from_timestamp_millis(42),
+                None,
+                50,
+            ),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_unbuffered_roundtrip() {
+        let sink = sink();
+        let op = write(7);
+
+        let letter =
+            DeadLetter::unbuffered("topic", KafkaPartition::new(1), &op, &"no space").unwrap();
+        assert_eq!(letter.sequence_number, 7);
+        assert_eq!(letter.reason, SkipReason::Buffer);
+        assert_eq!(letter.error, "no space");
+
+        sink.record(&letter).await.unwrap();
+        let listed = sink.list().await.unwrap();
+        assert_eq!(listed, vec![letter.clone()]);
+
+        let decoded = listed[0].operation().unwrap();
+        assert_eq!(decoded.meta().sequence(), None);
+        assert_op_eq(
+            &decoded,
+            &DmlOperation::Write(DmlWrite::new(
+                "bananas",
+                lines_to_batches("mem foo=1 10", 0).unwrap(),
+                DmlMeta::unsequenced(None),
+            )),
+        );
+
+        sink.remove(&letter).await.unwrap();
+        assert!(sink.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_undecodable() {
+        let sink = sink();
+
+        let err = WriteBufferError::invalid_data("No content type header");
+        assert!(DeadLetter::undecodable("topic", KafkaPartition::new(2), &err).is_none());
+
+        let err = err.with_undecodable(UndecodableMessage {
+            sequence: Sequence::new(2, 3),
+            headers: vec![("iox-namespace".to_string(), b"bananas".to_vec())],
+            payload: vec![0, 1, 2, 255],
+        });
+        let letter = DeadLetter::undecodable("topic", KafkaPartition::new(2), &err).unwrap();
+        assert_eq!(letter.sequence_number, 3);
+        assert_eq!(letter.reason, SkipReason::Decode);
+        assert_eq!(letter.payload, vec![0, 1, 2, 255]);
+
+        // still undecodable
+        letter.operation().unwrap_err();
+
+        // letters are listed in sequence order
+        let mut later = letter.clone();
+        later.sequence_number = 10;
+        sink.record(&later).await.unwrap();
+        sink.record(&letter).await.unwrap();
+        assert_eq!(sink.list().await.unwrap(), vec![letter, later]);
+    }
+}
@@ -1,14 +1,22 @@
 //! Ingest handler
 
 use crate::{
-    data::{IngesterData, IngesterQueryResponse, SequencerData},
-    lifecycle::{run_lifecycle_manager, LifecycleConfig, LifecycleHandle, LifecycleManager},
+    data::{IngesterData, IngesterQueryResponse, PartitionNames, SequencerData},
+    dead_letter::{self, DeadLetter, DeadLetterSink, SkipReason},
+    lifecycle::{
+        run_lifecycle_manager, LifecycleConfig, LifecycleHandle, LifecycleManager,
+        PartitionLifecycleStats,
+    },
     poison::{PoisonCabinet, PoisonPill},
     querier_handler::prepare_data_to_querier,
+    wal::Wal,
 };
 use async_trait::async_trait;
 use backoff::BackoffConfig;
-use data_types2::{IngesterQueryRequest, KafkaPartition, KafkaTopic, Sequencer, SequencerId};
+use data_types2::{
+    IngesterQueryRequest, KafkaPartition, KafkaTopic, PartitionId, SequenceNumber, Sequencer,
+    SequencerId,
+};
 use db::write_buffer::metrics::{SequencerMetrics, WriteBufferIngestMetrics};
 use dml::DmlOperation;
 use futures::{
@@ -53,6 +61,9 @@ pub enum Error {
     WriteBuffer {
         source: write_buffer::core::WriteBufferError,
     },
+
+    #[snafu(display("Error reading write-ahead log: {}", source))]
+    WalReplay { source: crate::wal::Error },
 }
 
 /// When the lifecycle manager indicates that ingest should be paused because of
@@ -72,6 +83,21 @@ pub trait IngestHandler: Send + Sync {
         request: IngesterQueryRequest,
     ) -> Result<IngesterQueryResponse, crate::querier_handler::Error>;
 
+    /// Return the partitions buffered by the ingester, as tracked by the
+    /// lifecycle manager.
+    async fn buffered_partitions(&self) -> Vec<BufferedPartition>;
+
+    /// Persist the partition on the next run of the lifecycle manager.
+    /// Returns false if the partition is not buffered.
+    fn persist_partition(&self, partition_id: PartitionId) -> bool;
+
+    /// Persist all buffered partitions on the next run of the lifecycle
+    /// manager, returning the number of partitions.
+    fn persist_all(&self) -> usize;
+
+    /// Return the ingest progress of each sequencer.
+    async fn sequencer_progress(&self) -> Vec<SequencerProgress>;
+
     /// Wait until the handler finished  to shutdown.
     ///
     /// Use [`shutdown`](Self::shutdown) to trigger a shutdown.
@@ -81,6 +107,30 @@ pub trait IngestHandler: Send + Sync {
     fn shutdown(&self);
 }
 
+/// A partition buffered by the ingester.
+#[derive(Debug, Clone)]
+pub struct BufferedPartition {
+    /// The lifecycle stats of the partition
+    pub stats: PartitionLifecycleStats,
+    /// The names identifying the partition. `None` if the partition was
+    /// removed from the buffer in the meantime.
+    pub names: Option<PartitionNames>,
+}
+
+/// The ingest progress of a sequencer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequencerProgress {
+    /// The sequencer
+    pub sequencer_id: SequencerId,
+    /// The write buffer partition of the sequencer
+    pub kafka_partition: KafkaPartition,
+    /// The sequence number of the last operation buffered, if any
+    pub last_sequence_number: Option<SequenceNumber>,
+    /// The sequence number the write buffer will assign to the next operation
+    /// written to the sequencer. `None` if it could not be fetched.
+    pub high_watermark: Option<u64>,
+}
+
 /// A [`JoinHandle`] that can be cloned
 type SharedJoinHandle = Shared<BoxFuture<'static, Result<(), Arc<JoinError>>>>;
 
@@ -108,10 +158,26 @@ pub struct IngestHandlerImpl {
 
     /// The cache and buffered data for the ingester
     data: Arc<IngesterData>,
+
+    /// Handle to the lifecycle manager state
+    lifecycle_handle: LifecycleHandle,
+
+    /// The write buffer the sequencers are read from
+    write_buffer: Arc<dyn WriteBufferReading>,
+
+    /// The write buffer partition of each sequencer
+    kafka_partitions: BTreeMap<SequencerId, KafkaPartition>,
 }
 
 impl IngestHandlerImpl {
     /// Initialize the Ingester
+    ///
+    /// If a write-ahead log is given, the operations it contains for each
+    /// sequencer are buffered before the write buffer stream resumes.
+    /// Replayed operations that cannot be buffered are skipped.
+    ///
+    /// If a dead letter sink is given, write buffer operations that are
+    /// skipped because they cannot be decoded or buffered are recorded in it.
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
         lifecycle_config: LifecycleConfig,
@@ -123,6 +189,8 @@ impl IngestHandlerImpl {
         exec: Arc<Executor>,
         metric_registry: Arc<metric::Registry>,
         time_provider: Arc<dyn TimeProvider>,
+        wal: Option<Arc<Wal>>,
+        dead_letter_sink: Option<DeadLetterSink>,
     ) -> Result<Self> {
         // build the initial ingester data state
         let mut sequencers = BTreeMap::new();
@@ -135,6 +203,7 @@ impl IngestHandlerImpl {
             sequencers,
             exec,
             backoff_config: BackoffConfig::default(),
+            wal: wal.clone(),
         });
 
         let ingester_data = Arc::clone(&data);
@@ -165,6 +234,11 @@ impl IngestHandlerImpl {
         let mut join_handles = Vec::with_capacity(sequencer_states.len() + 1);
         join_handles.push(("lifecycle manager".to_owned(), shared_handle(handle)));
 
+        let kafka_partitions = sequencer_states
+            .iter()
+            .map(|(kafka_partition, sequencer)| (sequencer.id, *kafka_partition))
+            .collect();
+
         for (kafka_partition, sequencer) in sequencer_states {
             let worker_name = format!("stream handler for partition {}", kafka_partition.get());
             let metrics = ingest_metrics.new_sequencer_metrics(kafka_partition.get() as u32);
@@ -177,8 +251,26 @@ impl IngestHandlerImpl {
                 .await
                 .context(WriteBufferSnafu)?;
 
+            let mut start_sequence_number = sequencer.min_unpersisted_sequence_number;
+            if let Some(wal) = &wal {
+                if let Some(last) = replay_wal(
+                    wal,
+                    &ingester_data,
+                    &kafka_topic_name,
+                    kafka_partition,
+                    sequencer.id,
+                    SequenceNumber::new(sequencer.min_unpersisted_sequence_number),
+                    &lifecycle_handle,
+                    dead_letter_sink.as_ref(),
+                )
+                .await?
+                {
+                    start_sequence_number = start_sequence_number.max(last.get() + 1);
+                }
+            }
+
             stream_handler
-                .seek(sequencer.min_unpersisted_sequence_number as u64)
+                .seek(start_sequence_number as u64)
                 .await
                 .context(WriteBufferSnafu)?;
 
@@ -195,6 +287,7 @@ impl IngestHandlerImpl {
                 shutdown.clone(),
                 Arc::clone(&poison_cabinet),
                 Arc::clone(&time_provider),
+                dead_letter_sink.clone(),
             ));
             join_handles.push((worker_name, shared_handle(handle)));
         }
@@ -205,10 +298,80 @@ impl IngestHandlerImpl {
             join_handles,
             shutdown,
             poison_cabinet,
+            lifecycle_handle,
+            write_buffer,
+            kafka_partitions,
         })
     }
 }
 
+/// Buffer the operations in the write-ahead log of `sequencer_id` that have
+/// not been persisted yet, returning the sequence number of the last one.
+///
+/// Like operations read from the write buffer, operations that cannot be
+/// buffered (for example because their namespace no longer exists) are
+/// skipped and recorded in the dead letter sink rather than failing startup.
+#[allow(clippy::too_many_arguments)]
+async fn replay_wal(
+    wal: &Wal,
+    ingester_data: &IngesterData,
+    kafka_topic: &str,
+    kafka_partition: KafkaPartition,
+    sequencer_id: SequencerId,
+    min_unpersisted_sequence_number: SequenceNumber,
+    lifecycle_handle: &LifecycleHandle,
+    dead_letter_sink: Option<&DeadLetterSink>,
+) -> Result<Option<SequenceNumber>> {
+    let mut ops = wal
+        .replay(sequencer_id, min_unpersisted_sequence_number)
+        .await
+        .context(WalReplaySnafu)?;
+    info!(%sequencer_id, "replaying write-ahead log");
+
+    let mut last = None;
+    let mut n_ops = 0;
+    let mut n_skipped = 0;
+    while let Some(op) = ops.next().await {
+        let op = op.context(WalReplaySnafu)?;
+        let sequence_number = op
+            .meta()
+            .sequence()
+            .map(|s| SequenceNumber::new(s.number as i64));
+        last = last.max(sequence_number);
+        n_ops += 1;
+
+        let should_pause = match ingester_data
+            .replay_operation(sequencer_id, op.clone(), lifecycle_handle)
+            .await
+        {
+            Ok(should_pause) => should_pause,
+            Err(e) => {
+                warn!(
+                    %e,
+                    %kafka_topic,
+                    %sequencer_id,
+                    "Error buffering operation replayed from the write-ahead log, skipping"
+                );
+                n_skipped += 1;
+                if let Some(sink) = dead_letter_sink {
+                    let letter = DeadLetter::unbuffered(kafka_topic, kafka_partition, &op, &e);
+                    record_dead_letter(sink, letter).await;
+                }
+                continue;
+            }
+        };
+        if should_pause {
+            warn!(%sequencer_id, "pausing write-ahead log replay until persistence has run");
+            while !lifecycle_handle.can_resume_ingest() {
+                tokio::time::sleep(INGEST_PAUSE_DELAY).await;
+            }
+        }
+    }
+    info!(%sequencer_id, n_ops, n_skipped, "replayed write-ahead log");
+
+    Ok(last)
+}
+
 #[async_trait]
 impl IngestHandler for IngestHandlerImpl {
     async fn query(
@@ -218,6 +381,60 @@ impl IngestHandler for IngestHandlerImpl {
         prepare_data_to_querier(&self.data, &request).await
     }
 
+    async fn buffered_partitions(&self) -> Vec<BufferedPartition> {
+        let mut names = BTreeMap::new();
+        for sequencer_data in self.data.sequencers.values() {
+            names.append(&mut sequencer_data.partition_names().await);
+        }
+
+        self.lifecycle_handle
+            .stats()
+            .partition_stats
+            .into_iter()
+            .map(|stats| BufferedPartition {
+                names: names.remove(&stats.partition_id),
+                stats,
+            })
+            .collect()
+    }
+
+    fn persist_partition(&self, partition_id: PartitionId) -> bool {
+        self.lifecycle_handle.request_persist(partition_id)
+    }
+
+    fn persist_all(&self) -> usize {
+        self.lifecycle_handle.request_persist_all()
+    }
+
+    async fn sequencer_progress(&self) -> Vec<SequencerProgress> {
+        let mut progress = Vec::with_capacity(self.kafka_partitions.len());
+        for (sequencer_id, kafka_partition) in &self.kafka_partitions {
+            let high_watermark = match self
+                .write_buffer
+                .fetch_high_watermark(kafka_partition.get() as u32)
+                .await
+            {
+                Ok(w) => Some(w),
+                Err(e) => {
+                    warn!(%e, %kafka_partition, "Error while reading sequencer watermark");
+                    None
+                }
+            };
+
+            progress.push(SequencerProgress {
+                sequencer_id: *sequencer_id,
+                kafka_partition: *kafka_partition,
+                last_sequence_number: self
+                    .data
+                    .sequencers
+                    .get(sequencer_id)
+                    .and_then(|s| s.last_sequence_number()),
+                high_watermark,
+            });
+        }
+        progress
+    }
+
     async fn join(&self) {
         // Need to poll handlers unordered to detect early exists of any worker in the list.
         let mut unordered: FuturesUnordered<_> = self
@@ -283,6 +500,7 @@ async fn stream_in_sequenced_entries(
     shutdown: CancellationToken,
     poison_cabinet: Arc<PoisonCabinet>,
     time_provider: Arc<dyn TimeProvider>,
+    dead_letter_sink: Option<DeadLetterSink>,
 ) {
     let mut watermark_last_updated: Option<Instant> = None;
     let mut watermark = 0_u64;
@@ -310,6 +528,17 @@ async fn stream_in_sequenced_entries(
         ("sequencer_id", sequencer_id_attr.into()),
         ("kafka_topic", kafka_topic.clone().into()),
     ]);
+    let skipped_operations = metric_registry.register_metric::<U64Counter>(
+        "ingester_skipped_operations",
+        "Number of write buffer operations skipped because they could not be decoded or buffered",
+    );
+    let skipped_recorder = |reason: SkipReason| {
+        let mut attributes = attributes.clone();
+        attributes.insert("reason", reason.as_str());
+        skipped_operations.recorder(attributes)
+    };
+    let skipped_decode = skipped_recorder(SkipReason::Decode);
+    let skipped_buffer = skipped_recorder(SkipReason::Buffer);
     let time_to_be_readable_ms = metric_registry.register_metric::<U64Gauge>(
         "ingester_ttbr_ms",
         "Duration of time between producer writing to consumer putting into queryable cache in milliseconds",
@@ -391,6 +620,12 @@ async fn stream_in_sequenced_entries(
                     %kafka_partition,
                     "Error converting write buffer data to SequencedEntry",
                 );
+                if let Some(letter) = DeadLetter::undecodable(&kafka_topic, kafka_partition, &e) {
+                    skipped_decode.inc(1);
+                    if let Some(sink) = &dead_letter_sink {
+                        record_dead_letter(sink, Ok(letter)).await;
+                    }
+                }
                 continue;
             }
         };
@@ -445,11 +680,31 @@ async fn stream_in_sequenced_entries(
                     "Error storing SequencedEntry from write buffer in ingester buffer"
                 );
                 span_recorder.error("cannot store write");
+
+                skipped_buffer.inc(1);
+                if let Some(sink) = &dead_letter_sink {
+                    let letter =
+                        DeadLetter::unbuffered(&kafka_topic, kafka_partition, &dml_operation, &e);
+                    record_dead_letter(sink, letter).await;
+                }
             }
         }
     }
 }
 
+/// Store an operation skipped by the write buffer stream in the dead letter sink.
+///
+/// Failures are logged rather than returned so that they don't block ingest.
+async fn record_dead_letter(sink: &DeadLetterSink, letter: dead_letter::Result<DeadLetter>) {
+    let result = match letter {
+        Ok(letter) => sink.record(&letter).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!(%e, "Error recording skipped operation in dead letter sink");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,7 +717,10 @@ mod tests {
     use object_store::ObjectStoreImpl;
     use std::{num::NonZeroU32, ops::DerefMut};
     use time::{MockProvider, Time};
-    use write_buffer::mock::{MockBufferForReading, MockBufferSharedState};
+    use write_buffer::{
+        core::{UndecodableMessage, WriteBufferError},
+        mock::{MockBufferForReading, MockBufferSharedState},
+    };
 
     #[tokio::test]
     async fn read_from_write_buffer_write_to_mutable_buffer() {
@@ -656,6 +914,76 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn records_skipped_operations_as_dead_letters() {
+        let ingester = TestIngester::new(Time::from_timestamp_millis(0)).await;
+
+        // a message that cannot be decoded...
+        ingester.write_buffer_state.push_error(
+            WriteBufferError::invalid_data("No content type header").with_undecodable(
+                UndecodableMessage {
+                    sequence: Sequence::new(0, 1),
+                    headers: vec![],
+                    payload: b"bananas".to_vec(),
+                },
+            ),
+            0,
+        );
+        // ...and a write to a namespace that does not exist
+        ingester.write_buffer_state.push_write(DmlWrite::new(
+            "bananas",
+            lines_to_batches("mem foo=1 10", 0).unwrap(),
+            DmlMeta::sequenced(
+                Sequence::new(0, 2),
+                Time::from_timestamp_millis(42),
+                None,
+                50,
+            ),
+        ));
+
+        let letters = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let letters = ingester.dead_letters.list().await.unwrap();
+                if letters.len() == 2 {
+                    break letters;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("timeout");
+
+        assert_eq!(letters[0].sequence_number, 1);
+        assert_eq!(letters[0].reason, SkipReason::Decode);
+        assert_eq!(letters[0].payload, b"bananas");
+
+        assert_eq!(letters[1].sequence_number, 2);
+        assert_eq!(letters[1].reason, SkipReason::Buffer);
+        dml::test_util::assert_op_eq(
+            &letters[1].operation().unwrap(),
+            &DmlOperation::Write(DmlWrite::new(
+                "bananas",
+                lines_to_batches("mem foo=1 10", 0).unwrap(),
+                DmlMeta::unsequenced(None),
+            )),
+        );
+
+        for reason in ["decode", "buffer"] {
+            let observation = ingester
+                .metrics
+                .get_instrument::<Metric<U64Counter>>("ingester_skipped_operations")
+                .unwrap()
+                .get_observer(&Attributes::from(&[
+                    ("kafka_topic", "whatevs"),
+                    ("sequencer_id", "0"),
+                    ("reason", reason),
+                ]))
+                .unwrap()
+                .fetch();
+            assert_eq!(observation, 1);
+        }
+    }
+
     #[tokio::test]
     async fn test_shutdown() {
         let ingester = TestIngester::new(Time::from_timestamp_millis(10000))
@@ -800,6 +1128,8 @@ mod tests {
             Arc::new(Executor::new(1)),
             Arc::clone(&metrics),
             Arc::new(SystemProvider::new()),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -842,6 +1172,315 @@ mod tests {
             .expect("timeout");
     }
 
+    #[tokio::test]
+    async fn reports_buffered_partitions_and_progress() {
+        let ingester = TestIngester::new(Time::from_timestamp_millis(10000)).await;
+
+        let progress = ingester.ingester.sequencer_progress().await;
+        assert_eq!(
+            progress,
+            vec![SequencerProgress {
+                sequencer_id: ingester.sequencer.id,
+                kafka_partition: ingester.kafka_partition,
+                last_sequence_number: None,
+                high_watermark: Some(0),
+            }]
+        );
+
+        let schema = NamespaceSchema::new(
+            ingester.namespace.id,
+            ingester.kafka_topic.id,
+            ingester.query_pool.id,
+        );
+        let mut txn = ingester.catalog.start_transaction().await.unwrap();
+        let w1 = DmlWrite::new(
+            "foo",
+            lines_to_batches("cpu bar=1 10", 0).unwrap(),
+            DmlMeta::sequenced(
+                Sequence::new(0, 3),
+                Time::from_timestamp_millis(42),
+                None,
+                50,
+            ),
+        );
+        let _schema = validate_or_insert_schema(w1.tables(), &schema, txn.deref_mut())
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+        ingester.write_buffer_state.push_write(w1);
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while ingester.ingester.buffered_partitions().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("timeout");
+
+        let progress = ingester.ingester.sequencer_progress().await;
+        assert_eq!(
+            progress[0].last_sequence_number,
+            Some(SequenceNumber::new(3))
+        );
+        assert_eq!(progress[0].high_watermark, Some(4));
+
+        let partitions = ingester.ingester.buffered_partitions().await;
+        assert_eq!(partitions.len(), 1);
+        let partition = &partitions[0];
+        assert_eq!(
+            partition.names,
+            Some(PartitionNames {
+                namespace: "foo".to_string(),
+                table: "cpu".to_string(),
+                partition_key: "1970-01-01".to_string(),
+            })
+        );
+        assert_eq!(partition.stats.sequencer_id, ingester.sequencer.id);
+        assert_eq!(
+            partition.stats.first_sequence_number,
+            SequenceNumber::new(3)
+        );
+        assert_eq!(partition.stats.last_sequence_number, SequenceNumber::new(3));
+        assert!(partition.stats.bytes_written > 0);
+
+        assert!(ingester
+            .ingester
+            .persist_partition(partition.stats.partition_id));
+        assert!(!ingester.ingester.persist_partition(PartitionId::new(42)));
+        assert_eq!(ingester.ingester.persist_all(), 1);
+
+        ingester.ingester.shutdown();
+    }
+
+    #[tokio::test]
+    async fn replays_wal_on_initialization() {
+        let metrics: Arc<metric::Registry> = Default::default();
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+
+        let mut txn = catalog.start_transaction().await.unwrap();
+        let kafka_topic = txn.kafka_topics().create_or_get("whatevs").await.unwrap();
+        let query_pool = txn.query_pools().create_or_get("whatevs").await.unwrap();
+        let kafka_partition = KafkaPartition::new(0);
+        let namespace = txn
+            .namespaces()
+            .create("foo", "inf", kafka_topic.id, query_pool.id)
+            .await
+            .unwrap();
+        let mut sequencer = txn
+            .sequencers()
+            .create_or_get(&kafka_topic, kafka_partition)
+            .await
+            .unwrap();
+        sequencer.min_unpersisted_sequence_number = 2;
+        txn.sequencers()
+            .update_min_unpersisted_sequence_number(sequencer.id, SequenceNumber::new(2))
+            .await
+            .unwrap();
+        let sequencer_id = sequencer.id;
+
+        let mut sequencer_states = BTreeMap::new();
+        sequencer_states.insert(kafka_partition, sequencer);
+
+        let schema = NamespaceSchema::new(namespace.id, kafka_topic.id, query_pool.id);
+        let writes: Vec<_> = (1..=4)
+            .map(|n| {
+                DmlWrite::new(
+                    "foo",
+                    lines_to_batches(&format!("cpu bar={} {}", n, n * 10), 0).unwrap(),
+                    DmlMeta::sequenced(
+                        Sequence::new(0, n),
+                        Time::from_timestamp_millis(42),
+                        None,
+                        150,
+                    ),
+                )
+            })
+            .collect();
+        let _schema = validate_or_insert_schema(writes[0].tables(), &schema, txn.deref_mut())
+            .await
+            .unwrap()
+            .unwrap();
+        txn.commit().await.unwrap();
+
+        // the WAL contains the first three writes, the first of which was
+        // already persisted
+        let wal_dir = test_helpers::tmp_dir().unwrap();
+        let wal = Arc::new(Wal::new(wal_dir.path(), 1024 * 1024, &metrics).unwrap());
+        for w in &writes[..3] {
+            wal.append(sequencer_id, &DmlOperation::Write(w.clone()))
+                .await
+                .unwrap();
+        }
+
+        // the write buffer contains all writes
+        let write_buffer_state =
+            MockBufferSharedState::empty_with_n_sequencers(NonZeroU32::try_from(1).unwrap());
+        for w in &writes {
+            write_buffer_state.push_write(w.clone());
+        }
+        let reading: Arc<dyn WriteBufferReading> =
+            Arc::new(MockBufferForReading::new(write_buffer_state, None).unwrap());
+
+        let lifecycle_config = LifecycleConfig::new(
+            1000000,
+            1000,
+            1000,
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+        );
+        let ingester = IngestHandlerImpl::new(
+            lifecycle_config,
+            kafka_topic.clone(),
+            sequencer_states,
+            Arc::clone(&catalog),
+            Arc::new(ObjectStoreImpl::new_in_memory()),
+            reading,
+            Arc::new(Executor::new(1)),
+            Arc::clone(&metrics),
+            Arc::new(SystemProvider::new()),
+            Some(wal),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // writes 2 and 3 are replayed from the WAL, write 4 is read from the
+        // write buffer, and nothing is buffered twice
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while buffered_rows(&ingester.data, sequencer_id).await < 3 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("timeout");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(buffered_rows(&ingester.data, sequencer_id).await, 3);
+
+        ingester.shutdown();
+    }
+
+    #[tokio::test]
+    async fn skips_unbufferable_wal_operations_on_initialization() {
+        let metrics: Arc<metric::Registry> = Default::default();
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+
+        let mut txn = catalog.start_transaction().await.unwrap();
+        let kafka_topic = txn.kafka_topics().create_or_get("whatevs").await.unwrap();
+        let query_pool = txn.query_pools().create_or_get("whatevs").await.unwrap();
+        let kafka_partition = KafkaPartition::new(0);
+        let namespace = txn
+            .namespaces()
+            .create("foo", "inf", kafka_topic.id, query_pool.id)
+            .await
+            .unwrap();
+        let sequencer = txn
+            .sequencers()
+            .create_or_get(&kafka_topic, kafka_partition)
+            .await
+            .unwrap();
+        let sequencer_id = sequencer.id;
+
+        // a write to a namespace that does not exist, followed by a valid one
+        let writes = [("bananas", 1), ("foo", 2)].map(|(namespace, n)| {
+            DmlWrite::new(
+                namespace,
+                lines_to_batches("cpu bar=1 10", 0).unwrap(),
+                DmlMeta::sequenced(
+                    Sequence::new(0, n),
+                    Time::from_timestamp_millis(42),
+                    None,
+                    50,
+                ),
+            )
+        });
+        let schema = NamespaceSchema::new(namespace.id, kafka_topic.id, query_pool.id);
+        validate_or_insert_schema(writes[1].tables(), &schema, txn.deref_mut())
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+
+        let write_buffer_state =
+            MockBufferSharedState::empty_with_n_sequencers(NonZeroU32::try_from(1).unwrap());
+        for w in &writes {
+            write_buffer_state.push_write(w.clone());
+        }
+        let object_store = Arc::new(ObjectStoreImpl::new_in_memory());
+        let dead_letters = DeadLetterSink::new(
+            Arc::clone(&object_store) as Arc<DynObjectStore>,
+            "dead_letters",
+        );
+        let wal_dir = test_helpers::tmp_dir().unwrap();
+
+        let lifecycle_config = LifecycleConfig::new(
+            1000000,
+            1000,
+            1000,
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+        );
+        let start = || {
+            let metrics: Arc<metric::Registry> = Default::default();
+            let wal = Arc::new(Wal::new(wal_dir.path(), 1024 * 1024, &metrics).unwrap());
+            let reading: Arc<dyn WriteBufferReading> =
+                Arc::new(MockBufferForReading::new(write_buffer_state.clone(), None).unwrap());
+            let mut sequencer_states = BTreeMap::new();
+            sequencer_states.insert(kafka_partition, sequencer);
+
+            IngestHandlerImpl::new(
+                lifecycle_config,
+                kafka_topic.clone(),
+                sequencer_states,
+                Arc::clone(&catalog),
+                Arc::clone(&object_store) as Arc<DynObjectStore>,
+                reading,
+                Arc::new(Executor::new(1)),
+                metrics,
+                Arc::new(SystemProvider::new()),
+                Some(wal),
+                Some(dead_letters.clone()),
+            )
+        };
+
+        // both writes are appended to the WAL, but only the second one is
+        // buffered
+        let ingester = start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while buffered_rows(&ingester.data, sequencer_id).await < 1 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("timeout");
+        ingester.shutdown();
+        ingester.join().await;
+
+        // after a restart the write to the missing namespace is skipped
+        // instead of failing the replay
+        let ingester = start().await.unwrap();
+        assert_eq!(buffered_rows(&ingester.data, sequencer_id).await, 1);
+
+        let letters = dead_letters.list().await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].sequence_number, 1);
+        assert_eq!(letters[0].reason, SkipReason::Buffer);
+
+        ingester.shutdown();
+    }
+
+    /// Number of rows of the `cpu` table of namespace `foo` buffered for
+    /// `sequencer_id`.
+    async fn buffered_rows(data: &IngesterData, sequencer_id: SequencerId) -> usize {
+        let data = data.sequencers.get(&sequencer_id).unwrap();
+        match data.namespace("foo") {
+            Some(data) => match data.snapshot("cpu", "1970-01-01").await {
+                Some((batches, _)) => batches.iter().map(|b| b.data.num_rows()).sum(),
+                None => 0,
+            },
+            None => 0,
+        }
+    }
+
     struct TestIngester {
         catalog: Arc<dyn Catalog>,
         sequencer: Sequencer,
@@ -851,6 +1490,7 @@ mod tests {
         query_pool: QueryPool,
         metrics: Arc<metric::Registry>,
         write_buffer_state: MockBufferSharedState,
+        dead_letters: DeadLetterSink,
         ingester: IngestHandlerImpl,
     }
 
@@ -883,6 +1523,10 @@ mod tests {
             let reading: Arc<dyn WriteBufferReading> =
                 Arc::new(MockBufferForReading::new(write_buffer_state.clone(), None).unwrap());
             let object_store = Arc::new(ObjectStoreImpl::new_in_memory());
+            let dead_letters = DeadLetterSink::new(
+                Arc::clone(&object_store) as Arc<DynObjectStore>,
+                "dead_letters",
+            );
 
             let time_provider = Arc::new(MockProvider::new(start_time));
 
@@ -903,6 +1547,8 @@ mod tests {
                 Arc::new(Executor::new(1)),
                 Arc::clone(&metrics),
                 time_provider,
+                None,
+                Some(dead_letters.clone()),
             )
             .await
             .unwrap();
@@ -916,6 +1562,7 @@ mod tests {
                 query_pool,
                 metrics,
                 write_buffer_state,
+                dead_letters,
                 ingester,
             }
         }
@@ -16,6 +16,7 @@
 
 pub mod compact;
 pub mod data;
+pub mod dead_letter;
 pub mod handler;
 mod job;
 pub mod lifecycle;
@@ -24,6 +25,7 @@ mod poison;
 pub mod querier_handler;
 pub mod query;
 pub mod server;
+pub mod wal;
 
 #[cfg(test)]
 pub mod test_util;
@@ -14,7 +14,11 @@ use data_types2::{PartitionId, SequenceNumber, SequencerId};
 use metric::{Metric, U64Counter};
 use observability_deps::tracing::{error, info};
 use parking_lot::Mutex;
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
 use time::{Time, TimeProvider};
 use tokio_util::sync::CancellationToken;
 use tracker::TrackedFutureExt;
@@ -58,10 +62,12 @@ impl LifecycleHandle {
                     last_write: now,
                     bytes_written: 0,
                     first_sequence_number: sequence_number,
+                    last_sequence_number: sequence_number,
                 });
 
         stats.bytes_written += bytes_written;
         stats.last_write = now;
+        stats.last_sequence_number = stats.last_sequence_number.max(sequence_number);
 
         s.total_bytes += bytes_written;
         s.total_bytes > self.config.pause_ingest_size
@@ -73,6 +79,32 @@ impl LifecycleHandle {
         let s = self.state.lock();
         s.total_bytes < self.config.pause_ingest_size
     }
+
+    /// Returns a point in time snapshot of the lifecycle state.
+    pub fn stats(&self) -> LifecycleStats {
+        self.state.lock().stats()
+    }
+
+    /// Requests that the partition is persisted on the next run of the
+    /// [`LifecycleManager`], regardless of its size and age. Returns false if
+    /// the partition is not being tracked.
+    pub fn request_persist(&self, partition_id: PartitionId) -> bool {
+        let mut s = self.state.lock();
+        if !s.partition_stats.contains_key(&partition_id) {
+            return false;
+        }
+        s.persist_requested.insert(partition_id);
+        true
+    }
+
+    /// Requests that all partitions are persisted on the next run of the
+    /// [`LifecycleManager`], returning the number of partitions.
+    pub fn request_persist_all(&self) -> usize {
+        let mut s = self.state.lock();
+        let partition_ids: Vec<_> = s.partition_stats.keys().copied().collect();
+        s.persist_requested.extend(partition_ids);
+        s.partition_stats.len()
+    }
 }
 
 /// The lifecycle manager keeps track of the size and age of partitions across
@@ -111,6 +143,8 @@ pub struct LifecycleManager {
     persist_age_counter: U64Counter,
     /// Counter for a partition going cold for writes triggering a persist.
     persist_cold_counter: U64Counter,
+    /// Counter for a persist requested through a [`LifecycleHandle`].
+    persist_manual_counter: U64Counter,
 }
 
 /// The configuration options for the lifecycle on the ingester.
@@ -163,9 +197,18 @@ impl LifecycleConfig {
 struct LifecycleState {
     total_bytes: usize,
     partition_stats: BTreeMap<PartitionId, PartitionLifecycleStats>,
+    /// Partitions to persist on the next run, regardless of their stats.
+    persist_requested: BTreeSet<PartitionId>,
 }
 
 impl LifecycleState {
+    fn stats(&self) -> LifecycleStats {
+        LifecycleStats {
+            total_bytes: self.total_bytes,
+            partition_stats: self.partition_stats.values().cloned().collect(),
+        }
+    }
+
     fn remove(&mut self, partition_id: &PartitionId) -> Option<PartitionLifecycleStats> {
         self.partition_stats.remove(partition_id).map(|stats| {
             self.total_bytes -= stats.bytes_written;
@@ -188,20 +231,23 @@ pub struct LifecycleStats {
 #[derive(Debug, Clone, Copy)]
 pub struct PartitionLifecycleStats {
     /// The sequencer this partition is under
-    sequencer_id: SequencerId,
+    pub sequencer_id: SequencerId,
     /// The partition identifier
-    partition_id: PartitionId,
+    pub partition_id: PartitionId,
     /// Time that the partition received its first write. This is reset anytime
     /// the partition is persisted.
-    first_write: Time,
+    pub first_write: Time,
     /// Time that the partition received its last write. This is reset anytime
     /// the partition is persisted.
-    last_write: Time,
+    pub last_write: Time,
     /// The number of bytes in the partition as estimated by the mutable batch sizes.
-    bytes_written: usize,
+    pub bytes_written: usize,
     /// The sequence number the partition received on its first write. This is reset anytime
     /// the partition is persisted.
-    first_sequence_number: SequenceNumber,
+    pub first_sequence_number: SequenceNumber,
+    /// The highest sequence number the partition received. This is reset anytime the
+    /// partition is persisted.
+    pub last_sequence_number: SequenceNumber,
 }
 
 impl LifecycleManager {
@@ -221,6 +267,7 @@ impl LifecycleManager {
         let persist_size_counter = persist_counter.recorder(&[("trigger", "size")]);
         let persist_age_counter = persist_counter.recorder(&[("trigger", "age")]);
         let persist_cold_counter = persist_counter.recorder(&[("trigger", "cold")]);
+        let persist_manual_counter = persist_counter.recorder(&[("trigger", "manual")]);
 
         let job_registry = Arc::new(JobRegistry::new(
             metric_registry,
@@ -235,6 +282,7 @@ impl LifecycleManager {
             persist_size_counter,
             persist_age_counter,
             persist_cold_counter,
+            persist_manual_counter,
         }
     }
 
@@ -247,15 +295,22 @@ impl LifecycleManager {
         }
     }
 
-    /// This will persist any partitions that are over their size or age thresholds and
+    /// This will persist any partitions that are over their size or age thresholds, or that were
+    /// requested to be persisted through a [`LifecycleHandle`], and
     /// persist as many partitions as necessary (largest first) to get below the memory threshold.
     /// The persist operations are spawned in new tasks and run at the same time, but the
     /// function waits for all to return before completing.
     pub async fn maybe_persist<P: Persister>(&mut self, persister: &Arc<P>) {
-        let LifecycleStats {
-            mut total_bytes,
-            partition_stats,
-        } = self.stats();
+        let (
+            LifecycleStats {
+                mut total_bytes,
+                partition_stats,
+            },
+            persist_requested,
+        ) = {
+            let mut s = self.state.lock();
+            (s.stats(), std::mem::take(&mut s.persist_requested))
+        };
 
         // get anything over the threshold size or age to persist
         let now = self.time_provider.now();
@@ -285,7 +340,12 @@ impl LifecycleManager {
                 self.persist_size_counter.inc(1);
             }
 
-            aged_out || sized_out || is_cold
+            let requested = persist_requested.contains(&s.partition_id);
+            if requested {
+                self.persist_manual_counter.inc(1);
+            }
+
+            aged_out || sized_out || is_cold || requested
         });
 
         // keep track of what we'll be evicting to see what else to drop
@@ -375,13 +435,7 @@ impl LifecycleManager {
 
     /// Returns a point in time snapshot of the lifecycle state.
     pub fn stats(&self) -> LifecycleStats {
-        let s = self.state.lock();
-        let partition_stats: Vec<_> = s.partition_stats.values().cloned().collect();
-
-        LifecycleStats {
-            total_bytes: s.total_bytes,
-            partition_stats,
-        }
+        self.state.lock().stats()
     }
 
     /// Removes the partition from the state
@@ -813,6 +867,80 @@ mod tests {
         assert_eq!(cold_counter, 1);
     }
 
+    #[tokio::test]
+    async fn persists_on_request() {
+        let config = LifecycleConfig {
+            pause_ingest_size: 500,
+            persist_memory_threshold: 500,
+            partition_size_threshold: 500,
+            partition_age_threshold: Duration::from_secs(1000),
+            partition_cold_threshold: Duration::from_secs(1000),
+        };
+        let TestLifecycleManger {
+            mut m,
+            metric_registry,
+            ..
+        } = TestLifecycleManger::new(config);
+        let h = m.handle();
+        let persister = Arc::new(TestPersister::default());
+        let sequencer_id = SequencerId::new(1);
+
+        h.log_write(
+            PartitionId::new(1),
+            sequencer_id,
+            SequenceNumber::new(1),
+            10,
+        );
+        h.log_write(
+            PartitionId::new(1),
+            sequencer_id,
+            SequenceNumber::new(3),
+            10,
+        );
+        h.log_write(
+            PartitionId::new(2),
+            sequencer_id,
+            SequenceNumber::new(2),
+            10,
+        );
+        h.log_write(
+            PartitionId::new(3),
+            sequencer_id,
+            SequenceNumber::new(4),
+            10,
+        );
+
+        let stats = h.stats();
+        assert_eq!(stats.partition_stats[0].first_sequence_number.get(), 1);
+        assert_eq!(stats.partition_stats[0].last_sequence_number.get(), 3);
+
+        // unknown partitions cannot be persisted
+        assert!(!h.request_persist(PartitionId::new(4)));
+
+        assert!(h.request_persist(PartitionId::new(1)));
+        m.maybe_persist(&persister).await;
+
+        assert!(persister.persist_called_for(PartitionId::new(1)));
+        assert!(!persister.persist_called_for(PartitionId::new(2)));
+        assert_eq!(
+            persister.update_min_calls(),
+            vec![(sequencer_id, SequenceNumber::new(4))]
+        );
+        assert_eq!(h.stats().total_bytes, 20);
+
+        // the request is only served once
+        m.maybe_persist(&persister).await;
+        assert_eq!(persister.update_min_calls().len(), 1);
+
+        assert_eq!(h.request_persist_all(), 2);
+        m.maybe_persist(&persister).await;
+
+        assert!(persister.persist_called_for(PartitionId::new(2)));
+        assert!(persister.persist_called_for(PartitionId::new(3)));
+        assert_eq!(h.stats().total_bytes, 0);
+        assert_eq!(get_counter(&metric_registry, "manual"), 3);
+    }
+
     struct TestLifecycleManger {
         m: LifecycleManager,
         time_provider: Arc<MockProvider>,
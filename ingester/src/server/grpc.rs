@@ -106,6 +106,10 @@ impl Error {
             Self::InvalidTicket { .. } | Self::InvalidQuery { .. } => {
                 Status::invalid_argument(self.to_string())
             }
+            // the querier asks every ingester for every sequencer, so this is an expected outcome
+            Self::Query {
+                source: crate::querier_handler::Error::TableNotFound { .. },
+            } => Status::not_found(self.to_string()),
             Self::Query { .. }
             | Self::InvalidRecordBatch { .. }
             | Self::Dictionary { .. }
@@ -1,16 +1,31 @@
 //! HTTP service implementations for `ingester`.
 
-use crate::handler::IngestHandler;
-use hyper::{Body, Request, Response, StatusCode};
+use crate::handler::{BufferedPartition, IngestHandler, SequencerProgress};
+use data_types2::PartitionId;
+use hyper::{header::CONTENT_TYPE, Body, Method, Request, Response, StatusCode};
+use observability_deps::tracing::*;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use thiserror::Error;
 
-/// Errors returned by the `router2` HTTP request handler.
-#[derive(Debug, Error, Copy, Clone)]
+/// Errors returned by the `ingester` HTTP request handler.
+#[derive(Debug, Error)]
 pub enum Error {
     /// The requested path has no registered handler.
     #[error("not found")]
     NotFound,
+
+    /// The request query string cannot be decoded.
+    #[error("failed to deserialise request parameters: {0}")]
+    InvalidParameters(#[from] serde::de::value::Error),
+
+    /// The partition to persist is not buffered by the ingester.
+    #[error("partition {0} is not buffered")]
+    PartitionNotFound(PartitionId),
+
+    /// The response could not be serialised.
+    #[error("failed to serialise response: {0}")]
+    Serialise(#[from] serde_json::Error),
 }
 
 impl Error {
@@ -19,19 +34,141 @@ impl Error {
     pub fn as_status_code(&self) -> StatusCode {
         match self {
             Self::NotFound => StatusCode::NOT_FOUND,
+            Self::InvalidParameters(_) => StatusCode::BAD_REQUEST,
+            Self::PartitionNotFound(_) => StatusCode::NOT_FOUND,
+            Self::Serialise(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// The partitions buffered by the ingester, as returned by
+/// `GET /api/v1/partitions`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PartitionsResponse {
+    /// Total number of bytes buffered across all partitions.
+    pub total_bytes: usize,
+    /// The buffered partitions, largest first.
+    pub partitions: Vec<PartitionResponse>,
+}
+
+/// A single buffered partition.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PartitionResponse {
+    /// The partition ID.
+    pub partition_id: i64,
+    /// The sequencer ID.
+    pub sequencer_id: i16,
+    /// Name of the namespace, if still buffered.
+    pub namespace: Option<String>,
+    /// Name of the table, if still buffered.
+    pub table: Option<String>,
+    /// The partition key, if still buffered.
+    pub partition_key: Option<String>,
+    /// Estimated number of bytes buffered for the partition.
+    pub bytes: usize,
+    /// Sequence number of the first buffered write.
+    pub min_sequence_number: i64,
+    /// Sequence number of the last buffered write.
+    pub max_sequence_number: i64,
+    /// Time of the first buffered write, in nanoseconds since the epoch.
+    pub first_write_ns: i64,
+    /// Time of the last buffered write, in nanoseconds since the epoch.
+    pub last_write_ns: i64,
+}
+
+impl From<BufferedPartition> for PartitionResponse {
+    fn from(p: BufferedPartition) -> Self {
+        let (namespace, table, partition_key) = match p.names {
+            Some(n) => (Some(n.namespace), Some(n.table), Some(n.partition_key)),
+            None => (None, None, None),
+        };
+
+        Self {
+            partition_id: p.stats.partition_id.get(),
+            sequencer_id: p.stats.sequencer_id.get(),
+            namespace,
+            table,
+            partition_key,
+            bytes: p.stats.bytes_written,
+            min_sequence_number: p.stats.first_sequence_number.get(),
+            max_sequence_number: p.stats.last_sequence_number.get(),
+            first_write_ns: p.stats.first_write.timestamp_nanos(),
+            last_write_ns: p.stats.last_write.timestamp_nanos(),
+        }
+    }
+}
+
+/// The number of partitions scheduled for persistence, as returned by
+/// `POST /api/v1/persist`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PersistResponse {
+    /// Number of partitions that will be persisted.
+    pub partitions: usize,
+}
+
+/// The ingest progress of each sequencer, as returned by
+/// `GET /api/v1/sequencers`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SequencersResponse {
+    /// The sequencers consumed by the ingester.
+    pub sequencers: Vec<SequencerResponse>,
+}
+
+/// The ingest progress of a single sequencer.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SequencerResponse {
+    /// The sequencer ID.
+    pub sequencer_id: i16,
+    /// The write buffer partition of the sequencer.
+    pub kafka_partition: i32,
+    /// Sequence number of the last buffered operation, if any.
+    pub last_sequence_number: Option<i64>,
+    /// Sequence number of the next operation written to the write buffer, if
+    /// it could be fetched.
+    pub high_watermark: Option<u64>,
+    /// Number of operations in the write buffer not yet buffered by the
+    /// ingester, if known.
+    pub lag: Option<u64>,
+}
+
+impl From<SequencerProgress> for SequencerResponse {
+    fn from(p: SequencerProgress) -> Self {
+        let next = p
+            .last_sequence_number
+            .map(|n| n.get() as u64 + 1)
+            .unwrap_or_default();
+
+        Self {
+            sequencer_id: p.sequencer_id.get(),
+            kafka_partition: p.kafka_partition.get(),
+            last_sequence_number: p.last_sequence_number.map(|n| n.get()),
+            high_watermark: p.high_watermark,
+            lag: p.high_watermark.map(|w| w.saturating_sub(next)),
         }
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct PersistParams {
+    partition_id: Option<i64>,
+}
+
 /// This type is responsible for servicing requests to the `ingester` HTTP
 /// endpoint.
 ///
 /// Requests to some paths may be handled externally by the caller - the IOx
 /// server runner framework takes care of implementing the heath endpoint,
 /// metrics, pprof, etc.
+///
+/// The ingester serves:
+///
+/// * `GET /api/v1/partitions`: the buffered partitions and their sizes.
+/// * `POST /api/v1/persist[?partition_id=<id>]`: persist the given partition,
+///   or all partitions, on the next run of the lifecycle manager.
+/// * `GET /api/v1/sequencers`: the ingest position of each sequencer compared
+///   to the write buffer high watermark.
 #[derive(Debug, Default)]
 pub struct HttpDelegate<I: IngestHandler> {
-    #[allow(dead_code)]
     ingest_handler: Arc<I>,
 }
 
@@ -44,7 +181,287 @@ impl<I: IngestHandler> HttpDelegate<I> {
 
     /// Routes `req` to the appropriate handler, if any, returning the handler
     /// response.
-    pub fn route(&self, _req: Request<Body>) -> Result<Response<Body>, Error> {
-        unimplemented!()
+    pub async fn route(&self, req: Request<Body>) -> Result<Response<Body>, Error> {
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/api/v1/partitions") => self.partitions_handler().await,
+            (&Method::POST, "/api/v1/persist") => self.persist_handler(&req),
+            (&Method::GET, "/api/v1/sequencers") => self.sequencers_handler().await,
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    async fn partitions_handler(&self) -> Result<Response<Body>, Error> {
+        let mut partitions = self.ingest_handler.buffered_partitions().await;
+        partitions.sort_by(|a, b| b.stats.bytes_written.cmp(&a.stats.bytes_written));
+
+        let response = PartitionsResponse {
+            total_bytes: partitions.iter().map(|p| p.stats.bytes_written).sum(),
+            partitions: partitions.into_iter().map(Into::into).collect(),
+        };
+        json_response(StatusCode::OK, &response)
+    }
+
+    fn persist_handler(&self, req: &Request<Body>) -> Result<Response<Body>, Error> {
+        let params: PersistParams = serde_urlencoded::from_str(req.uri().query().unwrap_or(""))?;
+
+        let partitions = match params.partition_id.map(PartitionId::new) {
+            Some(partition_id) => {
+                if !self.ingest_handler.persist_partition(partition_id) {
+                    return Err(Error::PartitionNotFound(partition_id));
+                }
+                1
+            }
+            None => self.ingest_handler.persist_all(),
+        };
+        info!(
+            partitions,
+            partition_id = ?params.partition_id,
+            "persistence requested over HTTP"
+        );
+
+        json_response(StatusCode::ACCEPTED, &PersistResponse { partitions })
+    }
+
+    async fn sequencers_handler(&self) -> Result<Response<Body>, Error> {
+        let response = SequencersResponse {
+            sequencers: self
+                .ingest_handler
+                .sequencer_progress()
+                .await
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        };
+        json_response(StatusCode::OK, &response)
+    }
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Result<Response<Body>, Error> {
+    let body = serde_json::to_vec(body)?;
+    Ok(Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{IngesterQueryResponse, PartitionNames},
+        lifecycle::PartitionLifecycleStats,
+    };
+    use async_trait::async_trait;
+    use data_types2::{IngesterQueryRequest, KafkaPartition, SequenceNumber, SequencerId};
+    use parking_lot::Mutex;
+    use time::Time;
+
+    #[derive(Debug, Default)]
+    struct MockIngestHandler {
+        persist_calls: Mutex<Vec<Option<PartitionId>>>,
+    }
+
+    #[async_trait]
+    impl IngestHandler for MockIngestHandler {
+        async fn query(
+            &self,
+            _request: IngesterQueryRequest,
+        ) -> Result<IngesterQueryResponse, crate::querier_handler::Error> {
+            unimplemented!()
+        }
+
+        async fn buffered_partitions(&self) -> Vec<BufferedPartition> {
+            let partition = |id: i64, bytes_written: usize| BufferedPartition {
+                stats: PartitionLifecycleStats {
+                    sequencer_id: SequencerId::new(1),
+                    partition_id: PartitionId::new(id),
+                    first_write: Time::from_timestamp_nanos(10),
+                    last_write: Time::from_timestamp_nanos(20),
+                    bytes_written,
+                    first_sequence_number: SequenceNumber::new(3),
+                    last_sequence_number: SequenceNumber::new(5),
+                },
+                names: Some(PartitionNames {
+                    namespace: "ns".to_string(),
+                    table: "cpu".to_string(),
+                    partition_key: "1970-01-01".to_string(),
+                }),
+            };
+            vec![partition(1, 10), partition(2, 20)]
+        }
+
+        fn persist_partition(&self, partition_id: PartitionId) -> bool {
+            self.persist_calls.lock().push(Some(partition_id));
+            partition_id.get() == 1
+        }
+
+        fn persist_all(&self) -> usize {
+            self.persist_calls.lock().push(None);
+            2
+        }
+
+        async fn sequencer_progress(&self) -> Vec<SequencerProgress> {
+            vec![SequencerProgress {
+                sequencer_id: SequencerId::new(1),
+                kafka_partition: KafkaPartition::new(0),
+                last_sequence_number: Some(SequenceNumber::new(5)),
+                high_watermark: Some(10),
+            }]
+        }
+
+        async fn join(&self) {}
+
+        fn shutdown(&self) {}
+    }
+
+    async fn request(
+        delegate: &HttpDelegate<MockIngestHandler>,
+        method: Method,
+        uri: &str,
+    ) -> Result<(StatusCode, serde_json::Value), Error> {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = delegate.route(req).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        Ok((status, serde_json::from_slice(&body).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_partitions() {
+        let delegate = HttpDelegate::new(Arc::new(MockIngestHandler::default()));
+
+        let (status, body) = request(
+            &delegate,
+            Method::GET,
+            "https://bananas.example/api/v1/partitions",
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+
+        let got: PartitionsResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(got.total_bytes, 30);
+        assert_eq!(
+            got.partitions
+                .iter()
+                .map(|p| p.partition_id)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(
+            got.partitions[1],
+            PartitionResponse {
+                partition_id: 1,
+                sequencer_id: 1,
+                namespace: Some("ns".to_string()),
+                table: Some("cpu".to_string()),
+                partition_key: Some("1970-01-01".to_string()),
+                bytes: 10,
+                min_sequence_number: 3,
+                max_sequence_number: 5,
+                first_write_ns: 10,
+                last_write_ns: 20,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persist() {
+        let handler = Arc::new(MockIngestHandler::default());
+        let delegate = HttpDelegate::new(Arc::clone(&handler));
+
+        let (status, body) = request(
+            &delegate,
+            Method::POST,
+            "https://bananas.example/api/v1/persist?partition_id=1",
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(
+            serde_json::from_value::<PersistResponse>(body).unwrap(),
+            PersistResponse { partitions: 1 }
+        );
+
+        let (status, body) = request(
+            &delegate,
+            Method::POST,
+            "https://bananas.example/api/v1/persist",
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(
+            serde_json::from_value::<PersistResponse>(body).unwrap(),
+            PersistResponse { partitions: 2 }
+        );
+
+        let err = request(
+            &delegate,
+            Method::POST,
+            "https://bananas.example/api/v1/persist?partition_id=2",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.as_status_code(), StatusCode::NOT_FOUND);
+
+        let err = request(
+            &delegate,
+            Method::POST,
+            "https://bananas.example/api/v1/persist?partition_id=bananas",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.as_status_code(), StatusCode::BAD_REQUEST);
+
+        assert_eq!(
+            *handler.persist_calls.lock(),
+            vec![Some(PartitionId::new(1)), None, Some(PartitionId::new(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sequencers() {
+        let delegate = HttpDelegate::new(Arc::new(MockIngestHandler::default()));
+
+        let (status, body) = request(
+            &delegate,
+            Method::GET,
+            "https://bananas.example/api/v1/sequencers",
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_value::<SequencersResponse>(body).unwrap(),
+            SequencersResponse {
+                sequencers: vec![SequencerResponse {
+                    sequencer_id: 1,
+                    kafka_partition: 0,
+                    last_sequence_number: Some(5),
+                    high_watermark: Some(10),
+                    lag: Some(4),
+                }]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_not_found() {
+        let delegate = HttpDelegate::new(Arc::new(MockIngestHandler::default()));
+
+        let err = request(
+            &delegate,
+            Method::GET,
+            "https://bananas.example/api/v1/persist",
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::NotFound));
     }
 }
@@ -654,6 +654,7 @@ pub fn make_ingester_data(two_partitions: bool, loc: DataLocation) -> IngesterDa
         sequencers,
         exec,
         backoff_config: backoff::BackoffConfig::default(),
+        wal: None,
     }
 }
 
@@ -697,6 +698,7 @@ pub async fn make_ingester_data_with_tombstones(loc: DataLocation) -> IngesterDa
         sequencers,
         exec,
         backoff_config: backoff::BackoffConfig::default(),
+        wal: None,
     }
 }
 
@@ -0,0 +1,846 @@
+//! A local write-ahead log (WAL) of the operations buffered by the ingester.
+//!
+//! Without a WAL, buffered but unpersisted data is recovered after a crash by replaying the write
+//! buffer from each sequencer's `min_unpersisted_sequence_number`. If the write buffer retention is
+//! shorter than the age of the oldest unpersisted partition, that data is lost. With a WAL, every
+//! buffered [`DmlOperation`] is also appended to local segment files (one directory per
+//! sequencer), which are replayed on startup before the write buffer stream resumes. Segments are
+//! deleted once all of their operations have been persisted.
+//!
+//! Each entry of a segment is framed as
+//!
+//! ```text
+//! | payload length: u32 | CRC32 of the payload: u32 | payload |
+//! ```
+//!
+//! where the payload is
+//!
+//! ```text
+//! | kafka partition: u32 | sequence number: u64 | producer timestamp: i64 | bytes read: u64 |
+//! | namespace length: u32 | namespace | protobuf encoded operation |
+//! ```
+//!
+//! with all integers in little endian. Appends are flushed to the OS but not fsync-ed, so the WAL
+//! survives a crash of the ingester, but the latest entries may be lost if the host crashes.
+//! A torn entry at the end of a segment ends the replay of that segment.
+
+use data_types2::{Sequence, SequenceNumber, SequencerId};
+use dml::DmlOperation;
+use futures::stream::{self, BoxStream, StreamExt};
+use metric::U64Counter;
+use observability_deps::tracing::{debug, info, warn};
+use parking_lot::Mutex;
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufReader, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use time::Time;
+use write_buffer::{ContentType, IoxHeaders};
+
+/// File extension of WAL segments.
+const SEGMENT_EXTENSION: &str = "wal";
+
+/// Size of the `| payload length | CRC32 |` frame header.
+const FRAME_HEADER_LEN: usize = 8;
+
+#[derive(Debug, Snafu)]
+#[allow(missing_copy_implementations, missing_docs)]
+pub enum Error {
+    #[snafu(display("Error creating WAL directory {:?}: {}", path, source))]
+    CreateDir {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Error listing WAL directory {:?}: {}", path, source))]
+    ListDir {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Error reading WAL segment {:?}: {}", path, source))]
+    ReadSegment {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Error writing WAL segment {:?}: {}", path, source))]
+    WriteSegment {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Error deleting WAL segment {:?}: {}", path, source))]
+    DeleteSegment {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Error encoding operation for the WAL: {}", source))]
+    Encode {
+        source: write_buffer::core::WriteBufferError,
+    },
+
+    #[snafu(display(
+        "Error decoding operation {} from WAL segment {:?}: {}",
+        sequence_number,
+        path,
+        source
+    ))]
+    Decode {
+        source: write_buffer::core::WriteBufferError,
+        sequence_number: u64,
+        path: PathBuf,
+    },
+
+    #[snafu(display("Operation without a sequence number cannot be written to the WAL"))]
+    Unsequenced,
+}
+
+/// A specialized `Error` for WAL errors
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A write-ahead log of the operations buffered by the ingester, see the
+/// [module documentation](self).
+///
+/// All file IO runs on the blocking thread pool of the tokio runtime.
+#[derive(Debug)]
+pub struct Wal {
+    shared: Arc<Shared>,
+}
+
+/// State of a [`Wal`] that is shared with its blocking IO tasks.
+#[derive(Debug)]
+struct Shared {
+    dir: PathBuf,
+    segment_size_bytes: u64,
+
+    /// Appends of different sequencers do not wait for each other, so every sequencer has its
+    /// own lock.
+    sequencers: Mutex<BTreeMap<SequencerId, Arc<Mutex<SequencerWal>>>>,
+
+    /// Number of bytes appended to the WAL.
+    bytes_written: U64Counter,
+
+    /// Number of segments deleted because all their operations were persisted.
+    segments_deleted: U64Counter,
+}
+
+impl Wal {
+    /// Open (or create) the WAL stored in `dir`. A new segment is started once the current one
+    /// grows beyond `segment_size_bytes`.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        segment_size_bytes: u64,
+        registry: &metric::Registry,
+    ) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).context(CreateDirSnafu { path: &dir })?;
+        info!(?dir, segment_size_bytes, "opened ingester WAL");
+
+        let shared = Shared {
+            dir,
+            segment_size_bytes,
+            sequencers: Default::default(),
+            bytes_written: registry
+                .register_metric::<U64Counter>(
+                    "ingester_wal_bytes_written_total",
+                    "Number of bytes appended to the ingester write-ahead log",
+                )
+                .recorder(&[]),
+            segments_deleted: registry
+                .register_metric::<U64Counter>(
+                    "ingester_wal_segments_deleted_total",
+                    "Number of ingester write-ahead log segments deleted after persistence",
+                )
+                .recorder(&[]),
+        };
+        Ok(Self {
+            shared: Arc::new(shared),
+        })
+    }
+
+    /// Append `op` to the WAL of `sequencer_id`.
+    pub async fn append(&self, sequencer_id: SequencerId, op: &DmlOperation) -> Result<()> {
+        let meta = op.meta();
+        let sequence = meta.sequence().ok_or(Error::Unsequenced)?;
+        let producer_ts = meta
+            .producer_ts()
+            .map(|ts| ts.timestamp_nanos())
+            .unwrap_or_default();
+
+        let mut encoded = vec![];
+        write_buffer::encode_operation(op.namespace(), op, &mut encoded).context(EncodeSnafu)?;
+
+        let namespace = op.namespace().as_bytes();
+        let mut payload = Vec::with_capacity(32 + namespace.len() + encoded.len());
+        payload.extend_from_slice(&sequence.id.to_le_bytes());
+        payload.extend_from_slice(&sequence.number.to_le_bytes());
+        payload.extend_from_slice(&producer_ts.to_le_bytes());
+        payload.extend_from_slice(&(meta.bytes_read().unwrap_or_default() as u64).to_le_bytes());
+        payload.extend_from_slice(&(namespace.len() as u32).to_le_bytes());
+        payload.extend_from_slice(namespace);
+        payload.extend_from_slice(&encoded);
+
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+
+        let shared = Arc::clone(&self.shared);
+        run_blocking(move || {
+            let wal = shared.sequencer_wal(sequencer_id)?;
+            wal.lock()
+                .append(&frame, sequence.number, shared.segment_size_bytes)?;
+            shared.bytes_written.inc(frame.len() as u64);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stream all operations of `sequencer_id` with a sequence number of at least
+    /// `min_sequence_number`, in the order they were appended.
+    ///
+    /// The segments are read one entry at a time, so the WAL does not need to fit into memory.
+    /// This is meant to be called once per sequencer on startup, before anything is appended.
+    pub async fn replay(
+        &self,
+        sequencer_id: SequencerId,
+        min_sequence_number: SequenceNumber,
+    ) -> Result<BoxStream<'static, Result<DmlOperation>>> {
+        let shared = Arc::clone(&self.shared);
+        let segments = run_blocking(move || {
+            let wal = SequencerWal::open(shared.sequencer_dir(sequencer_id))?;
+            let segments = wal.closed.iter().map(|s| s.path.clone()).collect();
+            shared
+                .sequencers
+                .lock()
+                .insert(sequencer_id, Arc::new(Mutex::new(wal)));
+            Ok(segments)
+        })
+        .await?;
+
+        let replay = Replay {
+            segments,
+            reader: None,
+            min_sequence_number: min_sequence_number.get(),
+        };
+        Ok(stream::try_unfold(replay, |replay| run_blocking(move || replay.next_op())).boxed())
+    }
+
+    /// Delete the segments of `sequencer_id` that only contain operations with a sequence number
+    /// lower than `min_unpersisted_sequence_number`, returning the number of deleted segments.
+    pub async fn truncate(
+        &self,
+        sequencer_id: SequencerId,
+        min_unpersisted_sequence_number: SequenceNumber,
+    ) -> Result<usize> {
+        let shared = Arc::clone(&self.shared);
+        let deleted = run_blocking(move || {
+            let wal = shared.sequencer_wal(sequencer_id)?;
+            let deleted = wal.lock().truncate(min_unpersisted_sequence_number.get())?;
+            shared.segments_deleted.inc(deleted as u64);
+            Ok(deleted)
+        })
+        .await?;
+
+        if deleted > 0 {
+            debug!(
+                %sequencer_id,
+                deleted,
+                min_unpersisted_sequence_number = min_unpersisted_sequence_number.get(),
+                "truncated ingester WAL"
+            );
+        }
+
+        Ok(deleted)
+    }
+}
+
+impl Shared {
+    fn sequencer_dir(&self, sequencer_id: SequencerId) -> PathBuf {
+        self.dir.join(format!("sequencer-{}", sequencer_id.get()))
+    }
+
+    /// Get the state of the WAL of `sequencer_id`, loading it from disk if necessary.
+    fn sequencer_wal(&self, sequencer_id: SequencerId) -> Result<Arc<Mutex<SequencerWal>>> {
+        let mut sequencers = self.sequencers.lock();
+        if let Some(wal) = sequencers.get(&sequencer_id) {
+            return Ok(Arc::clone(wal));
+        }
+
+        let wal = Arc::new(Mutex::new(SequencerWal::open(
+            self.sequencer_dir(sequencer_id),
+        )?));
+        sequencers.insert(sequencer_id, Arc::clone(&wal));
+        Ok(wal)
+    }
+}
+
+/// Run the blocking `f` on the blocking thread pool.
+async fn run_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .expect("WAL IO task panicked")
+}
+
+/// A WAL segment file.
+#[derive(Debug)]
+struct Segment {
+    path: PathBuf,
+
+    /// Highest sequence number in this segment, `None` if the segment is empty.
+    max_sequence_number: Option<u64>,
+}
+
+/// The segment that is currently appended to.
+#[derive(Debug)]
+struct OpenSegment {
+    segment: Segment,
+    file: File,
+    size: u64,
+}
+
+/// The WAL of a single sequencer.
+#[derive(Debug)]
+struct SequencerWal {
+    dir: PathBuf,
+
+    /// Segments that are no longer appended to, oldest first.
+    closed: Vec<Segment>,
+
+    open: Option<OpenSegment>,
+
+    /// ID of the next segment to create.
+    next_id: u64,
+}
+
+impl SequencerWal {
+    /// Load the existing segments in `dir`.
+    ///
+    /// All existing segments are considered closed, appends start a new segment.
+    fn open(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).context(CreateDirSnafu { path: &dir })?;
+
+        let mut ids = vec![];
+        for dir_entry in std::fs::read_dir(&dir).context(ListDirSnafu { path: &dir })? {
+            let path = dir_entry.context(ListDirSnafu { path: &dir })?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            match path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                Some(id) => ids.push(id),
+                None => warn!(?path, "ignoring unexpected file in WAL directory"),
+            }
+        }
+        ids.sort_unstable();
+
+        let mut closed = Vec::with_capacity(ids.len());
+        for id in &ids {
+            let path = segment_path(&dir, *id);
+            let mut max_sequence_number = None;
+            if let Some(mut reader) = SegmentReader::open(&path)? {
+                while let Some(entry) = reader.next_entry()? {
+                    max_sequence_number = max_sequence_number.max(Some(entry.sequence_number));
+                }
+            }
+            closed.push(Segment {
+                path,
+                max_sequence_number,
+            });
+        }
+
+        Ok(Self {
+            dir,
+            closed,
+            open: None,
+            next_id: ids.last().map(|id| id + 1).unwrap_or_default(),
+        })
+    }
+
+    fn append(
+        &mut self,
+        frame: &[u8],
+        sequence_number: u64,
+        segment_size_bytes: u64,
+    ) -> Result<()> {
+        if self
+            .open
+            .as_ref()
+            .map(|open| open.size >= segment_size_bytes)
+            .unwrap_or(true)
+        {
+            self.rotate()?;
+        }
+        let open = self.open.as_mut().expect("segment opened above");
+
+        open.file
+            .write_all(frame)
+            .and_then(|_| open.file.flush())
+            .context(WriteSegmentSnafu {
+                path: &open.segment.path,
+            })?;
+        open.size += frame.len() as u64;
+        open.segment.max_sequence_number = Some(
+            open.segment
+                .max_sequence_number
+                .map_or(sequence_number, |max| max.max(sequence_number)),
+        );
+
+        Ok(())
+    }
+
+    /// Close the current segment (if any) and start a new one.
+    fn rotate(&mut self) -> Result<()> {
+        if let Some(open) = self.open.take() {
+            open.file.sync_all().context(WriteSegmentSnafu {
+                path: &open.segment.path,
+            })?;
+            self.closed.push(open.segment);
+        }
+
+        let path = segment_path(&self.dir, self.next_id);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .context(WriteSegmentSnafu { path: &path })?;
+        self.next_id += 1;
+        self.open = Some(OpenSegment {
+            segment: Segment {
+                path,
+                max_sequence_number: None,
+            },
+            file,
+            size: 0,
+        });
+
+        Ok(())
+    }
+
+    fn truncate(&mut self, min_unpersisted_sequence_number: i64) -> Result<usize> {
+        let persisted = |segment: &Segment| {
+            segment
+                .max_sequence_number
+                .map(|max| (max as i64) < min_unpersisted_sequence_number)
+                .unwrap_or(true)
+        };
+
+        let mut deleted = 0;
+        let mut kept = Vec::with_capacity(self.closed.len());
+        for segment in std::mem::take(&mut self.closed) {
+            if persisted(&segment) {
+                delete_segment(&segment.path)?;
+                deleted += 1;
+            } else {
+                kept.push(segment);
+            }
+        }
+        self.closed = kept;
+
+        // The next append starts a new segment.
+        if let Some(open) = self.open.take() {
+            if open.size > 0 && persisted(&open.segment) {
+                drop(open.file);
+                delete_segment(&open.segment.path)?;
+                deleted += 1;
+            } else {
+                self.open = Some(open);
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", id, SEGMENT_EXTENSION))
+}
+
+fn delete_segment(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).context(DeleteSegmentSnafu { path }),
+    }
+}
+
+/// A WAL entry that has not been decoded into a [`DmlOperation`] yet.
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    kafka_partition: u32,
+    sequence_number: u64,
+    producer_ts: i64,
+    bytes_read: usize,
+    namespace: String,
+    data: Vec<u8>,
+}
+
+impl Entry {
+    fn decode(self) -> Result<DmlOperation> {
+        write_buffer::decode(
+            &self.data,
+            IoxHeaders::new(ContentType::Protobuf, None, self.namespace),
+            Sequence::new(self.kafka_partition, self.sequence_number),
+            Time::from_timestamp_nanos(self.producer_ts),
+            self.bytes_read,
+        )
+        .context(DecodeSnafu {
+            sequence_number: self.sequence_number,
+            path: self.path,
+        })
+    }
+}
+
+/// State of a [`Wal::replay`] stream.
+#[derive(Debug)]
+struct Replay {
+    /// Segments that were not read yet, oldest first.
+    segments: VecDeque<PathBuf>,
+
+    /// The segment that is currently read.
+    reader: Option<SegmentReader>,
+
+    min_sequence_number: i64,
+}
+
+impl Replay {
+    /// Read the next operation to replay, if any.
+    fn next_op(mut self) -> Result<Option<(DmlOperation, Self)>> {
+        loop {
+            if self.reader.is_none() {
+                match self.segments.pop_front() {
+                    Some(path) => {
+                        self.reader = SegmentReader::open(&path)?;
+                        continue;
+                    }
+                    None => return Ok(None),
+                }
+            }
+            let reader = self.reader.as_mut().expect("segment opened above");
+
+            match reader.next_entry()? {
+                Some(entry) if entry.sequence_number as i64 >= self.min_sequence_number => {
+                    return Ok(Some((entry.decode()?, self)));
+                }
+                Some(_) => {}
+                None => self.reader = None,
+            }
+        }
+    }
+}
+
+/// Reads the entries of a segment one at a time, stopping at the first torn or corrupt entry.
+#[derive(Debug)]
+struct SegmentReader {
+    path: PathBuf,
+    reader: BufReader<File>,
+    offset: u64,
+    len: u64,
+}
+
+impl SegmentReader {
+    /// Open the segment at `path`, returns `None` if it does not exist (anymore).
+    fn open(path: &Path) -> Result<Option<Self>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            // deleted by a concurrent truncation
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context(ReadSegmentSnafu { path }),
+        };
+        let len = file.metadata().context(ReadSegmentSnafu { path })?.len();
+
+        Ok(Some(Self {
+            path: path.to_owned(),
+            reader: BufReader::new(file),
+            offset: 0,
+            len,
+        }))
+    }
+
+    /// Read the next entry, returns `None` at the end of the segment.
+    fn next_entry(&mut self) -> Result<Option<Entry>> {
+        if self.offset >= self.len {
+            return Ok(None);
+        }
+
+        let entry = self.read_entry()?;
+        if entry.is_none() {
+            warn!(
+                path=?self.path,
+                offset=self.offset,
+                "ignoring torn or corrupt entry at the end of WAL segment"
+            );
+            // skip the rest of the segment
+            self.offset = self.len;
+        }
+        Ok(entry)
+    }
+
+    /// Read the entry at the current offset, returning `None` if it is incomplete or does not
+    /// match its checksum.
+    fn read_entry(&mut self) -> Result<Option<Entry>> {
+        let mut header = [0; FRAME_HEADER_LEN];
+        if !self.read_exact(&mut header)? {
+            return Ok(None);
+        }
+        let len = u32::from_le_bytes(header[0..4].try_into().expect("4 bytes")) as u64;
+        let crc = u32::from_le_bytes(header[4..8].try_into().expect("4 bytes"));
+
+        // A corrupt length must not cause a huge allocation.
+        if len
+            > self
+                .len
+                .saturating_sub(self.offset + FRAME_HEADER_LEN as u64)
+        {
+            return Ok(None);
+        }
+        let mut payload = vec![0; len as usize];
+        if !self.read_exact(&mut payload)? || crc32fast::hash(&payload) != crc {
+            return Ok(None);
+        }
+
+        let entry = parse_payload(&self.path, &payload);
+        if entry.is_some() {
+            self.offset += FRAME_HEADER_LEN as u64 + len;
+        }
+        Ok(entry)
+    }
+
+    /// Fill `buf`, returning `false` if the segment ends before.
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<bool> {
+        match self.reader.read_exact(buf) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e).context(ReadSegmentSnafu { path: &self.path }),
+        }
+    }
+}
+
+/// Parse the payload of an entry, returning `None` if it is malformed.
+fn parse_payload(path: &Path, payload: &[u8]) -> Option<Entry> {
+    let kafka_partition = u32::from_le_bytes(payload.get(0..4)?.try_into().ok()?);
+    let sequence_number = u64::from_le_bytes(payload.get(4..12)?.try_into().ok()?);
+    let producer_ts = i64::from_le_bytes(payload.get(12..20)?.try_into().ok()?);
+    let bytes_read = u64::from_le_bytes(payload.get(20..28)?.try_into().ok()?) as usize;
+    let namespace_len = u32::from_le_bytes(payload.get(28..32)?.try_into().ok()?) as usize;
+    let namespace = std::str::from_utf8(payload.get(32..32 + namespace_len)?).ok()?;
+    let op = payload.get(32 + namespace_len..)?;
+
+    Some(Entry {
+        path: path.to_owned(),
+        kafka_partition,
+        sequence_number,
+        producer_ts,
+        bytes_read,
+        namespace: namespace.to_owned(),
+        data: op.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dml::{test_util::assert_op_eq, DmlMeta, DmlWrite};
+    use futures::TryStreamExt;
+    use mutable_batch_lp::lines_to_batches;
+
+    fn write(namespace: &str, lp: &str, sequence_number: u64) -> DmlOperation {
+        DmlOperation::Write(DmlWrite::new(
+            namespace,
+            lines_to_batches(lp, 0).unwrap(),
+            DmlMeta::sequenced(
+                Sequence::new(1, sequence_number),
+                Time::from_timestamp_nanos(42),
+                None,
+                100,
+            ),
+        ))
+    }
+
+    async fn replay(
+        wal: &Wal,
+        sequencer_id: SequencerId,
+        min_sequence_number: SequenceNumber,
+    ) -> Vec<DmlOperation> {
+        wal.replay(sequencer_id, min_sequence_number)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    fn sequence_numbers(ops: &[DmlOperation]) -> Vec<u64> {
+        ops.iter()
+            .map(|op| op.meta().sequence().unwrap().number)
+            .collect()
+    }
+
+    fn segments(dir: &Path, sequencer_id: SequencerId) -> usize {
+        std::fs::read_dir(dir.join(format!("sequencer-{}", sequencer_id.get())))
+            .unwrap()
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_append_replay() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let registry = metric::Registry::default();
+        let sequencer_id = SequencerId::new(1);
+        let other_sequencer_id = SequencerId::new(2);
+
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        let w1 = write("ns", "cpu foo=1 10", 1);
+        let w2 = write("ns", "cpu foo=2 20\nmem bar=1 20", 2);
+        wal.append(sequencer_id, &w1).await.unwrap();
+        wal.append(sequencer_id, &w2).await.unwrap();
+        wal.append(other_sequencer_id, &write("ns2", "cpu foo=3 30", 3))
+            .await
+            .unwrap();
+        drop(wal);
+
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        let ops = replay(&wal, sequencer_id, SequenceNumber::new(0)).await;
+        assert_eq!(ops.len(), 2);
+        assert_op_eq(&ops[0], &w1);
+        assert_op_eq(&ops[1], &w2);
+
+        let ops = replay(&wal, sequencer_id, SequenceNumber::new(2)).await;
+        assert_eq!(ops.len(), 1);
+        assert_op_eq(&ops[0], &w2);
+
+        let ops = replay(&wal, other_sequencer_id, SequenceNumber::new(0)).await;
+        assert_eq!(sequence_numbers(&ops), vec![3]);
+        assert_eq!(ops[0].namespace(), "ns2");
+
+        let ops = replay(&wal, SequencerId::new(3), SequenceNumber::new(0)).await;
+        assert!(ops.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_torn_entry() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let registry = metric::Registry::default();
+        let sequencer_id = SequencerId::new(1);
+
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        wal.append(sequencer_id, &write("ns", "cpu foo=1 10", 1))
+            .await
+            .unwrap();
+        wal.append(sequencer_id, &write("ns", "cpu foo=2 20", 2))
+            .await
+            .unwrap();
+        drop(wal);
+
+        // cut the last entry in half
+        let path = segment_path(&dir.path().join("sequencer-1"), 0);
+        let len = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 5)
+            .unwrap();
+
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        let ops = replay(&wal, sequencer_id, SequenceNumber::new(0)).await;
+        assert_eq!(sequence_numbers(&ops), vec![1]);
+
+        // appends go to a new segment
+        wal.append(sequencer_id, &write("ns", "cpu foo=3 30", 3))
+            .await
+            .unwrap();
+        drop(wal);
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        let ops = replay(&wal, sequencer_id, SequenceNumber::new(0)).await;
+        assert_eq!(sequence_numbers(&ops), vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_length() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let registry = metric::Registry::default();
+        let sequencer_id = SequencerId::new(1);
+
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        wal.append(sequencer_id, &write("ns", "cpu foo=1 10", 1))
+            .await
+            .unwrap();
+        drop(wal);
+
+        // a frame header claiming a payload far larger than the segment
+        let path = segment_path(&dir.path().join("sequencer-1"), 0);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        file.write_all(&0_u32.to_le_bytes()).unwrap();
+        drop(file);
+
+        let wal = Wal::new(dir.path(), 1024 * 1024, &registry).unwrap();
+        let ops = replay(&wal, sequencer_id, SequenceNumber::new(0)).await;
+        assert_eq!(sequence_numbers(&ops), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_truncate() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let registry = metric::Registry::default();
+        let sequencer_id = SequencerId::new(1);
+
+        // tiny segments, so that every append starts a new one
+        let wal = Wal::new(dir.path(), 1, &registry).unwrap();
+        for sequence_number in 1..=4 {
+            wal.append(sequencer_id, &write("ns", "cpu foo=1 10", sequence_number))
+                .await
+                .unwrap();
+        }
+        assert_eq!(segments(dir.path(), sequencer_id), 4);
+
+        assert_eq!(
+            wal.truncate(sequencer_id, SequenceNumber::new(1))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            wal.truncate(sequencer_id, SequenceNumber::new(3))
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(segments(dir.path(), sequencer_id), 2);
+
+        let ops = replay(&wal, sequencer_id, SequenceNumber::new(0)).await;
+        assert_eq!(sequence_numbers(&ops), vec![3, 4]);
+
+        // everything persisted, including the segment that is appended to
+        assert_eq!(
+            wal.truncate(sequencer_id, SequenceNumber::new(5))
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(segments(dir.path(), sequencer_id), 0);
+
+        let count = registry
+            .get_instrument::<metric::Metric<U64Counter>>("ingester_wal_segments_deleted_total")
+            .unwrap()
+            .get_observer(&metric::Attributes::from(&[]))
+            .unwrap()
+            .fetch();
+        assert_eq!(count, 4);
+    }
+}
@@ -0,0 +1,9 @@
+ALTER TABLE
+    IF EXISTS namespace
+    ADD
+    COLUMN deleted_at BIGINT NULL;
+
+ALTER TABLE
+    IF EXISTS table_name
+    ADD
+    COLUMN deleted_at BIGINT NULL;
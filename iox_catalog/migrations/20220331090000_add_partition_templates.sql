@@ -0,0 +1,9 @@
+ALTER TABLE
+    IF EXISTS namespace
+    ADD
+    COLUMN partition_template VARCHAR NULL;
+
+ALTER TABLE
+    IF EXISTS table_name
+    ADD
+    COLUMN partition_template VARCHAR NULL;
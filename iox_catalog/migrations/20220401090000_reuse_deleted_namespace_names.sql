@@ -0,0 +1,8 @@
+-- Only live namespaces need unique names, the name of a soft-deleted namespace can be reused.
+ALTER TABLE
+    IF EXISTS namespace
+    DROP CONSTRAINT IF EXISTS namespace_name_unique;
+
+CREATE UNIQUE INDEX IF NOT EXISTS namespace_name_unique
+    ON namespace (name)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,105 @@
+-- The SQLite catalog starts from the current state of the Postgres schema (see
+-- `../../migrations`) rather than replaying its history, as there are no
+-- existing SQLite catalogs to migrate.
+CREATE TABLE IF NOT EXISTS kafka_topic (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    CONSTRAINT kafka_topic_name_unique UNIQUE (name)
+);
+
+CREATE TABLE IF NOT EXISTS query_pool (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    CONSTRAINT query_pool_name_unique UNIQUE (name)
+);
+
+CREATE TABLE IF NOT EXISTS namespace (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    retention_duration VARCHAR,
+    kafka_topic_id INTEGER NOT NULL REFERENCES kafka_topic (id),
+    query_pool_id INTEGER NOT NULL REFERENCES query_pool (id),
+    max_tables INTEGER NOT NULL DEFAULT 10000,
+    max_columns_per_table INTEGER NOT NULL DEFAULT 1000,
+    deleted_at BIGINT NULL,
+    CONSTRAINT namespace_name_unique UNIQUE (name)
+);
+
+CREATE TABLE IF NOT EXISTS table_name (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    namespace_id INTEGER NOT NULL REFERENCES namespace (id),
+    name VARCHAR NOT NULL,
+    deleted_at BIGINT NULL,
+    CONSTRAINT table_name_unique UNIQUE (namespace_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS table_name_namespace_idx ON table_name (namespace_id);
+
+CREATE TABLE IF NOT EXISTS column_name (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id INTEGER NOT NULL REFERENCES table_name (id),
+    name VARCHAR NOT NULL,
+    column_type SMALLINT NOT NULL,
+    CONSTRAINT column_name_unique UNIQUE (table_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS sequencer (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kafka_topic_id INTEGER NOT NULL REFERENCES kafka_topic (id),
+    kafka_partition INTEGER NOT NULL,
+    min_unpersisted_sequence_number BIGINT,
+    CONSTRAINT sequencer_unique UNIQUE (kafka_topic_id, kafka_partition)
+);
+
+CREATE TABLE IF NOT EXISTS sharding_rule_override (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    namespace_id INTEGER NOT NULL REFERENCES namespace (id),
+    table_id INTEGER NOT NULL REFERENCES table_name (id),
+    column_id INTEGER NOT NULL REFERENCES column_name (id)
+);
+
+CREATE TABLE IF NOT EXISTS partition (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sequencer_id INTEGER NOT NULL REFERENCES sequencer (id),
+    table_id INTEGER NOT NULL REFERENCES table_name (id),
+    partition_key VARCHAR NOT NULL,
+    CONSTRAINT partition_key_unique UNIQUE (table_id, partition_key)
+);
+
+CREATE TABLE IF NOT EXISTS parquet_file (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sequencer_id INTEGER NOT NULL REFERENCES sequencer (id),
+    table_id INTEGER NOT NULL REFERENCES table_name (id),
+    partition_id INTEGER NOT NULL REFERENCES partition (id),
+    object_store_id BLOB NOT NULL,
+    min_sequence_number BIGINT,
+    max_sequence_number BIGINT,
+    min_time BIGINT,
+    max_time BIGINT,
+    to_delete BIGINT,
+    row_count BIGINT NOT NULL DEFAULT 0,
+    file_size_bytes BIGINT NOT NULL DEFAULT 0,
+    parquet_metadata BLOB NOT NULL DEFAULT x'',
+    compaction_level SMALLINT NOT NULL DEFAULT 0,
+    created_at BIGINT,
+    CONSTRAINT parquet_location_unique UNIQUE (object_store_id)
+);
+
+CREATE INDEX IF NOT EXISTS parquet_file_table_idx ON parquet_file (table_id);
+
+CREATE TABLE IF NOT EXISTS tombstone (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id INTEGER NOT NULL REFERENCES table_name (id),
+    sequencer_id INTEGER NOT NULL REFERENCES sequencer (id),
+    sequence_number BIGINT NOT NULL,
+    min_time BIGINT NOT NULL,
+    max_time BIGINT NOT NULL,
+    serialized_predicate TEXT NOT NULL,
+    CONSTRAINT tombstone_unique UNIQUE (table_id, sequencer_id, sequence_number)
+);
+
+CREATE TABLE IF NOT EXISTS processed_tombstone (
+    tombstone_id INTEGER NOT NULL REFERENCES tombstone (id),
+    parquet_file_id INTEGER NOT NULL REFERENCES parquet_file (id),
+    PRIMARY KEY (tombstone_id, parquet_file_id)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE namespace ADD COLUMN partition_template VARCHAR NULL;
+
+ALTER TABLE table_name ADD COLUMN partition_template VARCHAR NULL;
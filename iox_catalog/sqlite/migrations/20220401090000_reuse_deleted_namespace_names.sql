@@ -0,0 +1,30 @@
+-- Only live namespaces need unique names, the name of a soft-deleted namespace can be reused.
+--
+-- SQLite cannot drop a constraint, so the table is rebuilt. This relies on foreign key enforcement
+-- being disabled while migrating.
+CREATE TABLE namespace_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    retention_duration VARCHAR,
+    kafka_topic_id INTEGER NOT NULL REFERENCES kafka_topic (id),
+    query_pool_id INTEGER NOT NULL REFERENCES query_pool (id),
+    max_tables INTEGER NOT NULL DEFAULT 10000,
+    max_columns_per_table INTEGER NOT NULL DEFAULT 1000,
+    deleted_at BIGINT NULL,
+    partition_template VARCHAR NULL
+);
+
+INSERT INTO namespace_new (
+    id, name, retention_duration, kafka_topic_id, query_pool_id, max_tables,
+    max_columns_per_table, deleted_at, partition_template
+)
+SELECT
+    id, name, retention_duration, kafka_topic_id, query_pool_id, max_tables,
+    max_columns_per_table, deleted_at, partition_template
+FROM namespace;
+
+DROP TABLE namespace;
+
+ALTER TABLE namespace_new RENAME TO namespace;
+
+CREATE UNIQUE INDEX namespace_name_unique ON namespace (name) WHERE deleted_at IS NULL;
@@ -2,12 +2,13 @@
 
 use async_trait::async_trait;
 use data_types2::{
-    Column, ColumnSchema, ColumnType, KafkaPartition, KafkaTopic, KafkaTopicId, Namespace,
-    NamespaceId, NamespaceSchema, ParquetFile, ParquetFileId, ParquetFileParams, Partition,
-    PartitionId, PartitionInfo, ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber,
-    Sequencer, SequencerId, Table, TableId, TablePartition, TableSchema, Timestamp, Tombstone,
-    TombstoneId,
+    encode_partition_template, parse_partition_template, Column, ColumnSchema, ColumnType,
+    KafkaPartition, KafkaTopic, KafkaTopicId, Namespace, NamespaceId, NamespaceSchema, ParquetFile,
+    ParquetFileId, ParquetFileParams, Partition, PartitionId, PartitionInfo, PartitionTemplate,
+    ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber, Sequencer, SequencerId, Table,
+    TableId, TablePartition, TableSchema, Timestamp, Tombstone, TombstoneId,
 };
+use observability_deps::tracing::warn;
 use snafu::{OptionExt, Snafu};
 use std::{collections::BTreeMap, convert::TryFrom, fmt::Debug, sync::Arc};
 use uuid::Uuid;
@@ -103,6 +104,16 @@ pub enum Error {
 
     #[snafu(display("Datbase setup error: {}", source))]
     Setup { source: sqlx::Error },
+
+    #[snafu(display("{}", source))]
+    InvalidRetentionDuration {
+        source: data_types2::RetentionDurationParseError,
+    },
+
+    #[snafu(display("{}", source))]
+    InvalidPartitionTemplate {
+        source: data_types2::PartitionTemplateParseError,
+    },
 }
 
 /// A specialized `Error` for Catalog errors
@@ -255,13 +266,14 @@ pub trait NamespaceRepo: Send + Sync {
         query_pool_id: QueryPoolId,
     ) -> Result<Namespace>;
 
-    /// List all namespaces.
+    /// List all namespaces that are not soft-deleted.
     async fn list(&mut self) -> Result<Vec<Namespace>>;
 
-    /// Gets the namespace by its ID.
+    /// Gets the namespace by its ID. Soft-deleted namespaces are returned as well, check
+    /// [`Namespace::deleted_at`].
     async fn get_by_id(&mut self, id: NamespaceId) -> Result<Option<Namespace>>;
 
-    /// Gets the namespace by its unique name.
+    /// Gets the namespace by its unique name, unless it is soft-deleted.
     async fn get_by_name(&mut self, name: &str) -> Result<Option<Namespace>>;
 
     /// Update the limit on the number of tables that can exist per namespace.
@@ -269,27 +281,80 @@ pub trait NamespaceRepo: Send + Sync {
 
     /// Update the limit on the number of columns that can exist per table in a given namespace.
     async fn update_column_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace>;
+
+    /// Assign the namespace to a different query pool.
+    async fn update_query_pool(
+        &mut self,
+        name: &str,
+        query_pool_id: QueryPoolId,
+    ) -> Result<Namespace>;
+
+    /// Update the retention duration of the namespace. Data older than the retention period is
+    /// rejected on write, hidden from queries and eventually removed from object storage.
+    ///
+    /// The duration must be parseable by [`data_types2::parse_retention_duration`].
+    async fn update_retention_duration(
+        &mut self,
+        name: &str,
+        retention_duration: &str,
+    ) -> Result<Namespace>;
+
+    /// Set the partition template used to partition writes to the namespace, or reset it to the
+    /// default template if `None`. Existing partitions are not affected.
+    async fn update_partition_template(
+        &mut self,
+        name: &str,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Namespace>;
+
+    /// Soft-delete the namespace and flag all of its parquet files for deletion.
+    ///
+    /// The namespace is no longer returned by [`get_by_name`](Self::get_by_name) and
+    /// [`list`](Self::list), so it stops accepting writes and serving queries. Its name can be
+    /// reused by a new namespace.
+    async fn soft_delete(&mut self, name: &str) -> Result<()>;
+
+    /// List soft-deleted namespaces, only those deleted after `deleted_after` if it is set.
+    async fn list_soft_deleted(
+        &mut self,
+        deleted_after: Option<Timestamp>,
+    ) -> Result<Vec<Namespace>>;
 }
 
 /// Functions for working with tables in the catalog
 #[async_trait]
 pub trait TableRepo: Send + Sync {
-    /// Creates the table in the catalog or get the existing record by name.
+    /// Creates the table in the catalog or get the existing record by name. A soft-deleted table
+    /// of that name is restored without its data and columns.
     async fn create_or_get(&mut self, name: &str, namespace_id: NamespaceId) -> Result<Table>;
 
-    /// get table by ID
+    /// get table by ID. Soft-deleted tables are returned as well, check [`Table::deleted_at`].
     async fn get_by_id(&mut self, table_id: TableId) -> Result<Option<Table>>;
 
-    /// get table by namespace ID and name
+    /// get table by namespace ID and name, unless it is soft-deleted
     async fn get_by_namespace_and_name(
         &mut self,
         namespace_id: NamespaceId,
         name: &str,
     ) -> Result<Option<Table>>;
 
-    /// Lists all tables in the catalog for the given namespace id.
+    /// Lists all tables in the catalog for the given namespace id that are not soft-deleted.
     async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Table>>;
 
+    /// Set the partition template used to partition writes to the table, overriding the template
+    /// of its namespace, or reset it to the namespace template if `None`.
+    async fn update_partition_template(
+        &mut self,
+        table_id: TableId,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Table>;
+
+    /// Soft-delete the table and flag all of its parquet files for deletion.
+    async fn soft_delete(&mut self, table_id: TableId) -> Result<()>;
+
+    /// List soft-deleted tables, only those deleted after `deleted_after` if it is set.
+    async fn list_soft_deleted(&mut self, deleted_after: Option<Timestamp>) -> Result<Vec<Table>>;
+
     /// Gets the table persistence info for the given sequencer
     async fn get_table_persist_info(
         &mut self,
@@ -508,9 +573,27 @@ pub trait ParquetFileRepo: Send + Sync {
         parquet_file_ids: &[ParquetFileId],
     ) -> Result<Vec<ParquetFileId>>;
 
+    /// List all parquet files that were [flagged for deletion](Self::flag_for_delete) before
+    /// `older_than`. The garbage collector uses this to find files whose grace period is over.
+    async fn list_to_delete_older_than(
+        &mut self,
+        older_than: Timestamp,
+    ) -> Result<Vec<ParquetFile>>;
+
+    /// Permanently remove the specified parquet files from the catalog, together with the
+    /// processed tombstones that reference them. Files that are not flagged for deletion are
+    /// left untouched. Returns the IDs of the files that were deleted.
+    async fn delete_by_ids(
+        &mut self,
+        parquet_file_ids: &[ParquetFileId],
+    ) -> Result<Vec<ParquetFileId>>;
+
     /// Verify if the parquet file exists by selecting its id
     async fn exist(&mut self, id: ParquetFileId) -> Result<bool>;
 
+    /// Verify if a parquet file (flagged for deletion or not) refers to the given object store id
+    async fn exist_by_object_store_id(&mut self, object_store_id: Uuid) -> Result<bool>;
+
     /// Return count
     async fn count(&mut self) -> Result<i64>;
 }
@@ -551,19 +634,45 @@ where
     let columns = repos.columns().list_by_namespace_id(namespace.id).await?;
     let tables = repos.tables().list_by_namespace_id(namespace.id).await?;
 
+    // Retention durations are validated when they are written, so an unparseable value can only
+    // originate from outside of the catalog API. Don't fail all writes to the namespace because of
+    // it, but retain its data forever instead.
+    let retention_period = namespace.retention_period().unwrap_or_else(|e| {
+        warn!(%e, namespace=%namespace.name, "ignoring invalid namespace retention duration");
+        None
+    });
+
+    // The same applies to partition templates, fall back to the default template instead.
+    let partition_template = namespace.partition_template().unwrap_or_else(|e| {
+        warn!(%e, namespace=%namespace.name, "ignoring invalid namespace partition template");
+        None
+    });
+
+    let (max_tables, max_columns_per_table) =
+        (namespace.max_tables, namespace.max_columns_per_table);
+
     let mut namespace = NamespaceSchema::new(
         namespace.id,
         namespace.kafka_topic_id,
         namespace.query_pool_id,
     );
+    namespace.retention_period = retention_period;
+    namespace.partition_template = partition_template;
+    namespace.max_tables = max_tables;
+    namespace.max_columns_per_table = max_columns_per_table;
 
     let mut table_id_to_schema = BTreeMap::new();
     for t in tables {
-        table_id_to_schema.insert(t.id, (t.name, TableSchema::new(t.id)));
+        let schema = table_schema(&t);
+        table_id_to_schema.insert(t.id, (t.name, schema));
     }
 
     for c in columns {
-        let (_, t) = table_id_to_schema.get_mut(&c.table_id).unwrap();
+        let t = match table_id_to_schema.get_mut(&c.table_id) {
+            Some((_, t)) => t,
+            // column of a soft-deleted table
+            None => continue,
+        };
         match ColumnType::try_from(c.column_type) {
             Ok(column_type) => {
                 t.columns.insert(
@@ -590,11 +699,36 @@ where
     Ok(namespace)
 }
 
+/// Initialise an empty [`TableSchema`] for `table`, including its partition template.
+pub(crate) fn table_schema(table: &Table) -> TableSchema {
+    let mut schema = TableSchema::new(table.id);
+    schema.partition_template = table.partition_template().unwrap_or_else(|e| {
+        warn!(%e, table=%table.name, "ignoring invalid table partition template");
+        None
+    });
+    schema
+}
+
+/// Encode `partition_template` for storage in the catalog, rejecting templates that cannot be
+/// used to partition writes.
+pub(crate) fn encode_checked_partition_template(
+    partition_template: Option<&PartitionTemplate>,
+) -> Result<Option<String>> {
+    partition_template
+        .map(|t| {
+            let encoded = encode_partition_template(t);
+            parse_partition_template(&encoded)
+                .map_err(|source| Error::InvalidPartitionTemplate { source })?;
+            Ok(encoded)
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 pub(crate) mod test_helpers {
     use super::*;
     use ::test_helpers::{assert_contains, tracing::TracingCapture};
-    use data_types2::ColumnId;
+    use data_types2::{ColumnId, TemplatePart};
     use metric::{Attributes, Metric, U64Histogram};
     use std::{sync::Arc, time::Duration};
 
@@ -610,22 +744,28 @@ pub(crate) mod test_helpers {
         test_tombstone(Arc::clone(&catalog)).await;
         test_tombstones_by_parquet_file(Arc::clone(&catalog)).await;
         test_parquet_file(Arc::clone(&catalog)).await;
+        test_parquet_file_delete(Arc::clone(&catalog)).await;
         test_parquet_file_compaction_level_0(Arc::clone(&catalog)).await;
         test_parquet_file_compaction_level_1(Arc::clone(&catalog)).await;
         test_update_to_compaction_level_1(Arc::clone(&catalog)).await;
         test_txn_isolation(Arc::clone(&catalog)).await;
         test_txn_drop(Arc::clone(&catalog)).await;
+        test_soft_delete(Arc::clone(&catalog)).await;
 
         let metrics = catalog.metrics();
         assert_metric_hit(&*metrics, "kafka_create_or_get");
         assert_metric_hit(&*metrics, "query_create_or_get");
         assert_metric_hit(&*metrics, "namespace_create");
+        assert_metric_hit(&*metrics, "namespace_update_retention_duration");
         assert_metric_hit(&*metrics, "table_create_or_get");
         assert_metric_hit(&*metrics, "column_create_or_get");
         assert_metric_hit(&*metrics, "sequencer_create_or_get");
         assert_metric_hit(&*metrics, "partition_create_or_get");
         assert_metric_hit(&*metrics, "tombstone_create_or_get");
         assert_metric_hit(&*metrics, "parquet_create");
+        assert_metric_hit(&*metrics, "parquet_delete_by_ids");
+        assert_metric_hit(&*metrics, "namespace_soft_delete");
+        assert_metric_hit(&*metrics, "table_soft_delete");
     }
 
     async fn test_setup(catalog: Arc<dyn Catalog>) {
@@ -737,6 +877,118 @@ pub(crate) mod test_helpers {
             .await
             .expect("namespace should be updateable");
         assert_eq!(NEW_COLUMN_LIMIT, modified.max_columns_per_table);
+
+        let modified = repos
+            .namespaces()
+            .update_retention_duration(namespace_name, "7d")
+            .await
+            .expect("namespace should be updateable");
+        assert_eq!(modified.retention_duration.as_deref(), Some("7d"));
+        assert_eq!(
+            modified.retention_period().unwrap(),
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        let schema = get_schema_by_name(namespace_name, repos.as_mut())
+            .await
+            .unwrap();
+        assert_eq!(
+            schema.retention_period,
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+
+        let err = repos
+            .namespaces()
+            .update_retention_duration(namespace_name, "7 days")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidRetentionDuration { .. }));
+
+        let err = repos
+            .namespaces()
+            .update_retention_duration("does_not_exist", "inf")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NamespaceNotFound { .. }));
+
+        let pool2 = repos.query_pools().create_or_get("foo2").await.unwrap();
+        let modified = repos
+            .namespaces()
+            .update_query_pool(namespace_name, pool2.id)
+            .await
+            .expect("namespace should be updateable");
+        assert_eq!(modified.query_pool_id, pool2.id);
+
+        let err = repos
+            .namespaces()
+            .update_query_pool("does_not_exist", pool2.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NamespaceNotFound { .. }));
+
+        let err = repos
+            .namespaces()
+            .create("test_namespace3", "forever", kafka.id, pool.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidRetentionDuration { .. }));
+
+        assert_eq!(modified.partition_template().unwrap(), None);
+        let template = PartitionTemplate {
+            parts: vec![
+                TemplatePart::Table,
+                TemplatePart::TimeFormat("%Y-%m-%d %H".to_string()),
+            ],
+        };
+        let modified = repos
+            .namespaces()
+            .update_partition_template(namespace_name, Some(&template))
+            .await
+            .expect("namespace should be updateable");
+        assert_eq!(
+            modified
+                .partition_template()
+                .unwrap()
+                .map(|t| t.template().clone()),
+            Some(template.clone())
+        );
+        let schema = get_schema_by_name(namespace_name, repos.as_mut())
+            .await
+            .unwrap();
+        assert_eq!(
+            schema.partition_template.map(|t| t.template().clone()),
+            Some(template)
+        );
+
+        let modified = repos
+            .namespaces()
+            .update_partition_template(namespace_name, None)
+            .await
+            .expect("namespace should be updateable");
+        assert_eq!(modified.partition_template, None);
+
+        let err = repos
+            .namespaces()
+            .update_partition_template(namespace_name, Some(&PartitionTemplate::default()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidPartitionTemplate { .. }));
+
+        let invalid_format = PartitionTemplate {
+            parts: vec![TemplatePart::TimeFormat("%Q".to_string())],
+        };
+        let err = repos
+            .namespaces()
+            .update_partition_template(namespace_name, Some(&invalid_format))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidPartitionTemplate { .. }));
+
+        let err = repos
+            .namespaces()
+            .update_partition_template("does_not_exist", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NamespaceNotFound { .. }));
     }
 
     async fn test_table(catalog: Arc<dyn Catalog>) {
@@ -841,6 +1093,46 @@ pub(crate) mod test_helpers {
             Some(foo_table)
         );
 
+        // test we can set and reset the partition template of a table
+        assert_eq!(t.partition_template().unwrap(), None);
+        let template = PartitionTemplate {
+            parts: vec![TemplatePart::TimeFormat("%Y".to_string())],
+        };
+        let modified = repos
+            .tables()
+            .update_partition_template(t.id, Some(&template))
+            .await
+            .unwrap();
+        assert_eq!(
+            modified
+                .partition_template()
+                .unwrap()
+                .map(|t| t.template().clone()),
+            Some(template.clone())
+        );
+        let schema = get_schema_by_name(&namespace.name, repos.as_mut())
+            .await
+            .unwrap();
+        assert_eq!(
+            schema.tables["test_table"]
+                .partition_template
+                .as_ref()
+                .map(|t| t.template()),
+            Some(&template)
+        );
+        let modified = repos
+            .tables()
+            .update_partition_template(t.id, None)
+            .await
+            .unwrap();
+        assert_eq!(modified, t);
+        let err = repos
+            .tables()
+            .update_partition_template(TableId::new(i32::MAX), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::TableNotFound { .. }));
+
         // test we can get table persistence info with no persistence so far
         let seq = repos
             .sequencers()
@@ -1803,6 +2095,170 @@ pub(crate) mod test_helpers {
         assert!(files.is_empty());
     }
 
+    async fn test_parquet_file_delete(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let kafka = repos.kafka_topics().create_or_get("foo").await.unwrap();
+        let pool = repos.query_pools().create_or_get("foo").await.unwrap();
+        let namespace = repos
+            .namespaces()
+            .create(
+                "namespace_parquet_file_delete_test",
+                "inf",
+                kafka.id,
+                pool.id,
+            )
+            .await
+            .unwrap();
+        let table = repos
+            .tables()
+            .create_or_get("test_table", namespace.id)
+            .await
+            .unwrap();
+        let sequencer = repos
+            .sequencers()
+            .create_or_get(&kafka, KafkaPartition::new(100))
+            .await
+            .unwrap();
+        let partition = repos
+            .partitions()
+            .create_or_get("one", sequencer.id, table.id)
+            .await
+            .unwrap();
+
+        let parquet_file_params = ParquetFileParams {
+            sequencer_id: sequencer.id,
+            table_id: partition.table_id,
+            partition_id: partition.id,
+            object_store_id: Uuid::new_v4(),
+            min_sequence_number: SequenceNumber::new(10),
+            max_sequence_number: SequenceNumber::new(140),
+            min_time: Timestamp::new(1),
+            max_time: Timestamp::new(10),
+            file_size_bytes: 1337,
+            parquet_metadata: b"md1".to_vec(),
+            row_count: 0,
+            created_at: Timestamp::new(1),
+        };
+        let flagged = repos
+            .parquet_files()
+            .create(parquet_file_params.clone())
+            .await
+            .unwrap();
+        let other_flagged = repos
+            .parquet_files()
+            .create(ParquetFileParams {
+                object_store_id: Uuid::new_v4(),
+                ..parquet_file_params.clone()
+            })
+            .await
+            .unwrap();
+        let live = repos
+            .parquet_files()
+            .create(ParquetFileParams {
+                object_store_id: Uuid::new_v4(),
+                ..parquet_file_params.clone()
+            })
+            .await
+            .unwrap();
+        let ids = [flagged.id, other_flagged.id, live.id];
+
+        // the processed tombstone must not prevent the deletion of the file
+        let tombstone = repos
+            .tombstones()
+            .create_or_get(
+                table.id,
+                sequencer.id,
+                SequenceNumber::new(150),
+                Timestamp::new(1),
+                Timestamp::new(10),
+                "whatevs",
+            )
+            .await
+            .unwrap();
+        repos
+            .processed_tombstones()
+            .create(flagged.id, tombstone.id)
+            .await
+            .unwrap();
+
+        repos
+            .parquet_files()
+            .flag_for_delete(flagged.id)
+            .await
+            .unwrap();
+        repos
+            .parquet_files()
+            .flag_for_delete(other_flagged.id)
+            .await
+            .unwrap();
+
+        // other tests share the catalog, so only look at the files created here
+        let files: Vec<_> = repos
+            .parquet_files()
+            .list_to_delete_older_than(Timestamp::new(0))
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|f| ids.contains(&f.id))
+            .collect();
+        assert!(files.is_empty());
+        let mut files: Vec<_> = repos
+            .parquet_files()
+            .list_to_delete_older_than(Timestamp::new(i64::MAX))
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|f| ids.contains(&f.id))
+            .map(|f| f.id)
+            .collect();
+        files.sort();
+        assert_eq!(files, vec![flagged.id, other_flagged.id]);
+
+        assert!(repos
+            .parquet_files()
+            .exist_by_object_store_id(flagged.object_store_id)
+            .await
+            .unwrap());
+        assert!(!repos
+            .parquet_files()
+            .exist_by_object_store_id(Uuid::new_v4())
+            .await
+            .unwrap());
+
+        // files that are not flagged for deletion are never deleted
+        let deleted = repos
+            .parquet_files()
+            .delete_by_ids(&[flagged.id, live.id])
+            .await
+            .unwrap();
+        assert_eq!(deleted, vec![flagged.id]);
+
+        assert!(!repos.parquet_files().exist(flagged.id).await.unwrap());
+        assert!(!repos
+            .parquet_files()
+            .exist_by_object_store_id(flagged.object_store_id)
+            .await
+            .unwrap());
+        assert!(!repos
+            .processed_tombstones()
+            .exist(flagged.id, tombstone.id)
+            .await
+            .unwrap());
+        assert!(repos.parquet_files().exist(other_flagged.id).await.unwrap());
+        assert!(repos.parquet_files().exist(live.id).await.unwrap());
+
+        // IDs of deleted files are not handed out again
+        let new_file = repos
+            .parquet_files()
+            .create(ParquetFileParams {
+                object_store_id: Uuid::new_v4(),
+                ..parquet_file_params
+            })
+            .await
+            .unwrap();
+        assert!(!ids.contains(&new_file.id));
+    }
+
     async fn test_parquet_file_compaction_level_0(catalog: Arc<dyn Catalog>) {
         let mut repos = catalog.repositories().await;
         let kafka = repos.kafka_topics().create_or_get("foo").await.unwrap();
@@ -2321,6 +2777,278 @@ pub(crate) mod test_helpers {
         txn.abort().await.unwrap();
     }
 
+    async fn test_soft_delete(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let kafka = repos.kafka_topics().create_or_get("foo").await.unwrap();
+        let pool = repos.query_pools().create_or_get("foo").await.unwrap();
+        let namespace = repos
+            .namespaces()
+            .create("namespace_soft_delete_test", "inf", kafka.id, pool.id)
+            .await
+            .unwrap();
+        let sequencer = repos
+            .sequencers()
+            .create_or_get(&kafka, KafkaPartition::new(4242))
+            .await
+            .unwrap();
+
+        let t1 = repos
+            .tables()
+            .create_or_get("t1", namespace.id)
+            .await
+            .unwrap();
+        let t2 = repos
+            .tables()
+            .create_or_get("t2", namespace.id)
+            .await
+            .unwrap();
+        repos
+            .columns()
+            .create_or_get("c", t1.id, ColumnType::I64)
+            .await
+            .unwrap();
+
+        let mut files = vec![];
+        for table in [&t1, &t2] {
+            let partition = repos
+                .partitions()
+                .create_or_get("1970-01-01", sequencer.id, table.id)
+                .await
+                .unwrap();
+            let file = repos
+                .parquet_files()
+                .create(ParquetFileParams {
+                    sequencer_id: sequencer.id,
+                    table_id: table.id,
+                    partition_id: partition.id,
+                    object_store_id: Uuid::new_v4(),
+                    min_sequence_number: SequenceNumber::new(1),
+                    max_sequence_number: SequenceNumber::new(2),
+                    min_time: Timestamp::new(1),
+                    max_time: Timestamp::new(2),
+                    file_size_bytes: 0,
+                    parquet_metadata: vec![],
+                    row_count: 0,
+                    created_at: Timestamp::new(1),
+                })
+                .await
+                .unwrap();
+            files.push(file);
+        }
+
+        // soft-delete a table
+        repos.tables().soft_delete(t1.id).await.unwrap();
+        assert!(repos
+            .tables()
+            .get_by_namespace_and_name(namespace.id, "t1")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            repos
+                .tables()
+                .list_by_namespace_id(namespace.id)
+                .await
+                .unwrap(),
+            vec![t2.clone()]
+        );
+        let deleted = repos.tables().get_by_id(t1.id).await.unwrap().unwrap();
+        let deleted_at = deleted.deleted_at.expect("table is soft-deleted");
+        assert!(repos
+            .tables()
+            .list_soft_deleted(None)
+            .await
+            .unwrap()
+            .contains(&deleted));
+        assert!(repos
+            .tables()
+            .list_soft_deleted(Some(Timestamp::new(deleted_at.get() - 1)))
+            .await
+            .unwrap()
+            .contains(&deleted));
+        assert!(!repos
+            .tables()
+            .list_soft_deleted(Some(deleted_at))
+            .await
+            .unwrap()
+            .contains(&deleted));
+
+        // its files are flagged for deletion, the files of other tables are not
+        assert!(repos
+            .parquet_files()
+            .list_by_table_not_to_delete(t1.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            repos
+                .parquet_files()
+                .list_by_table_not_to_delete(t2.id)
+                .await
+                .unwrap(),
+            vec![files[1].clone()]
+        );
+
+        // the schema ignores the columns of deleted tables
+        let schema = get_schema_by_name(&namespace.name, repos.as_mut())
+            .await
+            .unwrap();
+        assert_eq!(schema.tables.keys().collect::<Vec<_>>(), vec!["t2"]);
+
+        let err = repos.tables().soft_delete(t1.id).await.unwrap_err();
+        assert!(matches!(err, Error::TableNotFound { .. }));
+
+        // deleted tables do not count towards the table limit
+        repos
+            .namespaces()
+            .update_table_limit(&namespace.name, 2)
+            .await
+            .unwrap();
+        let t3 = repos
+            .tables()
+            .create_or_get("t3", namespace.id)
+            .await
+            .unwrap();
+        repos.tables().soft_delete(t3.id).await.unwrap();
+        repos
+            .namespaces()
+            .update_table_limit(&namespace.name, namespace.max_tables)
+            .await
+            .unwrap();
+
+        // writing to a deleted table restores it, without the columns it had before
+        let restored = repos
+            .tables()
+            .create_or_get("t1", namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(restored, t1);
+        assert!(!repos
+            .tables()
+            .list_soft_deleted(None)
+            .await
+            .unwrap()
+            .iter()
+            .any(|t| t.id == t1.id));
+        assert!(!repos
+            .columns()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap()
+            .iter()
+            .any(|c| c.table_id == t1.id));
+        let schema = get_schema_by_name(&namespace.name, repos.as_mut())
+            .await
+            .unwrap();
+        assert!(schema.tables["t1"].columns.is_empty());
+
+        // restoring a live table is a no-op
+        let c = repos
+            .columns()
+            .create_or_get("c", t1.id, ColumnType::I64)
+            .await
+            .unwrap();
+        repos
+            .tables()
+            .create_or_get("t1", namespace.id)
+            .await
+            .unwrap();
+        let columns = repos
+            .columns()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert!(columns.contains(&c));
+
+        // soft-delete the namespace
+        repos
+            .namespaces()
+            .soft_delete(&namespace.name)
+            .await
+            .unwrap();
+        assert!(repos
+            .namespaces()
+            .get_by_name(&namespace.name)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!repos
+            .namespaces()
+            .list()
+            .await
+            .unwrap()
+            .iter()
+            .any(|n| n.id == namespace.id));
+        let deleted = repos
+            .namespaces()
+            .get_by_id(namespace.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let deleted_at = deleted.deleted_at.expect("namespace is soft-deleted");
+        assert!(repos
+            .namespaces()
+            .list_soft_deleted(None)
+            .await
+            .unwrap()
+            .contains(&deleted));
+        assert!(!repos
+            .namespaces()
+            .list_soft_deleted(Some(deleted_at))
+            .await
+            .unwrap()
+            .contains(&deleted));
+        assert!(repos
+            .parquet_files()
+            .list_by_namespace_not_to_delete(namespace.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let err = repos
+            .namespaces()
+            .soft_delete(&namespace.name)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NamespaceNotFound { .. }));
+        let err = repos
+            .namespaces()
+            .update_table_limit(&namespace.name, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NamespaceNotFound { .. }));
+
+        // the name of a deleted namespace can be reused
+        let recreated = repos
+            .namespaces()
+            .create(&namespace.name, "inf", kafka.id, pool.id)
+            .await
+            .unwrap();
+        assert_ne!(recreated.id, namespace.id);
+        assert_eq!(
+            repos
+                .namespaces()
+                .get_by_name(&namespace.name)
+                .await
+                .unwrap(),
+            Some(recreated.clone())
+        );
+        assert!(repos
+            .namespaces()
+            .list_soft_deleted(None)
+            .await
+            .unwrap()
+            .contains(&deleted));
+
+        // but only once at a time
+        let err = repos
+            .namespaces()
+            .create(&namespace.name, "inf", kafka.id, pool.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NameExists { .. }));
+    }
+
     fn assert_metric_hit(metrics: &metric::Registry, name: &'static str) {
         let histogram = metrics
             .get_instrument::<Metric<U64Histogram>>("catalog_op_duration_ms")
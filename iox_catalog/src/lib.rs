@@ -11,10 +11,9 @@
     clippy::clone_on_ref_ptr
 )]
 
-use crate::interface::{Error, Result, Transaction};
+use crate::interface::{table_schema, Error, Result, Transaction};
 use data_types2::{
     ColumnType, KafkaPartition, KafkaTopic, NamespaceSchema, QueryPool, Sequencer, SequencerId,
-    TableSchema,
 };
 use interface::{ColumnUpsertRequest, RepoCollection};
 use mutable_batch::MutableBatch;
@@ -32,6 +31,7 @@ pub mod interface;
 pub mod mem;
 pub mod metrics;
 pub mod postgres;
+pub mod sqlite;
 
 /// Given an iterator of `(table_name, batch)` to validate, this function
 /// ensures all the columns within `batch` match the existing schema for
@@ -92,7 +92,7 @@ where
                 .tables()
                 .create_or_get(table_name, schema.id)
                 .await
-                .map(|t| TableSchema::new(t.id))?;
+                .map(|t| table_schema(&t))?;
 
             // Always add a time column to all new tables.
             let time_col = repos
@@ -3,19 +3,20 @@
 
 use crate::{
     interface::{
-        sealed::TransactionFinalize, Catalog, ColumnRepo, ColumnUpsertRequest, Error,
-        KafkaTopicRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo, ProcessedTombstoneRepo,
-        QueryPoolRepo, RepoCollection, Result, SequencerRepo, TablePersistInfo, TableRepo,
-        TombstoneRepo, Transaction, INITIAL_COMPACTION_LEVEL,
+        encode_checked_partition_template, sealed::TransactionFinalize, Catalog, ColumnRepo,
+        ColumnUpsertRequest, Error, KafkaTopicRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo,
+        ProcessedTombstoneRepo, QueryPoolRepo, RepoCollection, Result, SequencerRepo,
+        TablePersistInfo, TableRepo, TombstoneRepo, Transaction, INITIAL_COMPACTION_LEVEL,
     },
     metrics::MetricDecorator,
 };
 use async_trait::async_trait;
 use data_types2::{
-    Column, ColumnId, ColumnType, KafkaPartition, KafkaTopic, KafkaTopicId, Namespace, NamespaceId,
-    ParquetFile, ParquetFileId, ParquetFileParams, Partition, PartitionId, PartitionInfo,
-    ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber, Sequencer, SequencerId, Table,
-    TableId, TablePartition, Timestamp, Tombstone, TombstoneId,
+    parse_retention_duration, Column, ColumnId, ColumnType, KafkaPartition, KafkaTopic,
+    KafkaTopicId, Namespace, NamespaceId, ParquetFile, ParquetFileId, ParquetFileParams, Partition,
+    PartitionId, PartitionInfo, PartitionTemplate, ProcessedTombstone, QueryPool, QueryPoolId,
+    SequenceNumber, Sequencer, SequencerId, Table, TableId, TablePartition, Timestamp, Tombstone,
+    TombstoneId, DEFAULT_MAX_COLUMNS_PER_TABLE, DEFAULT_MAX_TABLES,
 };
 use observability_deps::tracing::warn;
 use std::fmt::Formatter;
@@ -23,6 +24,7 @@ use std::sync::Arc;
 use std::{collections::HashSet, convert::TryFrom};
 use time::{SystemProvider, TimeProvider};
 use tokio::sync::{Mutex, OwnedMutexGuard};
+use uuid::Uuid;
 
 /// In-memory catalog that implements the `RepoCollection` and individual repo traits from
 /// the catalog interface.
@@ -58,6 +60,8 @@ struct MemCollections {
     partitions: Vec<Partition>,
     tombstones: Vec<Tombstone>,
     parquet_files: Vec<ParquetFile>,
+    /// Parquet files can be hard-deleted, so IDs cannot be derived from the number of files.
+    last_parquet_file_id: i64,
     processed_tombstones: Vec<ProcessedTombstone>,
     time_provider: Arc<dyn TimeProvider>,
 }
@@ -74,6 +78,7 @@ impl Default for MemCollections {
             partitions: Default::default(),
             tombstones: Default::default(),
             parquet_files: Default::default(),
+            last_parquet_file_id: 0,
             processed_tombstones: Default::default(),
             time_provider: Arc::new(SystemProvider::new()),
         }
@@ -291,9 +296,16 @@ impl NamespaceRepo for MemTxn {
         kafka_topic_id: KafkaTopicId,
         query_pool_id: QueryPoolId,
     ) -> Result<Namespace> {
+        parse_retention_duration(retention_duration)
+            .map_err(|source| Error::InvalidRetentionDuration { source })?;
+
         let stage = self.stage();
 
-        if stage.namespaces.iter().any(|n| n.name == name) {
+        if stage
+            .namespaces
+            .iter()
+            .any(|n| n.name == name && n.deleted_at.is_none())
+        {
             return Err(Error::NameExists {
                 name: name.to_string(),
             });
@@ -305,8 +317,10 @@ impl NamespaceRepo for MemTxn {
             kafka_topic_id,
             query_pool_id,
             retention_duration: Some(retention_duration.to_string()),
-            max_tables: 10000,
-            max_columns_per_table: 1000,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
+            deleted_at: None,
+            partition_template: None,
         };
         stage.namespaces.push(namespace);
         Ok(stage.namespaces.last().unwrap().clone())
@@ -315,7 +329,12 @@ impl NamespaceRepo for MemTxn {
     async fn list(&mut self) -> Result<Vec<Namespace>> {
         let stage = self.stage();
 
-        Ok(stage.namespaces.clone())
+        Ok(stage
+            .namespaces
+            .iter()
+            .filter(|n| n.deleted_at.is_none())
+            .cloned()
+            .collect())
     }
 
     async fn get_by_id(&mut self, id: NamespaceId) -> Result<Option<Namespace>> {
@@ -327,12 +346,20 @@ impl NamespaceRepo for MemTxn {
     async fn get_by_name(&mut self, name: &str) -> Result<Option<Namespace>> {
         let stage = self.stage();
 
-        Ok(stage.namespaces.iter().find(|n| n.name == name).cloned())
+        Ok(stage
+            .namespaces
+            .iter()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+            .cloned())
     }
 
     async fn update_table_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace> {
         let stage = self.stage();
-        match stage.namespaces.iter_mut().find(|n| n.name == name) {
+        match stage
+            .namespaces
+            .iter_mut()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+        {
             Some(n) => {
                 n.max_tables = new_max;
                 Ok(n.clone())
@@ -345,7 +372,11 @@ impl NamespaceRepo for MemTxn {
 
     async fn update_column_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace> {
         let stage = self.stage();
-        match stage.namespaces.iter_mut().find(|n| n.name == name) {
+        match stage
+            .namespaces
+            .iter_mut()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+        {
             Some(n) => {
                 n.max_columns_per_table = new_max;
                 Ok(n.clone())
@@ -355,6 +386,124 @@ impl NamespaceRepo for MemTxn {
             }),
         }
     }
+
+    async fn update_query_pool(
+        &mut self,
+        name: &str,
+        query_pool_id: QueryPoolId,
+    ) -> Result<Namespace> {
+        let stage = self.stage();
+        match stage
+            .namespaces
+            .iter_mut()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+        {
+            Some(n) => {
+                n.query_pool_id = query_pool_id;
+                Ok(n.clone())
+            }
+            None => Err(Error::NamespaceNotFound {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    async fn update_retention_duration(
+        &mut self,
+        name: &str,
+        retention_duration: &str,
+    ) -> Result<Namespace> {
+        parse_retention_duration(retention_duration)
+            .map_err(|source| Error::InvalidRetentionDuration { source })?;
+
+        let stage = self.stage();
+        match stage
+            .namespaces
+            .iter_mut()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+        {
+            Some(n) => {
+                n.retention_duration = Some(retention_duration.to_string());
+                Ok(n.clone())
+            }
+            None => Err(Error::NamespaceNotFound {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    async fn update_partition_template(
+        &mut self,
+        name: &str,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Namespace> {
+        let partition_template = encode_checked_partition_template(partition_template)?;
+
+        let stage = self.stage();
+        match stage
+            .namespaces
+            .iter_mut()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+        {
+            Some(n) => {
+                n.partition_template = partition_template;
+                Ok(n.clone())
+            }
+            None => Err(Error::NamespaceNotFound {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    async fn soft_delete(&mut self, name: &str) -> Result<()> {
+        let stage = self.stage();
+        let deleted_at = Timestamp::new(stage.time_provider.now().timestamp_nanos());
+
+        let namespace_id = match stage
+            .namespaces
+            .iter_mut()
+            .find(|n| n.name == name && n.deleted_at.is_none())
+        {
+            Some(n) => {
+                n.deleted_at = Some(deleted_at);
+                n.id
+            }
+            None => {
+                return Err(Error::NamespaceNotFound {
+                    name: name.to_string(),
+                })
+            }
+        };
+
+        let table_ids: HashSet<_> = stage
+            .tables
+            .iter()
+            .filter_map(|t| (t.namespace_id == namespace_id).then(|| t.id))
+            .collect();
+        for f in stage
+            .parquet_files
+            .iter_mut()
+            .filter(|f| table_ids.contains(&f.table_id) && f.to_delete.is_none())
+        {
+            f.to_delete = Some(deleted_at);
+        }
+
+        Ok(())
+    }
+
+    async fn list_soft_deleted(
+        &mut self,
+        deleted_after: Option<Timestamp>,
+    ) -> Result<Vec<Namespace>> {
+        let stage = self.stage();
+
+        Ok(stage
+            .namespaces
+            .iter()
+            .filter(|n| is_deleted_after(n.deleted_at, deleted_after))
+            .cloned()
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -379,7 +528,7 @@ impl TableRepo for MemTxn {
                 let tables_count = stage
                     .tables
                     .iter()
-                    .filter(|t| t.namespace_id == namespace_id)
+                    .filter(|t| t.namespace_id == namespace_id && t.deleted_at.is_none())
                     .count();
                 if tables_count >= max_tables.try_into().unwrap() {
                     return Err(Error::TableCreateLimitError {
@@ -392,15 +541,24 @@ impl TableRepo for MemTxn {
 
         let table = match stage
             .tables
-            .iter()
+            .iter_mut()
             .find(|t| t.name == name && t.namespace_id == namespace_id)
         {
-            Some(t) => t,
+            Some(t) => {
+                if t.deleted_at.take().is_some() {
+                    // restored without the columns it had before
+                    let table_id = t.id;
+                    stage.columns.retain(|c| c.table_id != table_id);
+                }
+                &*t
+            }
             None => {
                 let table = Table {
                     id: TableId::new(stage.tables.len() as i32 + 1),
                     namespace_id,
                     name: name.to_string(),
+                    deleted_at: None,
+                    partition_template: None,
                 };
                 stage.tables.push(table);
                 stage.tables.last().unwrap()
@@ -426,7 +584,7 @@ impl TableRepo for MemTxn {
         Ok(stage
             .tables
             .iter()
-            .find(|t| t.namespace_id == namespace_id && t.name == name)
+            .find(|t| t.namespace_id == namespace_id && t.name == name && t.deleted_at.is_none())
             .cloned())
     }
 
@@ -436,12 +594,68 @@ impl TableRepo for MemTxn {
         let tables: Vec<_> = stage
             .tables
             .iter()
-            .filter(|t| t.namespace_id == namespace_id)
+            .filter(|t| t.namespace_id == namespace_id && t.deleted_at.is_none())
             .cloned()
             .collect();
         Ok(tables)
     }
 
+    async fn update_partition_template(
+        &mut self,
+        table_id: TableId,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Table> {
+        let partition_template = encode_checked_partition_template(partition_template)?;
+
+        let stage = self.stage();
+        match stage
+            .tables
+            .iter_mut()
+            .find(|t| t.id == table_id && t.deleted_at.is_none())
+        {
+            Some(t) => {
+                t.partition_template = partition_template;
+                Ok(t.clone())
+            }
+            None => Err(Error::TableNotFound { id: table_id }),
+        }
+    }
+
+    async fn soft_delete(&mut self, table_id: TableId) -> Result<()> {
+        let stage = self.stage();
+        let deleted_at = Timestamp::new(stage.time_provider.now().timestamp_nanos());
+
+        match stage
+            .tables
+            .iter_mut()
+            .find(|t| t.id == table_id && t.deleted_at.is_none())
+        {
+            Some(t) => t.deleted_at = Some(deleted_at),
+            None => return Err(Error::TableNotFound { id: table_id }),
+        }
+
+        for f in stage
+            .parquet_files
+            .iter_mut()
+            .filter(|f| f.table_id == table_id && f.to_delete.is_none())
+        {
+            f.to_delete = Some(deleted_at);
+        }
+
+        Ok(())
+    }
+
+    async fn list_soft_deleted(&mut self, deleted_after: Option<Timestamp>) -> Result<Vec<Table>> {
+        let stage = self.stage();
+
+        Ok(stage
+            .tables
+            .iter()
+            .filter(|t| is_deleted_after(t.deleted_at, deleted_after))
+            .cloned()
+            .collect())
+    }
+
     async fn get_table_persist_info(
         &mut self,
         sequencer_id: SequencerId,
@@ -911,8 +1125,9 @@ impl ParquetFileRepo for MemTxn {
             return Err(Error::FileExists { object_store_id });
         }
 
+        stage.last_parquet_file_id += 1;
         let parquet_file = ParquetFile {
-            id: ParquetFileId::new(stage.parquet_files.len() as i64 + 1),
+            id: ParquetFileId::new(stage.last_parquet_file_id),
             sequencer_id,
             table_id,
             partition_id,
@@ -1049,12 +1264,56 @@ impl ParquetFileRepo for MemTxn {
         Ok(updated)
     }
 
+    async fn list_to_delete_older_than(
+        &mut self,
+        older_than: Timestamp,
+    ) -> Result<Vec<ParquetFile>> {
+        let stage = self.stage();
+
+        Ok(stage
+            .parquet_files
+            .iter()
+            .filter(|f| matches!(f.to_delete, Some(marked_at) if marked_at < older_than))
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_by_ids(
+        &mut self,
+        parquet_file_ids: &[ParquetFileId],
+    ) -> Result<Vec<ParquetFileId>> {
+        let stage = self.stage();
+
+        let deleted: Vec<_> = stage
+            .parquet_files
+            .iter()
+            .filter(|f| f.to_delete.is_some() && parquet_file_ids.contains(&f.id))
+            .map(|f| f.id)
+            .collect();
+
+        stage.parquet_files.retain(|f| !deleted.contains(&f.id));
+        stage
+            .processed_tombstones
+            .retain(|pt| !deleted.contains(&pt.parquet_file_id));
+
+        Ok(deleted)
+    }
+
     async fn exist(&mut self, id: ParquetFileId) -> Result<bool> {
         let stage = self.stage();
 
         Ok(stage.parquet_files.iter().any(|f| f.id == id))
     }
 
+    async fn exist_by_object_store_id(&mut self, object_store_id: Uuid) -> Result<bool> {
+        let stage = self.stage();
+
+        Ok(stage
+            .parquet_files
+            .iter()
+            .any(|f| f.object_store_id == object_store_id))
+    }
+
     async fn count(&mut self) -> Result<i64> {
         let stage = self.stage();
 
@@ -1136,6 +1395,15 @@ impl ProcessedTombstoneRepo for MemTxn {
     }
 }
 
+/// Returns `true` if `deleted_at` marks a soft-deleted record that is newer than `deleted_after`.
+fn is_deleted_after(deleted_at: Option<Timestamp>, deleted_after: Option<Timestamp>) -> bool {
+    match (deleted_at, deleted_after) {
+        (Some(deleted_at), Some(deleted_after)) => deleted_at > deleted_after,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -9,12 +9,13 @@ use async_trait::async_trait;
 use data_types2::{
     Column, ColumnType, KafkaPartition, KafkaTopic, KafkaTopicId, Namespace, NamespaceId,
     ParquetFile, ParquetFileId, ParquetFileParams, Partition, PartitionId, PartitionInfo,
-    ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber, Sequencer, SequencerId, Table,
-    TableId, TablePartition, Timestamp, Tombstone, TombstoneId,
+    PartitionTemplate, ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber, Sequencer,
+    SequencerId, Table, TableId, TablePartition, Timestamp, Tombstone, TombstoneId,
 };
 use metric::{Metric, U64Histogram, U64HistogramOptions};
 use std::{fmt::Debug, sync::Arc};
 use time::{SystemProvider, TimeProvider};
+use uuid::Uuid;
 
 /// Decorates a implementation of the catalog's [`RepoCollection`] (and the
 /// transactional variant) with instrumentation that emits latency histograms
@@ -202,6 +203,11 @@ decorate!(
         "namespace_get_by_name" = get_by_name(&mut self, name: &str) -> Result<Option<Namespace>>;
         "namespace_update_table_limit" = update_table_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace>;
         "namespace_update_column_limit" = update_column_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace>;
+        "namespace_update_query_pool" = update_query_pool(&mut self, name: &str, query_pool_id: QueryPoolId) -> Result<Namespace>;
+        "namespace_update_retention_duration" = update_retention_duration(&mut self, name: &str, retention_duration: &str) -> Result<Namespace>;
+        "namespace_update_partition_template" = update_partition_template(&mut self, name: &str, partition_template: Option<&PartitionTemplate>) -> Result<Namespace>;
+        "namespace_soft_delete" = soft_delete(&mut self, name: &str) -> Result<()>;
+        "namespace_list_soft_deleted" = list_soft_deleted(&mut self, deleted_after: Option<Timestamp>) -> Result<Vec<Namespace>>;
     ]
 );
 
@@ -212,7 +218,10 @@ decorate!(
         "table_get_by_id" = get_by_id(&mut self, table_id: TableId) -> Result<Option<Table>>;
         "table_get_by_namespace_and_name" = get_by_namespace_and_name(&mut self, namespace_id: NamespaceId, name: &str) -> Result<Option<Table>>;
         "table_list_by_namespace_id" = list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Table>>;
+        "table_update_partition_template" = update_partition_template(&mut self, table_id: TableId, partition_template: Option<&PartitionTemplate>) -> Result<Table>;
         "get_table_persist_info" = get_table_persist_info(&mut self, sequencer_id: SequencerId, namespace_id: NamespaceId, table_name: &str) -> Result<Option<TablePersistInfo>>;
+        "table_soft_delete" = soft_delete(&mut self, table_id: TableId) -> Result<()>;
+        "table_list_soft_deleted" = list_soft_deleted(&mut self, deleted_after: Option<Timestamp>) -> Result<Vec<Table>>;
     ]
 );
 
@@ -269,7 +278,10 @@ decorate!(
         "parquet_level_0" = level_0(&mut self, sequencer_id: SequencerId) -> Result<Vec<ParquetFile>>;
         "parquet_level_1" = level_1(&mut self, table_partition: TablePartition, min_time: Timestamp, max_time: Timestamp) -> Result<Vec<ParquetFile>>;
         "parquet_update_to_level_1" = update_to_level_1(&mut self, parquet_file_ids: &[ParquetFileId]) -> Result<Vec<ParquetFileId>>;
+        "parquet_list_to_delete_older_than" = list_to_delete_older_than(&mut self, older_than: Timestamp) -> Result<Vec<ParquetFile>>;
+        "parquet_delete_by_ids" = delete_by_ids(&mut self, parquet_file_ids: &[ParquetFileId]) -> Result<Vec<ParquetFileId>>;
         "parquet_exist" = exist(&mut self, id: ParquetFileId) -> Result<bool>;
+        "parquet_exist_by_object_store_id" = exist_by_object_store_id(&mut self, object_store_id: Uuid) -> Result<bool>;
         "parquet_count" = count(&mut self) -> Result<i64>;
     ]
 );
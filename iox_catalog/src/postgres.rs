@@ -2,25 +2,26 @@
 
 use crate::{
     interface::{
-        sealed::TransactionFinalize, Catalog, ColumnRepo, ColumnUpsertRequest, Error,
-        KafkaTopicRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo, ProcessedTombstoneRepo,
-        QueryPoolRepo, RepoCollection, Result, SequencerRepo, TablePersistInfo, TableRepo,
-        TombstoneRepo, Transaction, INITIAL_COMPACTION_LEVEL,
+        encode_checked_partition_template, sealed::TransactionFinalize, Catalog, ColumnRepo,
+        ColumnUpsertRequest, Error, KafkaTopicRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo,
+        ProcessedTombstoneRepo, QueryPoolRepo, RepoCollection, Result, SequencerRepo,
+        TablePersistInfo, TableRepo, TombstoneRepo, Transaction, INITIAL_COMPACTION_LEVEL,
     },
     metrics::MetricDecorator,
 };
 use async_trait::async_trait;
 use data_types2::{
-    Column, ColumnType, KafkaPartition, KafkaTopic, KafkaTopicId, Namespace, NamespaceId,
-    ParquetFile, ParquetFileId, ParquetFileParams, Partition, PartitionId, PartitionInfo,
-    ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber, Sequencer, SequencerId, Table,
-    TableId, TablePartition, Timestamp, Tombstone, TombstoneId,
+    parse_retention_duration, Column, ColumnType, KafkaPartition, KafkaTopic, KafkaTopicId,
+    Namespace, NamespaceId, ParquetFile, ParquetFileId, ParquetFileParams, Partition, PartitionId,
+    PartitionInfo, PartitionTemplate, ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber,
+    Sequencer, SequencerId, Table, TableId, TablePartition, Timestamp, Tombstone, TombstoneId,
 };
 use observability_deps::tracing::{info, warn};
 use sqlx::{migrate::Migrator, postgres::PgPoolOptions, Acquire, Executor, Postgres, Row};
 use sqlx_hotswap_pool::HotSwapPool;
 use std::{sync::Arc, time::Duration};
 use time::{SystemProvider, TimeProvider};
+use uuid::Uuid;
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
 const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
@@ -542,6 +543,9 @@ impl NamespaceRepo for PostgresTxn {
         kafka_topic_id: KafkaTopicId,
         query_pool_id: QueryPoolId,
     ) -> Result<Namespace> {
+        parse_retention_duration(retention_duration)
+            .map_err(|source| Error::InvalidRetentionDuration { source })?;
+
         let rec = sqlx::query_as::<_, Namespace>(
             r#"
 INSERT INTO namespace ( name, retention_duration, kafka_topic_id, query_pool_id )
@@ -574,7 +578,8 @@ RETURNING *;
         let rec = sqlx::query_as::<_, Namespace>(
             r#"
 SELECT *
-FROM namespace;
+FROM namespace
+WHERE deleted_at IS NULL;
             "#,
         )
         .fetch_all(&mut self.inner)
@@ -610,7 +615,7 @@ WHERE id = $1;
             r#"
 SELECT *
 FROM namespace
-WHERE name = $1;
+WHERE name = $1 AND deleted_at IS NULL;
         "#,
         )
         .bind(&name) // $1
@@ -631,7 +636,7 @@ WHERE name = $1;
             r#"
 UPDATE namespace
 SET max_tables = $1
-WHERE name = $2
+WHERE name = $2 AND deleted_at IS NULL
 RETURNING *;
         "#,
         )
@@ -655,7 +660,7 @@ RETURNING *;
             r#"
 UPDATE namespace
 SET max_columns_per_table = $1
-WHERE name = $2
+WHERE name = $2 AND deleted_at IS NULL
 RETURNING *;
         "#,
         )
@@ -673,11 +678,178 @@ RETURNING *;
 
         Ok(namespace)
     }
+
+    async fn update_query_pool(
+        &mut self,
+        name: &str,
+        query_pool_id: QueryPoolId,
+    ) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET query_pool_id = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&query_pool_id) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_retention_duration(
+        &mut self,
+        name: &str,
+        retention_duration: &str,
+    ) -> Result<Namespace> {
+        parse_retention_duration(retention_duration)
+            .map_err(|source| Error::InvalidRetentionDuration { source })?;
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET retention_duration = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&retention_duration) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_partition_template(
+        &mut self,
+        name: &str,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Namespace> {
+        let partition_template = encode_checked_partition_template(partition_template)?;
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET partition_template = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&partition_template) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn soft_delete(&mut self, name: &str) -> Result<()> {
+        let deleted_at = Timestamp::new(self.time_provider.now().timestamp_nanos());
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET deleted_at = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        sqlx::query(
+            r#"
+UPDATE parquet_file
+SET to_delete = $1
+FROM table_name
+WHERE parquet_file.table_id = table_name.id
+  AND table_name.namespace_id = $2
+  AND parquet_file.to_delete IS NULL;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&namespace.id) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(())
+    }
+
+    async fn list_soft_deleted(
+        &mut self,
+        deleted_after: Option<Timestamp>,
+    ) -> Result<Vec<Namespace>> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+SELECT *
+FROM namespace
+WHERE deleted_at IS NOT NULL AND deleted_at > $1;
+            "#,
+        )
+        .bind(deleted_after.unwrap_or_else(|| Timestamp::new(i64::MIN))) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
 }
 
 #[async_trait]
 impl TableRepo for PostgresTxn {
     async fn create_or_get(&mut self, name: &str, namespace_id: NamespaceId) -> Result<Table> {
+        // A soft-deleted table is restored without its data, so the columns it had before must not
+        // reappear either.
+        sqlx::query(
+            r#"
+DELETE FROM column_name
+WHERE table_id IN (
+    SELECT id FROM table_name
+    WHERE namespace_id = $1 AND name = $2 AND deleted_at IS NOT NULL
+);
+        "#,
+        )
+        .bind(&namespace_id) // $1
+        .bind(&name) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
         // A simple insert statement becomes quite complicated in order to avoid checking the table
         // limits in a select and then conditionally inserting (which would be racey).
         //
@@ -692,12 +864,13 @@ impl TableRepo for PostgresTxn {
 INSERT INTO table_name ( name, namespace_id )
 SELECT $1, id FROM (
     SELECT namespace.id AS id, max_tables, COUNT(table_name.*) AS count
-    FROM namespace LEFT JOIN table_name ON namespace.id = table_name.namespace_id
+    FROM namespace LEFT JOIN table_name
+        ON namespace.id = table_name.namespace_id AND table_name.deleted_at IS NULL
     WHERE namespace.id = $2
     GROUP BY namespace.max_tables, table_name.namespace_id, namespace.id
 ) AS get_count WHERE count < max_tables
 ON CONFLICT ON CONSTRAINT table_name_unique
-DO UPDATE SET name = table_name.name
+DO UPDATE SET deleted_at = NULL
 RETURNING *;
         "#,
         )
@@ -752,7 +925,7 @@ WHERE id = $1;
             r#"
 SELECT *
 FROM table_name
-WHERE namespace_id = $1 AND name = $2;
+WHERE namespace_id = $1 AND name = $2 AND deleted_at IS NULL;
             "#,
         )
         .bind(&namespace_id) // $1
@@ -774,7 +947,7 @@ WHERE namespace_id = $1 AND name = $2;
             r#"
 SELECT *
 FROM table_name
-WHERE namespace_id = $1;
+WHERE namespace_id = $1 AND deleted_at IS NULL;
             "#,
         )
         .bind(&namespace_id)
@@ -785,6 +958,87 @@ WHERE namespace_id = $1;
         Ok(rec)
     }
 
+    async fn update_partition_template(
+        &mut self,
+        table_id: TableId,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Table> {
+        let partition_template = encode_checked_partition_template(partition_template)?;
+
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+UPDATE table_name
+SET partition_template = $1
+WHERE id = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&partition_template) // $1
+        .bind(&table_id) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let table = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::TableNotFound { id: table_id },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(table)
+    }
+
+    async fn soft_delete(&mut self, table_id: TableId) -> Result<()> {
+        let deleted_at = Timestamp::new(self.time_provider.now().timestamp_nanos());
+
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+UPDATE table_name
+SET deleted_at = $1
+WHERE id = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&table_id) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::TableNotFound { id: table_id },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        sqlx::query(
+            r#"
+UPDATE parquet_file
+SET to_delete = $1
+WHERE table_id = $2 AND to_delete IS NULL;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&table_id) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(())
+    }
+
+    async fn list_soft_deleted(&mut self, deleted_after: Option<Timestamp>) -> Result<Vec<Table>> {
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+SELECT *
+FROM table_name
+WHERE deleted_at IS NOT NULL AND deleted_at > $1;
+            "#,
+        )
+        .bind(deleted_after.unwrap_or_else(|| Timestamp::new(i64::MIN))) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+
     async fn get_table_persist_info(
         &mut self,
         sequencer_id: SequencerId,
@@ -1507,6 +1761,64 @@ RETURNING id;
         Ok(updated)
     }
 
+    async fn list_to_delete_older_than(
+        &mut self,
+        older_than: Timestamp,
+    ) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT *
+FROM parquet_file
+WHERE to_delete IS NOT NULL
+  AND to_delete < $1
+ORDER BY id;
+             "#,
+        )
+        .bind(&older_than) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn delete_by_ids(
+        &mut self,
+        parquet_file_ids: &[ParquetFileId],
+    ) -> Result<Vec<ParquetFileId>> {
+        // See `update_to_level_1` for why the IDs are converted before binding.
+        let ids: Vec<_> = parquet_file_ids.iter().map(|p| p.get()).collect();
+
+        // processed tombstones reference the parquet file, so they have to go first
+        sqlx::query(
+            r#"
+DELETE FROM processed_tombstone
+USING parquet_file
+WHERE processed_tombstone.parquet_file_id = parquet_file.id
+  AND parquet_file.id = ANY($1)
+  AND parquet_file.to_delete IS NOT NULL;
+        "#,
+        )
+        .bind(&ids[..]) // $1
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        let deleted = sqlx::query(
+            r#"
+DELETE FROM parquet_file
+WHERE id = ANY($1)
+  AND to_delete IS NOT NULL
+RETURNING id;
+        "#,
+        )
+        .bind(&ids[..]) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        let deleted = deleted.into_iter().map(|row| row.get("id")).collect();
+        Ok(deleted)
+    }
+
     async fn exist(&mut self, id: ParquetFileId) -> Result<bool> {
         let read_result = sqlx::query_as::<_, Count>(
             r#"SELECT count(*) as count FROM parquet_file WHERE id = $1;"#,
@@ -1519,6 +1831,18 @@ RETURNING id;
         Ok(read_result.count > 0)
     }
 
+    async fn exist_by_object_store_id(&mut self, object_store_id: Uuid) -> Result<bool> {
+        let read_result = sqlx::query_as::<_, Count>(
+            r#"SELECT count(*) as count FROM parquet_file WHERE object_store_id = $1;"#,
+        )
+        .bind(&object_store_id) // $1
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(read_result.count > 0)
+    }
+
     async fn count(&mut self) -> Result<i64> {
         let read_result =
             sqlx::query_as::<_, Count>(r#"SELECT count(*) as count FROM parquet_file;"#)
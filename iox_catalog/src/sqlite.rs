@@ -0,0 +1,1943 @@
+//! A SQLite backed implementation of the Catalog
+//!
+//! The whole catalog is stored in a single local file, which makes it a good fit for single-node
+//! deployments and local development, where running Postgres is not worth the effort and the
+//! in-memory catalog is not durable enough.
+
+use crate::{
+    interface::{
+        encode_checked_partition_template, sealed::TransactionFinalize, Catalog, ColumnRepo,
+        ColumnUpsertRequest, Error, KafkaTopicRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo,
+        ProcessedTombstoneRepo, QueryPoolRepo, RepoCollection, Result, SequencerRepo,
+        TablePersistInfo, TableRepo, TombstoneRepo, Transaction, INITIAL_COMPACTION_LEVEL,
+    },
+    metrics::MetricDecorator,
+};
+use async_trait::async_trait;
+use data_types2::{
+    parse_retention_duration, Column, ColumnType, KafkaPartition, KafkaTopic, KafkaTopicId,
+    Namespace, NamespaceId, ParquetFile, ParquetFileId, ParquetFileParams, Partition, PartitionId,
+    PartitionInfo, PartitionTemplate, ProcessedTombstone, QueryPool, QueryPoolId, SequenceNumber,
+    Sequencer, SequencerId, Table, TableId, TablePartition, Timestamp, Tombstone, TombstoneId,
+};
+use observability_deps::tracing::{info, warn};
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    ConnectOptions, Executor, Pool, Row, Sqlite,
+};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use time::{SystemProvider, TimeProvider};
+use uuid::Uuid;
+
+/// How long a connection waits for a lock held by another connection (i.e. another transaction)
+/// before failing with a "database is locked" error.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+static MIGRATOR: Migrator = sqlx::migrate!("./sqlite/migrations");
+
+/// SQLite catalog.
+#[derive(Debug)]
+pub struct SqliteCatalog {
+    metrics: Arc<metric::Registry>,
+    options: SqliteConnectOptions,
+    pool: Pool<Sqlite>,
+    time_provider: Arc<dyn TimeProvider>,
+}
+
+// struct to get return value from "select count(*) ..." query
+#[derive(sqlx::FromRow)]
+struct Count {
+    count: i64,
+}
+
+impl SqliteCatalog {
+    /// Connect to the catalog stored in the file referenced by `dsn` (e.g.
+    /// `sqlite:///var/lib/iox/catalog.sqlite`), creating the file if it does not exist.
+    ///
+    /// Every connection of the pool opens the file separately, so in-memory databases
+    /// (`sqlite::memory:`) are not supported.
+    pub async fn connect(
+        dsn: &str,
+        max_conns: u32,
+        metrics: Arc<metric::Registry>,
+    ) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(dsn)
+            .map_err(|e| Error::SqlxError { source: e })?
+            .create_if_missing(true)
+            // WAL allows readers to proceed while a transaction is writing
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(BUSY_TIMEOUT)
+            .foreign_keys(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_conns)
+            .connect_with(options.clone())
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        info!(%dsn, "connected to SQLite catalog");
+
+        Ok(Self {
+            options,
+            pool,
+            metrics,
+            time_provider: Arc::new(SystemProvider::new()),
+        })
+    }
+}
+
+/// transaction for [`SqliteCatalog`].
+#[derive(Debug)]
+pub struct SqliteTxn {
+    inner: SqliteTxnInner,
+    time_provider: Arc<dyn TimeProvider>,
+}
+
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+enum SqliteTxnInner {
+    Txn(Option<sqlx::Transaction<'static, Sqlite>>),
+    Oneshot(Pool<Sqlite>),
+}
+
+impl<'c> Executor<'c> for &'c mut SqliteTxnInner {
+    type Database = Sqlite;
+
+    #[allow(clippy::type_complexity)]
+    fn fetch_many<'e, 'q: 'e, E: 'q>(
+        self,
+        query: E,
+    ) -> futures::stream::BoxStream<
+        'e,
+        Result<
+            sqlx::Either<
+                <Self::Database as sqlx::Database>::QueryResult,
+                <Self::Database as sqlx::Database>::Row,
+            >,
+            sqlx::Error,
+        >,
+    >
+    where
+        'c: 'e,
+        E: sqlx::Execute<'q, Self::Database>,
+    {
+        match self {
+            SqliteTxnInner::Txn(txn) => txn.as_mut().expect("Not yet finalized").fetch_many(query),
+            SqliteTxnInner::Oneshot(pool) => pool.fetch_many(query),
+        }
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E: 'q>(
+        self,
+        query: E,
+    ) -> futures::future::BoxFuture<
+        'e,
+        Result<Option<<Self::Database as sqlx::Database>::Row>, sqlx::Error>,
+    >
+    where
+        'c: 'e,
+        E: sqlx::Execute<'q, Self::Database>,
+    {
+        match self {
+            SqliteTxnInner::Txn(txn) => txn
+                .as_mut()
+                .expect("Not yet finalized")
+                .fetch_optional(query),
+            SqliteTxnInner::Oneshot(pool) => pool.fetch_optional(query),
+        }
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [<Self::Database as sqlx::Database>::TypeInfo],
+    ) -> futures::future::BoxFuture<
+        'e,
+        Result<<Self::Database as sqlx::database::HasStatement<'q>>::Statement, sqlx::Error>,
+    >
+    where
+        'c: 'e,
+    {
+        match self {
+            SqliteTxnInner::Txn(txn) => txn
+                .as_mut()
+                .expect("Not yet finalized")
+                .prepare_with(sql, parameters),
+            SqliteTxnInner::Oneshot(pool) => pool.prepare_with(sql, parameters),
+        }
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> futures::future::BoxFuture<'e, Result<sqlx::Describe<Self::Database>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        match self {
+            SqliteTxnInner::Txn(txn) => txn.as_mut().expect("Not yet finalized").describe(sql),
+            SqliteTxnInner::Oneshot(pool) => pool.describe(sql),
+        }
+    }
+}
+
+impl Drop for SqliteTxn {
+    fn drop(&mut self) {
+        if let SqliteTxnInner::Txn(Some(_)) = self.inner {
+            warn!("Dropping SqliteTxn w/o finalizing (commit or abort)");
+
+            // SQLx ensures that the inner transaction enqueues a rollback when it is dropped, so
+            // we don't need to spawn a task here to call `rollback` manually.
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionFinalize for SqliteTxn {
+    async fn commit_inplace(&mut self) -> Result<(), Error> {
+        match &mut self.inner {
+            SqliteTxnInner::Txn(txn) => txn
+                .take()
+                .expect("Not yet finalized")
+                .commit()
+                .await
+                .map_err(|e| Error::SqlxError { source: e }),
+            SqliteTxnInner::Oneshot(_) => {
+                panic!("cannot commit oneshot");
+            }
+        }
+    }
+
+    async fn abort_inplace(&mut self) -> Result<(), Error> {
+        match &mut self.inner {
+            SqliteTxnInner::Txn(txn) => txn
+                .take()
+                .expect("Not yet finalized")
+                .rollback()
+                .await
+                .map_err(|e| Error::SqlxError { source: e }),
+            SqliteTxnInner::Oneshot(_) => {
+                panic!("cannot abort oneshot");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Catalog for SqliteCatalog {
+    async fn setup(&self) -> Result<(), Error> {
+        // Migrations that rebuild a table must not trip over foreign keys referencing it, and
+        // enforcement cannot be toggled inside the migration's transaction, so migrate on a
+        // dedicated connection without it.
+        //
+        // See <https://www.sqlite.org/lang_altertable.html#otheralter>.
+        let mut conn = self
+            .options
+            .clone()
+            .foreign_keys(false)
+            .connect()
+            .await
+            .map_err(|e| Error::Setup { source: e })?;
+
+        MIGRATOR
+            .run(&mut conn)
+            .await
+            .map_err(|e| Error::Setup { source: e.into() })?;
+
+        Ok(())
+    }
+
+    async fn start_transaction(&self) -> Result<Box<dyn Transaction>, Error> {
+        let transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Box::new(MetricDecorator::new(
+            SqliteTxn {
+                inner: SqliteTxnInner::Txn(Some(transaction)),
+                time_provider: Arc::clone(&self.time_provider),
+            },
+            Arc::clone(&self.metrics),
+        )))
+    }
+
+    async fn repositories(&self) -> Box<dyn RepoCollection> {
+        Box::new(MetricDecorator::new(
+            SqliteTxn {
+                inner: SqliteTxnInner::Oneshot(self.pool.clone()),
+                time_provider: Arc::clone(&self.time_provider),
+            },
+            Arc::clone(&self.metrics),
+        ))
+    }
+
+    fn metrics(&self) -> Arc<metric::Registry> {
+        Arc::clone(&self.metrics)
+    }
+}
+
+#[async_trait]
+impl RepoCollection for SqliteTxn {
+    fn kafka_topics(&mut self) -> &mut dyn KafkaTopicRepo {
+        self
+    }
+
+    fn query_pools(&mut self) -> &mut dyn QueryPoolRepo {
+        self
+    }
+
+    fn namespaces(&mut self) -> &mut dyn NamespaceRepo {
+        self
+    }
+
+    fn tables(&mut self) -> &mut dyn TableRepo {
+        self
+    }
+
+    fn columns(&mut self) -> &mut dyn ColumnRepo {
+        self
+    }
+
+    fn sequencers(&mut self) -> &mut dyn SequencerRepo {
+        self
+    }
+
+    fn partitions(&mut self) -> &mut dyn PartitionRepo {
+        self
+    }
+
+    fn tombstones(&mut self) -> &mut dyn TombstoneRepo {
+        self
+    }
+
+    fn parquet_files(&mut self) -> &mut dyn ParquetFileRepo {
+        self
+    }
+
+    fn processed_tombstones(&mut self) -> &mut dyn ProcessedTombstoneRepo {
+        self
+    }
+}
+
+#[async_trait]
+impl KafkaTopicRepo for SqliteTxn {
+    async fn create_or_get(&mut self, name: &str) -> Result<KafkaTopic> {
+        let rec = sqlx::query_as::<_, KafkaTopic>(
+            r#"
+INSERT INTO kafka_topic ( name )
+VALUES ( $1 )
+ON CONFLICT ( name )
+DO UPDATE SET name = kafka_topic.name
+RETURNING *;
+        "#,
+        )
+        .bind(&name) // $1
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+
+    async fn get_by_name(&mut self, name: &str) -> Result<Option<KafkaTopic>> {
+        let rec = sqlx::query_as::<_, KafkaTopic>(
+            r#"
+SELECT *
+FROM kafka_topic
+WHERE name = $1;
+        "#,
+        )
+        .bind(&name) // $1
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let kafka_topic = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(kafka_topic))
+    }
+}
+
+#[async_trait]
+impl QueryPoolRepo for SqliteTxn {
+    async fn create_or_get(&mut self, name: &str) -> Result<QueryPool> {
+        let rec = sqlx::query_as::<_, QueryPool>(
+            r#"
+INSERT INTO query_pool ( name )
+VALUES ( $1 )
+ON CONFLICT ( name )
+DO UPDATE SET name = query_pool.name
+RETURNING *;
+        "#,
+        )
+        .bind(&name) // $1
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+}
+
+#[async_trait]
+impl NamespaceRepo for SqliteTxn {
+    async fn create(
+        &mut self,
+        name: &str,
+        retention_duration: &str,
+        kafka_topic_id: KafkaTopicId,
+        query_pool_id: QueryPoolId,
+    ) -> Result<Namespace> {
+        parse_retention_duration(retention_duration)
+            .map_err(|source| Error::InvalidRetentionDuration { source })?;
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+INSERT INTO namespace ( name, retention_duration, kafka_topic_id, query_pool_id )
+VALUES ( $1, $2, $3, $4 )
+RETURNING *;
+        "#,
+        )
+        .bind(&name) // $1
+        .bind(&retention_duration) // $2
+        .bind(kafka_topic_id) // $3
+        .bind(query_pool_id) // $4
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| {
+            if is_unique_violation(&e) {
+                Error::NameExists {
+                    name: name.to_string(),
+                }
+            } else if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        Ok(rec)
+    }
+
+    async fn list(&mut self) -> Result<Vec<Namespace>> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+SELECT *
+FROM namespace
+WHERE deleted_at IS NULL;
+            "#,
+        )
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+
+    async fn get_by_id(&mut self, id: NamespaceId) -> Result<Option<Namespace>> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+SELECT *
+FROM namespace
+WHERE id = $1;
+        "#,
+        )
+        .bind(&id) // $1
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let namespace = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(namespace))
+    }
+
+    async fn get_by_name(&mut self, name: &str) -> Result<Option<Namespace>> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+SELECT *
+FROM namespace
+WHERE name = $1 AND deleted_at IS NULL;
+        "#,
+        )
+        .bind(&name) // $1
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let namespace = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(namespace))
+    }
+
+    async fn update_table_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET max_tables = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&new_max)
+        .bind(&name)
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_column_limit(&mut self, name: &str, new_max: i32) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET max_columns_per_table = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&new_max)
+        .bind(&name)
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_query_pool(
+        &mut self,
+        name: &str,
+        query_pool_id: QueryPoolId,
+    ) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET query_pool_id = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&query_pool_id) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_retention_duration(
+        &mut self,
+        name: &str,
+        retention_duration: &str,
+    ) -> Result<Namespace> {
+        parse_retention_duration(retention_duration)
+            .map_err(|source| Error::InvalidRetentionDuration { source })?;
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET retention_duration = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&retention_duration) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_partition_template(
+        &mut self,
+        name: &str,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Namespace> {
+        let partition_template = encode_checked_partition_template(partition_template)?;
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET partition_template = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&partition_template) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn soft_delete(&mut self, name: &str) -> Result<()> {
+        let deleted_at = Timestamp::new(self.time_provider.now().timestamp_nanos());
+
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET deleted_at = $1
+WHERE name = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFound {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        sqlx::query(
+            r#"
+UPDATE parquet_file
+SET to_delete = $1
+WHERE to_delete IS NULL
+  AND table_id IN (SELECT id FROM table_name WHERE namespace_id = $2);
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&namespace.id) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(())
+    }
+
+    async fn list_soft_deleted(
+        &mut self,
+        deleted_after: Option<Timestamp>,
+    ) -> Result<Vec<Namespace>> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+SELECT *
+FROM namespace
+WHERE deleted_at IS NOT NULL AND deleted_at > $1;
+            "#,
+        )
+        .bind(deleted_after.unwrap_or_else(|| Timestamp::new(i64::MIN))) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+}
+
+#[async_trait]
+impl TableRepo for SqliteTxn {
+    async fn create_or_get(&mut self, name: &str, namespace_id: NamespaceId) -> Result<Table> {
+        // A soft-deleted table is restored without the columns it had before.
+        sqlx::query(
+            r#"
+DELETE FROM column_name
+WHERE table_id IN (
+    SELECT id FROM table_name
+    WHERE namespace_id = $1 AND name = $2 AND deleted_at IS NOT NULL
+);
+        "#,
+        )
+        .bind(&namespace_id) // $1
+        .bind(&name) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        // See the Postgres implementation for how this query enforces the table limit: selecting
+        // the values to insert yields no rows once the limit is hit, in which case fetch_one()
+        // returns a RowNotFound error.
+        //
+        // SQLite requires the SELECT of an upsert to have a WHERE clause to tell the ON CONFLICT
+        // clause apart from a join constraint, which the limit check conveniently provides.
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+INSERT INTO table_name ( name, namespace_id )
+SELECT $1, id FROM (
+    SELECT namespace.id AS id, max_tables, COUNT(table_name.id) AS count
+    FROM namespace LEFT JOIN table_name
+        ON namespace.id = table_name.namespace_id AND table_name.deleted_at IS NULL
+    WHERE namespace.id = $2
+    GROUP BY namespace.max_tables, table_name.namespace_id, namespace.id
+) AS get_count WHERE count < max_tables
+ON CONFLICT ( namespace_id, name )
+DO UPDATE SET deleted_at = NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&name) // $1
+        .bind(&namespace_id) // $2
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::TableCreateLimitError {
+                table_name: name.to_string(),
+                namespace_id,
+            },
+            _ => {
+                if is_fk_violation(&e) {
+                    Error::ForeignKeyViolation { source: e }
+                } else {
+                    Error::SqlxError { source: e }
+                }
+            }
+        })?;
+
+        Ok(rec)
+    }
+
+    async fn get_by_id(&mut self, table_id: TableId) -> Result<Option<Table>> {
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+SELECT *
+FROM table_name
+WHERE id = $1;
+            "#,
+        )
+        .bind(&table_id) // $1
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let table = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(table))
+    }
+
+    async fn get_by_namespace_and_name(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+    ) -> Result<Option<Table>> {
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+SELECT *
+FROM table_name
+WHERE namespace_id = $1 AND name = $2 AND deleted_at IS NULL;
+            "#,
+        )
+        .bind(&namespace_id) // $1
+        .bind(&name) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let table = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(table))
+    }
+
+    async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Table>> {
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+SELECT *
+FROM table_name
+WHERE namespace_id = $1 AND deleted_at IS NULL;
+            "#,
+        )
+        .bind(&namespace_id)
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+
+    async fn update_partition_template(
+        &mut self,
+        table_id: TableId,
+        partition_template: Option<&PartitionTemplate>,
+    ) -> Result<Table> {
+        let partition_template = encode_checked_partition_template(partition_template)?;
+
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+UPDATE table_name
+SET partition_template = $1
+WHERE id = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&partition_template) // $1
+        .bind(&table_id) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        let table = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::TableNotFound { id: table_id },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(table)
+    }
+
+    async fn soft_delete(&mut self, table_id: TableId) -> Result<()> {
+        let deleted_at = Timestamp::new(self.time_provider.now().timestamp_nanos());
+
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+UPDATE table_name
+SET deleted_at = $1
+WHERE id = $2 AND deleted_at IS NULL
+RETURNING *;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&table_id) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::TableNotFound { id: table_id },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        sqlx::query(
+            r#"
+UPDATE parquet_file
+SET to_delete = $1
+WHERE table_id = $2 AND to_delete IS NULL;
+        "#,
+        )
+        .bind(&deleted_at) // $1
+        .bind(&table_id) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(())
+    }
+
+    async fn list_soft_deleted(&mut self, deleted_after: Option<Timestamp>) -> Result<Vec<Table>> {
+        let rec = sqlx::query_as::<_, Table>(
+            r#"
+SELECT *
+FROM table_name
+WHERE deleted_at IS NOT NULL AND deleted_at > $1;
+            "#,
+        )
+        .bind(deleted_after.unwrap_or_else(|| Timestamp::new(i64::MIN))) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+
+    async fn get_table_persist_info(
+        &mut self,
+        sequencer_id: SequencerId,
+        namespace_id: NamespaceId,
+        table_name: &str,
+    ) -> Result<Option<TablePersistInfo>> {
+        let rec = sqlx::query_as::<_, TablePersistInfo>(
+            r#"
+WITH tid as (SELECT id FROM table_name WHERE name = $2 AND namespace_id = $3)
+SELECT $1 as sequencer_id, id as table_id,
+       parquet_file.max_sequence_number AS parquet_max_sequence_number,
+       tombstone.sequence_number as tombstone_max_sequence_number
+FROM tid
+LEFT JOIN (
+  SELECT tombstone.table_id, sequence_number
+  FROM tombstone
+  WHERE sequencer_id = $1 AND tombstone.table_id = (SELECT id FROM tid)
+  ORDER BY sequence_number DESC
+  LIMIT 1
+) tombstone ON tombstone.table_id = tid.id
+LEFT JOIN (
+  SELECT parquet_file.table_id, max_sequence_number
+  FROM parquet_file
+  WHERE parquet_file.sequencer_id = $1 AND parquet_file.table_id = (SELECT id from tid)
+  ORDER BY max_sequence_number DESC
+  LIMIT 1
+) parquet_file ON parquet_file.table_id = tid.id;
+            "#,
+        )
+        .bind(&sequencer_id) // $1
+        .bind(&table_name) // $2
+        .bind(&namespace_id) // $3
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let info = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(info))
+    }
+}
+
+#[async_trait]
+impl ColumnRepo for SqliteTxn {
+    async fn create_or_get(
+        &mut self,
+        name: &str,
+        table_id: TableId,
+        column_type: ColumnType,
+    ) -> Result<Column> {
+        let ct = column_type as i16;
+        let rec = sqlx::query_as::<_, Column>(
+            r#"
+INSERT INTO column_name ( name, table_id, column_type )
+SELECT $1, table_id, $3 FROM (
+    SELECT max_columns_per_table, namespace.id, table_name.id as table_id, COUNT(column_name.id) AS count
+    FROM namespace LEFT JOIN table_name ON namespace.id = table_name.namespace_id
+                   LEFT JOIN column_name ON table_name.id = column_name.table_id
+    WHERE table_name.id = $2
+    GROUP BY namespace.max_columns_per_table, namespace.id, table_name.id
+) AS get_count WHERE count < max_columns_per_table
+ON CONFLICT ( table_id, name )
+DO UPDATE SET name = column_name.name
+RETURNING *;
+        "#,
+        )
+        .bind(&name) // $1
+        .bind(&table_id) // $2
+        .bind(&ct) // $3
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::ColumnCreateLimitError {
+                column_name: name.to_string(),
+                table_id,
+            },
+            _ => {
+                if is_fk_violation(&e) {
+                    Error::ForeignKeyViolation { source: e }
+                } else {
+                    Error::SqlxError { source: e }
+                }
+            }
+        })?;
+
+        if rec.column_type != ct {
+            return Err(Error::ColumnTypeMismatch {
+                name: name.to_string(),
+                existing: rec.name,
+                new: column_type.to_string(),
+            });
+        }
+
+        Ok(rec)
+    }
+
+    async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Column>> {
+        let rec = sqlx::query_as::<_, Column>(
+            r#"
+SELECT column_name.* FROM table_name
+INNER JOIN column_name on column_name.table_id = table_name.id
+WHERE table_name.namespace_id = $1;
+            "#,
+        )
+        .bind(&namespace_id)
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rec)
+    }
+
+    async fn create_or_get_many(
+        &mut self,
+        columns: &[ColumnUpsertRequest<'_>],
+    ) -> Result<Vec<Column>> {
+        // SQLite has no UNNEST, and the rows returned by a multi-row insert are not guaranteed
+        // to be in insertion order, so upsert the columns one by one instead.
+        let mut out = Vec::with_capacity(columns.len());
+        for c in columns {
+            let want = c.column_type as i16;
+            let existing = sqlx::query_as::<_, Column>(
+                r#"
+INSERT INTO column_name ( name, table_id, column_type )
+VALUES ( $1, $2, $3 )
+ON CONFLICT ( table_id, name )
+DO UPDATE SET name = column_name.name
+RETURNING *;
+                "#,
+            )
+            .bind(c.name) // $1
+            .bind(&c.table_id) // $2
+            .bind(&want) // $3
+            .fetch_one(&mut self.inner)
+            .await
+            .map_err(|e| {
+                if is_fk_violation(&e) {
+                    Error::ForeignKeyViolation { source: e }
+                } else {
+                    Error::SqlxError { source: e }
+                }
+            })?;
+
+            if existing.column_type != want {
+                return Err(Error::ColumnTypeMismatch {
+                    name: existing.name,
+                    existing: ColumnType::try_from(existing.column_type)
+                        .unwrap()
+                        .to_string(),
+                    new: c.column_type.to_string(),
+                });
+            }
+            out.push(existing);
+        }
+
+        Ok(out)
+    }
+}
+
+#[async_trait]
+impl SequencerRepo for SqliteTxn {
+    async fn create_or_get(
+        &mut self,
+        topic: &KafkaTopic,
+        partition: KafkaPartition,
+    ) -> Result<Sequencer> {
+        sqlx::query_as::<_, Sequencer>(
+            r#"
+INSERT INTO sequencer
+    ( kafka_topic_id, kafka_partition, min_unpersisted_sequence_number )
+VALUES
+    ( $1, $2, 0 )
+ON CONFLICT ( kafka_topic_id, kafka_partition )
+DO UPDATE SET kafka_topic_id = sequencer.kafka_topic_id
+RETURNING *;
+        "#,
+        )
+        .bind(&topic.id) // $1
+        .bind(&partition) // $2
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })
+    }
+
+    async fn get_by_topic_id_and_partition(
+        &mut self,
+        topic_id: KafkaTopicId,
+        partition: KafkaPartition,
+    ) -> Result<Option<Sequencer>> {
+        let rec = sqlx::query_as::<_, Sequencer>(
+            r#"
+SELECT *
+FROM sequencer
+WHERE kafka_topic_id = $1
+  AND kafka_partition = $2;
+        "#,
+        )
+        .bind(topic_id) // $1
+        .bind(partition) // $2
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let sequencer = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(sequencer))
+    }
+
+    async fn list(&mut self) -> Result<Vec<Sequencer>> {
+        sqlx::query_as::<_, Sequencer>(r#"SELECT * FROM sequencer;"#)
+            .fetch_all(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_kafka_topic(&mut self, topic: &KafkaTopic) -> Result<Vec<Sequencer>> {
+        sqlx::query_as::<_, Sequencer>(r#"SELECT * FROM sequencer WHERE kafka_topic_id = $1;"#)
+            .bind(&topic.id) // $1
+            .fetch_all(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn update_min_unpersisted_sequence_number(
+        &mut self,
+        sequencer_id: SequencerId,
+        sequence_number: SequenceNumber,
+    ) -> Result<()> {
+        let _ = sqlx::query(
+            r#"UPDATE sequencer SET min_unpersisted_sequence_number = $1 WHERE id = $2;"#,
+        )
+        .bind(&sequence_number.get()) // $1
+        .bind(&sequencer_id) // $2
+        .execute(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PartitionRepo for SqliteTxn {
+    async fn create_or_get(
+        &mut self,
+        key: &str,
+        sequencer_id: SequencerId,
+        table_id: TableId,
+    ) -> Result<Partition> {
+        let v = sqlx::query_as::<_, Partition>(
+            r#"
+INSERT INTO partition
+    ( partition_key, sequencer_id, table_id )
+VALUES
+    ( $1, $2, $3 )
+ON CONFLICT ( table_id, partition_key )
+DO UPDATE SET partition_key = partition.partition_key
+RETURNING *;
+        "#,
+        )
+        .bind(key) // $1
+        .bind(&sequencer_id) // $2
+        .bind(&table_id) // $3
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        // If the partition_key_unique constraint was hit because there was an
+        // existing record for (table_id, partition_key) ensure the partition
+        // key in the DB is mapped to the same sequencer_id the caller
+        // requested.
+        assert_eq!(
+            v.sequencer_id, sequencer_id,
+            "attempted to overwrite partition with different sequencer ID"
+        );
+
+        Ok(v)
+    }
+
+    async fn get_by_id(&mut self, partition_id: PartitionId) -> Result<Option<Partition>> {
+        let rec = sqlx::query_as::<_, Partition>(r#"SELECT * FROM partition WHERE id = $1;"#)
+            .bind(&partition_id) // $1
+            .fetch_one(&mut self.inner)
+            .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let partition = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(partition))
+    }
+
+    async fn list_by_sequencer(&mut self, sequencer_id: SequencerId) -> Result<Vec<Partition>> {
+        sqlx::query_as::<_, Partition>(r#"SELECT * FROM partition WHERE sequencer_id = $1;"#)
+            .bind(&sequencer_id) // $1
+            .fetch_all(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_namespace(&mut self, namespace_id: NamespaceId) -> Result<Vec<Partition>> {
+        sqlx::query_as::<_, Partition>(
+            r#"
+SELECT partition.id as id,
+       partition.sequencer_id as sequencer_id,
+       partition.table_id as table_id,
+       partition.partition_key as partition_key
+FROM table_name
+INNER JOIN partition on partition.table_id = table_name.id
+WHERE table_name.namespace_id = $1;
+            "#,
+        )
+        .bind(&namespace_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn partition_info_by_id(
+        &mut self,
+        partition_id: PartitionId,
+    ) -> Result<Option<PartitionInfo>> {
+        let info = sqlx::query(
+            r#"
+SELECT namespace.name as namespace_name, table_name.name as table_name, partition.id,
+       partition.sequencer_id, partition.table_id, partition.partition_key
+FROM partition
+INNER JOIN table_name on table_name.id = partition.table_id
+INNER JOIN namespace on namespace.id = table_name.namespace_id
+WHERE partition.id = $1;
+        "#,
+        )
+        .bind(&partition_id) // $1
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        let namespace_name = info.get("namespace_name");
+        let table_name = info.get("table_name");
+        let partition = Partition {
+            id: info.get("id"),
+            sequencer_id: info.get("sequencer_id"),
+            table_id: info.get("table_id"),
+            partition_key: info.get("partition_key"),
+        };
+
+        Ok(Some(PartitionInfo {
+            namespace_name,
+            table_name,
+            partition,
+        }))
+    }
+}
+
+#[async_trait]
+impl TombstoneRepo for SqliteTxn {
+    async fn create_or_get(
+        &mut self,
+        table_id: TableId,
+        sequencer_id: SequencerId,
+        sequence_number: SequenceNumber,
+        min_time: Timestamp,
+        max_time: Timestamp,
+        predicate: &str,
+    ) -> Result<Tombstone> {
+        let v = sqlx::query_as::<_, Tombstone>(
+            r#"
+INSERT INTO tombstone
+    ( table_id, sequencer_id, sequence_number, min_time, max_time, serialized_predicate )
+VALUES
+    ( $1, $2, $3, $4, $5, $6 )
+ON CONFLICT ( table_id, sequencer_id, sequence_number )
+DO UPDATE SET table_id = tombstone.table_id
+RETURNING *;
+        "#,
+        )
+        .bind(&table_id) // $1
+        .bind(&sequencer_id) // $2
+        .bind(&sequence_number) // $3
+        .bind(&min_time) // $4
+        .bind(&max_time) // $5
+        .bind(predicate) // $6
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        // If tombstone_unique is hit, a record with (table_id, sequencer_id,
+        // sequence_number) already exists.
+        //
+        // Ensure the caller does not falsely believe they have created the
+        // record with the provided values if the DB row contains different
+        // values.
+        assert_eq!(
+            v.min_time, min_time,
+            "attempted to overwrite min_time in tombstone record"
+        );
+        assert_eq!(
+            v.max_time, max_time,
+            "attempted to overwrite max_time in tombstone record"
+        );
+        assert_eq!(
+            v.serialized_predicate, predicate,
+            "attempted to overwrite predicate in tombstone record"
+        );
+
+        Ok(v)
+    }
+
+    async fn list_by_namespace(&mut self, namespace_id: NamespaceId) -> Result<Vec<Tombstone>> {
+        sqlx::query_as::<_, Tombstone>(
+            r#"
+SELECT
+    tombstone.id as id,
+    tombstone.table_id as table_id,
+    tombstone.sequencer_id as sequencer_id,
+    tombstone.sequence_number as sequence_number,
+    tombstone.min_time as min_time,
+    tombstone.max_time as max_time,
+    tombstone.serialized_predicate as serialized_predicate
+FROM table_name
+INNER JOIN tombstone on tombstone.table_id = table_name.id
+WHERE table_name.namespace_id = $1;
+            "#,
+        )
+        .bind(&namespace_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_table(&mut self, table_id: TableId) -> Result<Vec<Tombstone>> {
+        sqlx::query_as::<_, Tombstone>(
+            r#"
+SELECT *
+FROM tombstone
+WHERE table_id = $1
+ORDER BY id;
+            "#,
+        )
+        .bind(&table_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_tombstones_by_sequencer_greater_than(
+        &mut self,
+        sequencer_id: SequencerId,
+        sequence_number: SequenceNumber,
+    ) -> Result<Vec<Tombstone>> {
+        sqlx::query_as::<_, Tombstone>(
+            r#"
+SELECT *
+FROM tombstone
+WHERE sequencer_id = $1
+  AND sequence_number > $2
+ORDER BY id;
+            "#,
+        )
+        .bind(&sequencer_id) // $1
+        .bind(&sequence_number) // $2
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_tombstones_for_time_range(
+        &mut self,
+        sequencer_id: SequencerId,
+        table_id: TableId,
+        sequence_number: SequenceNumber,
+        min_time: Timestamp,
+        max_time: Timestamp,
+    ) -> Result<Vec<Tombstone>> {
+        sqlx::query_as::<_, Tombstone>(
+            r#"
+SELECT *
+FROM tombstone
+WHERE sequencer_id = $1
+  AND table_id = $2
+  AND sequence_number > $3
+  AND ((min_time <= $4 AND max_time >= $4)
+        OR (min_time > $4 AND min_time <= $5))
+ORDER BY id;
+            "#,
+        )
+        .bind(&sequencer_id) // $1
+        .bind(&table_id) // $2
+        .bind(&sequence_number) // $3
+        .bind(&min_time) // $4
+        .bind(&max_time) // $5
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+}
+
+#[async_trait]
+impl ParquetFileRepo for SqliteTxn {
+    async fn create(&mut self, parquet_file_params: ParquetFileParams) -> Result<ParquetFile> {
+        let ParquetFileParams {
+            sequencer_id,
+            table_id,
+            partition_id,
+            object_store_id,
+            min_sequence_number,
+            max_sequence_number,
+            min_time,
+            max_time,
+            file_size_bytes,
+            parquet_metadata,
+            row_count,
+            created_at,
+        } = parquet_file_params;
+
+        let rec = sqlx::query_as::<_, ParquetFile>(
+            r#"
+INSERT INTO parquet_file (
+    sequencer_id, table_id, partition_id, object_store_id, min_sequence_number,
+    max_sequence_number, min_time, max_time, file_size_bytes, parquet_metadata,
+    row_count, compaction_level, created_at )
+VALUES ( $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13 )
+RETURNING *;
+        "#,
+        )
+        .bind(sequencer_id) // $1
+        .bind(table_id) // $2
+        .bind(partition_id) // $3
+        .bind(object_store_id) // $4
+        .bind(min_sequence_number) // $5
+        .bind(max_sequence_number) // $6
+        .bind(min_time) // $7
+        .bind(max_time) // $8
+        .bind(file_size_bytes) // $9
+        .bind(parquet_metadata) // $10
+        .bind(row_count) // $11
+        .bind(INITIAL_COMPACTION_LEVEL) // $12
+        .bind(created_at) // $13
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| {
+            if is_unique_violation(&e) {
+                Error::FileExists { object_store_id }
+            } else if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        Ok(rec)
+    }
+
+    async fn flag_for_delete(&mut self, id: ParquetFileId) -> Result<()> {
+        let marked_at = Timestamp::new(self.time_provider.now().timestamp_nanos());
+
+        let _ = sqlx::query(r#"UPDATE parquet_file SET to_delete = $1 WHERE id = $2;"#)
+            .bind(&marked_at) // $1
+            .bind(&id) // $2
+            .execute(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(())
+    }
+
+    async fn list_by_sequencer_greater_than(
+        &mut self,
+        sequencer_id: SequencerId,
+        sequence_number: SequenceNumber,
+    ) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT *
+FROM parquet_file
+WHERE sequencer_id = $1
+  AND max_sequence_number > $2
+ORDER BY id;
+            "#,
+        )
+        .bind(&sequencer_id) // $1
+        .bind(&sequence_number) // $2
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_namespace_not_to_delete(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT parquet_file.*
+FROM parquet_file
+INNER JOIN table_name on table_name.id = parquet_file.table_id
+WHERE table_name.namespace_id = $1
+  AND parquet_file.to_delete IS NULL;
+             "#,
+        )
+        .bind(&namespace_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_table_not_to_delete(&mut self, table_id: TableId) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT *
+FROM parquet_file
+WHERE table_id = $1 AND to_delete IS NULL;
+             "#,
+        )
+        .bind(&table_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn level_0(&mut self, sequencer_id: SequencerId) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT *
+FROM parquet_file
+WHERE parquet_file.sequencer_id = $1
+  AND parquet_file.compaction_level = 0
+  AND parquet_file.to_delete IS NULL;
+        "#,
+        )
+        .bind(&sequencer_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn level_1(
+        &mut self,
+        table_partition: TablePartition,
+        min_time: Timestamp,
+        max_time: Timestamp,
+    ) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT *
+FROM parquet_file
+WHERE parquet_file.sequencer_id = $1
+  AND parquet_file.table_id = $2
+  AND parquet_file.partition_id = $3
+  AND parquet_file.compaction_level = 1
+  AND parquet_file.to_delete IS NULL
+  AND ((parquet_file.min_time <= $5 AND parquet_file.max_time >= $4)
+      OR (parquet_file.min_time > $5 AND parquet_file.min_time <= $5));
+        "#,
+        )
+        .bind(&table_partition.sequencer_id) // $1
+        .bind(&table_partition.table_id) // $2
+        .bind(&table_partition.partition_id) // $3
+        .bind(min_time) // $4
+        .bind(max_time) // $5
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn update_to_level_1(
+        &mut self,
+        parquet_file_ids: &[ParquetFileId],
+    ) -> Result<Vec<ParquetFileId>> {
+        let query = format!(
+            r#"
+UPDATE parquet_file
+SET compaction_level = 1
+WHERE id IN ( {} )
+RETURNING id;
+        "#,
+            placeholders(parquet_file_ids.len())
+        );
+        let updated = parquet_file_ids
+            .iter()
+            .fold(sqlx::query(&query), |q, id| q.bind(id))
+            .fetch_all(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        let updated = updated.into_iter().map(|row| row.get("id")).collect();
+        Ok(updated)
+    }
+
+    async fn list_to_delete_older_than(
+        &mut self,
+        older_than: Timestamp,
+    ) -> Result<Vec<ParquetFile>> {
+        sqlx::query_as::<_, ParquetFile>(
+            r#"
+SELECT *
+FROM parquet_file
+WHERE to_delete IS NOT NULL
+  AND to_delete < $1
+ORDER BY id;
+             "#,
+        )
+        .bind(&older_than) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn delete_by_ids(
+        &mut self,
+        parquet_file_ids: &[ParquetFileId],
+    ) -> Result<Vec<ParquetFileId>> {
+        let ids = placeholders(parquet_file_ids.len());
+
+        // processed tombstones reference the parquet file, so they have to go first
+        let query = format!(
+            r#"
+DELETE FROM processed_tombstone
+WHERE parquet_file_id IN (
+    SELECT id
+    FROM parquet_file
+    WHERE id IN ( {} )
+      AND to_delete IS NOT NULL
+);
+        "#,
+            ids
+        );
+        parquet_file_ids
+            .iter()
+            .fold(sqlx::query(&query), |q, id| q.bind(id))
+            .execute(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        let query = format!(
+            r#"
+DELETE FROM parquet_file
+WHERE id IN ( {} )
+  AND to_delete IS NOT NULL
+RETURNING id;
+        "#,
+            ids
+        );
+        let deleted = parquet_file_ids
+            .iter()
+            .fold(sqlx::query(&query), |q, id| q.bind(id))
+            .fetch_all(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        let deleted = deleted.into_iter().map(|row| row.get("id")).collect();
+        Ok(deleted)
+    }
+
+    async fn exist(&mut self, id: ParquetFileId) -> Result<bool> {
+        let read_result = sqlx::query_as::<_, Count>(
+            r#"SELECT count(*) as count FROM parquet_file WHERE id = $1;"#,
+        )
+        .bind(&id) // $1
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(read_result.count > 0)
+    }
+
+    async fn exist_by_object_store_id(&mut self, object_store_id: Uuid) -> Result<bool> {
+        let read_result = sqlx::query_as::<_, Count>(
+            r#"SELECT count(*) as count FROM parquet_file WHERE object_store_id = $1;"#,
+        )
+        .bind(&object_store_id) // $1
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(read_result.count > 0)
+    }
+
+    async fn count(&mut self) -> Result<i64> {
+        let read_result =
+            sqlx::query_as::<_, Count>(r#"SELECT count(*) as count FROM parquet_file;"#)
+                .fetch_one(&mut self.inner)
+                .await
+                .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(read_result.count)
+    }
+}
+
+#[async_trait]
+impl ProcessedTombstoneRepo for SqliteTxn {
+    async fn create(
+        &mut self,
+        parquet_file_id: ParquetFileId,
+        tombstone_id: TombstoneId,
+    ) -> Result<ProcessedTombstone> {
+        sqlx::query_as::<_, ProcessedTombstone>(
+            r#"
+INSERT INTO processed_tombstone ( tombstone_id, parquet_file_id )
+VALUES ( $1, $2 )
+RETURNING *;
+        "#,
+        )
+        .bind(tombstone_id) // $1
+        .bind(parquet_file_id) // $2
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| {
+            if is_unique_violation(&e) {
+                Error::ProcessTombstoneExists {
+                    tombstone_id: tombstone_id.get(),
+                    parquet_file_id: parquet_file_id.get(),
+                }
+            } else if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })
+    }
+
+    async fn exist(
+        &mut self,
+        parquet_file_id: ParquetFileId,
+        tombstone_id: TombstoneId,
+    ) -> Result<bool> {
+        let read_result = sqlx::query_as::<_, Count>(
+            r#"
+SELECT count(*) as count
+FROM processed_tombstone
+WHERE parquet_file_id = $1
+  AND tombstone_id = $2;
+            "#,
+        )
+        .bind(&parquet_file_id) // $1
+        .bind(&tombstone_id) // $2
+        .fetch_one(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(read_result.count > 0)
+    }
+
+    async fn count(&mut self) -> Result<i64> {
+        let read_result =
+            sqlx::query_as::<_, Count>(r#"SELECT count(*) as count FROM processed_tombstone;"#)
+                .fetch_one(&mut self.inner)
+                .await
+                .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(read_result.count)
+    }
+}
+
+/// Returns `n` comma-separated bind parameters (`$1, $2, ...`), to be used in an `IN ( ... )`
+/// list in place of Postgres' `= ANY($1)`.
+fn placeholders(n: usize) -> String {
+    (1..=n)
+        .map(|i| format!("${}", i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The extended error codes returned by SQLite for a unique / primary key constraint violation.
+///
+/// See <https://www.sqlite.org/rescode.html>
+const SQLITE_CONSTRAINT_UNIQUE: &[&str] = &["2067", "1555"];
+
+/// Returns true if `e` is a unique constraint violation error.
+fn is_unique_violation(e: &sqlx::Error) -> bool {
+    if let sqlx::Error::Database(inner) = e {
+        if let Some(code) = inner.code() {
+            if SQLITE_CONSTRAINT_UNIQUE.contains(&code.as_ref()) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// The extended error code returned by SQLite for a foreign key constraint violation.
+const SQLITE_CONSTRAINT_FOREIGNKEY: &str = "787";
+
+fn is_fk_violation(e: &sqlx::Error) -> bool {
+    if let sqlx::Error::Database(inner) = e {
+        if let Some(code) = inner.code() {
+            if code == SQLITE_CONSTRAINT_FOREIGNKEY {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_or_get_default_records;
+    use std::ops::DerefMut;
+    use tempfile::TempDir;
+
+    async fn setup_db(dir: &TempDir) -> SqliteCatalog {
+        let dsn = format!("sqlite://{}", dir.path().join("catalog.sqlite").display());
+        let catalog = SqliteCatalog::connect(&dsn, 3, Arc::new(metric::Registry::default()))
+            .await
+            .expect("failed to open catalog");
+        catalog
+            .setup()
+            .await
+            .expect("failed to initialise database");
+        catalog
+    }
+
+    #[tokio::test]
+    async fn test_catalog() {
+        let dir = TempDir::new().unwrap();
+        let sqlite: Arc<dyn Catalog> = Arc::new(setup_db(&dir).await);
+
+        crate::interface::test_helpers::test_catalog(sqlite).await;
+    }
+
+    #[tokio::test]
+    async fn test_reopen() {
+        let dir = TempDir::new().unwrap();
+
+        let sqlite = setup_db(&dir).await;
+        let mut txn = sqlite.start_transaction().await.expect("txn start");
+        let (kafka, query, _) = create_or_get_default_records(1, txn.deref_mut())
+            .await
+            .expect("db init failed");
+        txn.commit().await.expect("txn commit");
+        let namespace = sqlite
+            .repositories()
+            .await
+            .namespaces()
+            .create("ns", crate::INFINITE_RETENTION_POLICY, kafka.id, query.id)
+            .await
+            .expect("namespace create failed");
+        sqlite.pool.close().await;
+
+        // the catalog survives a restart
+        let sqlite = setup_db(&dir).await;
+        let got = sqlite
+            .repositories()
+            .await
+            .namespaces()
+            .get_by_name("ns")
+            .await
+            .unwrap();
+        assert_eq!(got, Some(namespace));
+    }
+
+    #[tokio::test]
+    async fn test_tombstone_create_or_get_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let sqlite: Arc<dyn Catalog> = Arc::new(setup_db(&dir).await);
+
+        let mut txn = sqlite.start_transaction().await.expect("txn start");
+        let (kafka, query, sequencers) = create_or_get_default_records(1, txn.deref_mut())
+            .await
+            .expect("db init failed");
+        txn.commit().await.expect("txn commit");
+
+        let namespace_id = sqlite
+            .repositories()
+            .await
+            .namespaces()
+            .create("ns", crate::INFINITE_RETENTION_POLICY, kafka.id, query.id)
+            .await
+            .expect("namespace create failed")
+            .id;
+        let table_id = sqlite
+            .repositories()
+            .await
+            .tables()
+            .create_or_get("table", namespace_id)
+            .await
+            .expect("create table failed")
+            .id;
+
+        let sequencer_id = *sequencers.keys().next().expect("no sequencer");
+        let sequence_number = SequenceNumber::new(3);
+        let min_timestamp = Timestamp::new(10);
+        let max_timestamp = Timestamp::new(100);
+        let predicate = "bananas";
+
+        let a = sqlite
+            .repositories()
+            .await
+            .tombstones()
+            .create_or_get(
+                table_id,
+                sequencer_id,
+                sequence_number,
+                min_timestamp,
+                max_timestamp,
+                predicate,
+            )
+            .await
+            .expect("should create OK");
+
+        // Call create_or_get for the same (table_id, sequencer_id,
+        // sequence_number) triplet, setting the same metadata to ensure the
+        // write is idempotent.
+        let b = sqlite
+            .repositories()
+            .await
+            .tombstones()
+            .create_or_get(
+                table_id,
+                sequencer_id,
+                sequence_number,
+                min_timestamp,
+                max_timestamp,
+                predicate,
+            )
+            .await
+            .expect("idempotent write should succeed");
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders(0), "");
+        assert_eq!(placeholders(1), "$1");
+        assert_eq!(placeholders(3), "$1, $2, $3");
+    }
+}
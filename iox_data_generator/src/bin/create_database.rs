@@ -178,6 +178,7 @@ fn test_write(db_name: &str) -> WriteRequest {
     WriteRequest {
         database_batch: Some(DatabaseBatch {
             database_name: db_name.to_string(),
+            partition_key: Default::default(),
             table_batches: vec![TableBatch {
                 table_name: "write_test".to_string(),
                 columns: vec![
@@ -17,7 +17,11 @@
 use bytes::Bytes;
 use data_types::server_id::ServerId;
 use futures::{stream::BoxStream, StreamExt, TryStreamExt};
-use object_store::{path::Path, DynObjectStore, GetResult, Result};
+use object_store::{
+    cache::{Cache, CachedFile},
+    path::Path,
+    DynObjectStore, GetResult, ObjectStoreFileCache, Result,
+};
 use observability_deps::tracing::warn;
 use snafu::{ensure, ResultExt, Snafu};
 use std::{ops::Deref, sync::Arc};
@@ -54,6 +58,7 @@ pub struct IoxObjectStore {
     root_path: RootPath,
     data_path: DataPath,
     transactions_path: TransactionsPath,
+    cache: Option<Arc<ObjectStoreFileCache>>,
 }
 
 impl IoxObjectStore {
@@ -180,9 +185,17 @@ impl IoxObjectStore {
             root_path,
             data_path,
             transactions_path,
+            cache: None,
         }
     }
 
+    /// Serve parquet files from the given local filesystem cache, see
+    /// [`get_cached_parquet_file`](Self::get_cached_parquet_file).
+    pub fn with_cache(mut self, cache: Arc<ObjectStoreFileCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// In the database's root directory, write out a file pointing to the server's config. This
     /// data can serve as an extra check on which server owns this database.
     pub async fn put_owner_file(&self, bytes: Bytes) -> Result<()> {
@@ -308,6 +321,23 @@ impl IoxObjectStore {
         self.inner.get(&self.full_parquet_path(location)).await
     }
 
+    /// Get a local copy of this parquet file from the filesystem cache, downloading it if
+    /// necessary.
+    ///
+    /// The file is not evicted from the cache while the returned [`CachedFile`] is alive. Returns
+    /// `None` if no cache is configured.
+    pub async fn get_cached_parquet_file(
+        &self,
+        location: &ParquetFilePath,
+    ) -> Option<object_store::cache::Result<CachedFile>> {
+        let cache = self.cache.as_ref()?;
+        Some(
+            cache
+                .fs_path_or_cache(&self.full_parquet_path(location), Arc::clone(&self.inner))
+                .await,
+        )
+    }
+
     /// Store the data for this parquet file in this database's object store.
     pub async fn put_parquet_file(&self, location: &ParquetFilePath, bytes: Bytes) -> Result<()> {
         self.inner
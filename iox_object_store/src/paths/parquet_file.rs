@@ -62,6 +62,16 @@ impl ParquetFilePath {
         matches!(self.0, Variant::New { .. })
     }
 
+    /// Object store ID of an NG-style path, `None` for old-style paths.
+    pub fn object_store_id(&self) -> Option<Uuid> {
+        match &self.0 {
+            Variant::Old { .. } => None,
+            Variant::New {
+                object_store_id, ..
+            } => Some(*object_store_id),
+        }
+    }
+
     /// Turn this into directories and file names to be added to a root path or to be serialized
     /// in protobuf.
     ///
@@ -155,6 +165,71 @@ impl ParquetFilePath {
         }))
     }
 
+    /// Parse an NG-style path as returned by
+    /// [`absolute_dirs_and_file_name`](Self::absolute_dirs_and_file_name).
+    pub fn from_absolute_dirs_and_file_name(
+        dirs_and_file_name: &DirsAndFileName,
+    ) -> Result<Self, ParquetFilePathParseError> {
+        let mut directories = dirs_and_file_name.directories.iter();
+        let mut next_id = |name: &'static str| {
+            directories
+                .next()
+                .context(MissingIdSnafu { name })?
+                .to_string()
+                .parse::<i64>()
+                .context(InvalidIdSnafu { name })
+        };
+
+        let namespace_id = next_id("namespace")?;
+        let table_id = next_id("table")?;
+        let sequencer_id = next_id("sequencer")?;
+        let partition_id = next_id("partition")?;
+        ensure!(directories.next().is_none(), UnexpectedDirectorySnafu);
+
+        let namespace_id = NamespaceId::new(
+            namespace_id
+                .try_into()
+                .ok()
+                .context(IdOutOfRangeSnafu { name: "namespace" })?,
+        );
+        let table_id = TableId::new(
+            table_id
+                .try_into()
+                .ok()
+                .context(IdOutOfRangeSnafu { name: "table" })?,
+        );
+        let sequencer_id = SequencerId::new(
+            sequencer_id
+                .try_into()
+                .ok()
+                .context(IdOutOfRangeSnafu { name: "sequencer" })?,
+        );
+        let partition_id = PartitionId::new(partition_id);
+
+        let file_name = dirs_and_file_name
+            .file_name
+            .as_ref()
+            .context(MissingChunkIdSnafu)?
+            .to_string();
+        let mut parts = file_name.split('.');
+        let object_store_id = parts
+            .next()
+            .context(MissingChunkIdSnafu)?
+            .parse::<Uuid>()
+            .context(InvalidChunkIdSnafu)?;
+        let ext = parts.next().context(MissingExtensionSnafu)?;
+        ensure!(ext == "parquet", InvalidExtensionSnafu { ext });
+        ensure!(parts.next().is_none(), UnexpectedExtensionSnafu);
+
+        Ok(Self(Variant::New {
+            namespace_id,
+            table_id,
+            sequencer_id,
+            partition_id,
+            object_store_id,
+        }))
+    }
+
     // Deliberately pub(crate); this transformation should only happen within this crate
     pub(crate) fn from_absolute(
         absolute_path: ObjStoPath,
@@ -196,6 +271,18 @@ pub enum ParquetFilePathParseError {
     #[snafu(display("Too many directories found"))]
     UnexpectedDirectory,
 
+    #[snafu(display("Could not find required {} id", name))]
+    MissingId { name: &'static str },
+
+    #[snafu(display("Could not parse {} id: {}", name, source))]
+    InvalidId {
+        name: &'static str,
+        source: std::num::ParseIntError,
+    },
+
+    #[snafu(display("{} id is out of range", name))]
+    IdOutOfRange { name: &'static str },
+
     #[snafu(display("Could not find required chunk id"))]
     MissingChunkId,
 
@@ -383,6 +470,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parquet_file_absolute_dirs_and_file_path_round_trip() {
+        let pfp = ParquetFilePath::new_new_gen(
+            NamespaceId::new(1),
+            TableId::new(2),
+            SequencerId::new(3),
+            PartitionId::new(4),
+            Uuid::nil(),
+        );
+        let dirs_and_file_name = pfp.absolute_dirs_and_file_name();
+        let round_trip =
+            ParquetFilePath::from_absolute_dirs_and_file_name(&dirs_and_file_name).unwrap();
+        assert_eq!(pfp, round_trip);
+        assert_eq!(round_trip.object_store_id(), Some(Uuid::nil()));
+
+        // Error cases
+        use ParquetFilePathParseError::*;
+
+        let mut df = DirsAndFileName::default();
+        df.push_all_dirs(&["1", "2", "3"]);
+        assert_error!(
+            ParquetFilePath::from_absolute_dirs_and_file_name(&df),
+            MissingId { name: "partition" },
+        );
+
+        let mut df = DirsAndFileName::default();
+        df.push_all_dirs(&["1", "foo", "3", "4"]);
+        assert_error!(
+            ParquetFilePath::from_absolute_dirs_and_file_name(&df),
+            InvalidId { name: "table", .. },
+        );
+
+        let mut df = DirsAndFileName::default();
+        df.push_all_dirs(&["1", "2", "100000", "4"]);
+        assert_error!(
+            ParquetFilePath::from_absolute_dirs_and_file_name(&df),
+            IdOutOfRange { name: "sequencer" },
+        );
+
+        let mut df = DirsAndFileName::default();
+        df.push_all_dirs(&["1", "2", "3", "4", "5"]);
+        assert_error!(
+            ParquetFilePath::from_absolute_dirs_and_file_name(&df),
+            UnexpectedDirectory,
+        );
+
+        let mut df = DirsAndFileName::default();
+        df.push_all_dirs(&["1", "2", "3", "4"]);
+        df.set_file_name("rules.pb");
+        assert_error!(
+            ParquetFilePath::from_absolute_dirs_and_file_name(&df),
+            InvalidChunkId { .. },
+        );
+    }
+
     #[test]
     #[should_panic(expected = "absolute dirs don't apply to old-gen parquet file paths")]
     fn parquet_file_absolute_dirs_and_file_path_old_gen() {
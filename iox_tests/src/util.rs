@@ -9,7 +9,7 @@ use data_types2::{
     ColumnType, KafkaPartition, KafkaTopic, Namespace, ParquetFile, ParquetFileParams, Partition,
     QueryPool, SequenceNumber, Sequencer, Table, Timestamp, Tombstone,
 };
-use iox_catalog::{interface::Catalog, mem::MemCatalog};
+use iox_catalog::{interface::Catalog, mem::MemCatalog, INFINITE_RETENTION_POLICY};
 use iox_object_store::{IoxObjectStore, ParquetFilePath};
 use mutable_batch::MutableBatch;
 use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
@@ -88,6 +88,16 @@ impl TestCatalog {
 
     /// Create a namesapce in teh catalog
     pub async fn create_namespace(self: &Arc<Self>, name: &str) -> Arc<TestNamespace> {
+        self.create_namespace_with_retention(name, INFINITE_RETENTION_POLICY)
+            .await
+    }
+
+    /// Create a namespace with the given retention duration in the catalog
+    pub async fn create_namespace_with_retention(
+        self: &Arc<Self>,
+        name: &str,
+        retention_duration: &str,
+    ) -> Arc<TestNamespace> {
         let mut repos = self.catalog.repositories().await;
 
         let kafka_topic = repos
@@ -98,7 +108,7 @@ impl TestCatalog {
         let query_pool = repos.query_pools().create_or_get("pool").await.unwrap();
         let namespace = repos
             .namespaces()
-            .create(name, "1y", kafka_topic.id, query_pool.id)
+            .create(name, retention_duration, kafka_topic.id, query_pool.id)
             .await
             .unwrap();
 
@@ -54,6 +54,12 @@ pub enum Error {
 
     #[snafu(context(false))]
     WriterError { source: writer::Error },
+
+    #[snafu(display("Invalid regex '{}' in partition template: {}", regex, source))]
+    InvalidPartitionTemplateRegex { regex: String, source: regex::Error },
+
+    #[snafu(display("Invalid strftime format '{}' in partition template", format))]
+    InvalidPartitionTemplateFormat { format: String },
 }
 
 /// A specialized `Error` for [`MutableBatch`] errors
@@ -2,7 +2,6 @@
 
 use crate::column::ColumnData;
 use crate::{MutableBatch, Result};
-use data_types::database_rules::PartitionTemplate;
 use hashbrown::HashMap;
 use schema::TIME_COLUMN_NAME;
 use std::num::NonZeroUsize;
@@ -11,6 +10,8 @@ use std::ops::Range;
 mod filter;
 mod partition;
 
+pub use partition::CompiledPartitionTemplate;
+
 /// A payload that can be written to a mutable batch
 pub trait WritePayload {
     /// Write this payload to `batch`
@@ -99,11 +100,11 @@ impl<'a> PartitionWrite<'a> {
     }
 
     /// Create a collection of [`PartitionWrite`] indexed by partition key
-    /// from a [`MutableBatch`] and [`PartitionTemplate`]
+    /// from a [`MutableBatch`] and [`CompiledPartitionTemplate`]
     pub fn partition(
         table_name: &str,
         batch: &'a MutableBatch,
-        partition_template: &PartitionTemplate,
+        partition_template: &CompiledPartitionTemplate,
     ) -> HashMap<String, Self> {
         use hashbrown::hash_map::Entry;
         let time = get_time_column(batch);
@@ -3,17 +3,79 @@
 //! The returned ranges can then be used with [`MutableBatch::extend_from_range`]
 
 use crate::column::{Column, ColumnData};
-use crate::MutableBatch;
-use chrono::{format::StrftimeItems, TimeZone, Utc};
-use data_types::database_rules::{PartitionTemplate, TemplatePart};
+use crate::{
+    InvalidPartitionTemplateFormatSnafu, InvalidPartitionTemplateRegexSnafu, MutableBatch, Result,
+};
+use chrono::{
+    format::{Item, StrftimeItems},
+    TimeZone, Utc,
+};
+use data_types::database_rules::{PartitionTemplate, RegexCapture, StrftimeColumn, TemplatePart};
+use regex::Regex;
 use schema::TIME_COLUMN_NAME;
+use snafu::{ensure, ResultExt};
 use std::ops::Range;
 
+/// A [`PartitionTemplate`] that is known to be valid, ready to partition batches.
+///
+/// The regexes of [`TemplatePart::RegexCapture`] parts are compiled, and the `strftime` formats
+/// of [`TemplatePart::TimeFormat`] and [`TemplatePart::StrftimeColumn`] parts are checked, once
+/// when the template is compiled instead of for every batch. Rendering a partition key with a
+/// compiled template cannot fail.
+#[derive(Debug, Clone)]
+pub struct CompiledPartitionTemplate {
+    template: PartitionTemplate,
+
+    /// The compiled regex of every part of the template, `None` for parts other than
+    /// [`TemplatePart::RegexCapture`].
+    regexes: Vec<Option<Regex>>,
+}
+
+impl CompiledPartitionTemplate {
+    /// Compile `template`, returning an error if any of its regexes or `strftime` formats is
+    /// invalid.
+    pub fn try_new(template: PartitionTemplate) -> Result<Self> {
+        let regexes = template
+            .parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::RegexCapture(RegexCapture { regex, .. }) => Regex::new(regex)
+                    .map(Some)
+                    .context(InvalidPartitionTemplateRegexSnafu { regex }),
+                TemplatePart::TimeFormat(format)
+                | TemplatePart::StrftimeColumn(StrftimeColumn { format, .. }) => {
+                    ensure!(
+                        StrftimeItems::new(format).all(|item| item != Item::Error),
+                        InvalidPartitionTemplateFormatSnafu { format }
+                    );
+                    Ok(None)
+                }
+                TemplatePart::Table | TemplatePart::Column(_) => Ok(None),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { template, regexes })
+    }
+
+    /// The compiled [`PartitionTemplate`].
+    pub fn template(&self) -> &PartitionTemplate {
+        &self.template
+    }
+}
+
+impl PartialEq for CompiledPartitionTemplate {
+    fn eq(&self, other: &Self) -> bool {
+        self.template == other.template
+    }
+}
+
+impl Eq for CompiledPartitionTemplate {}
+
 /// Returns an iterator identifying consecutive ranges for a given partition key
 pub fn partition_batch<'a>(
     batch: &'a MutableBatch,
     table_name: &'a str,
-    template: &'a PartitionTemplate,
+    template: &'a CompiledPartitionTemplate,
 ) -> impl Iterator<Item = (String, Range<usize>)> + 'a {
     range_encode(partition_keys(batch, table_name, template))
 }
@@ -26,10 +88,20 @@ pub fn partition_batch<'a>(
 ///
 /// [`Template::fmt_row`] can then be used to render the template for that particular row
 /// to the provided string, without performing any additional column lookups
+///
+/// Rows for which a template part has no value render that part as an empty string,
+/// this is the case for:
+///
+/// * [`TemplatePart::Column`] - a null value
+/// * [`TemplatePart::RegexCapture`] - a null or non-string value, or a value the
+///   regex doesn't match
+/// * [`TemplatePart::StrftimeColumn`] - a null or non-integer value
 enum Template<'a> {
     Table(&'a str),
     Column(&'a Column, &'a str),
     TimeFormat(&'a [i64], StrftimeItems<'a>),
+    RegexCapture(&'a Column, &'a str, &'a Regex),
+    StrftimeColumn(&'a Column, StrftimeItems<'a>),
 }
 
 impl<'a> Template<'a> {
@@ -63,15 +135,58 @@ impl<'a> Template<'a> {
                     .format_with_items(format.clone());
                 write!(out, "{}", formatted)
             }
+            Template::RegexCapture(col, col_name, regex) if col.valid.get(idx) => {
+                let value = match &col.data {
+                    ColumnData::String(col_data, _) => col_data.get(idx).unwrap(),
+                    ColumnData::Tag(col_data, dictionary, _) => {
+                        dictionary.lookup_id(col_data[idx]).unwrap()
+                    }
+                    _ => return Ok(()),
+                };
+
+                match regex_capture(regex, value) {
+                    Some(captured) => {
+                        out.write_str(col_name)?;
+                        out.write_char('_')?;
+                        out.write_str(captured)
+                    }
+                    None => Ok(()),
+                }
+            }
+            Template::RegexCapture(_, _, _) => Ok(()),
+            Template::StrftimeColumn(col, format) if col.valid.get(idx) => match &col.data {
+                ColumnData::I64(col_data, _) => {
+                    let formatted = Utc
+                        .timestamp_nanos(col_data[idx])
+                        .format_with_items(format.clone());
+                    write!(out, "{}", formatted)
+                }
+                _ => Ok(()),
+            },
+            Template::StrftimeColumn(_, _) => Ok(()),
         }
     }
 }
 
+/// Returns the part of `value` captured by `regex`.
+///
+/// This is the first capture group if the regex has one, or the whole match
+/// otherwise. Returns `None` if the regex doesn't match, or the first capture
+/// group doesn't participate in the match.
+fn regex_capture<'a>(regex: &Regex, value: &'a str) -> Option<&'a str> {
+    let captures = regex.captures(value)?;
+    let captured = match regex.captures_len() {
+        1 => captures.get(0),
+        _ => captures.get(1),
+    };
+    captured.map(|m| m.as_str())
+}
+
 /// Returns an iterator of partition keys for the given table batch
 fn partition_keys<'a>(
     batch: &'a MutableBatch,
     table_name: &'a str,
-    template: &'a PartitionTemplate,
+    template: &'a CompiledPartitionTemplate,
 ) -> impl Iterator<Item = String> + 'a {
     let time = batch.column(TIME_COLUMN_NAME).expect("time column");
     let time = match &time.data {
@@ -80,23 +195,32 @@ fn partition_keys<'a>(
     };
 
     let cols: Vec<_> = template
+        .template
         .parts
         .iter()
+        .zip(&template.regexes)
         // If a column isn't present it can be skipped as it has no impact on partition key
-        .filter_map(|part| match part {
+        .filter_map(|(part, regex)| match part {
             TemplatePart::Table => Some(Template::Table(table_name)),
             TemplatePart::Column(name) => Some(Template::Column(batch.column(name).ok()?, name)),
             TemplatePart::TimeFormat(fmt) => {
                 Some(Template::TimeFormat(time, StrftimeItems::new(fmt)))
             }
-            TemplatePart::RegexCapture(_) => unimplemented!(),
-            TemplatePart::StrftimeColumn(_) => unimplemented!(),
+            TemplatePart::RegexCapture(RegexCapture { column, .. }) => {
+                let col = batch.column(column).ok()?;
+                let regex = regex.as_ref().expect("regex compiled with the template");
+                Some(Template::RegexCapture(col, column, regex))
+            }
+            TemplatePart::StrftimeColumn(StrftimeColumn { column, format }) => Some(
+                Template::StrftimeColumn(batch.column(column).ok()?, StrftimeItems::new(format)),
+            ),
         })
         .collect();
 
     (0..batch.row_count).map(move |idx| {
         let mut string = String::new();
         for (col_idx, col) in cols.iter().enumerate() {
+            // The formats were checked when compiling the template
             col.fmt_row(&mut string, idx)
                 .expect("string writing is infallible");
 
@@ -144,7 +268,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::writer::Writer;
+    use crate::{writer::Writer, Error};
     use rand::prelude::*;
 
     fn make_rng() -> StdRng {
@@ -215,6 +339,7 @@ mod tests {
 
         writer.commit();
 
+        let template = CompiledPartitionTemplate::try_new(template).unwrap();
         let keys: Vec<_> = partition_keys(&batch, "foo", &template).collect();
 
         assert_eq!(
@@ -228,4 +353,164 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_partition_regex_capture() {
+        let mut batch = MutableBatch::new();
+        let mut writer = Writer::new(&mut batch, 5);
+
+        writer
+            .write_time("time", vec![1, 2, 3, 4, 5].into_iter())
+            .unwrap();
+
+        writer
+            .write_tag(
+                "host",
+                Some(&[0b00011011]),
+                vec!["web-01.eu", "db-02.us", "other", "web-03.eu"].into_iter(),
+            )
+            .unwrap();
+
+        writer
+            .write_f64("f64", None, vec![2., 4.5, 6., 3., 6.].into_iter())
+            .unwrap();
+
+        writer.commit();
+
+        // Uses the first capture group
+        let template = PartitionTemplate {
+            parts: vec![TemplatePart::RegexCapture(RegexCapture {
+                column: "host".to_string(),
+                regex: r"^(\w+)-\d+".to_string(),
+            })],
+        };
+        let template = CompiledPartitionTemplate::try_new(template).unwrap();
+        let keys: Vec<_> = partition_keys(&batch, "foo", &template).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "host_web".to_string(),
+                "host_db".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "host_web".to_string(),
+            ]
+        );
+
+        // Uses the whole match without a capture group
+        let template = PartitionTemplate {
+            parts: vec![
+                TemplatePart::Table,
+                TemplatePart::RegexCapture(RegexCapture {
+                    column: "host".to_string(),
+                    regex: r"\.\w+$".to_string(),
+                }),
+            ],
+        };
+        let template = CompiledPartitionTemplate::try_new(template).unwrap();
+        let keys: Vec<_> = partition_keys(&batch, "foo", &template).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "foo-host_.eu".to_string(),
+                "foo-host_.us".to_string(),
+                "foo-".to_string(),
+                "foo-".to_string(),
+                "foo-host_.eu".to_string(),
+            ]
+        );
+
+        // Non-string columns never match
+        let template = PartitionTemplate {
+            parts: vec![
+                TemplatePart::Table,
+                TemplatePart::RegexCapture(RegexCapture {
+                    column: "f64".to_string(),
+                    regex: ".*".to_string(),
+                }),
+            ],
+        };
+        let template = CompiledPartitionTemplate::try_new(template).unwrap();
+        let keys: Vec<_> = partition_keys(&batch, "foo", &template).collect();
+        assert_eq!(keys, vec!["foo-".to_string(); 5]);
+    }
+
+    #[test]
+    fn test_compile_invalid_template() {
+        let template = PartitionTemplate {
+            parts: vec![
+                TemplatePart::Table,
+                TemplatePart::RegexCapture(RegexCapture {
+                    column: "host".to_string(),
+                    regex: "(".to_string(),
+                }),
+            ],
+        };
+        let err = CompiledPartitionTemplate::try_new(template).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidPartitionTemplateRegex { .. }),
+            "{}",
+            err
+        );
+
+        for part in [
+            TemplatePart::TimeFormat("%Y-%Q".to_string()),
+            TemplatePart::StrftimeColumn(StrftimeColumn {
+                column: "created".to_string(),
+                format: "%Q".to_string(),
+            }),
+        ] {
+            let template = PartitionTemplate {
+                parts: vec![TemplatePart::Table, part],
+            };
+            let err = CompiledPartitionTemplate::try_new(template).unwrap_err();
+            assert!(
+                matches!(err, Error::InvalidPartitionTemplateFormat { .. }),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_partition_strftime_column() {
+        let mut batch = MutableBatch::new();
+        let mut writer = Writer::new(&mut batch, 3);
+
+        writer
+            .write_time("time", vec![1, 2, 3].into_iter())
+            .unwrap();
+
+        writer
+            .write_i64(
+                "created",
+                Some(&[0b00000101]),
+                vec![1465839830100400200, 1644347270670952000].into_iter(),
+            )
+            .unwrap();
+
+        writer.commit();
+
+        let template = PartitionTemplate {
+            parts: vec![
+                TemplatePart::Table,
+                TemplatePart::StrftimeColumn(StrftimeColumn {
+                    column: "created".to_string(),
+                    format: "%Y-%m-%d".to_string(),
+                }),
+            ],
+        };
+
+        let template = CompiledPartitionTemplate::try_new(template).unwrap();
+        let keys: Vec<_> = partition_keys(&batch, "foo", &template).collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                "foo-2016-06-13".to_string(),
+                "foo-".to_string(),
+                "foo-2022-02-08".to_string(),
+            ]
+        )
+    }
 }
@@ -22,7 +22,7 @@ use arrow_util::bitset::BitSet;
 use data_types::database_rules::{PartitionTemplate, TemplatePart};
 use data_types::partition_metadata::{IsNan, StatValues, Statistics};
 use mutable_batch::writer::Writer;
-use mutable_batch::{MutableBatch, PartitionWrite, WritePayload};
+use mutable_batch::{CompiledPartitionTemplate, MutableBatch, PartitionWrite, WritePayload};
 use schema::selection::Selection;
 
 fn make_rng() -> StdRng {
@@ -435,13 +435,11 @@ fn test_partition_write() {
         assert_eq!(write.rows().get() as u64, stats.total_count);
     };
 
-    let partitioned = PartitionWrite::partition(
-        "table",
-        &batch,
-        &PartitionTemplate {
-            parts: vec![TemplatePart::Column("b1".to_string())],
-        },
-    );
+    let template = CompiledPartitionTemplate::try_new(PartitionTemplate {
+        parts: vec![TemplatePart::Column("b1".to_string())],
+    })
+    .unwrap();
+    let partitioned = PartitionWrite::partition("table", &batch, &template);
 
     for (_, write) in &partitioned {
         verify_write(write);
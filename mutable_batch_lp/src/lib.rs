@@ -15,7 +15,8 @@ use hashbrown::HashMap;
 use influxdb_line_protocol::{parse_lines, FieldValue, ParsedLine};
 use mutable_batch::writer::Writer;
 use mutable_batch::MutableBatch;
-use snafu::{ResultExt, Snafu};
+use schema::{InfluxColumnType, InfluxFieldType};
+use snafu::{ensure, ResultExt, Snafu};
 
 /// Error type for line protocol conversion
 #[derive(Debug, Snafu)]
@@ -36,8 +37,36 @@ pub enum Error {
     #[snafu(display("empty write payload"))]
     EmptyPayload,
 
-    #[snafu(display("timestamp overflows i64"))]
-    TimestampOverflow,
+    #[snafu(display("timestamp of line {} overflows i64", line))]
+    TimestampOverflow { line: usize },
+
+    #[snafu(display(
+        "error writing line {}: column {} is type {} but write has type {}",
+        line,
+        column,
+        existing,
+        inserted
+    ))]
+    ColumnTypeConflict {
+        line: usize,
+        column: String,
+        existing: InfluxColumnType,
+        inserted: InfluxColumnType,
+    },
+}
+
+impl Error {
+    /// Returns the (1-based) number of the line that caused this error, if
+    /// the error is specific to a line.
+    pub fn line_number(&self) -> Option<usize> {
+        match self {
+            Self::LineProtocol { line, .. }
+            | Self::Write { line, .. }
+            | Self::TimestampOverflow { line }
+            | Self::ColumnTypeConflict { line, .. } => Some(*line),
+            Self::EmptyPayload => None,
+        }
+    }
 }
 
 /// Result type for line protocol conversion
@@ -84,37 +113,77 @@ impl LinesConverter {
     /// Write some line protocol data
     pub fn write_lp(&mut self, lines: &str) -> Result<()> {
         for (line_idx, maybe_line) in parse_lines(lines).enumerate() {
-            let mut line = maybe_line.context(LineProtocolSnafu { line: line_idx + 1 })?;
+            let line_number = line_idx + 1;
+            let line = maybe_line.context(LineProtocolSnafu { line: line_number })?;
+            self.write_parsed_line(line_number, line)?;
+        }
+        Ok(())
+    }
 
-            if let Some(t) = line.timestamp.as_mut() {
-                *t = t
-                    .checked_mul(self.timestamp_base)
-                    .ok_or(Error::TimestampOverflow)?;
-            }
+    /// Write some line protocol data, skipping any line that fails to parse,
+    /// conflicts with the schema of the lines written before it, or conflicts
+    /// with the type of an existing column.
+    ///
+    /// `column_type` is called with a table and column name, and returns the
+    /// type of that column if it already exists outside of this payload (for
+    /// example in the catalog), or `None` if any type is acceptable.
+    ///
+    /// Returns the errors of the skipped lines, in line order. Skipped lines
+    /// are not included in the [`PayloadStatistics`].
+    pub fn write_lp_partial<F>(&mut self, lines: &str, column_type: F) -> Vec<Error>
+    where
+        F: Fn(&str, &str) -> Option<InfluxColumnType>,
+    {
+        parse_lines(lines)
+            .enumerate()
+            .filter_map(|(line_idx, maybe_line)| {
+                let line_number = line_idx + 1;
+                maybe_line
+                    .context(LineProtocolSnafu { line: line_number })
+                    .and_then(|line| {
+                        check_column_types(line_number, &line, &column_type)?;
+                        self.write_parsed_line(line_number, line)
+                    })
+                    .err()
+            })
+            .collect()
+    }
 
-            self.stats.num_lines += 1;
-            self.stats.num_fields += line.field_set.len();
+    /// Write the (1-based) line number `line_number` of a line protocol
+    /// payload, leaving the batches unchanged if it cannot be written.
+    fn write_parsed_line(&mut self, line_number: usize, mut line: ParsedLine<'_>) -> Result<()> {
+        if let Some(t) = line.timestamp.as_mut() {
+            *t = t
+                .checked_mul(self.timestamp_base)
+                .ok_or(Error::TimestampOverflow { line: line_number })?;
+        }
 
-            let measurement = line.series.measurement.as_str();
+        let measurement = line.series.measurement.as_str();
 
-            let (_, batch) = self
-                .batches
-                .raw_entry_mut()
-                .from_key(measurement)
-                .or_insert_with(|| (measurement.to_string(), MutableBatch::new()));
+        let (_, batch) = self
+            .batches
+            .raw_entry_mut()
+            .from_key(measurement)
+            .or_insert_with(|| (measurement.to_string(), MutableBatch::new()));
+
+        // TODO: Reuse writer
+        let mut writer = Writer::new(batch, 1);
+        write_line(&mut writer, &line, self.default_time)
+            .context(WriteSnafu { line: line_number })?;
+        writer.commit();
+
+        self.stats.num_lines += 1;
+        self.stats.num_fields += line.field_set.len();
 
-            // TODO: Reuse writer
-            let mut writer = Writer::new(batch, 1);
-            write_line(&mut writer, &line, self.default_time)
-                .context(WriteSnafu { line: line_idx + 1 })?;
-            writer.commit();
-        }
         Ok(())
     }
 
     /// Consume this [`LinesConverter`] returning the [`MutableBatch`]
     /// and the [`PayloadStatistics`] for the written data
-    pub fn finish(self) -> Result<(HashMap<String, MutableBatch>, PayloadStatistics)> {
+    pub fn finish(mut self) -> Result<(HashMap<String, MutableBatch>, PayloadStatistics)> {
+        // Drop the batches of tables whose lines were all skipped
+        self.batches.retain(|_, batch| batch.rows() > 0);
+
         match self.batches.is_empty() {
             false => Ok((self.batches, self.stats)),
             true => Err(Error::EmptyPayload),
@@ -122,6 +191,48 @@ impl LinesConverter {
     }
 }
 
+/// Returns an error if a tag or field of `line` has a different type than the
+/// existing column of the same name returned by `column_type`.
+fn check_column_types<F>(line_number: usize, line: &ParsedLine<'_>, column_type: &F) -> Result<()>
+where
+    F: Fn(&str, &str) -> Option<InfluxColumnType>,
+{
+    let table = line.series.measurement.as_str();
+
+    let tags = line
+        .series
+        .tag_set
+        .iter()
+        .flatten()
+        .map(|(key, _)| (key.as_str(), InfluxColumnType::Tag));
+    let fields = line.field_set.iter().map(|(key, value)| {
+        let field_type = match value {
+            FieldValue::I64(_) => InfluxFieldType::Integer,
+            FieldValue::U64(_) => InfluxFieldType::UInteger,
+            FieldValue::F64(_) => InfluxFieldType::Float,
+            FieldValue::String(_) => InfluxFieldType::String,
+            FieldValue::Boolean(_) => InfluxFieldType::Boolean,
+        };
+        (key.as_str(), InfluxColumnType::Field(field_type))
+    });
+
+    for (column, inserted) in tags.chain(fields) {
+        if let Some(existing) = column_type(table, column) {
+            ensure!(
+                existing == inserted,
+                ColumnTypeConflictSnafu {
+                    line: line_number,
+                    column,
+                    existing,
+                    inserted,
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// Converts the provided lines of line protocol to a set of [`MutableBatch`]
 /// keyed by measurement name
 pub fn lines_to_batches(lines: &str, default_time: i64) -> Result<HashMap<String, MutableBatch>> {
@@ -309,4 +420,95 @@ m b=t 1639612800000000000
         assert!(u.is_valid(1));
         assert!(!u.is_valid(2));
     }
+
+    #[test]
+    fn test_partial() {
+        let lp = r#"cpu,tag1=v1 val=2i 0
+        cpu,tag1=v2 val=2.0 0
+        not a line
+        mem val=1i 9223372036854775807
+        disk val="bananas" 0
+        cpu,tag1=v3 val=3i 0"#;
+
+        let mut converter = LinesConverter::new(5);
+        converter.set_timestamp_base(10);
+        let errors = converter.write_lp_partial(lp, |_, _| None);
+
+        // The type conflict, unparsable line and timestamp overflow are
+        // rejected.
+        let lines: Vec<_> = errors.iter().map(|e| e.line_number()).collect();
+        assert_eq!(lines, vec![Some(2), Some(3), Some(4)]);
+        assert!(matches!(errors[0], Error::Write { .. }));
+        assert!(matches!(errors[1], Error::LineProtocol { .. }));
+        assert!(matches!(errors[2], Error::TimestampOverflow { .. }));
+
+        let (batches, stats) = converter.finish().unwrap();
+        assert_eq!(stats.num_lines, 3);
+        assert_eq!(stats.num_fields, 3);
+
+        // The "mem" table has no valid lines and is dropped
+        let mut tables: Vec<_> = batches.keys().cloned().collect();
+        tables.sort();
+        assert_eq!(tables, vec!["cpu".to_string(), "disk".to_string()]);
+
+        assert_batches_eq!(
+            &[
+                "+------+----------------------+-----+",
+                "| tag1 | time                 | val |",
+                "+------+----------------------+-----+",
+                "| v1   | 1970-01-01T00:00:00Z | 2   |",
+                "| v3   | 1970-01-01T00:00:00Z | 3   |",
+                "+------+----------------------+-----+",
+            ],
+            &[batches["cpu"].to_arrow(Selection::All).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_partial_existing_column_types() {
+        let lp = r#"cpu,host=a val=2i 0
+        cpu,host=b val=2.0 0
+        cpu,val=c other=1i 0
+        mem,host=a val=2.0 0"#;
+
+        // The "val" column of the "cpu" table already exists as an integer
+        // field, and "host" as a tag.
+        let column_type = |table: &str, column: &str| match (table, column) {
+            ("cpu", "val") => Some(InfluxColumnType::Field(InfluxFieldType::Integer)),
+            ("cpu", "host") => Some(InfluxColumnType::Tag),
+            _ => None,
+        };
+
+        let mut converter = LinesConverter::new(5);
+        let errors = converter.write_lp_partial(lp, column_type);
+
+        let lines: Vec<_> = errors.iter().map(|e| e.line_number()).collect();
+        assert_eq!(lines, vec![Some(2), Some(3)]);
+        assert!(matches!(
+            &errors[0],
+            Error::ColumnTypeConflict { column, .. } if column == "val"
+        ));
+        assert!(matches!(
+            &errors[1],
+            Error::ColumnTypeConflict {
+                column,
+                existing: InfluxColumnType::Field(InfluxFieldType::Integer),
+                inserted: InfluxColumnType::Tag,
+                ..
+            } if column == "val"
+        ));
+
+        let (batches, stats) = converter.finish().unwrap();
+        assert_eq!(stats.num_lines, 2);
+        assert_eq!(batches["cpu"].rows(), 1);
+        assert_eq!(batches["mem"].rows(), 1);
+    }
+
+    #[test]
+    fn test_partial_all_rejected() {
+        let mut converter = LinesConverter::new(5);
+        let errors = converter.write_lp_partial("not a line\nnor this", |_, _| None);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(converter.finish(), Err(Error::EmptyPayload)));
+    }
 }
@@ -1,11 +1,39 @@
 //! This module contains a trait and implementation for caching object storage objects
 //! in the local filesystem. In the case of the disk backed object store implementation,
 //! it yields locations to its files for cache locations and no-ops any cache modifications.
+//!
+//! [`LocalFSCache`] stores every cached object as a single file in a flat cache directory. The
+//! file name is the percent-encoded raw object store path, so the cache contents can be
+//! recovered from an existing directory after a restart. Objects are first downloaded into a
+//! temporary file which is atomically renamed into place, so a crash can never leave a
+//! partially written object behind under its final name.
+//!
+//! Every lookup returns a [`CachedFile`] that pins the object until it is dropped. Pinned objects
+//! are never evicted, so readers can hold on to a cached file for as long as they need it.
 
-use crate::{path::Path, DynObjectStore};
+use crate::{
+    path::{ObjectStorePath, Path},
+    DynObjectStore,
+};
 use async_trait::async_trait;
-use snafu::Snafu;
-use std::sync::Arc;
+use futures::TryStreamExt;
+use metric::{Metric, U64Counter, U64Gauge};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    path::{Path as StdPath, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
+};
+use tokio::io::AsyncWriteExt;
+
+/// Prefix of files that are still being written. Encoded object names never start with a `.`.
+const TEMP_FILE_PREFIX: &str = ".tmp-";
 
 /// Result for the cache
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -14,8 +42,26 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 #[derive(Debug, Snafu)]
 #[allow(missing_docs)]
 pub enum Error {
-    #[snafu(display("unable to evict '{}' from the local cache", name))]
-    UnableToEvict { name: String },
+    #[snafu(display("unable to evict '{}' from the local cache: {}", name, source))]
+    UnableToEvict { name: String, source: io::Error },
+
+    #[snafu(display("unable to create cache directory {:?}: {}", path, source))]
+    UnableToCreateDir { path: PathBuf, source: io::Error },
+
+    #[snafu(display("unable to read cache directory {:?}: {}", path, source))]
+    UnableToReadDir { path: PathBuf, source: io::Error },
+
+    #[snafu(display("unable to fetch '{}' from object store: {}", name, source))]
+    UnableToFetch { name: String, source: crate::Error },
+
+    #[snafu(display("unable to write cache file {:?}: {}", path, source))]
+    UnableToWrite { path: PathBuf, source: io::Error },
+
+    #[snafu(display("cannot evict '{}' from the local cache while it is being read", name))]
+    ObjectPinned { name: String },
+
+    #[snafu(display("'{}' is not a path of this object store", name))]
+    UnsupportedPath { name: String },
 }
 
 /// Defines an LRU cache with local file locations for objects from object store.
@@ -24,11 +70,13 @@ pub trait Cache {
     /// Evicts an object from the local filesystem cache.
     fn evict(&self, path: &Path) -> Result<()>;
 
-    /// Returns the local filesystem path for the given object. If it isn't present, this
-    /// will get the object from object storage and write it to the local filesystem cache.
-    /// If the cache is over its limit, it will evict other cached objects based on an LRU
-    /// policy.
-    async fn fs_path_or_cache(&self, path: &Path, store: Arc<DynObjectStore>) -> Result<&str>;
+    /// Returns the local file for the given object. If it isn't present, this will get the
+    /// object from object storage and write it to the local filesystem cache. If the cache is
+    /// over its limit, it will evict other cached objects based on an LRU policy.
+    ///
+    /// The object cannot be evicted until the returned [`CachedFile`] is dropped.
+    async fn fs_path_or_cache(&self, path: &Path, store: Arc<DynObjectStore>)
+        -> Result<CachedFile>;
 
     /// The size in bytes of all files in the cache.
     fn size(&self) -> u64;
@@ -37,27 +85,637 @@ pub trait Cache {
     fn limit(&self) -> u64;
 }
 
+/// A file in the local filesystem returned by a [`Cache`].
+///
+/// The object stays pinned in the cache, i.e. it is not evicted, until this is dropped.
+#[derive(Debug)]
+pub struct CachedFile {
+    path: PathBuf,
+    pin: Option<PinGuard>,
+}
+
+impl CachedFile {
+    /// A file that is not managed by a cache and therefore never evicted.
+    pub fn unmanaged(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            pin: None,
+        }
+    }
+
+    /// The location of the file in the local filesystem.
+    pub fn path(&self) -> &StdPath {
+        &self.path
+    }
+}
+
+/// Releases the pin on a cached object when dropped.
+#[derive(Debug)]
+struct PinGuard {
+    state: Arc<Mutex<CacheState>>,
+    name: String,
+}
+
+impl Drop for PinGuard {
+    fn drop(&mut self) {
+        self.state
+            .lock()
+            .expect("cache state poisoned")
+            .unpin(&self.name);
+    }
+}
+
 /// Implementation of the local file system cache that keeps the LRU stats and
 /// performs any evictions to load new objects in.
 #[derive(Debug)]
-#[allow(missing_copy_implementations)]
-pub struct LocalFSCache {}
+pub struct LocalFSCache {
+    /// Directory holding the cached objects.
+    dir: PathBuf,
+
+    /// Upper bound for the summed size of all cached objects.
+    limit: u64,
+
+    /// Index over the cached objects, shared with the pins of [`CachedFile`]s.
+    state: Arc<Mutex<CacheState>>,
+
+    /// Used to generate unique names for temporary files.
+    next_temp_file: AtomicU64,
+
+    hits: U64Counter,
+    misses: U64Counter,
+    evictions: U64Counter,
+    size_bytes: U64Gauge,
+}
+
+/// A single cached object.
+#[derive(Debug, Clone, Copy)]
+struct CacheEntry {
+    size: u64,
+    last_used: u64,
+
+    /// Number of [`CachedFile`]s referencing this entry.
+    pins: usize,
+}
+
+/// LRU bookkeeping, keyed by the encoded object name.
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+
+    /// Encoded object names ordered by their last access, oldest first.
+    lru: BTreeMap<u64, String>,
+
+    /// Logical clock used to order accesses.
+    clock: u64,
+
+    /// Summed size of all entries.
+    size: u64,
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Marks the given entry as used and pins it, returning `true` if it exists.
+    fn touch_and_pin(&mut self, name: &str) -> bool {
+        let now = self.tick();
+        match self.entries.get_mut(name) {
+            Some(entry) => {
+                self.lru.remove(&entry.last_used);
+                entry.last_used = now;
+                entry.pins += 1;
+                self.lru.insert(now, name.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts the given entry. Pins of a previous entry under the same name are kept.
+    fn insert(&mut self, name: String, size: u64) {
+        let pins = self
+            .remove(&name)
+            .map(|entry| entry.pins)
+            .unwrap_or_default();
+        let last_used = self.tick();
+        self.lru.insert(last_used, name.clone());
+        self.entries.insert(
+            name,
+            CacheEntry {
+                size,
+                last_used,
+                pins,
+            },
+        );
+        self.size += size;
+    }
+
+    fn pin(&mut self, name: &str) {
+        if let Some(entry) = self.entries.get_mut(name) {
+            entry.pins += 1;
+        }
+    }
+
+    fn unpin(&mut self, name: &str) {
+        if let Some(entry) = self.entries.get_mut(name) {
+            entry.pins = entry.pins.saturating_sub(1);
+        }
+    }
+
+    fn remove(&mut self, name: &str) -> Option<CacheEntry> {
+        let entry = self.entries.remove(name)?;
+        self.lru.remove(&entry.last_used);
+        self.size -= entry.size;
+        Some(entry)
+    }
+
+    /// Removes least recently used entries until the size is within `limit`, never removing
+    /// pinned entries. Returns the names of the removed entries.
+    ///
+    /// If pinned entries alone exceed `limit`, the cache stays over its limit until they are
+    /// unpinned and a later call evicts them.
+    fn evict_to(&mut self, limit: u64) -> Vec<String> {
+        let mut evicted = vec![];
+        while self.size > limit {
+            let entries = &self.entries;
+            let victim = match self
+                .lru
+                .values()
+                .find(|name| entries[name.as_str()].pins == 0)
+            {
+                Some(name) => name.clone(),
+                None => break,
+            };
+            self.remove(&victim);
+            evicted.push(victim);
+        }
+        evicted
+    }
+}
+
+impl LocalFSCache {
+    /// Create a cache in `dir` that holds at most `limit` bytes.
+    ///
+    /// Objects already present in `dir` (e.g. from a previous run) are added to the cache,
+    /// oldest modification time first, and leftover temporary files are removed. If the
+    /// recovered objects exceed `limit`, the oldest ones are evicted.
+    pub fn new(dir: impl Into<PathBuf>, limit: u64, registry: &metric::Registry) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).context(UnableToCreateDirSnafu { path: &dir })?;
+
+        let requests: Metric<U64Counter> = registry.register_metric(
+            "object_store_cache_requests",
+            "number of object lookups in the local filesystem cache",
+        );
+        let evictions = registry
+            .register_metric::<U64Counter>(
+                "object_store_cache_evictions",
+                "number of objects evicted from the local filesystem cache",
+            )
+            .recorder(&[]);
+        let size_bytes = registry
+            .register_metric::<U64Gauge>(
+                "object_store_cache_size_bytes",
+                "summed size of all objects in the local filesystem cache",
+            )
+            .recorder(&[]);
+
+        let cache = Self {
+            state: Arc::new(Mutex::new(Self::recover(&dir)?)),
+            dir,
+            limit,
+            next_temp_file: AtomicU64::new(0),
+            hits: requests.recorder(&[("result", "hit")]),
+            misses: requests.recorder(&[("result", "miss")]),
+            evictions,
+            size_bytes,
+        };
+
+        let evicted = {
+            let mut state = cache.state.lock().expect("cache state poisoned");
+            let evicted = state.evict_to(limit);
+            cache.size_bytes.set(state.size);
+            evicted
+        };
+        cache.remove_files(evicted);
+
+        Ok(cache)
+    }
+
+    /// Rebuild the cache index from the contents of `dir`.
+    fn recover(dir: &std::path::Path) -> Result<CacheState> {
+        let mut files = vec![];
+        for entry in std::fs::read_dir(dir).context(UnableToReadDirSnafu { path: dir })? {
+            let entry = entry.context(UnableToReadDirSnafu { path: dir })?;
+            let metadata = entry
+                .metadata()
+                .context(UnableToReadDirSnafu { path: entry.path() })?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let name = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            if name.starts_with(TEMP_FILE_PREFIX) {
+                // a write that never completed, the object is fetched again on demand
+                std::fs::remove_file(entry.path())
+                    .context(UnableToWriteSnafu { path: entry.path() })?;
+                continue;
+            }
+
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((modified, name, metadata.len()));
+        }
+
+        files.sort();
+
+        let mut state = CacheState::default();
+        for (_, name, size) in files {
+            state.insert(name, size);
+        }
+        Ok(state)
+    }
+
+    fn file_path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// A [`CachedFile`] for an entry that was pinned by the caller.
+    fn pinned_file(&self, name: String) -> CachedFile {
+        CachedFile {
+            path: self.file_path(&name),
+            pin: Some(PinGuard {
+                state: Arc::clone(&self.state),
+                name,
+            }),
+        }
+    }
+
+    /// Best-effort removal of evicted files. Failures only leak disk space until the next
+    /// restart, where the file is picked up again by the recovery.
+    fn remove_files(&self, names: Vec<String>) {
+        for name in names {
+            self.evictions.inc(1);
+            let _ = std::fs::remove_file(self.file_path(&name));
+        }
+    }
+
+    /// Download `path` into a temporary file and atomically move it to `target`, returning the
+    /// size of the object.
+    async fn fetch(
+        &self,
+        path: &Path,
+        store: &DynObjectStore,
+        target: &std::path::Path,
+    ) -> Result<u64> {
+        let name = path.to_raw();
+        let temp = self.file_path(&format!(
+            "{}{}",
+            TEMP_FILE_PREFIX,
+            self.next_temp_file.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let res: Result<u64> = async {
+            let mut stream = store
+                .get(path)
+                .await
+                .context(UnableToFetchSnafu { name: &name })?
+                .into_stream();
+
+            let mut file = tokio::fs::File::create(&temp)
+                .await
+                .context(UnableToWriteSnafu { path: &temp })?;
+            let mut size = 0;
+            while let Some(bytes) = stream
+                .try_next()
+                .await
+                .context(UnableToFetchSnafu { name: &name })?
+            {
+                size += bytes.len() as u64;
+                file.write_all(&bytes)
+                    .await
+                    .context(UnableToWriteSnafu { path: &temp })?;
+            }
+            file.sync_all()
+                .await
+                .context(UnableToWriteSnafu { path: &temp })?;
+
+            tokio::fs::rename(&temp, target)
+                .await
+                .context(UnableToWriteSnafu { path: target })?;
+            Ok(size)
+        }
+        .await;
+
+        if res.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        res
+    }
+}
+
+/// Encode a raw object store path into a flat file name.
+fn encode_name(path: &Path) -> String {
+    utf8_percent_encode(&path.to_raw(), NON_ALPHANUMERIC).to_string()
+}
 
 #[async_trait]
 impl Cache for LocalFSCache {
-    fn evict(&self, _path: &Path) -> Result<()> {
-        todo!()
+    fn evict(&self, path: &Path) -> Result<()> {
+        let name = encode_name(path);
+        let removed = {
+            let mut state = self.state.lock().expect("cache state poisoned");
+            if state.entries.get(&name).map(|entry| entry.pins > 0) == Some(true) {
+                return Err(Error::ObjectPinned {
+                    name: path.to_raw(),
+                });
+            }
+            let removed = state.remove(&name);
+            self.size_bytes.set(state.size);
+            removed
+        };
+
+        if removed.is_some() {
+            self.evictions.inc(1);
+            match std::fs::remove_file(self.file_path(&name)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(source) => {
+                    return Err(Error::UnableToEvict {
+                        name: path.to_raw(),
+                        source,
+                    })
+                }
+            }
+        }
+
+        Ok(())
     }
 
-    async fn fs_path_or_cache(&self, _path: &Path, _store: Arc<DynObjectStore>) -> Result<&str> {
-        todo!()
+    async fn fs_path_or_cache(
+        &self,
+        path: &Path,
+        store: Arc<DynObjectStore>,
+    ) -> Result<CachedFile> {
+        let name = encode_name(path);
+
+        if self
+            .state
+            .lock()
+            .expect("cache state poisoned")
+            .touch_and_pin(&name)
+        {
+            self.hits.inc(1);
+            return Ok(self.pinned_file(name));
+        }
+        self.misses.inc(1);
+
+        let target = self.file_path(&name);
+        let size = self.fetch(path, store.as_ref(), &target).await?;
+
+        let evicted = {
+            let mut state = self.state.lock().expect("cache state poisoned");
+            state.insert(name.clone(), size);
+            state.pin(&name);
+            let evicted = state.evict_to(self.limit);
+            self.size_bytes.set(state.size);
+            evicted
+        };
+        self.remove_files(evicted);
+
+        Ok(self.pinned_file(name))
     }
 
     fn size(&self) -> u64 {
-        todo!()
+        self.state.lock().expect("cache state poisoned").size
     }
 
     fn limit(&self) -> u64 {
-        todo!()
+        self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ObjectStoreApi, ObjectStoreImpl};
+    use bytes::Bytes;
+    use metric::Attributes;
+
+    fn counter<const N: usize>(
+        registry: &metric::Registry,
+        name: &'static str,
+        attr: [(&'static str, &'static str); N],
+    ) -> u64 {
+        registry
+            .get_instrument::<Metric<U64Counter>>(name)
+            .expect("failed to read counter")
+            .get_observer(&Attributes::from(&attr))
+            .expect("failed to get observer")
+            .fetch()
+    }
+
+    async fn store_with(objects: &[(&str, usize)]) -> Arc<DynObjectStore> {
+        let store = ObjectStoreImpl::new_in_memory();
+        for (name, size) in objects {
+            store
+                .put(&store.path_from_raw(name), Bytes::from(vec![42_u8; *size]))
+                .await
+                .unwrap();
+        }
+        Arc::new(store)
+    }
+
+    /// Look up `name` and immediately release the pin, returning the local path.
+    async fn fetch_path(cache: &LocalFSCache, store: &Arc<DynObjectStore>, name: &str) -> PathBuf {
+        cache
+            .fs_path_or_cache(&store.path_from_raw(name), Arc::clone(store))
+            .await
+            .unwrap()
+            .path()
+            .to_path_buf()
+    }
+
+    #[tokio::test]
+    async fn test_hit_and_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = metric::Registry::default();
+        let store = store_with(&[("foo/bar.parquet", 10)]).await;
+        let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+        let path = store.path_from_raw("foo/bar.parquet");
+
+        let local = fetch_path(&cache, &store, "foo/bar.parquet").await;
+        assert_eq!(std::fs::read(&local).unwrap(), vec![42_u8; 10]);
+        assert_eq!(cache.size(), 10);
+        assert_eq!(cache.limit(), 100);
+
+        // the object store is not consulted again
+        store.delete(&path).await.unwrap();
+        let local2 = fetch_path(&cache, &store, "foo/bar.parquet").await;
+        assert_eq!(local, local2);
+
+        assert_eq!(
+            counter(
+                &registry,
+                "object_store_cache_requests",
+                [("result", "miss")]
+            ),
+            1
+        );
+        assert_eq!(
+            counter(
+                &registry,
+                "object_store_cache_requests",
+                [("result", "hit")]
+            ),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = metric::Registry::default();
+        let store = store_with(&[]).await;
+        let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+
+        let err = cache
+            .fs_path_or_cache(&store.path_from_raw("missing"), Arc::clone(&store))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::UnableToFetch { .. }));
+        assert_eq!(cache.size(), 0);
+
+        // no temporary file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = metric::Registry::default();
+        let store = store_with(&[("a", 40), ("b", 40), ("c", 40)]).await;
+        let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+
+        let a = fetch_path(&cache, &store, "a").await;
+        let b = fetch_path(&cache, &store, "b").await;
+
+        // use "a" again so that "b" is the least recently used object
+        fetch_path(&cache, &store, "a").await;
+
+        let c = fetch_path(&cache, &store, "c").await;
+
+        assert_eq!(cache.size(), 80);
+        assert!(a.exists());
+        assert!(!b.exists());
+        assert!(c.exists());
+        assert_eq!(counter(&registry, "object_store_cache_evictions", []), 1);
+
+        // explicit eviction
+        cache.evict(&store.path_from_raw("a")).unwrap();
+        assert!(!a.exists());
+        assert_eq!(cache.size(), 40);
+        assert_eq!(counter(&registry, "object_store_cache_evictions", []), 2);
+
+        // evicting an unknown object is a no-op
+        cache.evict(&store.path_from_raw("a")).unwrap();
+        assert_eq!(counter(&registry, "object_store_cache_evictions", []), 2);
+    }
+
+    #[tokio::test]
+    async fn test_object_larger_than_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = metric::Registry::default();
+        let store = store_with(&[("a", 10), ("big", 200)]).await;
+        let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+
+        let a = fetch_path(&cache, &store, "a").await;
+        let big = fetch_path(&cache, &store, "big").await;
+
+        // the requested object is always served, everything else is evicted
+        assert!(!a.exists());
+        assert!(big.exists());
+        assert_eq!(cache.size(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store_with(&[("a", 10), ("b", 20)]).await;
+
+        let a = {
+            let registry = metric::Registry::default();
+            let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+            let a = fetch_path(&cache, &store, "a").await;
+            fetch_path(&cache, &store, "b").await;
+            a
+        };
+
+        // simulate an interrupted download
+        let temp = dir.path().join(format!("{}0", TEMP_FILE_PREFIX));
+        std::fs::write(&temp, b"partial").unwrap();
+
+        let registry = metric::Registry::default();
+        let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+        assert_eq!(cache.size(), 30);
+        assert!(!temp.exists());
+
+        // recovered objects are served without consulting the object store
+        store.delete(&store.path_from_raw("a")).await.unwrap();
+        let local = fetch_path(&cache, &store, "a").await;
+        assert_eq!(local, a);
+        assert_eq!(
+            counter(
+                &registry,
+                "object_store_cache_requests",
+                [("result", "hit")]
+            ),
+            1
+        );
+
+        // a smaller limit evicts recovered objects on startup
+        drop(cache);
+        let registry = metric::Registry::default();
+        let cache = LocalFSCache::new(dir.path(), 25, &registry).unwrap();
+        assert!(cache.size() <= 25);
+        assert_eq!(counter(&registry, "object_store_cache_evictions", []), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_objects_are_not_evicted() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = metric::Registry::default();
+        let store = store_with(&[("a", 60), ("b", 60), ("c", 60)]).await;
+        let cache = LocalFSCache::new(dir.path(), 100, &registry).unwrap();
+
+        let a = cache
+            .fs_path_or_cache(&store.path_from_raw("a"), Arc::clone(&store))
+            .await
+            .unwrap();
+
+        // "a" is the least recently used object but still being read, so the cache stays over
+        // its limit
+        let b = fetch_path(&cache, &store, "b").await;
+        assert!(a.path().exists());
+        assert!(b.exists());
+        assert_eq!(cache.size(), 120);
+        assert_eq!(counter(&registry, "object_store_cache_evictions", []), 0);
+
+        // explicit eviction is refused as well
+        let err = cache.evict(&store.path_from_raw("a")).unwrap_err();
+        assert!(matches!(err, Error::ObjectPinned { .. }));
+
+        // once released, "a" is evicted by the next insertion
+        let a_path = a.path().to_path_buf();
+        drop(a);
+        let c = fetch_path(&cache, &store, "c").await;
+        assert!(!a_path.exists());
+        assert!(!b.exists());
+        assert!(c.exists());
+        assert_eq!(cache.size(), 60);
     }
 }
@@ -1,7 +1,10 @@
 //! This module contains the IOx implementation for using local disk as the
 //! object store.
 use crate::path::{parsed::DirsAndFileName, Path};
-use crate::{cache::Cache, DynObjectStore};
+use crate::{
+    cache::{Cache, CachedFile},
+    DynObjectStore,
+};
 use crate::{path::file::FilePath, GetResult, ListResult, ObjectMeta, ObjectStoreApi};
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -270,23 +273,29 @@ impl ObjectStoreApi for File {
 #[async_trait]
 impl Cache for File {
     fn evict(&self, _path: &Path) -> crate::cache::Result<()> {
-        todo!()
+        // the files are the source of truth, nothing to evict
+        Ok(())
     }
 
     async fn fs_path_or_cache(
         &self,
-        _path: &Path,
+        path: &Path,
         _store: Arc<DynObjectStore>,
-    ) -> crate::cache::Result<&str> {
-        todo!()
+    ) -> crate::cache::Result<CachedFile> {
+        match path {
+            Path::File(location) => Ok(CachedFile::unmanaged(self.path(location))),
+            _ => Err(crate::cache::Error::UnsupportedPath {
+                name: path.to_raw(),
+            }),
+        }
     }
 
     fn size(&self) -> u64 {
-        todo!()
+        0
     }
 
     fn limit(&self) -> u64 {
-        todo!()
+        0
     }
 }
 
@@ -372,6 +381,40 @@ mod tests {
         assert_eq!(&*read_data, expected_data);
     }
 
+    #[tokio::test]
+    async fn cache_passthrough() {
+        let root = TempDir::new().unwrap();
+        let integration = Arc::new(ObjectStoreImpl::new_file(root.path()));
+        let cache = File::new(root.path());
+
+        let mut location = integration.new_path();
+        location.set_file_name("some_file");
+        integration
+            .put(&location, Bytes::from("arbitrary data"))
+            .await
+            .unwrap();
+
+        let local = cache
+            .fs_path_or_cache(&location, Arc::clone(&integration) as _)
+            .await
+            .unwrap();
+        assert_eq!(local.path(), root.path().join("some_file"));
+        assert_eq!(std::fs::read(local.path()).unwrap(), b"arbitrary data");
+
+        // evicting leaves the file in place
+        cache.evict(&location).unwrap();
+        assert!(local.path().exists());
+        assert_eq!(cache.size(), 0);
+
+        // paths of other object stores are rejected
+        let foreign = ObjectStoreImpl::new_in_memory().path_from_raw("some_file");
+        let err = cache
+            .fs_path_or_cache(&foreign, Arc::clone(&integration) as _)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::cache::Error::UnsupportedPath { .. }));
+    }
+
     #[tokio::test]
     async fn unknown_length() {
         let root = TempDir::new().unwrap();
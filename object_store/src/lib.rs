@@ -52,7 +52,7 @@ use throttle::ThrottledStore;
 pub use throttle::ThrottleConfig;
 
 use crate::{
-    cache::{Cache, LocalFSCache},
+    cache::{Cache, CachedFile, LocalFSCache},
     path::Path,
 };
 use async_trait::async_trait;
@@ -123,7 +123,7 @@ pub trait ObjectStoreApi: Send + Sync + Debug + 'static {
 pub struct ObjectStoreImpl {
     /// The object store
     pub integration: ObjectStoreIntegration,
-    cache: Option<ObjectStoreFileCache>,
+    cache: Option<Arc<ObjectStoreFileCache>>,
 }
 
 impl ObjectStoreImpl {
@@ -219,8 +219,18 @@ impl ObjectStoreImpl {
         })
     }
 
+    /// Cache objects of a remote object store in the local filesystem using `cache`.
+    ///
+    /// Local file storage always serves its own files and ignores this setting.
+    pub fn with_local_fs_cache(mut self, cache: LocalFSCache) -> Self {
+        if !matches!(self.integration, ObjectStoreIntegration::File(_)) {
+            self.cache = Some(Arc::new(ObjectStoreFileCache::File(cache)));
+        }
+        self
+    }
+
     /// Returns the filesystem cache if configured
-    pub fn cache(&self) -> &Option<ObjectStoreFileCache> {
+    pub fn cache(&self) -> &Option<Arc<ObjectStoreFileCache>> {
         &self.cache
     }
 }
@@ -506,7 +516,7 @@ impl Cache for ObjectStoreFileCache {
         &self,
         path: &Path,
         store: Arc<DynObjectStore>,
-    ) -> crate::cache::Result<&str> {
+    ) -> crate::cache::Result<CachedFile> {
         match &self {
             Self::Passthrough(f) => f.fs_path_or_cache(path, store).await,
             Self::File(f) => f.fs_path_or_cache(path, store).await,
@@ -522,8 +532,8 @@ impl Cache for ObjectStoreFileCache {
 
     fn limit(&self) -> u64 {
         match &self {
-            Self::Passthrough(f) => f.size(),
-            Self::File(f) => f.size(),
+            Self::Passthrough(f) => f.limit(),
+            Self::File(f) => f.limit(),
         }
     }
 }
@@ -68,6 +68,12 @@ pub enum Error {
     #[snafu(display("Error reading data from object store: {}", source))]
     ReadingObjectStore { source: object_store::Error },
 
+    #[snafu(display("Error reading data from local object store cache: {}", source))]
+    ReadingObjectStoreCache { source: object_store::cache::Error },
+
+    #[snafu(display("Error opening cached file: {}", source))]
+    OpenCachedFile { source: std::io::Error },
+
     #[snafu(display("Cannot extract Parquet metadata from byte array: {}", source))]
     ExtractingMetadataFailure { source: crate::metadata::Error },
 
@@ -250,6 +256,9 @@ impl Storage {
     /// Downloads the specified parquet file to a local temporary file
     /// and uses the `[ParquetExec`]
     ///
+    /// If the object store has a local filesystem cache, the cached copy is read instead and
+    /// stays pinned in the cache until the scan is done.
+    ///
     /// The resulting record batches from Parquet are sent back to `tx`
     fn download_and_scan_parquet(
         projection: Vec<usize>,
@@ -260,7 +269,39 @@ impl Storage {
         // Size of each batch
         let batch_size = 1024; // Todo: make a constant or policy for this
 
-        let read_stream = futures::executor::block_on(store.get_parquet_file(&path))
+        // `_cached_file` holds the pin on the cached copy until the end of the scan
+        let (file, _cached_file) =
+            match futures::executor::block_on(store.get_cached_parquet_file(&path)) {
+                Some(cached_file) => {
+                    let cached_file = cached_file.context(ReadingObjectStoreCacheSnafu)?;
+                    debug!(?path, local_path = ?cached_file.path(), "Using cached file");
+                    let file =
+                        std::fs::File::open(cached_file.path()).context(OpenCachedFileSnafu)?;
+                    (file, Some(cached_file))
+                }
+                None => (Self::download_parquet(&path, &store)?, None),
+            };
+
+        let file_reader = SerializedFileReader::new(file).context(ParquetReaderSnafu)?;
+        let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+        let record_batch_reader = arrow_reader
+            .get_record_reader_by_columns(projection, batch_size)
+            .context(ParquetReaderSnafu)?;
+
+        for batch in record_batch_reader {
+            if tx.blocking_send(batch).is_err() {
+                debug!(?path, "Receiver hung up - exiting");
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get a local file with the content of the specified parquet file, downloading it to a
+    /// temporary file if the object store is not backed by local files.
+    fn download_parquet(path: &ParquetFilePath, store: &IoxObjectStore) -> Result<std::fs::File> {
+        let read_stream = futures::executor::block_on(store.get_parquet_file(path))
             .context(ReadingObjectStoreSnafu)?;
 
         let file = match read_stream {
@@ -287,20 +328,7 @@ impl Storage {
             }
         };
 
-        let file_reader = SerializedFileReader::new(file).context(ParquetReaderSnafu)?;
-        let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
-        let record_batch_reader = arrow_reader
-            .get_record_reader_by_columns(projection, batch_size)
-            .context(ParquetReaderSnafu)?;
-
-        for batch in record_batch_reader {
-            if tx.blocking_send(batch).is_err() {
-                debug!(?path, "Receiver hung up - exiting");
-                break;
-            }
-        }
-
-        Ok(())
+        Ok(file)
     }
 
     pub fn read_filter(
@@ -399,6 +427,10 @@ mod tests {
     use arrow_util::assert_batches_eq;
     use data_types::chunk_metadata::{ChunkId, ChunkOrder};
     use datafusion_util::{stream_from_batch, MemoryStream};
+    use object_store::{
+        cache::{Cache, LocalFSCache},
+        DynObjectStore, ObjectStoreFileCache, ObjectStoreImpl,
+    };
     use parquet::schema::types::ColumnPath;
     use time::Time;
 
@@ -449,7 +481,33 @@ mod tests {
     async fn test_roundtrip() {
         test_helpers::maybe_start_logging();
         // validates that the async plumbing is setup to read parquet files from object store
+        assert_roundtrip(make_iox_object_store().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip_cached() {
+        test_helpers::maybe_start_logging();
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cache = Arc::new(ObjectStoreFileCache::File(
+            LocalFSCache::new(cache_dir.path(), 1_000_000, &metric::Registry::default()).unwrap(),
+        ));
+        let object_store: Arc<DynObjectStore> = Arc::new(ObjectStoreImpl::new_in_memory());
+        let iox_object_store = Arc::new(
+            IoxObjectStore::existing(
+                Arc::clone(&object_store),
+                IoxObjectStore::root_path_for(&*object_store, uuid::Uuid::new_v4()),
+            )
+            .with_cache(Arc::clone(&cache)),
+        );
+
+        assert_roundtrip(iox_object_store).await;
+
+        // the file was read through the cache
+        assert!(cache.size() > 0);
+    }
 
+    async fn assert_roundtrip(iox_object_store: Arc<IoxObjectStore>) {
         // prepare input
         let array = StringArray::from(vec!["foo", "bar", "baz"]);
         let batch = RecordBatch::try_from_iter(vec![(
@@ -475,7 +533,6 @@ mod tests {
         let table_name = Arc::from("my_table");
         let partition_key = Arc::from("my_partition");
         let chunk_id = ChunkId::new_test(33);
-        let iox_object_store = make_iox_object_store().await;
         let db_name = Arc::from("db1");
         let storage = Storage::new(Arc::clone(&iox_object_store));
 
@@ -5,7 +5,7 @@ use data_types2::{
 };
 use iox_catalog::interface::Catalog;
 use iox_object_store::IoxObjectStore;
-use object_store::DynObjectStore;
+use object_store::{DynObjectStore, ObjectStoreFileCache};
 use parquet_file::chunk::{
     new_parquet_chunk, ChunkMetrics as ParquetChunkMetrics, DecodedParquetFile, ParquetChunk,
 };
@@ -156,17 +156,24 @@ pub struct ParquetChunkAdapter {
 
 impl ParquetChunkAdapter {
     /// Create new adapter with empty cache.
+    ///
+    /// Parquet files are read through `object_store_cache` if it is set.
     pub fn new(
         catalog_cache: Arc<CatalogCache>,
         object_store: Arc<DynObjectStore>,
+        object_store_cache: Option<Arc<ObjectStoreFileCache>>,
         metric_registry: Arc<metric::Registry>,
         time_provider: Arc<dyn TimeProvider>,
     ) -> Self {
         // create a virtual IOx object store, the UUID won't be used anyways
-        let iox_object_store = Arc::new(IoxObjectStore::existing(
+        let iox_object_store = IoxObjectStore::existing(
             Arc::clone(&object_store),
             IoxObjectStore::root_path_for(&*object_store, uuid::Uuid::new_v4()),
-        ));
+        );
+        let iox_object_store = Arc::new(match object_store_cache {
+            Some(cache) => iox_object_store.with_cache(cache),
+            None => iox_object_store,
+        });
 
         Self {
             catalog_cache,
@@ -186,6 +193,11 @@ impl ParquetChunkAdapter {
         self.catalog_cache.catalog()
     }
 
+    /// Get underlying time provider.
+    pub fn time_provider(&self) -> &Arc<dyn TimeProvider> {
+        &self.time_provider
+    }
+
     /// Create parquet chunk.
     ///
     /// Returns `None` if some data required to create this chunk is already gone from the catalog.
@@ -305,6 +317,7 @@ mod tests {
                 catalog.time_provider(),
             )),
             catalog.object_store(),
+            None,
             catalog.metric_registry(),
             catalog.time_provider(),
         );
@@ -2,13 +2,14 @@
 
 use crate::{
     cache::CatalogCache,
+    ingester::IngesterConnection,
     namespace::QuerierNamespace,
     poison::{PoisonCabinet, PoisonPill},
 };
 use backoff::{Backoff, BackoffConfig};
 use data_types2::NamespaceId;
 use iox_catalog::interface::Catalog;
-use object_store::DynObjectStore;
+use object_store::{DynObjectStore, ObjectStoreFileCache};
 use observability_deps::tracing::{error, info};
 use parking_lot::RwLock;
 use query::exec::Executor;
@@ -46,11 +47,17 @@ pub struct QuerierDatabase {
     /// Object store.
     object_store: Arc<DynObjectStore>,
 
+    /// Local filesystem cache for parquet files, if any.
+    object_store_cache: Option<Arc<ObjectStoreFileCache>>,
+
     /// Time provider.
     time_provider: Arc<dyn TimeProvider>,
 
     /// Executor for queries.
     exec: Arc<Executor>,
+
+    /// Connection to ingester
+    ingester_connection: Arc<dyn IngesterConnection>,
 }
 
 impl QueryDatabaseProvider for QuerierDatabase {
@@ -69,8 +76,10 @@ impl QuerierDatabase {
         catalog: Arc<dyn Catalog>,
         metric_registry: Arc<metric::Registry>,
         object_store: Arc<DynObjectStore>,
+        object_store_cache: Option<Arc<ObjectStoreFileCache>>,
         time_provider: Arc<dyn TimeProvider>,
         exec: Arc<Executor>,
+        ingester_connection: Arc<dyn IngesterConnection>,
     ) -> Self {
         let catalog_cache = Arc::new(CatalogCache::new(
             Arc::clone(&catalog),
@@ -84,8 +93,10 @@ impl QuerierDatabase {
             metric_registry,
             namespaces: RwLock::new(HashMap::new()),
             object_store,
+            object_store_cache,
             time_provider,
             exec,
+            ingester_connection,
         }
     }
 
@@ -139,7 +150,6 @@ impl QuerierDatabase {
 
             // perform modification
             for name in to_delete {
-                // TODO(marco): this is currently untested because `iox_catalog` doesn't implement namespace deletion
                 namespaces_guard.remove(&name);
             }
             for (name, id) in to_add {
@@ -151,8 +161,10 @@ impl QuerierDatabase {
                         id,
                         Arc::clone(&self.metric_registry),
                         Arc::clone(&self.object_store),
+                        self.object_store_cache.clone(),
                         Arc::clone(&self.time_provider),
                         Arc::clone(&self.exec),
+                        Arc::clone(&self.ingester_connection),
                     )),
                 );
             }
@@ -200,6 +212,8 @@ pub(crate) async fn database_sync_loop(
 mod tests {
     use iox_tests::util::TestCatalog;
 
+    use crate::ingester::create_ingester_connection_for_testing;
+
     use super::*;
 
     #[tokio::test]
@@ -210,8 +224,10 @@ mod tests {
             catalog.catalog(),
             catalog.metric_registry(),
             catalog.object_store(),
+            None,
             catalog.time_provider(),
             catalog.exec(),
+            create_ingester_connection_for_testing(),
         );
         assert_eq!(ns_names(&db), vec![]);
 
@@ -238,6 +254,20 @@ mod tests {
         let ns2_b = db.namespace("ns2").unwrap();
         assert!(Arc::ptr_eq(&ns1_a, &ns1_b));
         assert!(Arc::ptr_eq(&ns2_a, &ns2_b));
+
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .namespaces()
+            .soft_delete("ns2")
+            .await
+            .unwrap();
+        db.sync().await;
+        assert_eq!(ns_names(&db), vec![Arc::from("ns1"), Arc::from("ns3")]);
+        assert!(db.namespace("ns2").is_none());
+        let ns1_c = db.namespace("ns1").unwrap();
+        assert!(Arc::ptr_eq(&ns1_a, &ns1_c));
     }
 
     fn ns_names(db: &QuerierDatabase) -> Vec<Arc<str>> {
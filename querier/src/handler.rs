@@ -123,7 +123,7 @@ mod tests {
     use query::exec::Executor;
     use time::{MockProvider, Time};
 
-    use crate::poison::PoisonPill;
+    use crate::{ingester::create_ingester_connection_for_testing, poison::PoisonPill};
 
     use super::*;
 
@@ -179,8 +179,10 @@ mod tests {
                 catalog,
                 metric_registry,
                 object_store,
+                None,
                 time_provider,
                 exec,
+                create_ingester_connection_for_testing(),
             ));
             let querier = QuerierHandlerImpl::new(database);
 
@@ -0,0 +1,701 @@
+//! Access to the unpersisted (in-memory) data held by the ingesters.
+use crate::flight::{Client as FlightClient, Error as FlightError};
+use arrow::{
+    array::{new_null_array, ArrayRef},
+    compute::cast,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use client_util::connection;
+use data_types2::{
+    ChunkAddr, ChunkId, ChunkOrder, DeletePredicate, IngesterQueryRequest, SequenceNumber,
+    SequencerId, TableSummary,
+};
+use datafusion::physical_plan::{
+    common::SizedRecordBatchStream,
+    metrics::{ExecutionPlanMetricsSet, MemTrackingMetrics},
+    SendableRecordBatchStream,
+};
+use futures::future::join_all;
+use observability_deps::tracing::{debug, trace, warn};
+use predicate::{Predicate, PredicateMatch};
+use query::{
+    exec::{stringset::StringSet, IOxSessionContext},
+    QueryChunk, QueryChunkError, QueryChunkMeta,
+};
+use schema::{selection::Selection, sort::SortKey, Schema};
+use snafu::{ResultExt, Snafu};
+use std::{any::Any, sync::Arc};
+
+#[cfg(test)]
+pub(crate) mod test_util;
+
+#[derive(Debug, Snafu)]
+#[allow(missing_copy_implementations, missing_docs)]
+pub enum Error {
+    #[snafu(display("Failed to connect to ingester '{}': {}", ingester_address, source))]
+    Connecting {
+        ingester_address: String,
+        source: connection::Error,
+    },
+
+    #[snafu(display(
+        "Failed to perform flight request to ingester '{}' for sequencer {}: {}",
+        ingester_address,
+        sequencer_id.get(),
+        source
+    ))]
+    RemoteQuery {
+        ingester_address: String,
+        sequencer_id: SequencerId,
+        source: FlightError,
+    },
+
+    #[snafu(display(
+        "Failed to read record batches from ingester '{}' for sequencer {}: {}",
+        ingester_address,
+        sequencer_id.get(),
+        source
+    ))]
+    ReadBatches {
+        ingester_address: String,
+        sequencer_id: SequencerId,
+        source: FlightError,
+    },
+
+    #[snafu(display(
+        "Ingester '{}' returned data for sequencer {} that does not fit the table schema: {}",
+        ingester_address,
+        sequencer_id.get(),
+        source
+    ))]
+    InvalidBatch {
+        ingester_address: String,
+        sequencer_id: SequencerId,
+        source: arrow::error::ArrowError,
+    },
+}
+
+/// A specialized `Error` for ingester access.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Create a new connection given the ingester addresses.
+pub fn create_ingester_connection(ingester_addresses: Vec<String>) -> Arc<dyn IngesterConnection> {
+    Arc::new(IngesterConnectionImpl::new(ingester_addresses))
+}
+
+/// Create a new ingester connection that never returns any data.
+///
+/// This is used when no ingester addresses are configured.
+pub fn create_ingester_connection_for_testing() -> Arc<dyn IngesterConnection> {
+    Arc::new(IngesterConnectionImpl::new(vec![]))
+}
+
+/// Dynamic error type that is used by [`IngesterConnection`] users.
+pub type DynIngesterError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Handles communicating with the ingester(s) to retrieve data that is not yet persisted.
+#[async_trait]
+pub trait IngesterConnection: std::fmt::Debug + Send + Sync + 'static {
+    /// Returns all partitions of the given table that the ingesters hold for the given sequencers.
+    ///
+    /// The `expected_schema` is the querier's view of the table schema. The data sent by the ingesters is projected
+    /// and casted to this schema.
+    ///
+    /// Ingesters that cannot be reached are skipped, so the result may lack their unpersisted data. Any other error
+    /// fails the request.
+    async fn partitions(
+        &self,
+        sequencer_ids: &[SequencerId],
+        namespace_name: Arc<str>,
+        table_name: Arc<str>,
+        columns: Vec<String>,
+        predicate: &Predicate,
+        expected_schema: Arc<Schema>,
+    ) -> Result<Vec<Arc<IngesterPartition>>, DynIngesterError>;
+
+    /// Return backend as [`Any`] which can be used to downcast to a specific implementation.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// IngesterConnection that communicates with the ingesters via the Arrow Flight gRPC API.
+#[derive(Debug)]
+pub struct IngesterConnectionImpl {
+    ingester_addresses: Vec<Arc<str>>,
+}
+
+impl IngesterConnectionImpl {
+    /// Create a new connection given the ingester addresses.
+    ///
+    /// Every ingester is asked for every sequencer of a namespace, so an ingester that does not own a sequencer
+    /// simply answers with "not found".
+    pub fn new(ingester_addresses: Vec<String>) -> Self {
+        Self {
+            ingester_addresses: ingester_addresses.into_iter().map(Arc::from).collect(),
+        }
+    }
+}
+
+/// Struct that names all parameters to [`execute`].
+#[derive(Debug, Clone)]
+struct GetPartitionForIngester<'a> {
+    ingester_address: Arc<str>,
+    sequencer_id: SequencerId,
+    namespace_name: Arc<str>,
+    table_name: Arc<str>,
+    columns: Vec<String>,
+    predicate: &'a Predicate,
+    expected_schema: Arc<Schema>,
+}
+
+/// Fetches the partition data of a single sequencer from a single ingester.
+///
+/// Returns `None` if the ingester does not hold any data for the given sequencer and table.
+async fn execute(request: GetPartitionForIngester<'_>) -> Result<Option<Arc<IngesterPartition>>> {
+    let GetPartitionForIngester {
+        ingester_address,
+        sequencer_id,
+        namespace_name,
+        table_name,
+        columns,
+        predicate,
+        expected_schema,
+    } = request;
+
+    // TODO: pool connections instead of creating a new one for every request
+    let connection = connection::Builder::default()
+        .build(ingester_address.as_ref())
+        .await
+        .context(ConnectingSnafu {
+            ingester_address: ingester_address.as_ref(),
+        })?;
+    let mut client = FlightClient::new(connection);
+
+    let ingester_query_request = IngesterQueryRequest::new(
+        namespace_name.to_string(),
+        sequencer_id,
+        table_name.to_string(),
+        columns,
+        Some(predicate.clone()),
+    );
+
+    let query_res = client.perform_query(ingester_query_request).await;
+    let mut perform_query = match query_res {
+        Ok(perform_query) => perform_query,
+        Err(FlightError::GrpcError(status)) if status.code() == tonic::Code::NotFound => {
+            debug!(
+                ingester_address = ingester_address.as_ref(),
+                sequencer_id = sequencer_id.get(),
+                namespace = namespace_name.as_ref(),
+                table = table_name.as_ref(),
+                "Ingester does not know namespace or table, skipping",
+            );
+            return Ok(None);
+        }
+        Err(e) => {
+            return Err(e).context(RemoteQuerySnafu {
+                ingester_address: ingester_address.as_ref(),
+                sequencer_id,
+            });
+        }
+    };
+
+    let batches = perform_query.collect().await.context(ReadBatchesSnafu {
+        ingester_address: ingester_address.as_ref(),
+        sequencer_id,
+    })?;
+    let batches: Vec<_> = batches
+        .into_iter()
+        .filter(|batch| batch.num_rows() > 0)
+        .collect();
+    if batches.is_empty() {
+        return Ok(None);
+    }
+
+    let partition = IngesterPartition::try_new(
+        ChunkId::new(),
+        namespace_name,
+        table_name,
+        sequencer_id,
+        perform_query.parquet_max_sequence_number,
+        perform_query.tombstone_max_sequence_number,
+        expected_schema,
+        batches,
+    )
+    .context(InvalidBatchSnafu {
+        ingester_address: ingester_address.as_ref(),
+        sequencer_id,
+    })?;
+
+    Ok(Some(Arc::new(partition)))
+}
+
+#[async_trait]
+impl IngesterConnection for IngesterConnectionImpl {
+    async fn partitions(
+        &self,
+        sequencer_ids: &[SequencerId],
+        namespace_name: Arc<str>,
+        table_name: Arc<str>,
+        columns: Vec<String>,
+        predicate: &Predicate,
+        expected_schema: Arc<Schema>,
+    ) -> Result<Vec<Arc<IngesterPartition>>, DynIngesterError> {
+        let requests = self.ingester_addresses.iter().flat_map(|ingester_address| {
+            sequencer_ids
+                .iter()
+                .map(|sequencer_id| GetPartitionForIngester {
+                    ingester_address: Arc::clone(ingester_address),
+                    sequencer_id: *sequencer_id,
+                    namespace_name: Arc::clone(&namespace_name),
+                    table_name: Arc::clone(&table_name),
+                    columns: columns.clone(),
+                    predicate,
+                    expected_schema: Arc::clone(&expected_schema),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut partitions = vec![];
+        for result in join_all(requests.map(execute)).await {
+            match result {
+                Ok(partition) => partitions.extend(partition),
+                // An unreachable ingester must not fail the whole query. Its unpersisted data is missing from the
+                // result until the ingester is back.
+                Err(e @ Error::Connecting { .. }) => {
+                    warn!(
+                        %e,
+                        namespace = namespace_name.as_ref(),
+                        table = table_name.as_ref(),
+                        "Ingester unavailable, querying without its unpersisted data",
+                    );
+                }
+                Err(e) => return Err(Box::new(e)),
+            }
+        }
+
+        Ok(partitions)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+}
+
+/// A wrapper around the unpersisted data of a table that an ingester holds for a single sequencer.
+///
+/// The data is already deduplicated by the ingester per partition, however the record batches of different partitions
+/// are combined, so this chunk is treated as if it may contain duplicates.
+#[derive(Debug, Clone)]
+pub struct IngesterPartition {
+    chunk_id: ChunkId,
+    namespace_name: Arc<str>,
+    table_name: Arc<str>,
+    sequencer_id: SequencerId,
+
+    /// Maximum sequence number of parquet files the ingester has persisted for this table.
+    parquet_max_sequence_number: Option<SequenceNumber>,
+
+    /// Maximum sequence number of tombstones that the ingester has already applied to this data.
+    tombstone_max_sequence_number: Option<SequenceNumber>,
+
+    /// Schema of this partition, a projection of the table schema to the columns sent by the ingester.
+    schema: Arc<Schema>,
+
+    /// Data, already projected and casted to `schema`.
+    batches: Vec<Arc<RecordBatch>>,
+
+    /// Delete predicates that the querier applies on top of the data sent by the ingester.
+    delete_predicates: Vec<Arc<DeletePredicate>>,
+}
+
+impl IngesterPartition {
+    /// Creates a new partition from the record batches sent by an ingester.
+    ///
+    /// The batches are projected to the columns of `expected_schema` that they contain and casted to the respective
+    /// data types (the ingester for example sends tags as plain strings instead of dictionaries).
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new(
+        chunk_id: ChunkId,
+        namespace_name: Arc<str>,
+        table_name: Arc<str>,
+        sequencer_id: SequencerId,
+        parquet_max_sequence_number: Option<SequenceNumber>,
+        tombstone_max_sequence_number: Option<SequenceNumber>,
+        expected_schema: Arc<Schema>,
+        batches: Vec<RecordBatch>,
+    ) -> Result<Self, arrow::error::ArrowError> {
+        // only keep the columns that the ingester actually sent
+        let column_indices: Vec<usize> = expected_schema
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (_, field))| {
+                batches
+                    .iter()
+                    .any(|batch| batch.schema().column_with_name(field.name()).is_some())
+                    .then(|| idx)
+            })
+            .collect();
+        let schema = Arc::new(expected_schema.select_by_indices(&column_indices));
+
+        let batches = batches
+            .iter()
+            .map(|batch| ensure_schema(batch, &schema).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            chunk_id,
+            namespace_name,
+            table_name,
+            sequencer_id,
+            parquet_max_sequence_number,
+            tombstone_max_sequence_number,
+            schema,
+            batches,
+            delete_predicates: vec![],
+        })
+    }
+
+    /// Set delete predicates that are applied by the querier when this partition is scanned.
+    ///
+    /// Tombstones are already applied by the ingester (up to `tombstone_max_sequence_number`), so this is only used
+    /// for predicates that the ingester is unaware of, like the retention period of the namespace.
+    pub fn with_delete_predicates(self, delete_predicates: Vec<Arc<DeletePredicate>>) -> Self {
+        Self {
+            delete_predicates,
+            ..self
+        }
+    }
+
+    /// Sequencer that this data belongs to.
+    pub fn sequencer_id(&self) -> SequencerId {
+        self.sequencer_id
+    }
+
+    /// Maximum sequence number of parquet files the ingester has persisted for this table.
+    pub fn parquet_max_sequence_number(&self) -> Option<SequenceNumber> {
+        self.parquet_max_sequence_number
+    }
+
+    /// Maximum sequence number of tombstones that the ingester has already applied to this data.
+    pub fn tombstone_max_sequence_number(&self) -> Option<SequenceNumber> {
+        self.tombstone_max_sequence_number
+    }
+}
+
+/// Projects and casts `batch` to the given `schema`, filling columns missing from the batch with NULLs.
+fn ensure_schema(
+    batch: &RecordBatch,
+    schema: &Schema,
+) -> Result<RecordBatch, arrow::error::ArrowError> {
+    let columns = schema
+        .iter()
+        .map(|(_, field)| {
+            let array: ArrayRef = match batch.schema().index_of(field.name()) {
+                Ok(idx) => {
+                    let array = batch.column(idx);
+                    if array.data_type() == field.data_type() {
+                        Arc::clone(array)
+                    } else {
+                        cast(array, field.data_type())?
+                    }
+                }
+                Err(_) => new_null_array(field.data_type(), batch.num_rows()),
+            };
+            Ok(array)
+        })
+        .collect::<Result<Vec<_>, arrow::error::ArrowError>>()?;
+
+    RecordBatch::try_new(schema.as_arrow(), columns)
+}
+
+impl QueryChunkMeta for IngesterPartition {
+    fn summary(&self) -> Option<&TableSummary> {
+        None
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.schema)
+    }
+
+    fn sort_key(&self) -> Option<&SortKey> {
+        None
+    }
+
+    fn delete_predicates(&self) -> &[Arc<DeletePredicate>] {
+        // the ingester already applied all tombstones up to `tombstone_max_sequence_number`, so this only contains
+        // predicates added by the querier
+        &self.delete_predicates
+    }
+}
+
+impl QueryChunk for IngesterPartition {
+    fn id(&self) -> ChunkId {
+        self.chunk_id
+    }
+
+    fn addr(&self) -> ChunkAddr {
+        ChunkAddr {
+            db_name: Arc::clone(&self.namespace_name),
+            table_name: Arc::clone(&self.table_name),
+            partition_key: Arc::from(format!("{}-ingester", self.sequencer_id.get())),
+            chunk_id: self.chunk_id,
+        }
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_ref()
+    }
+
+    fn may_contain_pk_duplicates(&self) -> bool {
+        // the ingester combines the data of multiple partitions and persisting batches
+        true
+    }
+
+    fn apply_predicate_to_metadata(
+        &self,
+        _predicate: &Predicate,
+    ) -> Result<PredicateMatch, QueryChunkError> {
+        // the ingester already applied the predicate
+        Ok(PredicateMatch::Unknown)
+    }
+
+    fn column_names(
+        &self,
+        _ctx: IOxSessionContext,
+        predicate: &Predicate,
+        columns: Selection<'_>,
+    ) -> Result<Option<StringSet>, QueryChunkError> {
+        if !predicate.is_empty() || !self.delete_predicates.is_empty() {
+            // if there is anything in the predicate, bail for now and force a full plan
+            return Ok(None);
+        }
+
+        // the data is in memory, so the answer is exact: a column is present if it has at least one non-null value
+        let selected = self.schema.select(columns).map_err(|e| Box::new(e) as _)?;
+        let names = selected
+            .iter()
+            .map(|(_, field)| field.name())
+            .filter(|name| {
+                self.batches
+                    .iter()
+                    .any(|batch| match batch.schema().index_of(name) {
+                        Ok(idx) => batch.column(idx).null_count() < batch.num_rows(),
+                        Err(_) => false,
+                    })
+            })
+            .cloned()
+            .collect();
+        Ok(Some(names))
+    }
+
+    fn column_values(
+        &self,
+        _ctx: IOxSessionContext,
+        _column_name: &str,
+        _predicate: &Predicate,
+    ) -> Result<Option<StringSet>, QueryChunkError> {
+        // There is no advantage to manually implementing this vs just letting DataFusion do its thing
+        Ok(None)
+    }
+
+    fn read_filter(
+        &self,
+        mut ctx: IOxSessionContext,
+        _predicate: &Predicate,
+        selection: Selection<'_>,
+    ) -> Result<SendableRecordBatchStream, QueryChunkError> {
+        ctx.set_metadata("storage", "ingester");
+        ctx.set_metadata("projection", format!("{}", selection));
+        trace!(?selection, "selection");
+
+        let schema = self
+            .schema
+            .select(selection)
+            .map_err(|e| Box::new(e) as _)?;
+        let batches = self
+            .batches
+            .iter()
+            .map(|batch| ensure_schema(batch, &schema).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Box::new(e) as _)?;
+
+        // Return stream of data
+        let dummy_metrics = ExecutionPlanMetricsSet::new();
+        let mem_metrics = MemTrackingMetrics::new(&dummy_metrics, 0);
+        let stream = SizedRecordBatchStream::new(schema.as_arrow(), batches, mem_metrics);
+        Ok(Box::pin(stream))
+    }
+
+    fn chunk_type(&self) -> &str {
+        "IngesterPartition"
+    }
+
+    fn order(&self) -> ChunkOrder {
+        // Unpersisted data is always newer than any persisted data of the same partition, so it must win during
+        // deduplication against overlapping parquet files.
+        ChunkOrder::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{Int64Array, StringArray, TimestampNanosecondArray},
+        datatypes::{DataType, Field, Schema as ArrowSchema, TimeUnit},
+    };
+    use arrow_util::assert_batches_eq;
+    use futures::StreamExt;
+    use schema::builder::SchemaBuilder;
+
+    #[tokio::test]
+    async fn test_ingester_partition_casts_and_projects() {
+        let expected_schema = Arc::new(
+            SchemaBuilder::new()
+                .tag("tag1")
+                .tag("tag2")
+                .influx_field("field_int", schema::InfluxFieldType::Integer)
+                .timestamp()
+                .build()
+                .unwrap(),
+        );
+
+        // the ingester hydrates dictionaries and only sends the columns it knows about
+        let arrow_schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("tag1", DataType::Utf8, true),
+            Field::new("field_int", DataType::Int64, true),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            arrow_schema,
+            vec![
+                Arc::new(StringArray::from(vec!["A", "B"])),
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(TimestampNanosecondArray::from(vec![10, 20])),
+            ],
+        )
+        .unwrap();
+
+        let partition = IngesterPartition::try_new(
+            ChunkId::new_test(1),
+            Arc::from("ns"),
+            Arc::from("table"),
+            SequencerId::new(1),
+            None,
+            None,
+            expected_schema,
+            vec![batch],
+        )
+        .unwrap();
+
+        assert_eq!(
+            partition
+                .schema()
+                .iter()
+                .map(|(_, f)| f.name().clone())
+                .collect::<Vec<_>>(),
+            vec![
+                String::from("tag1"),
+                String::from("field_int"),
+                String::from("time")
+            ],
+        );
+        assert_eq!(partition.order(), ChunkOrder::MAX);
+        assert!(partition.may_contain_pk_duplicates());
+
+        let batches: Vec<_> = partition
+            .read_filter(
+                IOxSessionContext::default(),
+                &Default::default(),
+                Selection::All,
+            )
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            batches[0].schema().field(0).data_type(),
+            &DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+        );
+        assert_batches_eq!(
+            &[
+                "+------+-----------+--------------------------------+",
+                "| tag1 | field_int | time                           |",
+                "+------+-----------+--------------------------------+",
+                "| A    | 1         | 1970-01-01T00:00:00.000000010Z |",
+                "| B    | 2         | 1970-01-01T00:00:00.000000020Z |",
+                "+------+-----------+--------------------------------+",
+            ],
+            &batches
+        );
+
+        let names = partition
+            .column_names(
+                IOxSessionContext::default(),
+                &Default::default(),
+                Selection::Some(&["tag1", "time"]),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![String::from("tag1"), String::from("time")],
+        );
+
+        // delete predicates may remove rows, so the names cannot be answered from the data alone
+        let partition = partition.with_delete_predicates(vec![Arc::new(DeletePredicate {
+            range: data_types2::TimestampRange::new(0, 15),
+            exprs: vec![],
+        })]);
+        assert!(partition
+            .column_names(
+                IOxSessionContext::default(),
+                &Default::default(),
+                Selection::All,
+            )
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_ingester() {
+        // nothing listens on this port
+        let connection = create_ingester_connection(vec!["http://127.0.0.1:1".to_string()]);
+        let partitions = connection
+            .partitions(
+                &[SequencerId::new(1)],
+                Arc::from("ns"),
+                Arc::from("table"),
+                vec![],
+                &Default::default(),
+                Arc::new(SchemaBuilder::new().build().unwrap()),
+            )
+            .await
+            .unwrap();
+        assert!(partitions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_ingesters() {
+        let connection = create_ingester_connection_for_testing();
+        let partitions = connection
+            .partitions(
+                &[SequencerId::new(1)],
+                Arc::from("ns"),
+                Arc::from("table"),
+                vec![],
+                &Default::default(),
+                Arc::new(SchemaBuilder::new().build().unwrap()),
+            )
+            .await
+            .unwrap();
+        assert!(partitions.is_empty());
+    }
+}
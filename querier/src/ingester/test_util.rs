@@ -0,0 +1,48 @@
+use super::IngesterConnection;
+use async_trait::async_trait;
+use data_types2::SequencerId;
+use parking_lot::Mutex;
+use predicate::Predicate;
+use schema::Schema;
+use std::{any::Any, sync::Arc};
+
+/// IngesterConnection for testing
+#[derive(Debug, Default)]
+pub struct MockIngesterConnection {
+    next_response: Mutex<Option<super::Result<Vec<Arc<super::IngesterPartition>>>>>,
+}
+
+impl MockIngesterConnection {
+    /// Create connection w/ an empty response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set next response for this connection.
+    pub fn next_response(&self, response: super::Result<Vec<Arc<super::IngesterPartition>>>) {
+        *self.next_response.lock() = Some(response);
+    }
+}
+
+#[async_trait]
+impl IngesterConnection for MockIngesterConnection {
+    async fn partitions(
+        &self,
+        _sequencer_ids: &[SequencerId],
+        _namespace_name: Arc<str>,
+        _table_name: Arc<str>,
+        _columns: Vec<String>,
+        _predicate: &Predicate,
+        _expected_schema: Arc<Schema>,
+    ) -> Result<Vec<Arc<super::IngesterPartition>>, super::DynIngesterError> {
+        self.next_response
+            .lock()
+            .take()
+            .unwrap_or_else(|| Ok(vec![]))
+            .map_err(|e| Box::new(e) as _)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+}
@@ -20,6 +20,7 @@ pub mod database;
 /// Flight client to the ingester to request in-memory data.
 pub mod flight;
 pub mod handler;
+pub mod ingester;
 pub mod namespace;
 mod poison;
 pub mod server;
@@ -1,9 +1,12 @@
 //! Namespace within the whole database.
-use crate::{cache::CatalogCache, chunk::ParquetChunkAdapter, table::QuerierTable};
+use crate::{
+    cache::CatalogCache, chunk::ParquetChunkAdapter, ingester::IngesterConnection,
+    table::QuerierTable,
+};
 use backoff::{Backoff, BackoffConfig};
-use data_types2::NamespaceId;
+use data_types2::{NamespaceId, SequencerId};
 use iox_catalog::interface::{get_schema_by_name, Catalog};
-use object_store::DynObjectStore;
+use object_store::{DynObjectStore, ObjectStoreFileCache};
 use observability_deps::tracing::warn;
 use parking_lot::RwLock;
 use query::exec::Executor;
@@ -48,20 +51,26 @@ pub struct QuerierNamespace {
 
     /// Executor for queries.
     exec: Arc<Executor>,
+
+    /// Connection to ingester
+    ingester_connection: Arc<dyn IngesterConnection>,
 }
 
 impl QuerierNamespace {
     /// Create new, empty namespace.
     ///
     /// You may call [`sync`](Self::sync) to fill the namespace with chunks.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         catalog_cache: Arc<CatalogCache>,
         name: Arc<str>,
         id: NamespaceId,
         metric_registry: Arc<metric::Registry>,
         object_store: Arc<DynObjectStore>,
+        object_store_cache: Option<Arc<ObjectStoreFileCache>>,
         time_provider: Arc<dyn TimeProvider>,
         exec: Arc<Executor>,
+        ingester_connection: Arc<dyn IngesterConnection>,
     ) -> Self {
         let catalog = catalog_cache.catalog();
 
@@ -73,12 +82,14 @@ impl QuerierNamespace {
             chunk_adapter: Arc::new(ParquetChunkAdapter::new(
                 catalog_cache,
                 object_store,
+                object_store_cache,
                 metric_registry,
                 time_provider,
             )),
             id,
             name,
             exec,
+            ingester_connection,
         }
     }
 
@@ -92,6 +103,7 @@ impl QuerierNamespace {
     /// This includes:
     /// - tables
     /// - schemas
+    /// - sequencers (used to query the ingesters)
     ///
     /// Chunks and tombstones are queried on-demand.
     ///
@@ -119,6 +131,19 @@ impl QuerierNamespace {
             }
         };
 
+        let kafka_topic_id = catalog_schema_desired.kafka_topic_id;
+        let retention_period = catalog_schema_desired.retention_period;
+        let sequencer_ids: Arc<[SequencerId]> = Backoff::new(&self.backoff_config)
+            .retry_all_errors("get sequencers", || async {
+                self.catalog.repositories().await.sequencers().list().await
+            })
+            .await
+            .expect("retry forever")
+            .into_iter()
+            .filter(|sequencer| sequencer.kafka_topic_id == kafka_topic_id)
+            .map(|sequencer| sequencer.id)
+            .collect();
+
         let tables: HashMap<_, _> = catalog_schema_desired
             .tables
             .into_iter()
@@ -128,9 +153,13 @@ impl QuerierNamespace {
                 let schema = Schema::try_from(table_schema).expect("cannot build schema");
 
                 let table = Arc::new(QuerierTable::new(
+                    Arc::clone(&self.name),
                     id,
                     Arc::clone(&name),
                     Arc::new(schema),
+                    retention_period,
+                    Arc::clone(&sequencer_ids),
+                    Arc::clone(&self.ingester_connection),
                     Arc::clone(&self.chunk_adapter),
                 ));
 
@@ -145,7 +174,9 @@ impl QuerierNamespace {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::namespace::test_util::querier_namespace;
+    use crate::{
+        ingester::create_ingester_connection_for_testing, namespace::test_util::querier_namespace,
+    };
     use data_types2::ColumnType;
     use iox_tests::util::TestCatalog;
     use schema::{builder::SchemaBuilder, InfluxColumnType, InfluxFieldType};
@@ -163,8 +194,10 @@ mod tests {
             NamespaceId::new(1),
             catalog.metric_registry(),
             catalog.object_store(),
+            None,
             catalog.time_provider(),
             catalog.exec(),
+            create_ingester_connection_for_testing(),
         );
 
         // The container (`QuerierDatabase`) should prune the namespace if it's gone, however the `sync` might still be
@@ -184,7 +217,7 @@ mod tests {
         assert_eq!(tables(&querier_namespace), Vec::<String>::new());
 
         ns.create_table("table1").await;
-        ns.create_table("table2").await;
+        let table2 = ns.create_table("table2").await;
         querier_namespace.sync().await;
         assert_eq!(
             tables(&querier_namespace),
@@ -201,6 +234,20 @@ mod tests {
                 String::from("table3")
             ]
         );
+
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .tables()
+            .soft_delete(table2.table.id)
+            .await
+            .unwrap();
+        querier_namespace.sync().await;
+        assert_eq!(
+            tables(&querier_namespace),
+            vec![String::from("table1"), String::from("table3")]
+        );
     }
 
     #[tokio::test]
@@ -5,6 +5,7 @@ use datafusion::{
     catalog::{catalog::CatalogProvider, schema::SchemaProvider},
     datasource::TableProvider,
 };
+use observability_deps::tracing::error;
 use predicate::{rpc_predicate::QueryDatabaseMeta, Predicate};
 use query::{
     exec::{ExecutionContextProvider, ExecutorType, IOxSessionContext},
@@ -42,7 +43,20 @@ impl QueryDatabase for QuerierNamespace {
             }
         };
 
-        let mut chunks = table.chunks().await;
+        // `QueryDatabase::chunks` cannot report errors, so fall back to the persisted data if the ingesters cannot be
+        // queried
+        let mut chunks = match table.chunks(predicate).await {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                error!(
+                    %e,
+                    namespace = self.name.as_ref(),
+                    table_name,
+                    "Cannot get unpersisted data from ingesters, only querying persisted data",
+                );
+                table.persisted_chunks().await
+            }
+        };
 
         // if there is a field restriction on the predicate, only
         // chunks with that field should be returned. If the chunk has
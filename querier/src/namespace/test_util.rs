@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::cache::CatalogCache;
+use crate::{cache::CatalogCache, ingester::create_ingester_connection_for_testing};
 use iox_tests::util::{TestCatalog, TestNamespace};
 
 use super::QuerierNamespace;
@@ -15,7 +15,9 @@ pub fn querier_namespace(catalog: &Arc<TestCatalog>, ns: &Arc<TestNamespace>) ->
         ns.namespace.id,
         catalog.metric_registry(),
         catalog.object_store(),
+        None,
         catalog.time_provider(),
         catalog.exec(),
+        create_ingester_connection_for_testing(),
     )
 }
@@ -1,11 +1,17 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use backoff::{Backoff, BackoffConfig};
-use data_types2::TableId;
+use data_types2::{DeletePredicate, SequencerId, TableId, TimestampRange};
+use predicate::Predicate;
 use query::{provider::ChunkPruner, QueryChunk};
 use schema::Schema;
+use snafu::{ResultExt, Snafu};
 
-use crate::{chunk::ParquetChunkAdapter, tombstone::QuerierTombstone};
+use crate::{
+    chunk::ParquetChunkAdapter,
+    ingester::{self, IngesterConnection},
+    tombstone::QuerierTombstone,
+};
 
 use self::query_access::QuerierTableChunkPruner;
 
@@ -14,27 +20,62 @@ mod query_access;
 #[cfg(test)]
 mod test_util;
 
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error getting partitions from ingester: {}", source))]
+    GettingIngesterPartitions { source: ingester::DynIngesterError },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
 /// Table representation for the querier.
 #[derive(Debug)]
 pub struct QuerierTable {
+    /// Namespace the table is in
+    namespace_name: Arc<str>,
+
+    /// Table name.
     name: Arc<str>,
+
+    /// Table ID.
     id: TableId,
+
+    /// Table schema.
     schema: Arc<Schema>,
+
+    /// Retention period of the namespace, `None` if data is retained forever.
+    retention_period: Option<Duration>,
+
+    /// Sequencers that the namespace of this table is assigned to.
+    sequencer_ids: Arc<[SequencerId]>,
+
+    /// Connection to ingester
+    ingester_connection: Arc<dyn IngesterConnection>,
+
+    /// Interface to create chunks for this table.
     chunk_adapter: Arc<ParquetChunkAdapter>,
 }
 
 impl QuerierTable {
     /// Create new table.
     pub fn new(
+        namespace_name: Arc<str>,
         id: TableId,
         name: Arc<str>,
         schema: Arc<Schema>,
+        retention_period: Option<Duration>,
+        sequencer_ids: Arc<[SequencerId]>,
+        ingester_connection: Arc<dyn IngesterConnection>,
         chunk_adapter: Arc<ParquetChunkAdapter>,
     ) -> Self {
         Self {
+            namespace_name,
             name,
             id,
             schema,
+            retention_period,
+            sequencer_ids,
+            ingester_connection,
             chunk_adapter,
         }
     }
@@ -56,8 +97,85 @@ impl QuerierTable {
 
     /// Query all chunks within this table.
     ///
-    /// This currently contains all parquet files linked to their unprocessed tombstones.
-    pub async fn chunks(&self) -> Vec<Arc<dyn QueryChunk>> {
+    /// This contains all parquet files linked to their unprocessed tombstones as well as the unpersisted data that the
+    /// ingesters hold for this table.
+    ///
+    /// Data older than the retention period of the namespace is filtered out.
+    pub async fn chunks(&self, predicate: &Predicate) -> Result<Vec<Arc<dyn QueryChunk>>> {
+        let retention_cutoff = self.retention_cutoff();
+        let mut chunks = self.parquet_chunks(retention_cutoff).await;
+        chunks.extend(
+            self.ingester_partitions(predicate, retention_cutoff)
+                .await?,
+        );
+        Ok(chunks)
+    }
+
+    /// Query the persisted chunks of this table, i.e. [`chunks`](Self::chunks) without the unpersisted data of the
+    /// ingesters.
+    ///
+    /// This is the fallback for callers that cannot report an error if the ingesters cannot be queried.
+    pub async fn persisted_chunks(&self) -> Vec<Arc<dyn QueryChunk>> {
+        self.parquet_chunks(self.retention_cutoff()).await
+    }
+
+    /// Oldest timestamp (in nanoseconds since the epoch) that is still within the retention period of the
+    /// namespace, or `None` if no data has expired (e.g. because the namespace retains data forever).
+    fn retention_cutoff(&self) -> Option<i64> {
+        let retention_period = self.retention_period?;
+        self.chunk_adapter
+            .time_provider()
+            .now()
+            .checked_sub(retention_period)
+            .map(|t| t.timestamp_nanos())
+    }
+
+    /// Get partitions from ingesters.
+    async fn ingester_partitions(
+        &self,
+        predicate: &Predicate,
+        retention_cutoff: Option<i64>,
+    ) -> Result<Vec<Arc<dyn QueryChunk>>> {
+        // For now, ask for *all* columns in the table from the ingester (need
+        // at least all pk (time, tag) columns for deduplication.
+        //
+        // As a future optimization, might be able to fetch only
+        // fields that are needed in query
+        let columns: Vec<String> = self
+            .schema
+            .iter()
+            .map(|(_, field)| field.name().to_string())
+            .collect();
+
+        let partitions = self
+            .ingester_connection
+            .partitions(
+                &self.sequencer_ids,
+                Arc::clone(&self.namespace_name),
+                Arc::clone(&self.name),
+                columns,
+                predicate,
+                Arc::clone(&self.schema),
+            )
+            .await
+            .context(GettingIngesterPartitionsSnafu)?;
+
+        Ok(partitions
+            .into_iter()
+            .map(|partition| match retention_cutoff {
+                Some(cutoff) => Arc::new(
+                    partition
+                        .as_ref()
+                        .clone()
+                        .with_delete_predicates(vec![retention_delete_predicate(cutoff)]),
+                ) as Arc<dyn QueryChunk>,
+                None => partition as Arc<dyn QueryChunk>,
+            })
+            .collect())
+    }
+
+    /// Get parquet-backed chunks from the catalog.
+    async fn parquet_chunks(&self, retention_cutoff: Option<i64>) -> Vec<Arc<dyn QueryChunk>> {
         // get parquet files and tombstones in a single catalog transaction
         // TODO: figure out some form of caching
         let backoff_config = BackoffConfig::default();
@@ -85,8 +203,16 @@ impl QuerierTable {
         // convert parquet files and tombstones to nicer objects
         let mut chunks = Vec::with_capacity(parquet_files.len());
         for parquet_file in parquet_files {
+            // files that only contain expired data are skipped entirely, files that partially overlap the retention
+            // period are filtered during the scan
+            let needs_retention_filter = match retention_cutoff {
+                Some(cutoff) if parquet_file.max_time.get() < cutoff => continue,
+                Some(cutoff) => parquet_file.min_time.get() < cutoff,
+                None => false,
+            };
+
             if let Some(chunk) = self.chunk_adapter.new_querier_chunk(parquet_file).await {
-                chunks.push(chunk);
+                chunks.push((chunk, needs_retention_filter));
             }
         }
         let querier_tombstones: Vec<_> =
@@ -101,11 +227,10 @@ impl QuerierTable {
                 .push(tombstone);
         }
         let mut chunks2 = Vec::with_capacity(chunks.len());
-        for chunk in chunks.into_iter() {
-            let chunk = if let Some(tombstones) =
-                tombstones_by_sequencer.get(&chunk.meta().sequencer_id())
-            {
-                let mut delete_predicates = Vec::with_capacity(tombstones.len());
+        for (chunk, needs_retention_filter) in chunks.into_iter() {
+            let mut delete_predicates = vec![];
+
+            if let Some(tombstones) = tombstones_by_sequencer.get(&chunk.meta().sequencer_id()) {
                 for tombstone in tombstones {
                     // check conditions that don't need catalog access first to avoid unnecessary catalog load
 
@@ -134,9 +259,16 @@ impl QuerierTable {
 
                     delete_predicates.push(Arc::clone(tombstone.delete_predicate()));
                 }
-                chunk.with_delete_predicates(delete_predicates)
-            } else {
+            }
+
+            if let (true, Some(cutoff)) = (needs_retention_filter, retention_cutoff) {
+                delete_predicates.push(retention_delete_predicate(cutoff));
+            }
+
+            let chunk = if delete_predicates.is_empty() {
                 chunk
+            } else {
+                chunk.with_delete_predicates(delete_predicates)
             };
 
             chunks2.push(Arc::new(chunk) as Arc<dyn QueryChunk>);
@@ -151,12 +283,33 @@ impl QuerierTable {
     }
 }
 
+/// Delete predicate that removes all data older than `cutoff` (in nanoseconds since the epoch).
+///
+/// The range of a delete predicate is inclusive at both ends, so rows stamped exactly at `cutoff` must be excluded
+/// from it to stay visible.
+fn retention_delete_predicate(cutoff: i64) -> Arc<DeletePredicate> {
+    Arc::new(DeletePredicate {
+        range: TimestampRange::new(i64::MIN, cutoff.saturating_sub(1)),
+        exprs: vec![],
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use data_types2::{ChunkId, ColumnType};
+    use arrow::{
+        array::{Int64Array, TimestampNanosecondArray},
+        datatypes::{DataType, Field, Schema as ArrowSchema, TimeUnit},
+        record_batch::RecordBatch,
+    };
+    use data_types2::{ChunkId, ChunkOrder, ColumnType, SequenceNumber, SequencerId};
     use iox_tests::util::{now, TestCatalog};
 
-    use crate::table::test_util::querier_table;
+    use crate::{
+        ingester::{test_util::MockIngesterConnection, IngesterPartition},
+        table::test_util::{querier_table, querier_table_with_ingester},
+    };
+
+    use super::*;
 
     #[tokio::test]
     async fn test_chunks() {
@@ -189,7 +342,11 @@ mod tests {
         let querier_table = querier_table(&catalog, &table1).await;
 
         // no parquet files yet
-        assert!(querier_table.chunks().await.is_empty());
+        assert!(querier_table
+            .chunks(&Predicate::default())
+            .await
+            .unwrap()
+            .is_empty());
 
         let file111 = partition11
             .create_parquet_file_with_min_max(
@@ -258,7 +415,7 @@ mod tests {
         // this contains all files except for:
         // - file111: marked for delete
         // - file221: wrong table
-        let mut chunks = querier_table.chunks().await;
+        let mut chunks = querier_table.chunks(&Predicate::default()).await.unwrap();
         chunks.sort_by_key(|c| c.id());
         assert_eq!(chunks.len(), 4);
 
@@ -290,4 +447,150 @@ mod tests {
         // file121: wrong sequencer
         assert_eq!(chunks[3].delete_predicates().len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_chunks_with_ingester_partitions() {
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace("ns").await;
+        let table = ns.create_table("table").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let partition = table.with_sequencer(&sequencer).create_partition("k").await;
+
+        table.create_column("foo", ColumnType::I64).await;
+        table.create_column("time", ColumnType::Time).await;
+
+        partition
+            .create_parquet_file_with_min_max(
+                "table foo=1 11",
+                1,
+                2,
+                now().timestamp_nanos(),
+                now().timestamp_nanos(),
+            )
+            .await;
+
+        let ingester_connection = Arc::new(MockIngesterConnection::new());
+        let querier_table =
+            querier_table_with_ingester(&catalog, &table, Arc::clone(&ingester_connection)).await;
+
+        let arrow_schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("foo", DataType::Int64, true),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            arrow_schema,
+            vec![
+                Arc::new(Int64Array::from(vec![2])),
+                Arc::new(TimestampNanosecondArray::from(vec![22])),
+            ],
+        )
+        .unwrap();
+        let ingester_partition = IngesterPartition::try_new(
+            ChunkId::new_test(1000),
+            Arc::from("ns"),
+            Arc::from("table"),
+            SequencerId::new(1),
+            Some(SequenceNumber::new(2)),
+            None,
+            Arc::clone(querier_table.schema()),
+            vec![batch],
+        )
+        .unwrap();
+        ingester_connection.next_response(Ok(vec![Arc::new(ingester_partition)]));
+
+        let mut chunks = querier_table.chunks(&Predicate::default()).await.unwrap();
+        chunks.sort_by_key(|c| c.order());
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].chunk_type(), "parquet");
+        assert_eq!(chunks[1].chunk_type(), "IngesterPartition");
+
+        // ingester data must always win over persisted data
+        assert_eq!(chunks[1].order(), ChunkOrder::MAX);
+        assert!(chunks[0].order() < chunks[1].order());
+
+        // the fallback for unavailable ingesters only contains the persisted data
+        let chunks = querier_table.persisted_chunks().await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].chunk_type(), "parquet");
+    }
+
+    #[tokio::test]
+    async fn test_chunks_retention_period() {
+        let catalog = TestCatalog::new();
+        let hour = Duration::from_secs(60 * 60).as_nanos() as i64;
+        catalog
+            .mock_time_provider()
+            .set(time::Time::from_timestamp_nanos(10 * hour));
+
+        let ns = catalog.create_namespace_with_retention("ns", "1h").await;
+        let table = ns.create_table("table").await;
+        let sequencer = ns.create_sequencer(1).await;
+        let partition = table.with_sequencer(&sequencer).create_partition("k").await;
+
+        table.create_column("foo", ColumnType::I64).await;
+        table.create_column("time", ColumnType::Time).await;
+
+        // only contains expired data
+        partition
+            .create_parquet_file_with_min_max("table foo=1 11", 1, 2, hour, 2 * hour)
+            .await;
+        // partially expired
+        let file_partial = partition
+            .create_parquet_file_with_min_max("table foo=2 22", 3, 4, 8 * hour, 10 * hour)
+            .await;
+        // entirely within the retention period
+        let file_fresh = partition
+            .create_parquet_file_with_min_max("table foo=3 33", 5, 6, 10 * hour, 10 * hour)
+            .await;
+
+        let ingester_connection = Arc::new(MockIngesterConnection::new());
+        let querier_table =
+            querier_table_with_ingester(&catalog, &table, Arc::clone(&ingester_connection)).await;
+
+        let ingester_partition = IngesterPartition::try_new(
+            ChunkId::new_test(1000),
+            Arc::from("ns"),
+            Arc::from("table"),
+            SequencerId::new(1),
+            Some(SequenceNumber::new(6)),
+            None,
+            Arc::clone(querier_table.schema()),
+            vec![],
+        )
+        .unwrap();
+        ingester_connection.next_response(Ok(vec![Arc::new(ingester_partition)]));
+
+        let mut chunks = querier_table.chunks(&Predicate::default()).await.unwrap();
+        chunks.sort_by_key(|c| c.order());
+        assert_eq!(chunks.len(), 3);
+
+        assert_eq!(
+            chunks[0].id(),
+            ChunkId::new_test(file_partial.parquet_file.id.get() as u128),
+        );
+        let expected_retention_predicate = retention_delete_predicate(9 * hour);
+        // rows stamped exactly at the cutoff are still within the retention period
+        assert_eq!(expected_retention_predicate.range.end(), 9 * hour - 1);
+        assert_eq!(
+            chunks[0].delete_predicates(),
+            &[Arc::clone(&expected_retention_predicate)]
+        );
+
+        assert_eq!(
+            chunks[1].id(),
+            ChunkId::new_test(file_fresh.parquet_file.id.get() as u128),
+        );
+        assert!(chunks[1].delete_predicates().is_empty());
+
+        assert_eq!(chunks[2].chunk_type(), "IngesterPartition");
+        assert_eq!(
+            chunks[2].delete_predicates(),
+            &[expected_retention_predicate]
+        );
+    }
 }
@@ -4,11 +4,21 @@ use iox_catalog::interface::get_schema_by_name;
 use iox_tests::util::{TestCatalog, TestTable};
 use schema::Schema;
 
-use crate::{cache::CatalogCache, chunk::ParquetChunkAdapter};
+use crate::{
+    cache::CatalogCache, chunk::ParquetChunkAdapter, ingester::test_util::MockIngesterConnection,
+};
 
 use super::QuerierTable;
 
 pub async fn querier_table(catalog: &Arc<TestCatalog>, table: &Arc<TestTable>) -> QuerierTable {
+    querier_table_with_ingester(catalog, table, Arc::new(MockIngesterConnection::new())).await
+}
+
+pub async fn querier_table_with_ingester(
+    catalog: &Arc<TestCatalog>,
+    table: &Arc<TestTable>,
+    ingester_connection: Arc<MockIngesterConnection>,
+) -> QuerierTable {
     let catalog_cache = Arc::new(CatalogCache::new(
         catalog.catalog(),
         catalog.time_provider(),
@@ -16,6 +26,7 @@ pub async fn querier_table(catalog: &Arc<TestCatalog>, table: &Arc<TestTable>) -
     let chunk_adapter = Arc::new(ParquetChunkAdapter::new(
         catalog_cache,
         catalog.object_store(),
+        None,
         catalog.metric_registry(),
         catalog.time_provider(),
     ));
@@ -24,13 +35,28 @@ pub async fn querier_table(catalog: &Arc<TestCatalog>, table: &Arc<TestTable>) -
     let mut catalog_schema = get_schema_by_name(&table.namespace.namespace.name, repos.as_mut())
         .await
         .unwrap();
+    let retention_period = catalog_schema.retention_period;
     let schema = catalog_schema.tables.remove(&table.table.name).unwrap();
     let schema = Arc::new(Schema::try_from(schema).unwrap());
 
+    let sequencer_ids: Arc<[_]> = repos
+        .sequencers()
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|s| s.kafka_topic_id == table.namespace.namespace.kafka_topic_id)
+        .map(|s| s.id)
+        .collect();
+
     QuerierTable::new(
+        table.namespace.namespace.name.clone().into(),
         table.table.id,
         table.table.name.clone().into(),
         schema,
+        retention_period,
+        sequencer_ids,
+        ingester_connection,
         chunk_adapter,
     )
 }
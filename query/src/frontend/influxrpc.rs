@@ -9,8 +9,8 @@ use data_types::chunk_metadata::ChunkId;
 use datafusion::{
     error::DataFusionError,
     logical_plan::{
-        binary_expr, col, when, DFSchemaRef, Expr, ExprRewritable, ExprSchemable, LogicalPlan,
-        LogicalPlanBuilder,
+        binary_expr, col, concat, lit, replace, when, DFSchemaRef, Expr, ExprRewritable,
+        ExprSchemable, LogicalPlan, LogicalPlanBuilder,
     },
 };
 use datafusion_util::AsExpr;
@@ -634,6 +634,178 @@ impl InfluxRpcPlanner {
             .context(CreatingStringSetSnafu)
     }
 
+    /// Returns a plan which produces the distinct series keys (the
+    /// measurement name followed by its sorted `tag=value` pairs,
+    /// e.g. `cpu,host=a,region=west`) of all rows in this database
+    /// that pass the conditions specified by `predicate`.
+    ///
+    /// Backslashes, commas and equal signs in the measurement name, tag
+    /// names and tag values are escaped with a backslash, so that
+    /// different series never share a key.
+    ///
+    /// The number of strings produced is the series cardinality.
+    pub async fn series_keys(
+        &self,
+        database: &dyn QueryDatabase,
+        rpc_predicate: InfluxRpcPredicate,
+    ) -> Result<StringSetPlan> {
+        let ctx = self.ctx.child_ctx("series_keys planning");
+        debug!(?rpc_predicate, "planning series_keys");
+
+        // Special case predicates that span the entire valid timestamp range
+        let rpc_predicate = rpc_predicate.clear_timestamp_if_max_range();
+
+        // The basic algorithm is:
+        //
+        // 1. Find all the potential tables in the chunks
+        //
+        // 2. For each table/chunk pair, figure out whether all rows
+        // that pass the predicate belong to a single series that can
+        // be determined from only metadata, and which need full plans
+
+        // Key is table name, value is set of chunks which had data
+        // for that table but that we couldn't evaluate the predicate
+        // entirely using the metadata
+        let mut need_full_plans = BTreeMap::new();
+        let mut known_keys = BTreeSet::new();
+
+        let table_predicates = rpc_predicate
+            .table_predicates(database.as_meta())
+            .context(CreatingPredicatesSnafu)?;
+        for (table_name, predicate) in &table_predicates {
+            for chunk in database.chunks(table_name, predicate).await {
+                // If there are delete predicates, we need to scan (or do full plan) the data to eliminate
+                // deleted data before getting series keys
+                let mut do_full_plan = chunk.has_delete_predicates();
+
+                if !do_full_plan {
+                    // Try and apply the predicate using only metadata
+                    let pred_result = chunk.apply_predicate_to_metadata(predicate).context(
+                        CheckingChunkPredicateSnafu {
+                            chunk_id: chunk.id(),
+                        },
+                    )?;
+
+                    match pred_result {
+                        PredicateMatch::Zero => continue,
+                        PredicateMatch::Unknown => do_full_plan = true,
+                        PredicateMatch::AtLeastOneNonNullField => {
+                            match self.series_key_from_metadata(
+                                ctx.child_ctx("series_key_from_metadata"),
+                                table_name,
+                                chunk.as_ref(),
+                                predicate,
+                            )? {
+                                Some(key) => {
+                                    debug!(
+                                        %table_name,
+                                        %key,
+                                        chunk_id=%chunk.id().get(),
+                                        "series key found from metadata",
+                                    );
+                                    known_keys.insert(key);
+                                }
+                                None => do_full_plan = true,
+                            }
+                        }
+                    }
+                }
+
+                if do_full_plan {
+                    debug!(
+                        %table_name,
+                        chunk_id=%chunk.id().get(),
+                        "need full plan to find series keys"
+                    );
+
+                    need_full_plans
+                        .entry(table_name)
+                        .or_insert_with(Vec::new)
+                        .push(Arc::clone(&chunk));
+                }
+            }
+        }
+
+        let mut builder = StringSetPlanBuilder::new();
+
+        for (table_name, predicate) in &table_predicates {
+            if let Some(chunks) = need_full_plans.remove(table_name) {
+                let schema = database
+                    .table_schema(table_name)
+                    .context(TableRemovedSnafu { table_name })?;
+
+                let plan = self.series_keys_plan(
+                    ctx.child_ctx("series_keys_plan"),
+                    table_name,
+                    schema,
+                    predicate,
+                    chunks,
+                )?;
+
+                if let Some(plan) = plan {
+                    builder = builder.append_other(plan.into());
+                }
+            }
+        }
+
+        // add the known keys we could find from metadata only
+        builder
+            .append_other(known_keys.into())
+            .build()
+            .context(CreatingStringSetSnafu)
+    }
+
+    /// Returns the single series key shared by all rows of `chunk`
+    /// that pass `predicate`, if it can be determined from metadata.
+    ///
+    /// This is the case when every tag column of the chunk has no
+    /// nulls and exactly one value passes the predicate. Returns
+    /// `None` if a full plan is required.
+    fn series_key_from_metadata(
+        &self,
+        ctx: IOxSessionContext,
+        table_name: &str,
+        chunk: &dyn QueryChunk,
+        predicate: &Predicate,
+    ) -> Result<Option<String>> {
+        let summary = match chunk.summary() {
+            Some(summary) => summary,
+            None => return Ok(None),
+        };
+
+        let schema = chunk.schema();
+        let tag_names: BTreeSet<_> = schema.tags_iter().map(|f| f.name().as_str()).collect();
+
+        let mut key = escape_series_key_part(table_name);
+        for tag_name in tag_names {
+            match summary.column(tag_name) {
+                Some(column) if column.null_count() == 0 => {}
+                _ => return Ok(None),
+            }
+
+            let values = chunk
+                .column_values(
+                    ctx.child_ctx("column_values execution"),
+                    tag_name,
+                    predicate,
+                )
+                .context(FindingColumnValuesSnafu)?;
+
+            match values {
+                Some(values) if values.len() == 1 => {
+                    let value = values.into_iter().next().expect("checked length");
+                    key.push(',');
+                    key.push_str(&escape_series_key_part(tag_name));
+                    key.push('=');
+                    key.push_str(&escape_series_key_part(&value));
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Some(key))
+    }
+
     /// Returns a plan that produces a list of columns and their
     /// datatypes (as defined in the data written via `write_lines`),
     /// and which have more than zero rows which pass the conditions
@@ -948,6 +1120,70 @@ impl InfluxRpcPlanner {
         Ok(Some(plan.into()))
     }
 
+    /// Creates a DataFusion LogicalPlan that returns the series key
+    /// of every row in the specified table that passes the predicate.
+    /// Tags which are null for a row are omitted from its key.
+    ///
+    /// returns `None` if the table contains no rows that would pass
+    /// the predicate.
+    ///
+    /// The created plan looks like:
+    ///
+    /// ```text
+    ///  Projection (concat(table_name, ',tag0=', tag0, ...))
+    ///    Filter(predicate) [optional]
+    ///      Scan
+    /// ```
+    fn series_keys_plan(
+        &self,
+        ctx: IOxSessionContext,
+        table_name: &str,
+        schema: Arc<Schema>,
+        predicate: &Predicate,
+        chunks: Vec<Arc<dyn QueryChunk>>,
+    ) -> Result<Option<LogicalPlan>> {
+        let scan_and_filter = self.scan_and_filter(
+            ctx.child_ctx("scan_and_filter planning"),
+            table_name,
+            schema,
+            predicate,
+            chunks,
+        )?;
+        let TableScanAndFilter {
+            plan_builder,
+            schema,
+        } = match scan_and_filter {
+            None => return Ok(None),
+            Some(t) => t,
+        };
+
+        let tag_names: BTreeSet<_> = schema.tags_iter().map(|f| f.name().as_str()).collect();
+
+        let mut key_exprs = vec![lit(escape_series_key_part(table_name))];
+        for tag_name in tag_names {
+            let value = Expr::Cast {
+                expr: Box::new(col(tag_name)),
+                data_type: DataType::Utf8,
+            };
+            let value = SERIES_KEY_ESCAPES.iter().fold(value, |expr, (from, to)| {
+                replace(expr, lit(*from), lit(*to))
+            });
+            let prefix = format!(",{}=", escape_series_key_part(tag_name));
+            let pair = when(col(tag_name).is_null(), lit(""))
+                .otherwise(concat(&[lit(prefix), value]))
+                .context(BuildingPlanSnafu)?;
+            key_exprs.push(pair);
+        }
+
+        let plan = plan_builder
+            .project(vec![concat(&key_exprs).alias("series_key")])
+            .context(BuildingPlanSnafu)?
+            .build()
+            .context(BuildingPlanSnafu)?;
+
+        Ok(Some(plan))
+    }
+
     /// Creates a DataFusion LogicalPlan that returns the timestamp
     /// and all field columns for a specified table:
     ///
@@ -1487,6 +1723,17 @@ fn prune_chunks_metadata(
     Ok(filtered)
 }
 
+/// Characters escaped in the parts of a series key, with their escaped
+/// form. The backslash must be escaped first.
+const SERIES_KEY_ESCAPES: [(&str, &str); 3] = [("\\", "\\\\"), (",", "\\,"), ("=", "\\=")];
+
+/// Escape a measurement name, tag name or tag value for use in a series key.
+fn escape_series_key_part(s: &str) -> String {
+    SERIES_KEY_ESCAPES
+        .iter()
+        .fold(s.to_string(), |s, (from, to)| s.replace(from, to))
+}
+
 /// Return a `Vec` of `Exprs` such that it starts with `prefix` cols and
 /// then has all columns in `schema` that are not already in the prefix.
 fn project_exprs_in_schema(prefix: &[&str], schema: &DFSchemaRef) -> Vec<Expr> {
@@ -1826,7 +2073,6 @@ fn make_selector_expr<'a>(
 
 #[cfg(test)]
 mod tests {
-    use datafusion::logical_plan::lit;
     use futures::{future::BoxFuture, FutureExt};
     use predicate::PredicateBuilder;
 
@@ -1837,6 +2083,12 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_escape_series_key_part() {
+        assert_eq!(escape_series_key_part("cpu"), "cpu");
+        assert_eq!(escape_series_key_part(r"a,b=c\d"), r"a\,b\=c\\d");
+    }
+
     #[tokio::test]
     async fn test_predicate_rewrite_table_names() {
         run_test(|test_db, rpc_predicate| {
@@ -1879,6 +2131,20 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_predicate_rewrite_series_keys() {
+        run_test(|test_db, rpc_predicate| {
+            async move {
+                InfluxRpcPlanner::new()
+                    .series_keys(test_db, rpc_predicate)
+                    .await
+                    .expect("creating plan");
+            }
+            .boxed()
+        })
+        .await
+    }
+
     #[tokio::test]
     async fn test_predicate_rewrite_field_columns() {
         run_test(|test_db, rpc_predicate| {
@@ -0,0 +1,158 @@
+use datafusion::logical_plan::{col, lit};
+use predicate::rpc_predicate::InfluxRpcPredicate;
+use predicate::PredicateBuilder;
+use query::{
+    exec::stringset::{IntoStringSet, StringSetRef},
+    frontend::influxrpc::InfluxRpcPlanner,
+};
+
+use crate::scenarios::*;
+
+/// runs series_keys(predicate) and compares it to the expected
+/// output
+async fn run_series_keys_test_case<D>(
+    db_setup: D,
+    predicate: InfluxRpcPredicate,
+    expected_series_keys: Vec<&str>,
+) where
+    D: DbSetup,
+{
+    test_helpers::maybe_start_logging();
+
+    for scenario in db_setup.make().await {
+        let DbScenario {
+            scenario_name, db, ..
+        } = scenario;
+        println!("Running scenario '{}'", scenario_name);
+        println!("Predicate: '{:#?}'", predicate);
+        let planner = InfluxRpcPlanner::default();
+        let ctx = db.new_query_context(None);
+
+        let plan = planner
+            .series_keys(db.as_query_database(), predicate.clone())
+            .await
+            .expect("built plan successfully");
+        let keys = ctx
+            .to_string_set(plan)
+            .await
+            .expect("converted plan to strings successfully");
+
+        assert_eq!(
+            keys,
+            to_stringset(&expected_series_keys),
+            "Error in  scenario '{}'\n\nexpected:\n{:?}\nactual:\n{:?}",
+            scenario_name,
+            expected_series_keys,
+            keys
+        );
+    }
+}
+
+#[tokio::test]
+async fn list_series_keys_no_data_no_pred() {
+    run_series_keys_test_case(NoData {}, InfluxRpcPredicate::default(), vec![]).await;
+}
+
+#[tokio::test]
+async fn list_series_keys_no_predicate() {
+    let expected_series_keys = vec![
+        "h2o,city=Boston,county=Suffolk,state=MA",
+        "h2o,city=LA,county=LA,state=CA",
+        "o2,borough=Brooklyn,city=NYC,state=NY",
+        "o2,city=Boston,state=MA",
+        "o2,city=NYC,state=NY",
+        "o2,state=CA",
+        "o2,state=NY",
+    ];
+    run_series_keys_test_case(
+        TwoMeasurementsManyNulls {},
+        InfluxRpcPredicate::default(),
+        expected_series_keys,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn list_series_keys_timestamp_pred() {
+    let predicate = PredicateBuilder::default().timestamp_range(50, 201).build();
+    let predicate = InfluxRpcPredicate::new(None, predicate);
+    let expected_series_keys = vec!["h2o,city=LA,county=LA,state=CA", "o2,city=Boston,state=MA"];
+    run_series_keys_test_case(TwoMeasurementsManyNulls {}, predicate, expected_series_keys).await;
+}
+
+#[tokio::test]
+async fn list_series_keys_tag_pred() {
+    let predicate = PredicateBuilder::default()
+        .add_expr(col("state").eq(lit("MA"))) // state=MA
+        .build();
+    let predicate = InfluxRpcPredicate::new(None, predicate);
+    let expected_series_keys = vec![
+        "h2o,city=Boston,county=Suffolk,state=MA",
+        "o2,city=Boston,state=MA",
+    ];
+    run_series_keys_test_case(TwoMeasurementsManyNulls {}, predicate, expected_series_keys).await;
+}
+
+#[tokio::test]
+async fn list_series_keys_table_pred() {
+    let predicate = InfluxRpcPredicate::new_table("h2o", Default::default());
+    let expected_series_keys = vec![
+        "h2o,city=Boston,county=Suffolk,state=MA",
+        "h2o,city=LA,county=LA,state=CA",
+    ];
+    run_series_keys_test_case(TwoMeasurementsManyNulls {}, predicate, expected_series_keys).await;
+}
+
+#[tokio::test]
+async fn list_series_keys_single_series_per_chunk() {
+    // each chunk holds a single series, which can be found from metadata
+    let expected_series_keys = vec!["cpu,region=west", "disk,region=east"];
+    run_series_keys_test_case(
+        TwoMeasurements {},
+        InfluxRpcPredicate::default(),
+        expected_series_keys,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn list_series_keys_escaped() {
+    // without escaping, the first and last key would be the same
+    let expected_series_keys = vec![r"h2o,city=a\,state\=b", "h2o,city=c", "h2o,city=a,state=b"];
+    run_series_keys_test_case(
+        OneMeasurementSeparatorsInTagValues {},
+        InfluxRpcPredicate::default(),
+        expected_series_keys,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn list_series_keys_with_delete() {
+    let expected_series_keys = vec![
+        "h2o,city=Boston,county=Suffolk,state=MA",
+        "h2o,city=Boston,state=MA",
+        "h2o,city=LA,county=LA,state=CA",
+        "h2o,state=CA",
+    ];
+    run_series_keys_test_case(
+        OneMeasurementManyNullTagsWithDelete {},
+        InfluxRpcPredicate::default(),
+        expected_series_keys,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn list_series_keys_with_delete_all() {
+    run_series_keys_test_case(
+        OneMeasurementManyNullTagsWithDeleteAll {},
+        InfluxRpcPredicate::default(),
+        vec![],
+    )
+    .await;
+}
+
+fn to_stringset(v: &[&str]) -> StringSetRef {
+    v.into_stringset().unwrap()
+}
@@ -558,6 +558,25 @@ impl DbSetup for TwoMeasurementsManyNulls {
     }
 }
 
+/// One measurement with tag values containing the separators of a
+/// series key
+#[derive(Debug)]
+pub struct OneMeasurementSeparatorsInTagValues {}
+#[async_trait]
+impl DbSetup for OneMeasurementSeparatorsInTagValues {
+    async fn make(&self) -> Vec<DbScenario> {
+        let partition_key = "1970-01-01T00";
+
+        let lp_lines1 = vec![
+            r"h2o,city=a\,state\=b temp=70.4 100",
+            "h2o,city=c temp=72.4 250",
+        ];
+        let lp_lines2 = vec!["h2o,city=a,state=b temp=50.4 200"];
+
+        make_two_chunk_scenarios(partition_key, &lp_lines1.join("\n"), &lp_lines2.join("\n")).await
+    }
+}
+
 #[derive(Debug)]
 pub struct TwoMeasurementsManyFields {}
 #[async_trait]
@@ -1239,6 +1239,7 @@ async fn make_querier_namespace(ns: Arc<TestNamespace>) -> Arc<QuerierNamespace>
         ns.namespace.id,
         ns.catalog.metric_registry(),
         ns.catalog.object_store(),
+        None,
         ns.catalog.time_provider(),
         ns.catalog.exec(),
     ));
@@ -1,7 +1,7 @@
 use std::{collections::BTreeSet, iter, sync::Arc};
 
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
-use data_types2::{PartitionTemplate, TemplatePart};
+use data_types2::{CompiledPartitionTemplate, PartitionTemplate, TemplatePart};
 use hyper::{Body, Request};
 use iox_catalog::{interface::Catalog, mem::MemCatalog};
 use router2::{
@@ -13,6 +13,7 @@ use router2::{
     server::http::HttpDelegate,
     sharder::JumpHash,
 };
+use time::SystemProvider;
 use tokio::runtime::Runtime;
 use write_buffer::core::WriteBufferWriting;
 use write_buffer::mock::{MockBufferForWriting, MockBufferSharedState};
@@ -60,10 +61,17 @@ fn e2e_benchmarks(c: &mut Criterion) {
         ));
 
         let write_buffer = init_write_buffer(1);
-        let schema_validator = SchemaValidator::new(Arc::clone(&catalog), Arc::clone(&ns_cache));
-        let partitioner = Partitioner::new(PartitionTemplate {
+        let schema_validator = SchemaValidator::new(
+            Arc::clone(&catalog),
+            Arc::clone(&ns_cache),
+            Arc::new(SystemProvider::new()),
+            &metrics,
+        );
+        let default_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
             parts: vec![TemplatePart::TimeFormat("%Y-%m-%d".to_owned())],
-        });
+        })
+        .unwrap();
+        let partitioner = Partitioner::new(default_template, ns_cache);
 
         let handler_stack =
             schema_validator.and_then(partitioner.and_then(FanOutAdaptor::new(write_buffer)));
@@ -70,6 +70,10 @@
 //! The [`ShardedWriteBuffer`] uses a sharder implementation to direct the DML
 //! operations into a fixed set of sequencers.
 //!
+//! The [`RateLimiter`] decorator can wrap the handler stack to provide
+//! admission control, rejecting requests when too many are in flight, or when
+//! a namespace exceeds its write rate limit.
+//!
 //! [`NamespaceCache`]: crate::namespace_cache::NamespaceCache
 //! [`NamespaceSchema`]: data_types2::NamespaceSchema
 
@@ -99,5 +103,8 @@ pub use chain::*;
 mod fan_out;
 pub use fan_out::*;
 
+mod rate_limit;
+pub use rate_limit::*;
+
 #[cfg(test)]
 pub mod mock;
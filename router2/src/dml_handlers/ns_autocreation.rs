@@ -132,7 +132,9 @@ where
 mod tests {
     use super::*;
     use crate::namespace_cache::MemoryNamespaceCache;
-    use data_types2::{Namespace, NamespaceId, NamespaceSchema};
+    use data_types2::{
+        Namespace, NamespaceId, NamespaceSchema, DEFAULT_MAX_COLUMNS_PER_TABLE, DEFAULT_MAX_TABLES,
+    };
     use iox_catalog::mem::MemCatalog;
     use std::sync::Arc;
 
@@ -149,6 +151,10 @@ mod tests {
                 kafka_topic_id: KafkaTopicId::new(2),
                 query_pool_id: QueryPoolId::new(3),
                 tables: Default::default(),
+                retention_period: None,
+                partition_template: None,
+                max_tables: DEFAULT_MAX_TABLES,
+                max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
             },
         );
 
@@ -224,6 +230,8 @@ mod tests {
                 query_pool_id: QueryPoolId::new(42),
                 max_tables: 10000,
                 max_columns_per_table: 1000,
+                deleted_at: None,
+                partition_template: None,
             }
         );
     }
@@ -1,6 +1,7 @@
 use super::DmlHandler;
+use crate::namespace_cache::NamespaceCache;
 use async_trait::async_trait;
-use data_types2::{DatabaseName, DeletePredicate, PartitionTemplate};
+use data_types2::{CompiledPartitionTemplate, DatabaseName, DeletePredicate};
 use hashbrown::HashMap;
 use mutable_batch::{MutableBatch, PartitionWrite, WritePayload};
 use observability_deps::tracing::*;
@@ -40,26 +41,41 @@ impl<T> Partitioned<T> {
 }
 
 /// A [`DmlHandler`] implementation that splits per-table [`MutableBatch`] into
-/// partitioned per-table [`MutableBatch`] instances according to a configured
-/// [`PartitionTemplate`]. Deletes pass through unmodified.
+/// partitioned per-table [`MutableBatch`] instances according to a
+/// [`CompiledPartitionTemplate`]. Deletes pass through unmodified.
+///
+/// The template used for each table is resolved from the cached
+/// [`NamespaceSchema`] of the write's namespace - a table template takes
+/// precedence over a namespace template, and the configured default template
+/// is used when neither is set (or the namespace is not cached).
 ///
 /// A vector of partitions are returned to the caller, or the first error that
 /// occurs during partitioning.
+///
+/// [`NamespaceSchema`]: data_types2::NamespaceSchema
 #[derive(Debug)]
-pub struct Partitioner {
-    partition_template: PartitionTemplate,
+pub struct Partitioner<C> {
+    partition_template: CompiledPartitionTemplate,
+    cache: C,
 }
 
-impl Partitioner {
+impl<C> Partitioner<C> {
     /// Initialise a new [`Partitioner`], splitting writes according to the
-    /// specified [`PartitionTemplate`].
-    pub fn new(partition_template: PartitionTemplate) -> Self {
-        Self { partition_template }
+    /// templates of the namespaces in `ns_cache`, or the specified default
+    /// [`CompiledPartitionTemplate`] if none is set.
+    pub fn new(partition_template: CompiledPartitionTemplate, ns_cache: C) -> Self {
+        Self {
+            partition_template,
+            cache: ns_cache,
+        }
     }
 }
 
 #[async_trait]
-impl DmlHandler for Partitioner {
+impl<C> DmlHandler for Partitioner<C>
+where
+    C: NamespaceCache,
+{
     type WriteError = PartitionError;
     type DeleteError = PartitionError;
 
@@ -69,18 +85,25 @@ impl DmlHandler for Partitioner {
     /// Partition the per-table [`MutableBatch`].
     async fn write(
         &self,
-        _namespace: &DatabaseName<'static>,
+        namespace: &DatabaseName<'static>,
         batch: Self::WriteInput,
         _span_ctx: Option<SpanContext>,
     ) -> Result<Self::WriteOutput, Self::WriteError> {
         // A collection of partition-keyed, per-table MutableBatch instances.
         let mut partitions: HashMap<_, HashMap<_, MutableBatch>> = HashMap::default();
 
+        let schema = self.cache.get_schema(namespace);
+
         for (table_name, batch) in batch {
-            // Partition the table batch according to the configured partition
+            let template = schema
+                .as_ref()
+                .and_then(|schema| schema.partition_template_for(&table_name))
+                .unwrap_or(&self.partition_template);
+
+            // Partition the table batch according to the resolved partition
             // template and write it into the partition-keyed map.
             for (partition_key, partition_payload) in
-                PartitionWrite::partition(&table_name, &batch, &self.partition_template)
+                PartitionWrite::partition(&table_name, &batch, template)
             {
                 let partition = partitions.entry(partition_key).or_default();
                 let table_batch = partition
@@ -116,8 +139,13 @@ impl DmlHandler for Partitioner {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::namespace_cache::MemoryNamespaceCache;
     use assert_matches::assert_matches;
-    use data_types2::TemplatePart;
+    use data_types2::{
+        KafkaTopicId, NamespaceId, NamespaceSchema, PartitionTemplate, QueryPoolId, TableId,
+        TableSchema, TemplatePart,
+    };
+    use std::sync::Arc;
 
     /// The default timestamp applied to test LP if the write does not specify
     /// one.
@@ -137,11 +165,14 @@ mod tests {
             paste::paste! {
                 #[tokio::test]
                 async fn [<test_write_ $name>]() {
-                    let partition_template = PartitionTemplate {
+                    let partition_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
                         parts: vec![TemplatePart::TimeFormat("%Y-%m-%d".to_owned())],
-                    };
+                    }).unwrap();
 
-                    let partitioner = Partitioner::new(partition_template);
+                    let partitioner = Partitioner::new(
+                        partition_template,
+                        Arc::new(MemoryNamespaceCache::default()),
+                    );
                     let ns = DatabaseName::new("bananas").expect("valid db name");
 
                     let (writes, _) = mutable_batch_lp::lines_to_batches_stats($lp, DEFAULT_TIMESTAMP_NANOS).expect("failed to parse test LP");
@@ -243,4 +274,71 @@ mod tests {
         ],
         want_handler_ret = Ok(_)
     );
+
+    #[tokio::test]
+    async fn test_write_cached_templates() {
+        let default_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
+            parts: vec![TemplatePart::TimeFormat("%Y-%m-%d".to_owned())],
+        })
+        .unwrap();
+
+        let ns = DatabaseName::new("bananas").expect("valid db name");
+
+        // The namespace partitions by year, and the "platanos" table overrides
+        // it to partition by the "tag1" value.
+        let mut schema = NamespaceSchema::new(
+            NamespaceId::new(1),
+            KafkaTopicId::new(1),
+            QueryPoolId::new(1),
+        );
+        schema.partition_template = Some(
+            CompiledPartitionTemplate::try_new(PartitionTemplate {
+                parts: vec![TemplatePart::TimeFormat("%Y".to_owned())],
+            })
+            .unwrap(),
+        );
+        let mut table = TableSchema::new(TableId::new(1));
+        table.partition_template = Some(
+            CompiledPartitionTemplate::try_new(PartitionTemplate {
+                parts: vec![TemplatePart::Column("tag1".to_owned())],
+            })
+            .unwrap(),
+        );
+        schema.tables.insert("platanos".to_owned(), table);
+
+        let cache = Arc::new(MemoryNamespaceCache::default());
+        cache.put_schema(ns.clone(), schema);
+
+        let partitioner = Partitioner::new(default_template, cache);
+
+        let (writes, _) = mutable_batch_lp::lines_to_batches_stats(
+            "\
+            bananas,tag1=A,tag2=B val=42i 1465839830100400200\n\
+            platanos,tag1=A,tag2=B value=42i 1465839830100400200\n\
+            ",
+            DEFAULT_TIMESTAMP_NANOS,
+        )
+        .expect("failed to parse test LP");
+
+        let got = partitioner
+            .write(&ns, writes, None)
+            .await
+            .expect("partitioning should succeed")
+            .into_iter()
+            .map(|partition| {
+                let mut tables = partition.payload.keys().cloned().collect::<Vec<_>>();
+                tables.sort();
+                (partition.key, tables)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let want = [
+            ("2016".to_owned(), vec!["bananas".to_owned()]),
+            ("tag1_A".to_owned(), vec!["platanos".to_owned()]),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        pretty_assertions::assert_eq!(want, got);
+    }
 }
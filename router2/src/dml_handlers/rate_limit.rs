@@ -0,0 +1,532 @@
+use super::{DmlError, DmlHandler};
+use async_trait::async_trait;
+use data_types2::{DatabaseName, DeletePredicate};
+use hashbrown::HashMap;
+use metric::U64Counter;
+use mutable_batch::MutableBatch;
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error;
+use time::{Time, TimeProvider};
+use tokio::sync::Semaphore;
+use trace::ctx::SpanContext;
+
+/// The retry hint returned to clients rejected because the in-flight request
+/// limit was reached.
+///
+/// In-flight requests are typically short-lived, so a short backoff is
+/// sufficient for capacity to become available.
+pub const IN_FLIGHT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Errors returned by the [`RateLimiter`] when rejecting a request.
+#[derive(Debug, Error)]
+pub enum RateLimitError {
+    /// The router is already processing the maximum number of concurrent
+    /// requests.
+    #[error("too many in-flight requests (limit {0})")]
+    TooManyRequests(usize),
+
+    /// The namespace exceeded its write rate limit.
+    #[error(
+        "namespace {namespace} exceeded the write rate limit of {limit} {unit}/s, retry after {}s",
+        .retry_after.as_secs_f64().ceil()
+    )]
+    NamespaceRateLimited {
+        /// The rate limited namespace.
+        namespace: String,
+        /// The limit that was exceeded.
+        limit: NonZeroU64,
+        /// The unit of `limit`.
+        unit: &'static str,
+        /// The time after which the namespace is expected to be within its
+        /// limits again.
+        retry_after: Duration,
+    },
+}
+
+impl RateLimitError {
+    /// The duration the client should wait before retrying the request.
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            Self::TooManyRequests(_) => IN_FLIGHT_RETRY_AFTER,
+            Self::NamespaceRateLimited { retry_after, .. } => *retry_after,
+        }
+    }
+}
+
+/// The write throughput limits applied to a single namespace.
+///
+/// A limit of [`None`] is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NamespaceRateLimit {
+    /// The maximum sustained write rate, in bytes per second.
+    pub bytes_per_second: Option<NonZeroU64>,
+    /// The maximum sustained write rate, in lines (rows) per second.
+    pub lines_per_second: Option<NonZeroU64>,
+}
+
+impl NamespaceRateLimit {
+    fn is_unlimited(&self) -> bool {
+        self.bytes_per_second.is_none() && self.lines_per_second.is_none()
+    }
+}
+
+/// Configuration of the [`RateLimiter`].
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// The maximum number of requests processed concurrently, or [`None`] for
+    /// no limit.
+    pub max_in_flight: Option<NonZeroUsize>,
+    /// The limits applied to namespaces without an override.
+    pub namespace_default: NamespaceRateLimit,
+    /// Per-namespace limits, replacing `namespace_default`.
+    pub namespace_overrides: HashMap<String, NamespaceRateLimit>,
+}
+
+/// A token bucket refilled at `rate` tokens per second, holding at most one
+/// second worth of tokens.
+///
+/// Requests are admitted while the bucket is not in debt, and the full cost
+/// of an admitted request is deducted - a request larger than the bucket
+/// capacity is therefore admitted, and the debt repaid before the next one.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: NonZeroU64,
+    tokens: f64,
+    last_refill: Time,
+}
+
+impl TokenBucket {
+    fn new(rate: NonZeroU64, now: Time) -> Self {
+        Self {
+            rate,
+            tokens: rate.get() as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Time) {
+        // Ignore time going backwards.
+        if let Some(delta) = now.checked_duration_since(self.last_refill) {
+            let capacity = self.rate.get() as f64;
+            self.tokens = (self.tokens + delta.as_secs_f64() * capacity).min(capacity);
+            self.last_refill = now;
+        }
+    }
+
+    /// Returns the time until the bucket is no longer in debt, or [`None`] if
+    /// a request can be admitted now.
+    fn wait_time(&self) -> Option<Duration> {
+        (self.tokens < 0.0).then(|| Duration::from_secs_f64(-self.tokens / self.rate.get() as f64))
+    }
+
+    fn take(&mut self, n: u64) {
+        self.tokens -= n as f64;
+    }
+}
+
+/// The token buckets of a single namespace.
+#[derive(Debug)]
+struct NamespaceBuckets {
+    bytes: Option<TokenBucket>,
+    lines: Option<TokenBucket>,
+}
+
+impl NamespaceBuckets {
+    fn new(limit: &NamespaceRateLimit, now: Time) -> Self {
+        Self {
+            bytes: limit.bytes_per_second.map(|r| TokenBucket::new(r, now)),
+            lines: limit.lines_per_second.map(|r| TokenBucket::new(r, now)),
+        }
+    }
+}
+
+/// A [`DmlHandler`] decorator providing admission control for the inner
+/// handler `D`.
+///
+/// Two independent limits are enforced:
+///
+///   * A global limit on the number of requests (writes and deletes) being
+///     processed concurrently, rejecting requests that arrive while the
+///     limit is reached.
+///   * A per-namespace write rate limit in bytes/s and lines/s, enforced
+///     with a token bucket allowing bursts of up to one second worth of
+///     data. Writes to a namespace that exhausted its bucket are rejected
+///     until the bucket is refilled.
+///
+/// The byte size of a write is the approximate in-memory size of the decoded
+/// [`MutableBatch`] instances, and the line count their total number of rows.
+///
+/// Rejected requests return a [`RateLimitError`] carrying a hint of when the
+/// client should retry.
+#[derive(Debug)]
+pub struct RateLimiter<D> {
+    inner: D,
+
+    in_flight: Option<(Semaphore, usize)>,
+
+    namespace_default: NamespaceRateLimit,
+    namespace_overrides: HashMap<String, NamespaceRateLimit>,
+    buckets: Mutex<HashMap<String, NamespaceBuckets>>,
+
+    time_provider: Arc<dyn TimeProvider>,
+
+    rejected_in_flight: U64Counter,
+    rejected_rate: U64Counter,
+}
+
+impl<D> RateLimiter<D> {
+    /// Enforce the limits in `config` for requests to `inner`.
+    pub fn new(
+        inner: D,
+        config: RateLimitConfig,
+        time_provider: Arc<dyn TimeProvider>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let rejected = metrics.register_metric::<U64Counter>(
+            "dml_handler_rate_limited",
+            "number of dml requests rejected by admission control",
+        );
+
+        Self {
+            inner,
+            in_flight: config
+                .max_in_flight
+                .map(|max| (Semaphore::new(max.get()), max.get())),
+            namespace_default: config.namespace_default,
+            namespace_overrides: config.namespace_overrides,
+            buckets: Default::default(),
+            time_provider,
+            rejected_in_flight: rejected.recorder(&[("reason", "in_flight")]),
+            rejected_rate: rejected.recorder(&[("reason", "namespace_rate")]),
+        }
+    }
+
+    /// Charge a write of `bytes` and `lines` to the rate limit of
+    /// `namespace`, or return an error if the namespace is over its limit.
+    fn charge(
+        &self,
+        namespace: &DatabaseName<'static>,
+        bytes: u64,
+        lines: u64,
+    ) -> Result<(), RateLimitError> {
+        let limit = self
+            .namespace_overrides
+            .get(namespace.as_str())
+            .unwrap_or(&self.namespace_default);
+        if limit.is_unlimited() {
+            return Ok(());
+        }
+
+        let now = self.time_provider.now();
+        let mut buckets = self.buckets.lock();
+        let buckets = buckets
+            .entry(namespace.to_string())
+            .or_insert_with(|| NamespaceBuckets::new(limit, now));
+
+        // Check both buckets before charging either, so a rejected write
+        // consumes no capacity.
+        for (bucket, unit) in [(&mut buckets.bytes, "bytes"), (&mut buckets.lines, "lines")] {
+            if let Some(bucket) = bucket {
+                bucket.refill(now);
+                if let Some(retry_after) = bucket.wait_time() {
+                    return Err(RateLimitError::NamespaceRateLimited {
+                        namespace: namespace.to_string(),
+                        limit: bucket.rate,
+                        unit,
+                        retry_after,
+                    });
+                }
+            }
+        }
+
+        if let Some(b) = buckets.bytes.as_mut() {
+            b.take(bytes);
+        }
+        if let Some(b) = buckets.lines.as_mut() {
+            b.take(lines);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<D> DmlHandler for RateLimiter<D>
+where
+    D: DmlHandler<WriteInput = HashMap<String, MutableBatch>>,
+{
+    type WriteInput = D::WriteInput;
+    type WriteOutput = D::WriteOutput;
+    type WriteError = DmlError;
+    type DeleteError = DmlError;
+
+    /// Admit the write if within the configured limits, and call the inner
+    /// handler.
+    async fn write(
+        &self,
+        namespace: &DatabaseName<'static>,
+        input: Self::WriteInput,
+        span_ctx: Option<SpanContext>,
+    ) -> Result<Self::WriteOutput, Self::WriteError> {
+        // The permit is held until the inner handler completes.
+        let _permit = self.acquire()?;
+
+        let (bytes, lines) = input.values().fold((0, 0), |(bytes, lines), batch| {
+            (bytes + batch.size() as u64, lines + batch.rows() as u64)
+        });
+        self.charge(namespace, bytes, lines).map_err(|e| {
+            debug!(%namespace, error=%e, "rate limiting write");
+            self.rejected_rate.inc(1);
+            e
+        })?;
+
+        self.inner
+            .write(namespace, input, span_ctx)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Admit the delete if within the in-flight request limit, and call the
+    /// inner handler.
+    ///
+    /// Deletes are not subject to the namespace rate limits.
+    async fn delete(
+        &self,
+        namespace: &DatabaseName<'static>,
+        table_name: &str,
+        predicate: &DeletePredicate,
+        span_ctx: Option<SpanContext>,
+    ) -> Result<(), Self::DeleteError> {
+        let _permit = self.acquire()?;
+
+        self.inner
+            .delete(namespace, table_name, predicate, span_ctx)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+impl<D> RateLimiter<D> {
+    /// Acquire an in-flight request permit, if an in-flight limit is
+    /// configured.
+    fn acquire(&self) -> Result<Option<tokio::sync::SemaphorePermit<'_>>, RateLimitError> {
+        let (semaphore, max) = match &self.in_flight {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        semaphore.try_acquire().map(Some).map_err(|_| {
+            self.rejected_in_flight.inc(1);
+            RateLimitError::TooManyRequests(*max)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dml_handlers::mock::{MockDmlHandler, MockDmlHandlerCall};
+    use assert_matches::assert_matches;
+    use data_types2::TimestampRange;
+    use metric::{Attributes, Metric};
+    use time::MockProvider;
+
+    fn lp_to_writes(lp: &str) -> HashMap<String, MutableBatch> {
+        let (writes, _) = mutable_batch_lp::lines_to_batches_stats(lp, 42)
+            .expect("failed to build test writes from LP");
+        writes
+    }
+
+    fn rejected_count(metrics: &metric::Registry, reason: &'static str) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Counter>>("dml_handler_rate_limited")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[("reason", reason)]))
+            .expect("failed to get observer")
+            .fetch()
+    }
+
+    fn limiter(
+        config: RateLimitConfig,
+        time: Arc<MockProvider>,
+        metrics: &metric::Registry,
+    ) -> RateLimiter<Arc<MockDmlHandler<HashMap<String, MutableBatch>>>> {
+        let inner = Arc::new(
+            MockDmlHandler::default()
+                .with_write_return([Ok(()), Ok(()), Ok(())])
+                .with_delete_return([Ok(())]),
+        );
+        RateLimiter::new(inner, config, time, metrics)
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_limit() {
+        let metrics = metric::Registry::default();
+        let time = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let handler = limiter(
+            RateLimitConfig {
+                max_in_flight: Some(NonZeroUsize::new(1).unwrap()),
+                ..Default::default()
+            },
+            time,
+            &metrics,
+        );
+        let ns = DatabaseName::new("bananas").unwrap();
+        let pred = DeletePredicate {
+            range: TimestampRange::new(1, 2),
+            exprs: vec![],
+        };
+
+        // Simulate a concurrent request holding the only permit.
+        let permit = handler.acquire().unwrap();
+
+        let err = handler
+            .write(&ns, lp_to_writes("cpu v=1"), None)
+            .await
+            .expect_err("write should be rejected");
+        assert_matches!(
+            err,
+            DmlError::RateLimited(RateLimitError::TooManyRequests(1))
+        );
+
+        let err = handler
+            .delete(&ns, "cpu", &pred, None)
+            .await
+            .expect_err("delete should be rejected");
+        assert_matches!(&err, DmlError::RateLimited(e) if e.retry_after() == IN_FLIGHT_RETRY_AFTER);
+        assert_eq!(rejected_count(&metrics, "in_flight"), 2);
+        assert!(handler.inner.calls().is_empty());
+
+        // Once the in-flight request completes, requests are admitted again.
+        drop(permit);
+        handler
+            .write(&ns, lp_to_writes("cpu v=1"), None)
+            .await
+            .expect("write should be admitted");
+        handler
+            .delete(&ns, "cpu", &pred, None)
+            .await
+            .expect("delete should be admitted");
+        assert_eq!(handler.inner.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_lines_limit() {
+        let metrics = metric::Registry::default();
+        let time = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let handler = limiter(
+            RateLimitConfig {
+                namespace_default: NamespaceRateLimit {
+                    bytes_per_second: None,
+                    lines_per_second: Some(NonZeroU64::new(2).unwrap()),
+                },
+                ..Default::default()
+            },
+            Arc::clone(&time),
+            &metrics,
+        );
+        let ns = DatabaseName::new("bananas").unwrap();
+
+        // A burst exceeding the limit is admitted, putting the bucket in debt
+        // by 2 lines.
+        handler
+            .write(
+                &ns,
+                lp_to_writes("cpu v=1 1\ncpu v=2 2\ncpu v=3 3\ncpu v=4 4"),
+                None,
+            )
+            .await
+            .expect("write should be admitted");
+
+        // Until the debt is repaid, writes are rejected.
+        let err = handler
+            .write(&ns, lp_to_writes("cpu v=1"), None)
+            .await
+            .expect_err("write should be rejected");
+        assert_matches!(
+            &err,
+            DmlError::RateLimited(e @ RateLimitError::NamespaceRateLimited { unit: "lines", .. }) => {
+                assert_eq!(e.retry_after(), Duration::from_secs(1));
+            }
+        );
+        assert_eq!(rejected_count(&metrics, "namespace_rate"), 1);
+
+        // Other namespaces have their own bucket.
+        handler
+            .write(
+                &DatabaseName::new("platanos").unwrap(),
+                lp_to_writes("cpu v=1"),
+                None,
+            )
+            .await
+            .expect("write should be admitted");
+
+        time.inc(Duration::from_secs(1));
+        handler
+            .write(&ns, lp_to_writes("cpu v=1"), None)
+            .await
+            .expect("write should be admitted");
+
+        let calls = handler.inner.calls();
+        assert_eq!(calls.len(), 3);
+        assert_matches!(&calls[2], MockDmlHandlerCall::Write { namespace, .. } => {
+            assert_eq!(namespace, "bananas");
+        });
+    }
+
+    #[tokio::test]
+    async fn test_namespace_override() {
+        let metrics = metric::Registry::default();
+        let time = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let mut namespace_overrides = HashMap::new();
+        namespace_overrides.insert(
+            "bananas".to_string(),
+            NamespaceRateLimit {
+                bytes_per_second: Some(NonZeroU64::new(1).unwrap()),
+                lines_per_second: None,
+            },
+        );
+        namespace_overrides.insert("unlimited".to_string(), NamespaceRateLimit::default());
+        let handler = limiter(
+            RateLimitConfig {
+                namespace_default: NamespaceRateLimit {
+                    bytes_per_second: None,
+                    lines_per_second: Some(NonZeroU64::new(1).unwrap()),
+                },
+                namespace_overrides,
+                ..Default::default()
+            },
+            time,
+            &metrics,
+        );
+
+        let ns = DatabaseName::new("bananas").unwrap();
+        handler
+            .write(&ns, lp_to_writes("cpu v=1"), None)
+            .await
+            .expect("write should be admitted");
+        let err = handler
+            .write(&ns, lp_to_writes("cpu v=1"), None)
+            .await
+            .expect_err("write should be rejected");
+        assert_matches!(
+            err,
+            DmlError::RateLimited(RateLimitError::NamespaceRateLimited { unit: "bytes", .. })
+        );
+
+        // The override removes the default limit.
+        let ns = DatabaseName::new("unlimited").unwrap();
+        for _ in 0..2 {
+            handler
+                .write(&ns, lp_to_writes("cpu v=1\ncpu v=2 2"), None)
+                .await
+                .expect("write should be admitted");
+        }
+    }
+}
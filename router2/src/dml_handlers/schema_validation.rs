@@ -1,16 +1,18 @@
 use super::DmlHandler;
 use crate::namespace_cache::{metrics::InstrumentedCache, MemoryNamespaceCache, NamespaceCache};
 use async_trait::async_trait;
-use data_types2::{DatabaseName, DeletePredicate};
+use data_types2::{DatabaseName, DeletePredicate, NamespaceSchema};
 use hashbrown::HashMap;
 use iox_catalog::{
     interface::{get_schema_by_name, Catalog},
     validate_or_insert_schema,
 };
+use metric::{Metric, U64Counter};
 use mutable_batch::MutableBatch;
 use observability_deps::tracing::*;
-use std::{ops::DerefMut, sync::Arc};
+use std::{borrow::Cow, ops::DerefMut, sync::Arc};
 use thiserror::Error;
+use time::TimeProvider;
 use trace::ctx::SpanContext;
 
 /// Errors emitted during schema validation.
@@ -27,6 +29,88 @@ pub enum SchemaError {
     /// the failure reason.
     #[error(transparent)]
     Validate(iox_catalog::interface::Error),
+
+    /// The request contains data older than the retention period of the
+    /// namespace.
+    #[error(
+        "table {table_name} contains a point with timestamp {min_timestamp}, \
+        which is older than the namespace retention cutoff {cutoff}"
+    )]
+    RetentionPeriod {
+        /// The table containing the expired data.
+        table_name: String,
+        /// The oldest timestamp in the rejected table batch.
+        min_timestamp: i64,
+        /// The oldest timestamp (in nanoseconds since the epoch) accepted by
+        /// the namespace at the time of the write.
+        cutoff: i64,
+    },
+
+    /// The request would create more tables than the namespace allows.
+    #[error(
+        "couldn't create table {table_name}: namespace {namespace} has {count} tables, \
+        and the write would exceed the limit of {limit}"
+    )]
+    TableLimit {
+        /// The namespace of the write.
+        namespace: String,
+        /// The table that would exceed the limit.
+        table_name: String,
+        /// The number of tables in the namespace.
+        count: usize,
+        /// The maximum number of tables in the namespace.
+        limit: usize,
+    },
+
+    /// The request would create more columns in a table than the namespace
+    /// allows.
+    #[error(
+        "couldn't create column {column_name} in table {table_name}: the table has {count} \
+        columns, and the write would exceed the limit of {limit} for namespace {namespace}"
+    )]
+    ColumnLimit {
+        /// The namespace of the write.
+        namespace: String,
+        /// The table the column would be created in.
+        table_name: String,
+        /// The column that would exceed the limit.
+        column_name: String,
+        /// The number of columns in the table.
+        count: usize,
+        /// The maximum number of columns per table in the namespace.
+        limit: usize,
+    },
+}
+
+impl SchemaError {
+    /// Returns true if this error is caused by the request exceeding a table or
+    /// column limit of the namespace, either detected by the
+    /// [`SchemaValidator`] or by the catalog.
+    pub fn is_limit_violation(&self) -> bool {
+        matches!(
+            self,
+            Self::TableLimit { .. }
+                | Self::ColumnLimit { .. }
+                | Self::Validate(
+                    iox_catalog::interface::Error::TableCreateLimitError { .. }
+                        | iox_catalog::interface::Error::ColumnCreateLimitError { .. }
+                )
+        )
+    }
+
+    /// Returns true if this error is caused by the request namespace not
+    /// existing in the catalog.
+    ///
+    /// Without [`NamespaceAutocreation`] in the handler chain, writes to a
+    /// namespace that was not explicitly created fail with this error.
+    ///
+    /// [`NamespaceAutocreation`]: super::NamespaceAutocreation
+    pub fn is_namespace_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::NamespaceLookup(iox_catalog::interface::Error::NamespaceNotFound { .. })
+        )
+    }
 }
 
 /// A [`SchemaValidator`] checks the schema of incoming writes against a
@@ -36,7 +120,8 @@ pub enum SchemaError {
 /// # Caching
 ///
 /// This validator attempts to incrementally build an in-memory cache of all
-/// table schemas it observes (never evicting the schemas).
+/// table schemas it observes. Schemas are only removed from the cache by the
+/// [`NamespaceCache`] implementation itself (i.e. when bounding its size).
 ///
 /// All schema operations are scoped to a single namespace.
 ///
@@ -49,6 +134,30 @@ pub enum SchemaError {
 /// Any successful write that adds new columns causes the new schema to be
 /// cached.
 ///
+/// A write rejected by the catalog with a column type conflict, or for
+/// exceeding a table / column limit that was not detected using the cached
+/// schema, indicates the cached schema may be stale. The schema is then
+/// reloaded from the catalog (or removed from the cache if the reload fails)
+/// so subsequent writes are validated against the current schema.
+///
+/// # Retention
+///
+/// Writes containing a point with a timestamp older than the retention period
+/// of the namespace (relative to the current time of the [`TimeProvider`]) are
+/// rejected as a whole with [`SchemaError::RetentionPeriod`], before any
+/// schema changes are made.
+///
+/// # Limits
+///
+/// Writes that would create more tables, or more columns in a table, than the
+/// limits of the cached namespace schema allow are rejected as a whole with
+/// [`SchemaError::TableLimit`] or [`SchemaError::ColumnLimit`], without
+/// making any catalog requests. The catalog remains the source of truth for
+/// the limits, and rejects any violation missed due to a stale cached schema.
+///
+/// Rejections are counted per namespace and limit in the
+/// `schema_validation_limit_rejections` metric.
+///
 /// To minimise locking, this cache is designed to allow (and tolerate) spurious
 /// cache "updates" racing with each other and overwriting newer schemas with
 /// older schemas. This is acceptable due to the incremental, additive schema
@@ -79,19 +188,94 @@ pub struct SchemaValidator<C = Arc<InstrumentedCache<MemoryNamespaceCache>>> {
     catalog: Arc<dyn Catalog>,
 
     cache: C,
+
+    time_provider: Arc<dyn TimeProvider>,
+
+    limit_rejections: Metric<U64Counter>,
 }
 
 impl<C> SchemaValidator<C> {
     /// Initialise a new [`SchemaValidator`] decorator, loading schemas from
     /// `catalog`.
     ///
-    /// Schemas are cached in `ns_cache`.
-    pub fn new(catalog: Arc<dyn Catalog>, ns_cache: C) -> Self {
+    /// Schemas are cached in `ns_cache`, and the namespace retention period is
+    /// evaluated against the current time of `time_provider`. Limit rejections
+    /// are recorded in `metrics`.
+    pub fn new(
+        catalog: Arc<dyn Catalog>,
+        ns_cache: C,
+        time_provider: Arc<dyn TimeProvider>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let limit_rejections = metrics.register_metric::<U64Counter>(
+            "schema_validation_limit_rejections",
+            "number of writes rejected for exceeding a namespace table or column limit",
+        );
+
         Self {
             catalog,
             cache: ns_cache,
+            time_provider,
+            limit_rejections,
         }
     }
+
+    /// Increment the rejection count of `limit` for `namespace`.
+    fn record_limit_rejection(&self, namespace: &DatabaseName<'_>, limit: &'static str) {
+        self.limit_rejections
+            .recorder([
+                ("namespace", Cow::Owned(namespace.to_string())),
+                ("limit", Cow::Borrowed(limit)),
+            ])
+            .inc(1);
+    }
+}
+
+/// Check the tables and columns `batches` would create against the limits of
+/// the cached namespace `schema`.
+fn check_limits(
+    namespace: &DatabaseName<'_>,
+    schema: &NamespaceSchema,
+    batches: &HashMap<String, MutableBatch>,
+) -> Result<(), SchemaError> {
+    let max_tables = usize::try_from(schema.max_tables).unwrap_or_default();
+    let max_columns = usize::try_from(schema.max_columns_per_table).unwrap_or_default();
+
+    let mut tables = schema.tables.len();
+    for (table_name, batch) in batches {
+        let table = schema.tables.get(table_name);
+        if table.is_none() {
+            if tables >= max_tables {
+                return Err(SchemaError::TableLimit {
+                    namespace: namespace.to_string(),
+                    table_name: table_name.clone(),
+                    count: schema.tables.len(),
+                    limit: max_tables,
+                });
+            }
+            tables += 1;
+        }
+
+        let existing_columns = table.map(|t| t.columns.len()).unwrap_or_default();
+        let mut columns = existing_columns;
+        for (column_name, _) in batch.columns() {
+            if table.map_or(false, |t| t.columns.contains_key(column_name)) {
+                continue;
+            }
+            if columns >= max_columns {
+                return Err(SchemaError::ColumnLimit {
+                    namespace: namespace.to_string(),
+                    table_name: table_name.clone(),
+                    column_name: column_name.clone(),
+                    count: existing_columns,
+                    limit: max_columns,
+                });
+            }
+            columns += 1;
+        }
+    }
+
+    Ok(())
 }
 
 #[async_trait]
@@ -112,6 +296,13 @@ where
     /// If `namespace` does not exist, [`SchemaError::NamespaceLookup`] is
     /// returned.
     ///
+    /// If any of the `batches` contains data older than the retention period of
+    /// the namespace, [`SchemaError::RetentionPeriod`] is returned.
+    ///
+    /// If the `batches` would exceed the table or column limits of the
+    /// namespace, [`SchemaError::TableLimit`] or [`SchemaError::ColumnLimit`]
+    /// is returned.
+    ///
     /// If the schema validation fails, [`SchemaError::Validate`] is returned.
     /// Callers should inspect the inner error to determine if the failure was
     /// caused by catalog I/O, or a schema conflict.
@@ -150,17 +341,90 @@ where
             }
         };
 
-        let maybe_new_schema = validate_or_insert_schema(
+        if let Some(retention_period) = schema.retention_period {
+            let cutoff = self
+                .time_provider
+                .now()
+                .checked_sub(retention_period)
+                .map(|t| t.timestamp_nanos())
+                .unwrap_or(i64::MIN);
+
+            for (table_name, batch) in &batches {
+                let min_timestamp = match batch.timestamp_summary().and_then(|s| s.stats.min) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                if min_timestamp < cutoff {
+                    warn!(
+                        %namespace,
+                        %table_name,
+                        min_timestamp,
+                        cutoff,
+                        "rejecting write outside of namespace retention period"
+                    );
+                    return Err(SchemaError::RetentionPeriod {
+                        table_name: table_name.clone(),
+                        min_timestamp,
+                        cutoff,
+                    });
+                }
+            }
+        }
+
+        if let Err(e) = check_limits(namespace, &schema, &batches) {
+            warn!(error=%e, %namespace, "rejecting write exceeding namespace limits");
+            self.record_limit_rejection(
+                namespace,
+                match e {
+                    SchemaError::TableLimit { .. } => "max_tables",
+                    _ => "max_columns_per_table",
+                },
+            );
+            return Err(e);
+        }
+
+        let maybe_new_schema = match validate_or_insert_schema(
             batches.iter().map(|(k, v)| (k.as_str(), v)),
             &schema,
             repos.deref_mut(),
         )
         .await
-        .map_err(|e| {
-            warn!(error=%e, %namespace, "schema validation failed");
-            SchemaError::Validate(e)
-        })?
-        .map(Arc::new);
+        {
+            Ok(v) => v.map(Arc::new),
+            Err(e) => {
+                warn!(error=%e, %namespace, "schema validation failed");
+                let stale = match &e {
+                    iox_catalog::interface::Error::TableCreateLimitError { .. } => {
+                        self.record_limit_rejection(namespace, "max_tables");
+                        true
+                    }
+                    iox_catalog::interface::Error::ColumnCreateLimitError { .. } => {
+                        self.record_limit_rejection(namespace, "max_columns_per_table");
+                        true
+                    }
+                    iox_catalog::interface::Error::ColumnTypeMismatch { .. } => true,
+                    _ => false,
+                };
+
+                // The cached schema disagrees with the catalog - reload it so
+                // the next write observes the conflicting schema change.
+                if stale {
+                    match get_schema_by_name(namespace, repos.deref_mut()).await {
+                        Ok(v) => {
+                            self.cache.refresh_schema(namespace.clone(), v);
+                            debug!(%namespace, "reloaded conflicting schema");
+                        }
+                        Err(e) => {
+                            warn!(error=%e, %namespace, "failed to reload conflicting schema");
+                            self.cache.remove_schema(namespace);
+                        }
+                    }
+                }
+
+                return Err(SchemaError::Validate(e));
+            }
+        };
 
         trace!(%namespace, "schema validation complete");
 
@@ -203,7 +467,9 @@ mod tests {
     use assert_matches::assert_matches;
     use data_types2::{ColumnType, KafkaTopicId, QueryPoolId, TimestampRange};
     use iox_catalog::mem::MemCatalog;
-    use std::sync::Arc;
+    use metric::{Attributes, Registry};
+    use std::{sync::Arc, time::Duration};
+    use time::{MockProvider, SystemProvider, Time};
 
     lazy_static::lazy_static! {
         static ref NAMESPACE: DatabaseName<'static> = "bananas".try_into().unwrap();
@@ -260,7 +526,12 @@ mod tests {
     #[tokio::test]
     async fn test_write_ok() {
         let catalog = create_catalog().await;
-        let handler = SchemaValidator::new(catalog, Arc::new(MemoryNamespaceCache::default()));
+        let handler = SchemaValidator::new(
+            catalog,
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        );
 
         let writes = lp_to_writes("bananas,tag1=A,tag2=B val=42i 123456");
         handler
@@ -278,7 +549,12 @@ mod tests {
     #[tokio::test]
     async fn test_write_schema_not_found() {
         let catalog = create_catalog().await;
-        let handler = SchemaValidator::new(catalog, Arc::new(MemoryNamespaceCache::default()));
+        let handler = SchemaValidator::new(
+            catalog,
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        );
 
         let ns = DatabaseName::try_from("A_DIFFERENT_NAMESPACE").unwrap();
 
@@ -289,6 +565,7 @@ mod tests {
             .expect_err("request should fail");
 
         assert_matches!(err, SchemaError::NamespaceLookup(_));
+        assert!(err.is_namespace_not_found());
 
         // The cache should not have retained the schema.
         assert!(handler.cache.get_schema(&ns).is_none());
@@ -297,7 +574,12 @@ mod tests {
     #[tokio::test]
     async fn test_write_validation_failure() {
         let catalog = create_catalog().await;
-        let handler = SchemaValidator::new(catalog, Arc::new(MemoryNamespaceCache::default()));
+        let handler = SchemaValidator::new(
+            catalog,
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        );
 
         // First write sets the schema
         let writes = lp_to_writes("bananas,tag1=A,tag2=B val=42i 123456"); // val=i64
@@ -323,13 +605,276 @@ mod tests {
         assert_cache(&handler, "bananas", "time", ColumnType::Time);
     }
 
+    #[tokio::test]
+    async fn test_write_conflict_reloads_schema() {
+        let catalog = create_catalog().await;
+        let handler = SchemaValidator::new(
+            Arc::clone(&catalog),
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        );
+
+        let writes = lp_to_writes("bananas,tag1=A val=42i 123456");
+        handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect("request should succeed");
+
+        // Another router adds a float column the cached schema does not
+        // contain.
+        {
+            let mut repos = catalog.repositories().await;
+            let table = handler
+                .cache
+                .get_schema(&*NAMESPACE)
+                .expect("cache should be populated")
+                .tables
+                .get("bananas")
+                .expect("table should be cached")
+                .id;
+            repos
+                .columns()
+                .create_or_get("other", table, ColumnType::F64)
+                .await
+                .expect("failed to create column");
+        }
+
+        // A write with a conflicting type for the uncached column is rejected
+        // by the catalog...
+        let writes = lp_to_writes("bananas,tag1=A other=1i 123456");
+        let err = handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect_err("request should fail");
+        assert_matches!(
+            err,
+            SchemaError::Validate(iox_catalog::interface::Error::ColumnTypeMismatch { .. })
+        );
+
+        // ...causing the cached schema to be reloaded.
+        assert_cache(&handler, "bananas", "val", ColumnType::I64);
+        assert_cache(&handler, "bananas", "other", ColumnType::F64);
+    }
+
+    #[tokio::test]
+    async fn test_write_retention_period() {
+        let catalog = create_catalog().await;
+        catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_retention_duration(NAMESPACE.as_str(), "1h")
+            .await
+            .expect("failed to set retention");
+
+        let now = Time::from_timestamp_nanos(0) + Duration::from_secs(10 * 60 * 60);
+        let handler = SchemaValidator::new(
+            catalog,
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(MockProvider::new(now)),
+            &metric::Registry::default(),
+        );
+
+        // A write containing a single point older than the retention period
+        // is rejected as a whole.
+        let oldest = now.timestamp_nanos() - Duration::from_secs(2 * 60 * 60).as_nanos() as i64;
+        let writes = lp_to_writes(&format!(
+            "bananas,tag1=A val=42i {}\nbananas,tag1=B val=42i {}",
+            now.timestamp_nanos(),
+            oldest
+        ));
+        let err = handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect_err("request should fail");
+        assert_matches!(err, SchemaError::RetentionPeriod { table_name, min_timestamp, .. } => {
+            assert_eq!(table_name, "bananas");
+            assert_eq!(min_timestamp, oldest);
+        });
+
+        // No schema changes were made for the rejected write.
+        let ns = handler
+            .cache
+            .get_schema(&*NAMESPACE)
+            .expect("cache should be populated");
+        assert!(ns.tables.is_empty());
+
+        // Data within the retention period is accepted.
+        let writes = lp_to_writes(&format!("bananas,tag1=A val=42i {}", now.timestamp_nanos()));
+        handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect("request should succeed");
+        assert_cache(&handler, "bananas", "val", ColumnType::I64);
+    }
+
+    fn assert_limit_rejections(metrics: &Registry, limit: &'static str, want: u64) {
+        let got = metrics
+            .get_instrument::<Metric<U64Counter>>("schema_validation_limit_rejections")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[
+                ("namespace", NAMESPACE.as_str()),
+                ("limit", limit),
+            ]))
+            .expect("failed to get observer")
+            .fetch();
+        assert_eq!(got, want);
+    }
+
+    #[tokio::test]
+    async fn test_write_table_limit() {
+        let catalog = create_catalog().await;
+        catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_table_limit(NAMESPACE.as_str(), 1)
+            .await
+            .expect("failed to set table limit");
+
+        let metrics = Registry::default();
+        let handler = SchemaValidator::new(
+            Arc::clone(&catalog),
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metrics,
+        );
+
+        let writes = lp_to_writes("bananas,tag1=A val=42i 123456");
+        handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect("request should succeed");
+
+        let writes = lp_to_writes("platanos,tag1=A val=42i 123456");
+        let err = handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect_err("request should fail");
+        assert!(err.is_limit_violation());
+        assert_matches!(err, SchemaError::TableLimit { table_name, count, limit, .. } => {
+            assert_eq!(table_name, "platanos");
+            assert_eq!(count, 1);
+            assert_eq!(limit, 1);
+        });
+        assert_limit_rejections(&metrics, "max_tables", 1);
+
+        // The table was never created in the catalog.
+        let ns = handler
+            .cache
+            .get_schema(&*NAMESPACE)
+            .expect("cache should be populated");
+        let tables = catalog
+            .repositories()
+            .await
+            .tables()
+            .list_by_namespace_id(ns.id)
+            .await
+            .expect("failed to list tables");
+        assert_eq!(tables.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_column_limit() {
+        let catalog = create_catalog().await;
+        catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_column_limit(NAMESPACE.as_str(), 3)
+            .await
+            .expect("failed to set column limit");
+
+        let metrics = Registry::default();
+        let handler = SchemaValidator::new(
+            catalog,
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metrics,
+        );
+
+        // tag1, val and time reach the limit.
+        let writes = lp_to_writes("bananas,tag1=A val=42i 123456");
+        handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect("request should succeed");
+
+        let writes = lp_to_writes("bananas,tag1=A,tag2=B val=42i 123456");
+        let err = handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect_err("request should fail");
+        assert!(err.is_limit_violation());
+        assert_matches!(err, SchemaError::ColumnLimit { table_name, column_name, count, limit, .. } => {
+            assert_eq!(table_name, "bananas");
+            assert_eq!(column_name, "tag2");
+            assert_eq!(count, 3);
+            assert_eq!(limit, 3);
+        });
+        assert_limit_rejections(&metrics, "max_columns_per_table", 1);
+
+        // Writes to existing columns are unaffected.
+        let writes = lp_to_writes("bananas,tag1=B val=42i 123456");
+        handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect("request should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_write_table_limit_stale_cache() {
+        let catalog = create_catalog().await;
+        let metrics = Registry::default();
+        let handler = SchemaValidator::new(
+            Arc::clone(&catalog),
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metrics,
+        );
+
+        let writes = lp_to_writes("bananas,tag1=A val=42i 123456");
+        handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect("request should succeed");
+
+        // Lower the limit after the schema was cached, so only the catalog
+        // observes the violation.
+        catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_table_limit(NAMESPACE.as_str(), 1)
+            .await
+            .expect("failed to set table limit");
+
+        let writes = lp_to_writes("platanos,tag1=A val=42i 123456");
+        let err = handler
+            .write(&*NAMESPACE, writes, None)
+            .await
+            .expect_err("request should fail");
+        assert!(err.is_limit_violation());
+        assert_matches!(
+            err,
+            SchemaError::Validate(iox_catalog::interface::Error::TableCreateLimitError { .. })
+        );
+        assert_limit_rejections(&metrics, "max_tables", 1);
+    }
+
     #[tokio::test]
     async fn test_write_delete_passthrough_ok() {
         const NAMESPACE: &str = "NAMESPACE_IS_NOT_VALIDATED";
         const TABLE: &str = "bananas";
 
         let catalog = create_catalog().await;
-        let handler = SchemaValidator::new(catalog, Arc::new(MemoryNamespaceCache::default()));
+        let handler = SchemaValidator::new(
+            catalog,
+            Arc::new(MemoryNamespaceCache::default()),
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        );
 
         let predicate = DeletePredicate {
             range: TimestampRange::new(1, 2),
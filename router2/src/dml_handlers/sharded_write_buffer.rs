@@ -1,22 +1,30 @@
 //! Logic to shard writes/deletes and push them into a write buffer sequencer.
 
 use super::Partitioned;
-use crate::{dml_handlers::DmlHandler, sequencer::Sequencer, sharder::Sharder};
+use crate::{
+    dml_handlers::DmlHandler,
+    sequencer::Sequencer,
+    sharder::{BatchSharder, Sharder},
+};
 use async_trait::async_trait;
+use backoff::{Backoff, BackoffConfig};
 use data_types2::{DatabaseName, DeletePredicate, NonEmptyString};
 use dml::{DmlDelete, DmlMeta, DmlOperation, DmlWrite};
 use futures::{stream::FuturesUnordered, StreamExt};
 use hashbrown::HashMap;
+use metric::U64Counter;
 use mutable_batch::MutableBatch;
 use observability_deps::tracing::*;
 use std::{
     fmt::{Debug, Display},
     future,
     sync::Arc,
+    time::Duration,
 };
 use thiserror::Error;
+use tokio::time::Instant;
 use trace::ctx::SpanContext;
-use write_buffer::core::WriteBufferError;
+use write_buffer::core::{WriteBufferError, WriteBufferErrorKind};
 
 /// Errors occurring while writing to one or more write buffer shards.
 #[derive(Debug, Error)]
@@ -47,35 +55,136 @@ where
         .join("; ")
 }
 
+/// Configuration of the retries of failed write buffer enqueues.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// The maximum number of times a failed enqueue to a single shard is
+    /// retried.
+    pub max_retries: usize,
+
+    /// The time after the start of a request beyond which no further retries
+    /// are started.
+    pub deadline: Duration,
+
+    /// The backoff between retries.
+    pub backoff: BackoffConfig,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            deadline: Duration::from_secs(5),
+            backoff: BackoffConfig {
+                init_backoff: Duration::from_millis(50),
+                max_backoff: Duration::from_secs(1),
+                base: 3.,
+            },
+        }
+    }
+}
+
+/// The retry configuration and metrics of a [`ShardedWriteBuffer`].
+#[derive(Debug)]
+struct RetryPolicy {
+    config: RetryConfig,
+
+    /// The number of retried shard enqueues.
+    retries: U64Counter,
+    /// The number of shard enqueues that failed after exhausting all retries,
+    /// or with an error that is not retried.
+    failures: U64Counter,
+}
+
+/// The set of [`Sequencer`] a [`Sharder`] maps a delete to.
+///
+/// A delete must reach every sequencer a write for the same table may have
+/// been routed to, which may be more than one.
+pub trait SequencerSet: Debug + Send + Sync {
+    /// Return the sequencers in this set.
+    fn sequencers(&self) -> &[Arc<Sequencer>];
+}
+
+impl SequencerSet for Arc<Sequencer> {
+    fn sequencers(&self) -> &[Arc<Sequencer>] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl SequencerSet for [Arc<Sequencer>] {
+    fn sequencers(&self) -> &[Arc<Sequencer>] {
+        self
+    }
+}
+
 /// A [`ShardedWriteBuffer`] combines a [`Sequencer`] with a [`Sharder`], using
 /// the latter to split writes (and deletes) up into per-shard [`DmlOperation`]
 /// instances and dispatching them to the write buffer.
 ///
-/// Writes are batched per-shard, producing one op per shard, per write. For a
-/// single write, all shards are wrote to in parallel.
+/// Writes are batched per-shard, producing one op per shard, per write. The
+/// rows of a single table may be split over multiple shards by the
+/// [`BatchSharder`]. For a single write, all shards are wrote to in parallel.
 ///
 /// The buffering / async return behaviour of the methods on this type are
 /// defined by the behaviour of the underlying [write buffer] implementation.
 ///
+/// # Retries
+///
+/// When configured [with retries](Self::with_retries), an enqueue failing with
+/// a retryable (I/O) error is retried with a backoff, independently for each
+/// shard - ops successfully enqueued to other shards are never sent again. A
+/// request stops retrying once the configured number of retries is exhausted,
+/// or when waiting for the next retry would exceed the request deadline.
+///
 /// [write buffer]: write_buffer::core::WriteBufferWriting
 #[derive(Debug)]
 pub struct ShardedWriteBuffer<S> {
     sharder: S,
+    retry: Option<Arc<RetryPolicy>>,
 }
 
 impl<S> ShardedWriteBuffer<S> {
     /// Construct a [`ShardedWriteBuffer`] using the specified [`Sharder`]
     /// implementation.
     pub fn new(sharder: S) -> Self {
-        Self { sharder }
+        Self {
+            sharder,
+            retry: None,
+        }
+    }
+
+    /// Retry failed enqueues according to `config`, recording the number of
+    /// retries and final failures to `metrics`.
+    pub fn with_retries(self, config: RetryConfig, metrics: &metric::Registry) -> Self {
+        let retries = metrics
+            .register_metric::<U64Counter>(
+                "sharded_write_buffer_enqueue_retries",
+                "number of retried write buffer enqueues",
+            )
+            .recorder([]);
+        let failures = metrics
+            .register_metric::<U64Counter>(
+                "sharded_write_buffer_enqueue_failures",
+                "number of write buffer enqueues that failed after any retries",
+            )
+            .recorder([]);
+
+        Self {
+            retry: Some(Arc::new(RetryPolicy {
+                config,
+                retries,
+                failures,
+            })),
+            ..self
+        }
     }
 }
 
 #[async_trait]
 impl<S> DmlHandler for ShardedWriteBuffer<S>
 where
-    S: Sharder<MutableBatch, Item = Arc<Sequencer>>
-        + Sharder<DeletePredicate, Item = Arc<Sequencer>>,
+    S: Sharder<MutableBatch, Item = Arc<Sequencer>> + BatchSharder + Sharder<DeletePredicate>,
+    <S as Sharder<DeletePredicate>>::Item: SequencerSet,
 {
     type WriteError = ShardError;
     type DeleteError = ShardError;
@@ -99,18 +208,19 @@ where
         // per shard to maximise the size of each write, and therefore increase
         // the effectiveness of compression of ops in the write buffer.
         for (table, batch) in writes.into_iter() {
-            let sequencer = Arc::clone(self.sharder.shard(&table, namespace, &batch));
-
-            let existing = collated
-                .entry(sequencer)
-                .or_default()
-                .insert(table, batch.clone());
+            for (sequencer, batch) in self.sharder.shard_batch(&table, namespace, batch) {
+                let existing = collated
+                    .entry(Arc::clone(sequencer))
+                    .or_default()
+                    .insert(table.clone(), batch);
 
-            assert!(existing.is_none());
+                assert!(existing.is_none());
+            }
         }
 
         let iter = collated.into_iter().map(|(sequencer, batch)| {
-            let dml = DmlWrite::new(namespace, batch, DmlMeta::unsequenced(span_ctx.clone()));
+            let dml = DmlWrite::new(namespace, batch, DmlMeta::unsequenced(span_ctx.clone()))
+                .with_partition_key(partition_key.to_string());
 
             trace!(
                 %partition_key,
@@ -124,7 +234,7 @@ where
             (sequencer, DmlOperation::from(dml))
         });
 
-        parallel_enqueue(iter).await
+        parallel_enqueue(iter, self.retry.clone()).await
     }
 
     /// Shard `predicate` and dispatch it to the appropriate shard.
@@ -135,43 +245,47 @@ where
         predicate: &DeletePredicate,
         span_ctx: Option<SpanContext>,
     ) -> Result<(), ShardError> {
-        let predicate = predicate.clone();
-        let sequencer = self.sharder.shard(table_name, namespace, &predicate);
-
-        trace!(sequencer_id=%sequencer.id(), %table_name, %namespace, "routing delete to shard");
+        let sequencers = self
+            .sharder
+            .shard(table_name, namespace, predicate)
+            .sequencers();
 
-        let dml = DmlDelete::new(
+        let dml = DmlOperation::from(DmlDelete::new(
             namespace,
-            predicate,
+            predicate.clone(),
             NonEmptyString::new(table_name),
             DmlMeta::unsequenced(span_ctx),
-        );
+        ));
 
-        sequencer
-            .enqueue(DmlOperation::from(dml))
-            .await
-            .map_err(|e| ShardError::WriteBufferErrors {
-                successes: 0,
-                errs: vec![e],
-            })?;
+        let iter = sequencers.iter().map(|sequencer| {
+            trace!(sequencer_id=%sequencer.id(), %table_name, %namespace, "routing delete to shard");
+            (Arc::clone(sequencer), dml.clone())
+        });
 
-        Ok(())
+        parallel_enqueue(iter, self.retry.clone()).await
     }
 }
 
 /// Enumerates all items in the iterator, maps each to a future that dispatches
-/// the [`DmlOperation`] to its paired [`Sequencer`], executes all the futures
-/// in parallel and gathers any errors.
-async fn parallel_enqueue<T>(v: T) -> Result<(), ShardError>
+/// the [`DmlOperation`] to its paired [`Sequencer`] (retrying according to
+/// `retry`), executes all the futures in parallel and gathers any errors.
+async fn parallel_enqueue<T>(v: T, retry: Option<Arc<RetryPolicy>>) -> Result<(), ShardError>
 where
     T: Iterator<Item = (Arc<Sequencer>, DmlOperation)> + Send,
 {
+    let deadline = retry.as_ref().map(|r| Instant::now() + r.config.deadline);
+
     let mut successes = 0;
     let errs = v
-        .map(|(sequencer, op)| async move {
-            tokio::spawn(async move { sequencer.enqueue(op).await })
+        .map(|(sequencer, op)| {
+            let retry = retry.clone();
+            async move {
+                tokio::spawn(async move {
+                    enqueue_with_retry(&sequencer, &op, retry.as_deref(), deadline).await
+                })
                 .await
                 .expect("shard enqueue panic")
+            }
         })
         .collect::<FuturesUnordered<_>>()
         .filter_map(|v| {
@@ -189,17 +303,152 @@ where
     }
 }
 
+/// Enqueue `op` into `sequencer`, retrying retryable errors according to
+/// `retry` until `deadline`.
+async fn enqueue_with_retry(
+    sequencer: &Sequencer,
+    op: &DmlOperation,
+    retry: Option<&RetryPolicy>,
+    deadline: Option<Instant>,
+) -> Result<DmlMeta, WriteBufferError> {
+    let (retry, deadline) = match (retry, deadline) {
+        (Some(r), Some(d)) => (r, d),
+        _ => return sequencer.enqueue(op).await,
+    };
+
+    let mut backoff = Backoff::new(&retry.config.backoff);
+    let mut attempt = 0;
+    loop {
+        let e = match sequencer.enqueue(op).await {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+
+        let delay = backoff.next_backoff();
+        if !is_retryable(&e)
+            || attempt >= retry.config.max_retries
+            || Instant::now() + delay > deadline
+        {
+            retry.failures.inc(1);
+            return Err(e);
+        }
+
+        attempt += 1;
+        retry.retries.inc(1);
+        warn!(
+            error=%e,
+            sequencer_id=%sequencer.id(),
+            attempt,
+            backoff_ms=%delay.as_millis(),
+            "write buffer enqueue failed, retrying"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Returns true if an enqueue that failed with `e` may succeed when retried.
+///
+/// I/O errors (such as a Kafka leader election in progress) are transient,
+/// while invalid ops are rejected again on every attempt.
+fn is_retryable(e: &WriteBufferError) -> bool {
+    matches!(e.kind(), WriteBufferErrorKind::IO)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         dml_handlers::DmlHandler,
-        sharder::mock::{MockSharder, MockSharderCall},
+        sharder::{
+            mock::{MockSharder, MockSharderCall},
+            HashRing, ShardConfig, TableOverride, TableShard,
+        },
     };
     use assert_matches::assert_matches;
     use data_types2::TimestampRange;
-    use std::sync::Arc;
-    use write_buffer::mock::{MockBufferForWriting, MockBufferSharedState};
+    use metric::{Attributes, Metric};
+    use std::{
+        collections::BTreeSet,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+    use write_buffer::{
+        core::WriteBufferWriting,
+        mock::{MockBufferForWriting, MockBufferSharedState},
+    };
+
+    /// A [`WriteBufferWriting`] failing the first `failures` enqueues with an
+    /// error of `kind`, before passing calls through to `inner`.
+    #[derive(Debug)]
+    struct FlakyWriteBuffer {
+        inner: MockBufferForWriting,
+        failures: AtomicUsize,
+        kind: WriteBufferErrorKind,
+    }
+
+    impl FlakyWriteBuffer {
+        fn new(inner: MockBufferForWriting, failures: usize, kind: WriteBufferErrorKind) -> Self {
+            Self {
+                inner,
+                failures: AtomicUsize::new(failures),
+                kind,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl WriteBufferWriting for FlakyWriteBuffer {
+        fn sequencer_ids(&self) -> BTreeSet<u32> {
+            self.inner.sequencer_ids()
+        }
+
+        async fn store_operation(
+            &self,
+            sequencer_id: u32,
+            operation: &DmlOperation,
+        ) -> Result<DmlMeta, WriteBufferError> {
+            let fail = self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| v.checked_sub(1))
+                .is_ok();
+            if fail {
+                return Err(WriteBufferError::new(self.kind, "leader election"));
+            }
+            self.inner.store_operation(sequencer_id, operation).await
+        }
+
+        async fn flush(&self) -> Result<(), WriteBufferError> {
+            self.inner.flush().await
+        }
+
+        fn type_name(&self) -> &'static str {
+            "flaky"
+        }
+    }
+
+    fn test_retry_config(max_retries: usize) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            deadline: Duration::from_secs(10),
+            backoff: BackoffConfig {
+                init_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                base: 1.5,
+            },
+        }
+    }
+
+    fn assert_counter(metrics: &metric::Registry, name: &'static str, want: u64) {
+        let got = metrics
+            .get_instrument::<Metric<U64Counter>>(name)
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[]))
+            .expect("failed to get observer")
+            .fetch();
+        assert_eq!(got, want, "unexpected value for {}", name);
+    }
 
     // Parse `lp` into a table-keyed MutableBatch map.
     fn lp_to_writes(lp: &str) -> Partitioned<HashMap<String, MutableBatch>> {
@@ -458,4 +707,204 @@ mod tests {
             assert_eq!(*d.predicate(), predicate);
         });
     }
+
+    #[tokio::test]
+    async fn test_write_retry_success() {
+        let writes = lp_to_writes(
+            "\
+                bananas,tag1=A,tag2=B val=42i 123456\n\
+                platanos,tag1=A,tag2=B value=42i 123456\n\
+            ",
+        );
+
+        // The first shard always succeeds.
+        let write_buffer1 = init_write_buffer(1);
+        let write_buffer1_state = write_buffer1.state();
+        let shard1 = Arc::new(Sequencer::new(
+            0,
+            Arc::new(write_buffer1),
+            &Default::default(),
+        ));
+
+        // The second shard fails twice with a transient error.
+        let write_buffer2 = init_write_buffer(1);
+        let write_buffer2_state = write_buffer2.state();
+        let shard2 = Arc::new(Sequencer::new(
+            0,
+            Arc::new(FlakyWriteBuffer::new(
+                write_buffer2,
+                2,
+                WriteBufferErrorKind::IO,
+            )),
+            &Default::default(),
+        ));
+
+        let sharder = Arc::new(
+            MockSharder::default().with_return([Arc::clone(&shard1), Arc::clone(&shard2)]),
+        );
+
+        let metrics = metric::Registry::default();
+        let w = ShardedWriteBuffer::new(Arc::clone(&sharder))
+            .with_retries(test_retry_config(3), &metrics);
+
+        let ns = DatabaseName::new("bananas").unwrap();
+        w.write(&ns, writes, None)
+            .await
+            .expect("write should succeed");
+
+        // Each shard observes exactly one write - the successful shard is not
+        // written to again while the failing shard is retried.
+        assert_eq!(write_buffer1_state.get_messages(0).len(), 1);
+        assert_eq!(write_buffer2_state.get_messages(0).len(), 1);
+
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_retries", 2);
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_failures", 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_retries_exhausted() {
+        let writes = lp_to_writes("bananas,tag1=A,tag2=B val=42i 123456");
+
+        let write_buffer = init_write_buffer(1);
+        let write_buffer_state = write_buffer.state();
+        let shard = Arc::new(Sequencer::new(
+            0,
+            Arc::new(FlakyWriteBuffer::new(
+                write_buffer,
+                10,
+                WriteBufferErrorKind::IO,
+            )),
+            &Default::default(),
+        ));
+        let sharder = Arc::new(MockSharder::default().with_return([Arc::clone(&shard)]));
+
+        let metrics = metric::Registry::default();
+        let w = ShardedWriteBuffer::new(Arc::clone(&sharder))
+            .with_retries(test_retry_config(2), &metrics);
+
+        let ns = DatabaseName::new("bananas").unwrap();
+        let err = w
+            .write(&ns, writes, None)
+            .await
+            .expect_err("write should fail");
+        assert_matches!(err, ShardError::WriteBufferErrors{successes, errs} => {
+            assert_eq!(errs.len(), 1);
+            assert_eq!(successes, 0);
+        });
+        assert!(write_buffer_state.get_messages(0).is_empty());
+
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_retries", 2);
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_failures", 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_non_retryable_error() {
+        let writes = lp_to_writes("bananas,tag1=A,tag2=B val=42i 123456");
+
+        let shard = Arc::new(Sequencer::new(
+            0,
+            Arc::new(FlakyWriteBuffer::new(
+                init_write_buffer(1),
+                1,
+                WriteBufferErrorKind::InvalidInput,
+            )),
+            &Default::default(),
+        ));
+        let sharder = Arc::new(MockSharder::default().with_return([Arc::clone(&shard)]));
+
+        let metrics = metric::Registry::default();
+        let w = ShardedWriteBuffer::new(Arc::clone(&sharder))
+            .with_retries(test_retry_config(3), &metrics);
+
+        let ns = DatabaseName::new("bananas").unwrap();
+        w.write(&ns, writes, None)
+            .await
+            .expect_err("write should fail");
+
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_retries", 0);
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_failures", 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_retry_deadline() {
+        let writes = lp_to_writes("bananas,tag1=A,tag2=B val=42i 123456");
+
+        let shard = Arc::new(Sequencer::new(
+            0,
+            Arc::new(FlakyWriteBuffer::new(
+                init_write_buffer(1),
+                1,
+                WriteBufferErrorKind::IO,
+            )),
+            &Default::default(),
+        ));
+        let sharder = Arc::new(MockSharder::default().with_return([Arc::clone(&shard)]));
+
+        // The first backoff exceeds the request deadline.
+        let metrics = metric::Registry::default();
+        let config = RetryConfig {
+            deadline: Duration::from_millis(1),
+            backoff: BackoffConfig {
+                init_backoff: Duration::from_secs(10),
+                max_backoff: Duration::from_secs(10),
+                base: 1.5,
+            },
+            ..test_retry_config(3)
+        };
+        let w = ShardedWriteBuffer::new(Arc::clone(&sharder)).with_retries(config, &metrics);
+
+        let ns = DatabaseName::new("bananas").unwrap();
+        w.write(&ns, writes, None)
+            .await
+            .expect_err("write should fail");
+
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_retries", 0);
+        assert_counter(&metrics, "sharded_write_buffer_enqueue_failures", 1);
+    }
+
+    #[tokio::test]
+    async fn test_shard_delete_spread_table() {
+        let write_buffer = init_write_buffer(3);
+        let write_buffer_state = write_buffer.state();
+        let write_buffer: Arc<dyn WriteBufferWriting> = Arc::new(write_buffer);
+
+        let shards = (0..3).map(|id| {
+            (
+                id,
+                Arc::new(Sequencer::new(
+                    id,
+                    Arc::clone(&write_buffer),
+                    &Default::default(),
+                )),
+            )
+        });
+        let config = ShardConfig {
+            table_overrides: vec![TableOverride {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                shard: TableShard::Spread {
+                    tag: "host".to_string(),
+                    count: 2,
+                },
+            }],
+            ..Default::default()
+        };
+        let w = ShardedWriteBuffer::new(HashRing::new(shards, &config).unwrap());
+
+        let predicate = DeletePredicate {
+            range: TimestampRange::new(1, 2),
+            exprs: vec![],
+        };
+        let ns = DatabaseName::new("bananas").unwrap();
+        w.delete(&ns, "cpu", &predicate, None)
+            .await
+            .expect("delete failed");
+
+        // The delete reaches both sequencers the table is spread over.
+        let got = (0..3)
+            .map(|id| write_buffer_state.get_messages(id).len())
+            .collect::<Vec<_>>();
+        assert_eq!(got.iter().sum::<usize>(), 2);
+        assert!(got.iter().all(|&v| v <= 1));
+    }
 }
@@ -4,7 +4,9 @@ use std::{error::Error, fmt::Debug, sync::Arc};
 use thiserror::Error;
 use trace::ctx::SpanContext;
 
-use super::{partitioner::PartitionError, NamespaceCreationError, SchemaError, ShardError};
+use super::{
+    partitioner::PartitionError, NamespaceCreationError, RateLimitError, SchemaError, ShardError,
+};
 
 /// Errors emitted by a [`DmlHandler`] implementation during DML request
 /// processing.
@@ -30,6 +32,10 @@ pub enum DmlError {
     #[error(transparent)]
     Partition(#[from] PartitionError),
 
+    /// The request was rejected by admission control.
+    #[error(transparent)]
+    RateLimited(#[from] RateLimitError),
+
     /// An unknown error occured while processing the DML request.
     #[error("internal dml handler error: {0}")]
     Internal(Box<dyn Error + Send + Sync>),
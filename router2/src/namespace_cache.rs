@@ -6,6 +6,15 @@ pub use memory::*;
 mod sharded_cache;
 pub use sharded_cache::*;
 
+mod lru;
+pub use lru::*;
+
+mod deletion_pruner;
+pub use deletion_pruner::*;
+
+mod refresher;
+pub use refresher::*;
+
 pub mod metrics;
 
 use data_types2::{DatabaseName, NamespaceSchema};
@@ -23,5 +32,42 @@ pub trait NamespaceCache: Debug + Send + Sync {
         &self,
         namespace: DatabaseName<'static>,
         schema: impl Into<Arc<NamespaceSchema>>,
-    ) -> Option<Arc<NamespaceSchema>>;
+    ) -> Option<Arc<NamespaceSchema>>
+    where
+        Self: Sized;
+
+    /// Remove the [`NamespaceSchema`] mapped to `namespace`, returning it if
+    /// it was cached.
+    fn remove_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>>;
+
+    /// Remove the [`NamespaceSchema`] mapped to `namespace` to make room for
+    /// other entries, returning it if it was cached.
+    ///
+    /// Defaults to [`remove_schema`](Self::remove_schema) - this method exists
+    /// to allow decorators to tell evictions apart from removals.
+    fn evict_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        self.remove_schema(namespace)
+    }
+
+    /// Replace the cached [`NamespaceSchema`] mapped to `namespace` with
+    /// `schema` reloaded from the catalog, returning the previous value, if
+    /// any.
+    ///
+    /// Unlike [`put_schema`](Self::put_schema), a refresh does not count as a
+    /// use of the entry, and size-bounded implementations MAY discard the
+    /// refresh of a namespace that is no longer cached. Defaults to
+    /// [`put_schema`](Self::put_schema).
+    fn refresh_schema(
+        &self,
+        namespace: DatabaseName<'static>,
+        schema: impl Into<Arc<NamespaceSchema>>,
+    ) -> Option<Arc<NamespaceSchema>>
+    where
+        Self: Sized,
+    {
+        self.put_schema(namespace, schema)
+    }
+
+    /// Return the names of all cached namespaces.
+    fn namespaces(&self) -> Vec<DatabaseName<'static>>;
 }
@@ -0,0 +1,286 @@
+use super::NamespaceCache;
+use data_types2::{DatabaseName, NamespaceId, Timestamp};
+use iox_catalog::interface::Catalog;
+use observability_deps::tracing::*;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Removes namespaces that were soft-deleted in the catalog, or that contain a
+/// soft-deleted table, from a [`NamespaceCache`].
+///
+/// A cached [`NamespaceSchema`] allows writes to be validated without
+/// consulting the catalog, so a deletion goes unnoticed by the router for as
+/// long as the affected namespace is cached. Once removed, the next write to
+/// the namespace reloads the schema from the catalog, which no longer contains
+/// the deleted namespace / table.
+///
+/// Only deletions newer than the latest one seen by a previous run (minus an
+/// overlap, see [`DEFAULT_WATERMARK_OVERLAP`]) are listed, so the cost of a
+/// run does not grow with the number of deleted namespaces and tables.
+///
+/// [`NamespaceSchema`]: data_types2::NamespaceSchema
+#[derive(Debug)]
+pub struct DeletionPruner<C> {
+    catalog: Arc<dyn Catalog>,
+    cache: C,
+
+    /// The most recent `deleted_at` timestamp seen so far.
+    watermark: Option<Timestamp>,
+    watermark_overlap: Duration,
+}
+
+/// How far behind the watermark deletions are listed again.
+///
+/// The `deleted_at` timestamps are taken from the clocks of the deleting
+/// nodes and a deletion may become visible after a newer one committed, so
+/// deletions slightly older than the newest one seen are not necessarily
+/// handled yet. Pruning is idempotent, listing a deletion twice is harmless.
+pub const DEFAULT_WATERMARK_OVERLAP: Duration = Duration::from_secs(60);
+
+impl<C> DeletionPruner<C>
+where
+    C: NamespaceCache,
+{
+    /// Initialise a [`DeletionPruner`] removing deleted entries from `cache`.
+    pub fn new(catalog: Arc<dyn Catalog>, cache: C) -> Self {
+        Self {
+            catalog,
+            cache,
+            watermark: None,
+            watermark_overlap: DEFAULT_WATERMARK_OVERLAP,
+        }
+    }
+
+    /// Override the [`DEFAULT_WATERMARK_OVERLAP`].
+    pub fn with_watermark_overlap(self, watermark_overlap: Duration) -> Self {
+        Self {
+            watermark_overlap,
+            ..self
+        }
+    }
+
+    /// Remove the entries deleted since the last call from the cache, returning
+    /// the number of removed namespaces.
+    pub async fn prune(&mut self) -> Result<usize, iox_catalog::interface::Error> {
+        let mut repos = self.catalog.repositories().await;
+        let mut removed = 0;
+
+        let overlap = i64::try_from(self.watermark_overlap.as_nanos()).unwrap_or(i64::MAX);
+        let deleted_after = self
+            .watermark
+            .map(|w| Timestamp::new(w.get().saturating_sub(overlap)));
+
+        let namespaces = repos.namespaces().list_soft_deleted(deleted_after).await?;
+        let tables = repos.tables().list_soft_deleted(deleted_after).await?;
+
+        // Only advance the watermark once both lists were fetched, so a failed
+        // run is retried in full.
+        let newest = namespaces
+            .iter()
+            .filter_map(|n| n.deleted_at)
+            .chain(tables.iter().filter_map(|t| t.deleted_at))
+            .max();
+        self.watermark = self.watermark.max(newest);
+
+        for namespace in namespaces {
+            let name = match DatabaseName::new(namespace.name.as_str()) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            // The name of a deleted namespace may have been reused, only evict
+            // the cached schema if it belongs to the deleted namespace.
+            let is_deleted = self
+                .cache
+                .get_schema(&name)
+                .map(|schema| schema.id == namespace.id)
+                .unwrap_or_default();
+            if is_deleted && self.cache.remove_schema(&name).is_some() {
+                debug!(namespace=%name, "removed deleted namespace from cache");
+                removed += 1;
+            }
+        }
+
+        let mut namespace_names: HashMap<NamespaceId, Option<String>> = HashMap::new();
+        for table in tables {
+            let namespace_name = match namespace_names.entry(table.namespace_id) {
+                Entry::Occupied(v) => v.into_mut(),
+                Entry::Vacant(v) => {
+                    let namespace = repos.namespaces().get_by_id(table.namespace_id).await?;
+                    v.insert(namespace.map(|n| n.name))
+                }
+            };
+            let name = match namespace_name.as_deref().map(DatabaseName::new) {
+                Some(Ok(v)) => v,
+                _ => continue,
+            };
+
+            // Only evict the namespace if the cached schema still contains the
+            // deleted table.
+            let contains_table = self
+                .cache
+                .get_schema(&name)
+                .map(|schema| schema.tables.get(&table.name).map(|t| t.id) == Some(table.id))
+                .unwrap_or_default();
+            if contains_table && self.cache.remove_schema(&name).is_some() {
+                debug!(
+                    namespace=%name,
+                    table=%table.name,
+                    "removed namespace with deleted table from cache"
+                );
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Call [`prune`](Self::prune) every `interval`, forever.
+    pub async fn run(mut self, interval: Duration) {
+        loop {
+            if let Err(e) = self.prune().await {
+                warn!(error=%e, "failed to prune deleted namespaces from cache");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespace_cache::MemoryNamespaceCache;
+    use data_types2::{KafkaTopicId, NamespaceSchema, QueryPoolId, TableSchema};
+    use iox_catalog::mem::MemCatalog;
+
+    #[tokio::test]
+    async fn test_prune() {
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(MemCatalog::new(Arc::new(metric::Registry::default())));
+        let cache = Arc::new(MemoryNamespaceCache::default());
+        let mut pruner = DeletionPruner::new(Arc::clone(&catalog), Arc::clone(&cache));
+
+        let mut repos = catalog.repositories().await;
+        let mut cached = vec![];
+        for name in ["deleted_ns", "deleted_table", "live"] {
+            let namespace = repos
+                .namespaces()
+                .create(name, "inf", KafkaTopicId::new(1), QueryPoolId::new(1))
+                .await
+                .unwrap();
+            let table = repos
+                .tables()
+                .create_or_get("bananas", namespace.id)
+                .await
+                .unwrap();
+
+            let mut schema = NamespaceSchema::new(
+                namespace.id,
+                namespace.kafka_topic_id,
+                namespace.query_pool_id,
+            );
+            schema
+                .tables
+                .insert(table.name.clone(), TableSchema::new(table.id));
+
+            let name = DatabaseName::new(name).unwrap();
+            cache.put_schema(name.clone(), schema);
+            cached.push((name, namespace, table));
+        }
+        drop(repos);
+
+        // nothing to prune
+        assert_eq!(pruner.prune().await.unwrap(), 0);
+
+        let mut repos = catalog.repositories().await;
+        repos
+            .namespaces()
+            .soft_delete(&cached[0].1.name)
+            .await
+            .unwrap();
+        repos.tables().soft_delete(cached[1].2.id).await.unwrap();
+        drop(repos);
+
+        assert_eq!(pruner.prune().await.unwrap(), 2);
+        assert!(cache.get_schema(&cached[0].0).is_none());
+        assert!(cache.get_schema(&cached[1].0).is_none());
+        assert!(cache.get_schema(&cached[2].0).is_some());
+
+        // a reloaded schema without the deleted table is kept
+        let namespace = &cached[1].1;
+        cache.put_schema(
+            cached[1].0.clone(),
+            NamespaceSchema::new(
+                namespace.id,
+                namespace.kafka_topic_id,
+                namespace.query_pool_id,
+            ),
+        );
+        assert_eq!(pruner.prune().await.unwrap(), 0);
+        assert!(cache.get_schema(&cached[1].0).is_some());
+
+        // a namespace recreated with the name of the deleted one is kept
+        let mut repos = catalog.repositories().await;
+        let recreated = repos
+            .namespaces()
+            .create(
+                "deleted_ns",
+                "inf",
+                KafkaTopicId::new(1),
+                QueryPoolId::new(1),
+            )
+            .await
+            .unwrap();
+        drop(repos);
+        assert_ne!(recreated.id, cached[0].1.id);
+        cache.put_schema(
+            cached[0].0.clone(),
+            NamespaceSchema::new(
+                recreated.id,
+                recreated.kafka_topic_id,
+                recreated.query_pool_id,
+            ),
+        );
+        assert_eq!(pruner.prune().await.unwrap(), 0);
+        assert!(cache.get_schema(&cached[0].0).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prune_watermark() {
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(MemCatalog::new(Arc::new(metric::Registry::default())));
+        let cache = Arc::new(MemoryNamespaceCache::default());
+        let mut pruner = DeletionPruner::new(Arc::clone(&catalog), Arc::clone(&cache))
+            .with_watermark_overlap(Duration::ZERO);
+
+        let mut repos = catalog.repositories().await;
+        let namespace = repos
+            .namespaces()
+            .create("bananas", "inf", KafkaTopicId::new(1), QueryPoolId::new(1))
+            .await
+            .unwrap();
+        repos.namespaces().soft_delete("bananas").await.unwrap();
+        let deleted = repos.namespaces().list_soft_deleted(None).await.unwrap();
+        drop(repos);
+        let schema = || {
+            NamespaceSchema::new(
+                namespace.id,
+                namespace.kafka_topic_id,
+                namespace.query_pool_id,
+            )
+        };
+        let name = DatabaseName::new("bananas").unwrap();
+
+        cache.put_schema(name.clone(), schema());
+        assert_eq!(pruner.prune().await.unwrap(), 1);
+        assert_eq!(pruner.watermark, deleted[0].deleted_at);
+
+        // the deletion was handled already and is not listed again
+        cache.put_schema(name.clone(), schema());
+        assert_eq!(pruner.prune().await.unwrap(), 0);
+        assert!(cache.get_schema(&name).is_some());
+    }
+}
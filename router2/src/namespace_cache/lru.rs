@@ -0,0 +1,252 @@
+use super::NamespaceCache;
+use data_types2::{DatabaseName, NamespaceSchema};
+use observability_deps::tracing::*;
+use parking_lot::RwLock;
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// A namespace tracked by a [`LruNamespaceCache`].
+#[derive(Debug)]
+struct Entry {
+    name: DatabaseName<'static>,
+    /// The [`LruNamespaceCache`] clock value of the last use of `name`.
+    last_used: AtomicU64,
+}
+
+/// A decorator bounding the number of namespaces held in the inner
+/// [`NamespaceCache`] `T`, evicting the least recently used namespace when a
+/// put would exceed the configured capacity.
+///
+/// Both [`get_schema`](NamespaceCache::get_schema) hits and
+/// [`put_schema`](NamespaceCache::put_schema) calls count as a use of a
+/// namespace, while [`refresh_schema`](NamespaceCache::refresh_schema) does not
+/// (and is discarded for namespaces that are no longer cached).
+///
+/// The recency order is approximate: a use stamps the namespace with an atomic
+/// clock value under a shared lock, so concurrent reads never serialise, and
+/// the exclusive lock is only taken by mutations. Racing uses may be recorded
+/// slightly out of order, and finding the eviction candidate is a linear scan
+/// of the tracked namespaces, paid only by puts that exceed the capacity.
+///
+/// Evicted namespaces are removed from `T` using
+/// [`evict_schema`](NamespaceCache::evict_schema), and are reloaded from the
+/// catalog by the next write that needs them.
+///
+/// All mutations of `T` must be made through this decorator, otherwise the
+/// tracked namespaces and the contents of `T` diverge.
+#[derive(Debug)]
+pub struct LruNamespaceCache<T> {
+    inner: T,
+    capacity: NonZeroUsize,
+    /// A monotonically increasing counter, bumped on every use.
+    clock: AtomicU64,
+    /// The tracked namespaces, keyed by name.
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl<T> LruNamespaceCache<T> {
+    /// Bound `inner` to at most `capacity` namespaces.
+    pub fn new(inner: T, capacity: NonZeroUsize) -> Self {
+        Self {
+            inner,
+            capacity,
+            clock: AtomicU64::new(0),
+            entries: Default::default(),
+        }
+    }
+
+    /// Return the number of namespaces in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    /// Returns true if the cache contains no namespaces.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+impl<T> NamespaceCache for Arc<LruNamespaceCache<T>>
+where
+    T: NamespaceCache,
+{
+    fn get_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        if let Some(entry) = self.entries.read().get(namespace.as_str()) {
+            entry.last_used.store(self.tick(), Ordering::Relaxed);
+        }
+
+        // Misses are passed through to the inner cache too, allowing an inner
+        // decorator to observe them.
+        self.inner.get_schema(namespace)
+    }
+
+    fn put_schema(
+        &self,
+        namespace: DatabaseName<'static>,
+        schema: impl Into<Arc<NamespaceSchema>>,
+    ) -> Option<Arc<NamespaceSchema>> {
+        // The lock is held while modifying the inner cache so that a racing
+        // eviction cannot leave an untracked entry in the inner cache.
+        let mut entries = self.entries.write();
+        let now = self.tick();
+        match entries.get_mut(namespace.as_str()) {
+            Some(entry) => *entry.last_used.get_mut() = now,
+            None => {
+                entries.insert(
+                    namespace.to_string(),
+                    Entry {
+                        name: namespace.clone(),
+                        last_used: AtomicU64::new(now),
+                    },
+                );
+            }
+        }
+
+        while entries.len() > self.capacity.get() {
+            let victim = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used.load(Ordering::Relaxed))
+                .map(|(key, _)| key.clone())
+                .expect("over-capacity cache must have an entry to evict");
+            let victim = entries.remove(&victim).unwrap().name;
+            debug!(namespace=%victim, "evicting namespace from cache");
+            self.inner.evict_schema(&victim);
+        }
+
+        self.inner.put_schema(namespace, schema)
+    }
+
+    fn remove_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        let mut entries = self.entries.write();
+        entries.remove(namespace.as_str());
+        self.inner.remove_schema(namespace)
+    }
+
+    fn evict_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        let mut entries = self.entries.write();
+        entries.remove(namespace.as_str());
+        self.inner.evict_schema(namespace)
+    }
+
+    fn refresh_schema(
+        &self,
+        namespace: DatabaseName<'static>,
+        schema: impl Into<Arc<NamespaceSchema>>,
+    ) -> Option<Arc<NamespaceSchema>> {
+        // Do not resurrect a namespace evicted since the refresh started. The
+        // shared lock prevents a concurrent eviction, while still allowing
+        // concurrent reads.
+        let entries = self.entries.read();
+        if !entries.contains_key(namespace.as_str()) {
+            return None;
+        }
+
+        self.inner.refresh_schema(namespace, schema)
+    }
+
+    fn namespaces(&self) -> Vec<DatabaseName<'static>> {
+        self.entries
+            .read()
+            .values()
+            .map(|entry| entry.name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespace_cache::MemoryNamespaceCache;
+    use data_types2::{KafkaTopicId, NamespaceId, QueryPoolId};
+
+    fn schema_with_id(id: i32) -> NamespaceSchema {
+        NamespaceSchema::new(
+            NamespaceId::new(id),
+            KafkaTopicId::new(1),
+            QueryPoolId::new(1),
+        )
+    }
+
+    fn name(v: &str) -> DatabaseName<'static> {
+        DatabaseName::new(v.to_string()).expect("valid database name")
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let inner = Arc::new(MemoryNamespaceCache::default());
+        let cache = Arc::new(LruNamespaceCache::new(
+            Arc::clone(&inner),
+            NonZeroUsize::new(2).unwrap(),
+        ));
+
+        assert!(cache.put_schema(name("a"), schema_with_id(1)).is_none());
+        assert!(cache.put_schema(name("b"), schema_with_id(2)).is_none());
+        assert_eq!(cache.len(), 2);
+
+        // Use "a", making "b" the least recently used entry.
+        assert!(cache.get_schema(&name("a")).is_some());
+
+        assert!(cache.put_schema(name("c"), schema_with_id(3)).is_none());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_schema(&name("b")).is_none());
+        assert!(inner.get_schema(&name("b")).is_none());
+        assert_eq!(*cache.get_schema(&name("a")).unwrap(), schema_with_id(1));
+        assert_eq!(*cache.get_schema(&name("c")).unwrap(), schema_with_id(3));
+
+        // Overwriting an existing entry does not evict anything.
+        assert!(cache.put_schema(name("c"), schema_with_id(4)).is_some());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_schema(&name("a")).is_some());
+
+        let mut namespaces = cache.namespaces();
+        namespaces.sort_unstable();
+        assert_eq!(namespaces, vec![name("a"), name("c")]);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let cache = Arc::new(LruNamespaceCache::new(
+            Arc::new(MemoryNamespaceCache::default()),
+            NonZeroUsize::new(2).unwrap(),
+        ));
+
+        cache.put_schema(name("a"), schema_with_id(1));
+        cache.put_schema(name("b"), schema_with_id(2));
+
+        // Refreshing "a" replaces the schema without marking it as used, so it
+        // remains the eviction candidate.
+        assert!(cache.refresh_schema(name("a"), schema_with_id(5)).is_some());
+        cache.put_schema(name("c"), schema_with_id(3));
+        assert!(cache.get_schema(&name("a")).is_none());
+        assert_eq!(*cache.get_schema(&name("b")).unwrap(), schema_with_id(2));
+
+        // Refreshing an uncached namespace is discarded.
+        assert!(cache.refresh_schema(name("a"), schema_with_id(5)).is_none());
+        assert!(cache.get_schema(&name("a")).is_none());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_remove() {
+        let cache = Arc::new(LruNamespaceCache::new(
+            Arc::new(MemoryNamespaceCache::default()),
+            NonZeroUsize::new(1).unwrap(),
+        ));
+
+        cache.put_schema(name("a"), schema_with_id(1));
+        assert_eq!(*cache.remove_schema(&name("a")).unwrap(), schema_with_id(1));
+        assert!(cache.is_empty());
+        assert!(cache.get_schema(&name("a")).is_none());
+        assert!(cache.remove_schema(&name("a")).is_none());
+    }
+}
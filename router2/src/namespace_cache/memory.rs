@@ -23,12 +23,22 @@ impl NamespaceCache for Arc<MemoryNamespaceCache> {
     ) -> Option<Arc<NamespaceSchema>> {
         self.cache.write().insert(namespace, schema.into())
     }
+
+    fn remove_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        self.cache.write().remove(namespace)
+    }
+
+    fn namespaces(&self) -> Vec<DatabaseName<'static>> {
+        self.cache.read().keys().cloned().collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use data_types2::{KafkaTopicId, NamespaceId, QueryPoolId};
+    use data_types2::{
+        KafkaTopicId, NamespaceId, QueryPoolId, DEFAULT_MAX_COLUMNS_PER_TABLE, DEFAULT_MAX_TABLES,
+    };
 
     #[test]
     fn test_put_get() {
@@ -42,6 +52,10 @@ mod tests {
             kafka_topic_id: KafkaTopicId::new(24),
             query_pool_id: QueryPoolId::new(1234),
             tables: Default::default(),
+            retention_period: None,
+            partition_template: None,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
         };
         assert!(cache.put_schema(ns.clone(), schema1.clone()).is_none());
         assert_eq!(*cache.get_schema(&ns).expect("lookup failure"), schema1);
@@ -51,6 +65,10 @@ mod tests {
             kafka_topic_id: KafkaTopicId::new(2),
             query_pool_id: QueryPoolId::new(2),
             tables: Default::default(),
+            retention_period: None,
+            partition_template: None,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
         };
 
         assert_eq!(
@@ -60,5 +78,16 @@ mod tests {
             schema1
         );
         assert_eq!(*cache.get_schema(&ns).expect("lookup failure"), schema2);
+        assert_eq!(cache.namespaces(), vec![ns.clone()]);
+
+        assert_eq!(
+            *cache
+                .remove_schema(&ns)
+                .expect("should have existing schema"),
+            schema2
+        );
+        assert!(cache.get_schema(&ns).is_none());
+        assert!(cache.remove_schema(&ns).is_none());
+        assert!(cache.namespaces().is_empty());
     }
 }
@@ -2,21 +2,28 @@
 
 use super::NamespaceCache;
 use data_types2::{DatabaseName, NamespaceSchema};
-use metric::{Metric, U64Gauge, U64Histogram, U64HistogramOptions};
+use metric::{Metric, U64Counter, U64Gauge, U64Histogram, U64HistogramOptions};
 use std::sync::Arc;
 use time::{SystemProvider, TimeProvider};
 
 /// An [`InstrumentedCache`] decorates a [`NamespaceCache`] with cache read
-/// hit/miss and cache put insert/update metrics.
+/// hit/miss and cache put insert/update metrics, and counts the number of
+/// cached namespaces, evictions and refreshes.
 #[derive(Debug)]
 pub struct InstrumentedCache<T, P = SystemProvider> {
     inner: T,
     time_provider: P,
 
     /// Metrics derived from the [`NamespaceSchema`] held within the cache.
+    namespace_count: U64Gauge,
     table_count: U64Gauge,
     column_count: U64Gauge,
 
+    /// Namespaces evicted to bound the size of the cache.
+    evictions: U64Counter,
+    /// Cached namespaces replaced with a schema reloaded from the catalog.
+    refreshes: U64Counter,
+
     /// A cache read hit
     get_hit: U64Histogram,
     /// A cache read miss
@@ -51,6 +58,12 @@ impl<T> InstrumentedCache<T> {
         let put_insert = put_counter.recorder(&[("op", "insert")]);
         let put_update = put_counter.recorder(&[("op", "update")]);
 
+        let namespace_count = registry
+            .register_metric::<U64Gauge>(
+                "namespace_cache_namespace_count",
+                "number of namespaces in the cache",
+            )
+            .recorder([]);
         let table_count = registry
             .register_metric::<U64Gauge>(
                 "namespace_cache_table_count",
@@ -64,11 +77,27 @@ impl<T> InstrumentedCache<T> {
             )
             .recorder([]);
 
+        let evictions = registry
+            .register_metric::<U64Counter>(
+                "namespace_cache_evictions",
+                "number of namespaces evicted to bound the size of the cache",
+            )
+            .recorder([]);
+        let refreshes = registry
+            .register_metric::<U64Counter>(
+                "namespace_cache_refreshes",
+                "number of cached namespaces replaced with a schema reloaded from the catalog",
+            )
+            .recorder([]);
+
         Self {
             inner,
             time_provider: Default::default(),
+            namespace_count,
             table_count,
             column_count,
+            evictions,
+            refreshes,
             get_hit,
             get_miss,
             put_insert,
@@ -109,37 +138,87 @@ where
         let t = self.time_provider.now();
         let res = self.inner.put_schema(namespace, schema);
 
-        match res {
-            Some(v) => {
-                if let Some(delta) = self.time_provider.now().checked_duration_since(t) {
-                    self.put_update.record(delta.as_millis() as _);
-                }
+        if let Some(delta) = self.time_provider.now().checked_duration_since(t) {
+            match res {
+                Some(_) => self.put_update.record(delta.as_millis() as _),
+                None => self.put_insert.record(delta.as_millis() as _),
+            }
+        }
+
+        self.record_replaced(&stats, res.as_deref());
+
+        res
+    }
+
+    fn remove_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        let res = self.inner.remove_schema(namespace);
+        self.record_removed(res.as_deref());
+        res
+    }
+
+    fn evict_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        let res = self.inner.evict_schema(namespace);
+        if res.is_some() {
+            self.evictions.inc(1);
+        }
+        self.record_removed(res.as_deref());
+        res
+    }
+
+    fn refresh_schema(
+        &self,
+        namespace: DatabaseName<'static>,
+        schema: impl Into<Arc<NamespaceSchema>>,
+    ) -> Option<Arc<NamespaceSchema>> {
+        let schema = schema.into();
+        let stats = NamespaceStats::new(&*schema);
+
+        let res = self.inner.refresh_schema(namespace, schema);
+        self.refreshes.inc(1);
+        self.record_replaced(&stats, res.as_deref());
 
+        res
+    }
+
+    fn namespaces(&self) -> Vec<DatabaseName<'static>> {
+        self.inner.namespaces()
+    }
+}
+
+impl<T, P> InstrumentedCache<T, P> {
+    /// Adjust the gauges to reflect a namespace with `stats` replacing `old`,
+    /// or being inserted if there was no `old` namespace.
+    fn record_replaced(&self, stats: &NamespaceStats, old: Option<&NamespaceSchema>) {
+        match old {
+            Some(v) => {
                 // Figure out the difference between the new namespace and the
                 // evicted old namespace
-                let old_stats = NamespaceStats::new(&*v);
+                let old_stats = NamespaceStats::new(v);
                 let table_count_diff = stats.table_count as i64 - old_stats.table_count as i64;
                 let column_count_diff = stats.column_count as i64 - old_stats.column_count as i64;
 
                 // Adjust the metrics to reflect the change
                 self.table_count.delta(table_count_diff);
                 self.column_count.delta(column_count_diff);
-
-                Some(v)
             }
             None => {
-                if let Some(delta) = self.time_provider.now().checked_duration_since(t) {
-                    self.put_insert.record(delta.as_millis() as _);
-                }
-
                 // Add the new namespace stats to the counts.
+                self.namespace_count.inc(1);
                 self.table_count.inc(stats.table_count);
                 self.column_count.inc(stats.column_count);
-
-                None
             }
         }
     }
+
+    /// Remove the stats of the `removed` namespace (if any) from the counts.
+    fn record_removed(&self, removed: Option<&NamespaceSchema>) {
+        if let Some(v) = removed {
+            let stats = NamespaceStats::new(v);
+            self.namespace_count.dec(1);
+            self.table_count.dec(stats.table_count);
+            self.column_count.dec(stats.column_count);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -165,7 +244,7 @@ mod tests {
     use crate::namespace_cache::MemoryNamespaceCache;
     use data_types2::{
         ColumnId, ColumnSchema, ColumnType, KafkaTopicId, NamespaceId, QueryPoolId, TableId,
-        TableSchema,
+        TableSchema, DEFAULT_MAX_COLUMNS_PER_TABLE, DEFAULT_MAX_TABLES,
     };
     use metric::{Attributes, MetricObserver, Observation};
     use std::collections::BTreeMap;
@@ -195,6 +274,7 @@ mod tests {
                     TableSchema {
                         id: TableId::new(i as _),
                         columns,
+                        partition_template: None,
                     },
                 )
             })
@@ -205,6 +285,10 @@ mod tests {
             kafka_topic_id: KafkaTopicId::new(24),
             query_pool_id: QueryPoolId::new(1234),
             tables,
+            retention_period: None,
+            partition_template: None,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
         }
     }
 
@@ -387,5 +471,51 @@ mod tests {
             ("result", "hit"),
             1,
         );
+
+        // Remove a namespace
+        assert!(cache.remove_schema(&ns).is_some());
+        assert_eq!(cache.table_count.observe(), Observation::U64Gauge(2));
+        assert_eq!(cache.column_count.observe(), Observation::U64Gauge(11));
+        assert!(cache.remove_schema(&ns).is_none());
+        assert_eq!(cache.table_count.observe(), Observation::U64Gauge(2));
+        assert_eq!(cache.namespace_count.observe(), Observation::U64Gauge(1));
+    }
+
+    #[test]
+    fn test_evict_refresh() {
+        let ns = DatabaseName::new("test").expect("database name is valid");
+        let registry = metric::Registry::default();
+        let cache = Arc::new(MemoryNamespaceCache::default());
+        let cache = Arc::new(InstrumentedCache::new(cache, &registry));
+
+        // Refreshing an uncached namespace inserts it.
+        assert!(cache.refresh_schema(ns.clone(), new_schema(&[1])).is_none());
+        assert_eq!(cache.refreshes.observe(), Observation::U64Counter(1));
+        assert_eq!(cache.namespace_count.observe(), Observation::U64Gauge(1));
+        assert_eq!(cache.table_count.observe(), Observation::U64Gauge(1));
+        assert_eq!(cache.column_count.observe(), Observation::U64Gauge(1));
+
+        // Refreshing a cached namespace replaces it, without recording a put.
+        assert!(cache
+            .refresh_schema(ns.clone(), new_schema(&[3, 2]))
+            .is_some());
+        assert_eq!(cache.refreshes.observe(), Observation::U64Counter(2));
+        assert_eq!(cache.namespace_count.observe(), Observation::U64Gauge(1));
+        assert_eq!(cache.table_count.observe(), Observation::U64Gauge(2));
+        assert_eq!(cache.column_count.observe(), Observation::U64Gauge(5));
+        assert_histogram_hit(
+            &registry,
+            "namespace_cache_put_duration_ms",
+            ("op", "update"),
+            0,
+        );
+
+        // Evictions are counted separately from removals.
+        assert!(cache.evict_schema(&ns).is_some());
+        assert!(cache.evict_schema(&ns).is_none());
+        assert_eq!(cache.evictions.observe(), Observation::U64Counter(1));
+        assert_eq!(cache.namespace_count.observe(), Observation::U64Gauge(0));
+        assert_eq!(cache.table_count.observe(), Observation::U64Gauge(0));
+        assert_eq!(cache.column_count.observe(), Observation::U64Gauge(0));
     }
 }
@@ -0,0 +1,155 @@
+use super::NamespaceCache;
+use data_types2::DatabaseName;
+use futures::{future, StreamExt};
+use iox_catalog::interface::{get_schema_by_name, Catalog, Error};
+use observability_deps::tracing::*;
+use std::{num::NonZeroUsize, ops::DerefMut, sync::Arc, time::Duration};
+
+/// The default number of namespaces reloaded from the catalog concurrently.
+const DEFAULT_CONCURRENCY: usize = 10;
+
+/// Periodically replaces the [`NamespaceSchema`] of every namespace in a
+/// [`NamespaceCache`] with the current schema in the catalog.
+///
+/// The router only learns of schema changes it makes itself - changes made by
+/// other router instances, or by an operator (such as a raised table limit),
+/// are only observed once the cached schema is reloaded. Namespaces that no
+/// longer exist in the catalog are removed from the cache.
+///
+/// Each namespace is loaded independently - a failure to load one namespace is
+/// logged and leaves its cached schema unchanged until the next refresh.
+///
+/// [`NamespaceSchema`]: data_types2::NamespaceSchema
+#[derive(Debug)]
+pub struct SchemaRefresher<C> {
+    catalog: Arc<dyn Catalog>,
+    cache: C,
+    concurrency: NonZeroUsize,
+}
+
+impl<C> SchemaRefresher<C>
+where
+    C: NamespaceCache,
+{
+    /// Initialise a [`SchemaRefresher`] reloading the entries of `cache` from
+    /// `catalog`.
+    pub fn new(catalog: Arc<dyn Catalog>, cache: C) -> Self {
+        Self {
+            catalog,
+            cache,
+            concurrency: NonZeroUsize::new(DEFAULT_CONCURRENCY).unwrap(),
+        }
+    }
+
+    /// Load at most `concurrency` namespaces from the catalog at any one time.
+    pub fn with_concurrency(self, concurrency: NonZeroUsize) -> Self {
+        Self {
+            concurrency,
+            ..self
+        }
+    }
+
+    /// Reload all the cached namespaces from the catalog, returning the number
+    /// of refreshed namespaces.
+    pub async fn refresh(&self) -> usize {
+        futures::stream::iter(self.cache.namespaces())
+            .map(|name| self.refresh_namespace(name))
+            .buffer_unordered(self.concurrency.get())
+            .filter(|refreshed| future::ready(*refreshed))
+            .count()
+            .await
+    }
+
+    /// Reload the schema of `name` from the catalog, returning true if the
+    /// cached schema was refreshed.
+    async fn refresh_namespace(&self, name: DatabaseName<'static>) -> bool {
+        let mut repos = self.catalog.repositories().await;
+        match get_schema_by_name(&name, repos.deref_mut()).await {
+            Ok(schema) => {
+                self.cache.refresh_schema(name, schema);
+                true
+            }
+            Err(Error::NamespaceNotFound { .. }) => {
+                debug!(namespace=%name, "removing unknown namespace from cache");
+                self.cache.remove_schema(&name);
+                false
+            }
+            Err(e) => {
+                warn!(error=%e, namespace=%name, "failed to refresh cached namespace schema");
+                false
+            }
+        }
+    }
+
+    /// Call [`refresh`](Self::refresh) every `interval`, forever.
+    pub async fn run(self, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+            let refreshed = self.refresh().await;
+            debug!(refreshed, "refreshed namespace cache");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespace_cache::MemoryNamespaceCache;
+    use data_types2::{KafkaTopicId, NamespaceSchema, QueryPoolId};
+    use iox_catalog::mem::MemCatalog;
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(MemCatalog::new(Arc::new(metric::Registry::default())));
+        let cache = Arc::new(MemoryNamespaceCache::default());
+        let refresher = SchemaRefresher::new(Arc::clone(&catalog), Arc::clone(&cache));
+
+        // nothing to refresh
+        assert_eq!(refresher.refresh().await, 0);
+
+        let mut repos = catalog.repositories().await;
+        let namespace = repos
+            .namespaces()
+            .create("bananas", "inf", KafkaTopicId::new(1), QueryPoolId::new(1))
+            .await
+            .unwrap();
+        let name = DatabaseName::new("bananas").unwrap();
+        let stale = NamespaceSchema::new(
+            namespace.id,
+            namespace.kafka_topic_id,
+            namespace.query_pool_id,
+        );
+        cache.put_schema(name.clone(), stale);
+
+        // A table created by another router is not in the cached schema.
+        repos
+            .tables()
+            .create_or_get("platanos", namespace.id)
+            .await
+            .unwrap();
+        drop(repos);
+        assert!(cache.get_schema(&name).unwrap().tables.is_empty());
+
+        assert_eq!(refresher.refresh().await, 1);
+        assert!(cache
+            .get_schema(&name)
+            .unwrap()
+            .tables
+            .contains_key("platanos"));
+
+        // A namespace that does not exist in the catalog is removed.
+        let unknown = DatabaseName::new("unknown").unwrap();
+        cache.put_schema(
+            unknown.clone(),
+            NamespaceSchema::new(
+                namespace.id,
+                namespace.kafka_topic_id,
+                namespace.query_pool_id,
+            ),
+        );
+        assert_eq!(refresher.refresh().await, 1);
+        assert!(cache.get_schema(&unknown).is_none());
+        assert!(cache.get_schema(&name).is_some());
+    }
+}
@@ -34,13 +34,41 @@ where
     ) -> Option<Arc<NamespaceSchema>> {
         self.shards.hash(&namespace).put_schema(namespace, schema)
     }
+
+    fn remove_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        self.shards.hash(namespace).remove_schema(namespace)
+    }
+
+    fn evict_schema(&self, namespace: &DatabaseName<'_>) -> Option<Arc<NamespaceSchema>> {
+        self.shards.hash(namespace).evict_schema(namespace)
+    }
+
+    fn refresh_schema(
+        &self,
+        namespace: DatabaseName<'static>,
+        schema: impl Into<Arc<NamespaceSchema>>,
+    ) -> Option<Arc<NamespaceSchema>> {
+        self.shards
+            .hash(&namespace)
+            .refresh_schema(namespace, schema)
+    }
+
+    fn namespaces(&self) -> Vec<DatabaseName<'static>> {
+        self.shards
+            .shards()
+            .iter()
+            .flat_map(|shard| shard.namespaces())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::namespace_cache::MemoryNamespaceCache;
-    use data_types2::{KafkaTopicId, NamespaceId, QueryPoolId};
+    use data_types2::{
+        KafkaTopicId, NamespaceId, QueryPoolId, DEFAULT_MAX_COLUMNS_PER_TABLE, DEFAULT_MAX_TABLES,
+    };
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
     use std::{collections::HashMap, iter};
 
@@ -60,6 +88,10 @@ mod tests {
             kafka_topic_id: KafkaTopicId::new(1),
             query_pool_id: QueryPoolId::new(1),
             tables: Default::default(),
+            retention_period: None,
+            partition_template: None,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_columns_per_table: DEFAULT_MAX_COLUMNS_PER_TABLE,
         }
     }
 
@@ -95,9 +127,23 @@ mod tests {
         }
 
         // The mapping should be stable
+        for (name, id) in names.iter() {
+            let want = schema_with_id(*id as _);
+            assert_eq!(cache.get_schema(name), Some(Arc::new(want)));
+        }
+
+        // All the namespaces are listed across the shards
+        let mut listed = cache.namespaces();
+        listed.sort_unstable();
+        let mut want = names.keys().cloned().collect::<Vec<_>>();
+        want.sort_unstable();
+        assert_eq!(listed, want);
+
+        // Removals are routed to the same shard
         for (name, id) in names {
             let want = schema_with_id(id as _);
-            assert_eq!(cache.get_schema(&name), Some(Arc::new(want)));
+            assert_eq!(cache.remove_schema(&name), Some(Arc::new(want)));
+            assert!(cache.get_schema(&name).is_none());
         }
     }
 }
@@ -72,10 +72,10 @@ impl Sequencer {
     /// The buffering / async return behaviour of this method is defined by the
     /// behaviour of the [`WriteBufferWriting::store_operation()`]
     /// implementation this [`Sequencer`] wraps.
-    pub async fn enqueue<'a>(&self, op: DmlOperation) -> Result<DmlMeta, WriteBufferError> {
+    pub async fn enqueue(&self, op: &DmlOperation) -> Result<DmlMeta, WriteBufferError> {
         let t = self.time_provider.now();
 
-        let res = self.inner.store_operation(self.id as u32, &op).await;
+        let res = self.inner.store_operation(self.id as u32, op).await;
 
         if let Some(delta) = self.time_provider.now().checked_duration_since(t) {
             match &res {
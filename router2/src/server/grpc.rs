@@ -2,12 +2,16 @@
 
 use std::sync::Arc;
 
+use data_types2::{DatabaseName, PartitionTemplate};
 use generated_types::{
-    google::FieldViolation,
-    influxdata::{iox::schema::v1::*, pbdata::v1::*},
+    google::{FieldViolation, ResourceExhausted},
+    influxdata::{
+        iox::{namespace::v1 as namespace, schema::v1::*},
+        pbdata::v1::*,
+    },
 };
 use hashbrown::HashMap;
-use iox_catalog::interface::{get_schema_by_name, Catalog};
+use iox_catalog::interface::{get_schema_by_name, Catalog, RepoCollection};
 use metric::U64Counter;
 use mutable_batch::MutableBatch;
 use observability_deps::tracing::*;
@@ -16,7 +20,10 @@ use std::ops::DerefMut;
 use tonic::{Request, Response, Status};
 use trace::ctx::SpanContext;
 
-use crate::dml_handlers::{DmlError, DmlHandler, PartitionError};
+use crate::{
+    dml_handlers::{DmlError, DmlHandler, PartitionError},
+    namespace_cache::NamespaceCache,
+};
 
 /// This type is responsible for managing all gRPC services exposed by
 /// `router2`.
@@ -24,20 +31,29 @@ use crate::dml_handlers::{DmlError, DmlHandler, PartitionError};
 pub struct GrpcDelegate<D> {
     dml_handler: Arc<D>,
     catalog: Arc<dyn Catalog>,
+    ns_cache: Arc<dyn NamespaceCache>,
     metrics: Arc<metric::Registry>,
 }
 
 impl<D> GrpcDelegate<D> {
     /// Initialise a new gRPC handler, dispatching DML operations to
     /// `dml_handler`.
-    pub fn new(
+    ///
+    /// Namespaces updated through the namespace gRPC service are removed from
+    /// `ns_cache`, so that the update is observed by subsequent writes.
+    pub fn new<C>(
         dml_handler: Arc<D>,
         catalog: Arc<dyn Catalog>,
+        ns_cache: C,
         metrics: Arc<metric::Registry>,
-    ) -> Self {
+    ) -> Self
+    where
+        C: NamespaceCache + 'static,
+    {
         Self {
             dml_handler,
             catalog,
+            ns_cache: Arc::new(ns_cache),
             metrics,
         }
     }
@@ -69,6 +85,20 @@ where
             &self.catalog,
         )))
     }
+
+    /// Acquire a [`NamespaceService`] gRPC service implementation.
+    ///
+    /// [`NamespaceService`]: generated_types::influxdata::iox::namespace::v1::namespace_service_server::NamespaceService.
+    pub fn namespace_service(
+        &self,
+    ) -> namespace::namespace_service_server::NamespaceServiceServer<
+        impl namespace::namespace_service_server::NamespaceService,
+    > {
+        namespace::namespace_service_server::NamespaceServiceServer::new(NamespaceService::new(
+            Arc::clone(&self.catalog),
+            Arc::clone(&self.ns_cache),
+        ))
+    }
 }
 
 #[derive(Debug)]
@@ -164,7 +194,18 @@ where
             .await
             .map_err(|e| match e.into() {
                 e @ DmlError::DatabaseNotFound(_) => Status::not_found(e.to_string()),
+                DmlError::Schema(e) if e.is_namespace_not_found() => {
+                    Status::not_found(e.to_string())
+                }
+                DmlError::Schema(e) if e.is_limit_violation() => {
+                    Status::failed_precondition(e.to_string())
+                }
                 e @ DmlError::Schema(_) => Status::aborted(e.to_string()),
+                DmlError::RateLimited(e) => ResourceExhausted {
+                    description: e.to_string(),
+                    retry_after: Some(e.retry_after()),
+                }
+                .into(),
 
                 e @ (DmlError::Internal(_)
                 | DmlError::WriteBuffer(_)
@@ -250,18 +291,318 @@ fn schema_to_proto(schema: Arc<data_types2::NamespaceSchema>) -> GetSchemaRespon
     response
 }
 
+/// Explicit management of the namespaces in the [`Catalog`].
+#[derive(Debug)]
+struct NamespaceService {
+    /// Catalog.
+    catalog: Arc<dyn Catalog>,
+
+    /// The namespace cache of this router, invalidated when a namespace is
+    /// updated.
+    ns_cache: Arc<dyn NamespaceCache>,
+}
+
+impl NamespaceService {
+    fn new(catalog: Arc<dyn Catalog>, ns_cache: Arc<dyn NamespaceCache>) -> Self {
+        Self { catalog, ns_cache }
+    }
+}
+
+#[tonic::async_trait]
+impl namespace::namespace_service_server::NamespaceService for NamespaceService {
+    async fn create_namespace(
+        &self,
+        request: Request<namespace::CreateNamespaceRequest>,
+    ) -> Result<Response<namespace::CreateNamespaceResponse>, Status> {
+        let req = request.into_inner();
+
+        let name = DatabaseName::try_from(req.name).map_err(|e| FieldViolation {
+            field: "name".into(),
+            description: format!("Invalid namespace name: {}", e),
+        })?;
+        check_limit("max_tables", req.max_tables)?;
+        check_limit("max_columns_per_table", req.max_columns_per_table)?;
+
+        let mut txn = self
+            .catalog
+            .start_transaction()
+            .await
+            .map_err(catalog_error_to_status)?;
+
+        let kafka_topic_id = kafka_topic_id(txn.as_mut(), &req.kafka_topic).await?;
+        let query_pool_id = query_pool_id(txn.as_mut(), &req.query_pool).await?;
+
+        let mut ns = txn
+            .namespaces()
+            .create(
+                &name,
+                req.retention_duration.as_deref().unwrap_or("inf"),
+                kafka_topic_id,
+                query_pool_id,
+            )
+            .await
+            .map_err(catalog_error_to_status)?;
+        if let Some(max_tables) = req.max_tables {
+            ns = txn
+                .namespaces()
+                .update_table_limit(&name, max_tables)
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+        if let Some(max_columns_per_table) = req.max_columns_per_table {
+            ns = txn
+                .namespaces()
+                .update_column_limit(&name, max_columns_per_table)
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+
+        txn.commit().await.map_err(catalog_error_to_status)?;
+
+        info!(namespace=%name, %req.kafka_topic, %req.query_pool, "created namespace");
+
+        Ok(Response::new(namespace::CreateNamespaceResponse {
+            namespace: Some(namespace_to_proto(ns)),
+        }))
+    }
+
+    async fn get_namespaces(
+        &self,
+        _request: Request<namespace::GetNamespacesRequest>,
+    ) -> Result<Response<namespace::GetNamespacesResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let namespaces = repos
+            .namespaces()
+            .list()
+            .await
+            .map_err(catalog_error_to_status)?;
+
+        Ok(Response::new(namespace::GetNamespacesResponse {
+            namespaces: namespaces.into_iter().map(namespace_to_proto).collect(),
+        }))
+    }
+
+    async fn get_namespace(
+        &self,
+        request: Request<namespace::GetNamespaceRequest>,
+    ) -> Result<Response<namespace::GetNamespaceResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let req = request.into_inner();
+        let ns = repos
+            .namespaces()
+            .get_by_name(&req.name)
+            .await
+            .map_err(catalog_error_to_status)?
+            .ok_or_else(|| Status::not_found(format!("namespace {} not found", req.name)))?;
+
+        Ok(Response::new(namespace::GetNamespaceResponse {
+            namespace: Some(namespace_to_proto(ns)),
+        }))
+    }
+
+    async fn update_namespace(
+        &self,
+        request: Request<namespace::UpdateNamespaceRequest>,
+    ) -> Result<Response<namespace::UpdateNamespaceResponse>, Status> {
+        let req = request.into_inner();
+        check_limit("max_tables", req.max_tables)?;
+        check_limit("max_columns_per_table", req.max_columns_per_table)?;
+
+        // A template without parts resets the namespace to the default template.
+        let partition_template = req
+            .partition_template
+            .map(|proto| match proto.parts.is_empty() {
+                true => Ok(None),
+                false => PartitionTemplate::try_from(proto)
+                    .map(Some)
+                    .map_err(|e| e.scope("partition_template")),
+            })
+            .transpose()?;
+
+        let mut txn = self
+            .catalog
+            .start_transaction()
+            .await
+            .map_err(catalog_error_to_status)?;
+
+        let mut ns = txn
+            .namespaces()
+            .get_by_name(&req.name)
+            .await
+            .map_err(catalog_error_to_status)?
+            .ok_or_else(|| Status::not_found(format!("namespace {} not found", req.name)))?;
+
+        // Operations already written to the current topic would not follow
+        // the namespace to a new one.
+        if let Some(kafka_topic) = &req.kafka_topic {
+            if kafka_topic_id(txn.as_mut(), kafka_topic).await? != ns.kafka_topic_id {
+                return Err(Status::failed_precondition(format!(
+                    "namespace {} cannot be moved to kafka topic {}: the kafka topic of a \
+                     namespace is fixed when it is created",
+                    req.name, kafka_topic
+                )));
+            }
+        }
+
+        if let Some(retention_duration) = &req.retention_duration {
+            ns = txn
+                .namespaces()
+                .update_retention_duration(&req.name, retention_duration)
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+        if let Some(query_pool) = &req.query_pool {
+            let query_pool_id = query_pool_id(txn.as_mut(), query_pool).await?;
+            ns = txn
+                .namespaces()
+                .update_query_pool(&req.name, query_pool_id)
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+        if let Some(max_tables) = req.max_tables {
+            ns = txn
+                .namespaces()
+                .update_table_limit(&req.name, max_tables)
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+        if let Some(max_columns_per_table) = req.max_columns_per_table {
+            ns = txn
+                .namespaces()
+                .update_column_limit(&req.name, max_columns_per_table)
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+        if let Some(partition_template) = &partition_template {
+            ns = txn
+                .namespaces()
+                .update_partition_template(&req.name, partition_template.as_ref())
+                .await
+                .map_err(catalog_error_to_status)?;
+        }
+
+        txn.commit().await.map_err(catalog_error_to_status)?;
+
+        // The cached schema carries the limits and partition template of the
+        // namespace - drop it so the next write reloads it from the catalog.
+        if let Ok(name) = DatabaseName::try_from(req.name.as_str()) {
+            self.ns_cache.remove_schema(&name);
+        }
+
+        info!(namespace=%req.name, "updated namespace");
+
+        Ok(Response::new(namespace::UpdateNamespaceResponse {
+            namespace: Some(namespace_to_proto(ns)),
+        }))
+    }
+}
+
+/// Resolve the ID of the existing kafka topic `name`.
+async fn kafka_topic_id<R>(repos: &mut R, name: &str) -> Result<data_types2::KafkaTopicId, Status>
+where
+    R: RepoCollection + ?Sized,
+{
+    repos
+        .kafka_topics()
+        .get_by_name(name)
+        .await
+        .map_err(catalog_error_to_status)?
+        .map(|topic| topic.id)
+        .ok_or_else(|| {
+            FieldViolation {
+                field: "kafka_topic".into(),
+                description: format!("no kafka topic named {:?} in catalog", name),
+            }
+            .into()
+        })
+}
+
+/// Resolve the ID of the query pool `name`, creating it if it does not exist.
+async fn query_pool_id<R>(repos: &mut R, name: &str) -> Result<data_types2::QueryPoolId, Status>
+where
+    R: RepoCollection + ?Sized,
+{
+    if name.is_empty() {
+        return Err(FieldViolation::required("query_pool").into());
+    }
+
+    repos
+        .query_pools()
+        .create_or_get(name)
+        .await
+        .map(|pool| pool.id)
+        .map_err(catalog_error_to_status)
+}
+
+/// Reject a non-positive table or column limit.
+fn check_limit(field: &str, limit: Option<i32>) -> Result<(), FieldViolation> {
+    match limit {
+        Some(v) if v <= 0 => Err(FieldViolation {
+            field: field.into(),
+            description: format!("limit must be positive, got {}", v),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn catalog_error_to_status(e: iox_catalog::interface::Error) -> Status {
+    use iox_catalog::interface::Error;
+
+    match e {
+        e @ Error::NameExists { .. } => Status::already_exists(e.to_string()),
+        e @ Error::NamespaceNotFound { .. } => Status::not_found(e.to_string()),
+        e @ (Error::InvalidRetentionDuration { .. }
+        | Error::InvalidPartitionTemplate { .. }
+        | Error::ForeignKeyViolation { .. }) => Status::invalid_argument(e.to_string()),
+        e => {
+            warn!(error=%e, "namespace catalog request failed");
+            Status::internal(e.to_string())
+        }
+    }
+}
+
+fn namespace_to_proto(ns: data_types2::Namespace) -> namespace::Namespace {
+    // Templates are validated when they are written, an unparseable template
+    // is reported as the default template, as it is by the router.
+    let partition_template = ns
+        .partition_template()
+        .ok()
+        .flatten()
+        .map(|t| t.template().clone().into());
+
+    namespace::Namespace {
+        id: ns.id.get(),
+        name: ns.name,
+        retention_duration: ns.retention_duration.unwrap_or_else(|| "inf".to_string()),
+        kafka_topic_id: ns.kafka_topic_id.get(),
+        query_pool_id: ns.query_pool_id.get() as i32,
+        max_tables: ns.max_tables,
+        max_columns_per_table: ns.max_columns_per_table,
+        partition_template,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use data_types2::ColumnType;
+    use data_types2::{ColumnType, KafkaTopicId, NamespaceId, NamespaceSchema, QueryPoolId};
     use generated_types::influxdata::{
-        iox::schema::v1::schema_service_server::SchemaService,
+        iox::{
+            management::v1 as management,
+            namespace::v1::namespace_service_server::NamespaceService,
+            schema::v1::schema_service_server::SchemaService,
+        },
         pbdata::v1::write_service_server::WriteService,
     };
     use iox_catalog::mem::MemCatalog;
 
-    use crate::dml_handlers::{mock::MockDmlHandler, DmlError};
+    use crate::{
+        dml_handlers::{mock::MockDmlHandler, DmlError, SchemaError},
+        namespace_cache::MemoryNamespaceCache,
+    };
 
     use super::*;
 
@@ -291,6 +632,7 @@ mod tests {
         let req = WriteRequest {
             database_batch: Some(DatabaseBatch {
                 database_name: "".to_owned(),
+                partition_key: Default::default(),
                 table_batches: vec![],
             }),
         };
@@ -313,6 +655,7 @@ mod tests {
         let req = WriteRequest {
             database_batch: Some(DatabaseBatch {
                 database_name: "bananas".to_owned(),
+                partition_key: Default::default(),
                 table_batches: vec![],
             }),
         };
@@ -334,6 +677,7 @@ mod tests {
         let req = WriteRequest {
             database_batch: Some(DatabaseBatch {
                 database_name: "bananas".to_owned(),
+                partition_key: Default::default(),
                 table_batches: vec![],
             }),
         };
@@ -347,6 +691,36 @@ mod tests {
         assert!(err.message().contains("nope"));
     }
 
+    #[tokio::test]
+    async fn test_write_limit_error() {
+        let metrics = Arc::new(metric::Registry::default());
+        let handler = Arc::new(MockDmlHandler::default().with_write_return([Err(
+            DmlError::Schema(SchemaError::TableLimit {
+                namespace: "bananas".to_string(),
+                table_name: "platanos".to_string(),
+                count: 1,
+                limit: 1,
+            }),
+        )]));
+        let grpc = super::WriteService::new(Arc::clone(&handler), &metrics);
+
+        let req = WriteRequest {
+            database_batch: Some(DatabaseBatch {
+                database_name: "bananas".to_owned(),
+                partition_key: Default::default(),
+                table_batches: vec![],
+            }),
+        };
+
+        let err = grpc
+            .write(Request::new(req))
+            .await
+            .expect_err("rpc request should fail");
+
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+        assert!(err.message().contains("limit of 1"));
+    }
+
     #[tokio::test]
     async fn test_schema() {
         // create a catalog and populate it with some test data, then drop the write lock
@@ -401,4 +775,208 @@ mod tests {
             vec![&"schema_test_column".to_string()]
         );
     }
+
+    #[tokio::test]
+    async fn test_namespace_service() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(metrics));
+        catalog
+            .repositories()
+            .await
+            .kafka_topics()
+            .create_or_get("franz")
+            .await
+            .unwrap();
+
+        let ns_cache = Arc::new(MemoryNamespaceCache::default());
+        let grpc =
+            super::NamespaceService::new(Arc::clone(&catalog), Arc::new(Arc::clone(&ns_cache)));
+
+        let created = grpc
+            .create_namespace(Request::new(namespace::CreateNamespaceRequest {
+                name: "bananas".to_string(),
+                retention_duration: Some("30d".to_string()),
+                kafka_topic: "franz".to_string(),
+                query_pool: "pool".to_string(),
+                max_tables: Some(42),
+                max_columns_per_table: None,
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .namespace
+            .expect("namespace should be Some()");
+        assert_eq!(created.name, "bananas");
+        assert_eq!(created.retention_duration, "30d");
+        assert_eq!(created.max_tables, 42);
+        assert_eq!(
+            created.max_columns_per_table,
+            data_types2::DEFAULT_MAX_COLUMNS_PER_TABLE
+        );
+
+        // Creating the namespace again fails.
+        let err = grpc
+            .create_namespace(Request::new(namespace::CreateNamespaceRequest {
+                name: "bananas".to_string(),
+                kafka_topic: "franz".to_string(),
+                query_pool: "pool".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("rpc request should fail");
+        assert_eq!(err.code(), tonic::Code::AlreadyExists);
+
+        // An unknown kafka topic is rejected, and no namespace is created.
+        let err = grpc
+            .create_namespace(Request::new(namespace::CreateNamespaceRequest {
+                name: "platanos".to_string(),
+                kafka_topic: "kafka".to_string(),
+                query_pool: "pool".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("rpc request should fail");
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err.message().contains("kafka"));
+
+        let namespaces = grpc
+            .get_namespaces(Request::new(namespace::GetNamespacesRequest {}))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .namespaces;
+        assert_eq!(namespaces, vec![created.clone()]);
+
+        // Updating the namespace removes it from the cache.
+        let bananas = DatabaseName::try_from("bananas").unwrap();
+        ns_cache.put_schema(
+            bananas.clone(),
+            NamespaceSchema::new(
+                NamespaceId::new(created.id),
+                KafkaTopicId::new(1),
+                QueryPoolId::new(1),
+            ),
+        );
+
+        let updated = grpc
+            .update_namespace(Request::new(namespace::UpdateNamespaceRequest {
+                name: "bananas".to_string(),
+                retention_duration: Some("inf".to_string()),
+                kafka_topic: Some("franz".to_string()),
+                query_pool: Some("pool2".to_string()),
+                max_columns_per_table: Some(24),
+                ..Default::default()
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .namespace
+            .expect("namespace should be Some()");
+        assert_eq!(updated.retention_duration, "inf");
+        assert_ne!(updated.query_pool_id, created.query_pool_id);
+        assert_eq!(updated.max_tables, 42);
+        assert_eq!(updated.max_columns_per_table, 24);
+        assert!(ns_cache.get_schema(&bananas).is_none());
+
+        let got = grpc
+            .get_namespace(Request::new(namespace::GetNamespaceRequest {
+                name: "bananas".to_string(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .namespace
+            .expect("namespace should be Some()");
+        assert_eq!(got, updated);
+        assert_eq!(got.partition_template, None);
+
+        // Set the partition template, and reset it to the default with an
+        // empty template.
+        let time_template = |format: &str| management::PartitionTemplate {
+            parts: vec![management::partition_template::Part {
+                part: Some(management::partition_template::part::Part::Time(
+                    format.to_string(),
+                )),
+            }],
+        };
+        for (template, want) in [
+            (time_template("%Y"), Some(time_template("%Y"))),
+            (management::PartitionTemplate::default(), None),
+        ] {
+            let updated = grpc
+                .update_namespace(Request::new(namespace::UpdateNamespaceRequest {
+                    name: "bananas".to_string(),
+                    partition_template: Some(template),
+                    ..Default::default()
+                }))
+                .await
+                .expect("rpc request should succeed")
+                .into_inner()
+                .namespace
+                .expect("namespace should be Some()");
+            assert_eq!(updated.partition_template, want);
+            assert_eq!(updated.max_columns_per_table, 24);
+        }
+
+        // An invalid template is rejected.
+        let err = grpc
+            .update_namespace(Request::new(namespace::UpdateNamespaceRequest {
+                name: "bananas".to_string(),
+                partition_template: Some(time_template("%Q")),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("rpc request should fail");
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        // Moving the namespace to another kafka topic is rejected, and
+        // nothing is updated.
+        catalog
+            .repositories()
+            .await
+            .kafka_topics()
+            .create_or_get("kafka")
+            .await
+            .unwrap();
+        let err = grpc
+            .update_namespace(Request::new(namespace::UpdateNamespaceRequest {
+                name: "bananas".to_string(),
+                kafka_topic: Some("kafka".to_string()),
+                max_tables: Some(7),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("rpc request should fail");
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+        assert!(err.message().contains("cannot be moved"));
+        let got = grpc
+            .get_namespace(Request::new(namespace::GetNamespaceRequest {
+                name: "bananas".to_string(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .namespace
+            .expect("namespace should be Some()");
+        assert_eq!(got.kafka_topic_id, created.kafka_topic_id);
+        assert_eq!(got.max_tables, 42);
+
+        let err = grpc
+            .update_namespace(Request::new(namespace::UpdateNamespaceRequest {
+                name: "bananas".to_string(),
+                max_tables: Some(0),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("rpc request should fail");
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let err = grpc
+            .get_namespace(Request::new(namespace::GetNamespaceRequest {
+                name: "platanos".to_string(),
+            }))
+            .await
+            .expect_err("rpc request should fail");
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
 }
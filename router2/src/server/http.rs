@@ -1,20 +1,28 @@
 //! HTTP service implementations for `router2`.
 
-use std::{str::Utf8Error, sync::Arc};
+use std::{str::Utf8Error, sync::Arc, time::Duration};
 
-use crate::dml_handlers::{DmlError, DmlHandler, PartitionError};
+use crate::{
+    dml_handlers::{DmlError, DmlHandler, PartitionError},
+    namespace_cache::NamespaceCache,
+};
 
 use bytes::{Bytes, BytesMut};
-use data_types2::{org_and_bucket_to_database, OrgBucketMappingError};
+use data_types2::{
+    db_and_rp_to_database, org_and_bucket_to_database, DatabaseName, OrgBucketMappingError,
+};
 use futures::StreamExt;
 use hashbrown::HashMap;
-use hyper::{header::CONTENT_ENCODING, Body, Method, Request, Response, StatusCode};
+use hyper::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    Body, Method, Request, Response, StatusCode,
+};
 use metric::U64Counter;
 use mutable_batch::MutableBatch;
 use mutable_batch_lp::LinesConverter;
 use observability_deps::tracing::*;
 use predicate::delete_predicate::{parse_delete_predicate, parse_http_delete_request};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::{SystemProvider, TimeProvider};
 use trace::ctx::SpanContext;
@@ -30,6 +38,10 @@ pub enum Error {
     #[error(transparent)]
     InvalidOrgBucket(#[from] OrgBucketError),
 
+    /// An error with the db/rp in an InfluxDB 1.x request.
+    #[error(transparent)]
+    InvalidDbRp(#[from] DbRpError),
+
     /// The request body content is not valid utf8.
     #[error("body content is not valid utf8: {0}")]
     NonUtf8Body(Utf8Error),
@@ -74,6 +86,7 @@ impl Error {
         match self {
             Error::NoHandler => StatusCode::NOT_FOUND,
             Error::InvalidOrgBucket(_) => StatusCode::BAD_REQUEST,
+            Error::InvalidDbRp(_) => StatusCode::BAD_REQUEST,
             Error::ClientHangup(_) => StatusCode::BAD_REQUEST,
             Error::InvalidGzip(_) => StatusCode::BAD_REQUEST,
             Error::NonUtf8ContentHeader(_) => StatusCode::BAD_REQUEST,
@@ -88,17 +101,28 @@ impl Error {
             Error::DmlHandler(err) => StatusCode::from(err),
         }
     }
+
+    /// Returns the duration the client should wait before retrying the
+    /// request, if the request was rejected due to load.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::DmlHandler(DmlError::RateLimited(e)) => Some(e.retry_after()),
+            _ => None,
+        }
+    }
 }
 
 impl From<&DmlError> for StatusCode {
     fn from(e: &DmlError) -> Self {
         match e {
             DmlError::DatabaseNotFound(_) => StatusCode::NOT_FOUND,
+            DmlError::Schema(e) if e.is_namespace_not_found() => StatusCode::NOT_FOUND,
             DmlError::Schema(_) => StatusCode::BAD_REQUEST,
             DmlError::Internal(_) | DmlError::WriteBuffer(_) | DmlError::NamespaceCreation(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             DmlError::Partition(PartitionError::BatchWrite(_)) => StatusCode::INTERNAL_SERVER_ERROR,
+            DmlError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 }
@@ -120,6 +144,23 @@ pub enum OrgBucketError {
     MappingFail(#[from] OrgBucketMappingError),
 }
 
+/// Errors returned when decoding the database / retention policy information
+/// from an InfluxDB 1.x HTTP request and deriving the database name from it.
+#[derive(Debug, Error)]
+pub enum DbRpError {
+    /// The request contains no db destination information.
+    #[error("no db destination provided")]
+    NotSpecified,
+
+    /// The request contains invalid parameters.
+    #[error("failed to deserialise db/rp/precision in request: {0}")]
+    DecodeFail(#[from] serde::de::value::Error),
+
+    /// The provided db/rp could not be converted into a database name.
+    #[error(transparent)]
+    MappingFail(#[from] OrgBucketMappingError),
+}
+
 #[derive(Debug, Deserialize)]
 enum Precision {
     #[serde(rename = "s")]
@@ -176,22 +217,119 @@ impl<T> TryFrom<&Request<T>> for WriteInfo {
     }
 }
 
+/// The timestamp precisions accepted by the InfluxDB 1.x write API.
+#[derive(Debug, Deserialize)]
+enum V1Precision {
+    #[serde(rename = "h")]
+    Hours,
+    #[serde(rename = "m")]
+    Minutes,
+    #[serde(rename = "s")]
+    Seconds,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "u", alias = "us")]
+    Microseconds,
+    #[serde(rename = "n", alias = "ns")]
+    Nanoseconds,
+}
+
+impl Default for V1Precision {
+    fn default() -> Self {
+        Self::Nanoseconds
+    }
+}
+
+impl V1Precision {
+    /// Returns the multiplier to convert to nanosecond timestamps
+    fn timestamp_base(&self) -> i64 {
+        match self {
+            V1Precision::Hours => 3_600_000_000_000,
+            V1Precision::Minutes => 60_000_000_000,
+            V1Precision::Seconds => 1_000_000_000,
+            V1Precision::Milliseconds => 1_000_000,
+            V1Precision::Microseconds => 1_000,
+            V1Precision::Nanoseconds => 1,
+        }
+    }
+}
+
+/// Database & retention policy identifiers for an InfluxDB 1.x write.
+///
+/// The `u` & `p` credential parameters are accepted, but ignored.
+#[derive(Debug, Deserialize)]
+pub struct V1WriteInfo {
+    db: String,
+    rp: Option<String>,
+
+    #[serde(default)]
+    precision: V1Precision,
+}
+
+impl<T> TryFrom<&Request<T>> for V1WriteInfo {
+    type Error = DbRpError;
+
+    fn try_from(req: &Request<T>) -> Result<Self, Self::Error> {
+        let query = req.uri().query().ok_or(DbRpError::NotSpecified)?;
+        let got: V1WriteInfo = serde_urlencoded::from_str(query)?;
+
+        // An empty db is not acceptable.
+        if got.db.is_empty() {
+            return Err(DbRpError::NotSpecified);
+        }
+
+        Ok(got)
+    }
+}
+
 /// This type is responsible for servicing requests to the `router2` HTTP
 /// endpoint.
 ///
 /// Requests to some paths may be handled externally by the caller - the IOx
 /// server runner framework takes care of implementing the heath endpoint,
 /// metrics, pprof, etc.
+///
+/// # Partial Writes
+///
+/// By default a write request is rejected as a whole if any of its lines
+/// cannot be parsed. When partial writes are enabled with
+/// [`HttpDelegate::with_partial_writes()`], the valid lines of the request are
+/// written, and the lines that fail to parse, conflict with the schema of the
+/// lines before them, or conflict with the column types of the namespace
+/// schema cached by the router are rejected. The response to a partial write
+/// is a [`StatusCode::BAD_REQUEST`] with a JSON body modelled on the InfluxDB 2
+/// partial write error, listing the line number and error of (up to a
+/// configured maximum number of) the rejected lines.
+///
+/// Lines are only checked against the cached schema - a conflict with a
+/// column that is not yet cached (for example, one concurrently added by
+/// another router) is detected when the write is validated against the
+/// catalog, and still rejects the request as a whole.
+///
+/// # InfluxDB 1.x Writes
+///
+/// Writes to the InfluxDB 1.x `/write` endpoint are mapped to a namespace
+/// using the `db` and `rp` parameters (see [`db_and_rp_to_database()`]) and
+/// are otherwise handled identically to `/api/v2/write` requests.
 #[derive(Debug, Default)]
 pub struct HttpDelegate<D, T = SystemProvider> {
     max_request_bytes: usize,
     time_provider: T,
     dml_handler: Arc<D>,
 
+    /// The maximum number of rejected lines listed in a partial write
+    /// response, or `None` if partial writes are disabled.
+    partial_write_max_errors: Option<usize>,
+
+    /// The namespace cache the lines of partial writes are checked against.
+    partial_write_ns_cache: Option<Box<dyn NamespaceCache>>,
+
     write_metric_lines: U64Counter,
     write_metric_fields: U64Counter,
     write_metric_tables: U64Counter,
     write_metric_body_size: U64Counter,
+    write_metric_partial: U64Counter,
+    write_metric_rejected_lines: U64Counter,
     delete_metric_body_size: U64Counter,
 }
 
@@ -226,6 +364,18 @@ impl<D> HttpDelegate<D, SystemProvider> {
                 "cumulative byte size of successfully routed (decompressed) line protocol write requests",
             )
             .recorder(&[]);
+        let write_metric_partial = metrics
+            .register_metric::<U64Counter>(
+                "http_write_partial_total",
+                "cumulative number of write requests with one or more rejected lines",
+            )
+            .recorder(&[]);
+        let write_metric_rejected_lines = metrics
+            .register_metric::<U64Counter>(
+                "http_write_rejected_lines_total",
+                "cumulative number of line protocol lines rejected by partial writes",
+            )
+            .recorder(&[]);
         let delete_metric_body_size = metrics
             .register_metric::<U64Counter>(
                 "http_delete_body_bytes_total",
@@ -237,15 +387,35 @@ impl<D> HttpDelegate<D, SystemProvider> {
             max_request_bytes,
             time_provider: SystemProvider::default(),
             dml_handler,
+            partial_write_max_errors: None,
+            partial_write_ns_cache: None,
             write_metric_lines,
             write_metric_fields,
             write_metric_tables,
             write_metric_body_size,
+            write_metric_partial,
+            write_metric_rejected_lines,
             delete_metric_body_size,
         }
     }
 }
 
+impl<D, T> HttpDelegate<D, T> {
+    /// Enable partial writes, listing at most `max_errors` rejected lines in
+    /// the response to a partial write.
+    ///
+    /// Lines conflicting with the column types of the namespace schema cached
+    /// in `ns_cache` are rejected.
+    pub fn with_partial_writes<C>(mut self, max_errors: usize, ns_cache: C) -> Self
+    where
+        C: NamespaceCache + 'static,
+    {
+        self.partial_write_max_errors = Some(max_errors);
+        self.partial_write_ns_cache = Some(Box::new(ns_cache));
+        self
+    }
+}
+
 impl<D, T> HttpDelegate<D, T>
 where
     D: DmlHandler<WriteInput = HashMap<String, MutableBatch>>,
@@ -255,22 +425,44 @@ where
     /// response.
     pub async fn route(&self, req: Request<Body>) -> Result<Response<Body>, Error> {
         match (req.method(), req.uri().path()) {
-            (&Method::POST, "/api/v2/write") => self.write_handler(req).await,
-            (&Method::POST, "/api/v2/delete") => self.delete_handler(req).await,
-            _ => return Err(Error::NoHandler),
+            (&Method::POST, "/api/v2/write") => {
+                let write_info = WriteInfo::try_from(&req)?;
+                let namespace = org_and_bucket_to_database(&write_info.org, &write_info.bucket)
+                    .map_err(OrgBucketError::MappingFail)?;
+
+                trace!(org=%write_info.org, bucket=%write_info.bucket, %namespace, "processing write request");
+
+                self.write_handler(req, namespace, write_info.precision.timestamp_base())
+                    .await
+            }
+            (&Method::POST, "/write") => {
+                let write_info = V1WriteInfo::try_from(&req)?;
+                let namespace = db_and_rp_to_database(&write_info.db, write_info.rp.as_deref())
+                    .map_err(DbRpError::MappingFail)?;
+
+                trace!(db=%write_info.db, rp=?write_info.rp, %namespace, "processing v1 write request");
+
+                self.write_handler(req, namespace, write_info.precision.timestamp_base())
+                    .await
+            }
+            (&Method::POST, "/api/v2/delete") => self
+                .delete_handler(req)
+                .await
+                .map(|_| response_no_content()),
+            _ => Err(Error::NoHandler),
         }
-        .map(|_| response_no_content())
     }
 
-    async fn write_handler(&self, req: Request<Body>) -> Result<(), Error> {
+    /// Write the line protocol body of `req` to `namespace`, scaling the
+    /// timestamps by `timestamp_base` to convert them to nanoseconds.
+    async fn write_handler(
+        &self,
+        req: Request<Body>,
+        namespace: DatabaseName<'static>,
+        timestamp_base: i64,
+    ) -> Result<Response<Body>, Error> {
         let span_ctx: Option<SpanContext> = req.extensions().get().cloned();
 
-        let write_info = WriteInfo::try_from(&req)?;
-        let namespace = org_and_bucket_to_database(&write_info.org, &write_info.bucket)
-            .map_err(OrgBucketError::MappingFail)?;
-
-        trace!(org=%write_info.org, bucket=%write_info.bucket, %namespace, "processing write request");
-
         // Read the HTTP body and convert it to a str.
         let body = self.read_body(req).await?;
         let body = std::str::from_utf8(&body).map_err(Error::NonUtf8Body)?;
@@ -280,12 +472,32 @@ where
         let default_time = self.time_provider.now().timestamp_nanos();
 
         let mut converter = LinesConverter::new(default_time);
-        converter.set_timestamp_base(write_info.precision.timestamp_base());
-        let (batches, stats) = match converter.write_lp(body).and_then(|_| converter.finish()) {
+        converter.set_timestamp_base(timestamp_base);
+        let line_errors = match self.partial_write_max_errors {
+            Some(_) => {
+                let schema = self
+                    .partial_write_ns_cache
+                    .as_ref()
+                    .and_then(|ns_cache| ns_cache.get_schema(&namespace));
+                converter.write_lp_partial(body, |table, column| {
+                    let column = schema.as_ref()?.tables.get(table)?.columns.get(column)?;
+                    Some(column.column_type.into())
+                })
+            }
+            None => {
+                converter.write_lp(body).map_err(Error::ParseLineProtocol)?;
+                vec![]
+            }
+        };
+        let (batches, stats) = match converter.finish() {
             Ok(v) => v,
-            Err(mutable_batch_lp::Error::EmptyPayload) => {
+            Err(mutable_batch_lp::Error::EmptyPayload) if line_errors.is_empty() => {
                 debug!("nothing to write");
-                return Ok(());
+                return Ok(response_no_content());
+            }
+            Err(mutable_batch_lp::Error::EmptyPayload) => {
+                debug!(%namespace, rejected_lines=line_errors.len(), "all lines rejected");
+                return Ok(self.partial_write(0, line_errors));
             }
             Err(e) => return Err(Error::ParseLineProtocol(e)),
         };
@@ -295,11 +507,9 @@ where
             num_lines=stats.num_lines,
             num_fields=stats.num_fields,
             num_tables,
-            precision=?write_info.precision,
+            timestamp_base,
             body_size=body.len(),
             %namespace,
-            org=%write_info.org,
-            bucket=%write_info.bucket,
             "routing write",
         );
 
@@ -313,7 +523,49 @@ where
         self.write_metric_tables.inc(num_tables as _);
         self.write_metric_body_size.inc(body.len() as _);
 
-        Ok(())
+        if !line_errors.is_empty() {
+            debug!(%namespace, rejected_lines=line_errors.len(), "partial write");
+            return Ok(self.partial_write(stats.num_lines, line_errors));
+        }
+
+        Ok(response_no_content())
+    }
+
+    /// Record a write of `lines_written` lines that rejected the lines in
+    /// `line_errors`, returning the partial write response.
+    fn partial_write(
+        &self,
+        lines_written: usize,
+        line_errors: Vec<mutable_batch_lp::Error>,
+    ) -> Response<Body> {
+        self.write_metric_partial.inc(1);
+        self.write_metric_rejected_lines.inc(line_errors.len() as _);
+
+        let max_errors = self.partial_write_max_errors.unwrap_or_default();
+        let body = PartialWriteError {
+            code: "invalid",
+            message: format!(
+                "partial write error ({} written): {} lines rejected",
+                lines_written,
+                line_errors.len()
+            ),
+            line_errors: line_errors
+                .iter()
+                .take(max_errors)
+                .map(|e| LineError {
+                    line: e.line_number(),
+                    error: e.to_string(),
+                })
+                .collect(),
+        };
+
+        Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::to_string(&body).expect("partial write error is serialisable"),
+            ))
+            .unwrap()
     }
 
     async fn delete_handler(&self, req: Request<Body>) -> Result<(), Error> {
@@ -422,6 +674,21 @@ where
     }
 }
 
+/// The body of a partial write response.
+#[derive(Debug, Serialize)]
+struct PartialWriteError {
+    code: &'static str,
+    message: String,
+    line_errors: Vec<LineError>,
+}
+
+/// A rejected line of a partial write.
+#[derive(Debug, Serialize)]
+struct LineError {
+    line: Option<usize>,
+    error: String,
+}
+
 fn response_no_content() -> Response<Body> {
     Response::builder()
         .status(StatusCode::NO_CONTENT)
@@ -439,7 +706,17 @@ mod tests {
     use hyper::header::HeaderValue;
     use metric::{Attributes, Metric};
 
-    use crate::dml_handlers::mock::{MockDmlHandler, MockDmlHandlerCall};
+    use crate::{
+        dml_handlers::{
+            mock::{MockDmlHandler, MockDmlHandlerCall},
+            RateLimitError, IN_FLIGHT_RETRY_AFTER,
+        },
+        namespace_cache::MemoryNamespaceCache,
+    };
+    use data_types2::{
+        ColumnId, ColumnSchema, ColumnType, KafkaTopicId, NamespaceId, NamespaceSchema,
+        QueryPoolId, TableId, TableSchema,
+    };
 
     use super::*;
 
@@ -539,7 +816,7 @@ mod tests {
                     // and metrics should be recorded.
                     if let Ok(v) = got {
                         assert_eq!(v.status(), StatusCode::NO_CONTENT);
-                        if $uri.contains("/api/v2/write") {
+                        if !$uri.contains("/api/v2/delete") {
                             assert_metric_hit(&metrics, "http_write_lines_total", None);
                             assert_metric_hit(&metrics, "http_write_fields_total", None);
                             assert_metric_hit(&metrics, "http_write_tables_total", None);
@@ -898,6 +1175,160 @@ mod tests {
         }
     );
 
+    // Wrapper over test_http_handler specifically for InfluxDB 1.x write
+    // requests.
+    macro_rules! test_v1_write_handler {
+        (
+            $name:ident,
+            query_string = $query_string:expr,   // Request URI query string
+            body = $body:expr,                   // Request body content
+            dml_handler = $dml_handler:expr,     // DML write handler response (if called)
+            want_result = $want_result:pat,
+            want_dml_calls = $($want_dml_calls:tt )+
+        ) => {
+            paste::paste! {
+                test_http_handler!(
+                    [<v1_write_ $name>],
+                    uri = format!("https://bananas.example/write{}", $query_string),
+                    body = $body,
+                    dml_write_handler = $dml_handler,
+                    dml_delete_handler = [],
+                    want_result = $want_result,
+                    want_dml_calls = $($want_dml_calls)+
+                );
+            }
+        };
+    }
+
+    test_v1_write_handler!(
+        ok,
+        query_string = "?db=bananas&u=user&p=pass",
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, ..}] => {
+            assert_eq!(namespace, "bananas");
+        }
+    );
+
+    test_v1_write_handler!(
+        ok_autogen_rp,
+        query_string = "?db=bananas&rp=autogen",
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, ..}] => {
+            assert_eq!(namespace, "bananas");
+        }
+    );
+
+    test_v1_write_handler!(
+        ok_rp,
+        query_string = "?db=bananas&rp=weekly",
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, ..}] => {
+            assert_eq!(namespace, "bananas_weekly");
+        }
+    );
+
+    test_v1_write_handler!(
+        ok_precision_h,
+        query_string = "?db=bananas&precision=h",
+        body = "platanos,tag1=A,tag2=B val=42i 457673".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, write_input}] => {
+            assert_eq!(namespace, "bananas");
+
+            let table = write_input.get("platanos").expect("table not found");
+            let ts = table.timestamp_summary().expect("no timestamp summary");
+            assert_eq!(Some(1647622800000000000), ts.stats.min);
+        }
+    );
+
+    test_v1_write_handler!(
+        ok_precision_m,
+        query_string = "?db=bananas&precision=m",
+        body = "platanos,tag1=A,tag2=B val=42i 27460380".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, write_input}] => {
+            assert_eq!(namespace, "bananas");
+
+            let table = write_input.get("platanos").expect("table not found");
+            let ts = table.timestamp_summary().expect("no timestamp summary");
+            assert_eq!(Some(1647622800000000000), ts.stats.min);
+        }
+    );
+
+    test_v1_write_handler!(
+        ok_precision_u,
+        query_string = "?db=bananas&precision=u",
+        body = "platanos,tag1=A,tag2=B val=42i 1647622847000000".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, write_input}] => {
+            assert_eq!(namespace, "bananas");
+
+            let table = write_input.get("platanos").expect("table not found");
+            let ts = table.timestamp_summary().expect("no timestamp summary");
+            assert_eq!(Some(1647622847000000000), ts.stats.min);
+        }
+    );
+
+    test_v1_write_handler!(
+        ok_precision_n,
+        query_string = "?db=bananas&precision=n",
+        body = "platanos,tag1=A,tag2=B val=42i 1647622847000000000".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Ok(_),
+        want_dml_calls = [MockDmlHandlerCall::Write{namespace, write_input}] => {
+            assert_eq!(namespace, "bananas");
+
+            let table = write_input.get("platanos").expect("table not found");
+            let ts = table.timestamp_summary().expect("no timestamp summary");
+            assert_eq!(Some(1647622847000000000), ts.stats.min);
+        }
+    );
+
+    test_v1_write_handler!(
+        invalid_precision,
+        query_string = "?db=bananas&precision=d",
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Err(Error::InvalidDbRp(DbRpError::DecodeFail(_))),
+        want_dml_calls = [] // None
+    );
+
+    test_v1_write_handler!(
+        no_query_params,
+        query_string = "",
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Err(Error::InvalidDbRp(DbRpError::NotSpecified)),
+        want_dml_calls = [] // None
+    );
+
+    test_v1_write_handler!(
+        empty_db,
+        query_string = "?db=&rp=autogen",
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Err(Error::InvalidDbRp(DbRpError::NotSpecified)),
+        want_dml_calls = [] // None
+    );
+
+    test_v1_write_handler!(
+        invalid_db,
+        query_string = format!("?db={}", "A".repeat(1000)),
+        body = "platanos,tag1=A,tag2=B val=42i 123456".as_bytes(),
+        dml_handler = [Ok(())],
+        want_result = Err(Error::InvalidDbRp(DbRpError::MappingFail(_))),
+        want_dml_calls = [] // None
+    );
+
     test_http_handler!(
         not_found,
         uri = "https://bananas.example/wat",
@@ -907,4 +1338,151 @@ mod tests {
         want_result = Err(Error::NoHandler),
         want_dml_calls = []
     );
+
+    const PARTIAL_BODY: &str = "\
+        platanos,tag1=A val=42i 1\n\
+        platanos,tag1=A val=4.2 2\n\
+        bananas\n\
+        platanos,tag1=B val=24i 3";
+
+    fn write_request(body: &'static str) -> Request<Body> {
+        Request::builder()
+            .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test")
+            .method("POST")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_partial_write() {
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([Ok(())]));
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(MAX_BYTES, Arc::clone(&dml_handler), &metrics)
+            .with_partial_writes(1, Arc::new(MemoryNamespaceCache::default()));
+
+        let got = delegate
+            .route(write_request(PARTIAL_BODY))
+            .await
+            .expect("partial write should succeed");
+        assert_eq!(got.status(), StatusCode::BAD_REQUEST);
+
+        let body = hyper::body::to_bytes(got.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "invalid");
+        assert_eq!(
+            body["message"],
+            "partial write error (2 written): 2 lines rejected"
+        );
+
+        // Only the first rejected line is listed.
+        let line_errors = body["line_errors"].as_array().unwrap();
+        assert_eq!(line_errors.len(), 1);
+        assert_eq!(line_errors[0]["line"], 2);
+
+        // The valid lines are written.
+        assert_matches!(dml_handler.calls().as_slice(), [MockDmlHandlerCall::Write{namespace, write_input}] => {
+            assert_eq!(namespace, "bananas_test");
+            assert_eq!(write_input.get("platanos").unwrap().rows(), 2);
+        });
+
+        assert_metric_hit(&metrics, "http_write_lines_total", Some(2));
+        assert_metric_hit(&metrics, "http_write_partial_total", Some(1));
+        assert_metric_hit(&metrics, "http_write_rejected_lines_total", Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_partial_write_all_rejected() {
+        let dml_handler = Arc::new(MockDmlHandler::default());
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(MAX_BYTES, Arc::clone(&dml_handler), &metrics)
+            .with_partial_writes(10, Arc::new(MemoryNamespaceCache::default()));
+
+        let got = delegate
+            .route(write_request("bananas\nplatanos"))
+            .await
+            .expect("partial write should succeed");
+        assert_eq!(got.status(), StatusCode::BAD_REQUEST);
+
+        let body = hyper::body::to_bytes(got.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["line_errors"].as_array().unwrap().len(), 2);
+
+        // Nothing is written.
+        assert!(dml_handler.calls().is_empty());
+        assert_metric_hit(&metrics, "http_write_rejected_lines_total", Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_partial_write_cached_schema_conflict() {
+        // The cached schema has an integer "val" field in the "platanos" table.
+        let mut table = TableSchema::new(TableId::new(1));
+        table.columns.insert(
+            "val".to_string(),
+            ColumnSchema {
+                id: ColumnId::new(1),
+                column_type: ColumnType::I64,
+            },
+        );
+        let mut schema = NamespaceSchema::new(
+            NamespaceId::new(1),
+            KafkaTopicId::new(1),
+            QueryPoolId::new(1),
+        );
+        schema.tables.insert("platanos".to_string(), table);
+
+        let ns_cache = Arc::new(MemoryNamespaceCache::default());
+        ns_cache.put_schema(DatabaseName::new("bananas_test").unwrap(), schema);
+
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([Ok(())]));
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(MAX_BYTES, Arc::clone(&dml_handler), &metrics)
+            .with_partial_writes(10, ns_cache);
+
+        let got = delegate
+            .route(write_request(
+                "platanos val=4.2 1\nplatanos val=42i 2\nbananas val=4.2 3",
+            ))
+            .await
+            .expect("partial write should succeed");
+        assert_eq!(got.status(), StatusCode::BAD_REQUEST);
+
+        let body = hyper::body::to_bytes(got.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let line_errors = body["line_errors"].as_array().unwrap();
+        assert_eq!(line_errors.len(), 1);
+        assert_eq!(line_errors[0]["line"], 1);
+
+        // Only the conflicting line is dropped.
+        assert_matches!(dml_handler.calls().as_slice(), [MockDmlHandlerCall::Write{write_input, ..}] => {
+            assert_eq!(write_input.get("platanos").unwrap().rows(), 1);
+            assert_eq!(write_input.get("bananas").unwrap().rows(), 1);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_partial_write_disabled() {
+        let dml_handler = Arc::new(MockDmlHandler::default());
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(MAX_BYTES, Arc::clone(&dml_handler), &metrics);
+
+        let got = delegate.route(write_request(PARTIAL_BODY)).await;
+        assert_matches!(got, Err(Error::ParseLineProtocol(_)));
+        assert!(dml_handler.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_rate_limited() {
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([Err(
+            DmlError::RateLimited(RateLimitError::TooManyRequests(42)),
+        )]));
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(MAX_BYTES, Arc::clone(&dml_handler), &metrics);
+
+        let err = delegate
+            .route(write_request("platanos val=42i 123456"))
+            .await
+            .expect_err("rate limited write should fail");
+        assert_eq!(err.as_status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(err.retry_after(), Some(IN_FLIGHT_RETRY_AFTER));
+    }
 }
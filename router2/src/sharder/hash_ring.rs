@@ -0,0 +1,714 @@
+use super::{BatchSharder, Sharder};
+use data_types2::{DatabaseName, DeletePredicate};
+use hashbrown::HashMap;
+use mutable_batch::{column::ColumnData, MutableBatch};
+use serde::Deserialize;
+use siphasher::sip::SipHasher13;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+use thiserror::Error;
+
+/// The number of points placed on the ring for each unit of sequencer weight.
+const POINTS_PER_WEIGHT: u32 = 64;
+
+/// Errors returned when initialising a [`HashRing`] from a [`ShardConfig`].
+#[derive(Debug, Error, PartialEq)]
+pub enum ShardConfigError {
+    /// The configuration references a sequencer that does not exist.
+    #[error("shard config references unknown sequencer {0}")]
+    UnknownSequencer(usize),
+
+    /// All the sequencers have a weight of 0.
+    #[error("at least one sequencer must have a non-zero weight")]
+    NoWeightedSequencers,
+
+    /// A table is spread over 0 sequencers.
+    #[error("table {namespace}.{table} must be spread over at least 1 sequencer")]
+    EmptySpread {
+        /// The namespace of the table.
+        namespace: String,
+        /// The name of the table.
+        table: String,
+    },
+}
+
+/// The placement of the writes of a single table.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TableShard {
+    /// All writes for the table go to the sequencer with the given ID.
+    Sequencer(usize),
+
+    /// Writes for the table are spread over `count` sequencers by the hash of
+    /// the value of `tag`.
+    Spread {
+        /// The name of the tag column to hash.
+        tag: String,
+        /// The number of sequencers to spread the table over.
+        count: usize,
+    },
+}
+
+/// A [`TableShard`] override of the placement of a single table.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TableOverride {
+    /// The namespace of the table.
+    pub namespace: String,
+    /// The name of the table.
+    pub table: String,
+    /// The placement of the table.
+    pub shard: TableShard,
+}
+
+/// Configuration of the sequencer placement of a [`HashRing`].
+///
+/// Sequencers are identified by their ID (the Kafka partition index).
+///
+/// Overrides are only read from this configuration - the catalog's
+/// `sharding_rule_override` table is not consulted, and changing an override
+/// requires restarting (all) the routers.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ShardConfig {
+    /// The relative weight of a sequencer, determining the share of tables
+    /// placed on it by the hash ring. Sequencers without an explicit weight
+    /// have a weight of 1, and a weight of 0 removes the sequencer from the
+    /// ring (it only receives overridden namespaces / tables).
+    #[serde(default)]
+    pub weights: BTreeMap<usize, u32>,
+
+    /// Namespaces whose tables are all placed on the given sequencer ID.
+    #[serde(default)]
+    pub namespace_overrides: BTreeMap<String, usize>,
+
+    /// Per-table placements, taking precedence over the namespace overrides.
+    #[serde(default)]
+    pub table_overrides: Vec<TableOverride>,
+}
+
+/// The resolved placement of an overridden table.
+#[derive(Debug)]
+enum Placement<T> {
+    /// The index of the sequencer in [`HashRing::shards`].
+    Fixed(usize),
+    /// The set of sequencers the table is spread over by the value of `tag`.
+    Spread { tag: String, shards: Vec<T> },
+}
+
+/// A [`HashRing`] maps writes for a given table in a given namespace to
+/// sequencers placed on a weighted consistent hash ring, with support for
+/// overriding the placement of namespaces and tables through a
+/// [`ShardConfig`].
+///
+/// Each sequencer is placed on the ring at a number of points proportional to
+/// its weight, allowing a sequencer to receive a larger (or smaller) share of
+/// the tables. Adding or removing a sequencer remaps approximately `1/N` of
+/// the tables.
+///
+/// # Hot Tables
+///
+/// A table configured with [`TableShard::Spread`] is spread over `count`
+/// sequencers, chosen by walking the ring from the position of the table. Each
+/// row is routed to one of them by hashing the value of the configured tag,
+/// with rows without a value for the tag hashed as an empty value.
+///
+/// [`BatchSharder::shard_batch`] splits a write into one batch per sequencer
+/// its rows are routed to, while [`Sharder::shard`] (which must return a
+/// single sequencer) routes the whole write by the tag value of its first row.
+///
+/// # Deletes
+///
+/// Deletes are routed to every sequencer a write for the same table might be
+/// routed to, which is all the sequencers of a spread table.
+///
+/// # Correctness
+///
+/// Like [`JumpHash`](super::JumpHash), all routers must use the same set of
+/// sequencers and the same [`ShardConfig`] to map the same table to the same
+/// sequencers.
+#[derive(Debug)]
+pub struct HashRing<T> {
+    hasher: SipHasher13,
+    shards: Vec<T>,
+    ring: BTreeMap<u64, usize>,
+
+    namespace_overrides: HashMap<String, usize>,
+    /// Table placements, keyed by namespace and then table name.
+    table_overrides: HashMap<String, HashMap<String, Placement<T>>>,
+}
+
+#[derive(Hash)]
+struct HashKey<'a> {
+    table: &'a str,
+    namespace: &'a str,
+}
+
+impl<T> HashRing<T>
+where
+    T: Clone,
+{
+    /// Initialise a [`HashRing`] placing the `(id, T)` pairs in `shards` on the
+    /// ring according to `config`.
+    pub fn new(
+        shards: impl IntoIterator<Item = (usize, T)>,
+        config: &ShardConfig,
+    ) -> Result<Self, ShardConfigError> {
+        // A randomly generated static siphash key to ensure all router
+        // instances hash the same input to the same point on the ring.
+        //
+        // Generated with: xxd -i -l 16 /dev/urandom
+        let key = [
+            0x1f, 0x9a, 0x52, 0xc4, 0x07, 0xe3, 0x6b, 0x88, 0x3d, 0xf0, 0x21, 0x5e, 0xa9, 0x14,
+            0xc7, 0x60,
+        ];
+        let hasher = SipHasher13::new_with_key(&key);
+
+        let (ids, shards): (Vec<usize>, Vec<T>) = shards.into_iter().unzip();
+        let index_of = |id: usize| {
+            ids.iter()
+                .position(|&v| v == id)
+                .ok_or(ShardConfigError::UnknownSequencer(id))
+        };
+
+        for &id in config.weights.keys() {
+            index_of(id)?;
+        }
+
+        let mut ring = BTreeMap::new();
+        for (index, &id) in ids.iter().enumerate() {
+            let weight = config.weights.get(&id).copied().unwrap_or(1);
+            for point in 0..weight * POINTS_PER_WEIGHT {
+                let mut state = hasher;
+                (id, point).hash(&mut state);
+                ring.insert(state.finish(), index);
+            }
+        }
+        if ring.is_empty() {
+            return Err(ShardConfigError::NoWeightedSequencers);
+        }
+
+        let mut this = Self {
+            hasher,
+            shards,
+            ring,
+            namespace_overrides: HashMap::new(),
+            table_overrides: HashMap::new(),
+        };
+
+        for (namespace, &id) in &config.namespace_overrides {
+            this.namespace_overrides
+                .insert(namespace.clone(), index_of(id)?);
+        }
+
+        for o in &config.table_overrides {
+            let placement = match &o.shard {
+                TableShard::Sequencer(id) => Placement::Fixed(index_of(*id)?),
+                TableShard::Spread { count: 0, .. } => {
+                    return Err(ShardConfigError::EmptySpread {
+                        namespace: o.namespace.clone(),
+                        table: o.table.clone(),
+                    })
+                }
+                TableShard::Spread { tag, count } => {
+                    let start = this.hash(&HashKey {
+                        table: &o.table,
+                        namespace: &o.namespace,
+                    });
+                    let shards = this
+                        .walk(start, *count)
+                        .into_iter()
+                        .map(|i| this.shards[i].clone())
+                        .collect();
+                    Placement::Spread {
+                        tag: tag.clone(),
+                        shards,
+                    }
+                }
+            };
+            this.table_overrides
+                .entry(o.namespace.clone())
+                .or_default()
+                .insert(o.table.clone(), placement);
+        }
+
+        Ok(this)
+    }
+}
+
+impl<T> HashRing<T> {
+    fn hash<H>(&self, key: H) -> u64
+    where
+        H: Hash,
+    {
+        let mut state = self.hasher;
+        key.hash(&mut state);
+        state.finish()
+    }
+
+    /// Return the index of the first sequencer at or after `point` on the
+    /// ring.
+    fn lookup(&self, point: u64) -> usize {
+        self.ring
+            .range(point..)
+            .chain(self.ring.iter())
+            .next()
+            .map(|(_, &index)| index)
+            .expect("ring is never empty")
+    }
+
+    /// Return the indexes of up to `count` distinct sequencers, walking the
+    /// ring clockwise from `point`.
+    fn walk(&self, point: u64, count: usize) -> Vec<usize> {
+        let mut seen = BTreeSet::new();
+        self.ring
+            .range(point..)
+            .chain(self.ring.range(..point))
+            .map(|(_, &index)| index)
+            .filter(|index| seen.insert(*index))
+            .take(count)
+            .collect()
+    }
+
+    fn table_override(&self, table: &str, namespace: &str) -> Option<&Placement<T>> {
+        self.table_overrides.get(namespace)?.get(table)
+    }
+
+    /// Return the index of the sequencer for `table` in `namespace`, ignoring
+    /// spread table overrides.
+    fn fixed_index(&self, table: &str, namespace: &str) -> usize {
+        if let Some(Placement::Fixed(index)) = self.table_override(table, namespace) {
+            return *index;
+        }
+        if let Some(&index) = self.namespace_overrides.get(namespace) {
+            return index;
+        }
+        self.lookup(self.hash(&HashKey { table, namespace }))
+    }
+
+    /// Return the tag and sequencers of `table` in `namespace`, if it is
+    /// spread over multiple sequencers.
+    fn spread(&self, table: &str, namespace: &str) -> Option<(&str, &[T])> {
+        match self.table_override(table, namespace) {
+            Some(Placement::Spread { tag, shards }) => Some((tag, shards)),
+            _ => None,
+        }
+    }
+
+    /// Return the index into a spread table's sequencers of the rows with the
+    /// given `value` of the spread tag.
+    fn spread_index(&self, value: &str, shards: usize) -> usize {
+        (self.hash(value) % shards as u64) as usize
+    }
+
+    /// Return the row ranges of `batch`, keyed by the index into a spread
+    /// table's `shards` sequencers they are routed to.
+    fn spread_ranges(
+        &self,
+        batch: &MutableBatch,
+        tag: &str,
+        shards: usize,
+    ) -> BTreeMap<usize, Vec<Range<usize>>> {
+        let mut out: BTreeMap<usize, Vec<Range<usize>>> = BTreeMap::new();
+
+        let (ids, dictionary) = match batch.column(tag).map(|c| c.data()) {
+            Ok(ColumnData::Tag(ids, dictionary, _)) => (ids, dictionary),
+            _ => {
+                out.insert(self.spread_index("", shards), vec![0..batch.rows()]);
+                return out;
+            }
+        };
+
+        // Hash each distinct tag value once, and emit a range for each run of
+        // consecutive rows routed to the same sequencer.
+        let mut by_id = HashMap::new();
+        let mut run: Option<(usize, usize)> = None;
+        for (row, id) in ids.iter().enumerate() {
+            let index = *by_id.entry(*id).or_insert_with(|| {
+                self.spread_index(dictionary.lookup_id(*id).unwrap_or_default(), shards)
+            });
+
+            match run {
+                Some((_, current)) if current == index => {}
+                Some((start, current)) => {
+                    out.entry(current).or_default().push(start..row);
+                    run = Some((row, index));
+                }
+                None => run = Some((row, index)),
+            }
+        }
+        if let Some((start, current)) = run {
+            out.entry(current).or_default().push(start..ids.len());
+        }
+
+        out
+    }
+}
+
+/// Return the value of the `tag` column in the first row of `batch`, if any.
+fn first_tag_value<'a>(batch: &'a MutableBatch, tag: &str) -> Option<&'a str> {
+    match batch.column(tag).ok()?.data() {
+        ColumnData::Tag(ids, dictionary, _) => dictionary.lookup_id(*ids.first()?),
+        _ => None,
+    }
+}
+
+impl<T> Sharder<MutableBatch> for HashRing<T>
+where
+    T: Debug + Send + Sync,
+{
+    type Item = T;
+
+    fn shard(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        payload: &MutableBatch,
+    ) -> &Self::Item {
+        if let Some((tag, shards)) = self.spread(table, namespace.as_str()) {
+            let value = first_tag_value(payload, tag).unwrap_or_default();
+            return &shards[self.spread_index(value, shards.len())];
+        }
+
+        &self.shards[self.fixed_index(table, namespace.as_str())]
+    }
+}
+
+impl<T> BatchSharder for HashRing<T>
+where
+    T: Debug + Send + Sync,
+{
+    fn shard_batch(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        payload: MutableBatch,
+    ) -> Vec<(&Self::Item, MutableBatch)> {
+        let (tag, shards) = match self.spread(table, namespace.as_str()) {
+            Some(v) => v,
+            None => {
+                let index = self.fixed_index(table, namespace.as_str());
+                return vec![(&self.shards[index], payload)];
+            }
+        };
+
+        let ranges = self.spread_ranges(&payload, tag, shards.len());
+        if ranges.len() == 1 {
+            // All rows are routed to the same sequencer - avoid copying them.
+            let index = *ranges.keys().next().unwrap();
+            return vec![(&shards[index], payload)];
+        }
+
+        ranges
+            .into_iter()
+            .map(|(index, ranges)| {
+                let mut batch = MutableBatch::new();
+                batch
+                    .extend_from_ranges(&payload, &ranges)
+                    .expect("extending an empty batch cannot conflict");
+                (&shards[index], batch)
+            })
+            .collect()
+    }
+}
+
+impl<T> Sharder<DeletePredicate> for HashRing<T>
+where
+    T: Debug + Send + Sync,
+{
+    type Item = [T];
+
+    fn shard(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        _payload: &DeletePredicate,
+    ) -> &Self::Item {
+        if let Some((_, shards)) = self.spread(table, namespace.as_str()) {
+            return shards;
+        }
+
+        std::slice::from_ref(&self.shards[self.fixed_index(table, namespace.as_str())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_types2::TimestampRange;
+
+    fn ns(v: &str) -> DatabaseName<'static> {
+        DatabaseName::new(v.to_string()).unwrap()
+    }
+
+    fn lp(v: &str) -> MutableBatch {
+        let (mut writes, _) = mutable_batch_lp::lines_to_batches_stats(v, 42).unwrap();
+        assert_eq!(writes.len(), 1);
+        writes.drain().next().unwrap().1
+    }
+
+    fn delete() -> DeletePredicate {
+        DeletePredicate {
+            range: TimestampRange::new(1, 2),
+            exprs: vec![],
+        }
+    }
+
+    /// Shard a write of `batch` to the "cpu" table in `namespace`.
+    fn write_shard<'a>(
+        ring: &'a HashRing<usize>,
+        namespace: &str,
+        batch: &MutableBatch,
+    ) -> &'a usize {
+        Sharder::<MutableBatch>::shard(ring, "cpu", &ns(namespace), batch)
+    }
+
+    #[test]
+    fn test_consistent_mapping() {
+        let ring = HashRing::new((0..10).map(|i| (i, i)), &Default::default()).unwrap();
+        let batch = lp("cpu,host=a v=1 1");
+
+        let want = *write_shard(&ring, "bananas", &batch);
+        let ring2 = HashRing::new((0..10).map(|i| (i, i)), &Default::default()).unwrap();
+        assert_eq!(*write_shard(&ring2, "bananas", &batch), want);
+
+        // Deletes go to the same (single) sequencer.
+        let got = Sharder::<DeletePredicate>::shard(&ring, "cpu", &ns("bananas"), &delete());
+        assert_eq!(got, &[want]);
+    }
+
+    #[test]
+    fn test_weights() {
+        let config = ShardConfig {
+            weights: [(0, 3), (1, 1), (2, 0)].into_iter().collect(),
+            ..Default::default()
+        };
+        let ring = HashRing::new((0..3).map(|i| (i, i)), &config).unwrap();
+
+        let mut counts = [0_usize; 3];
+        for i in 0..10_000 {
+            let table = format!("table{}", i);
+            counts[*Sharder::<DeletePredicate>::shard(&ring, &table, &ns("bananas"), &delete())
+                [0]] += 1;
+        }
+
+        // The sequencer with weight 3 receives roughly 3 times the tables of
+        // the sequencer with weight 1, and the zero-weight sequencer none.
+        assert_eq!(counts[2], 0);
+        assert!(counts[0] > counts[1] * 2, "{:?}", counts);
+        assert!(counts[0] < counts[1] * 4, "{:?}", counts);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config = ShardConfig {
+            weights: [(7, 0)].into_iter().collect(),
+            namespace_overrides: [("pinned".to_string(), 7)].into_iter().collect(),
+            table_overrides: vec![TableOverride {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                shard: TableShard::Sequencer(7),
+            }],
+        };
+        let ring = HashRing::new((0..10).map(|i| (i, i)), &config).unwrap();
+        let batch = lp("cpu,host=a v=1 1");
+
+        // Table override
+        assert_eq!(*write_shard(&ring, "bananas", &batch), 7);
+        assert_eq!(
+            Sharder::<DeletePredicate>::shard(&ring, "cpu", &ns("bananas"), &delete()),
+            &[7]
+        );
+
+        // Namespace override
+        for table in ["cpu", "mem", "disk"] {
+            assert_eq!(
+                *Sharder::<MutableBatch>::shard(&ring, table, &ns("pinned"), &batch),
+                7
+            );
+        }
+
+        // Other tables are not placed on the zero-weight sequencer.
+        for i in 0..1000 {
+            let table = format!("table{}", i);
+            assert_ne!(
+                *Sharder::<MutableBatch>::shard(&ring, &table, &ns("bananas"), &batch),
+                7
+            );
+        }
+    }
+
+    #[test]
+    fn test_spread() {
+        let config = ShardConfig {
+            table_overrides: vec![TableOverride {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                shard: TableShard::Spread {
+                    tag: "host".to_string(),
+                    count: 4,
+                },
+            }],
+            ..Default::default()
+        };
+        let ring = HashRing::new((0..10).map(|i| (i, i)), &config).unwrap();
+
+        // Deletes reach all 4 sequencers the table is spread over.
+        let targets = Sharder::<DeletePredicate>::shard(&ring, "cpu", &ns("bananas"), &delete());
+        assert_eq!(targets.len(), 4);
+        assert_eq!(targets.iter().collect::<BTreeSet<_>>().len(), 4);
+
+        // Writes are spread over (only) those sequencers, consistently for the
+        // same tag value.
+        let mut seen = BTreeSet::new();
+        for i in 0..100 {
+            let batch = lp(&format!("cpu,host=h{} v=1 1", i));
+            let got = *write_shard(&ring, "bananas", &batch);
+            assert!(targets.contains(&got));
+            assert_eq!(*write_shard(&ring, "bananas", &batch), got);
+            seen.insert(got);
+        }
+        assert_eq!(seen.len(), 4);
+
+        // Writes without the tag are placed on one of the sequencers.
+        let batch = lp("cpu v=1 1");
+        assert!(targets.contains(write_shard(&ring, "bananas", &batch)));
+
+        // The same table in another namespace is not spread.
+        let targets = Sharder::<DeletePredicate>::shard(&ring, "cpu", &ns("other"), &delete());
+        assert_eq!(targets.len(), 1);
+    }
+
+    #[test]
+    fn test_spread_split() {
+        let config = ShardConfig {
+            table_overrides: vec![TableOverride {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                shard: TableShard::Spread {
+                    tag: "host".to_string(),
+                    count: 4,
+                },
+            }],
+            ..Default::default()
+        };
+        let ring = HashRing::new((0..10).map(|i| (i, i)), &config).unwrap();
+
+        // The rows of a single write are split by tag value into one batch per
+        // sequencer, each placed where a write of only that row would be.
+        let lines = (0..100)
+            .map(|i| format!("cpu,host=h{} v=1 {}", i % 50, i))
+            .collect::<Vec<_>>();
+        let mut want = BTreeMap::new();
+        for line in &lines {
+            *want
+                .entry(*write_shard(&ring, "bananas", &lp(line)))
+                .or_insert(0) += 1;
+        }
+        assert_eq!(want.len(), 4);
+
+        let parts = ring.shard_batch("cpu", &ns("bananas"), lp(&lines.join("\n")));
+        let got = parts
+            .iter()
+            .map(|(shard, batch)| (**shard, batch.rows()))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(got, want);
+
+        // A write without the tag is not split.
+        let parts = ring.shard_batch("cpu", &ns("bananas"), lp("cpu v=1 1\ncpu v=2 2"));
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].1.rows(), 2);
+
+        // Nor is a write to a table that is not spread.
+        let parts = ring.shard_batch("cpu", &ns("other"), lp(&lines.join("\n")));
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].1.rows(), 100);
+    }
+
+    #[test]
+    fn test_spread_capped() {
+        let config = ShardConfig {
+            table_overrides: vec![TableOverride {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                shard: TableShard::Spread {
+                    tag: "host".to_string(),
+                    count: 42,
+                },
+            }],
+            ..Default::default()
+        };
+        let ring = HashRing::new((0..3).map(|i| (i, i)), &config).unwrap();
+        let targets = Sharder::<DeletePredicate>::shard(&ring, "cpu", &ns("bananas"), &delete());
+        assert_eq!(targets.len(), 3);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let shards = || (0..3).map(|i| (i, i));
+
+        let config = ShardConfig {
+            namespace_overrides: [("bananas".to_string(), 42)].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_eq!(
+            HashRing::new(shards(), &config).unwrap_err(),
+            ShardConfigError::UnknownSequencer(42)
+        );
+
+        let config = ShardConfig {
+            weights: [(0, 0), (1, 0), (2, 0)].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_eq!(
+            HashRing::new(shards(), &config).unwrap_err(),
+            ShardConfigError::NoWeightedSequencers
+        );
+
+        let config = ShardConfig {
+            table_overrides: vec![TableOverride {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                shard: TableShard::Spread {
+                    tag: "host".to_string(),
+                    count: 0,
+                },
+            }],
+            ..Default::default()
+        };
+        assert_matches::assert_matches!(
+            HashRing::new(shards(), &config),
+            Err(ShardConfigError::EmptySpread { .. })
+        );
+    }
+
+    #[test]
+    fn test_config_json() {
+        let config: ShardConfig = serde_json::from_str(
+            r#"{
+                "weights": {"0": 2},
+                "namespace_overrides": {"pinned": 1},
+                "table_overrides": [
+                    {"namespace": "bananas", "table": "cpu", "shard": {"sequencer": 2}},
+                    {"namespace": "bananas", "table": "mem", "shard": {"spread": {"tag": "host", "count": 2}}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.weights.get(&0), Some(&2));
+        assert_eq!(config.namespace_overrides.get("pinned"), Some(&1));
+        assert_eq!(config.table_overrides[0].shard, TableShard::Sequencer(2));
+        assert_eq!(
+            config.table_overrides[1].shard,
+            TableShard::Spread {
+                tag: "host".to_string(),
+                count: 2
+            }
+        );
+    }
+}
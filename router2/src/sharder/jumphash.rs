@@ -1,4 +1,4 @@
-use super::Sharder;
+use super::{BatchSharder, Sharder};
 use data_types2::DatabaseName;
 use siphasher::sip::SipHasher13;
 use std::{
@@ -70,6 +70,11 @@ impl<T> JumpHash<T> {
         Self { hasher, ..self }
     }
 
+    /// Return all the shards, in the order they were provided at construction.
+    pub fn shards(&self) -> &[T] {
+        &self.shards
+    }
+
     /// Consistently hash `key` to a `T`.
     pub fn hash<H>(&self, key: H) -> &T
     where
@@ -126,6 +131,8 @@ where
     }
 }
 
+impl<T> BatchSharder for JumpHash<T> where T: Debug + Send + Sync {}
+
 #[cfg(test)]
 mod tests {
     use hashbrown::HashMap;
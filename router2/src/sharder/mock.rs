@@ -1,4 +1,4 @@
-use super::Sharder;
+use super::{BatchSharder, Sharder};
 use data_types2::{DatabaseName, DeletePredicate};
 use mutable_batch::MutableBatch;
 use parking_lot::Mutex;
@@ -94,6 +94,8 @@ where
     }
 }
 
+impl<T> BatchSharder for Arc<MockSharder<T>> where T: Debug + Send + Sync {}
+
 impl<T> Sharder<DeletePredicate> for Arc<MockSharder<T>>
 where
     T: Debug + Send + Sync,
@@ -6,5 +6,8 @@ pub use r#trait::*;
 mod jumphash;
 pub use jumphash::*;
 
+mod hash_ring;
+pub use hash_ring::*;
+
 #[cfg(test)]
 pub mod mock;
@@ -1,4 +1,5 @@
 use data_types2::DatabaseName;
+use mutable_batch::MutableBatch;
 use std::fmt::Debug;
 
 /// A [`Sharder`] implementation is responsible for mapping an opaque payload
@@ -14,10 +15,28 @@ use std::fmt::Debug;
 pub trait Sharder<P>: Debug + Send + Sync {
     /// The type returned by a sharder.
     ///
-    /// This could be a shard ID, a sequencer, an array of multiple sequencers,
+    /// This could be a shard ID, a sequencer, a slice of multiple sequencers,
     /// etc.
-    type Item: Debug + Send + Sync;
+    type Item: Debug + Send + Sync + ?Sized;
 
     /// Map the specified `payload` to a shard.
     fn shard(&self, table: &str, namespace: &DatabaseName<'_>, payload: &P) -> &Self::Item;
 }
+
+/// A [`Sharder`] of writes that may map the rows of a single [`MutableBatch`]
+/// to more than one shard.
+///
+/// The provided implementation maps the whole batch to the shard returned by
+/// [`Sharder::shard`].
+pub trait BatchSharder: Sharder<MutableBatch> {
+    /// Split `payload` into one batch per shard its rows map to, returning
+    /// each shard at most once.
+    fn shard_batch(
+        &self,
+        table: &str,
+        namespace: &DatabaseName<'_>,
+        payload: MutableBatch,
+    ) -> Vec<(&Self::Item, MutableBatch)> {
+        vec![(self.shard(table, namespace, &payload), payload)]
+    }
+}
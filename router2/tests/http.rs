@@ -1,5 +1,7 @@
 use assert_matches::assert_matches;
-use data_types2::{KafkaTopicId, PartitionTemplate, QueryPoolId, TemplatePart};
+use data_types2::{
+    CompiledPartitionTemplate, KafkaTopicId, PartitionTemplate, QueryPoolId, TemplatePart,
+};
 use dml::DmlOperation;
 use hashbrown::HashMap;
 use hyper::{Body, Request, StatusCode};
@@ -17,6 +19,7 @@ use router2::{
     sharder::JumpHash,
 };
 use std::{collections::BTreeSet, iter, string::String, sync::Arc};
+use time::SystemProvider;
 use write_buffer::{
     core::WriteBufferWriting,
     mock::{MockBufferForWriting, MockBufferSharedState},
@@ -52,7 +55,7 @@ type HttpDelegateStack = HttpDelegate<
                     >,
                     SchemaValidator<Arc<ShardedCache<Arc<MemoryNamespaceCache>>>>,
                 >,
-                Partitioner,
+                Partitioner<Arc<ShardedCache<Arc<MemoryNamespaceCache>>>>,
             >,
             FanOutAdaptor<
                 ShardedWriteBuffer<JumpHash<Arc<Sequencer>>>,
@@ -100,10 +103,17 @@ impl TestContext {
             iox_catalog::INFINITE_RETENTION_POLICY.to_owned(),
         );
 
-        let schema_validator = SchemaValidator::new(Arc::clone(&catalog), ns_cache);
-        let partitioner = Partitioner::new(PartitionTemplate {
+        let schema_validator = SchemaValidator::new(
+            Arc::clone(&catalog),
+            Arc::clone(&ns_cache),
+            Arc::new(SystemProvider::new()),
+            &metrics,
+        );
+        let default_template = CompiledPartitionTemplate::try_new(PartitionTemplate {
             parts: vec![TemplatePart::TimeFormat("%Y-%m-%d".to_owned())],
-        });
+        })
+        .unwrap();
+        let partitioner = Partitioner::new(default_template, ns_cache);
 
         let handler_stack = ns_creator
             .and_then(schema_validator)
@@ -114,6 +114,29 @@ impl Planner {
             .await
     }
 
+    /// Creates a plan as described on
+    /// [`InfluxRpcPlanner::series_keys`], on a separate threadpool
+    pub async fn series_keys<D>(
+        &self,
+        database: Arc<D>,
+        predicate: InfluxRpcPredicate,
+    ) -> Result<StringSetPlan>
+    where
+        D: QueryDatabase + 'static,
+    {
+        let planner =
+            InfluxRpcPlanner::new().with_execution_context(self.ctx.child_ctx("influxrpc_planner"));
+
+        self.ctx
+            .run(async move {
+                planner
+                    .series_keys(database.as_ref(), predicate)
+                    .await
+                    .map_err(|e| Error::Plan(format!("series_keys error: {}", e)))
+            })
+            .await
+    }
+
     /// Creates a plan as described on
     /// [`InfluxRpcPlanner::field_columns`], on a separate threadpool
     pub async fn field_columns<D>(
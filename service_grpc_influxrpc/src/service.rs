@@ -70,6 +70,16 @@ pub enum Error {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    #[snafu(display(
+        "Error computing series cardinality in database '{}': {}",
+        db_name,
+        source
+    ))]
+    ComputingSeriesCardinality {
+        db_name: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[snafu(display("Error creating series plans for database '{}': {}", db_name, source))]
     PlanningFilteringSeries {
         db_name: String,
@@ -197,6 +207,7 @@ impl Error {
                 // TODO: distinguish between input errors and internal errors
                 Status::invalid_argument(self.to_string())
             }
+            Self::ComputingSeriesCardinality { .. } => Status::internal(self.to_string()),
             Self::PlanningFilteringSeries { .. } => Status::invalid_argument(self.to_string()),
             Self::PlanningGroupSeries { .. } => Status::invalid_argument(self.to_string()),
             Self::FilteringSeries { .. } => Status::invalid_argument(self.to_string()),
@@ -549,9 +560,44 @@ where
 
     async fn read_series_cardinality(
         &self,
-        _req: tonic::Request<ReadSeriesCardinalityRequest>,
+        req: tonic::Request<ReadSeriesCardinalityRequest>,
     ) -> Result<tonic::Response<Self::ReadSeriesCardinalityStream>, Status> {
-        unimplemented!("read_series_cardinality not yet implemented. https://github.com/influxdata/influxdb_iox/issues/447");
+        let span_ctx = req.extensions().get().cloned();
+        let (tx, rx) = mpsc::channel(4);
+
+        let req = req.into_inner();
+
+        let db_name = get_database_name(&req)?;
+        let db = self
+            .db_store
+            .db(&db_name)
+            .context(DatabaseNotFoundSnafu { db_name: &db_name })?;
+
+        let ctx = db.new_query_context(span_ctx);
+        let mut query_completed_token =
+            db.record_query(&ctx, "read_series_cardinality", defer_json(&req));
+
+        let ReadSeriesCardinalityRequest {
+            read_series_cardinality_source: _source,
+            range,
+            predicate,
+        } = req;
+
+        info!(%db_name, ?range, predicate=%predicate.loggable(), "read_series_cardinality");
+
+        let response = series_cardinality_impl(Arc::clone(&db), db_name, range, predicate, &ctx)
+            .await
+            .map_err(|e| e.to_status());
+
+        if response.is_ok() {
+            query_completed_token.set_success();
+        }
+
+        tx.send(response)
+            .await
+            .expect("sending read_series_cardinality response to server");
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
     }
 
     async fn capabilities(
@@ -872,6 +918,51 @@ where
     Ok(StringValuesResponse { values })
 }
 
+/// Return the number of distinct series (measurement and tag set)
+/// with at least one row that passes the timestamp and arbitrary
+/// predicates
+async fn series_cardinality_impl<D>(
+    db: Arc<D>,
+    db_name: DatabaseName<'static>,
+    range: Option<TimestampRange>,
+    rpc_predicate: Option<Predicate>,
+    ctx: &IOxSessionContext,
+) -> Result<Int64ValuesResponse>
+where
+    D: QueryDatabase + ExecutionContextProvider + 'static,
+{
+    let rpc_predicate_string = format!("{:?}", rpc_predicate);
+    let db_name = db_name.as_str();
+
+    let predicate = InfluxRpcPredicateBuilder::default()
+        .set_range(range)
+        .rpc_predicate(rpc_predicate)
+        .context(ConvertingPredicateSnafu {
+            rpc_predicate_string,
+        })?
+        .build();
+
+    let plan = Planner::new(ctx)
+        .series_keys(db, predicate)
+        .await
+        .map_err(|e| Box::new(e) as _)
+        .context(ComputingSeriesCardinalitySnafu { db_name })?;
+
+    let series_keys = ctx
+        .to_string_set(plan)
+        .await
+        .map_err(|e| Box::new(e) as _)
+        .context(ComputingSeriesCardinalitySnafu { db_name })?;
+
+    trace!(
+        series_cardinality = series_keys.len(),
+        "Series cardinality response"
+    );
+    Ok(Int64ValuesResponse {
+        values: vec![series_keys.len() as i64],
+    })
+}
+
 /// Return tag keys with optional measurement, timestamp and arbitrary
 /// predicates
 async fn tag_keys_impl<D>(
@@ -1777,6 +1868,53 @@ mod tests {
         grpc_request_metric_has_count(&fixture, "TagValues", "ok", 1);
     }
 
+    #[tokio::test]
+    async fn test_storage_rpc_read_series_cardinality() {
+        test_helpers::maybe_start_logging();
+        // Start a test gRPC server on a randomally allocated port
+        let mut fixture = Fixture::new().await.expect("Connecting to test server");
+
+        let db_info = org_and_bucket();
+
+        // three rows, each with a distinct tag set
+        let chunk0 = TestChunk::new("h2o")
+            .with_id(0)
+            .with_time_column()
+            .with_tag_column("tag1")
+            .with_tag_column("tag2")
+            .with_three_rows_of_data();
+
+        // ruled out by metadata
+        let chunk1 = TestChunk::new("o2")
+            .with_id(1)
+            .with_time_column()
+            .with_tag_column("tag1")
+            .with_predicate_match(PredicateMatch::Zero)
+            .with_one_row_of_data();
+
+        fixture
+            .test_storage
+            .db_or_create(db_info.db_name())
+            .await
+            .add_chunk("my_partition_key", Arc::new(chunk0))
+            .add_chunk("my_partition_key", Arc::new(chunk1));
+
+        let request = ReadSeriesCardinalityRequest {
+            read_series_cardinality_source: Some(StorageClient::read_source(&db_info, 1)),
+            range: None,
+            predicate: None,
+        };
+
+        let cardinality = fixture
+            .storage_client
+            .read_series_cardinality(request)
+            .await
+            .unwrap();
+        assert_eq!(cardinality, 3);
+
+        grpc_request_metric_has_count(&fixture, "ReadSeriesCardinality", "ok", 1);
+    }
+
     /// test the plumbing of the RPC layer for tag_values
     ///
     /// For the special case of
@@ -5,7 +5,7 @@ use futures::{
     future::{BoxFuture, Shared},
     FutureExt, TryFutureExt,
 };
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, error::TryRecvError};
 use tokio::task::JoinError;
 
 use observability_deps::tracing::{error, info, warn};
@@ -14,10 +14,18 @@ use trace::{span::Span, TraceCollector};
 /// Size of the exporter buffer
 const CHANNEL_SIZE: usize = 100_000;
 
+/// Default maximum number of spans passed to an `AsyncExport` in a single batch
+const DEFAULT_MAX_BATCH_SIZE: usize = 1_000;
+
 /// An `AsyncExport` is a batched async version of `trace::TraceCollector`
 #[async_trait]
 pub trait AsyncExport: Send + 'static {
     async fn export(&mut self, span: Vec<Span>);
+
+    /// The maximum number of spans passed to a single call to `export`
+    fn max_batch_size(&self) -> usize {
+        DEFAULT_MAX_BATCH_SIZE
+    }
 }
 
 /// `AsyncExporter` wraps a `AsyncExport` and sinks spans to it
@@ -28,7 +36,8 @@ pub trait AsyncExport: Send + 'static {
 /// If this worker cannot keep up, and this queue fills up, spans will
 /// be dropped and warnings logged
 ///
-/// Note: Currently this does not batch spans (#2392)
+/// The worker batches up all the spans queued by the time it picks up a span,
+/// up to `AsyncExport::max_batch_size` spans per batch
 #[derive(Debug)]
 pub struct AsyncExporter {
     join: Shared<BoxFuture<'static, Result<(), Arc<JoinError>>>>,
@@ -84,9 +93,10 @@ async fn background_worker<T: AsyncExport>(
     mut exporter: T,
     mut receiver: mpsc::Receiver<Option<Span>>,
 ) {
+    let max_batch_size = exporter.max_batch_size().max(1);
     loop {
-        match receiver.recv().await {
-            Some(Some(span)) => exporter.export(vec![span]).await,
+        let mut batch = match receiver.recv().await {
+            Some(Some(span)) => vec![span],
             Some(None) => {
                 info!("async exporter shut down");
                 break;
@@ -95,6 +105,29 @@ async fn background_worker<T: AsyncExport>(
                 error!("sender-side of async exporter dropped without waiting for shut down");
                 break;
             }
+        };
+
+        let mut terminated = false;
+        while batch.len() < max_batch_size {
+            match receiver.try_recv() {
+                Ok(Some(span)) => batch.push(span),
+                Ok(None) => {
+                    info!("async exporter shut down");
+                    terminated = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    error!("sender-side of async exporter dropped without waiting for shut down");
+                    terminated = true;
+                    break;
+                }
+            }
+        }
+
+        exporter.export(batch).await;
+        if terminated {
+            break;
         }
     }
 }
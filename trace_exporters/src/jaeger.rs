@@ -87,6 +87,12 @@ impl AsyncExport for JaegerAgentExporter {
             error!(%e, "error writing batch to jaeger agent")
         }
     }
+
+    /// Each batch is sent as a single UDP packet, which must not exceed the
+    /// maximum packet size, so spans are exported one at a time
+    fn max_batch_size(&self) -> usize {
+        1
+    }
 }
 
 /// `NoopReader` is a `std::io::Read` that never returns any data
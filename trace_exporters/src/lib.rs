@@ -9,14 +9,19 @@
 
 use crate::export::AsyncExporter;
 use crate::jaeger::JaegerAgentExporter;
+use crate::zipkin::ZipkinExporter;
 use snafu::Snafu;
 use std::num::NonZeroU16;
 use std::sync::Arc;
 
+pub use crate::zipkin::ZipkinEncoding;
+
 pub mod export;
 
 mod jaeger;
 
+mod zipkin;
+
 /// Auto-generated thrift code
 #[allow(
     dead_code,
@@ -42,7 +47,7 @@ pub const DEFAULT_JAEGER_TRACE_CONTEXT_HEADER_NAME: &str = "uber-trace-id";
 pub struct TracingConfig {
     /// Tracing: exporter type
     ///
-    /// Can be one of: none, jaeger
+    /// Can be one of: none, jaeger, zipkin
     #[clap(
         long = "--traces-exporter",
         env = "TRACES_EXPORTER",
@@ -103,6 +108,41 @@ pub struct TracingConfig {
         default_value = "jaeger-debug-id"
     )]
     pub traces_jaeger_debug_name: String,
+
+    /// Tracing: Zipkin collector endpoint
+    ///
+    /// Spans are POSTed to this URL. JSON v2 encoded spans are usually accepted at
+    /// `/api/v2/spans`, Thrift encoded spans at `/api/v1/spans`.
+    ///
+    /// Only used if `--traces-exporter` is "zipkin".
+    #[clap(
+        long = "--traces-exporter-zipkin-endpoint",
+        env = "TRACES_EXPORTER_ZIPKIN_ENDPOINT",
+        default_value = "http://localhost:9411/api/v2/spans"
+    )]
+    pub traces_exporter_zipkin_endpoint: String,
+
+    /// Tracing: Zipkin encoding
+    ///
+    /// Can be one of: json, thrift
+    ///
+    /// Only used if `--traces-exporter` is "zipkin".
+    #[clap(
+        long = "--traces-exporter-zipkin-encoding",
+        env = "TRACES_EXPORTER_ZIPKIN_ENCODING",
+        default_value = "json"
+    )]
+    pub traces_exporter_zipkin_encoding: ZipkinEncoding,
+
+    /// Tracing: Zipkin service name.
+    ///
+    /// Only used if `--traces-exporter` is "zipkin".
+    #[clap(
+        long = "--traces-exporter-zipkin-service-name",
+        env = "TRACES_EXPORTER_ZIPKIN_SERVICE_NAME",
+        default_value = "iox-conductor"
+    )]
+    pub traces_exporter_zipkin_service_name: String,
 }
 
 impl TracingConfig {
@@ -110,6 +150,7 @@ impl TracingConfig {
         match self.traces_exporter {
             TracesExporter::None => Ok(None),
             TracesExporter::Jaeger => Ok(Some(jaeger_exporter(self)?)),
+            TracesExporter::Zipkin => Ok(Some(zipkin_exporter(self)?)),
         }
     }
 }
@@ -118,6 +159,7 @@ impl TracingConfig {
 pub enum TracesExporter {
     None,
     Jaeger,
+    Zipkin,
 }
 
 impl std::str::FromStr for TracesExporter {
@@ -127,8 +169,9 @@ impl std::str::FromStr for TracesExporter {
         match s.to_ascii_lowercase().as_str() {
             "none" => Ok(Self::None),
             "jaeger" => Ok(Self::Jaeger),
+            "zipkin" => Ok(Self::Zipkin),
             _ => Err(format!(
-                "Invalid traces exporter '{}'. Valid options: none, jaeger",
+                "Invalid traces exporter '{}'. Valid options: none, jaeger, zipkin",
                 s
             )),
         }
@@ -140,6 +183,15 @@ pub enum Error {
     #[snafu(display("Failed to resolve address: {}", address))]
     ResolutionError { address: String },
 
+    #[snafu(display("Invalid zipkin endpoint '{}': {}", endpoint, source))]
+    InvalidZipkinEndpointError {
+        endpoint: String,
+        source: url::ParseError,
+    },
+
+    #[snafu(display("Failed to create HTTP client: {}", source))]
+    HttpClientError { source: reqwest::Error },
+
     #[snafu(context(false))]
     IOError { source: std::io::Error },
 }
@@ -158,3 +210,13 @@ fn jaeger_exporter(config: &TracingConfig) -> Result<Arc<AsyncExporter>> {
 
     Ok(Arc::new(AsyncExporter::new(jaeger)))
 }
+
+fn zipkin_exporter(config: &TracingConfig) -> Result<Arc<AsyncExporter>> {
+    let zipkin = ZipkinExporter::new(
+        config.traces_exporter_zipkin_service_name.clone(),
+        &config.traces_exporter_zipkin_endpoint,
+        config.traces_exporter_zipkin_encoding,
+    )?;
+
+    Ok(Arc::new(AsyncExporter::new(zipkin)))
+}
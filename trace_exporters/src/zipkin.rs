@@ -0,0 +1,438 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use snafu::ResultExt;
+use thrift::protocol::{TBinaryOutputProtocol, TListIdentifier, TOutputProtocol, TType};
+
+use observability_deps::tracing::{error, info};
+use trace::span::Span;
+
+use crate::export::AsyncExport;
+use crate::thrift::zipkincore;
+
+mod span;
+
+/// Timeout for a single request to the zipkin collector
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The wire format used to send spans to the zipkin collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZipkinEncoding {
+    /// Zipkin v2 JSON, usually accepted at `/api/v2/spans`
+    Json,
+
+    /// Zipkin v1 Thrift/Binary, usually accepted at `/api/v1/spans`
+    Thrift,
+}
+
+impl std::str::FromStr for ZipkinEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "thrift" => Ok(Self::Thrift),
+            _ => Err(format!(
+                "Invalid zipkin encoding '{}'. Valid options: json, thrift",
+                s
+            )),
+        }
+    }
+}
+
+/// `ZipkinExporter` receives span data and POSTs it to a zipkin collector over HTTP
+///
+/// Note: batches that cannot be delivered are logged and dropped
+#[derive(Debug)]
+pub struct ZipkinExporter {
+    /// The name of the service
+    service_name: String,
+
+    /// The collector URL spans are POSTed to
+    endpoint: reqwest::Url,
+
+    encoding: ZipkinEncoding,
+
+    client: reqwest::Client,
+}
+
+impl ZipkinExporter {
+    pub fn new(
+        service_name: String,
+        endpoint: &str,
+        encoding: ZipkinEncoding,
+    ) -> super::Result<Self> {
+        info!(%endpoint, %service_name, ?encoding, "Creating zipkin tracing exporter");
+        let endpoint =
+            reqwest::Url::parse(endpoint.trim()).context(super::InvalidZipkinEndpointSnafu {
+                endpoint: endpoint.to_string(),
+            })?;
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .context(super::HttpClientSnafu)?;
+
+        Ok(Self {
+            service_name,
+            endpoint,
+            encoding,
+            client,
+        })
+    }
+
+    /// Encodes a batch of spans, returning the content type and body of the request
+    fn encode(&self, spans: Vec<Span>) -> Result<(&'static str, Vec<u8>), String> {
+        match self.encoding {
+            ZipkinEncoding::Json => {
+                let spans: Vec<_> = spans
+                    .into_iter()
+                    .map(|s| span::to_json(s, &self.service_name))
+                    .collect();
+                let body = serde_json::to_vec(&spans).map_err(|e| e.to_string())?;
+                Ok(("application/json", body))
+            }
+            ZipkinEncoding::Thrift => {
+                let endpoint = zipkincore::Endpoint {
+                    ipv4: None,
+                    port: None,
+                    service_name: Some(self.service_name.clone()),
+                    ipv6: None,
+                };
+                let spans = spans
+                    .into_iter()
+                    .map(|s| span::to_thrift(s, &endpoint))
+                    .collect();
+                let body = encode_thrift(spans).map_err(|e| e.to_string())?;
+                Ok(("application/x-thrift", body))
+            }
+        }
+    }
+}
+
+/// The collector expects a thrift list of spans in the binary protocol
+fn encode_thrift(spans: Vec<zipkincore::Span>) -> thrift::Result<Vec<u8>> {
+    let mut body = vec![];
+    let mut protocol = TBinaryOutputProtocol::new(&mut body, true);
+
+    protocol.write_list_begin(&TListIdentifier::new(TType::Struct, spans.len() as i32))?;
+    for span in &spans {
+        span.write_to_out_protocol(&mut protocol)?;
+    }
+    protocol.write_list_end()?;
+    protocol.flush()?;
+    drop(protocol);
+
+    Ok(body)
+}
+
+#[async_trait]
+impl AsyncExport for ZipkinExporter {
+    async fn export(&mut self, spans: Vec<Span>) {
+        let (content_type, body) = match self.encode(spans) {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                error!(%e, "error encoding batch for zipkin collector");
+                return;
+            }
+        };
+
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        if let Err(e) = response {
+            error!(%e, "error writing batch to zipkin collector")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::AsyncExporter;
+    use chrono::{TimeZone, Utc};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server, StatusCode,
+    };
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+    use thrift::protocol::{TBinaryInputProtocol, TInputProtocol};
+    use trace::ctx::{SpanContext, SpanId, TraceId};
+    use trace::span::{MetaValue, SpanEvent, SpanStatus};
+    use trace::TraceCollector;
+
+    /// A request received by the mock collector
+    #[derive(Debug)]
+    struct Received {
+        path: String,
+        content_type: String,
+        body: Vec<u8>,
+    }
+
+    /// Starts a mock zipkin collector that records all requests
+    fn collector() -> (SocketAddr, Arc<Mutex<Vec<Received>>>) {
+        let received = Arc::new(Mutex::new(vec![]));
+
+        let captured = Arc::clone(&received);
+        let make_service = make_service_fn(move |_conn| {
+            let captured = Arc::clone(&captured);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let captured = Arc::clone(&captured);
+                    async move {
+                        let path = req.uri().path().to_string();
+                        let content_type = req
+                            .headers()
+                            .get(hyper::header::CONTENT_TYPE)
+                            .map(|v| v.to_str().unwrap().to_string())
+                            .unwrap_or_default();
+                        let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                        captured.lock().unwrap().push(Received {
+                            path,
+                            content_type,
+                            body: body.to_vec(),
+                        });
+
+                        let mut response = Response::new(Body::empty());
+                        *response.status_mut() = StatusCode::ACCEPTED;
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        (addr, received)
+    }
+
+    fn test_span() -> Span {
+        let ctx = SpanContext {
+            trace_id: TraceId::new(43434).unwrap(),
+            parent_span_id: None,
+            span_id: SpanId::new(3495993).unwrap(),
+            links: vec![],
+            collector: None,
+        };
+        let mut span = ctx.child("foo");
+        span.status = SpanStatus::Ok;
+        span.events = vec![SpanEvent {
+            time: Utc.timestamp_nanos(200000),
+            msg: "hello".into(),
+        }];
+        span.metadata.insert("count".into(), MetaValue::Int(42));
+        span.start = Some(Utc.timestamp_nanos(100000));
+        span.end = Some(Utc.timestamp_nanos(300000));
+        span
+    }
+
+    #[tokio::test]
+    async fn test_zipkin_json() {
+        let (addr, received) = collector();
+        let mut exporter = ZipkinExporter::new(
+            "service_name".to_string(),
+            &format!("http://{}/api/v2/spans", addr),
+            ZipkinEncoding::Json,
+        )
+        .unwrap();
+
+        let span = test_span();
+        exporter.export(vec![span.clone(), span.clone()]).await;
+        exporter.export(vec![span.clone()]).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].path, "/api/v2/spans");
+        assert_eq!(received[0].content_type, "application/json");
+
+        let b1: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+        let b2: serde_json::Value = serde_json::from_slice(&received[1].body).unwrap();
+        assert_eq!(b1.as_array().unwrap().len(), 2);
+        assert_eq!(b2.as_array().unwrap().len(), 1);
+
+        assert_eq!(
+            b1[0],
+            serde_json::json!({
+                "traceId": "000000000000a9aa",
+                "id": format!("{:016x}", span.ctx.span_id.get()),
+                "parentId": "0000000000355839",
+                "name": "foo",
+                // microseconds not nanoseconds
+                "timestamp": 100,
+                "duration": 200,
+                "localEndpoint": {"serviceName": "service_name"},
+                "annotations": [{"timestamp": 200, "value": "hello"}],
+                "tags": {"count": "42", "ok": "true"},
+            })
+        );
+        assert_eq!(b1[0], b1[1]);
+        assert_eq!(b1[0], b2[0]);
+    }
+
+    #[tokio::test]
+    async fn test_zipkin_thrift() {
+        let (addr, received) = collector();
+        let mut exporter = ZipkinExporter::new(
+            "service_name".to_string(),
+            &format!("http://{}/api/v1/spans", addr),
+            ZipkinEncoding::Thrift,
+        )
+        .unwrap();
+
+        let span = test_span();
+        exporter.export(vec![span.clone(), span.clone()]).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].path, "/api/v1/spans");
+        assert_eq!(received[0].content_type, "application/x-thrift");
+
+        let mut protocol = TBinaryInputProtocol::new(received[0].body.as_slice(), true);
+        let list = protocol.read_list_begin().unwrap();
+        assert_eq!(list.size, 2);
+        let spans: Vec<_> = (0..list.size)
+            .map(|_| zipkincore::Span::read_from_in_protocol(&mut protocol).unwrap())
+            .collect();
+        protocol.read_list_end().unwrap();
+
+        let s0 = &spans[0];
+        assert_eq!(s0, &spans[1]);
+        assert_eq!(s0.trace_id, Some(43434));
+        assert_eq!(s0.trace_id_high, None);
+        assert_eq!(s0.id, Some(span.ctx.span_id.get() as i64));
+        assert_eq!(s0.parent_id, Some(3495993));
+        assert_eq!(s0.name.as_deref(), Some("foo"));
+        assert_eq!(s0.timestamp, Some(100));
+        assert_eq!(s0.duration, Some(200));
+
+        let annotations = s0.annotations.as_ref().unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].timestamp, Some(200));
+        assert_eq!(annotations[0].value.as_deref(), Some("hello"));
+        assert_eq!(
+            annotations[0]
+                .host
+                .as_ref()
+                .unwrap()
+                .service_name
+                .as_deref(),
+            Some("service_name")
+        );
+
+        let mut binary_annotations = s0.binary_annotations.clone().unwrap();
+        binary_annotations.sort_by(|a, b| a.key.cmp(&b.key));
+        let binary_annotations: Vec<_> = binary_annotations
+            .into_iter()
+            .map(|a| (a.key.unwrap(), a.annotation_type.unwrap(), a.value.unwrap()))
+            .collect();
+        assert_eq!(
+            binary_annotations,
+            vec![
+                (
+                    "count".to_string(),
+                    zipkincore::AnnotationType::I64,
+                    42_i64.to_be_bytes().to_vec()
+                ),
+                (
+                    "lc".to_string(),
+                    zipkincore::AnnotationType::String,
+                    b"service_name".to_vec()
+                ),
+                ("ok".to_string(), zipkincore::AnnotationType::Bool, vec![1]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_exporter() {
+        let (addr, received) = collector();
+        let zipkin = ZipkinExporter::new(
+            "service_name".to_string(),
+            &format!("http://{}/api/v2/spans", addr),
+            ZipkinEncoding::Json,
+        )
+        .unwrap();
+        let exporter = AsyncExporter::new(zipkin);
+
+        exporter.export(test_span());
+        exporter.export(test_span());
+
+        // Drain should wait for all published spans to be sent to the collector
+        exporter.drain().await.unwrap();
+
+        let spans: usize = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let batch: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+                batch.as_array().unwrap().len()
+            })
+            .sum();
+        assert_eq!(spans, 2);
+    }
+
+    #[tokio::test]
+    async fn test_async_exporter_batches() {
+        let (addr, received) = collector();
+        let zipkin = ZipkinExporter::new(
+            "service_name".to_string(),
+            &format!("http://{}/api/v2/spans", addr),
+            ZipkinEncoding::Json,
+        )
+        .unwrap();
+        let exporter = AsyncExporter::new(zipkin);
+
+        // The background worker does not run before the test yields, so all
+        // the spans are queued by the time it picks up the first one
+        for _ in 0..5 {
+            exporter.export(test_span());
+        }
+        exporter.drain().await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let batch: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(batch.as_array().unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_collector_unavailable() {
+        // Nothing is listening on this port, the batch is dropped without panicking
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let mut exporter = ZipkinExporter::new(
+            "service_name".to_string(),
+            &format!("http://{}/api/v2/spans", addr),
+            ZipkinEncoding::Json,
+        )
+        .unwrap();
+        exporter.export(vec![test_span()]).await;
+    }
+
+    #[test]
+    fn test_invalid_endpoint() {
+        let err = ZipkinExporter::new(
+            "service_name".to_string(),
+            "not a url",
+            ZipkinEncoding::Json,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InvalidZipkinEndpointError { .. }
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+/// Contains the conversion logic from a `trace::span::Span` to the zipkin JSON v2 and thrift representations
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use trace::{
+    ctx::{SpanId, TraceId},
+    span::{MetaValue, Span, SpanStatus},
+};
+
+use crate::thrift::zipkincore;
+
+/// A span in the zipkin v2 JSON model
+///
+/// See <https://zipkin.io/zipkin-api/#/default/post_spans>
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonSpan {
+    pub trace_id: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+    pub local_endpoint: JsonEndpoint,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<JsonAnnotation>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonEndpoint {
+    pub service_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonAnnotation {
+    pub timestamp: i64,
+    pub value: String,
+}
+
+/// Start time and duration of a span in microseconds
+fn timing(s: &Span) -> (Option<i64>, Option<i64>) {
+    match (s.start, s.end) {
+        (Some(start), Some(end)) => (
+            Some(start.timestamp_nanos() / 1000),
+            Some((end - start).num_microseconds().expect("no overflow")),
+        ),
+        (Some(start), _) => (Some(start.timestamp_nanos() / 1000), None),
+        _ => (None, None),
+    }
+}
+
+/// Records the status of the span as metadata
+///
+/// Zipkin treats the presence of an "error" tag as indicating an error
+fn status_metadata(s: &mut Span) {
+    let key = match s.status {
+        SpanStatus::Ok => "ok",
+        SpanStatus::Err => "error",
+        SpanStatus::Unknown => return,
+    };
+    s.metadata
+        .entry(Cow::Borrowed(key))
+        .or_insert(MetaValue::Bool(true));
+}
+
+/// Zipkin expects lower-hex encoded 64 or 128 bit trace IDs
+fn trace_id_hex(trace_id: TraceId) -> String {
+    let trace_id = trace_id.get();
+    if trace_id >> 64 == 0 {
+        format!("{:016x}", trace_id)
+    } else {
+        format!("{:032x}", trace_id)
+    }
+}
+
+fn span_id_hex(span_id: SpanId) -> String {
+    format!("{:016x}", span_id.get())
+}
+
+fn meta_to_string(value: MetaValue) -> String {
+    match value {
+        MetaValue::String(v) => v.to_string(),
+        MetaValue::Float(v) => v.to_string(),
+        MetaValue::Int(v) => v.to_string(),
+        MetaValue::Bool(v) => v.to_string(),
+    }
+}
+
+pub fn to_json(mut s: Span, service_name: &str) -> JsonSpan {
+    status_metadata(&mut s);
+    let (timestamp, duration) = timing(&s);
+
+    JsonSpan {
+        trace_id: trace_id_hex(s.ctx.trace_id),
+        id: span_id_hex(s.ctx.span_id),
+        parent_id: s.ctx.parent_span_id.map(span_id_hex),
+        name: s.name.to_string(),
+        timestamp,
+        duration,
+        local_endpoint: JsonEndpoint {
+            service_name: service_name.to_string(),
+        },
+        annotations: s
+            .events
+            .into_iter()
+            .map(|event| JsonAnnotation {
+                timestamp: event.time.timestamp_nanos() / 1000,
+                value: event.msg.to_string(),
+            })
+            .collect(),
+        tags: s
+            .metadata
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), meta_to_string(value)))
+            .collect(),
+    }
+}
+
+pub fn to_thrift(mut s: Span, endpoint: &zipkincore::Endpoint) -> zipkincore::Span {
+    status_metadata(&mut s);
+    let (timestamp, duration) = timing(&s);
+
+    let trace_id = s.ctx.trace_id.get();
+    // A high trace ID of 0 indicates a 64 bit trace ID
+    let trace_id_high = (trace_id >> 64) as i64;
+
+    let annotations = s
+        .events
+        .into_iter()
+        .map(|event| zipkincore::Annotation {
+            timestamp: Some(event.time.timestamp_nanos() / 1000),
+            value: Some(event.msg.to_string()),
+            host: Some(endpoint.clone()),
+        })
+        .collect();
+
+    // Spans without a client or server annotation are "local" spans, which zipkin
+    // identifies by the "lc" (local component) binary annotation
+    let local_component = zipkincore::BinaryAnnotation {
+        key: Some(zipkincore::L_O_C_A_L_C_O_M_P_O_N_E_N_T.to_string()),
+        value: Some(
+            endpoint
+                .service_name
+                .clone()
+                .unwrap_or_default()
+                .into_bytes(),
+        ),
+        annotation_type: Some(zipkincore::AnnotationType::String),
+        host: Some(endpoint.clone()),
+    };
+    let binary_annotations = std::iter::once(local_component)
+        .chain(
+            s.metadata
+                .into_iter()
+                .map(|(key, value)| binary_annotation_from_meta(key.to_string(), value, endpoint)),
+        )
+        .collect();
+
+    zipkincore::Span {
+        trace_id: Some(trace_id as i64),
+        name: Some(s.name.to_string()),
+        id: Some(s.ctx.span_id.get() as i64),
+        parent_id: s.ctx.parent_span_id.map(|id| id.get() as i64),
+        annotations: Some(annotations),
+        binary_annotations: Some(binary_annotations),
+        debug: None,
+        timestamp,
+        duration,
+        trace_id_high: (trace_id_high != 0).then(|| trace_id_high),
+    }
+}
+
+/// Values of binary annotations are big-endian encoded
+fn binary_annotation_from_meta(
+    key: String,
+    value: MetaValue,
+    endpoint: &zipkincore::Endpoint,
+) -> zipkincore::BinaryAnnotation {
+    let (annotation_type, value) = match value {
+        MetaValue::String(v) => (zipkincore::AnnotationType::String, v.as_bytes().to_vec()),
+        MetaValue::Float(v) => (zipkincore::AnnotationType::Double, v.to_be_bytes().to_vec()),
+        MetaValue::Int(v) => (zipkincore::AnnotationType::I64, v.to_be_bytes().to_vec()),
+        MetaValue::Bool(v) => (zipkincore::AnnotationType::Bool, vec![v as u8]),
+    };
+
+    zipkincore::BinaryAnnotation {
+        key: Some(key),
+        value: Some(value),
+        annotation_type: Some(annotation_type),
+        host: Some(endpoint.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_id_hex() {
+        let trace_id = TraceId::new(0xFF).unwrap();
+        assert_eq!(trace_id_hex(trace_id), "00000000000000ff");
+
+        let trace_id = TraceId::new(1 << 64 | 0xFF).unwrap();
+        assert_eq!(trace_id_hex(trace_id), "000000000000000100000000000000ff");
+    }
+}
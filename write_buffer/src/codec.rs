@@ -196,11 +196,12 @@ pub fn decode(
                         ))
                     })?;
 
-                    Ok(DmlOperation::Write(DmlWrite::new(
-                        headers.namespace,
-                        tables,
-                        meta,
-                    )))
+                    let mut op = DmlWrite::new(headers.namespace, tables, meta);
+                    if !write.partition_key.is_empty() {
+                        op = op.with_partition_key(write.partition_key);
+                    }
+
+                    Ok(DmlOperation::Write(op))
                 }
                 Payload::Delete(delete) => {
                     let predicate = delete
@@ -326,4 +327,28 @@ mod tests {
 
         assert!(iox_headers2.span_context.is_none());
     }
+
+    #[test]
+    fn partition_key_roundtrip() {
+        let tables = mutable_batch_lp::lines_to_batches("cpu,host=a usage=1 10", 0).unwrap();
+        let headers = || IoxHeaders::new(ContentType::Protobuf, None, "namespace".to_owned());
+        let sequence = Sequence::new(1, 2);
+        let ts = Time::from_timestamp_millis(42);
+
+        for key in [None, Some("host_a")] {
+            let mut write = DmlWrite::new("namespace", tables.clone(), DmlMeta::unsequenced(None));
+            if let Some(key) = key {
+                write = write.with_partition_key(key);
+            }
+
+            let mut buf = vec![];
+            encode_operation("namespace", &DmlOperation::Write(write), &mut buf).unwrap();
+
+            let decoded = decode(&buf, headers(), sequence, ts, buf.len()).unwrap();
+            match decoded {
+                DmlOperation::Write(w) => assert_eq!(w.partition_key(), key),
+                DmlOperation::Delete(_) => panic!("expected write"),
+            }
+        }
+    }
 }
@@ -96,6 +96,7 @@ impl WriteBufferConfigFactory {
                 let file_buffer = FileBufferProducer::new(
                     &root,
                     db_name,
+                    &cfg.connection_config,
                     cfg.creation_config.as_ref(),
                     Arc::clone(&self.time_provider),
                 )
@@ -6,6 +6,7 @@ use std::{
 };
 
 use async_trait::async_trait;
+use data_types::sequence::Sequence;
 use dml::{DmlMeta, DmlOperation, DmlWrite};
 use futures::stream::BoxStream;
 
@@ -16,6 +17,7 @@ use futures::stream::BoxStream;
 pub struct WriteBufferError {
     inner: Box<dyn std::error::Error + Sync + Send>,
     kind: WriteBufferErrorKind,
+    undecodable: Option<Box<UndecodableMessage>>,
 }
 
 impl WriteBufferError {
@@ -26,6 +28,7 @@ impl WriteBufferError {
         Self {
             inner: e.into(),
             kind,
+            undecodable: None,
         }
     }
 
@@ -50,6 +53,18 @@ impl WriteBufferError {
     pub fn inner(&self) -> &dyn std::error::Error {
         self.inner.as_ref()
     }
+
+    /// Attach the raw message that could not be decoded to this error.
+    pub fn with_undecodable(mut self, message: UndecodableMessage) -> Self {
+        self.undecodable = Some(Box::new(message));
+        self
+    }
+
+    /// Returns the raw message this error was produced for, if it was raised while decoding a message read from the
+    /// write buffer.
+    pub fn undecodable(&self) -> Option<&UndecodableMessage> {
+        self.undecodable.as_deref()
+    }
 }
 
 impl Display for WriteBufferError {
@@ -65,6 +80,7 @@ impl From<std::io::Error> for WriteBufferError {
         Self {
             inner: Box::new(e),
             kind: WriteBufferErrorKind::IO,
+            undecodable: None,
         }
     }
 }
@@ -74,6 +90,7 @@ impl From<rskafka::client::error::Error> for WriteBufferError {
         Self {
             inner: Box::new(e),
             kind: WriteBufferErrorKind::IO,
+            undecodable: None,
         }
     }
 }
@@ -83,6 +100,7 @@ impl From<rskafka::client::producer::Error> for WriteBufferError {
         Self {
             inner: Box::new(e),
             kind: WriteBufferErrorKind::IO,
+            undecodable: None,
         }
     }
 }
@@ -92,6 +110,7 @@ impl From<String> for WriteBufferError {
         Self {
             inner: e.into(),
             kind: WriteBufferErrorKind::Unknown,
+            undecodable: None,
         }
     }
 }
@@ -101,10 +120,24 @@ impl From<&'static str> for WriteBufferError {
         Self {
             inner: e.into(),
             kind: WriteBufferErrorKind::Unknown,
+            undecodable: None,
         }
     }
 }
 
+/// The raw contents of a message read from the write buffer that could not be decoded into a [`DmlOperation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndecodableMessage {
+    /// Sequencer and sequence number the message was read from.
+    pub sequence: Sequence,
+
+    /// Message headers.
+    pub headers: Vec<(String, Vec<u8>)>,
+
+    /// Message payload.
+    pub payload: Vec<u8>,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum WriteBufferErrorKind {
     /// This operation failed for an unknown reason
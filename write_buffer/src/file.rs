@@ -1,8 +1,7 @@
 //! Write buffer that uses files to encode messages.
 //!
 //! This implementation can be used by multiple readers and writers at the same time. It is ideal for local end2end
-//! testing. However it might not perform extremely well when dealing with large messages and (currently) does not
-//! implement any message pruning.
+//! testing. However it might not perform extremely well when dealing with large messages.
 //!
 //! # Format
 //! Given a root path, the database name and the number of sequencers, the directory structure looks like this:
@@ -28,9 +27,12 @@
 //!                         :      : :
 //!                         :      : :
 //!                         :      : /temp/<uuid>        \
-//!                         :      :      /<uuid>        | Message files
-//!                         :      :      /<uuid>        | (to be committed)
-//!                         :      :       ...           /
+//!                         :      : :    /<uuid>        | Message files
+//!                         :      : :    /<uuid>        | (to be committed)
+//!                         :      : :     ...           /
+//!                         :      : :
+//!                         :      : :
+//!                         :      : /pruned/<n>         | Empty marker files, see "Retention"
 //!                         :      :
 //!                         :      :
 //!                         :      /1/...                \
@@ -56,6 +58,24 @@
 //!
 //! The payload is binary data. The headers contain metadata about it (like timestamp, format, tracing information).
 //!
+//! # Retention
+//! Committed message files are kept forever unless the producer is configured with one of the following options,
+//! either as connection config or as [`WriteBufferCreationConfig`] options (which take precedence):
+//!
+//! - **`retention_ms`:** Messages produced longer ago than this are pruned.
+//! - **`retention_bytes`:** The oldest messages are pruned while the message files of a sequencer are larger than
+//!   this in total.
+//!
+//! Producers prune all sequencers in the background at most every [`PRUNE_INTERVAL`] while writing, or when
+//! [`FileBufferProducer::prune`] is called. The newest message of a sequencer is never pruned because it determines
+//! the next sequence number.
+//!
+//! Before any message file is deleted, an empty marker file `pruned/<n>` is created, declaring that all sequence
+//! numbers below `n` are pruned. Readers consult the largest marker whenever they cannot find a message file, so they
+//! can tell pruned messages (resulting in an [`UnknownSequenceNumber`] error) from gaps left by failed writers (which
+//! are skipped). Readers that are in the middle of reading a message file while it is deleted are not affected since
+//! the data of an unlinked file stays accessible to open handles.
+//!
 //!
 //! # Implementation Notes
 //! Some notes about file system functionality that shaped this implementation
@@ -108,6 +128,7 @@
 //! [`rename(2)`]: https://man7.org/linux/man-pages/man2/rename.2.html
 //! [`symlink(2)`]: https://man7.org/linux/man-pages/man2/symlink.2.html
 //! [`unlink(2)`]: https://man7.org/linux/man-pages/man2/unlink.2.html
+//! [`UnknownSequenceNumber`]: crate::core::WriteBufferErrorKind::UnknownSequenceNumber
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
@@ -117,6 +138,7 @@ use std::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
 use crate::{
@@ -127,23 +149,76 @@ use async_trait::async_trait;
 use data_types::{sequence::Sequence, write_buffer::WriteBufferCreationConfig};
 use dml::{DmlMeta, DmlOperation};
 use futures::{stream::BoxStream, Stream, StreamExt};
+use observability_deps::tracing::warn;
+use parking_lot::Mutex;
 use pin_project::pin_project;
 use time::{Time, TimeProvider};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio_util::sync::ReusableBoxFuture;
 use trace::TraceCollector;
 use uuid::Uuid;
 
-use crate::core::{WriteBufferError, WriteBufferReading, WriteBufferWriting};
+use crate::core::{UndecodableMessage, WriteBufferError, WriteBufferReading, WriteBufferWriting};
 
 /// Header used to declare the creation time of the message.
 pub const HEADER_TIME: &str = "last-modified";
 
+/// Minimum time between two prune runs triggered by writes.
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum number of bytes read from the start of a message file to find its headers, see [`read_message_time`].
+const MAX_HEADER_BYTES: u64 = 64 * 1024;
+
+/// Retention of the committed messages of a sequencer, see the module docs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct RetentionConfig {
+    /// Extracted from the `retention_ms` option.
+    time: Option<Duration>,
+
+    /// Extracted from the `retention_bytes` option.
+    bytes: Option<u64>,
+}
+
+impl RetentionConfig {
+    /// Extract the retention from the `creation_config` options, falling back to the `connection_config` for options
+    /// that are not set there.
+    fn try_new(
+        connection_config: &BTreeMap<String, String>,
+        creation_config: Option<&WriteBufferCreationConfig>,
+    ) -> Result<Self, WriteBufferError> {
+        let parse = |key: &str| {
+            creation_config
+                .and_then(|cfg| cfg.options.get(key))
+                .or_else(|| connection_config.get(key))
+                .map(|s| {
+                    s.parse::<u64>().map_err(|e| {
+                        WriteBufferError::invalid_input(format!(
+                            "Cannot parse `{key}` from '{s}': {e}"
+                        ))
+                    })
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            time: parse("retention_ms")?.map(Duration::from_millis),
+            bytes: parse("retention_bytes")?,
+        })
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.time.is_some() || self.bytes.is_some()
+    }
+}
+
 /// File-based write buffer writer.
 #[derive(Debug)]
 pub struct FileBufferProducer {
     db_name: String,
-    dirs: BTreeMap<u32, PathBuf>,
+    dirs: Arc<BTreeMap<u32, PathBuf>>,
     time_provider: Arc<dyn TimeProvider>,
+    retention: RetentionConfig,
+    last_pruned: Mutex<Option<Time>>,
 }
 
 impl FileBufferProducer {
@@ -151,17 +226,51 @@ impl FileBufferProducer {
     pub async fn new(
         root: &Path,
         database_name: &str,
+        connection_config: &BTreeMap<String, String>,
         creation_config: Option<&WriteBufferCreationConfig>,
         time_provider: Arc<dyn TimeProvider>,
     ) -> Result<Self, WriteBufferError> {
+        let retention = RetentionConfig::try_new(connection_config, creation_config)?;
         let root = root.join(database_name);
         let dirs = maybe_auto_create_directories(&root, creation_config).await?;
         Ok(Self {
             db_name: database_name.to_string(),
-            dirs,
+            dirs: Arc::new(dirs),
             time_provider,
+            retention,
+            last_pruned: Mutex::new(None),
         })
     }
+
+    /// Delete the committed messages of all sequencers that are outside the configured retention.
+    ///
+    /// This is a no-op if no retention is configured.
+    pub async fn prune(&self) -> Result<(), WriteBufferError> {
+        if !self.retention.is_enabled() {
+            return Ok(());
+        }
+
+        prune_sequencers(&self.dirs, &self.retention, self.time_provider.now()).await
+    }
+
+    /// Returns true if retention is configured and pruning did not run within the last [`PRUNE_INTERVAL`].
+    fn should_prune(&self) -> bool {
+        if !self.retention.is_enabled() {
+            return false;
+        }
+
+        let now = self.time_provider.now();
+        let mut last_pruned = self.last_pruned.lock();
+        match *last_pruned {
+            Some(last) if now.checked_duration_since(last).unwrap_or_default() < PRUNE_INTERVAL => {
+                false
+            }
+            _ => {
+                *last_pruned = Some(now);
+                true
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -243,6 +352,19 @@ impl WriteBufferWriting for FileBufferProducer {
         // unlink scratchpad file (and ignore error)
         tokio::fs::remove_file(&temp_file).await.ok();
 
+        // the message is committed, so pruning must neither delay nor fail the write
+        if self.should_prune() {
+            let dirs = Arc::clone(&self.dirs);
+            let retention = self.retention;
+            let now = self.time_provider.now();
+            let db_name = self.db_name.clone();
+            tokio::spawn(async move {
+                if let Err(e) = prune_sequencers(&dirs, &retention, now).await {
+                    warn!(%e, db_name = db_name.as_str(), "Error pruning file write buffer");
+                }
+            });
+        }
+
         Ok(DmlMeta::sequenced(
             Sequence::new(sequencer_id, sequence_number),
             now,
@@ -422,30 +544,39 @@ impl ConsumerStream {
                         id: sequencer_id,
                         number: sequence_number,
                     };
-                    match Self::decode_file(data, sequence, trace_collector.clone()) {
-                        Ok(write) => {
-                            match next_sequence_number.compare_exchange(
-                                sequence_number,
-                                sequence_number + 1,
-                                Ordering::SeqCst,
-                                Ordering::SeqCst,
-                            ) {
-                                Ok(_) => {
-                                    // can send to output
-                                    Ok(write)
-                                }
-                                Err(_) => {
-                                    // interleaving change, retry
-                                    continue;
-                                }
-                            }
+                    let msg = Self::decode_file(&data, sequence, trace_collector.clone())
+                        .map_err(|e| e.with_undecodable(Self::undecodable(data, sequence)));
+
+                    // advance even if the file is invalid so we don't get stuck on it
+                    match next_sequence_number.compare_exchange(
+                        sequence_number,
+                        sequence_number + 1,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    ) {
+                        Ok(_) => {
+                            // can send to output
+                            msg
+                        }
+                        Err(_) => {
+                            // interleaving change, retry
+                            continue;
                         }
-                        Err(e) => Err(e),
                     }
                 }
                 Err(error) => {
                     match error.kind() {
                         std::io::ErrorKind::NotFound => {
+                            // messages below the low watermark were deleted by retention
+                            if let Ok(low_watermark) = low_watermark(&path).await {
+                                if sequence_number < low_watermark {
+                                    terminated.store(true, Ordering::SeqCst);
+                                    return Some(Err(WriteBufferError::unknown_sequence_number(
+                                        format!("sequence number {sequence_number} was pruned, oldest retained sequence number is {low_watermark}"),
+                                    )));
+                                }
+                            }
+
                             // figure out watermark and see if there's a gap in the stream
                             if let Ok(watermark) = watermark(&path).await {
                                 // watermark is "last sequence number + 1", so substract 1 before comparing
@@ -492,13 +623,13 @@ impl ConsumerStream {
     }
 
     fn decode_file(
-        mut data: Vec<u8>,
+        data: &[u8],
         sequence: Sequence,
         trace_collector: Option<Arc<dyn TraceCollector>>,
     ) -> Result<DmlOperation, WriteBufferError> {
         let mut headers = [httparse::EMPTY_HEADER; 16];
         let status =
-            httparse::parse_headers(&data, &mut headers).map_err(WriteBufferError::invalid_data)?;
+            httparse::parse_headers(data, &mut headers).map_err(WriteBufferError::invalid_data)?;
 
         match status {
             httparse::Status::Complete((offset, headers)) => {
@@ -508,37 +639,44 @@ impl ConsumerStream {
                 )?;
 
                 // parse timestamp
-                let mut timestamp = None;
-                for header in headers {
-                    if header.name.eq_ignore_ascii_case(HEADER_TIME) {
-                        if let Ok(value) = String::from_utf8(header.value.to_vec()) {
-                            if let Ok(time) = Time::from_rfc3339(&value) {
-                                timestamp = Some(time);
-                            }
-                        }
-                    }
-                }
-                let timestamp = if let Some(timestamp) = timestamp {
+                let timestamp = if let Some(timestamp) = parse_time(headers) {
                     timestamp
                 } else {
                     return Err("Timestamp missing".to_string().into());
                 };
 
                 // parse entry
-                let full_data_length = data.len();
-                let entry_data = data.split_off(offset);
-
                 crate::codec::decode(
-                    &entry_data,
+                    &data[offset..],
                     iox_headers,
                     sequence,
                     timestamp,
-                    full_data_length,
+                    data.len(),
                 )
             }
             httparse::Status::Partial => Err("Too many headers".to_string().into()),
         }
     }
+
+    /// Split the contents of a file that could not be decoded into headers and payload.
+    fn undecodable(data: Vec<u8>, sequence: Sequence) -> UndecodableMessage {
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        match httparse::parse_headers(&data, &mut headers) {
+            Ok(httparse::Status::Complete((offset, headers))) => UndecodableMessage {
+                sequence,
+                headers: headers
+                    .iter()
+                    .map(|header| (header.name.to_string(), header.value.to_vec()))
+                    .collect(),
+                payload: data[offset..].to_vec(),
+            },
+            _ => UndecodableMessage {
+                sequence,
+                headers: vec![],
+                payload: data,
+            },
+        }
+    }
 }
 
 impl Stream for ConsumerStream {
@@ -674,6 +812,152 @@ async fn watermark(path: &Path) -> Result<u64, WriteBufferError> {
     Ok(watermark)
 }
 
+/// Get the sequence number below which all messages were pruned, given the `committed` directory of a sequencer.
+async fn low_watermark(committed: &Path) -> Result<u64, WriteBufferError> {
+    let pruned = committed.with_file_name("pruned");
+    if tokio::fs::metadata(&pruned).await.is_err() {
+        // never pruned
+        return Ok(0);
+    }
+
+    let markers = scan_dir::<u64>(&pruned, FileType::File).await?;
+    Ok(markers.keys().max().copied().unwrap_or(0))
+}
+
+/// Parse the [`HEADER_TIME`] header.
+fn parse_time(headers: &[httparse::Header<'_>]) -> Option<Time> {
+    headers
+        .iter()
+        .filter(|header| header.name.eq_ignore_ascii_case(HEADER_TIME))
+        .find_map(|header| {
+            std::str::from_utf8(header.value)
+                .ok()
+                .and_then(|value| Time::from_rfc3339(value).ok())
+        })
+}
+
+/// Read the [`HEADER_TIME`] of a message file. Returns `None` if the file is gone or has no valid time header.
+///
+/// Only the header section (up to [`MAX_HEADER_BYTES`]) is read, not the payload.
+async fn read_message_time(path: &Path) -> Result<Option<Time>, WriteBufferError> {
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    // the headers end with an empty line
+    let mut reader = BufReader::new(file.take(MAX_HEADER_BYTES));
+    let mut data = Vec::new();
+    loop {
+        let n = reader.read_until(b'\n', &mut data).await?;
+        if n == 0 || n == 1 {
+            break;
+        }
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; 16];
+    match httparse::parse_headers(&data, &mut headers) {
+        Ok(httparse::Status::Complete((_, headers))) => Ok(parse_time(headers)),
+        _ => Ok(None),
+    }
+}
+
+/// Delete the committed messages of all sequencers in `dirs` that are outside of `retention`.
+async fn prune_sequencers(
+    dirs: &BTreeMap<u32, PathBuf>,
+    retention: &RetentionConfig,
+    now: Time,
+) -> Result<(), WriteBufferError> {
+    for sequencer_path in dirs.values() {
+        prune_sequencer(sequencer_path, retention, now).await?;
+    }
+    Ok(())
+}
+
+/// Delete the committed messages of the sequencer at `sequencer_path` that are outside of `retention`.
+async fn prune_sequencer(
+    sequencer_path: &Path,
+    retention: &RetentionConfig,
+    now: Time,
+) -> Result<(), WriteBufferError> {
+    let files = scan_dir::<u64>(&sequencer_path.join("committed"), FileType::File).await?;
+
+    // the newest message is never pruned since it determines the next sequence number
+    let newest = match files.keys().next_back() {
+        Some(newest) => *newest,
+        None => return Ok(()),
+    };
+
+    // all sequence numbers below this will be pruned
+    let mut prune_before = 0;
+
+    if let Some(max_bytes) = retention.bytes {
+        let mut total_bytes = 0;
+        for (sequence_number, path) in files.iter().rev() {
+            total_bytes += match tokio::fs::metadata(path).await {
+                Ok(metadata) => metadata.len(),
+                // pruned concurrently
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            if total_bytes > max_bytes && *sequence_number != newest {
+                prune_before = sequence_number + 1;
+                break;
+            }
+        }
+    }
+
+    if let Some(cutoff) = retention.time.and_then(|time| now.checked_sub(time)) {
+        for (sequence_number, path) in files.range(prune_before..newest) {
+            match read_message_time(path).await? {
+                Some(time) if time < cutoff => prune_before = sequence_number + 1,
+                Some(_) => break,
+                // messages without a (valid) time are pruned with newer expired ones
+                None => continue,
+            }
+        }
+    }
+
+    if prune_before == 0 {
+        return Ok(());
+    }
+
+    // record the low watermark BEFORE deleting anything so readers can distinguish pruned messages from gaps
+    let pruned = sequencer_path.join("pruned");
+    tokio::fs::create_dir_all(&pruned).await?;
+    let markers = scan_dir::<u64>(&pruned, FileType::File).await?;
+    if markers.keys().max().copied().unwrap_or(0) < prune_before {
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(pruned.join(prune_before.to_string()))
+            .await
+        {
+            Ok(_) => {}
+            // created concurrently by another producer
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let outdated_markers = markers.range(..prune_before).map(|(_, path)| path);
+    for path in files
+        .range(..prune_before)
+        .map(|(_, path)| path)
+        .chain(outdated_markers)
+    {
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
 pub mod test_utils {
     use std::path::Path;
 
@@ -705,7 +989,10 @@ mod tests {
     use tempfile::TempDir;
     use trace::RingBufferTraceCollector;
 
-    use crate::core::test_utils::{perform_generic_tests, write, TestAdapter, TestContext};
+    use crate::core::{
+        test_utils::{perform_generic_tests, write, TestAdapter, TestContext},
+        WriteBufferErrorKind,
+    };
 
     use super::test_utils::remove_entry;
     use super::*;
@@ -756,6 +1043,26 @@ mod tests {
                 ..Default::default()
             })
         }
+
+        fn retention_config(&self, options: &[(&str, &str)]) -> WriteBufferCreationConfig {
+            WriteBufferCreationConfig {
+                n_sequencers: self.n_sequencers,
+                options: options
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            }
+        }
+
+        async fn committed(&self, sequencer_id: u32) -> BTreeMap<u64, PathBuf> {
+            let path = self
+                .path
+                .join(&self.database_name)
+                .join("active")
+                .join(sequencer_id.to_string())
+                .join("committed");
+            scan_dir(&path, FileType::File).await.unwrap()
+        }
     }
 
     #[async_trait]
@@ -767,6 +1074,7 @@ mod tests {
             FileBufferProducer::new(
                 &self.path,
                 &self.database_name,
+                &Default::default(),
                 self.creation_config(creation_config).as_ref(),
                 Arc::clone(&self.time_provider),
             )
@@ -860,4 +1168,242 @@ mod tests {
 
         assert_write_op_eq(&stream.next().await.unwrap().unwrap(), &w2);
     }
+
+    #[tokio::test]
+    async fn test_skips_undecodable_file() {
+        let adapter = FileTestAdapter::new();
+        let ctx = adapter.new_context(NonZeroU32::new(1).unwrap()).await;
+
+        let writer = ctx.writing(true).await.unwrap();
+        let sequencer_id = writer.sequencer_ids().into_iter().next().unwrap();
+
+        let w1 = write(
+            &ctx.database_name,
+            &writer,
+            "upc user=1 100",
+            sequencer_id,
+            None,
+        )
+        .await;
+        let broken_sequence_number = w1.meta().sequence().unwrap().number + 1;
+        tokio::fs::write(
+            ctx.path
+                .join(&ctx.database_name)
+                .join("active")
+                .join(sequencer_id.to_string())
+                .join("committed")
+                .join(broken_sequence_number.to_string()),
+            "iox-namespace: ns\r\n\r\nfoo",
+        )
+        .await
+        .unwrap();
+        let w2 = write(
+            &ctx.database_name,
+            &writer,
+            "upc user=2 200",
+            sequencer_id,
+            None,
+        )
+        .await;
+
+        let reader = ctx.reading(true).await.unwrap();
+        let mut handler = reader.stream_handler(sequencer_id).await.unwrap();
+        let mut stream = handler.stream().await;
+
+        assert_write_op_eq(&stream.next().await.unwrap().unwrap(), &w1);
+
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            err.undecodable().unwrap(),
+            &UndecodableMessage {
+                sequence: Sequence {
+                    id: sequencer_id,
+                    number: broken_sequence_number,
+                },
+                headers: vec![("iox-namespace".to_string(), b"ns".to_vec())],
+                payload: b"foo".to_vec(),
+            }
+        );
+
+        assert_write_op_eq(&stream.next().await.unwrap().unwrap(), &w2);
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_size() {
+        let adapter = FileTestAdapter::new();
+        let ctx = adapter.new_context(NonZeroU32::new(1).unwrap()).await;
+
+        let writer = FileBufferProducer::new(
+            &ctx.path,
+            &ctx.database_name,
+            &Default::default(),
+            Some(&ctx.retention_config(&[("retention_bytes", "1")])),
+            Arc::clone(&ctx.time_provider),
+        )
+        .await
+        .unwrap();
+        let sequencer_id = writer.sequencer_ids().into_iter().next().unwrap();
+
+        write(
+            &ctx.database_name,
+            &writer,
+            "upc user=1 100",
+            sequencer_id,
+            None,
+        )
+        .await;
+        write(
+            &ctx.database_name,
+            &writer,
+            "upc user=2 200",
+            sequencer_id,
+            None,
+        )
+        .await;
+        let w3 = write(
+            &ctx.database_name,
+            &writer,
+            "upc user=3 300",
+            sequencer_id,
+            None,
+        )
+        .await;
+        let newest = w3.meta().sequence().unwrap().number;
+
+        writer.prune().await.unwrap();
+
+        // the newest message is always retained
+        let committed = ctx.committed(sequencer_id).await;
+        assert_eq!(committed.into_keys().collect::<Vec<_>>(), vec![newest]);
+
+        let reader = ctx.reading(true).await.unwrap();
+        let mut handler = reader.stream_handler(sequencer_id).await.unwrap();
+
+        let mut stream = handler.stream().await;
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), WriteBufferErrorKind::UnknownSequenceNumber);
+        drop(stream);
+
+        handler.seek(newest).await.unwrap();
+        let mut stream = handler.stream().await;
+        assert_write_op_eq(&stream.next().await.unwrap().unwrap(), &w3);
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_time_on_write() {
+        let time_provider = Arc::new(time::MockProvider::new(Time::from_timestamp_millis(0)));
+        let adapter = FileTestAdapter::new();
+        let ctx = adapter
+            .new_context_with_time(NonZeroU32::new(1).unwrap(), Arc::clone(&time_provider) as _)
+            .await;
+
+        // retention can also be configured for existing sequencers, without a creation config
+        let writer = FileBufferProducer::new(
+            &ctx.path,
+            &ctx.database_name,
+            &BTreeMap::from([(String::from("retention_ms"), String::from("60000"))]),
+            ctx.creation_config(true).as_ref(),
+            Arc::clone(&ctx.time_provider),
+        )
+        .await
+        .unwrap();
+        let sequencer_id = writer.sequencer_ids().into_iter().next().unwrap();
+
+        write(
+            &ctx.database_name,
+            &writer,
+            "upc user=1 100",
+            sequencer_id,
+            None,
+        )
+        .await;
+        time_provider.inc(Duration::from_secs(30));
+        let w2 = write(
+            &ctx.database_name,
+            &writer,
+            "upc user=2 200",
+            sequencer_id,
+            None,
+        )
+        .await;
+
+        // within the retention period, nothing is pruned
+        assert_eq!(ctx.committed(sequencer_id).await.len(), 2);
+
+        // the first message is now outside the retention period, the write prunes it
+        time_provider.inc(Duration::from_secs(31));
+        let w3 = write(
+            &ctx.database_name,
+            &writer,
+            "upc user=3 300",
+            sequencer_id,
+            None,
+        )
+        .await;
+
+        // pruning runs in the background
+        let expected = vec![
+            w2.meta().sequence().unwrap().number,
+            w3.meta().sequence().unwrap().number,
+        ];
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while ctx
+                .committed(sequencer_id)
+                .await
+                .into_keys()
+                .collect::<Vec<_>>()
+                != expected
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("outdated message was not pruned");
+    }
+
+    #[tokio::test]
+    async fn test_retention_config() {
+        let adapter = FileTestAdapter::new();
+        let ctx = adapter.new_context(NonZeroU32::new(1).unwrap()).await;
+
+        let cfg = RetentionConfig::try_new(
+            &Default::default(),
+            Some(&ctx.retention_config(&[("retention_ms", "1000"), ("retention_bytes", "42")])),
+        )
+        .unwrap();
+        assert_eq!(
+            cfg,
+            RetentionConfig {
+                time: Some(Duration::from_secs(1)),
+                bytes: Some(42),
+            }
+        );
+
+        let cfg = RetentionConfig::try_new(&Default::default(), None).unwrap();
+        assert!(!cfg.is_enabled());
+
+        // the creation config takes precedence over the connection config
+        let cfg = RetentionConfig::try_new(
+            &BTreeMap::from([
+                (String::from("retention_ms"), String::from("1000")),
+                (String::from("retention_bytes"), String::from("42")),
+            ]),
+            Some(&ctx.retention_config(&[("retention_bytes", "7")])),
+        )
+        .unwrap();
+        assert_eq!(
+            cfg,
+            RetentionConfig {
+                time: Some(Duration::from_secs(1)),
+                bytes: Some(7),
+            }
+        );
+
+        let err = RetentionConfig::try_new(
+            &Default::default(),
+            Some(&ctx.retention_config(&[("retention_ms", "1h")])),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), WriteBufferErrorKind::InvalidInput);
+    }
 }
@@ -33,6 +33,9 @@ struct WriteAggregator {
     /// Namespace.
     namespace: String,
 
+    /// Partition key shared by all aggregated writes, if any.
+    partition_key: Option<String>,
+
     /// Data for every table.
     tables: HashMap<String, MutableBatch>,
 
@@ -53,6 +56,7 @@ impl WriteAggregator {
 
         Self {
             namespace: write.namespace().to_owned(),
+            partition_key: write.partition_key().map(ToOwned::to_owned),
             tables: write.into_tables().collect(),
             span_recorder,
             tag,
@@ -89,10 +93,15 @@ impl WriteAggregator {
         }
     }
 
-    /// Check if we can push the given write to this aggregator (mostly if the schemas match).
+    /// Check if we can push the given write to this aggregator (mostly if the schemas and
+    /// partition keys match).
     fn can_push(&self, write: &DmlWrite) -> bool {
         assert_eq!(write.namespace(), self.namespace);
 
+        if write.partition_key() != self.partition_key.as_deref() {
+            return false;
+        }
+
         for (table, batch) in write.tables() {
             if let Some(existing) = self.tables.get(table) {
                 match (
@@ -153,7 +162,11 @@ impl WriteAggregator {
         };
 
         let meta = DmlMeta::unsequenced(ctx);
-        DmlWrite::new(self.namespace.clone(), self.tables.clone(), meta)
+        let write = DmlWrite::new(self.namespace.clone(), self.tables.clone(), meta);
+        match &self.partition_key {
+            Some(partition_key) => write.with_partition_key(partition_key.clone()),
+            None => write,
+        }
     }
 }
 
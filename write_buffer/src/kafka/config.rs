@@ -1,6 +1,7 @@
-use std::{collections::BTreeMap, fmt::Display, str::FromStr, time::Duration};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf, str::FromStr, time::Duration};
 
 use data_types::write_buffer::WriteBufferCreationConfig;
+use rskafka::client::partition::Compression;
 
 use crate::core::WriteBufferError;
 
@@ -11,22 +12,89 @@ pub struct ClientConfig {
     ///
     /// extracted from `max_message_size`. Defaults to `None` (rskafka default).
     pub max_message_size: Option<usize>,
+
+    /// TLS config, if the broker connections use TLS.
+    ///
+    /// See [`TlsConfig`] for the keys it is extracted from. Defaults to `None` (plaintext).
+    pub tls: Option<TlsConfig>,
 }
 
 impl TryFrom<&BTreeMap<String, String>> for ClientConfig {
     type Error = WriteBufferError;
 
     fn try_from(cfg: &BTreeMap<String, String>) -> Result<Self, Self::Error> {
+        // The pinned rskafka version does not implement the SASL handshake - reject SASL config
+        // rather than silently connecting unauthenticated.
+        if let Some(k) = cfg.keys().find(|k| k.starts_with("sasl_")) {
+            return Err(WriteBufferError::invalid_input(format!(
+                "`{k}` is set but SASL authentication is not supported by the Kafka client"
+            )));
+        }
+
         Ok(Self {
             // TODO: Revert this back to after we have proper prod config management.
             //       See https://github.com/influxdata/influxdb_iox/issues/3723
             //
             //       max_message_size: parse_key(cfg, "max_message_size")?,
             max_message_size: Some(parse_key(cfg, "max_message_size")?.unwrap_or(10485760)),
+            tls: TlsConfig::try_from_config(cfg)?,
         })
     }
 }
 
+/// TLS config for broker connections.
+///
+/// TLS is enabled by setting `tls` to `true`, or by setting any of the file keys below.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TlsConfig {
+    /// PEM file containing the CA certificates used to verify the brokers.
+    ///
+    /// Extracted from `tls_ca_file`. Defaults to `None` (the Mozilla root certificates).
+    pub ca_file: Option<PathBuf>,
+
+    /// PEM files containing the client certificate chain and private key, used to authenticate
+    /// to the brokers.
+    ///
+    /// Extracted from `tls_cert_file` and `tls_key_file`, which must be set together. Defaults
+    /// to `None` (no client authentication).
+    pub client_auth: Option<(PathBuf, PathBuf)>,
+}
+
+impl TlsConfig {
+    fn try_from_config(cfg: &BTreeMap<String, String>) -> Result<Option<Self>, WriteBufferError> {
+        let enabled: Option<bool> = parse_key(cfg, "tls")?;
+        let ca_file: Option<PathBuf> = parse_key(cfg, "tls_ca_file")?;
+        let cert_file: Option<PathBuf> = parse_key(cfg, "tls_cert_file")?;
+        let key_file: Option<PathBuf> = parse_key(cfg, "tls_key_file")?;
+
+        let client_auth = match (cert_file, key_file) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (None, None) => None,
+            _ => {
+                return Err(WriteBufferError::invalid_input(
+                    "`tls_cert_file` and `tls_key_file` must be set together",
+                ))
+            }
+        };
+
+        let implied = ca_file.is_some() || client_auth.is_some();
+        match enabled {
+            Some(false) if implied => Err(WriteBufferError::invalid_input(
+                "TLS files are configured but `tls` is set to false",
+            )),
+            Some(true) => Ok(Some(Self {
+                ca_file,
+                client_auth,
+            })),
+            _ if implied => Ok(Some(Self {
+                ca_file,
+                client_auth,
+            })),
+            _ => Ok(None),
+        }
+    }
+}
+
 /// Config for topic creation.
 #[derive(Debug, PartialEq, Eq)]
 pub struct TopicCreationConfig {
@@ -104,6 +172,11 @@ pub struct ProducerConfig {
     ///
     /// Extracted from `producer_max_batch_size`. Defaults to `512 * 1024`.
     pub max_batch_size: usize,
+
+    /// Compression applied to the produced record batches.
+    ///
+    /// Extracted from `compression`. Defaults to [`ProducerCompression::None`].
+    pub compression: ProducerCompression,
 }
 
 impl TryFrom<&BTreeMap<String, String>> for ProducerConfig {
@@ -119,10 +192,60 @@ impl TryFrom<&BTreeMap<String, String>> for ProducerConfig {
             //
             //       max_batch_size: parse_key(cfg, "producer_max_batch_size")?.unwrap_or(512 * 1024),
             max_batch_size: parse_key(cfg, "producer_max_batch_size")?.unwrap_or(2621440),
+            compression: parse_key(cfg, "compression")?.unwrap_or_default(),
         })
     }
 }
 
+/// Compression codec of the record batches written by a producer.
+///
+/// Compression is a property of each record batch, so consumers handle topics
+/// containing a mix of compressed and uncompressed records regardless of this
+/// setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProducerCompression {
+    None,
+    Gzip,
+    Lz4,
+    Snappy,
+    Zstd,
+}
+
+impl Default for ProducerCompression {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl FromStr for ProducerCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "gzip" => Ok(Self::Gzip),
+            "lz4" => Ok(Self::Lz4),
+            "snappy" => Ok(Self::Snappy),
+            "zstd" => Ok(Self::Zstd),
+            _ => Err(
+                "unknown compression, expected one of none, gzip, lz4, snappy, zstd".to_string(),
+            ),
+        }
+    }
+}
+
+impl From<ProducerCompression> for Compression {
+    fn from(c: ProducerCompression) -> Self {
+        match c {
+            ProducerCompression::None => Self::NoCompression,
+            ProducerCompression::Gzip => Self::Gzip,
+            ProducerCompression::Lz4 => Self::Lz4,
+            ProducerCompression::Snappy => Self::Snappy,
+            ProducerCompression::Zstd => Self::Zstd,
+        }
+    }
+}
+
 fn parse_key<T>(cfg: &BTreeMap<String, String>, key: &str) -> Result<Option<T>, WriteBufferError>
 where
     T: FromStr,
@@ -149,6 +272,7 @@ mod tests {
         let actual = ClientConfig::try_from(&BTreeMap::default()).unwrap();
         let expected = ClientConfig {
             max_message_size: Some(10485760),
+            tls: None,
         };
         assert_eq!(actual, expected);
     }
@@ -162,6 +286,7 @@ mod tests {
         .unwrap();
         let expected = ClientConfig {
             max_message_size: Some(1024),
+            tls: None,
         };
         assert_eq!(actual, expected);
     }
@@ -179,6 +304,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_client_config_tls() {
+        let actual = ClientConfig::try_from(&BTreeMap::from([(
+            String::from("tls"),
+            String::from("true"),
+        )]))
+        .unwrap();
+        assert_eq!(
+            actual.tls,
+            Some(TlsConfig {
+                ca_file: None,
+                client_auth: None,
+            })
+        );
+
+        let actual = ClientConfig::try_from(&BTreeMap::from([
+            (String::from("tls_ca_file"), String::from("/ca.pem")),
+            (String::from("tls_cert_file"), String::from("/cert.pem")),
+            (String::from("tls_key_file"), String::from("/key.pem")),
+        ]))
+        .unwrap();
+        assert_eq!(
+            actual.tls,
+            Some(TlsConfig {
+                ca_file: Some(PathBuf::from("/ca.pem")),
+                client_auth: Some((PathBuf::from("/cert.pem"), PathBuf::from("/key.pem"))),
+            })
+        );
+
+        let err = ClientConfig::try_from(&BTreeMap::from([(
+            String::from("tls_cert_file"),
+            String::from("/cert.pem"),
+        )]))
+        .unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "`tls_cert_file` and `tls_key_file` must be set together"
+        );
+
+        let err = ClientConfig::try_from(&BTreeMap::from([
+            (String::from("tls"), String::from("false")),
+            (String::from("tls_ca_file"), String::from("/ca.pem")),
+        ]))
+        .unwrap_err();
+        assert_contains!(err.to_string(), "`tls` is set to false");
+    }
+
+    #[test]
+    fn test_client_config_sasl_unsupported() {
+        let err = ClientConfig::try_from(&BTreeMap::from([
+            (String::from("sasl_mechanism"), String::from("PLAIN")),
+            (String::from("sasl_username"), String::from("user")),
+        ]))
+        .unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "is set but SASL authentication is not supported by the Kafka client"
+        );
+    }
+
     #[test]
     fn test_topic_creation_config_default() {
         let actual = TopicCreationConfig::try_from(&WriteBufferCreationConfig {
@@ -303,6 +488,7 @@ mod tests {
         let expected = ProducerConfig {
             linger: None,
             max_batch_size: 2621440,
+            compression: ProducerCompression::None,
         };
         assert_eq!(actual, expected);
     }
@@ -315,12 +501,14 @@ mod tests {
                 String::from("producer_max_batch_size"),
                 String::from("1337"),
             ),
+            (String::from("compression"), String::from("zstd")),
             (String::from("foo"), String::from("bar")),
         ]))
         .unwrap();
         let expected = ProducerConfig {
             linger: Some(Duration::from_millis(42)),
             max_batch_size: 1337,
+            compression: ProducerCompression::Zstd,
         };
         assert_eq!(actual, expected);
     }
@@ -346,5 +534,15 @@ mod tests {
             err.to_string(),
             "Cannot parse `producer_max_batch_size` from 'xyz': invalid digit found in string"
         );
+
+        let err = ProducerConfig::try_from(&BTreeMap::from([(
+            String::from("compression"),
+            String::from("brotli"),
+        )]))
+        .unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "Cannot parse `compression` from 'brotli': unknown compression"
+        );
     }
 }
@@ -11,12 +11,15 @@ use data_types::{sequence::Sequence, write_buffer::WriteBufferCreationConfig};
 use dml::{DmlMeta, DmlOperation};
 use futures::{stream::BoxStream, StreamExt};
 use parking_lot::Mutex;
-use rskafka::client::{
-    consumer::{StartOffset, StreamConsumerBuilder},
-    error::{Error as RSKafkaError, ProtocolError},
-    partition::{OffsetAt, PartitionClient},
-    producer::{BatchProducer, BatchProducerBuilder},
-    ClientBuilder,
+use rskafka::{
+    client::{
+        consumer::{StartOffset, StreamConsumerBuilder},
+        error::{Error as RSKafkaError, ProtocolError},
+        partition::{OffsetAt, PartitionClient},
+        producer::{BatchProducer, BatchProducerBuilder},
+        ClientBuilder,
+    },
+    record::RecordAndOffset,
 };
 use time::{Time, TimeProvider};
 use trace::TraceCollector;
@@ -24,8 +27,8 @@ use trace::TraceCollector;
 use crate::{
     codec::IoxHeaders,
     core::{
-        WriteBufferError, WriteBufferErrorKind, WriteBufferReading, WriteBufferStreamHandler,
-        WriteBufferWriting,
+        UndecodableMessage, WriteBufferError, WriteBufferErrorKind, WriteBufferReading,
+        WriteBufferStreamHandler, WriteBufferWriting,
     },
 };
 
@@ -36,6 +39,7 @@ use self::{
 
 mod aggregator;
 mod config;
+mod tls;
 
 type Result<T, E = WriteBufferError> = std::result::Result<T, E>;
 
@@ -70,6 +74,8 @@ impl RSKafkaProducer {
                 if let Some(linger) = producer_config.linger {
                     producer_builder = producer_builder.with_linger(linger);
                 }
+                producer_builder =
+                    producer_builder.with_compression(producer_config.compression.into());
                 let producer = producer_builder.build(DmlAggregator::new(
                     trace_collector.clone(),
                     database_name.clone(),
@@ -181,37 +187,16 @@ impl WriteBufferStreamHandler for RSKafkaStreamHandler {
             // store new offset already so we don't get stuck on invalid records
             *next_offset.lock() = Some(record.offset + 1);
 
-            let kafka_read_size = record.record.approximate_size();
-
-            let headers =
-                IoxHeaders::from_headers(record.record.headers, trace_collector.as_ref())?;
-
-            let sequence = Sequence {
-                id: self.sequencer_id,
-                number: record
-                    .offset
-                    .try_into()
-                    .map_err(WriteBufferError::invalid_data)?,
-            };
-
-            let timestamp_millis =
-                i64::try_from(record.record.timestamp.unix_timestamp_nanos() / 1_000_000)
-                    .map_err(WriteBufferError::invalid_data)?;
-
-            let timestamp = Time::from_timestamp_millis_opt(timestamp_millis)
-                .ok_or_else::<WriteBufferError, _>(|| {
-                    format!(
-                        "Cannot parse timestamp for milliseconds: {}",
-                        timestamp_millis
-                    )
-                    .into()
-                })?;
-
-            let value = record
-                .record
-                .value
-                .ok_or_else::<WriteBufferError, _>(|| "Value missing".to_string().into())?;
-            crate::codec::decode(&value, headers, sequence, timestamp, kafka_read_size)
+            decode_record(&record, self.sequencer_id, trace_collector.as_ref()).map_err(|e| {
+                e.with_undecodable(UndecodableMessage {
+                    sequence: Sequence {
+                        id: self.sequencer_id,
+                        number: record.offset.try_into().unwrap_or_default(),
+                    },
+                    headers: record.record.headers.into_iter().collect(),
+                    payload: record.record.value.unwrap_or_default(),
+                })
+            })
         });
         stream.boxed()
     }
@@ -304,6 +289,45 @@ impl WriteBufferReading for RSKafkaConsumer {
     }
 }
 
+/// Decode a record read from Kafka into a [`DmlOperation`].
+fn decode_record(
+    record: &RecordAndOffset,
+    sequencer_id: u32,
+    trace_collector: Option<&Arc<dyn TraceCollector>>,
+) -> Result<DmlOperation> {
+    let kafka_read_size = record.record.approximate_size();
+
+    let headers = IoxHeaders::from_headers(&record.record.headers, trace_collector)?;
+
+    let sequence = Sequence {
+        id: sequencer_id,
+        number: record
+            .offset
+            .try_into()
+            .map_err(WriteBufferError::invalid_data)?,
+    };
+
+    let timestamp_millis =
+        i64::try_from(record.record.timestamp.unix_timestamp_nanos() / 1_000_000)
+            .map_err(WriteBufferError::invalid_data)?;
+
+    let timestamp = Time::from_timestamp_millis_opt(timestamp_millis)
+        .ok_or_else::<WriteBufferError, _>(|| {
+            format!(
+                "Cannot parse timestamp for milliseconds: {}",
+                timestamp_millis
+            )
+            .into()
+        })?;
+
+    let value = record
+        .record
+        .value
+        .as_ref()
+        .ok_or_else::<WriteBufferError, _>(|| "Value missing".to_string().into())?;
+    crate::codec::decode(value, headers, sequence, timestamp, kafka_read_size)
+}
+
 async fn setup_topic(
     conn: String,
     database_name: String,
@@ -315,6 +339,9 @@ async fn setup_topic(
     if let Some(max_message_size) = client_config.max_message_size {
         client_builder = client_builder.max_message_size(max_message_size);
     }
+    if let Some(tls) = &client_config.tls {
+        client_builder = client_builder.tls_config(tls::client_config(tls)?);
+    }
     let client = client_builder.build().await?;
     let controller_client = client.controller_client().await?;
 
@@ -542,6 +569,12 @@ mod tests {
         let err = stream.next().await.unwrap().unwrap_err();
         assert_contains!(err.to_string(), "No content type header");
 
+        // the raw message is attached to the error
+        let message = err.undecodable().unwrap();
+        assert_eq!(message.sequence.id, sequencer_id);
+        assert!(message.headers.is_empty());
+        assert!(message.payload.is_empty());
+
         // re-creating the stream should advance past the broken message
         drop(stream);
         let mut stream = handler.stream().await;
@@ -670,6 +703,68 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_mixed_compression() {
+        let conn = maybe_skip_kafka_integration!();
+        let adapter = RSKafkaTestAdapter::new(conn.clone());
+        let ctx = adapter.new_context(NonZeroU32::new(1).unwrap()).await;
+
+        let uncompressed = ctx.writing(true).await.unwrap();
+        let sequencer_id = set_pop_first(&mut uncompressed.sequencer_ids()).unwrap();
+
+        // interleave records of producers using every compression codec
+        let mut writes = vec![
+            crate::core::test_utils::write(
+                "namespace",
+                &uncompressed,
+                "table foo=1 1",
+                sequencer_id,
+                None,
+            )
+            .await,
+        ];
+        for (i, compression) in ["gzip", "lz4", "snappy", "zstd"].into_iter().enumerate() {
+            let producer = RSKafkaProducer::new(
+                conn.clone(),
+                ctx.database_name.clone(),
+                &BTreeMap::from([(String::from("compression"), String::from(compression))]),
+                None,
+                Arc::clone(&ctx.time_provider),
+                None,
+            )
+            .await
+            .unwrap();
+            writes.push(
+                crate::core::test_utils::write(
+                    "namespace",
+                    &producer,
+                    &format!("table foo={} {}", i + 2, i + 2),
+                    sequencer_id,
+                    None,
+                )
+                .await,
+            );
+        }
+        writes.push(
+            crate::core::test_utils::write(
+                "namespace",
+                &uncompressed,
+                "table foo=6 6",
+                sequencer_id,
+                None,
+            )
+            .await,
+        );
+
+        let consumer = ctx.reading(true).await.unwrap();
+        let mut handler = consumer.stream_handler(sequencer_id).await.unwrap();
+        let mut stream = handler.stream().await;
+        for w in &writes {
+            let op = stream.next().await.unwrap().unwrap();
+            assert_write_op_eq(&op, w);
+        }
+    }
+
     async fn write(
         namespace: &str,
         producer: &RSKafkaProducer,
@@ -0,0 +1,321 @@
+use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore};
+use rustls_pemfile::Item;
+
+use crate::core::WriteBufferError;
+
+use super::config::TlsConfig;
+
+/// Build the [`rustls`] client config used for broker connections from a [`TlsConfig`].
+pub fn client_config(config: &TlsConfig) -> Result<Arc<ClientConfig>, WriteBufferError> {
+    let mut roots = RootCertStore::empty();
+    match &config.ca_file {
+        Some(path) => {
+            for cert in read_certs(path)? {
+                roots.add(&cert).map_err(|e| {
+                    WriteBufferError::invalid_input(format!(
+                        "Invalid CA certificate in '{}': {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+            }
+        }
+        None => {
+            roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    ta.subject,
+                    ta.spki,
+                    ta.name_constraints,
+                )
+            }));
+        }
+    }
+
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+
+    let config = match &config.client_auth {
+        Some((cert_file, key_file)) => builder
+            .with_single_cert(read_certs(cert_file)?, read_key(key_file)?)
+            .map_err(|e| {
+                WriteBufferError::invalid_input(format!("Invalid client certificate or key: {}", e))
+            })?,
+        None => builder.with_no_client_auth(),
+    };
+
+    Ok(Arc::new(config))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, WriteBufferError> {
+    File::open(path).map(BufReader::new).map_err(|e| {
+        WriteBufferError::invalid_input(format!("Cannot open '{}': {}", path.display(), e))
+    })
+}
+
+/// Read all PEM-encoded certificates in `path`.
+fn read_certs(path: &Path) -> Result<Vec<Certificate>, WriteBufferError> {
+    let certs = rustls_pemfile::certs(&mut open(path)?).map_err(|e| {
+        WriteBufferError::invalid_input(format!(
+            "Cannot read certificates from '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    if certs.is_empty() {
+        return Err(WriteBufferError::invalid_input(format!(
+            "No certificates found in '{}'",
+            path.display()
+        )));
+    }
+
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+/// Read the first PEM-encoded private key (PKCS#8, RSA or SEC1) in `path`.
+fn read_key(path: &Path) -> Result<PrivateKey, WriteBufferError> {
+    let mut reader = open(path)?;
+    loop {
+        let item = rustls_pemfile::read_one(&mut reader).map_err(|e| {
+            WriteBufferError::invalid_input(format!(
+                "Cannot read private key from '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        match item {
+            Some(Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key)) => {
+                return Ok(PrivateKey(key))
+            }
+            Some(_) => continue,
+            None => {
+                return Err(WriteBufferError::invalid_input(format!(
+                    "No private key found in '{}'",
+                    path.display()
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, io::Write, path::PathBuf, time::Duration};
+
+    use data_types::write_buffer::WriteBufferCreationConfig;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa};
+    use rustls::{server::AllowAnyAuthenticatedClient, ServerConfig};
+    use tempfile::TempDir;
+    use test_helpers::assert_contains;
+    use time::SystemProvider;
+    use tokio::{net::TcpListener, sync::mpsc};
+    use tokio_rustls::TlsAcceptor;
+
+    use crate::kafka::{RSKafkaConsumer, RSKafkaProducer};
+
+    use super::*;
+
+    /// A CA and a server & client certificate signed by it, written to PEM files.
+    struct Pki {
+        dir: TempDir,
+        ca: Certificate,
+        server_chain: Vec<Certificate>,
+        server_key: PrivateKey,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let dir = TempDir::new().unwrap();
+
+            let mut ca_params = CertificateParams::new(vec![]);
+            ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = rcgen::Certificate::from_params(ca_params).unwrap();
+
+            let server = rcgen::Certificate::from_params(CertificateParams::new(vec![
+                "localhost".to_string()
+            ]))
+            .unwrap();
+            let client =
+                rcgen::Certificate::from_params(CertificateParams::new(vec!["client".to_string()]))
+                    .unwrap();
+
+            let write = |name: &str, data: String| {
+                let mut f = File::create(dir.path().join(name)).unwrap();
+                f.write_all(data.as_bytes()).unwrap();
+            };
+            write("ca.pem", ca.serialize_pem().unwrap());
+            write("client.pem", client.serialize_pem_with_signer(&ca).unwrap());
+            write("client.key", client.serialize_private_key_pem());
+
+            Self {
+                ca: Certificate(ca.serialize_der().unwrap()),
+                server_chain: vec![Certificate(server.serialize_der_with_signer(&ca).unwrap())],
+                server_key: PrivateKey(server.serialize_private_key_der()),
+                dir,
+            }
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.dir.path().join(name)
+        }
+    }
+
+    /// Start a TLS-enabled stand-in for a broker that requires a client certificate signed by
+    /// the CA of `pki`.
+    ///
+    /// For every accepted connection, the number of certificates the client presented (or the
+    /// handshake error) is sent to the returned channel. The stand-in does not speak the Kafka
+    /// protocol, so clients connecting to it never finish building.
+    async fn broker(pki: &Pki) -> (String, mpsc::UnboundedReceiver<Result<usize, String>>) {
+        let mut client_roots = RootCertStore::empty();
+        client_roots.add(&pki.ca).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(client_roots))
+            .with_single_cert(pki.server_chain.clone(), pki.server_key.clone())
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let handshake = acceptor.accept(stream).await.map(|stream| {
+                        stream
+                            .get_ref()
+                            .1
+                            .peer_certificates()
+                            .map(|certs| certs.len())
+                            .unwrap_or_default()
+                    });
+                    tx.send(handshake.map_err(|e| e.to_string())).ok();
+                });
+            }
+        });
+
+        (format!("localhost:{}", addr.port()), rx)
+    }
+
+    /// Wait for the next handshake seen by the stand-in broker.
+    async fn next_handshake(
+        rx: &mut mpsc::UnboundedReceiver<Result<usize, String>>,
+    ) -> Result<usize, String> {
+        tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("TLS handshake timed out")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mutual_tls_handshake() {
+        let pki = Pki::new();
+        let connection_config = BTreeMap::from([
+            (
+                "tls_ca_file".to_string(),
+                pki.path("ca.pem").display().to_string(),
+            ),
+            (
+                "tls_cert_file".to_string(),
+                pki.path("client.pem").display().to_string(),
+            ),
+            (
+                "tls_key_file".to_string(),
+                pki.path("client.key").display().to_string(),
+            ),
+        ]);
+        let creation_config = WriteBufferCreationConfig::default();
+
+        // Both the producer and the consumer connect via TLS (creating the topic if needed),
+        // presenting the configured client certificate. Every client gets its own broker, so
+        // that reconnection attempts of one client are not mistaken for another one's.
+        let (conn, mut handshakes) = broker(&pki).await;
+        let producer = {
+            let connection_config = connection_config.clone();
+            let creation_config = creation_config.clone();
+            tokio::spawn(async move {
+                RSKafkaProducer::new(
+                    conn,
+                    "topic".to_string(),
+                    &connection_config,
+                    Some(&creation_config),
+                    Arc::new(SystemProvider::new()),
+                    None,
+                )
+                .await
+                .map(|_| ())
+            })
+        };
+        assert_eq!(next_handshake(&mut handshakes).await, Ok(1));
+        producer.abort();
+
+        let (conn, mut handshakes) = broker(&pki).await;
+        let consumer = tokio::spawn(async move {
+            RSKafkaConsumer::new(
+                conn,
+                "topic".to_string(),
+                &connection_config,
+                Some(&creation_config),
+                None,
+            )
+            .await
+            .map(|_| ())
+        });
+        assert_eq!(next_handshake(&mut handshakes).await, Ok(1));
+        consumer.abort();
+
+        // Without a client certificate the broker rejects the connection.
+        let connection_config = BTreeMap::from([(
+            "tls_ca_file".to_string(),
+            pki.path("ca.pem").display().to_string(),
+        )]);
+        let (conn, mut handshakes) = broker(&pki).await;
+        let consumer = tokio::spawn(async move {
+            RSKafkaConsumer::new(conn, "topic".to_string(), &connection_config, None, None)
+                .await
+                .map(|_| ())
+        });
+        assert!(next_handshake(&mut handshakes).await.is_err());
+        consumer.abort();
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let pki = Pki::new();
+
+        let err = client_config(&TlsConfig {
+            ca_file: Some(pki.path("missing.pem")),
+            client_auth: None,
+        })
+        .unwrap_err();
+        assert_contains!(err.to_string(), "Cannot open");
+
+        let err = client_config(&TlsConfig {
+            ca_file: Some(pki.path("client.key")),
+            client_auth: None,
+        })
+        .unwrap_err();
+        assert_contains!(err.to_string(), "No certificates found");
+
+        let err = client_config(&TlsConfig {
+            ca_file: None,
+            client_auth: Some((pki.path("client.pem"), pki.path("ca.pem"))),
+        })
+        .unwrap_err();
+        assert_contains!(err.to_string(), "No private key found");
+
+        client_config(&TlsConfig {
+            ca_file: None,
+            client_auth: None,
+        })
+        .unwrap();
+    }
+}
@@ -14,3 +14,7 @@ pub mod core;
 pub mod file;
 pub mod kafka;
 pub mod mock;
+
+// The write buffer encoding of operations, for components that store operations outside of the
+// write buffer (e.g. the ingester write-ahead log and dead letters).
+pub use codec::{decode, encode_operation, ContentType, IoxHeaders};
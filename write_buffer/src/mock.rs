@@ -433,7 +433,11 @@ impl WriteBufferStreamHandler for MockBufferStreamHandler {
                     }
                     Err(e) => {
                         // found an error => return entry to caller
-                        return Poll::Ready(Some(Err(e.to_string().into())));
+                        let mut err: WriteBufferError = e.to_string().into();
+                        if let Some(message) = e.undecodable() {
+                            err = err.with_undecodable(message.clone());
+                        }
+                        return Poll::Ready(Some(Err(err)));
                     }
                 }
             }